@@ -0,0 +1,216 @@
+use crate::{codec, data_source::DataSource, Chain};
+use graph::blockchain as bc;
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, Default)]
+pub struct TriggerFilter {
+    pub(crate) block_filter: BitcoinBlockFilter,
+    pub(crate) input_filter: BitcoinScriptFilter,
+    pub(crate) output_filter: BitcoinScriptFilter,
+}
+
+impl bc::TriggerFilter<Chain> for TriggerFilter {
+    fn extend<'a>(&mut self, data_sources: impl Iterator<Item = &'a DataSource> + Clone) {
+        let TriggerFilter {
+            block_filter,
+            input_filter,
+            output_filter,
+        } = self;
+
+        block_filter.extend(BitcoinBlockFilter::from_data_sources(data_sources.clone()));
+        input_filter.extend(BitcoinScriptFilter::from_data_sources(
+            data_sources.clone(),
+            |ds| !ds.mapping.input_handlers.is_empty(),
+        ));
+        output_filter.extend(BitcoinScriptFilter::from_data_sources(data_sources, |ds| {
+            !ds.mapping.output_handlers.is_empty()
+        }));
+    }
+
+    fn node_capabilities(&self) -> bc::EmptyNodeCapabilities<Chain> {
+        bc::EmptyNodeCapabilities::default()
+    }
+
+    fn extend_with_template(
+        &mut self,
+        _data_source: impl Iterator<Item = <Chain as bc::Blockchain>::DataSourceTemplate>,
+    ) {
+    }
+
+    fn to_firehose_filter(self) -> Vec<prost_types::Any> {
+        vec![]
+    }
+}
+
+/// Matches output scripts that pay to one of the addresses or that start
+/// with one of the script prefixes of the data sources
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BitcoinScriptFilter {
+    addresses: HashSet<String>,
+    script_prefixes: HashSet<Vec<u8>>,
+}
+
+impl BitcoinScriptFilter {
+    pub fn matches(&self, script: &codec::ScriptPubKey) -> bool {
+        if self.addresses.iter().any(|address| script.pays_to(address)) {
+            return true;
+        }
+
+        if self.script_prefixes.is_empty() {
+            return false;
+        }
+        let bytes = script.bytes();
+        self.script_prefixes
+            .iter()
+            .any(|prefix| bytes.starts_with(prefix))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty() && self.script_prefixes.is_empty()
+    }
+
+    /// Collect the addresses and script prefixes of all data sources for
+    /// which `has_handlers` is true
+    pub fn from_data_sources<'a>(
+        iter: impl IntoIterator<Item = &'a DataSource>,
+        has_handlers: impl Fn(&DataSource) -> bool,
+    ) -> Self {
+        let mut filter = Self::default();
+        for data_source in iter.into_iter().filter(|ds| has_handlers(ds)) {
+            if let Some(address) = &data_source.source.address {
+                filter.addresses.insert(address.clone());
+            }
+            if let Some(prefix) = data_source.script_prefix() {
+                filter.script_prefixes.insert(prefix);
+            }
+        }
+        filter
+    }
+
+    pub fn extend(&mut self, other: BitcoinScriptFilter) {
+        self.addresses.extend(other.addresses);
+        self.script_prefixes.extend(other.script_prefixes);
+    }
+}
+
+/// BitcoinBlockFilter will match every block regardless of source being set.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BitcoinBlockFilter {
+    pub trigger_every_block: bool,
+}
+
+impl BitcoinBlockFilter {
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            trigger_every_block: iter
+                .into_iter()
+                .any(|data_source| !data_source.mapping.block_handlers.is_empty()),
+        }
+    }
+
+    pub fn extend(&mut self, other: BitcoinBlockFilter) {
+        self.trigger_every_block = self.trigger_every_block || other.trigger_every_block;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use graph::blockchain::TriggerFilter as _;
+    use graph::{prelude::Link, semver::Version};
+
+    use crate::codec::ScriptPubKey;
+    use crate::data_source::{DataSource, Mapping, MappingHandler, Source};
+
+    use super::TriggerFilter;
+
+    const ADDRESS: &str = "bc1q8wx0tu7nkhq7f503lmymd20x6nztxs53jevkz4";
+    const OTHER_ADDRESS: &str = "1BoatSLRHtKNngkdXEeobR76b53LETtpyT";
+
+    fn script(hex: &str, address: &str) -> ScriptPubKey {
+        ScriptPubKey {
+            hex: hex.to_string(),
+            address: address.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn script_filters_match_addresses_and_prefixes() {
+        let dss = vec![
+            new_datasource(Some(ADDRESS), None, false, true),
+            new_datasource(None, Some("6a"), true, false),
+        ];
+
+        let mut filter = TriggerFilter::default();
+        filter.extend(dss.iter());
+
+        assert!(!filter.block_filter.trigger_every_block);
+
+        assert!(filter.output_filter.matches(&script("00143b8c", ADDRESS)));
+        assert!(!filter.output_filter.matches(&script("6a0568656c6c6f", "")));
+        assert!(!filter
+            .output_filter
+            .matches(&script("76a914", OTHER_ADDRESS)));
+
+        assert!(filter.input_filter.matches(&script("6a0568656c6c6f", "")));
+        assert!(!filter.input_filter.matches(&script("00143b8c", ADDRESS)));
+    }
+
+    #[test]
+    fn script_filters_support_legacy_addresses() {
+        let dss = vec![new_datasource(Some(OTHER_ADDRESS), None, true, true)];
+
+        let mut filter = TriggerFilter::default();
+        filter.extend(dss.iter());
+
+        let legacy = ScriptPubKey {
+            hex: "76a914".to_string(),
+            addresses: vec![OTHER_ADDRESS.to_string()],
+            ..Default::default()
+        };
+        assert!(filter.input_filter.matches(&legacy));
+        assert!(filter.output_filter.matches(&legacy));
+    }
+
+    fn new_datasource(
+        address: Option<&str>,
+        script: Option<&str>,
+        input_handler: bool,
+        output_handler: bool,
+    ) -> DataSource {
+        let handler = |enabled: bool, name: &str| {
+            enabled
+                .then(|| MappingHandler {
+                    handler: name.into(),
+                })
+                .into_iter()
+                .collect()
+        };
+
+        DataSource {
+            kind: "bitcoin".into(),
+            network: None,
+            name: "".into(),
+            source: Source {
+                address: address.map(str::to_string),
+                script: script.map(str::to_string),
+                start_block: 10,
+                end_block: None,
+            },
+            mapping: Mapping {
+                api_version: Version::new(0, 0, 7),
+                language: "".into(),
+                entities: vec![],
+                block_handlers: vec![],
+                input_handlers: handler(input_handler, "handleInput"),
+                output_handlers: handler(output_handler, "handleOutput"),
+                runtime: Arc::new(vec![]),
+                link: Link { link: "".into() },
+            },
+            context: Arc::new(None),
+            creation_block: None,
+        }
+    }
+}
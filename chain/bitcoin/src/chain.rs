@@ -0,0 +1,527 @@
+use graph::anyhow;
+use graph::blockchain::client::ChainClient;
+use graph::blockchain::firehose_block_ingestor::FirehoseBlockIngestor;
+use graph::blockchain::{
+    Block, BlockIngestor, BlockchainKind, ChainHeadUpdateListener, EmptyNodeCapabilities,
+    NoopDecoderHook, NoopRuntimeAdapter, TriggerFilterWrapper,
+};
+use graph::cheap_clone::CheapClone;
+use graph::components::network_provider::ChainName;
+use graph::components::store::{DeploymentCursorTracker, SourceableStore};
+use graph::data::subgraph::UnifiedMappingApiVersion;
+use graph::firehose::FirehoseEndpoint;
+use graph::futures03::future::try_join_all;
+use graph::prelude::MetricsRegistry;
+use graph::substreams::Clock;
+use graph::{
+    blockchain::{
+        block_stream::{
+            BlockStreamEvent, BlockWithTriggers, FirehoseError,
+            FirehoseMapper as FirehoseMapperTrait, TriggersAdapter as TriggersAdapterTrait,
+        },
+        firehose_block_stream::FirehoseBlockStream,
+        polling_block_stream::PollingBlockStream,
+        BlockHash, BlockPtr, Blockchain, IngestorError, RuntimeAdapter as RuntimeAdapterTrait,
+    },
+    components::store::DeploymentLocator,
+    firehose::{self as firehose, ForkStep},
+    prelude::{async_trait, o, BlockNumber, ChainStore, Error, Logger, LoggerFactory, NodeId},
+};
+use prost::Message;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::adapter::TriggerFilter;
+use crate::data_source::{DataSourceTemplate, UnresolvedDataSourceTemplate};
+use crate::ingestor::PollingBlockIngestor;
+use crate::rpc::BitcoinRpcAdapters;
+use crate::trigger::{self, BitcoinTrigger};
+use crate::{
+    codec,
+    data_source::{DataSource, UnresolvedDataSource},
+};
+use graph::blockchain::block_stream::{
+    BlockStream, BlockStreamError, BlockStreamMapper, FirehoseCursor, TriggersAdapterWrapper,
+};
+
+/// The maximum number of blocks the polling block stream scans at once.
+/// Full blocks are fetched for every block in the range, so this is much
+/// lower than for chains that can filter on the node
+const MAX_BLOCK_RANGE_SIZE: BlockNumber = 10;
+
+/// The number of triggers the polling block stream aims for in each range
+const TARGET_TRIGGERS_PER_BLOCK_RANGE: u64 = 100;
+
+pub struct Chain {
+    logger_factory: LoggerFactory,
+    name: ChainName,
+    node_id: NodeId,
+    client: Arc<ChainClient<Self>>,
+    chain_store: Arc<dyn ChainStore>,
+    metrics_registry: Arc<MetricsRegistry>,
+    chain_head_update_listener: Arc<dyn ChainHeadUpdateListener>,
+    reorg_threshold: BlockNumber,
+    polling_interval: Duration,
+}
+
+impl std::fmt::Debug for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "chain: bitcoin")
+    }
+}
+
+impl Chain {
+    pub fn new(
+        logger_factory: LoggerFactory,
+        name: ChainName,
+        node_id: NodeId,
+        metrics_registry: Arc<MetricsRegistry>,
+        chain_store: Arc<dyn ChainStore>,
+        client: Arc<ChainClient<Self>>,
+        chain_head_update_listener: Arc<dyn ChainHeadUpdateListener>,
+        reorg_threshold: BlockNumber,
+        polling_interval: Duration,
+    ) -> Self {
+        Chain {
+            logger_factory,
+            name,
+            node_id,
+            client,
+            chain_store,
+            metrics_registry,
+            chain_head_update_listener,
+            reorg_threshold,
+            polling_interval,
+        }
+    }
+
+    fn rpc_adapters(&self) -> BitcoinRpcAdapters {
+        match self.client.as_ref() {
+            ChainClient::Rpc(adapters) => adapters.clone(),
+            ChainClient::Firehose(_) => BitcoinRpcAdapters::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl Blockchain for Chain {
+    const KIND: BlockchainKind = BlockchainKind::Bitcoin;
+
+    type Client = BitcoinRpcAdapters;
+    type Block = codec::Block;
+
+    type DataSource = DataSource;
+
+    type UnresolvedDataSource = UnresolvedDataSource;
+
+    type DataSourceTemplate = DataSourceTemplate;
+
+    type UnresolvedDataSourceTemplate = UnresolvedDataSourceTemplate;
+
+    type TriggerData = crate::trigger::BitcoinTrigger;
+
+    type MappingTrigger = crate::trigger::BitcoinTrigger;
+
+    type TriggerFilter = crate::adapter::TriggerFilter;
+
+    type NodeCapabilities = EmptyNodeCapabilities<Self>;
+
+    type DecoderHook = NoopDecoderHook;
+
+    fn triggers_adapter(
+        &self,
+        _loc: &DeploymentLocator,
+        _capabilities: &Self::NodeCapabilities,
+        _unified_api_version: UnifiedMappingApiVersion,
+    ) -> Result<Arc<dyn TriggersAdapterTrait<Self>>, Error> {
+        let adapter = TriggersAdapter {
+            adapters: self.rpc_adapters(),
+            chain_store: self.chain_store.cheap_clone(),
+        };
+        Ok(Arc::new(adapter))
+    }
+
+    fn is_refetch_block_required(&self) -> bool {
+        false
+    }
+
+    async fn refetch_firehose_block(
+        &self,
+        _logger: &Logger,
+        _cursor: FirehoseCursor,
+    ) -> Result<codec::Block, Error> {
+        unimplemented!("This chain does not support Dynamic Data Sources. is_refetch_block_required always returns false, this shouldn't be called.")
+    }
+
+    async fn new_block_stream(
+        &self,
+        deployment: DeploymentLocator,
+        store: impl DeploymentCursorTracker,
+        start_blocks: Vec<BlockNumber>,
+        source_subgraph_stores: Vec<Arc<dyn SourceableStore>>,
+        filter: Arc<TriggerFilterWrapper<Self>>,
+        unified_api_version: UnifiedMappingApiVersion,
+    ) -> Result<Box<dyn BlockStream<Self>>, Error> {
+        let adapter = self
+            .triggers_adapter(
+                &deployment,
+                &EmptyNodeCapabilities::default(),
+                unified_api_version.clone(),
+            )
+            .unwrap_or_else(|_| panic!("no adapter for network {}", self.name));
+
+        if self.client.is_firehose() {
+            let logger = self
+                .logger_factory
+                .subgraph_logger(&deployment)
+                .new(o!("component" => "FirehoseBlockStream"));
+
+            let firehose_mapper = Arc::new(FirehoseMapper {
+                adapter,
+                filter: filter.chain_filter.clone(),
+            });
+
+            return Ok(Box::new(FirehoseBlockStream::new(
+                deployment.hash,
+                self.chain_client(),
+                store.block_ptr(),
+                store.firehose_cursor(),
+                firehose_mapper,
+                start_blocks,
+                logger,
+                self.metrics_registry.clone(),
+            )));
+        }
+
+        let logger = self
+            .logger_factory
+            .subgraph_logger(&deployment)
+            .new(o!("component" => "BlockStream"));
+        let chain_head_update_stream = self
+            .chain_head_update_listener
+            .subscribe(self.name.to_string(), logger.clone());
+
+        Ok(Box::new(PollingBlockStream::new(
+            self.chain_store.cheap_clone(),
+            chain_head_update_stream,
+            Arc::new(TriggersAdapterWrapper::new(adapter, source_subgraph_stores)),
+            self.node_id.clone(),
+            deployment.hash,
+            filter,
+            start_blocks,
+            self.reorg_threshold,
+            logger,
+            MAX_BLOCK_RANGE_SIZE,
+            TARGET_TRIGGERS_PER_BLOCK_RANGE,
+            unified_api_version,
+            store.block_ptr(),
+        )))
+    }
+
+    fn chain_store(&self) -> Arc<dyn ChainStore> {
+        self.chain_store.clone()
+    }
+
+    async fn block_pointer_from_number(
+        &self,
+        logger: &Logger,
+        number: BlockNumber,
+    ) -> Result<BlockPtr, IngestorError> {
+        match self.client.as_ref() {
+            ChainClient::Firehose(_) => self
+                .client
+                .firehose_endpoint()
+                .await?
+                .block_ptr_for_number::<codec::Block>(logger, number)
+                .await
+                .map_err(Into::into),
+            ChainClient::Rpc(adapters) => {
+                let hash = adapters.adapter()?.block_hash(number).await?;
+                Ok(BlockPtr {
+                    hash: codec::decode_hash(&hash)?,
+                    number,
+                })
+            }
+        }
+    }
+
+    fn runtime(&self) -> anyhow::Result<(Arc<dyn RuntimeAdapterTrait<Self>>, Self::DecoderHook)> {
+        Ok((Arc::new(NoopRuntimeAdapter::default()), NoopDecoderHook))
+    }
+
+    fn chain_client(&self) -> Arc<ChainClient<Self>> {
+        self.client.clone()
+    }
+
+    async fn block_ingestor(&self) -> anyhow::Result<Box<dyn BlockIngestor>> {
+        if self.client.is_firehose() {
+            let ingestor = FirehoseBlockIngestor::<crate::Block, Self>::new(
+                self.chain_store.cheap_clone(),
+                self.chain_client(),
+                self.logger_factory
+                    .component_logger("BitcoinFirehoseBlockIngestor", None),
+                self.name.clone(),
+            );
+            return Ok(Box::new(ingestor));
+        }
+
+        let ingestor = PollingBlockIngestor::new(
+            self.logger_factory
+                .component_logger("BitcoinPollingBlockIngestor", None),
+            self.reorg_threshold,
+            self.rpc_adapters(),
+            self.chain_store.cheap_clone(),
+            self.polling_interval,
+            self.name.clone(),
+        );
+        Ok(Box::new(ingestor))
+    }
+}
+
+/// Blocks are fetched from the JSON-RPC API of the node when the chain uses
+/// the polling block stream. With Firehose, only `triggers_in_block` is
+/// used and `adapters` is empty
+pub struct TriggersAdapter {
+    adapters: BitcoinRpcAdapters,
+    chain_store: Arc<dyn ChainStore>,
+}
+
+#[async_trait]
+impl TriggersAdapterTrait<Chain> for TriggersAdapter {
+    async fn scan_triggers(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+        filter: &TriggerFilter,
+    ) -> Result<(Vec<BlockWithTriggers<Chain>>, BlockNumber), Error> {
+        let adapter = self.adapters.adapter()?;
+        let blocks =
+            try_join_all((from..=to).map(|number| adapter.block_by_number(number))).await?;
+
+        // The logger is only used to report duplicate triggers
+        let logger = Logger::root(graph::slog::Discard, o!());
+        let blocks = blocks
+            .into_iter()
+            .map(|block| {
+                let shared_block = Arc::new(block.clone());
+                let triggers = trigger::triggers_in_block(&shared_block, filter);
+                BlockWithTriggers::new(block, triggers, &logger)
+            })
+            // The last block is always returned so the block stream can
+            // advance past it
+            .filter(|block| !block.trigger_data.is_empty() || block.block.number() == to)
+            .collect();
+
+        Ok((blocks, to))
+    }
+
+    async fn chain_head_ptr(&self) -> Result<Option<BlockPtr>, Error> {
+        self.chain_store.cheap_clone().chain_head_ptr().await
+    }
+
+    async fn triggers_in_block(
+        &self,
+        logger: &Logger,
+        block: codec::Block,
+        filter: &TriggerFilter,
+    ) -> Result<BlockWithTriggers<Chain>, Error> {
+        // TODO: Find the best place to introduce an `Arc` and avoid this clone.
+        let shared_block = Arc::new(block.clone());
+        let trigger_data: Vec<BitcoinTrigger> = trigger::triggers_in_block(&shared_block, filter);
+
+        Ok(BlockWithTriggers::new(block, trigger_data, logger))
+    }
+
+    async fn is_on_main_chain(&self, ptr: BlockPtr) -> Result<bool, Error> {
+        let hash = self.adapters.adapter()?.block_hash(ptr.number).await?;
+        Ok(codec::decode_hash(&hash)? == ptr.hash)
+    }
+
+    async fn ancestor_block(
+        &self,
+        ptr: BlockPtr,
+        offset: BlockNumber,
+        root: Option<BlockHash>,
+    ) -> Result<Option<codec::Block>, Error> {
+        // The chain store only keeps the timestamps of blocks, so we find
+        // the ancestor there and fetch the full block from the node
+        let ancestor = self
+            .chain_store
+            .cheap_clone()
+            .ancestor_block(ptr, offset, root)
+            .await?;
+
+        match ancestor {
+            Some((_, ptr)) => {
+                let block = self
+                    .adapters
+                    .adapter()?
+                    .block_by_hash(&ptr.hash_hex())
+                    .await?;
+                Ok(Some(block))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn parent_ptr(&self, block: &BlockPtr) -> Result<Option<BlockPtr>, Error> {
+        let block = self
+            .adapters
+            .adapter()?
+            .block_by_hash(&block.hash_hex())
+            .await?;
+        Ok(block.parent_ptr())
+    }
+
+    async fn load_block_ptrs_by_numbers(
+        &self,
+        _logger: Logger,
+        block_numbers: BTreeSet<BlockNumber>,
+    ) -> Result<Vec<codec::Block>, Error> {
+        let adapter = self.adapters.adapter()?;
+        try_join_all(
+            block_numbers
+                .into_iter()
+                .map(|number| adapter.block_by_number(number)),
+        )
+        .await
+    }
+}
+
+pub struct FirehoseMapper {
+    adapter: Arc<dyn TriggersAdapterTrait<Chain>>,
+    filter: Arc<TriggerFilter>,
+}
+
+#[async_trait]
+impl BlockStreamMapper<Chain> for FirehoseMapper {
+    fn decode_block(
+        &self,
+        output: Option<&[u8]>,
+    ) -> Result<Option<codec::Block>, BlockStreamError> {
+        let block = match output {
+            Some(block) => codec::Block::decode(block)?,
+            None => {
+                return Err(anyhow::anyhow!(
+                    "Bitcoin mapper is expected to always have a block"
+                ))?
+            }
+        };
+
+        Ok(Some(block))
+    }
+
+    async fn block_with_triggers(
+        &self,
+        logger: &Logger,
+        block: codec::Block,
+    ) -> Result<BlockWithTriggers<Chain>, BlockStreamError> {
+        self.adapter
+            .triggers_in_block(logger, block, self.filter.as_ref())
+            .await
+            .map_err(BlockStreamError::from)
+    }
+    async fn handle_substreams_block(
+        &self,
+        _logger: &Logger,
+        _clock: Clock,
+        _cursor: FirehoseCursor,
+        _block: Vec<u8>,
+    ) -> Result<BlockStreamEvent<Chain>, BlockStreamError> {
+        unimplemented!()
+    }
+}
+
+#[async_trait]
+impl FirehoseMapperTrait<Chain> for FirehoseMapper {
+    fn trigger_filter(&self) -> &TriggerFilter {
+        self.filter.as_ref()
+    }
+
+    async fn to_block_stream_event(
+        &self,
+        logger: &Logger,
+        response: &firehose::Response,
+    ) -> Result<BlockStreamEvent<Chain>, FirehoseError> {
+        let step = ForkStep::try_from(response.step).unwrap_or_else(|_| {
+            panic!(
+                "unknown step i32 value {}, maybe you forgot update & re-regenerate the protobuf definitions?",
+                response.step
+            )
+        });
+
+        let any_block = response
+            .block
+            .as_ref()
+            .expect("block payload information should always be present");
+
+        // Right now, this is done in all cases but in reality, with how the BlockStreamEvent::Revert
+        // is defined right now, only block hash and block number is necessary. However, this information
+        // is not part of the actual bstream::BlockResponseV2 payload. As such, we need to decode the full
+        // block which is useless.
+        //
+        // Check about adding basic information about the block in the bstream::BlockResponseV2 or maybe
+        // define a slimmed down stuct that would decode only a few fields and ignore all the rest.
+        // unwrap: Input cannot be None so output will be error or block.
+        let block = self
+            .decode_block(Some(&any_block.value.as_ref()))
+            .map_err(Error::from)?
+            .unwrap();
+
+        use ForkStep::*;
+        match step {
+            StepNew => Ok(BlockStreamEvent::ProcessBlock(
+                self.block_with_triggers(&logger, block)
+                    .await
+                    .map_err(Error::from)?,
+                FirehoseCursor::from(response.cursor.clone()),
+            )),
+
+            StepUndo => {
+                let parent_ptr = block
+                    .parent_ptr()
+                    .expect("Genesis block should never be reverted");
+
+                Ok(BlockStreamEvent::Revert(
+                    parent_ptr,
+                    FirehoseCursor::from(response.cursor.clone()),
+                ))
+            }
+
+            StepFinal => {
+                panic!("irreversible step is not handled and should not be requested in the Firehose request")
+            }
+
+            StepUnset => {
+                panic!("unknown step should not happen in the Firehose response")
+            }
+        }
+    }
+
+    async fn block_ptr_for_number(
+        &self,
+        logger: &Logger,
+        endpoint: &Arc<FirehoseEndpoint>,
+        number: BlockNumber,
+    ) -> Result<BlockPtr, Error> {
+        endpoint
+            .block_ptr_for_number::<codec::Block>(logger, number)
+            .await
+    }
+
+    async fn final_block_ptr_for(
+        &self,
+        logger: &Logger,
+        endpoint: &Arc<FirehoseEndpoint>,
+        block: &codec::Block,
+    ) -> Result<BlockPtr, Error> {
+        // Bitcoin has probabilistic finality, we consider blocks final
+        // once they are buried under the reorg threshold
+        let final_block_number = block
+            .number()
+            .saturating_sub(graph::env::ENV_VARS.reorg_threshold());
+        self.block_ptr_for_number(logger, endpoint, final_block_number)
+            .await
+    }
+}
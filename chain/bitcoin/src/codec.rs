@@ -0,0 +1,217 @@
+#[rustfmt::skip]
+#[path = "protobuf/sf.bitcoin.r#type.v1.rs"]
+pub(crate) mod pbcodec;
+
+use std::ops::Deref;
+
+use graph::{
+    blockchain::Block as BlockchainBlock,
+    blockchain::{BlockHash, BlockPtr, BlockTime, ChainStoreBlock, ChainStoreData},
+    prelude::{anyhow, hex, serde_json, BlockNumber, Error},
+};
+use prost::bytes::{Buf, BufMut};
+use prost::encoding::{DecodeContext, WireType};
+use prost::{DecodeError, Message as _};
+
+pub use pbcodec::*;
+
+const SATOSHIS_PER_BTC: f64 = 100_000_000.0;
+
+/// Hashes are hex encoded in the block, in the byte order in which
+/// bitcoind displays them
+pub(crate) fn decode_hash(hash: &str) -> Result<BlockHash, Error> {
+    hex::decode(hash)
+        .map(BlockHash::from)
+        .map_err(|e| anyhow::anyhow!("invalid block hash `{}`: {}", hash, e))
+}
+
+/// A Bitcoin block together with its decoded hashes. The hashes are
+/// decoded when the block is decoded or converted from the node's
+/// response, so that a block with an invalid hash is rejected no matter
+/// where it comes from, and `ptr` and `parent_ptr` can not fail
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Block {
+    block: pbcodec::Block,
+    hash: BlockHash,
+    parent_hash: BlockHash,
+}
+
+impl Deref for Block {
+    type Target = pbcodec::Block;
+
+    fn deref(&self) -> &Self::Target {
+        &self.block
+    }
+}
+
+impl TryFrom<pbcodec::Block> for Block {
+    type Error = Error;
+
+    fn try_from(block: pbcodec::Block) -> Result<Self, Self::Error> {
+        let hash = decode_hash(&block.hash)?;
+        let parent_hash = decode_hash(&block.previous_hash)?;
+        Ok(Block {
+            block,
+            hash,
+            parent_hash,
+        })
+    }
+}
+
+/// Tags of the hash fields in `pbcodec::Block`
+const HASH_TAG: u32 = 1;
+const PREVIOUS_HASH_TAG: u32 = 18;
+
+impl prost::Message for Block {
+    fn encode_raw(&self, buf: &mut impl BufMut) {
+        self.block.encode_raw(buf)
+    }
+
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        self.block.merge_field(tag, wire_type, buf, ctx)?;
+
+        let decode = |hash: &str| decode_hash(hash).map_err(|e| DecodeError::new(e.to_string()));
+        match tag {
+            HASH_TAG => self.hash = decode(&self.block.hash)?,
+            PREVIOUS_HASH_TAG => self.parent_hash = decode(&self.block.previous_hash)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.block.encoded_len()
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl Block {
+    pub fn hash(&self) -> &BlockHash {
+        &self.hash
+    }
+
+    pub fn parent_hash(&self) -> &BlockHash {
+        &self.parent_hash
+    }
+}
+
+impl BlockchainBlock for Block {
+    fn number(&self) -> i32 {
+        BlockNumber::try_from(self.height).unwrap()
+    }
+
+    fn ptr(&self) -> BlockPtr {
+        BlockPtr {
+            hash: self.hash.clone(),
+            number: self.number(),
+        }
+    }
+
+    fn parent_ptr(&self) -> Option<BlockPtr> {
+        if self.height == 0 {
+            return None;
+        }
+
+        Some(BlockPtr {
+            hash: self.parent_hash.clone(),
+            number: self.number().saturating_sub(1),
+        })
+    }
+
+    fn data(&self) -> Result<serde_json::Value, serde_json::Error> {
+        // Only the timestamp is kept, blocks are fetched again from the
+        // node when they are needed
+        serde_json::to_value(ChainStoreData {
+            block: ChainStoreBlock::new(self.time, serde_json::Value::Null),
+        })
+    }
+
+    fn timestamp(&self) -> BlockTime {
+        BlockTime::since_epoch(self.time, 0)
+    }
+}
+
+impl Vout {
+    /// The value of the output in satoshis. Amounts in BTC have at most 8
+    /// decimals and are small enough to be represented exactly as `f64`
+    pub fn satoshis(&self) -> i64 {
+        (self.value * SATOSHIS_PER_BTC).round() as i64
+    }
+}
+
+impl ScriptPubKey {
+    /// Whether the script pays to `address`. Older versions of bitcoind
+    /// report addresses in `addresses`, newer ones in `address`
+    pub fn pays_to(&self, address: &str) -> bool {
+        self.address == address || self.addresses.iter().any(|a| a == address)
+    }
+
+    /// The address the script pays to, empty if it is not a standard
+    /// script with an address
+    pub fn main_address(&self) -> &str {
+        if !self.address.is_empty() {
+            return &self.address;
+        }
+        self.addresses
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        hex::decode(&self.hex).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::blockchain::{Block as _, BlockHash, BlockPtr};
+    use prost::Message;
+
+    use super::{pbcodec, Block, Vout};
+
+    #[test]
+    fn decode_block_hashes() {
+        let mut block = pbcodec::Block {
+            hash: "02".repeat(32),
+            previous_hash: "01".repeat(32),
+            height: 10,
+            ..Default::default()
+        };
+
+        let decoded = Block::decode(block.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(
+            BlockPtr::new(BlockHash::from(vec![2u8; 32]), 10),
+            decoded.ptr()
+        );
+        assert_eq!(
+            Some(BlockPtr::new(BlockHash::from(vec![1u8; 32]), 9)),
+            decoded.parent_ptr()
+        );
+
+        block.hash = "not hex".to_string();
+        assert!(Block::decode(block.encode_to_vec().as_slice()).is_err());
+        assert!(Block::try_from(block).is_err());
+    }
+
+    #[test]
+    fn vout_satoshis() {
+        let vout = |value| Vout {
+            value,
+            ..Default::default()
+        };
+
+        assert_eq!(1, vout(0.00000001).satoshis());
+        assert_eq!(2_099_999_997_690_000, vout(20999999.9769).satoshis());
+        assert_eq!(29_000_000, vout(0.29).satoshis());
+    }
+}
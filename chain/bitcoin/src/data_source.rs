@@ -0,0 +1,468 @@
+use graph::anyhow::Context;
+use graph::blockchain::{Block, TriggerWithHandler};
+use graph::components::store::StoredDynamicDataSource;
+use graph::components::subgraph::InstanceDSTemplateInfo;
+use graph::data::subgraph::DataSourceContext;
+use graph::prelude::{hex, SubgraphManifestValidationError};
+use graph::{
+    anyhow::{anyhow, Error},
+    blockchain::{self, Blockchain},
+    prelude::{async_trait, BlockNumber, CheapClone, Deserialize, Link, LinkResolver, Logger},
+    semver,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::chain::Chain;
+use crate::codec;
+use crate::trigger::BitcoinTrigger;
+
+pub const BITCOIN_KIND: &str = "bitcoin";
+const BLOCK_HANDLER_KIND: &str = "block";
+const INPUT_HANDLER_KIND: &str = "input";
+const OUTPUT_HANDLER_KIND: &str = "output";
+
+/// Runtime representation of a data source.
+#[derive(Clone, Debug)]
+pub struct DataSource {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub(crate) source: Source,
+    pub mapping: Mapping,
+    pub context: Arc<Option<DataSourceContext>>,
+    pub creation_block: Option<BlockNumber>,
+}
+
+impl blockchain::DataSource<Chain> for DataSource {
+    fn from_template_info(
+        _info: InstanceDSTemplateInfo,
+        _template: &graph::data_source::DataSourceTemplate<Chain>,
+    ) -> Result<Self, Error> {
+        Err(anyhow!("Bitcoin subgraphs do not support templates"))
+    }
+
+    fn address(&self) -> Option<&[u8]> {
+        self.source.address.as_ref().map(String::as_bytes)
+    }
+
+    fn start_block(&self) -> BlockNumber {
+        self.source.start_block
+    }
+
+    fn handler_kinds(&self) -> HashSet<&str> {
+        let mut kinds = HashSet::new();
+
+        if !self.mapping.block_handlers.is_empty() {
+            kinds.insert(BLOCK_HANDLER_KIND);
+        }
+
+        if !self.mapping.input_handlers.is_empty() {
+            kinds.insert(INPUT_HANDLER_KIND);
+        }
+
+        if !self.mapping.output_handlers.is_empty() {
+            kinds.insert(OUTPUT_HANDLER_KIND);
+        }
+
+        kinds
+    }
+
+    fn end_block(&self) -> Option<BlockNumber> {
+        self.source.end_block
+    }
+
+    fn match_and_decode(
+        &self,
+        trigger: &<Chain as Blockchain>::TriggerData,
+        block: &Arc<<Chain as Blockchain>::Block>,
+        _logger: &Logger,
+    ) -> Result<Option<TriggerWithHandler<Chain>>, Error> {
+        if self.source.start_block > block.number() {
+            return Ok(None);
+        }
+
+        let handler = match trigger {
+            // A block trigger matches if a block handler is present.
+            BitcoinTrigger::Block(_) => match self.mapping.block_handlers.first() {
+                Some(handler) => &handler.handler,
+                None => return Ok(None),
+            },
+            // An input trigger matches if the output it spends matches the
+            // source of this data source and an input handler is present.
+            BitcoinTrigger::Input(input) => {
+                let spent = input
+                    .input
+                    .prevout
+                    .as_ref()
+                    .and_then(|prevout| prevout.script_pub_key.as_ref());
+                if !spent.map_or(false, |script| self.matches_script(script)) {
+                    return Ok(None);
+                }
+                match self.mapping.input_handlers.first() {
+                    Some(handler) => &handler.handler,
+                    None => return Ok(None),
+                }
+            }
+            // An output trigger matches if its script matches the source of
+            // this data source and an output handler is present.
+            BitcoinTrigger::Output(output) => {
+                let script = output.output.script_pub_key.as_ref();
+                if !script.map_or(false, |script| self.matches_script(script)) {
+                    return Ok(None);
+                }
+                match self.mapping.output_handlers.first() {
+                    Some(handler) => &handler.handler,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        Ok(Some(TriggerWithHandler::<Chain>::new(
+            trigger.cheap_clone(),
+            handler.clone(),
+            block.ptr(),
+            block.timestamp(),
+        )))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn network(&self) -> Option<&str> {
+        self.network.as_deref()
+    }
+
+    fn context(&self) -> Arc<Option<DataSourceContext>> {
+        self.context.cheap_clone()
+    }
+
+    fn creation_block(&self) -> Option<BlockNumber> {
+        self.creation_block
+    }
+
+    fn is_duplicate_of(&self, other: &Self) -> bool {
+        let DataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context,
+
+            // The creation block is ignored for detection duplicate data sources.
+            creation_block: _,
+        } = self;
+
+        kind == &other.kind
+            && network == &other.network
+            && name == &other.name
+            && source == &other.source
+            && mapping.block_handlers == other.mapping.block_handlers
+            && mapping.input_handlers == other.mapping.input_handlers
+            && mapping.output_handlers == other.mapping.output_handlers
+            && context == &other.context
+    }
+
+    fn as_stored_dynamic_data_source(&self) -> StoredDynamicDataSource {
+        unreachable!("Bitcoin subgraphs do not support dynamic data sources")
+    }
+
+    fn from_stored_dynamic_data_source(
+        _template: &DataSourceTemplate,
+        _stored: StoredDynamicDataSource,
+    ) -> Result<Self, Error> {
+        Err(anyhow!(
+            "Bitcoin subgraphs do not support dynamic data sources"
+        ))
+    }
+
+    fn validate(&self, _: &semver::Version) -> Vec<Error> {
+        let mut errors = Vec::new();
+
+        if self.kind != BITCOIN_KIND {
+            errors.push(anyhow!(
+                "data source has invalid `kind`, expected {} but found {}",
+                BITCOIN_KIND,
+                self.kind
+            ))
+        }
+
+        // Validate that there is an address or a script pattern if there
+        // are input or output handlers
+        let has_script_handlers =
+            !self.mapping.input_handlers.is_empty() || !self.mapping.output_handlers.is_empty();
+        match (&self.source.address, &self.source.script) {
+            (None, None) if has_script_handlers => {
+                errors.push(SubgraphManifestValidationError::SourceAddressRequired.into())
+            }
+            (Some(_), Some(_)) => errors.push(anyhow!(
+                "data source can have either an `address` or a `script` but not both"
+            )),
+            (None, Some(script)) if self.script_prefix().is_none() => errors.push(anyhow!(
+                "data source has invalid script `{}`, expected hex encoded bytes",
+                script
+            )),
+            _ => {}
+        }
+
+        // Validate that there are no more than one of each kind of handler
+        if self.mapping.block_handlers.len() > 1 {
+            errors.push(anyhow!("data source has duplicated block handlers"));
+        }
+        if self.mapping.input_handlers.len() > 1 {
+            errors.push(anyhow!("data source has duplicated input handlers"));
+        }
+        if self.mapping.output_handlers.len() > 1 {
+            errors.push(anyhow!("data source has duplicated output handlers"));
+        }
+
+        errors
+    }
+
+    fn api_version(&self) -> semver::Version {
+        self.mapping.api_version.clone()
+    }
+
+    fn runtime(&self) -> Option<Arc<Vec<u8>>> {
+        Some(self.mapping.runtime.cheap_clone())
+    }
+}
+
+impl DataSource {
+    fn from_manifest(
+        kind: String,
+        network: Option<String>,
+        name: String,
+        source: Source,
+        mapping: Mapping,
+        context: Option<DataSourceContext>,
+    ) -> Result<Self, Error> {
+        // Data sources in the manifest are created "before genesis" so they have no creation block.
+        let creation_block = None;
+
+        Ok(DataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context: Arc::new(context),
+            creation_block,
+        })
+    }
+
+    /// The decoded script pattern of the source, `None` if there is none or
+    /// it is not valid hex
+    pub(crate) fn script_prefix(&self) -> Option<Vec<u8>> {
+        self.source.script.as_ref().and_then(|script| {
+            hex::decode(script.trim_start_matches("0x"))
+                .ok()
+                .filter(|bytes| !bytes.is_empty())
+        })
+    }
+
+    /// Whether `script` pays to the address of the source or starts with
+    /// its script pattern
+    fn matches_script(&self, script: &codec::ScriptPubKey) -> bool {
+        if let Some(address) = &self.source.address {
+            return script.pays_to(address);
+        }
+        match self.script_prefix() {
+            Some(prefix) => script.bytes().starts_with(&prefix),
+            None => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct UnresolvedDataSource {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub(crate) source: Source,
+    pub mapping: UnresolvedMapping,
+    pub context: Option<DataSourceContext>,
+}
+
+#[async_trait]
+impl blockchain::UnresolvedDataSource<Chain> for UnresolvedDataSource {
+    async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+        _manifest_idx: u32,
+    ) -> Result<DataSource, Error> {
+        let UnresolvedDataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context,
+        } = self;
+
+        let mapping = mapping.resolve(resolver, logger).await.with_context(|| {
+            format!(
+                "failed to resolve data source {} with address {:?}, script {:?} and start_block {}",
+                name, source.address, source.script, source.start_block
+            )
+        })?;
+
+        DataSource::from_manifest(kind, network, name, source, mapping, context)
+    }
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+pub struct BaseDataSourceTemplate<M> {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub mapping: M,
+}
+
+pub type UnresolvedDataSourceTemplate = BaseDataSourceTemplate<UnresolvedMapping>;
+pub type DataSourceTemplate = BaseDataSourceTemplate<Mapping>;
+
+#[async_trait]
+impl blockchain::UnresolvedDataSourceTemplate<Chain> for UnresolvedDataSourceTemplate {
+    async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+        _manifest_idx: u32,
+    ) -> Result<DataSourceTemplate, Error> {
+        let UnresolvedDataSourceTemplate {
+            kind,
+            network,
+            name,
+            mapping,
+        } = self;
+
+        let mapping = mapping
+            .resolve(resolver, logger)
+            .await
+            .with_context(|| format!("failed to resolve data source template {}", name))?;
+
+        Ok(DataSourceTemplate {
+            kind,
+            network,
+            name,
+            mapping,
+        })
+    }
+}
+
+impl blockchain::DataSourceTemplate<Chain> for DataSourceTemplate {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn api_version(&self) -> semver::Version {
+        self.mapping.api_version.clone()
+    }
+
+    fn runtime(&self) -> Option<Arc<Vec<u8>>> {
+        Some(self.mapping.runtime.cheap_clone())
+    }
+
+    fn manifest_idx(&self) -> u32 {
+        unreachable!("bitcoin does not support dynamic data sources")
+    }
+
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedMapping {
+    pub api_version: String,
+    pub language: String,
+    pub entities: Vec<String>,
+    #[serde(default)]
+    pub block_handlers: Vec<MappingBlockHandler>,
+    #[serde(default)]
+    pub input_handlers: Vec<MappingHandler>,
+    #[serde(default)]
+    pub output_handlers: Vec<MappingHandler>,
+    pub file: Link,
+}
+
+impl UnresolvedMapping {
+    pub async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+    ) -> Result<Mapping, Error> {
+        let UnresolvedMapping {
+            api_version,
+            language,
+            entities,
+            block_handlers,
+            input_handlers,
+            output_handlers,
+            file: link,
+        } = self;
+
+        let api_version = semver::Version::parse(&api_version)?;
+
+        let module_bytes = resolver
+            .cat(logger, &link)
+            .await
+            .with_context(|| format!("failed to resolve mapping {}", link.link))?;
+
+        Ok(Mapping {
+            api_version,
+            language,
+            entities,
+            block_handlers,
+            input_handlers,
+            output_handlers,
+            runtime: Arc::new(module_bytes),
+            link,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Mapping {
+    pub api_version: semver::Version,
+    pub language: String,
+    pub entities: Vec<String>,
+    pub block_handlers: Vec<MappingBlockHandler>,
+    pub input_handlers: Vec<MappingHandler>,
+    pub output_handlers: Vec<MappingHandler>,
+    pub runtime: Arc<Vec<u8>>,
+    pub link: Link,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingBlockHandler {
+    pub handler: String,
+}
+
+/// A handler for inputs or outputs
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingHandler {
+    pub handler: String,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Source {
+    // A data source that has neither an address nor a script can only have block handlers.
+    pub(crate) address: Option<String>,
+    /// Hex encoded prefix of the output scripts to match, e.g., `6a` for
+    /// `OP_RETURN` outputs
+    pub(crate) script: Option<String>,
+    #[serde(default)]
+    pub(crate) start_block: BlockNumber,
+    pub(crate) end_block: Option<BlockNumber>,
+}
@@ -0,0 +1,192 @@
+use graph::blockchain::BlockchainKind;
+use graph::components::network_provider::ChainName;
+use graph::slog::o;
+use graph::util::backoff::ExponentialBackoff;
+use graph::{
+    blockchain::{BlockHash, BlockIngestor, BlockPtr, IngestorError},
+    cheap_clone::CheapClone,
+    prelude::{async_trait, error, info, tokio, trace, warn, ChainStore, LogCode, Logger},
+};
+use std::{sync::Arc, time::Duration};
+
+use crate::rpc::{BitcoinRpcAdapter, BitcoinRpcAdapters};
+
+/// Ingests blocks from the JSON-RPC API of a bitcoind node into the chain
+/// store. This mirrors the polling ingestor for Ethereum
+pub struct PollingBlockIngestor {
+    logger: Logger,
+    ancestor_count: i32,
+    adapters: BitcoinRpcAdapters,
+    chain_store: Arc<dyn ChainStore>,
+    polling_interval: Duration,
+    network_name: ChainName,
+}
+
+impl PollingBlockIngestor {
+    pub fn new(
+        logger: Logger,
+        ancestor_count: i32,
+        adapters: BitcoinRpcAdapters,
+        chain_store: Arc<dyn ChainStore>,
+        polling_interval: Duration,
+        network_name: ChainName,
+    ) -> PollingBlockIngestor {
+        PollingBlockIngestor {
+            logger,
+            ancestor_count,
+            adapters,
+            chain_store,
+            polling_interval,
+            network_name,
+        }
+    }
+
+    async fn do_poll(
+        &self,
+        logger: &Logger,
+        adapter: Arc<BitcoinRpcAdapter>,
+    ) -> Result<(), IngestorError> {
+        trace!(&logger, "BlockIngestor::do_poll");
+
+        // Get chain head ptr from store
+        let head_block_ptr_opt = self.chain_store.cheap_clone().chain_head_ptr().await?;
+
+        // `getblockchaininfo` is much cheaper than fetching the block, and
+        // most of the time there won't be a new block
+        let latest_block: BlockPtr = adapter.latest_block_ptr().await?;
+
+        if let Some(head_block) = head_block_ptr_opt.as_ref() {
+            // If latest block matches head block in store, nothing needs to be done
+            if &latest_block == head_block {
+                return Ok(());
+            }
+
+            if latest_block.number < head_block.number {
+                // An ingestor might wait or move forward, but it never
+                // wavers and goes back. More seriously, this keeps us from
+                // later trying to ingest a block with the same number again
+                warn!(&logger,
+                    "Provider went backwards - ignoring this latest block";
+                    "current_block_head" => head_block.number,
+                    "latest_block_head" => latest_block.number);
+                return Ok(());
+            }
+        }
+
+        // Compare latest block with head ptr, alert user if far behind
+        match head_block_ptr_opt {
+            None => {
+                info!(
+                    &logger,
+                    "Downloading latest blocks from Bitcoin, this may take a few minutes..."
+                );
+            }
+            Some(head_block_ptr) => {
+                let latest_number = latest_block.number;
+                let head_number = head_block_ptr.number;
+                let distance = latest_number - head_number;
+                let blocks_needed = (distance).min(self.ancestor_count);
+                let code = if distance >= 15 {
+                    LogCode::BlockIngestionLagging
+                } else {
+                    LogCode::BlockIngestionStatus
+                };
+                if distance > 0 {
+                    info!(
+                    &logger,
+                        "Syncing {} blocks from Bitcoin",
+                        blocks_needed;
+                        "current_block_head" => head_number,
+                        "latest_block_head" => latest_number,
+                        "blocks_behind" => distance,
+                        "blocks_needed" => blocks_needed,
+                        "code" => code,
+                    );
+                }
+            }
+        }
+
+        // Store latest block in block store, then repeatedly fetch and
+        // ingest missing parent blocks. This terminates for the same
+        // reasons as the Ethereum ingestor: each iteration only reports
+        // parents with lower block numbers, down to `ancestor_count`
+        // blocks below the latest block
+        let mut missing_block_hash = self
+            .ingest_block(&logger, &adapter, &latest_block.hash)
+            .await?;
+
+        while let Some(hash) = missing_block_hash {
+            missing_block_hash = self.ingest_block(&logger, &adapter, &hash).await?;
+        }
+        Ok(())
+    }
+
+    async fn ingest_block(
+        &self,
+        logger: &Logger,
+        adapter: &Arc<BitcoinRpcAdapter>,
+        block_hash: &BlockHash,
+    ) -> Result<Option<BlockHash>, IngestorError> {
+        let block = adapter.block_by_hash(&block_hash.hash_hex()).await?;
+
+        // Store it in the database and try to advance the chain head pointer
+        self.chain_store.upsert_block(Arc::new(block)).await?;
+
+        self.chain_store
+            .cheap_clone()
+            .attempt_chain_head_update(self.ancestor_count)
+            .await
+            .map(|missing| missing.map(|h256| h256.into()))
+            .map_err(|e| {
+                error!(logger, "failed to update chain head");
+                IngestorError::Unknown(e)
+            })
+    }
+}
+
+#[async_trait]
+impl BlockIngestor for PollingBlockIngestor {
+    async fn run(self: Box<Self>) {
+        let mut backoff =
+            ExponentialBackoff::new(Duration::from_millis(250), Duration::from_secs(30));
+
+        loop {
+            let adapter = match self.adapters.adapter() {
+                Ok(adapter) => {
+                    backoff.reset();
+                    adapter
+                }
+                Err(err) => {
+                    error!(
+                        &self.logger,
+                        "unable to get bitcoin adapter, backing off... error: {}",
+                        err.to_string()
+                    );
+                    backoff.sleep_async().await;
+                    continue;
+                }
+            };
+            let logger = self
+                .logger
+                .new(o!("provider" => adapter.provider().to_string()));
+
+            match self.do_poll(&logger, adapter).await {
+                // Some polls will fail due to transient issues
+                Err(err) => {
+                    error!(logger, "Trying again after block polling failed: {}", err);
+                }
+                Ok(()) => (),
+            }
+
+            tokio::time::sleep(self.polling_interval).await;
+        }
+    }
+
+    fn network_name(&self) -> ChainName {
+        self.network_name.clone()
+    }
+
+    fn kind(&self) -> BlockchainKind {
+        BlockchainKind::Bitcoin
+    }
+}
@@ -0,0 +1,12 @@
+mod adapter;
+mod chain;
+mod codec;
+mod data_source;
+mod ingestor;
+mod rpc;
+mod runtime;
+mod trigger;
+
+pub use crate::chain::Chain;
+pub use codec::Block;
+pub use rpc::{BitcoinRpcAdapter, BitcoinRpcAdapters};
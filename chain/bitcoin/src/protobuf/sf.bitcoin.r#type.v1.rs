@@ -0,0 +1,116 @@
+// This file is @generated by prost-build.
+/// The fields mirror the output of bitcoind's `getblock` with verbosity 3
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Block {
+    #[prost(string, tag = "1")]
+    pub hash: ::prost::alloc::string::String,
+    #[prost(int32, tag = "3")]
+    pub size: i32,
+    #[prost(int32, tag = "4")]
+    pub stripped_size: i32,
+    #[prost(int32, tag = "5")]
+    pub weight: i32,
+    #[prost(int64, tag = "6")]
+    pub height: i64,
+    #[prost(int32, tag = "7")]
+    pub version: i32,
+    #[prost(string, tag = "8")]
+    pub version_hex: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub merkle_root: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "10")]
+    pub tx: ::prost::alloc::vec::Vec<Transaction>,
+    #[prost(int64, tag = "11")]
+    pub time: i64,
+    #[prost(int64, tag = "12")]
+    pub mediantime: i64,
+    #[prost(uint32, tag = "13")]
+    pub nonce: u32,
+    #[prost(string, tag = "14")]
+    pub bits: ::prost::alloc::string::String,
+    #[prost(double, tag = "15")]
+    pub difficulty: f64,
+    #[prost(string, tag = "16")]
+    pub chainwork: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "17")]
+    pub n_tx: u32,
+    #[prost(string, tag = "18")]
+    pub previous_hash: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Transaction {
+    #[prost(string, tag = "1")]
+    pub hex: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub txid: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub hash: ::prost::alloc::string::String,
+    #[prost(int32, tag = "4")]
+    pub size: i32,
+    #[prost(int32, tag = "5")]
+    pub vsize: i32,
+    #[prost(int32, tag = "6")]
+    pub weight: i32,
+    #[prost(uint32, tag = "7")]
+    pub version: u32,
+    #[prost(uint32, tag = "8")]
+    pub locktime: u32,
+    #[prost(message, repeated, tag = "9")]
+    pub vin: ::prost::alloc::vec::Vec<Vin>,
+    #[prost(message, repeated, tag = "10")]
+    pub vout: ::prost::alloc::vec::Vec<Vout>,
+    #[prost(string, tag = "11")]
+    pub blockhash: ::prost::alloc::string::String,
+    #[prost(int64, tag = "12")]
+    pub blocktime: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Vin {
+    #[prost(string, tag = "1")]
+    pub txid: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub vout: u32,
+    #[prost(string, tag = "3")]
+    pub coinbase: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub script_sig: ::core::option::Option<ScriptSig>,
+    #[prost(uint32, tag = "5")]
+    pub sequence: u32,
+    #[prost(string, repeated, tag = "6")]
+    pub txinwitness: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// The output spent by this input, not set for coinbase inputs
+    #[prost(message, optional, tag = "7")]
+    pub prevout: ::core::option::Option<Vout>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Vout {
+    /// The value in BTC
+    #[prost(double, tag = "1")]
+    pub value: f64,
+    #[prost(uint32, tag = "2")]
+    pub n: u32,
+    #[prost(message, optional, tag = "3")]
+    pub script_pub_key: ::core::option::Option<ScriptPubKey>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScriptSig {
+    #[prost(string, tag = "1")]
+    pub asm: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub hex: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScriptPubKey {
+    #[prost(string, tag = "1")]
+    pub asm: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub hex: ::prost::alloc::string::String,
+    #[prost(int32, tag = "3")]
+    pub req_sigs: i32,
+    #[prost(string, tag = "4")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "5")]
+    pub addresses: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "6")]
+    pub address: ::prost::alloc::string::String,
+}
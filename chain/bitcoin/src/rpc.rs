@@ -0,0 +1,560 @@
+//! A client for the JSON-RPC API of bitcoind and compatible nodes. Blocks
+//! are requested with `getblock` at verbosity 3 so that inputs include the
+//! outputs they spend, which bitcoind supports since version 23.
+
+use graph::anyhow::{anyhow, bail, Context};
+use graph::blockchain::{BlockPtr, ChainIdentifier};
+use graph::prelude::{reqwest, serde_json, BlockNumber, Error};
+use graph::url::Url;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::codec;
+
+pub struct BitcoinRpcAdapter {
+    provider: String,
+    url: Url,
+    credentials: Option<(String, Option<String>)>,
+    client: reqwest::Client,
+    request_id: AtomicU64,
+}
+
+impl std::fmt::Debug for BitcoinRpcAdapter {
+    // The url can contain credentials, so we don't print it
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BitcoinRpcAdapter")
+            .field("provider", &self.provider)
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct BlockchainInfo {
+    chain: String,
+    blocks: BlockNumber,
+    #[serde(rename = "bestblockhash")]
+    best_block_hash: String,
+}
+
+impl BitcoinRpcAdapter {
+    /// Credentials in `url` are sent with basic authentication, which is
+    /// what bitcoind's `rpcauth` expects
+    pub fn new(provider: impl Into<String>, mut url: Url) -> Self {
+        let credentials = if url.username().is_empty() {
+            None
+        } else {
+            let credentials = (
+                url.username().to_string(),
+                url.password().map(str::to_string),
+            );
+            // Neither can fail for urls that have a username
+            url.set_username("").ok();
+            url.set_password(None).ok();
+            Some(credentials)
+        };
+
+        BitcoinRpcAdapter {
+            provider: provider.into(),
+            url,
+            credentials,
+            client: reqwest::Client::new(),
+            request_id: AtomicU64::new(0),
+        }
+    }
+
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, Error> {
+        let body = serde_json::json!({
+            "jsonrpc": "1.0",
+            "id": self.request_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+
+        let mut request = self.client.post(self.url.clone());
+        if let Some((username, password)) = &self.credentials {
+            request = request.basic_auth(username, password.as_ref());
+        }
+
+        // bitcoind reports errors with HTTP status codes, but still
+        // includes the error in the body, so we don't check the status
+        let response: RpcResponse<T> = request
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("{} request to {} failed", method, self.provider))?
+            .json()
+            .await
+            .with_context(|| format!("invalid response to {} from {}", method, self.provider))?;
+
+        match response {
+            RpcResponse {
+                error: Some(RpcError { code, message }),
+                ..
+            } => bail!(
+                "{} failed on {} with error {}: {}",
+                method,
+                self.provider,
+                code,
+                message
+            ),
+            RpcResponse {
+                result: Some(result),
+                ..
+            } => Ok(result),
+            RpcResponse { result: None, .. } => {
+                bail!("{} returned no result on {}", method, self.provider)
+            }
+        }
+    }
+
+    async fn blockchain_info(&self) -> Result<BlockchainInfo, Error> {
+        self.call("getblockchaininfo", serde_json::json!([])).await
+    }
+
+    /// The pointer to the tip of the chain of the node
+    pub async fn latest_block_ptr(&self) -> Result<BlockPtr, Error> {
+        let info = self.blockchain_info().await?;
+        Ok(BlockPtr {
+            hash: codec::decode_hash(&info.best_block_hash)?,
+            number: info.blocks,
+        })
+    }
+
+    /// The hash of the block with the given number on the chain of the node
+    pub async fn block_hash(&self, number: BlockNumber) -> Result<String, Error> {
+        self.call("getblockhash", serde_json::json!([number])).await
+    }
+
+    pub async fn block_by_hash(&self, hash: &str) -> Result<codec::Block, Error> {
+        let block: RpcBlock = self.call("getblock", serde_json::json!([hash, 3])).await?;
+        block.try_into()
+    }
+
+    pub async fn block_by_number(&self, number: BlockNumber) -> Result<codec::Block, Error> {
+        let hash = self.block_hash(number).await?;
+        self.block_by_hash(&hash).await
+    }
+
+    pub async fn chain_identifier(&self) -> Result<ChainIdentifier, Error> {
+        let info = self.blockchain_info().await?;
+        let genesis_hash = self.block_hash(0).await?;
+        Ok(ChainIdentifier {
+            net_version: info.chain,
+            genesis_block_hash: codec::decode_hash(&genesis_hash)?,
+        })
+    }
+}
+
+/// The JSON-RPC clients for one chain
+#[derive(Clone, Debug, Default)]
+pub struct BitcoinRpcAdapters {
+    adapters: Vec<Arc<BitcoinRpcAdapter>>,
+}
+
+impl BitcoinRpcAdapters {
+    pub fn new(adapters: Vec<Arc<BitcoinRpcAdapter>>) -> Self {
+        BitcoinRpcAdapters { adapters }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adapters.is_empty()
+    }
+
+    pub fn adapters(&self) -> &[Arc<BitcoinRpcAdapter>] {
+        &self.adapters
+    }
+
+    /// The adapter to use for requests. Providers are used in the order in
+    /// which they are configured
+    pub fn adapter(&self) -> Result<Arc<BitcoinRpcAdapter>, Error> {
+        self.adapters
+            .first()
+            .cloned()
+            .ok_or_else(|| anyhow!("no bitcoin rpc adapter available"))
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcBlock {
+    hash: String,
+    #[serde(default)]
+    size: i32,
+    #[serde(default)]
+    strippedsize: i32,
+    #[serde(default)]
+    weight: i32,
+    height: i64,
+    #[serde(default)]
+    version: i32,
+    #[serde(default, rename = "versionHex")]
+    version_hex: String,
+    #[serde(default)]
+    merkleroot: String,
+    #[serde(default)]
+    tx: Vec<RpcTransaction>,
+    time: i64,
+    #[serde(default)]
+    mediantime: i64,
+    #[serde(default)]
+    nonce: u32,
+    #[serde(default)]
+    bits: String,
+    #[serde(default)]
+    difficulty: f64,
+    #[serde(default)]
+    chainwork: String,
+    #[serde(default, rename = "nTx")]
+    n_tx: u32,
+    // Not set for the genesis block
+    #[serde(default)]
+    previousblockhash: String,
+}
+
+#[derive(Deserialize)]
+struct RpcTransaction {
+    #[serde(default)]
+    hex: String,
+    txid: String,
+    #[serde(default)]
+    hash: String,
+    #[serde(default)]
+    size: i32,
+    #[serde(default)]
+    vsize: i32,
+    #[serde(default)]
+    weight: i32,
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    locktime: u32,
+    #[serde(default)]
+    vin: Vec<RpcVin>,
+    #[serde(default)]
+    vout: Vec<RpcVout>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcVin {
+    #[serde(default)]
+    txid: String,
+    #[serde(default)]
+    vout: u32,
+    #[serde(default)]
+    coinbase: String,
+    script_sig: Option<RpcScriptSig>,
+    #[serde(default)]
+    sequence: u32,
+    #[serde(default)]
+    txinwitness: Vec<String>,
+    prevout: Option<RpcPrevout>,
+}
+
+#[derive(Deserialize)]
+struct RpcScriptSig {
+    #[serde(default)]
+    asm: String,
+    #[serde(default)]
+    hex: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcPrevout {
+    value: f64,
+    script_pub_key: RpcScriptPubKey,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcVout {
+    value: f64,
+    n: u32,
+    script_pub_key: RpcScriptPubKey,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcScriptPubKey {
+    #[serde(default)]
+    asm: String,
+    #[serde(default)]
+    hex: String,
+    #[serde(default)]
+    req_sigs: i32,
+    #[serde(default, rename = "type")]
+    script_type: String,
+    #[serde(default)]
+    addresses: Vec<String>,
+    #[serde(default)]
+    address: String,
+}
+
+impl From<RpcScriptPubKey> for codec::ScriptPubKey {
+    fn from(script: RpcScriptPubKey) -> Self {
+        codec::ScriptPubKey {
+            asm: script.asm,
+            hex: script.hex,
+            req_sigs: script.req_sigs,
+            r#type: script.script_type,
+            addresses: script.addresses,
+            address: script.address,
+        }
+    }
+}
+
+impl From<RpcVin> for codec::Vin {
+    fn from(vin: RpcVin) -> Self {
+        let n = vin.vout;
+        codec::Vin {
+            txid: vin.txid,
+            vout: vin.vout,
+            coinbase: vin.coinbase,
+            script_sig: vin.script_sig.map(|script| codec::ScriptSig {
+                asm: script.asm,
+                hex: script.hex,
+            }),
+            sequence: vin.sequence,
+            txinwitness: vin.txinwitness,
+            prevout: vin.prevout.map(|prevout| codec::Vout {
+                value: prevout.value,
+                n,
+                script_pub_key: Some(prevout.script_pub_key.into()),
+            }),
+        }
+    }
+}
+
+impl From<RpcVout> for codec::Vout {
+    fn from(vout: RpcVout) -> Self {
+        codec::Vout {
+            value: vout.value,
+            n: vout.n,
+            script_pub_key: Some(vout.script_pub_key.into()),
+        }
+    }
+}
+
+impl TryFrom<RpcBlock> for codec::Block {
+    type Error = Error;
+
+    fn try_from(block: RpcBlock) -> Result<Self, Self::Error> {
+        let RpcBlock {
+            hash,
+            size,
+            strippedsize,
+            weight,
+            height,
+            version,
+            version_hex,
+            merkleroot,
+            tx,
+            time,
+            mediantime,
+            nonce,
+            bits,
+            difficulty,
+            chainwork,
+            n_tx,
+            previousblockhash,
+        } = block;
+
+        let tx = tx
+            .into_iter()
+            .map(|tx| codec::Transaction {
+                hex: tx.hex,
+                txid: tx.txid,
+                hash: tx.hash,
+                size: tx.size,
+                vsize: tx.vsize,
+                weight: tx.weight,
+                version: tx.version,
+                locktime: tx.locktime,
+                vin: tx.vin.into_iter().map(Into::into).collect(),
+                vout: tx.vout.into_iter().map(Into::into).collect(),
+                blockhash: hash.clone(),
+                blocktime: time,
+            })
+            .collect();
+
+        codec::pbcodec::Block {
+            hash,
+            size,
+            stripped_size: strippedsize,
+            weight,
+            height,
+            version,
+            version_hex,
+            merkle_root: merkleroot,
+            tx,
+            time,
+            mediantime,
+            nonce,
+            bits,
+            difficulty,
+            chainwork,
+            n_tx,
+            previous_hash: previousblockhash,
+        }
+        .try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::blockchain::Block as _;
+    use graph::prelude::serde_json;
+
+    use super::RpcBlock;
+    use crate::codec;
+
+    const BLOCK: &str = r#"{
+      "hash": "00000000000000000002a7c4c1e48d76c5a37902165a270156b7a8d72728a054",
+      "confirmations": 1,
+      "height": 800000,
+      "version": 536928256,
+      "versionHex": "2000e000",
+      "merkleroot": "9a0a5a5c3ebbf7c6d0c5d0c5d0c5d0c5d0c5d0c5d0c5d0c5d0c5d0c5d0c5d0c5",
+      "time": 1690168629,
+      "mediantime": 1690165851,
+      "nonce": 106861918,
+      "bits": "17053894",
+      "difficulty": 53911173001054.59,
+      "chainwork": "00000000000000000000000000000000000000004fc85ab8b77c7e3dca3a0a36",
+      "nTx": 2,
+      "previousblockhash": "00000000000000000001b8bf3d9e7f9c3a5e5a0b6d7f7b2b8d8f9c7a6b5c4d3e",
+      "strippedsize": 777000,
+      "size": 1500000,
+      "weight": 3831000,
+      "tx": [
+        {
+          "txid": "b75ca3106ed100521aa50e3ec267a06431c6319538898b25e1b757a5736f5fb4",
+          "hash": "b75ca3106ed100521aa50e3ec267a06431c6319538898b25e1b757a5736f5fb4",
+          "version": 1,
+          "size": 200,
+          "vsize": 173,
+          "weight": 692,
+          "locktime": 0,
+          "vin": [
+            { "coinbase": "0335350d", "sequence": 4294967295 }
+          ],
+          "vout": [
+            {
+              "value": 6.4363322,
+              "n": 0,
+              "scriptPubKey": {
+                "asm": "0 3b8cf5f3d3b5c1e4d1f1f6c9b8a9e6d4c4b3a291",
+                "hex": "00143b8cf5f3d3b5c1e4d1f1f6c9b8a9e6d4c4b3a291",
+                "address": "bc1q8wx0tu7nkhq7f503lmymd20x6nztxs53jevkz4",
+                "type": "witness_v0_keyhash"
+              }
+            }
+          ],
+          "hex": "01000000"
+        },
+        {
+          "txid": "c9f9f2f2d6b0d1b1e8b0b4b3c1c7f5e9b0a9c8d7e6f5a4b3c2d1e0f9a8b7c6d5",
+          "hash": "d9f9f2f2d6b0d1b1e8b0b4b3c1c7f5e9b0a9c8d7e6f5a4b3c2d1e0f9a8b7c6d5",
+          "version": 2,
+          "size": 222,
+          "vsize": 141,
+          "weight": 561,
+          "locktime": 799999,
+          "vin": [
+            {
+              "txid": "a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f90",
+              "vout": 3,
+              "scriptSig": { "asm": "", "hex": "" },
+              "txinwitness": ["3044", "02ab"],
+              "prevout": {
+                "generated": false,
+                "height": 799000,
+                "value": 0.5,
+                "scriptPubKey": {
+                  "asm": "1 7a2c",
+                  "hex": "51207a2c",
+                  "address": "bc1p0gkqtwm8np4",
+                  "type": "witness_v1_taproot"
+                }
+              },
+              "sequence": 4294967293
+            }
+          ],
+          "vout": [
+            {
+              "value": 0.0,
+              "n": 0,
+              "scriptPubKey": {
+                "asm": "OP_RETURN 68656c6c6f",
+                "hex": "6a0568656c6c6f",
+                "type": "nulldata"
+              }
+            }
+          ],
+          "fee": 0.00001,
+          "hex": "02000000"
+        }
+      ]
+    }"#;
+
+    #[test]
+    fn block_from_rpc() {
+        let block: RpcBlock = serde_json::from_str(BLOCK).unwrap();
+        let block = codec::Block::try_from(block).unwrap();
+
+        assert_eq!(800000, block.number());
+        assert_eq!(799999, block.parent_ptr().unwrap().number);
+        assert_eq!(
+            "00000000000000000002a7c4c1e48d76c5a37902165a270156b7a8d72728a054",
+            block.ptr().hash.hash_hex()
+        );
+        assert_eq!(2, block.tx.len());
+
+        let coinbase = &block.tx[0];
+        assert_eq!("0335350d", coinbase.vin[0].coinbase);
+        assert!(coinbase.vin[0].prevout.is_none());
+        assert_eq!(643_633_220, coinbase.vout[0].satoshis());
+        assert!(coinbase.vout[0]
+            .script_pub_key
+            .as_ref()
+            .unwrap()
+            .pays_to("bc1q8wx0tu7nkhq7f503lmymd20x6nztxs53jevkz4"));
+        assert_eq!(block.hash, coinbase.blockhash);
+
+        let spend = &block.tx[1];
+        let prevout = spend.vin[0].prevout.as_ref().unwrap();
+        assert_eq!(3, prevout.n);
+        assert_eq!(50_000_000, prevout.satoshis());
+        assert_eq!(
+            "bc1p0gkqtwm8np4",
+            prevout.script_pub_key.as_ref().unwrap().main_address()
+        );
+        assert_eq!(vec!["3044", "02ab"], spend.vin[0].txinwitness);
+        assert_eq!(
+            "nulldata",
+            spend.vout[0].script_pub_key.as_ref().unwrap().r#type
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use crate::codec;
+use crate::trigger::{InputWithInfo, OutputWithInfo};
+use graph::prelude::hex;
+use graph::runtime::gas::GasCounter;
+use graph::runtime::{asc_new, AscHeap, AscPtr, HostExportError, ToAscObj};
+use graph_runtime_wasm::asc_abi::class::{Array, Uint8Array};
+
+pub(crate) use super::generated::*;
+
+/// Hashes, txids and scripts are hex encoded by the node
+fn decode_hex(value: &str) -> Vec<u8> {
+    hex::decode(value).unwrap_or_default()
+}
+
+impl ToAscObj<AscBlock> for codec::Block {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscBlock, HostExportError> {
+        Ok(AscBlock {
+            hash: asc_new(heap, self.hash().as_slice(), gas)?,
+            previous_hash: asc_new(heap, self.parent_hash().as_slice(), gas)?,
+            merkle_root: asc_new(heap, decode_hex(&self.merkle_root).as_slice(), gas)?,
+            bits: asc_new(heap, &self.bits, gas)?,
+            height: self.height,
+            timestamp: self.time,
+            median_time: self.mediantime,
+            difficulty: self.difficulty,
+            version: self.version,
+            nonce: self.nonce,
+            size: self.size,
+            weight: self.weight,
+            tx_count: self.n_tx,
+        })
+    }
+}
+
+impl ToAscObj<AscTransaction> for codec::Transaction {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscTransaction, HostExportError> {
+        Ok(AscTransaction {
+            txid: asc_new(heap, decode_hex(&self.txid).as_slice(), gas)?,
+            hash: asc_new(heap, decode_hex(&self.hash).as_slice(), gas)?,
+            inputs: asc_new(heap, &self.vin, gas)?,
+            outputs: asc_new(heap, &self.vout, gas)?,
+            version: self.version,
+            locktime: self.locktime,
+            size: self.size,
+            vsize: self.vsize,
+            weight: self.weight,
+        })
+    }
+}
+
+impl ToAscObj<AscInput> for codec::Vin {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscInput, HostExportError> {
+        let script_sig = self
+            .script_sig
+            .as_ref()
+            .map(|script| decode_hex(&script.hex))
+            .unwrap_or_default();
+        let witness: Vec<Vec<u8>> = self
+            .txinwitness
+            .iter()
+            .map(|item| decode_hex(item))
+            .collect();
+
+        Ok(AscInput {
+            txid: asc_new(heap, decode_hex(&self.txid).as_slice(), gas)?,
+            coinbase: asc_new(heap, decode_hex(&self.coinbase).as_slice(), gas)?,
+            script_sig: asc_new(heap, script_sig.as_slice(), gas)?,
+            witness: asc_new(heap, &witness, gas)?,
+            prevout: match &self.prevout {
+                Some(prevout) => asc_new(heap, prevout, gas)?,
+                None => AscPtr::null(),
+            },
+            vout: self.vout,
+            sequence: self.sequence,
+        })
+    }
+}
+
+impl ToAscObj<AscOutput> for codec::Vout {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscOutput, HostExportError> {
+        let script = self.script_pub_key.clone().unwrap_or_default();
+
+        Ok(AscOutput {
+            value: self.satoshis(),
+            script_pub_key: asc_new(heap, script.bytes().as_slice(), gas)?,
+            script_type: asc_new(heap, &script.r#type, gas)?,
+            address: asc_new(heap, script.main_address(), gas)?,
+            n: self.n,
+        })
+    }
+}
+
+impl ToAscObj<AscInputArray> for Vec<codec::Vin> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscInputArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscInputArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscOutputArray> for Vec<codec::Vout> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscOutputArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscOutputArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscBytesArray> for Vec<Vec<u8>> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscBytesArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x.as_slice(), gas))
+            .collect::<Result<Vec<AscPtr<Uint8Array>>, _>>()?;
+        Ok(AscBytesArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscInputWithInfo> for InputWithInfo {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscInputWithInfo, HostExportError> {
+        Ok(AscInputWithInfo {
+            input: asc_new(heap, &self.input, gas)?,
+            transaction: asc_new(heap, self.transaction.as_ref(), gas)?,
+            block: asc_new(heap, self.block.as_ref(), gas)?,
+            index: self.index,
+        })
+    }
+}
+
+impl ToAscObj<AscOutputWithInfo> for OutputWithInfo {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscOutputWithInfo, HostExportError> {
+        Ok(AscOutputWithInfo {
+            output: asc_new(heap, &self.output, gas)?,
+            transaction: asc_new(heap, self.transaction.as_ref(), gas)?,
+            block: asc_new(heap, self.block.as_ref(), gas)?,
+            index: self.index,
+        })
+    }
+}
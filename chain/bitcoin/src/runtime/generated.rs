@@ -0,0 +1,164 @@
+use graph::runtime::{AscIndexId, AscPtr, AscType, DeterministicHostError, IndexForAscTypeId};
+use graph::semver::Version;
+use graph_runtime_derive::AscType;
+use graph_runtime_wasm::asc_abi::class::{Array, AscString, Uint8Array};
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscBlock {
+    pub hash: AscPtr<Uint8Array>,
+    pub previous_hash: AscPtr<Uint8Array>,
+    pub merkle_root: AscPtr<Uint8Array>,
+    pub bits: AscPtr<AscString>,
+    pub height: i64,
+    pub timestamp: i64,
+    pub median_time: i64,
+    pub difficulty: f64,
+    pub version: i32,
+    pub nonce: u32,
+    pub size: i32,
+    pub weight: i32,
+    pub tx_count: u32,
+}
+
+impl AscIndexId for AscBlock {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinBlock;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscTransaction {
+    pub txid: AscPtr<Uint8Array>,
+    pub hash: AscPtr<Uint8Array>,
+    pub inputs: AscPtr<AscInputArray>,
+    pub outputs: AscPtr<AscOutputArray>,
+    pub version: u32,
+    pub locktime: u32,
+    pub size: i32,
+    pub vsize: i32,
+    pub weight: i32,
+}
+
+impl AscIndexId for AscTransaction {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinTransaction;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscInput {
+    /// Empty for coinbase inputs
+    pub txid: AscPtr<Uint8Array>,
+    pub coinbase: AscPtr<Uint8Array>,
+    pub script_sig: AscPtr<Uint8Array>,
+    pub witness: AscPtr<AscBytesArray>,
+    /// The output spent by the input, null for coinbase inputs
+    pub prevout: AscPtr<AscOutput>,
+    pub vout: u32,
+    pub sequence: u32,
+}
+
+impl AscIndexId for AscInput {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinInput;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscOutput {
+    /// The value in satoshis
+    pub value: i64,
+    pub script_pub_key: AscPtr<Uint8Array>,
+    pub script_type: AscPtr<AscString>,
+    /// Empty if the script does not pay to an address
+    pub address: AscPtr<AscString>,
+    pub n: u32,
+}
+
+impl AscIndexId for AscOutput {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinOutput;
+}
+
+#[repr(C)]
+pub struct AscInputArray(pub(crate) Array<AscPtr<AscInput>>);
+
+impl AscType for AscInputArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscInputArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinInputArray;
+}
+
+#[repr(C)]
+pub struct AscOutputArray(pub(crate) Array<AscPtr<AscOutput>>);
+
+impl AscType for AscOutputArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscOutputArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinOutputArray;
+}
+
+#[repr(C)]
+pub struct AscBytesArray(pub(crate) Array<AscPtr<Uint8Array>>);
+
+impl AscType for AscBytesArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscBytesArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinBytesArray;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscInputWithInfo {
+    pub input: AscPtr<AscInput>,
+    pub transaction: AscPtr<AscTransaction>,
+    pub block: AscPtr<AscBlock>,
+    pub index: u32,
+}
+
+impl AscIndexId for AscInputWithInfo {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinInputWithInfo;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscOutputWithInfo {
+    pub output: AscPtr<AscOutput>,
+    pub transaction: AscPtr<AscTransaction>,
+    pub block: AscPtr<AscBlock>,
+    pub index: u32,
+}
+
+impl AscIndexId for AscOutputWithInfo {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::BitcoinOutputWithInfo;
+}
@@ -0,0 +1,267 @@
+use graph::blockchain::Block;
+use graph::blockchain::MappingTriggerTrait;
+use graph::blockchain::TriggerData;
+use graph::derive::CheapClone;
+use graph::prelude::BlockNumber;
+use graph::runtime::asc_new;
+use graph::runtime::gas::GasCounter;
+use graph::runtime::AscHeap;
+use graph::runtime::AscPtr;
+use graph::runtime::HostExportError;
+use graph_runtime_wasm::module::ToAscPtr;
+use std::{cmp::Ordering, sync::Arc};
+
+use crate::adapter::TriggerFilter;
+use crate::codec;
+
+// Logging the block is too verbose, so this strips the block from the trigger for Debug.
+impl std::fmt::Debug for BitcoinTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        #[allow(unused)]
+        #[derive(Debug)]
+        pub enum MappingTriggerWithoutBlock<'a> {
+            Block,
+            Input { txid: &'a str, index: u32 },
+            Output { txid: &'a str, index: u32 },
+        }
+
+        let trigger_without_block = match self {
+            BitcoinTrigger::Block(_) => MappingTriggerWithoutBlock::Block,
+            BitcoinTrigger::Input(input) => MappingTriggerWithoutBlock::Input {
+                txid: &input.transaction.txid,
+                index: input.index,
+            },
+            BitcoinTrigger::Output(output) => MappingTriggerWithoutBlock::Output {
+                txid: &output.transaction.txid,
+                index: output.index,
+            },
+        };
+
+        write!(f, "{:?}", trigger_without_block)
+    }
+}
+
+impl ToAscPtr for BitcoinTrigger {
+    fn to_asc_ptr<H: AscHeap>(
+        self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<()>, HostExportError> {
+        Ok(match self {
+            BitcoinTrigger::Block(block) => asc_new(heap, block.as_ref(), gas)?.erase(),
+            BitcoinTrigger::Input(input) => asc_new(heap, input.as_ref(), gas)?.erase(),
+            BitcoinTrigger::Output(output) => asc_new(heap, output.as_ref(), gas)?.erase(),
+        })
+    }
+}
+
+#[derive(Clone, CheapClone)]
+pub enum BitcoinTrigger {
+    Block(Arc<codec::Block>),
+    Input(Arc<InputWithInfo>),
+    Output(Arc<OutputWithInfo>),
+}
+
+impl PartialEq for BitcoinTrigger {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Block(a_ptr), Self::Block(b_ptr)) => a_ptr == b_ptr,
+            (Self::Input(a), Self::Input(b)) => {
+                a.transaction.txid == b.transaction.txid && a.index == b.index
+            }
+            (Self::Output(a), Self::Output(b)) => {
+                a.transaction.txid == b.transaction.txid && a.index == b.index
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for BitcoinTrigger {}
+
+impl BitcoinTrigger {
+    pub fn block_number(&self) -> BlockNumber {
+        match self {
+            BitcoinTrigger::Block(block) => block.number(),
+            BitcoinTrigger::Input(input) => input.block.number(),
+            BitcoinTrigger::Output(output) => output.block.number(),
+        }
+    }
+
+    pub fn block_hash(&self) -> String {
+        match self {
+            BitcoinTrigger::Block(block) => block.hash.clone(),
+            BitcoinTrigger::Input(input) => input.block.hash.clone(),
+            BitcoinTrigger::Output(output) => output.block.hash.clone(),
+        }
+    }
+
+    fn transaction_index(&self) -> Option<u32> {
+        match self {
+            BitcoinTrigger::Block(_) => None,
+            BitcoinTrigger::Input(input) => Some(input.transaction_index),
+            BitcoinTrigger::Output(output) => Some(output.transaction_index),
+        }
+    }
+
+    fn error_context(&self) -> std::string::String {
+        match self {
+            BitcoinTrigger::Block(..) => {
+                format!("Block #{} ({})", self.block_number(), self.block_hash())
+            }
+            BitcoinTrigger::Input(input) => {
+                format!(
+                    "Input #{} of tx {}, block #{}({})",
+                    input.index,
+                    input.transaction.txid,
+                    self.block_number(),
+                    self.block_hash()
+                )
+            }
+            BitcoinTrigger::Output(output) => {
+                format!(
+                    "Output #{} of tx {}, block #{}({})",
+                    output.index,
+                    output.transaction.txid,
+                    self.block_number(),
+                    self.block_hash()
+                )
+            }
+        }
+    }
+}
+
+impl Ord for BitcoinTrigger {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            // Keep the order when comparing two block triggers
+            (Self::Block(..), Self::Block(..)) => Ordering::Equal,
+
+            // Block triggers always come last
+            (Self::Block(..), _) => Ordering::Greater,
+            (_, Self::Block(..)) => Ordering::Less,
+
+            // Inputs and outputs are ordered by transaction. Within a
+            // transaction, we keep the order in which they were created
+            // from the block, i.e., inputs followed by outputs
+            _ => self.transaction_index().cmp(&other.transaction_index()),
+        }
+    }
+}
+
+impl PartialOrd for BitcoinTrigger {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl TriggerData for BitcoinTrigger {
+    fn error_context(&self) -> String {
+        self.error_context()
+    }
+
+    fn address_match(&self) -> Option<&[u8]> {
+        // Inputs and outputs are matched against both addresses and script
+        // patterns in `DataSource::match_and_decode`
+        None
+    }
+}
+
+impl MappingTriggerTrait for BitcoinTrigger {
+    fn error_context(&self) -> String {
+        self.error_context()
+    }
+}
+
+/// A transaction input together with the transaction and block it
+/// was spent in
+pub struct InputWithInfo {
+    pub input: codec::Vin,
+    /// The index of the input in the transaction
+    pub index: u32,
+    pub transaction_index: u32,
+    pub transaction: Arc<codec::Transaction>,
+    pub block: Arc<codec::Block>,
+}
+
+/// A transaction output together with the transaction and block it
+/// was created in
+pub struct OutputWithInfo {
+    pub output: codec::Vout,
+    /// The index of the output in the transaction
+    pub index: u32,
+    pub transaction_index: u32,
+    pub transaction: Arc<codec::Transaction>,
+    pub block: Arc<codec::Block>,
+}
+
+/// Create the triggers for `block` that pass `filter`. For each
+/// transaction, the triggers for its inputs come before the triggers for
+/// its outputs, and the block trigger comes last
+pub(crate) fn triggers_in_block(
+    block: &Arc<codec::Block>,
+    filter: &TriggerFilter,
+) -> Vec<BitcoinTrigger> {
+    let mut triggers = Vec::new();
+
+    for (transaction_index, transaction) in block.tx.iter().enumerate() {
+        let spends_match = !filter.input_filter.is_empty()
+            && transaction
+                .vin
+                .iter()
+                .any(|vin| spends_matching(vin, filter));
+        let pays_match = !filter.output_filter.is_empty()
+            && transaction.vout.iter().any(|vout| {
+                vout.script_pub_key
+                    .as_ref()
+                    .map_or(false, |script| filter.output_filter.matches(script))
+            });
+        if !spends_match && !pays_match {
+            continue;
+        }
+        let shared_transaction = Arc::new(transaction.clone());
+
+        for (index, vin) in transaction.vin.iter().enumerate() {
+            if spends_matching(vin, filter) {
+                triggers.push(BitcoinTrigger::Input(Arc::new(InputWithInfo {
+                    input: vin.clone(),
+                    index: index as u32,
+                    transaction_index: transaction_index as u32,
+                    transaction: shared_transaction.clone(),
+                    block: block.clone(),
+                })));
+            }
+        }
+
+        for (index, vout) in transaction.vout.iter().enumerate() {
+            let matches = vout
+                .script_pub_key
+                .as_ref()
+                .map_or(false, |script| filter.output_filter.matches(script));
+            if matches {
+                triggers.push(BitcoinTrigger::Output(Arc::new(OutputWithInfo {
+                    output: vout.clone(),
+                    index: index as u32,
+                    transaction_index: transaction_index as u32,
+                    transaction: shared_transaction.clone(),
+                    block: block.clone(),
+                })));
+            }
+        }
+    }
+
+    if filter.block_filter.trigger_every_block {
+        triggers.push(BitcoinTrigger::Block(block.clone()));
+    }
+
+    triggers
+}
+
+/// Whether `vin` spends an output whose script passes the input filter.
+/// Coinbase inputs don't spend any output and never match
+fn spends_matching(vin: &codec::Vin, filter: &TriggerFilter) -> bool {
+    vin.prevout
+        .as_ref()
+        .and_then(|prevout| prevout.script_pub_key.as_ref())
+        .map_or(false, |script| filter.input_filter.matches(script))
+}
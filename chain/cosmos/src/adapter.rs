@@ -0,0 +1,273 @@
+use crate::{
+    data_source::{DataSource, EventOrigin},
+    Chain,
+};
+use graph::blockchain as bc;
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, Default)]
+pub struct TriggerFilter {
+    pub(crate) block_filter: CosmosBlockFilter,
+    pub(crate) event_type_filter: CosmosEventTypeFilter,
+    pub(crate) transaction_filter: CosmosTransactionFilter,
+    pub(crate) message_type_filter: CosmosMessageTypeFilter,
+}
+
+impl bc::TriggerFilter<Chain> for TriggerFilter {
+    fn extend<'a>(&mut self, data_sources: impl Iterator<Item = &'a DataSource> + Clone) {
+        let TriggerFilter {
+            block_filter,
+            event_type_filter,
+            transaction_filter,
+            message_type_filter,
+        } = self;
+
+        block_filter.extend(CosmosBlockFilter::from_data_sources(data_sources.clone()));
+        event_type_filter.extend(CosmosEventTypeFilter::from_data_sources(
+            data_sources.clone(),
+        ));
+        transaction_filter.extend(CosmosTransactionFilter::from_data_sources(
+            data_sources.clone(),
+        ));
+        message_type_filter.extend(CosmosMessageTypeFilter::from_data_sources(data_sources));
+    }
+
+    fn node_capabilities(&self) -> bc::EmptyNodeCapabilities<Chain> {
+        bc::EmptyNodeCapabilities::default()
+    }
+
+    fn extend_with_template(
+        &mut self,
+        _data_source: impl Iterator<Item = <Chain as bc::Blockchain>::DataSourceTemplate>,
+    ) {
+    }
+
+    fn to_firehose_filter(self) -> Vec<prost_types::Any> {
+        vec![]
+    }
+}
+
+/// Matches events by their type and where in the block they were emitted.
+/// An entry without an origin matches events of that type from anywhere
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CosmosEventTypeFilter {
+    event_types: HashSet<(String, Option<EventOrigin>)>,
+}
+
+impl CosmosEventTypeFilter {
+    pub fn matches(&self, event_type: &str, origin: EventOrigin) -> bool {
+        // Avoid allocating a key in the common case of an empty filter
+        if self.event_types.is_empty() {
+            return false;
+        }
+
+        let event_type = event_type.to_string();
+        self.event_types.contains(&(event_type.clone(), None))
+            || self.event_types.contains(&(event_type, Some(origin)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.event_types.is_empty()
+    }
+
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            event_types: iter
+                .into_iter()
+                .flat_map(|data_source| data_source.mapping.event_handlers.iter())
+                .map(|handler| (handler.event.clone(), handler.origin))
+                .collect(),
+        }
+    }
+
+    pub fn extend(&mut self, other: CosmosEventTypeFilter) {
+        self.event_types.extend(other.event_types);
+    }
+}
+
+/// Matches messages by their protobuf type URL, e.g.,
+/// `/cosmos.bank.v1beta1.MsgSend`
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CosmosMessageTypeFilter {
+    message_types: HashSet<String>,
+}
+
+impl CosmosMessageTypeFilter {
+    pub fn matches(&self, type_url: &str) -> bool {
+        self.message_types.contains(type_url)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.message_types.is_empty()
+    }
+
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            message_types: iter
+                .into_iter()
+                .flat_map(|data_source| data_source.mapping.message_handlers.iter())
+                .map(|handler| handler.message.clone())
+                .collect(),
+        }
+    }
+
+    pub fn extend(&mut self, other: CosmosMessageTypeFilter) {
+        self.message_types.extend(other.message_types);
+    }
+}
+
+/// CosmosTransactionFilter will match every transaction regardless of its
+/// contents
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CosmosTransactionFilter {
+    pub trigger_every_transaction: bool,
+}
+
+impl CosmosTransactionFilter {
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            trigger_every_transaction: iter
+                .into_iter()
+                .any(|data_source| !data_source.mapping.transaction_handlers.is_empty()),
+        }
+    }
+
+    pub fn extend(&mut self, other: CosmosTransactionFilter) {
+        self.trigger_every_transaction =
+            self.trigger_every_transaction || other.trigger_every_transaction;
+    }
+}
+
+/// CosmosBlockFilter will match every block regardless of source being set.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CosmosBlockFilter {
+    pub trigger_every_block: bool,
+}
+
+impl CosmosBlockFilter {
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            trigger_every_block: iter
+                .into_iter()
+                .any(|data_source| !data_source.mapping.block_handlers.is_empty()),
+        }
+    }
+
+    pub fn extend(&mut self, other: CosmosBlockFilter) {
+        self.trigger_every_block = self.trigger_every_block || other.trigger_every_block;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use graph::blockchain::TriggerFilter as _;
+    use graph::{prelude::Link, semver::Version};
+
+    use crate::data_source::{
+        DataSource, EventOrigin, Mapping, MappingBlockHandler, MappingEventHandler,
+        MappingMessageHandler, MappingTransactionHandler, Source,
+    };
+
+    use super::TriggerFilter;
+
+    const MSG_SEND: &str = "/cosmos.bank.v1beta1.MsgSend";
+    const MSG_DELEGATE: &str = "/cosmos.staking.v1beta1.MsgDelegate";
+
+    #[test]
+    fn event_filter_respects_origin() {
+        let dss = vec![
+            new_datasource(false, false, vec![("transfer", None)], vec![]),
+            new_datasource(
+                false,
+                false,
+                vec![("rewards", Some(EventOrigin::BeginBlock))],
+                vec![],
+            ),
+        ];
+
+        let mut filter = TriggerFilter::default();
+        filter.extend(dss.iter());
+
+        assert!(!filter.block_filter.trigger_every_block);
+        assert!(!filter.transaction_filter.trigger_every_transaction);
+
+        let filter = filter.event_type_filter;
+        assert!(filter.matches("transfer", EventOrigin::BeginBlock));
+        assert!(filter.matches("transfer", EventOrigin::DeliverTx));
+        assert!(filter.matches("transfer", EventOrigin::EndBlock));
+        assert!(filter.matches("rewards", EventOrigin::BeginBlock));
+        assert!(!filter.matches("rewards", EventOrigin::DeliverTx));
+        assert!(!filter.matches("slash", EventOrigin::BeginBlock));
+    }
+
+    #[test]
+    fn message_filter_extend() {
+        let mut filter = TriggerFilter::default();
+        filter.extend(vec![new_datasource(true, false, vec![], vec![MSG_SEND])].iter());
+        filter.extend(vec![new_datasource(false, true, vec![], vec![MSG_DELEGATE])].iter());
+
+        assert!(filter.block_filter.trigger_every_block);
+        assert!(filter.transaction_filter.trigger_every_transaction);
+        assert!(filter.message_type_filter.matches(MSG_SEND));
+        assert!(filter.message_type_filter.matches(MSG_DELEGATE));
+        assert!(!filter
+            .message_type_filter
+            .matches("/cosmos.gov.v1beta1.MsgVote"));
+        assert!(filter.event_type_filter.is_empty());
+    }
+
+    fn new_datasource(
+        block_handler: bool,
+        transaction_handler: bool,
+        events: Vec<(&str, Option<EventOrigin>)>,
+        messages: Vec<&str>,
+    ) -> DataSource {
+        DataSource {
+            kind: "cosmos".into(),
+            network: None,
+            name: "".into(),
+            source: Source {
+                start_block: 10,
+                end_block: None,
+            },
+            mapping: Mapping {
+                api_version: Version::new(0, 0, 7),
+                language: "".into(),
+                entities: vec![],
+                block_handlers: block_handler
+                    .then(|| MappingBlockHandler {
+                        handler: "handleBlock".into(),
+                    })
+                    .into_iter()
+                    .collect(),
+                event_handlers: events
+                    .into_iter()
+                    .map(|(event, origin)| MappingEventHandler {
+                        event: event.into(),
+                        origin,
+                        handler: "handleEvent".into(),
+                    })
+                    .collect(),
+                transaction_handlers: transaction_handler
+                    .then(|| MappingTransactionHandler {
+                        handler: "handleTransaction".into(),
+                    })
+                    .into_iter()
+                    .collect(),
+                message_handlers: messages
+                    .into_iter()
+                    .map(|message| MappingMessageHandler {
+                        message: message.into(),
+                        handler: "handleMessage".into(),
+                    })
+                    .collect(),
+                runtime: Arc::new(vec![]),
+                link: Link { link: "".into() },
+            },
+            context: Arc::new(None),
+            creation_block: None,
+        }
+    }
+}
@@ -0,0 +1,149 @@
+#[rustfmt::skip]
+#[path = "protobuf/sf.cosmos.r#type.v1.rs"]
+mod pbcodec;
+
+use graph::{
+    blockchain::Block as BlockchainBlock,
+    blockchain::{BlockPtr, BlockTime},
+    prelude::BlockNumber,
+};
+use prost::Message;
+
+pub use pbcodec::*;
+
+impl Block {
+    pub fn header(&self) -> &Header {
+        self.header
+            .as_ref()
+            .expect("block header should always be present")
+    }
+
+    pub fn begin_block_events(&self) -> &[Event] {
+        self.result_begin_block
+            .as_ref()
+            .map(|result| result.events.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn end_block_events(&self) -> &[Event] {
+        self.result_end_block
+            .as_ref()
+            .map(|result| result.events.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+impl BlockchainBlock for Block {
+    fn number(&self) -> i32 {
+        BlockNumber::try_from(self.header().height).unwrap()
+    }
+
+    fn ptr(&self) -> BlockPtr {
+        BlockPtr {
+            hash: self.header().hash.clone().into(),
+            number: self.number(),
+        }
+    }
+
+    fn parent_ptr(&self) -> Option<BlockPtr> {
+        // Chains can start at any height, the first block of a chain has no
+        // last block id
+        let last_block_id = self
+            .header()
+            .last_block_id
+            .as_ref()
+            .filter(|id| !id.hash.is_empty())?;
+
+        Some(BlockPtr {
+            hash: last_block_id.hash.clone().into(),
+            number: self.number().saturating_sub(1),
+        })
+    }
+
+    fn timestamp(&self) -> BlockTime {
+        let time = self.header().time.clone().unwrap_or_default();
+        BlockTime::since_epoch(time.seconds, time.nanos as u32)
+    }
+}
+
+impl TxResult {
+    /// Transactions with a non-zero result code failed and had no effect
+    /// other than paying fees
+    pub fn succeeded(&self) -> bool {
+        self.result.as_ref().map_or(true, |result| result.code == 0)
+    }
+
+    pub fn events(&self) -> &[Event] {
+        self.result
+            .as_ref()
+            .map(|result| result.events.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The messages of the transaction. Transactions that are not Cosmos
+    /// SDK transactions can't be decoded and have no messages
+    pub fn messages(&self) -> Vec<prost_types::Any> {
+        Tx::decode(self.tx.as_slice())
+            .ok()
+            .and_then(|tx| tx.body)
+            .map(|body| body.messages)
+            .unwrap_or_default()
+    }
+}
+
+/// The transaction an event or message belongs to
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransactionContext {
+    pub hash: Vec<u8>,
+    pub index: u32,
+    pub code: u32,
+    pub gas_wanted: i64,
+    pub gas_used: i64,
+}
+
+impl From<&TxResult> for TransactionContext {
+    fn from(tx: &TxResult) -> Self {
+        let result = tx.result.clone().unwrap_or_default();
+        TransactionContext {
+            hash: tx.hash.clone(),
+            index: tx.index,
+            code: result.code,
+            gas_wanted: result.gas_wanted,
+            gas_used: result.gas_used,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+
+    use super::{Tx, TxBody, TxResult};
+
+    #[test]
+    fn decode_transaction_messages() {
+        let message = prost_types::Any {
+            type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+            value: vec![1, 2, 3],
+        };
+        let tx = Tx {
+            body: Some(TxBody {
+                messages: vec![message.clone()],
+                ..Default::default()
+            }),
+            signatures: vec![],
+        };
+        let result = TxResult {
+            tx: tx.encode_to_vec(),
+            ..Default::default()
+        };
+
+        assert_eq!(vec![message], result.messages());
+
+        let invalid = TxResult {
+            tx: vec![0xff, 0xff],
+            ..Default::default()
+        };
+        assert!(invalid.messages().is_empty());
+    }
+}
@@ -0,0 +1,489 @@
+use graph::anyhow::Context;
+use graph::blockchain::{Block, TriggerWithHandler};
+use graph::components::store::StoredDynamicDataSource;
+use graph::components::subgraph::InstanceDSTemplateInfo;
+use graph::data::subgraph::DataSourceContext;
+use graph::{
+    anyhow::{anyhow, Error},
+    blockchain::{self, Blockchain},
+    prelude::{async_trait, BlockNumber, CheapClone, Deserialize, Link, LinkResolver, Logger},
+    semver,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::chain::Chain;
+use crate::trigger::CosmosTrigger;
+
+pub const COSMOS_KIND: &str = "cosmos";
+const BLOCK_HANDLER_KIND: &str = "block";
+const EVENT_HANDLER_KIND: &str = "event";
+const TRANSACTION_HANDLER_KIND: &str = "transaction";
+const MESSAGE_HANDLER_KIND: &str = "message";
+
+/// Runtime representation of a data source.
+#[derive(Clone, Debug)]
+pub struct DataSource {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub(crate) source: Source,
+    pub mapping: Mapping,
+    pub context: Arc<Option<DataSourceContext>>,
+    pub creation_block: Option<BlockNumber>,
+}
+
+impl blockchain::DataSource<Chain> for DataSource {
+    fn from_template_info(
+        _info: InstanceDSTemplateInfo,
+        _template: &graph::data_source::DataSourceTemplate<Chain>,
+    ) -> Result<Self, Error> {
+        Err(anyhow!("Cosmos subgraphs do not support templates"))
+    }
+
+    fn address(&self) -> Option<&[u8]> {
+        None
+    }
+
+    fn start_block(&self) -> BlockNumber {
+        self.source.start_block
+    }
+
+    fn handler_kinds(&self) -> HashSet<&str> {
+        let mut kinds = HashSet::new();
+
+        if !self.mapping.block_handlers.is_empty() {
+            kinds.insert(BLOCK_HANDLER_KIND);
+        }
+
+        if !self.mapping.event_handlers.is_empty() {
+            kinds.insert(EVENT_HANDLER_KIND);
+        }
+
+        if !self.mapping.transaction_handlers.is_empty() {
+            kinds.insert(TRANSACTION_HANDLER_KIND);
+        }
+
+        if !self.mapping.message_handlers.is_empty() {
+            kinds.insert(MESSAGE_HANDLER_KIND);
+        }
+
+        kinds
+    }
+
+    fn end_block(&self) -> Option<BlockNumber> {
+        self.source.end_block
+    }
+
+    fn match_and_decode(
+        &self,
+        trigger: &<Chain as Blockchain>::TriggerData,
+        block: &Arc<<Chain as Blockchain>::Block>,
+        _logger: &Logger,
+    ) -> Result<Option<TriggerWithHandler<Chain>>, Error> {
+        if self.source.start_block > block.number() {
+            return Ok(None);
+        }
+
+        let handler = match trigger {
+            CosmosTrigger::Block(_) => match self.mapping.block_handlers.first() {
+                Some(handler) => &handler.handler,
+                None => return Ok(None),
+            },
+            CosmosTrigger::Event(event) => {
+                match self.handler_for_event(&event.event.event_type, event.origin) {
+                    Some(handler) => &handler.handler,
+                    None => return Ok(None),
+                }
+            }
+            CosmosTrigger::Transaction(_) => match self.mapping.transaction_handlers.first() {
+                Some(handler) => &handler.handler,
+                None => return Ok(None),
+            },
+            CosmosTrigger::Message(message) => {
+                match self.handler_for_message(&message.message.type_url) {
+                    Some(handler) => &handler.handler,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        Ok(Some(TriggerWithHandler::<Chain>::new(
+            trigger.cheap_clone(),
+            handler.clone(),
+            block.ptr(),
+            block.timestamp(),
+        )))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn network(&self) -> Option<&str> {
+        self.network.as_deref()
+    }
+
+    fn context(&self) -> Arc<Option<DataSourceContext>> {
+        self.context.cheap_clone()
+    }
+
+    fn creation_block(&self) -> Option<BlockNumber> {
+        self.creation_block
+    }
+
+    fn is_duplicate_of(&self, other: &Self) -> bool {
+        let DataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context,
+
+            // The creation block is ignored for detection duplicate data sources.
+            creation_block: _,
+        } = self;
+
+        kind == &other.kind
+            && network == &other.network
+            && name == &other.name
+            && source == &other.source
+            && mapping.block_handlers == other.mapping.block_handlers
+            && mapping.event_handlers == other.mapping.event_handlers
+            && mapping.transaction_handlers == other.mapping.transaction_handlers
+            && mapping.message_handlers == other.mapping.message_handlers
+            && context == &other.context
+    }
+
+    fn as_stored_dynamic_data_source(&self) -> StoredDynamicDataSource {
+        unreachable!("Cosmos subgraphs do not support dynamic data sources")
+    }
+
+    fn from_stored_dynamic_data_source(
+        _template: &DataSourceTemplate,
+        _stored: StoredDynamicDataSource,
+    ) -> Result<Self, Error> {
+        Err(anyhow!(
+            "Cosmos subgraphs do not support dynamic data sources"
+        ))
+    }
+
+    fn validate(&self, _: &semver::Version) -> Vec<Error> {
+        let mut errors = Vec::new();
+
+        if self.kind != COSMOS_KIND {
+            errors.push(anyhow!(
+                "data source has invalid `kind`, expected {} but found {}",
+                COSMOS_KIND,
+                self.kind
+            ))
+        }
+
+        // Validate that there is at most one of the block and transaction
+        // handlers, and that each event and message has at most one handler
+        if self.mapping.block_handlers.len() > 1 {
+            errors.push(anyhow!("data source has duplicated block handlers"));
+        }
+        if self.mapping.transaction_handlers.len() > 1 {
+            errors.push(anyhow!("data source has duplicated transaction handlers"));
+        }
+
+        let mut events = HashSet::new();
+        for handler in &self.mapping.event_handlers {
+            if !events.insert((&handler.event, handler.origin)) {
+                errors.push(anyhow!(
+                    "data source has more than one event handler for event {}",
+                    handler.event
+                ));
+            }
+        }
+
+        let mut messages = HashSet::new();
+        for handler in &self.mapping.message_handlers {
+            if !messages.insert(&handler.message) {
+                errors.push(anyhow!(
+                    "data source has more than one message handler for message {}",
+                    handler.message
+                ));
+            }
+        }
+
+        errors
+    }
+
+    fn api_version(&self) -> semver::Version {
+        self.mapping.api_version.clone()
+    }
+
+    fn runtime(&self) -> Option<Arc<Vec<u8>>> {
+        Some(self.mapping.runtime.cheap_clone())
+    }
+}
+
+impl DataSource {
+    fn from_manifest(
+        kind: String,
+        network: Option<String>,
+        name: String,
+        source: Source,
+        mapping: Mapping,
+        context: Option<DataSourceContext>,
+    ) -> Result<Self, Error> {
+        // Data sources in the manifest are created "before genesis" so they have no creation block.
+        let creation_block = None;
+
+        Ok(DataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context: Arc::new(context),
+            creation_block,
+        })
+    }
+
+    /// The handler for events of type `event_type` that were emitted at
+    /// `origin`. Handlers without an origin match events from anywhere
+    fn handler_for_event(
+        &self,
+        event_type: &str,
+        origin: EventOrigin,
+    ) -> Option<&MappingEventHandler> {
+        self.mapping.event_handlers.iter().find(|handler| {
+            handler.event == event_type && handler.origin.map_or(true, |o| o == origin)
+        })
+    }
+
+    fn handler_for_message(&self, type_url: &str) -> Option<&MappingMessageHandler> {
+        self.mapping
+            .message_handlers
+            .iter()
+            .find(|handler| handler.message == type_url)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct UnresolvedDataSource {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub(crate) source: Source,
+    pub mapping: UnresolvedMapping,
+    pub context: Option<DataSourceContext>,
+}
+
+#[async_trait]
+impl blockchain::UnresolvedDataSource<Chain> for UnresolvedDataSource {
+    async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+        _manifest_idx: u32,
+    ) -> Result<DataSource, Error> {
+        let UnresolvedDataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context,
+        } = self;
+
+        let mapping = mapping.resolve(resolver, logger).await.with_context(|| {
+            format!(
+                "failed to resolve data source {} with start_block {}",
+                name, source.start_block
+            )
+        })?;
+
+        DataSource::from_manifest(kind, network, name, source, mapping, context)
+    }
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+pub struct BaseDataSourceTemplate<M> {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub mapping: M,
+}
+
+pub type UnresolvedDataSourceTemplate = BaseDataSourceTemplate<UnresolvedMapping>;
+pub type DataSourceTemplate = BaseDataSourceTemplate<Mapping>;
+
+#[async_trait]
+impl blockchain::UnresolvedDataSourceTemplate<Chain> for UnresolvedDataSourceTemplate {
+    async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+        _manifest_idx: u32,
+    ) -> Result<DataSourceTemplate, Error> {
+        let UnresolvedDataSourceTemplate {
+            kind,
+            network,
+            name,
+            mapping,
+        } = self;
+
+        let mapping = mapping
+            .resolve(resolver, logger)
+            .await
+            .with_context(|| format!("failed to resolve data source template {}", name))?;
+
+        Ok(DataSourceTemplate {
+            kind,
+            network,
+            name,
+            mapping,
+        })
+    }
+}
+
+impl blockchain::DataSourceTemplate<Chain> for DataSourceTemplate {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn api_version(&self) -> semver::Version {
+        self.mapping.api_version.clone()
+    }
+
+    fn runtime(&self) -> Option<Arc<Vec<u8>>> {
+        Some(self.mapping.runtime.cheap_clone())
+    }
+
+    fn manifest_idx(&self) -> u32 {
+        unreachable!("cosmos does not support dynamic data sources")
+    }
+
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedMapping {
+    pub api_version: String,
+    pub language: String,
+    pub entities: Vec<String>,
+    #[serde(default)]
+    pub block_handlers: Vec<MappingBlockHandler>,
+    #[serde(default)]
+    pub event_handlers: Vec<MappingEventHandler>,
+    #[serde(default)]
+    pub transaction_handlers: Vec<MappingTransactionHandler>,
+    #[serde(default)]
+    pub message_handlers: Vec<MappingMessageHandler>,
+    pub file: Link,
+}
+
+impl UnresolvedMapping {
+    pub async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+    ) -> Result<Mapping, Error> {
+        let UnresolvedMapping {
+            api_version,
+            language,
+            entities,
+            block_handlers,
+            event_handlers,
+            transaction_handlers,
+            message_handlers,
+            file: link,
+        } = self;
+
+        let api_version = semver::Version::parse(&api_version)?;
+
+        let module_bytes = resolver
+            .cat(logger, &link)
+            .await
+            .with_context(|| format!("failed to resolve mapping {}", link.link))?;
+
+        Ok(Mapping {
+            api_version,
+            language,
+            entities,
+            block_handlers,
+            event_handlers,
+            transaction_handlers,
+            message_handlers,
+            runtime: Arc::new(module_bytes),
+            link,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Mapping {
+    pub api_version: semver::Version,
+    pub language: String,
+    pub entities: Vec<String>,
+    pub block_handlers: Vec<MappingBlockHandler>,
+    pub event_handlers: Vec<MappingEventHandler>,
+    pub transaction_handlers: Vec<MappingTransactionHandler>,
+    pub message_handlers: Vec<MappingMessageHandler>,
+    pub runtime: Arc<Vec<u8>>,
+    pub link: Link,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingBlockHandler {
+    pub handler: String,
+}
+
+/// Where in the block an event was emitted
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub enum EventOrigin {
+    BeginBlock,
+    DeliverTx,
+    EndBlock,
+}
+
+impl EventOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventOrigin::BeginBlock => "BeginBlock",
+            EventOrigin::DeliverTx => "DeliverTx",
+            EventOrigin::EndBlock => "EndBlock",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingEventHandler {
+    /// The type of the event, e.g., `transfer`
+    pub event: String,
+    /// Only match events emitted at this origin, or anywhere if not set
+    pub origin: Option<EventOrigin>,
+    pub handler: String,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingTransactionHandler {
+    pub handler: String,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingMessageHandler {
+    /// The type URL of the message, e.g., `/cosmos.bank.v1beta1.MsgSend`
+    pub message: String,
+    pub handler: String,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Source {
+    #[serde(default)]
+    pub(crate) start_block: BlockNumber,
+    pub(crate) end_block: Option<BlockNumber>,
+}
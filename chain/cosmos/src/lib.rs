@@ -0,0 +1,9 @@
+mod adapter;
+mod chain;
+mod codec;
+mod data_source;
+mod runtime;
+mod trigger;
+
+pub use crate::chain::Chain;
+pub use codec::Block;
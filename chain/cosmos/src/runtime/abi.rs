@@ -0,0 +1,237 @@
+use crate::codec;
+use crate::trigger::{EventWithInfo, MessageWithInfo, TransactionWithInfo};
+use graph::runtime::gas::GasCounter;
+use graph::runtime::{asc_new, asc_new_or_null, AscHeap, AscPtr, HostExportError, ToAscObj};
+use graph_runtime_wasm::asc_abi::class::Array;
+
+pub(crate) use super::generated::*;
+
+impl ToAscObj<AscBlock> for codec::Block {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscBlock, HostExportError> {
+        Ok(AscBlock {
+            header: asc_new(heap, self.header(), gas)?,
+            transactions: asc_new(heap, self.transactions.as_slice(), gas)?,
+            begin_block_events: asc_new(heap, self.begin_block_events(), gas)?,
+            end_block_events: asc_new(heap, self.end_block_events(), gas)?,
+        })
+    }
+}
+
+impl ToAscObj<AscHeader> for codec::Header {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscHeader, HostExportError> {
+        let time = self.time.clone().unwrap_or_default();
+        let last_block_hash = self
+            .last_block_id
+            .as_ref()
+            .map(|id| id.hash.as_slice())
+            .unwrap_or_default();
+
+        Ok(AscHeader {
+            height: self.height,
+            time_seconds: time.seconds,
+            chain_id: asc_new(heap, &self.chain_id, gas)?,
+            hash: asc_new(heap, self.hash.as_slice(), gas)?,
+            last_block_hash: asc_new(heap, last_block_hash, gas)?,
+            last_commit_hash: asc_new(heap, self.last_commit_hash.as_slice(), gas)?,
+            data_hash: asc_new(heap, self.data_hash.as_slice(), gas)?,
+            validators_hash: asc_new(heap, self.validators_hash.as_slice(), gas)?,
+            next_validators_hash: asc_new(heap, self.next_validators_hash.as_slice(), gas)?,
+            consensus_hash: asc_new(heap, self.consensus_hash.as_slice(), gas)?,
+            app_hash: asc_new(heap, self.app_hash.as_slice(), gas)?,
+            last_results_hash: asc_new(heap, self.last_results_hash.as_slice(), gas)?,
+            evidence_hash: asc_new(heap, self.evidence_hash.as_slice(), gas)?,
+            proposer_address: asc_new(heap, self.proposer_address.as_slice(), gas)?,
+            time_nanos: time.nanos,
+            _padding: 0,
+        })
+    }
+}
+
+impl ToAscObj<AscEvent> for codec::Event {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEvent, HostExportError> {
+        Ok(AscEvent {
+            event_type: asc_new(heap, &self.event_type, gas)?,
+            attributes: asc_new(heap, self.attributes.as_slice(), gas)?,
+        })
+    }
+}
+
+impl ToAscObj<AscEventArray> for [codec::Event] {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEventArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscEventArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscEventAttribute> for codec::EventAttribute {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEventAttribute, HostExportError> {
+        Ok(AscEventAttribute {
+            key: asc_new(heap, &self.key, gas)?,
+            value: asc_new(heap, &self.value, gas)?,
+            index: self.index,
+        })
+    }
+}
+
+impl ToAscObj<AscEventAttributeArray> for [codec::EventAttribute] {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEventAttributeArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscEventAttributeArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscTxResult> for codec::TxResult {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscTxResult, HostExportError> {
+        Ok(AscTxResult {
+            height: self.height,
+            hash: asc_new(heap, self.hash.as_slice(), gas)?,
+            tx: asc_new(heap, self.tx.as_slice(), gas)?,
+            result: asc_new_or_null(heap, &self.result, gas)?,
+            index: self.index,
+        })
+    }
+}
+
+impl ToAscObj<AscTxResultArray> for [codec::TxResult] {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscTxResultArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscTxResultArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscResponseDeliverTx> for codec::ResponseDeliverTx {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscResponseDeliverTx, HostExportError> {
+        Ok(AscResponseDeliverTx {
+            gas_wanted: self.gas_wanted,
+            gas_used: self.gas_used,
+            data: asc_new(heap, self.data.as_slice(), gas)?,
+            log: asc_new(heap, &self.log, gas)?,
+            info: asc_new(heap, &self.info, gas)?,
+            events: asc_new(heap, self.events.as_slice(), gas)?,
+            codespace: asc_new(heap, &self.codespace, gas)?,
+            code: self.code,
+        })
+    }
+}
+
+impl ToAscObj<AscTransactionContext> for codec::TransactionContext {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscTransactionContext, HostExportError> {
+        Ok(AscTransactionContext {
+            gas_wanted: self.gas_wanted,
+            gas_used: self.gas_used,
+            hash: asc_new(heap, self.hash.as_slice(), gas)?,
+            index: self.index,
+            code: self.code,
+            _padding: 0,
+        })
+    }
+}
+
+impl ToAscObj<AscAny> for prost_types::Any {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscAny, HostExportError> {
+        Ok(AscAny {
+            type_url: asc_new(heap, &self.type_url, gas)?,
+            value: asc_new(heap, self.value.as_slice(), gas)?,
+        })
+    }
+}
+
+impl ToAscObj<AscEventData> for EventWithInfo {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEventData, HostExportError> {
+        Ok(AscEventData {
+            event: asc_new(heap, &self.event, gas)?,
+            block: asc_new(heap, self.block.header(), gas)?,
+            tx: match &self.tx {
+                Some(tx) => asc_new(heap, tx.as_ref(), gas)?,
+                None => AscPtr::null(),
+            },
+            origin: asc_new(heap, self.origin.as_str(), gas)?,
+            index: self.index,
+        })
+    }
+}
+
+impl ToAscObj<AscTransactionData> for TransactionWithInfo {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscTransactionData, HostExportError> {
+        Ok(AscTransactionData {
+            tx: asc_new(heap, &self.tx, gas)?,
+            block: asc_new(heap, self.block.header(), gas)?,
+        })
+    }
+}
+
+impl ToAscObj<AscMessageData> for MessageWithInfo {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscMessageData, HostExportError> {
+        Ok(AscMessageData {
+            message: asc_new(heap, &self.message, gas)?,
+            block: asc_new(heap, self.block.header(), gas)?,
+            tx: asc_new(heap, self.tx.as_ref(), gas)?,
+            index: self.index,
+        })
+    }
+}
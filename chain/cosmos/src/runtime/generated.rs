@@ -0,0 +1,229 @@
+use graph::runtime::{AscIndexId, AscPtr, AscType, DeterministicHostError, IndexForAscTypeId};
+use graph::semver::Version;
+use graph_runtime_derive::AscType;
+use graph_runtime_wasm::asc_abi::class::{Array, AscString, Uint8Array};
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscBlock {
+    pub header: AscPtr<AscHeader>,
+    pub transactions: AscPtr<AscTxResultArray>,
+    pub begin_block_events: AscPtr<AscEventArray>,
+    pub end_block_events: AscPtr<AscEventArray>,
+}
+
+impl AscIndexId for AscBlock {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosBlock;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscHeader {
+    pub height: u64,
+    pub time_seconds: i64,
+    pub chain_id: AscPtr<AscString>,
+    pub hash: AscPtr<Uint8Array>,
+    pub last_block_hash: AscPtr<Uint8Array>,
+    pub last_commit_hash: AscPtr<Uint8Array>,
+    pub data_hash: AscPtr<Uint8Array>,
+    pub validators_hash: AscPtr<Uint8Array>,
+    pub next_validators_hash: AscPtr<Uint8Array>,
+    pub consensus_hash: AscPtr<Uint8Array>,
+    pub app_hash: AscPtr<Uint8Array>,
+    pub last_results_hash: AscPtr<Uint8Array>,
+    pub evidence_hash: AscPtr<Uint8Array>,
+    pub proposer_address: AscPtr<Uint8Array>,
+    pub time_nanos: i32,
+
+    // Rust pads the struct to a multiple of 8 bytes because of the 64-bit
+    // fields, AssemblyScript does not. So we add the padding ourself.
+    pub(crate) _padding: u32,
+}
+
+impl AscIndexId for AscHeader {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosHeader;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscEvent {
+    pub event_type: AscPtr<AscString>,
+    pub attributes: AscPtr<AscEventAttributeArray>,
+}
+
+impl AscIndexId for AscEvent {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosEvent;
+}
+
+#[repr(C)]
+pub struct AscEventArray(pub(crate) Array<AscPtr<AscEvent>>);
+
+impl AscType for AscEventArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscEventArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosEventArray;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscEventAttribute {
+    pub key: AscPtr<AscString>,
+    pub value: AscPtr<AscString>,
+    pub index: bool,
+}
+
+impl AscIndexId for AscEventAttribute {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosEventAttribute;
+}
+
+#[repr(C)]
+pub struct AscEventAttributeArray(pub(crate) Array<AscPtr<AscEventAttribute>>);
+
+impl AscType for AscEventAttributeArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscEventAttributeArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosEventAttributeArray;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscTxResult {
+    pub height: u64,
+    pub hash: AscPtr<Uint8Array>,
+    /// The raw, protobuf encoded transaction
+    pub tx: AscPtr<Uint8Array>,
+    pub result: AscPtr<AscResponseDeliverTx>,
+    pub index: u32,
+}
+
+impl AscIndexId for AscTxResult {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosTxResult;
+}
+
+#[repr(C)]
+pub struct AscTxResultArray(pub(crate) Array<AscPtr<AscTxResult>>);
+
+impl AscType for AscTxResultArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscTxResultArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosTxResultArray;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscResponseDeliverTx {
+    pub gas_wanted: i64,
+    pub gas_used: i64,
+    pub data: AscPtr<Uint8Array>,
+    pub log: AscPtr<AscString>,
+    pub info: AscPtr<AscString>,
+    pub events: AscPtr<AscEventArray>,
+    pub codespace: AscPtr<AscString>,
+    pub code: u32,
+}
+
+impl AscIndexId for AscResponseDeliverTx {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosResponseDeliverTx;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscTransactionContext {
+    pub gas_wanted: i64,
+    pub gas_used: i64,
+    pub hash: AscPtr<Uint8Array>,
+    pub index: u32,
+    pub code: u32,
+
+    // See `AscHeader`
+    pub(crate) _padding: u32,
+}
+
+impl AscIndexId for AscTransactionContext {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosTransactionContext;
+}
+
+/// A protobuf `Any`, i.e., a message together with its type URL
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscAny {
+    pub type_url: AscPtr<AscString>,
+    pub value: AscPtr<Uint8Array>,
+}
+
+impl AscIndexId for AscAny {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosAny;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscEventData {
+    pub event: AscPtr<AscEvent>,
+    pub block: AscPtr<AscHeader>,
+    /// Null for events that were not emitted by a transaction
+    pub tx: AscPtr<AscTransactionContext>,
+    /// One of `BeginBlock`, `DeliverTx` or `EndBlock`
+    pub origin: AscPtr<AscString>,
+    pub index: u32,
+}
+
+impl AscIndexId for AscEventData {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosEventData;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscTransactionData {
+    pub tx: AscPtr<AscTxResult>,
+    pub block: AscPtr<AscHeader>,
+}
+
+impl AscIndexId for AscTransactionData {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosTransactionData;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscMessageData {
+    pub message: AscPtr<AscAny>,
+    pub block: AscPtr<AscHeader>,
+    pub tx: AscPtr<AscTransactionContext>,
+    pub index: u32,
+}
+
+impl AscIndexId for AscMessageData {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::CosmosMessageData;
+}
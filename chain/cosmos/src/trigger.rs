@@ -0,0 +1,322 @@
+use graph::blockchain::Block;
+use graph::blockchain::MappingTriggerTrait;
+use graph::blockchain::TriggerData;
+use graph::derive::CheapClone;
+use graph::prelude::{hex, BlockNumber};
+use graph::runtime::asc_new;
+use graph::runtime::gas::GasCounter;
+use graph::runtime::AscHeap;
+use graph::runtime::AscPtr;
+use graph::runtime::HostExportError;
+use graph_runtime_wasm::module::ToAscPtr;
+use std::{cmp::Ordering, sync::Arc};
+
+use crate::adapter::TriggerFilter;
+use crate::codec;
+use crate::data_source::EventOrigin;
+
+// Logging the block is too verbose, so this strips the block from the trigger for Debug.
+impl std::fmt::Debug for CosmosTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        #[allow(unused)]
+        #[derive(Debug)]
+        pub enum MappingTriggerWithoutBlock<'a> {
+            Block,
+            Event {
+                event_type: &'a str,
+                origin: EventOrigin,
+                index: u32,
+            },
+            Transaction {
+                hash: String,
+            },
+            Message {
+                type_url: &'a str,
+                hash: String,
+                index: u32,
+            },
+        }
+
+        let trigger_without_block = match self {
+            CosmosTrigger::Block(_) => MappingTriggerWithoutBlock::Block,
+            CosmosTrigger::Event(event) => MappingTriggerWithoutBlock::Event {
+                event_type: &event.event.event_type,
+                origin: event.origin,
+                index: event.index,
+            },
+            CosmosTrigger::Transaction(transaction) => MappingTriggerWithoutBlock::Transaction {
+                hash: hex::encode(&transaction.tx.hash),
+            },
+            CosmosTrigger::Message(message) => MappingTriggerWithoutBlock::Message {
+                type_url: &message.message.type_url,
+                hash: hex::encode(&message.tx.hash),
+                index: message.index,
+            },
+        };
+
+        write!(f, "{:?}", trigger_without_block)
+    }
+}
+
+impl ToAscPtr for CosmosTrigger {
+    fn to_asc_ptr<H: AscHeap>(
+        self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<()>, HostExportError> {
+        Ok(match self {
+            CosmosTrigger::Block(block) => asc_new(heap, block.as_ref(), gas)?.erase(),
+            CosmosTrigger::Event(event) => asc_new(heap, event.as_ref(), gas)?.erase(),
+            CosmosTrigger::Transaction(transaction) => {
+                asc_new(heap, transaction.as_ref(), gas)?.erase()
+            }
+            CosmosTrigger::Message(message) => asc_new(heap, message.as_ref(), gas)?.erase(),
+        })
+    }
+}
+
+#[derive(Clone, CheapClone)]
+pub enum CosmosTrigger {
+    Block(Arc<codec::Block>),
+    Event(Arc<EventWithInfo>),
+    Transaction(Arc<TransactionWithInfo>),
+    Message(Arc<MessageWithInfo>),
+}
+
+impl PartialEq for CosmosTrigger {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Block(a_ptr), Self::Block(b_ptr)) => a_ptr == b_ptr,
+            (Self::Event(a), Self::Event(b)) => {
+                a.origin == b.origin
+                    && a.index == b.index
+                    && a.tx.as_ref().map(|tx| &tx.hash) == b.tx.as_ref().map(|tx| &tx.hash)
+            }
+            (Self::Transaction(a), Self::Transaction(b)) => a.tx.hash == b.tx.hash,
+            (Self::Message(a), Self::Message(b)) => a.tx.hash == b.tx.hash && a.index == b.index,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for CosmosTrigger {}
+
+impl CosmosTrigger {
+    pub fn block_number(&self) -> BlockNumber {
+        self.block().number()
+    }
+
+    pub fn block_hash(&self) -> String {
+        hex::encode(&self.block().header().hash)
+    }
+
+    fn block(&self) -> &codec::Block {
+        match self {
+            CosmosTrigger::Block(block) => block,
+            CosmosTrigger::Event(event) => &event.block,
+            CosmosTrigger::Transaction(transaction) => &transaction.block,
+            CosmosTrigger::Message(message) => &message.block,
+        }
+    }
+
+    /// The position of the trigger in the block as `(phase, tx_index)`.
+    /// Begin block events come first, followed by transactions in the
+    /// order in which they were executed, then end block events and
+    /// finally the block itself
+    fn position(&self) -> (u8, u32) {
+        match self {
+            CosmosTrigger::Event(event) => match event.origin {
+                EventOrigin::BeginBlock => (0, 0),
+                EventOrigin::DeliverTx => (1, event.tx.as_ref().map_or(0, |tx| tx.index)),
+                EventOrigin::EndBlock => (2, 0),
+            },
+            CosmosTrigger::Transaction(transaction) => (1, transaction.tx.index),
+            CosmosTrigger::Message(message) => (1, message.tx.index),
+            CosmosTrigger::Block(_) => (3, 0),
+        }
+    }
+
+    fn error_context(&self) -> std::string::String {
+        match self {
+            CosmosTrigger::Block(..) => {
+                format!("Block #{} ({})", self.block_number(), self.block_hash())
+            }
+            CosmosTrigger::Event(event) => {
+                format!(
+                    "Event #{} of type {}, block #{}({})",
+                    event.index,
+                    event.event.event_type,
+                    self.block_number(),
+                    self.block_hash()
+                )
+            }
+            CosmosTrigger::Transaction(transaction) => {
+                format!(
+                    "Tx {}, block #{}({})",
+                    hex::encode(&transaction.tx.hash),
+                    self.block_number(),
+                    self.block_hash()
+                )
+            }
+            CosmosTrigger::Message(message) => {
+                format!(
+                    "Message #{} of type {}, tx {}, block #{}({})",
+                    message.index,
+                    message.message.type_url,
+                    hex::encode(&message.tx.hash),
+                    self.block_number(),
+                    self.block_hash()
+                )
+            }
+        }
+    }
+}
+
+impl Ord for CosmosTrigger {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Triggers in the same phase and transaction keep the order in
+        // which they were created from the block, i.e., the transaction
+        // trigger, followed by its messages and then its events
+        self.position().cmp(&other.position())
+    }
+}
+
+impl PartialOrd for CosmosTrigger {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl TriggerData for CosmosTrigger {
+    fn error_context(&self) -> String {
+        self.error_context()
+    }
+
+    fn address_match(&self) -> Option<&[u8]> {
+        None
+    }
+}
+
+impl MappingTriggerTrait for CosmosTrigger {
+    fn error_context(&self) -> String {
+        self.error_context()
+    }
+}
+
+/// An event together with where in the block it was emitted
+pub struct EventWithInfo {
+    pub event: codec::Event,
+    pub origin: EventOrigin,
+    /// The index of the event in the list of events it was emitted with
+    pub index: u32,
+    /// The transaction that emitted the event. Only set for events that
+    /// originate from `DeliverTx`
+    pub tx: Option<Arc<codec::TransactionContext>>,
+    pub block: Arc<codec::Block>,
+}
+
+pub struct TransactionWithInfo {
+    pub tx: codec::TxResult,
+    pub block: Arc<codec::Block>,
+}
+
+/// A message of a transaction, still encoded as a protobuf `Any`. Mappings
+/// decode it according to its type URL
+pub struct MessageWithInfo {
+    pub message: prost_types::Any,
+    /// The index of the message in the transaction body
+    pub index: u32,
+    pub tx: Arc<codec::TransactionContext>,
+    pub block: Arc<codec::Block>,
+}
+
+/// Create the triggers for `block` that pass `filter`. Messages and events
+/// of failed transactions are ignored since they had no effect, but
+/// transaction handlers see every transaction
+pub(crate) fn triggers_in_block(
+    block: &Arc<codec::Block>,
+    filter: &TriggerFilter,
+) -> Vec<CosmosTrigger> {
+    let mut triggers = Vec::new();
+
+    event_triggers(
+        &mut triggers,
+        block,
+        filter,
+        block.begin_block_events(),
+        EventOrigin::BeginBlock,
+        None,
+    );
+
+    for tx in &block.transactions {
+        if filter.transaction_filter.trigger_every_transaction {
+            triggers.push(CosmosTrigger::Transaction(Arc::new(TransactionWithInfo {
+                tx: tx.clone(),
+                block: block.clone(),
+            })));
+        }
+
+        if !tx.succeeded() {
+            continue;
+        }
+        let context = Arc::new(codec::TransactionContext::from(tx));
+
+        if !filter.message_type_filter.is_empty() {
+            for (index, message) in tx.messages().into_iter().enumerate() {
+                if filter.message_type_filter.matches(&message.type_url) {
+                    triggers.push(CosmosTrigger::Message(Arc::new(MessageWithInfo {
+                        message,
+                        index: index as u32,
+                        tx: context.clone(),
+                        block: block.clone(),
+                    })));
+                }
+            }
+        }
+
+        event_triggers(
+            &mut triggers,
+            block,
+            filter,
+            tx.events(),
+            EventOrigin::DeliverTx,
+            Some(context),
+        );
+    }
+
+    event_triggers(
+        &mut triggers,
+        block,
+        filter,
+        block.end_block_events(),
+        EventOrigin::EndBlock,
+        None,
+    );
+
+    if filter.block_filter.trigger_every_block {
+        triggers.push(CosmosTrigger::Block(block.clone()));
+    }
+
+    triggers
+}
+
+fn event_triggers(
+    triggers: &mut Vec<CosmosTrigger>,
+    block: &Arc<codec::Block>,
+    filter: &TriggerFilter,
+    events: &[codec::Event],
+    origin: EventOrigin,
+    tx: Option<Arc<codec::TransactionContext>>,
+) {
+    for (index, event) in events.iter().enumerate() {
+        if filter.event_type_filter.matches(&event.event_type, origin) {
+            triggers.push(CosmosTrigger::Event(Arc::new(EventWithInfo {
+                event: event.clone(),
+                origin,
+                index: index as u32,
+                tx: tx.clone(),
+                block: block.clone(),
+            })));
+        }
+    }
+}
@@ -20,7 +20,7 @@ use std::fmt;
 use std::marker::Unpin;
 use thiserror::Error;
 use tiny_keccak::keccak256;
-use web3::types::{Address, Log, H256};
+use web3::types::{Address, Log, Transaction, H256};
 
 use graph::prelude::*;
 use graph::{
@@ -232,6 +232,8 @@ pub struct TriggerFilter {
     pub(crate) log: EthereumLogFilter,
     pub(crate) call: EthereumCallFilter,
     pub(crate) block: EthereumBlockFilter,
+    pub(crate) transaction: EthereumTransactionFilter,
+    pub(crate) transfer: EthereumTransferFilter,
 }
 
 impl TriggerFilter {
@@ -239,6 +241,10 @@ impl TriggerFilter {
         !self.call.is_empty() || self.block.requires_traces()
     }
 
+    pub(crate) fn requires_transfers(&self) -> bool {
+        !self.transfer.is_empty()
+    }
+
     #[cfg(debug_assertions)]
     pub fn log(&self) -> &EthereumLogFilter {
         &self.log
@@ -253,6 +259,16 @@ impl TriggerFilter {
     pub fn block(&self) -> &EthereumBlockFilter {
         &self.block
     }
+
+    #[cfg(debug_assertions)]
+    pub fn transaction(&self) -> &EthereumTransactionFilter {
+        &self.transaction
+    }
+
+    #[cfg(debug_assertions)]
+    pub fn transfer(&self) -> &EthereumTransferFilter {
+        &self.transfer
+    }
 }
 
 impl bc::TriggerFilter<Chain> for TriggerFilter {
@@ -262,13 +278,21 @@ impl bc::TriggerFilter<Chain> for TriggerFilter {
         self.call
             .extend(EthereumCallFilter::from_data_sources(data_sources.clone()));
         self.block
-            .extend(EthereumBlockFilter::from_data_sources(data_sources));
+            .extend(EthereumBlockFilter::from_data_sources(data_sources.clone()));
+        self.transaction
+            .extend(EthereumTransactionFilter::from_data_sources(
+                data_sources.clone(),
+            ));
+        self.transfer
+            .extend(EthereumTransferFilter::from_data_sources(data_sources));
     }
 
     fn node_capabilities(&self) -> NodeCapabilities {
+        // Nodes that support `trace_filter` are the ones we can expect to
+        // also support `debug_traceBlockByHash` for transfers
         NodeCapabilities {
             archive: false,
-            traces: self.requires_traces(),
+            traces: self.requires_traces() || self.requires_transfers(),
         }
     }
 
@@ -285,10 +309,25 @@ impl bc::TriggerFilter<Chain> for TriggerFilter {
 
             self.block
                 .extend(EthereumBlockFilter::from_mapping(&data_source.mapping));
+
+            self.transaction
+                .extend(EthereumTransactionFilter::from_mapping(
+                    &data_source.mapping,
+                ));
+
+            self.transfer
+                .extend(EthereumTransferFilter::from_mapping(&data_source.mapping));
         }
     }
 
     fn to_firehose_filter(self) -> Vec<prost_types::Any> {
+        // The combined filter can not express filters on transaction senders,
+        // so transaction handlers need to see every block. Transfers only
+        // show up in the call balance changes of full blocks
+        if !self.transaction.is_empty() || !self.transfer.is_empty() {
+            return Vec::new();
+        }
+
         let EthereumBlockFilter {
             polling_intervals,
             contract_addresses: _contract_addresses,
@@ -949,6 +988,123 @@ impl EthereumBlockFilter {
     }
 }
 
+/// Matches transactions by their sender, their receiver and the function
+/// selector at the start of their input. A field that is `None` matches
+/// any value.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TransactionPredicate {
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub function: Option<FunctionSelector>,
+}
+
+impl TransactionPredicate {
+    pub fn matches(&self, tx: &Transaction) -> bool {
+        self.from.map_or(true, |from| tx.from == Some(from))
+            && self.to.map_or(true, |to| tx.to == Some(to))
+            && self
+                .function
+                .map_or(true, |function| tx.input.0.starts_with(&function))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EthereumTransactionFilter {
+    pub predicates: HashSet<TransactionPredicate>,
+}
+
+impl EthereumTransactionFilter {
+    pub fn matches(&self, tx: &Transaction) -> bool {
+        self.predicates
+            .iter()
+            .any(|predicate| predicate.matches(tx))
+    }
+
+    /// Templates don't have an address yet, so transaction handlers that
+    /// filter on the data source address only match on their function here
+    /// and are further filtered when the trigger is matched to data
+    /// sources. Template handlers are required to have some filter, so this
+    /// never matches all transactions.
+    pub fn from_mapping(mapping: &Mapping) -> Self {
+        Self {
+            predicates: mapping
+                .transaction_handlers
+                .iter()
+                .flat_map(|handler| handler.predicates(None))
+                .collect(),
+        }
+    }
+
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            predicates: iter
+                .into_iter()
+                .flat_map(|data_source| {
+                    data_source
+                        .mapping
+                        .transaction_handlers
+                        .iter()
+                        .flat_map(|handler| handler.predicates(data_source.address))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn extend(&mut self, other: EthereumTransactionFilter) {
+        self.predicates.extend(other.predicates);
+    }
+
+    /// An empty filter is one that never matches.
+    pub fn is_empty(&self) -> bool {
+        self.predicates.is_empty()
+    }
+}
+
+/// Matches internal ETH transfers by their sender or recipient
+#[derive(Clone, Debug, Default)]
+pub struct EthereumTransferFilter {
+    pub addresses: HashSet<Address>,
+    /// Set by templates, whose address is only known once a data source is
+    /// created from them
+    pub match_all: bool,
+}
+
+impl EthereumTransferFilter {
+    pub fn matches(&self, transfer: &EthereumTransfer) -> bool {
+        self.match_all
+            || self.addresses.contains(&transfer.from)
+            || self.addresses.contains(&transfer.to)
+    }
+
+    pub fn from_mapping(mapping: &Mapping) -> Self {
+        Self {
+            addresses: HashSet::new(),
+            match_all: !mapping.transfer_handlers.is_empty(),
+        }
+    }
+
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            addresses: iter
+                .into_iter()
+                .filter(|data_source| !data_source.mapping.transfer_handlers.is_empty())
+                .filter_map(|data_source| data_source.address)
+                .collect(),
+            match_all: false,
+        }
+    }
+
+    pub fn extend(&mut self, other: EthereumTransferFilter) {
+        self.addresses.extend(other.addresses);
+        self.match_all |= other.match_all;
+    }
+
+    /// An empty filter is one that never matches.
+    pub fn is_empty(&self) -> bool {
+        !self.match_all && self.addresses.is_empty()
+    }
+}
+
 pub enum ProviderStatus {
     Working,
     VersionFail,
@@ -1191,6 +1347,17 @@ pub trait EthereumAdapter: Send + Sync + 'static {
         address: H160,
         block_ptr: BlockPtr,
     ) -> Box<dyn Future<Item = Bytes, Error = EthereumRpcError> + Send>;
+
+    /// Returns the value of the storage slot `slot` of the contract at
+    /// `address`. Values are cached in `cache` alongside contract calls
+    async fn get_storage_at(
+        &self,
+        logger: &Logger,
+        address: H160,
+        slot: H256,
+        block_ptr: BlockPtr,
+        cache: Arc<dyn EthereumCallCache>,
+    ) -> Result<(H256, call::Source), EthereumRpcError>;
 }
 
 #[cfg(test)]
@@ -1199,6 +1366,7 @@ mod tests {
 
     use super::{EthereumBlockFilter, LogFilterNode};
     use super::{EthereumCallFilter, EthereumLogFilter, TriggerFilter};
+    use super::{EthereumTransactionFilter, EthereumTransferFilter, TransactionPredicate};
 
     use base64::prelude::*;
     use graph::blockchain::TriggerFilter as _;
@@ -1207,7 +1375,8 @@ mod tests {
     use graph::prelude::ethabi::ethereum_types::H256;
     use graph::prelude::web3::types::Address;
     use graph::prelude::web3::types::Bytes;
-    use graph::prelude::EthereumCall;
+    use graph::prelude::web3::types::Transaction;
+    use graph::prelude::{EthereumCall, EthereumTransfer};
     use hex::ToHex;
     use itertools::Itertools;
     use prost::Message;
@@ -1324,6 +1493,8 @@ mod tests {
                 ]),
                 trigger_every_block: false,
             },
+            transaction: EthereumTransactionFilter::default(),
+            transfer: EthereumTransferFilter::default(),
         };
 
         let expected_call_filters = vec![
@@ -1440,6 +1611,8 @@ mod tests {
                 contract_addresses: HashSet::new(),
                 trigger_every_block: true,
             },
+            transaction: EthereumTransactionFilter::default(),
+            transfer: EthereumTransferFilter::default(),
         };
 
         filter.log.contracts_and_events_graph.add_edge(
@@ -1488,6 +1661,92 @@ mod tests {
         assert_eq!(true, actual_send_all_block_headers);
     }
 
+    #[test]
+    fn ethereum_trigger_filter_to_firehose_with_transaction_filter() {
+        let mut filter = TriggerFilter::default();
+        filter.call = EthereumCallFilter {
+            contract_addresses_function_signatures: HashMap::from_iter(vec![(
+                address(0),
+                (0, HashSet::from_iter(vec![[0u8; 4]])),
+            )]),
+            wildcard_signatures: HashSet::new(),
+        };
+        filter.transaction = EthereumTransactionFilter {
+            predicates: HashSet::from_iter(vec![TransactionPredicate {
+                from: Some(address(1)),
+                ..Default::default()
+            }]),
+        };
+
+        // Transaction handlers need to see all blocks
+        assert!(filter.to_firehose_filter().is_empty());
+    }
+
+    #[test]
+    fn matching_ethereum_transaction_filter() {
+        let tx = |from: Address, to: Option<Address>, input: Vec<u8>| Transaction {
+            from: Some(from),
+            to,
+            input: bytes(input),
+            ..Default::default()
+        };
+
+        let mut filter = EthereumTransactionFilter {
+            predicates: HashSet::from_iter(vec![
+                TransactionPredicate {
+                    from: Some(address(0)),
+                    ..Default::default()
+                },
+                TransactionPredicate {
+                    to: Some(address(1)),
+                    function: Some([1u8; 4]),
+                    ..Default::default()
+                },
+            ]),
+        };
+
+        assert!(filter.matches(&tx(address(0), None, vec![])));
+        assert!(filter.matches(&tx(address(0), Some(address(5)), vec![1; 4])));
+        assert!(filter.matches(&tx(address(2), Some(address(1)), vec![1; 8])));
+        assert!(!filter.matches(&tx(address(2), Some(address(1)), vec![2; 4])));
+        assert!(!filter.matches(&tx(address(2), Some(address(1)), vec![1; 3])));
+        assert!(!filter.matches(&tx(address(1), Some(address(2)), vec![1; 4])));
+
+        filter.extend(EthereumTransactionFilter {
+            predicates: HashSet::from_iter(vec![TransactionPredicate {
+                to: Some(address(2)),
+                ..Default::default()
+            }]),
+        });
+        assert!(filter.matches(&tx(address(1), Some(address(2)), vec![1; 4])));
+    }
+
+    #[test]
+    fn matching_ethereum_transfer_filter() {
+        let transfer = |from: Address, to: Address| EthereumTransfer {
+            from,
+            to,
+            ..Default::default()
+        };
+
+        let mut filter = EthereumTransferFilter {
+            addresses: HashSet::from_iter(vec![address(0)]),
+            match_all: false,
+        };
+        assert!(!filter.is_empty());
+        assert!(filter.matches(&transfer(address(0), address(1))));
+        assert!(filter.matches(&transfer(address(1), address(0))));
+        assert!(!filter.matches(&transfer(address(1), address(2))));
+
+        filter.extend(EthereumTransferFilter {
+            addresses: HashSet::new(),
+            match_all: true,
+        });
+        assert!(filter.matches(&transfer(address(1), address(2))));
+
+        assert!(EthereumTransferFilter::default().is_empty());
+    }
+
     #[test]
     fn matching_ethereum_call_filter() {
         let call = |to: Address, input: Vec<u8>| EthereumCall {
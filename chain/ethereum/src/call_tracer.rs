@@ -0,0 +1,217 @@
+//! Internal ETH transfers from the output of geth's `callTracer`, for
+//! nodes that support `debug_traceBlockByHash` but where we don't get the
+//! balance changes from Firehose.
+
+use graph::prelude::{
+    web3::types::{Address, H256, U256},
+    BlockNumber, EthereumTransfer,
+};
+use serde::Deserialize;
+
+/// The result for one transaction of `debug_traceBlockByHash` with the
+/// `callTracer`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TransactionTrace {
+    pub tx_hash: Option<H256>,
+    pub result: CallFrame,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct CallFrame {
+    #[serde(rename = "type")]
+    pub call_type: String,
+    pub from: Address,
+    pub to: Option<Address>,
+    pub value: Option<U256>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    /// Whether this frame moves ETH from `from` to `to`. Static and
+    /// delegate calls can't move ETH even if the tracer reports a value
+    fn moves_value(&self) -> bool {
+        matches!(
+            self.call_type.as_str(),
+            "CALL" | "CREATE" | "CREATE2" | "SELFDESTRUCT"
+        ) && self.value.map_or(false, |value| !value.is_zero())
+    }
+
+    fn collect_transfers(&self, transfers: &mut Vec<(Address, Address, U256)>) {
+        for frame in &self.calls {
+            // An error in a frame reverts everything that happened in it,
+            // including the transfers of any of its children
+            if frame.error.is_some() {
+                continue;
+            }
+            if let (true, Some(to)) = (frame.moves_value(), frame.to) {
+                transfers.push((frame.from, to, frame.value.unwrap_or_default()));
+            }
+            frame.collect_transfers(transfers);
+        }
+    }
+}
+
+/// Extract the internal transfers from the traces of all transactions in a
+/// block. `traces` must be in the order of the transactions in the block.
+/// The value sent by the transaction itself is not an internal transfer
+/// and is not included.
+pub(crate) fn transfers_from_traces(
+    block_number: BlockNumber,
+    block_hash: H256,
+    tx_hashes: &[H256],
+    traces: Vec<TransactionTrace>,
+) -> Result<Vec<EthereumTransfer>, anyhow::Error> {
+    if traces.len() != tx_hashes.len() {
+        anyhow::bail!(
+            "debug_traceBlockByHash returned {} traces for block {} with {} transactions",
+            traces.len(),
+            block_hash,
+            tx_hashes.len()
+        );
+    }
+
+    let mut transfers = Vec::new();
+    for (index, (trace, tx_hash)) in traces.into_iter().zip(tx_hashes).enumerate() {
+        if let Some(hash) = trace.tx_hash {
+            if &hash != tx_hash {
+                anyhow::bail!(
+                    "trace for transaction {} in block {} is for transaction {}",
+                    tx_hash,
+                    block_hash,
+                    hash
+                );
+            }
+        }
+        if trace.result.error.is_some() {
+            continue;
+        }
+
+        let mut tx_transfers = Vec::new();
+        trace.result.collect_transfers(&mut tx_transfers);
+        transfers.extend(tx_transfers.into_iter().enumerate().map(
+            |(transfer_index, (from, to, value))| EthereumTransfer {
+                from,
+                to,
+                value,
+                block_number,
+                block_hash,
+                transaction_hash: *tx_hash,
+                transaction_index: index as u64,
+                transfer_index: transfer_index as u64,
+            },
+        ));
+    }
+    Ok(transfers)
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::prelude::{serde_json, web3::types::H160};
+
+    use super::*;
+
+    const TRACES: &str = r#"[
+      {
+        "txHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "result": {
+          "type": "CALL",
+          "from": "0x0000000000000000000000000000000000000001",
+          "to": "0x0000000000000000000000000000000000000002",
+          "value": "0x10",
+          "calls": [
+            {
+              "type": "CALL",
+              "from": "0x0000000000000000000000000000000000000002",
+              "to": "0x0000000000000000000000000000000000000003",
+              "value": "0x5",
+              "calls": [
+                {
+                  "type": "STATICCALL",
+                  "from": "0x0000000000000000000000000000000000000003",
+                  "to": "0x0000000000000000000000000000000000000004"
+                }
+              ]
+            },
+            {
+              "type": "CALL",
+              "from": "0x0000000000000000000000000000000000000002",
+              "to": "0x0000000000000000000000000000000000000005",
+              "value": "0x7",
+              "error": "execution reverted",
+              "calls": [
+                {
+                  "type": "CALL",
+                  "from": "0x0000000000000000000000000000000000000005",
+                  "to": "0x0000000000000000000000000000000000000006",
+                  "value": "0x1"
+                }
+              ]
+            },
+            {
+              "type": "SELFDESTRUCT",
+              "from": "0x0000000000000000000000000000000000000002",
+              "to": "0x0000000000000000000000000000000000000007",
+              "value": "0x3"
+            }
+          ]
+        }
+      },
+      {
+        "txHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+        "result": {
+          "type": "CALL",
+          "from": "0x0000000000000000000000000000000000000001",
+          "to": "0x0000000000000000000000000000000000000002",
+          "error": "out of gas",
+          "calls": [
+            {
+              "type": "CALL",
+              "from": "0x0000000000000000000000000000000000000002",
+              "to": "0x0000000000000000000000000000000000000003",
+              "value": "0x5"
+            }
+          ]
+        }
+      }
+    ]"#;
+
+    #[test]
+    fn transfers_from_call_tracer_output() {
+        let traces: Vec<TransactionTrace> = serde_json::from_str(TRACES).unwrap();
+        let block_hash = H256::from_low_u64_be(42);
+        let tx_hashes = [H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+
+        let transfers = transfers_from_traces(7, block_hash, &tx_hashes, traces).unwrap();
+
+        let summary: Vec<_> = transfers
+            .iter()
+            .map(|t| (t.from, t.to, t.value.as_u64(), t.transfer_index))
+            .collect();
+        assert_eq!(
+            vec![
+                (H160::from_low_u64_be(2), H160::from_low_u64_be(3), 5, 0),
+                (H160::from_low_u64_be(2), H160::from_low_u64_be(7), 3, 1),
+            ],
+            summary
+        );
+        assert!(transfers.iter().all(|t| t.block_number == 7
+            && t.block_hash == block_hash
+            && t.transaction_hash == tx_hashes[0]
+            && t.transaction_index == 0));
+    }
+
+    #[test]
+    fn transfers_from_traces_checks_transactions() {
+        let traces: Vec<TransactionTrace> = serde_json::from_str(TRACES).unwrap();
+        let block_hash = H256::from_low_u64_be(42);
+
+        let tx_hashes = [H256::from_low_u64_be(1)];
+        assert!(transfers_from_traces(7, block_hash, &tx_hashes, traces.clone()).is_err());
+
+        let tx_hashes = [H256::from_low_u64_be(1), H256::from_low_u64_be(3)];
+        assert!(transfers_from_traces(7, block_hash, &tx_hashes, traces).is_err());
+    }
+}
@@ -57,7 +57,7 @@ use crate::{
     data_source::{DataSource, UnresolvedDataSource},
     ethereum_adapter::{
         blocks_with_triggers, get_calls, parse_block_triggers, parse_call_triggers,
-        parse_log_triggers,
+        parse_log_triggers, parse_transaction_triggers, parse_transfer_triggers,
     },
     SubgraphEthRpcMetrics, TriggerFilter, ENV_VARS,
 };
@@ -973,6 +973,7 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
             self.ethrpc_metrics.clone(),
             &self.capabilities,
             filter.requires_traces(),
+            filter.requires_transfers(),
             block,
         )
         .await?;
@@ -1006,6 +1007,11 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
                     &full_block.ethereum_block,
                 ));
                 triggers.append(&mut parse_call_triggers(&filter.call, full_block)?);
+                triggers.append(&mut parse_transaction_triggers(
+                    &filter.transaction,
+                    &full_block.ethereum_block,
+                )?);
+                triggers.append(&mut parse_transfer_triggers(&filter.transfer, full_block));
                 triggers.append(&mut parse_block_triggers(&filter.block, full_block));
                 Ok(BlockWithTriggers::new(block, triggers, logger))
             }
@@ -1055,6 +1061,7 @@ impl TriggersAdapterTrait<Chain> for TriggersAdapter {
             BlockFinality::NonFinal(EthereumBlockWithCalls {
                 ethereum_block: block,
                 calls: None,
+                transfers: None,
             })
         }))
     }
@@ -1144,8 +1151,8 @@ impl BlockStreamMapper<Chain> for FirehoseMapper {
         };
 
         // See comment(437a9f17-67cc-478f-80a3-804fe554b227) ethereum_block.calls is always Some even if calls
-        // is empty
-        let ethereum_block: EthereumBlockWithCalls = (&block).try_into()?;
+        // is empty. Internal transfers are only needed for transfer handlers
+        let ethereum_block = block.to_block_with_calls(self.filter.requires_transfers())?;
 
         Ok(Some(BlockFinality::NonFinal(ethereum_block)))
     }
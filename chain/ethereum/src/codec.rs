@@ -10,7 +10,7 @@ use graph::{
     prelude::{
         web3,
         web3::types::{Bytes, H160, H2048, H256, H64, U256, U64},
-        BlockNumber, Error, EthereumBlock, EthereumBlockWithCalls, EthereumCall,
+        BlockNumber, Error, EthereumBlock, EthereumBlockWithCalls, EthereumCall, EthereumTransfer,
         LightEthereumBlock,
     },
 };
@@ -221,6 +221,18 @@ impl TryInto<EthereumBlockWithCalls> for &Block {
     type Error = Error;
 
     fn try_into(self) -> Result<EthereumBlockWithCalls, Self::Error> {
+        self.to_block_with_calls(true)
+    }
+}
+
+impl Block {
+    /// Convert the Firehose block. Internal transfers are only extracted
+    /// from the call traces if `with_transfers` is set; otherwise they are
+    /// left as not checked
+    pub fn to_block_with_calls(
+        &self,
+        with_transfers: bool,
+    ) -> Result<EthereumBlockWithCalls, Error> {
         let header = self.header.as_ref().ok_or_else(|| {
             format_err!("block header should always be present from gRPC Firehose")
         })?;
@@ -370,12 +382,76 @@ impl TryInto<EthereumBlockWithCalls> for &Block {
                     })
                     .collect::<Result<_, _>>()?,
             ),
+            transfers: if with_transfers {
+                Some(
+                    self.transaction_traces
+                        .iter()
+                        .map(|trace| trace.internal_transfers(self))
+                        .collect::<Result<Vec<_>, Error>>()?
+                        .into_iter()
+                        .flatten()
+                        .collect(),
+                )
+            } else {
+                None
+            },
         };
 
         Ok(block)
     }
 }
 
+impl TransactionTrace {
+    /// The internal ETH transfers of this transaction, taken from the
+    /// balance changes of its calls. Value moved by the root call is the
+    /// value of the transaction itself, and the balance changes of reverted
+    /// calls never took effect, so both are skipped.
+    fn internal_transfers(&self, block: &Block) -> Result<Vec<EthereumTransfer>, Error> {
+        let mut transfers = Vec::new();
+
+        for call in self
+            .calls
+            .iter()
+            .filter(|call| call.depth > 0 && !call.state_reverted)
+        {
+            for change in &call.balance_changes {
+                let from = match balance_change::Reason::try_from(change.reason) {
+                    Ok(balance_change::Reason::Transfer) => &call.caller,
+                    Ok(balance_change::Reason::SuicideRefund) => &call.address,
+                    _ => continue,
+                };
+
+                // A transfer changes the balance of the sender and of the
+                // recipient; we only look at the recipient's side
+                let old_value = change
+                    .old_value
+                    .as_ref()
+                    .map_or_else(U256::zero, U256::from);
+                let new_value = change
+                    .new_value
+                    .as_ref()
+                    .map_or_else(U256::zero, U256::from);
+                if new_value <= old_value {
+                    continue;
+                }
+
+                transfers.push(EthereumTransfer {
+                    from: from.try_decode_proto("transfer from address")?,
+                    to: change.address.try_decode_proto("transfer to address")?,
+                    value: new_value - old_value,
+                    block_number: block.number as i32,
+                    block_hash: block.hash.try_decode_proto("transfer block hash")?,
+                    transaction_hash: self.hash.try_decode_proto("transfer transaction hash")?,
+                    transaction_index: self.index as u64,
+                    transfer_index: transfers.len() as u64,
+                });
+            }
+        }
+
+        Ok(transfers)
+    }
+}
+
 impl BlockHeader {
     pub fn parent_ptr(&self) -> Option<BlockPtr> {
         match self.parent_hash.len() {
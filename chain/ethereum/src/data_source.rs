@@ -34,17 +34,18 @@ use graph::{
         ethabi::{Address, Event, Function, LogParam, ParamType, RawLog},
         serde_json, warn,
         web3::types::{Log, Transaction, H256},
-        BlockNumber, CheapClone, EthereumCall, LightEthereumBlock, LightEthereumBlockExt,
-        LinkResolver, Logger,
+        BlockNumber, CheapClone, EthereumCall, EthereumTransfer, LightEthereumBlock,
+        LightEthereumBlockExt, LinkResolver, Logger,
     },
 };
 
 use graph::data::subgraph::{
-    calls_host_fn, DataSourceContext, Source, MIN_SPEC_VERSION, SPEC_VERSION_0_0_8,
-    SPEC_VERSION_1_2_0,
+    calls_host_fn, DataSourceContext, Source, API_VERSION_0_0_7, MIN_SPEC_VERSION,
+    SPEC_VERSION_0_0_8, SPEC_VERSION_1_2_0, SPEC_VERSION_1_3_0,
 };
 
 use crate::adapter::EthereumAdapter as _;
+use crate::adapter::{FunctionSelector, TransactionPredicate};
 use crate::chain::Chain;
 use crate::network::EthereumNetworkAdapters;
 use crate::trigger::{EthereumBlockTriggerType, EthereumTrigger, MappingTrigger};
@@ -55,6 +56,8 @@ const ETHEREUM_KINDS: &[&str] = &["ethereum/contract", "ethereum"];
 const EVENT_HANDLER_KIND: &str = "event";
 const CALL_HANDLER_KIND: &str = "call";
 const BLOCK_HANDLER_KIND: &str = "block";
+const TRANSACTION_HANDLER_KIND: &str = "transaction";
+const TRANSFER_HANDLER_KIND: &str = "transfer";
 
 /// Runtime representation of a data source.
 // Note: Not great for memory usage that this needs to be `Clone`, considering how there may be tens
@@ -148,6 +151,8 @@ impl blockchain::DataSource<Chain> for DataSource {
             event_handlers,
             call_handlers,
             block_handlers,
+            transaction_handlers,
+            transfer_handlers,
             ..
         } = &self.mapping;
 
@@ -160,6 +165,12 @@ impl blockchain::DataSource<Chain> for DataSource {
         for handler in block_handlers.iter() {
             kinds.insert(handler.kind());
         }
+        if !transaction_handlers.is_empty() {
+            kinds.insert(TRANSACTION_HANDLER_KIND);
+        }
+        if !transfer_handlers.is_empty() {
+            kinds.insert(TRANSFER_HANDLER_KIND);
+        }
 
         kinds
     }
@@ -231,6 +242,8 @@ impl blockchain::DataSource<Chain> for DataSource {
             && mapping.event_handlers == other.mapping.event_handlers
             && mapping.call_handlers == other.mapping.call_handlers
             && mapping.block_handlers == other.mapping.block_handlers
+            && mapping.transaction_handlers == other.mapping.transaction_handlers
+            && mapping.transfer_handlers == other.mapping.transfer_handlers
             && context == &other.context
     }
 
@@ -246,6 +259,7 @@ impl blockchain::DataSource<Chain> for DataSource {
                 .map(|ctx| serde_json::to_value(ctx).unwrap()),
             creation_block: self.creation_block,
             done_at: None,
+            chunks_done_at: vec![],
             causality_region: CausalityRegion::ONCHAIN,
         }
     }
@@ -260,6 +274,7 @@ impl blockchain::DataSource<Chain> for DataSource {
             context,
             creation_block,
             done_at,
+            chunks_done_at: _,
             causality_region,
         } = stored;
 
@@ -304,10 +319,22 @@ impl blockchain::DataSource<Chain> for DataSource {
         let no_source_address = self.address().is_none();
         let has_call_handlers = !self.mapping.call_handlers.is_empty();
         let has_block_handlers = !self.mapping.block_handlers.is_empty();
-        if no_source_address && (has_call_handlers || has_block_handlers) {
+        let has_transfer_handlers = !self.mapping.transfer_handlers.is_empty();
+        if no_source_address && (has_call_handlers || has_block_handlers || has_transfer_handlers) {
             errors.push(SubgraphManifestValidationError::SourceAddressRequired.into());
         };
 
+        // Transaction handlers without a `from` or `to` filter match
+        // transactions from or to the source address
+        let has_unfiltered_transaction_handlers = self
+            .mapping
+            .transaction_handlers
+            .iter()
+            .any(|handler| handler.from.is_none() && handler.to.is_none());
+        if no_source_address && has_unfiltered_transaction_handlers {
+            errors.push(SubgraphManifestValidationError::SourceAddressRequired.into());
+        }
+
         // Ensure that there is at most one instance of each type of block handler
         // and that a combination of a non-filtered block handler and a filtered block handler is not allowed.
 
@@ -369,6 +396,40 @@ impl blockchain::DataSource<Chain> for DataSource {
             }
         }
 
+        // Transaction handlers are passed the transaction receipt, which is
+        // only available from API version 0.0.7
+        if api_version < API_VERSION_0_0_7 && !self.mapping.transaction_handlers.is_empty() {
+            errors.push(anyhow!(
+                "data source has transaction handlers, but these are only supported for \
+                 apiVersion >= 0.0.7"
+            ));
+        }
+        if api_version < API_VERSION_0_0_7 && !self.mapping.transfer_handlers.is_empty() {
+            errors.push(anyhow!(
+                "data source has transfer handlers, but these are only supported for \
+                 apiVersion >= 0.0.7"
+            ));
+        }
+        if self.mapping.transfer_handlers.len() > 1 {
+            errors.push(anyhow!(
+                "data source has more than one transfer handler, but only one is allowed"
+            ));
+        }
+
+        for handler in &self.mapping.transaction_handlers {
+            if let Some(function) = &handler.function {
+                if self.contract_function_with_signature(function).is_none() {
+                    errors.push(anyhow!(
+                        "transaction handler {}: function with the signature \"{}\" not found in \
+                         contract \"{}\"",
+                        handler.handler,
+                        function,
+                        self.contract_abi.name
+                    ));
+                }
+            }
+        }
+
         if spec_version < &SPEC_VERSION_1_2_0 {
             for handler in &self.mapping.event_handlers {
                 if !handler.calls.decls.is_empty() {
@@ -421,12 +482,22 @@ impl blockchain::DataSource<Chain> for DataSource {
             }
         }
 
+        if !self.mapping.transaction_handlers.is_empty()
+            || !self.mapping.transfer_handlers.is_empty()
+        {
+            min_version = std::cmp::max(min_version, SPEC_VERSION_1_3_0);
+        }
+
         min_version
     }
 
     fn runtime(&self) -> Option<Arc<Vec<u8>>> {
         Some(self.mapping.runtime.cheap_clone())
     }
+
+    fn source_map(&self) -> Option<Arc<Vec<u8>>> {
+        self.mapping.source_map.cheap_clone()
+    }
 }
 
 impl DataSource {
@@ -486,6 +557,21 @@ impl DataSource {
         }))
     }
 
+    fn handler_for_transaction(&self, tx: &Transaction) -> Option<&MappingTransactionHandler> {
+        self.mapping
+            .transaction_handlers
+            .iter()
+            .find(|handler| handler.matches(tx, self.address))
+    }
+
+    fn handler_for_transfer(&self, transfer: &EthereumTransfer) -> Option<&MappingTransferHandler> {
+        let address = self.address?;
+        if transfer.from != address && transfer.to != address {
+            return None;
+        }
+        self.mapping.transfer_handlers.first()
+    }
+
     fn handler_for_block(
         &self,
         trigger_type: &EthereumBlockTriggerType,
@@ -932,6 +1018,60 @@ impl DataSource {
                     logging_extras,
                 )))
             }
+            EthereumTrigger::Transaction(transaction, receipt) => {
+                let handler = match self.handler_for_transaction(transaction) {
+                    Some(handler) => handler,
+                    None => return Ok(None),
+                };
+
+                let logging_extras = Arc::new(o! {
+                    "from" => format!("{:?}", &transaction.from),
+                    "to" => format!("{:?}", &transaction.to),
+                    "transaction" => format!("{}", &transaction.hash),
+                });
+                Ok(Some(TriggerWithHandler::<Chain>::new_with_logging_extras(
+                    MappingTrigger::Transaction {
+                        block: block.cheap_clone(),
+                        transaction: transaction.cheap_clone(),
+                        receipt: receipt.cheap_clone(),
+                    },
+                    handler.handler.clone(),
+                    block.block_ptr(),
+                    block.timestamp(),
+                    logging_extras,
+                )))
+            }
+            EthereumTrigger::Transfer(transfer) => {
+                let handler = match self.handler_for_transfer(transfer) {
+                    Some(handler) => handler,
+                    None => return Ok(None),
+                };
+
+                let transaction = Arc::new(
+                    block
+                        .transactions
+                        .iter()
+                        .find(|tx| tx.hash == transfer.transaction_hash)
+                        .cloned()
+                        .context("Found no transaction for transfer")?,
+                );
+                let logging_extras = Arc::new(o! {
+                    "from" => format!("{}", &transfer.from),
+                    "to" => format!("{}", &transfer.to),
+                    "transaction" => format!("{}", &transaction.hash),
+                });
+                Ok(Some(TriggerWithHandler::<Chain>::new_with_logging_extras(
+                    MappingTrigger::Transfer {
+                        block: block.cheap_clone(),
+                        transaction,
+                        transfer: transfer.cheap_clone(),
+                    },
+                    handler.handler.clone(),
+                    block.block_ptr(),
+                    block.timestamp(),
+                    logging_extras,
+                )))
+            }
         }
     }
 }
@@ -1261,6 +1401,22 @@ impl blockchain::UnresolvedDataSourceTemplate<Chain> for UnresolvedDataSourceTem
             .await
             .with_context(|| format!("failed to resolve data source template {}", name))?;
 
+        // Templates have no address to match transactions against; without
+        // a filter, a transaction handler would be called for, and fetch the
+        // receipt of, every transaction on the chain
+        if let Some(handler) = mapping
+            .transaction_handlers
+            .iter()
+            .find(|handler| handler.is_unfiltered())
+        {
+            return Err(anyhow!(
+                "data source template {}: transaction handler {} must declare at least one of \
+                 `from`, `to` or `function`",
+                name,
+                handler.handler
+            ));
+        }
+
         Ok(DataSourceTemplate {
             kind,
             network,
@@ -1308,7 +1464,13 @@ pub struct UnresolvedMapping {
     pub call_handlers: Vec<MappingCallHandler>,
     #[serde(default)]
     pub event_handlers: Vec<MappingEventHandler>,
+    #[serde(default)]
+    pub transaction_handlers: Vec<MappingTransactionHandler>,
+    #[serde(default)]
+    pub transfer_handlers: Vec<MappingTransferHandler>,
     pub file: Link,
+    #[serde(default)]
+    pub source_map: Option<Link>,
 }
 
 #[derive(Clone, Debug)]
@@ -1321,7 +1483,10 @@ pub struct Mapping {
     pub block_handlers: Vec<MappingBlockHandler>,
     pub call_handlers: Vec<MappingCallHandler>,
     pub event_handlers: Vec<MappingEventHandler>,
+    pub transaction_handlers: Vec<MappingTransactionHandler>,
+    pub transfer_handlers: Vec<MappingTransferHandler>,
     pub runtime: Arc<Vec<u8>>,
+    pub source_map: Option<Arc<Vec<u8>>>,
     pub link: Link,
 }
 
@@ -1367,7 +1532,10 @@ impl UnresolvedMapping {
             block_handlers,
             call_handlers,
             event_handlers,
+            transaction_handlers,
+            transfer_handlers,
             file: link,
+            source_map,
         } = self;
 
         let api_version = semver::Version::parse(&api_version)?;
@@ -1390,6 +1558,21 @@ impl UnresolvedMapping {
         .await
         .with_context(|| format!("failed to resolve mapping {}", link.link))?;
 
+        // The source map only improves error messages; a subgraph whose
+        // source map can not be fetched still works without it
+        let source_map = match source_map {
+            Some(source_map) => match resolver.cat(logger, &source_map).await {
+                Ok(bytes) => Some(Arc::new(bytes)),
+                Err(e) => {
+                    warn!(logger, "Failed to resolve source map, continuing without it";
+                        "link" => &source_map.link,
+                        "error" => format!("{:#}", e));
+                    None
+                }
+            },
+            None => None,
+        };
+
         Ok(Mapping {
             kind,
             api_version,
@@ -1399,7 +1582,10 @@ impl UnresolvedMapping {
             block_handlers: block_handlers.clone(),
             call_handlers: call_handlers.clone(),
             event_handlers: event_handlers.clone(),
+            transaction_handlers,
+            transfer_handlers,
             runtime,
+            source_map,
             link,
         })
     }
@@ -1442,6 +1628,81 @@ pub struct MappingCallHandler {
     pub handler: String,
 }
 
+/// A handler that is called for transactions. Transactions can be selected
+/// by their sender, their receiver and the function they call; if neither
+/// `from` nor `to` is given, the handler is called for all transactions
+/// from or to the data source address. Handlers in templates must have at
+/// least one filter.
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingTransactionHandler {
+    pub handler: String,
+    #[serde(default)]
+    pub from: Option<Address>,
+    #[serde(default)]
+    pub to: Option<Address>,
+    #[serde(default)]
+    pub function: Option<String>,
+}
+
+impl MappingTransactionHandler {
+    fn selector(&self) -> Option<FunctionSelector> {
+        self.function.as_ref().map(|function| {
+            let sig = keccak256(function.as_bytes());
+            [sig[0], sig[1], sig[2], sig[3]]
+        })
+    }
+
+    /// Whether this handler has neither a `from`, a `to` nor a `function`
+    /// filter, and therefore needs a data source address to match against
+    pub fn is_unfiltered(&self) -> bool {
+        self.from.is_none() && self.to.is_none() && self.function.is_none()
+    }
+
+    /// The predicates for the transactions this handler should be called
+    /// for when it belongs to a data source with the given `address`.
+    /// Without an address, the handler matches all transactions that call
+    /// its function; an unfiltered handler matches nothing.
+    pub fn predicates(&self, address: Option<Address>) -> Vec<TransactionPredicate> {
+        let function = self.selector();
+        match (self.from, self.to) {
+            (None, None) => match address {
+                Some(address) => vec![
+                    TransactionPredicate {
+                        from: Some(address),
+                        to: None,
+                        function,
+                    },
+                    TransactionPredicate {
+                        from: None,
+                        to: Some(address),
+                        function,
+                    },
+                ],
+                None if function.is_some() => vec![TransactionPredicate {
+                    from: None,
+                    to: None,
+                    function,
+                }],
+                None => vec![],
+            },
+            (from, to) => vec![TransactionPredicate { from, to, function }],
+        }
+    }
+
+    pub fn matches(&self, tx: &Transaction, address: Option<Address>) -> bool {
+        self.predicates(address)
+            .iter()
+            .any(|predicate| predicate.matches(tx))
+    }
+}
+
+/// A handler that is called for internal ETH transfers, i.e. value moved
+/// by calls inside a transaction, from or to the data source address.
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingTransferHandler {
+    pub handler: String,
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
 pub struct MappingEventHandler {
     pub event: String,
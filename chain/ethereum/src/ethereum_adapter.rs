@@ -57,6 +57,7 @@ use std::time::Instant;
 
 use crate::adapter::EthereumRpcError;
 use crate::adapter::ProviderStatus;
+use crate::call_tracer;
 use crate::chain::BlockFinality;
 use crate::trigger::LogRef;
 use crate::Chain;
@@ -65,8 +66,8 @@ use crate::TriggerFilter;
 use crate::{
     adapter::{
         ContractCallError, EthGetLogsFilter, EthereumAdapter as EthereumAdapterTrait,
-        EthereumBlockFilter, EthereumCallFilter, EthereumLogFilter, ProviderEthRpcMetrics,
-        SubgraphEthRpcMetrics,
+        EthereumBlockFilter, EthereumCallFilter, EthereumLogFilter, EthereumTransactionFilter,
+        EthereumTransferFilter, ProviderEthRpcMetrics, SubgraphEthRpcMetrics,
     },
     transport::Transport,
     trigger::{EthereumBlockTriggerType, EthereumTrigger},
@@ -572,6 +573,46 @@ impl EthereumAdapter {
             .compat()
     }
 
+    fn storage_at(
+        &self,
+        logger: &Logger,
+        address: Address,
+        slot: H256,
+        block_ptr: BlockPtr,
+    ) -> impl Future<Item = H256, Error = EthereumRpcError> + Send {
+        let web3 = self.web3.clone();
+        let logger = Logger::new(&logger, o!("provider" => self.provider.clone()));
+
+        let block_id = self.block_ptr_to_id(&block_ptr);
+        let retry_log_message = format!("eth_getStorageAt RPC call for block {}", block_ptr);
+
+        retry(retry_log_message, &logger)
+            .redact_log_urls(true)
+            .when(|result| match result {
+                Ok(_) => false,
+                Err(_) => true,
+            })
+            .limit(ENV_VARS.request_retries)
+            .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
+            .run(move || {
+                let web3 = web3.cheap_clone();
+                async move {
+                    let result: Result<H256, web3::Error> = web3
+                        .eth()
+                        .storage(address, U256::from(slot.as_bytes()), Some(block_id))
+                        .boxed()
+                        .await;
+                    match result {
+                        Ok(value) => Ok(value),
+                        Err(err) => Err(EthereumRpcError::Web3Error(err)),
+                    }
+                }
+            })
+            .map_err(|e| e.into_inner().unwrap_or(EthereumRpcError::Timeout))
+            .boxed()
+            .compat()
+    }
+
     async fn call(
         &self,
         logger: Logger,
@@ -1116,6 +1157,90 @@ impl EthereumAdapter {
             .collect())
     }
 
+    /// Get the internal ETH transfers in `block` by tracing it with the
+    /// `callTracer`. This requires a node that supports
+    /// `debug_traceBlockByHash`.
+    pub(crate) async fn transfers_in_block(
+        &self,
+        logger: &Logger,
+        subgraph_metrics: Arc<SubgraphEthRpcMetrics>,
+        block: &LightEthereumBlock,
+    ) -> Result<Vec<EthereumTransfer>, Error> {
+        if block.transactions.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let block_number = block.number();
+        let block_hash = block.hash.context("block without a hash")?;
+        let tx_hashes: Vec<H256> = block.transactions.iter().map(|tx| tx.hash).collect();
+
+        let eth = self.clone();
+        let logger = logger.clone();
+        let retry_log_message = format!(
+            "debug_traceBlockByHash RPC call for block: number = {}, hash = {:x}",
+            block_number, block_hash
+        );
+        let traces = retry(retry_log_message, &logger)
+            .redact_log_urls(true)
+            .limit(ENV_VARS.request_retries)
+            .timeout_secs(ENV_VARS.json_rpc_timeout.as_secs())
+            .run(move || {
+                let eth = eth.cheap_clone();
+                let logger = logger.clone();
+                let subgraph_metrics = subgraph_metrics.clone();
+                let start = Instant::now();
+
+                async move {
+                    let params = vec![
+                        json::to_value(block_hash)?,
+                        json::json!({ "tracer": "callTracer" }),
+                    ];
+                    let result = web3::Transport::execute(
+                        eth.web3.transport(),
+                        "debug_traceBlockByHash",
+                        params,
+                    )
+                    .await
+                    .map_err(Error::from)
+                    .and_then(|value| {
+                        json::from_value::<Vec<call_tracer::TransactionTrace>>(value)
+                            .map_err(Error::from)
+                    });
+
+                    let elapsed = start.elapsed().as_secs_f64();
+                    eth.metrics
+                        .observe_request(elapsed, "debug_traceBlockByHash", &eth.provider);
+                    subgraph_metrics.observe_request(
+                        elapsed,
+                        "debug_traceBlockByHash",
+                        &eth.provider,
+                    );
+                    if let Err(e) = &result {
+                        eth.metrics
+                            .add_error("debug_traceBlockByHash", &eth.provider);
+                        subgraph_metrics.add_error("debug_traceBlockByHash", &eth.provider);
+                        debug!(
+                            logger,
+                            "Error tracing block error = {:#} block = {}", e, block_number
+                        );
+                    }
+                    result
+                }
+            })
+            .map_err(move |e| {
+                e.into_inner().unwrap_or_else(move || {
+                    anyhow!(
+                        "Ethereum node took too long to respond to debug_traceBlockByHash \
+                         (block {})",
+                        block_number
+                    )
+                })
+            })
+            .await?;
+
+        call_tracer::transfers_from_traces(block_number, block_hash, &tx_hashes, traces)
+    }
+
     /// Reorg safety: `to` must be a final block.
     pub(crate) fn block_range_to_ptrs(
         &self,
@@ -1527,6 +1652,59 @@ impl EthereumAdapterTrait for EthereumAdapter {
         Box::new(self.code(logger, address, block_ptr))
     }
 
+    async fn get_storage_at(
+        &self,
+        logger: &Logger,
+        address: H160,
+        slot: H256,
+        block_ptr: BlockPtr,
+        cache: Arc<dyn EthereumCallCache>,
+    ) -> Result<(H256, call::Source), EthereumRpcError> {
+        let req = call::Request::storage_at(address, slot.as_fixed_bytes(), 0);
+
+        let cached = cache
+            .get_call(&req, block_ptr.cheap_clone())
+            .map_err(|e| error!(logger, "call cache get error"; "error" => e.to_string()))
+            .ok()
+            .flatten();
+        if let Some(call::Response {
+            retval: call::Retval::Value(value),
+            source,
+            ..
+        }) = cached
+        {
+            if value.as_slice().len() == H256::len_bytes() {
+                return Ok((H256::from_slice(value.as_slice()), source));
+            }
+        }
+
+        debug!(
+            logger, "eth_getStorageAt";
+            "address" => format!("{}", address),
+            "slot" => format!("{:x}", slot),
+            "block" => format!("{}", block_ptr)
+        );
+        let value = self
+            .storage_at(logger, address, slot, block_ptr.cheap_clone())
+            .compat()
+            .await?;
+
+        let _ = cache
+            .set_call(
+                logger,
+                req,
+                block_ptr,
+                call::Retval::Value(scalar::Bytes::from(value.as_bytes())),
+            )
+            .map_err(|e| {
+                error!(logger, "EthereumAdapter: call cache set error";
+                        "contract_address" => format!("{:?}", address),
+                        "error" => e.to_string())
+            });
+
+        Ok((value, call::Source::Rpc))
+    }
+
     async fn next_existing_ptr_to_number(
         &self,
         logger: &Logger,
@@ -1859,6 +2037,34 @@ pub(crate) async fn blocks_with_triggers(
         .boxed();
         trigger_futs.push(logs_future)
     }
+    // Scan for Transactions
+    if !filter.transaction.is_empty() {
+        let transactions_future = get_transactions_and_receipts(
+            &eth,
+            &logger,
+            chain_store.cheap_clone(),
+            subgraph_metrics.clone(),
+            from,
+            to,
+            &filter.transaction,
+        )
+        .boxed();
+        trigger_futs.push(transactions_future)
+    }
+    // Scan for Transfers
+    if !filter.transfer.is_empty() {
+        let transfers_future = get_transfers(
+            &eth,
+            &logger,
+            chain_store.cheap_clone(),
+            subgraph_metrics.clone(),
+            from,
+            to,
+            &filter.transfer,
+        )
+        .boxed();
+        trigger_futs.push(transfers_future)
+    }
     // Scan for Calls
     if !filter.call.is_empty() {
         let calls_future = eth
@@ -1967,40 +2173,64 @@ pub(crate) async fn get_calls(
     subgraph_metrics: Arc<SubgraphEthRpcMetrics>,
     capabilities: &NodeCapabilities,
     requires_traces: bool,
+    requires_transfers: bool,
     block: BlockFinality,
 ) -> Result<BlockFinality, Error> {
     // For final blocks, or nonfinal blocks where we already checked
-    // (`calls.is_some()`), do nothing; if we haven't checked for calls, do
-    // that now
+    // (`calls.is_some()` and `transfers.is_some()`), do nothing; if we
+    // haven't checked for calls or transfers, do that now
     match block {
         BlockFinality::Final(_)
         | BlockFinality::NonFinal(EthereumBlockWithCalls {
             ethereum_block: _,
             calls: Some(_),
+            transfers: Some(_),
         }) => Ok(block),
         BlockFinality::NonFinal(EthereumBlockWithCalls {
             ethereum_block,
-            calls: None,
+            calls,
+            transfers,
         }) => {
-            let calls = if !requires_traces || ethereum_block.transaction_receipts.is_empty() {
-                vec![]
-            } else {
-                client
-                    .rpc()?
-                    .cheapest_with(capabilities)
-                    .await?
-                    .calls_in_block(
-                        &logger,
-                        subgraph_metrics.clone(),
-                        BlockNumber::try_from(ethereum_block.block.number.unwrap().as_u64())
-                            .unwrap(),
-                        ethereum_block.block.hash.unwrap(),
-                    )
-                    .await?
+            let calls = match calls {
+                Some(calls) => calls,
+                None if !requires_traces || ethereum_block.transaction_receipts.is_empty() => {
+                    vec![]
+                }
+                None => {
+                    client
+                        .rpc()?
+                        .cheapest_with(capabilities)
+                        .await?
+                        .calls_in_block(
+                            &logger,
+                            subgraph_metrics.clone(),
+                            BlockNumber::try_from(ethereum_block.block.number.unwrap().as_u64())
+                                .unwrap(),
+                            ethereum_block.block.hash.unwrap(),
+                        )
+                        .await?
+                }
+            };
+            let transfers = match transfers {
+                Some(transfers) => transfers,
+                None if !requires_transfers => vec![],
+                None => {
+                    client
+                        .rpc()?
+                        .cheapest_with(capabilities)
+                        .await?
+                        .transfers_in_block(
+                            &logger,
+                            subgraph_metrics.clone(),
+                            &ethereum_block.block,
+                        )
+                        .await?
+                }
             };
             Ok(BlockFinality::NonFinal(EthereumBlockWithCalls {
                 ethereum_block,
                 calls: Some(calls),
+                transfers: Some(transfers),
             }))
         }
         BlockFinality::Ptr(_) => {
@@ -2028,6 +2258,55 @@ pub(crate) fn parse_log_triggers(
         .collect()
 }
 
+pub(crate) fn parse_transaction_triggers(
+    transaction_filter: &EthereumTransactionFilter,
+    block: &EthereumBlock,
+) -> anyhow::Result<Vec<EthereumTrigger>> {
+    if transaction_filter.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let receipts_by_hash: HashMap<H256, &Arc<TransactionReceipt>> = block
+        .transaction_receipts
+        .iter()
+        .map(|receipt| (receipt.transaction_hash, receipt))
+        .collect();
+
+    block
+        .block
+        .transactions
+        .iter()
+        .filter(|tx| transaction_filter.matches(tx))
+        .map(|tx| {
+            let receipt = receipts_by_hash
+                .get(&tx.hash)
+                .with_context(|| format!("Found no receipt for transaction {:x}", tx.hash))?;
+            Ok(EthereumTrigger::Transaction(
+                Arc::new(tx.clone()),
+                receipt.cheap_clone(),
+            ))
+        })
+        .collect()
+}
+
+pub(crate) fn parse_transfer_triggers(
+    transfer_filter: &EthereumTransferFilter,
+    block: &EthereumBlockWithCalls,
+) -> Vec<EthereumTrigger> {
+    if transfer_filter.is_empty() {
+        return vec![];
+    }
+
+    match &block.transfers {
+        Some(transfers) => transfers
+            .iter()
+            .filter(|transfer| transfer_filter.matches(transfer))
+            .map(|transfer| EthereumTrigger::Transfer(Arc::new(transfer.clone())))
+            .collect(),
+        None => vec![],
+    }
+}
+
 pub(crate) fn parse_call_triggers(
     call_filter: &EthereumCallFilter,
     block: &EthereumBlockWithCalls,
@@ -2608,6 +2887,113 @@ async fn get_logs_and_transactions(
     Ok(log_triggers)
 }
 
+/// Find the internal transfers in the block range that match
+/// `transfer_filter` by tracing each block that has transactions. At most
+/// `ETHEREUM_BLOCK_BATCH_SIZE` blocks are traced at the same time.
+async fn get_transfers(
+    adapter: &Arc<EthereumAdapter>,
+    logger: &Logger,
+    chain_store: Arc<dyn ChainStore>,
+    subgraph_metrics: Arc<SubgraphEthRpcMetrics>,
+    from: BlockNumber,
+    to: BlockNumber,
+    transfer_filter: &EthereumTransferFilter,
+) -> Result<Vec<EthereumTrigger>, anyhow::Error> {
+    let block_hashes: HashSet<H256> = adapter
+        .block_range_to_ptrs(logger.clone(), from, to)
+        .compat()
+        .await?
+        .iter()
+        .map(|ptr| ptr.hash_as_h256())
+        .collect();
+
+    let blocks: Vec<Arc<LightEthereumBlock>> = adapter
+        .load_blocks(logger.cheap_clone(), chain_store, block_hashes)
+        .await
+        .collect()
+        .compat()
+        .await?;
+
+    let transfers: Vec<Vec<EthereumTransfer>> = futures03::stream::iter(blocks.iter())
+        .map(|block| adapter.transfers_in_block(logger, subgraph_metrics.clone(), block))
+        .buffered(ENV_VARS.block_batch_size)
+        .try_collect()
+        .await?;
+
+    Ok(transfers
+        .into_iter()
+        .flatten()
+        .filter(|transfer| transfer_filter.matches(transfer))
+        .map(|transfer| EthereumTrigger::Transfer(Arc::new(transfer)))
+        .collect())
+}
+
+/// Finds the transactions in the block range that match the filter and
+/// fetches their receipts. Since transactions can't be filtered by the
+/// Ethereum node, this needs to load every block in the range.
+async fn get_transactions_and_receipts(
+    adapter: &Arc<EthereumAdapter>,
+    logger: &Logger,
+    chain_store: Arc<dyn ChainStore>,
+    subgraph_metrics: Arc<SubgraphEthRpcMetrics>,
+    from: BlockNumber,
+    to: BlockNumber,
+    transaction_filter: &EthereumTransactionFilter,
+) -> Result<Vec<EthereumTrigger>, anyhow::Error> {
+    let block_hashes: HashSet<H256> = adapter
+        .block_range_to_ptrs(logger.clone(), from, to)
+        .compat()
+        .await?
+        .iter()
+        .map(|ptr| ptr.hash_as_h256())
+        .collect();
+
+    let blocks: Vec<Arc<LightEthereumBlock>> = adapter
+        .load_blocks(logger.cheap_clone(), chain_store, block_hashes)
+        .await
+        .collect()
+        .compat()
+        .await?;
+
+    let transactions: Vec<Transaction> = blocks
+        .iter()
+        .flat_map(|block| block.transactions.iter())
+        .filter(|tx| transaction_filter.matches(tx))
+        .cloned()
+        .collect();
+
+    let transaction_hashes_by_block: HashMap<H256, HashSet<H256>> = transactions
+        .iter()
+        .filter_map(|tx| tx.block_hash.map(|block_hash| (block_hash, tx.hash)))
+        .fold(
+            HashMap::<H256, HashSet<H256>>::new(),
+            |mut acc, (block_hash, txn_hash)| {
+                acc.entry(block_hash).or_default().insert(txn_hash);
+                acc
+            },
+        );
+
+    // Obtain receipts externally
+    let transaction_receipts_by_hash = get_transaction_receipts_for_transaction_hashes(
+        adapter,
+        &transaction_hashes_by_block,
+        subgraph_metrics,
+        logger.cheap_clone(),
+    )
+    .await?;
+
+    transactions
+        .into_iter()
+        .map(|tx| {
+            let receipt = transaction_receipts_by_hash
+                .get(&tx.hash)
+                .cloned()
+                .with_context(|| format!("Found no receipt for transaction {:x}", tx.hash))?;
+            Ok(EthereumTrigger::Transaction(Arc::new(tx), receipt))
+        })
+        .collect()
+}
+
 /// Tries to retrive all transaction receipts for a set of transaction hashes.
 async fn get_transaction_receipts_for_transaction_hashes(
     adapter: &EthereumAdapter,
@@ -2691,19 +3077,23 @@ async fn get_transaction_receipts_for_transaction_hashes(
 #[cfg(test)]
 mod tests {
 
+    use crate::adapter::TransactionPredicate;
     use crate::trigger::{EthereumBlockTriggerType, EthereumTrigger};
 
     use super::{
-        check_block_receipt_support, parse_block_triggers, EthereumBlock, EthereumBlockFilter,
-        EthereumBlockWithCalls,
+        check_block_receipt_support, parse_block_triggers, parse_transaction_triggers,
+        parse_transfer_triggers, EthereumBlock, EthereumBlockFilter, EthereumBlockWithCalls,
+        EthereumTransactionFilter, EthereumTransferFilter,
     };
     use graph::blockchain::BlockPtr;
     use graph::prelude::ethabi::ethereum_types::U64;
     use graph::prelude::tokio::{self};
     use graph::prelude::web3::transports::test::TestTransport;
-    use graph::prelude::web3::types::{Address, Block, Bytes, H256};
+    use graph::prelude::web3::types::{
+        Address, Block, Bytes, Transaction, TransactionReceipt, H256,
+    };
     use graph::prelude::web3::Web3;
-    use graph::prelude::EthereumCall;
+    use graph::prelude::{EthereumCall, EthereumTransfer};
     use jsonrpc_core::serde_json::{self, Value};
     use std::collections::HashSet;
     use std::iter::FromIterator;
@@ -2725,6 +3115,7 @@ mod tests {
                 input: bytes(vec![1; 36]),
                 ..Default::default()
             }]),
+            transfers: None,
         };
 
         assert_eq!(
@@ -2891,6 +3282,7 @@ mod tests {
                 input: bytes(vec![1; 36]),
                 ..Default::default()
             }]),
+            transfers: None,
         };
 
         assert_eq!(
@@ -2923,6 +3315,7 @@ mod tests {
                 input: bytes(vec![1; 36]),
                 ..Default::default()
             }]),
+            transfers: None,
         };
 
         assert_eq!(
@@ -2942,6 +3335,114 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_transaction_triggers_matching_filter() {
+        let tx = |id: u8, from: Address, to: Address| Transaction {
+            hash: hash(id),
+            from: Some(from),
+            to: Some(to),
+            ..Default::default()
+        };
+        let receipt = |id: u8| {
+            Arc::new(TransactionReceipt {
+                transaction_hash: hash(id),
+                ..Default::default()
+            })
+        };
+
+        let block = EthereumBlock {
+            block: Arc::new(Block {
+                hash: Some(hash(2)),
+                number: Some(U64::from(2)),
+                transactions: vec![
+                    tx(10, address(1), address(2)),
+                    tx(11, address(3), address(4)),
+                    tx(12, address(2), address(1)),
+                ],
+                ..Default::default()
+            }),
+            transaction_receipts: vec![receipt(10), receipt(11), receipt(12)],
+        };
+        let filter = EthereumTransactionFilter {
+            predicates: HashSet::from_iter(vec![TransactionPredicate {
+                to: Some(address(2)),
+                ..Default::default()
+            }]),
+        };
+
+        assert_eq!(
+            vec![EthereumTrigger::Transaction(
+                Arc::new(tx(10, address(1), address(2))),
+                receipt(10)
+            )],
+            parse_transaction_triggers(&filter, &block).unwrap(),
+            "only the transaction to address 2 matches"
+        );
+
+        assert!(
+            parse_transaction_triggers(&EthereumTransactionFilter::default(), &block)
+                .unwrap()
+                .is_empty(),
+            "an empty filter matches no transactions"
+        );
+
+        let block_without_receipts = EthereumBlock {
+            block: block.block.clone(),
+            transaction_receipts: vec![],
+        };
+        assert!(
+            parse_transaction_triggers(&filter, &block_without_receipts).is_err(),
+            "matching transactions need a receipt"
+        );
+    }
+
+    #[test]
+    fn parse_transfer_triggers_matching_filter() {
+        let transfer = |index: u64, from: Address, to: Address| EthereumTransfer {
+            from,
+            to,
+            block_number: 2,
+            block_hash: hash(2),
+            transaction_hash: hash(10),
+            transfer_index: index,
+            ..Default::default()
+        };
+
+        let mut block = EthereumBlockWithCalls {
+            ethereum_block: EthereumBlock::default(),
+            calls: Some(vec![]),
+            transfers: Some(vec![
+                transfer(0, address(1), address(2)),
+                transfer(1, address(3), address(4)),
+                transfer(2, address(2), address(5)),
+            ]),
+        };
+        let filter = EthereumTransferFilter {
+            addresses: HashSet::from_iter(vec![address(2)]),
+            match_all: false,
+        };
+
+        assert_eq!(
+            vec![
+                EthereumTrigger::Transfer(Arc::new(transfer(0, address(1), address(2)))),
+                EthereumTrigger::Transfer(Arc::new(transfer(2, address(2), address(5)))),
+            ],
+            parse_transfer_triggers(&filter, &block),
+            "only the transfers from or to address 2 match"
+        );
+
+        assert!(
+            parse_transfer_triggers(&EthereumTransferFilter::default(), &block).is_empty(),
+            "an empty filter matches no transfers"
+        );
+
+        block.transfers = None;
+        assert!(
+            parse_transfer_triggers(&filter, &block).is_empty(),
+            "blocks without transfers have no transfer triggers"
+        );
+    }
+
     fn address(id: u64) -> Address {
         Address::from_low_u64_be(id)
     }
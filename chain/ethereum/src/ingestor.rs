@@ -185,6 +185,7 @@ impl PollingBlockIngestor {
         let ethereum_block = BlockFinality::NonFinal(EthereumBlockWithCalls {
             ethereum_block,
             calls: None,
+            transfers: None,
         });
 
         // Store it in the database and try to advance the chain head pointer
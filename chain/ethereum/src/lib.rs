@@ -1,5 +1,6 @@
 mod adapter;
 mod buffered_call_cache;
+mod call_tracer;
 mod capabilities;
 pub mod codec;
 mod data_source;
@@ -1,6 +1,7 @@
-use super::runtime_adapter::UnresolvedContractCall;
+use super::runtime_adapter::{StorageAtCall, UnresolvedContractCall};
 use crate::trigger::{
     EthereumBlockData, EthereumCallData, EthereumEventData, EthereumTransactionData,
+    EthereumTransactionTriggerData, EthereumTransferData,
 };
 use graph::{
     prelude::{
@@ -177,6 +178,98 @@ impl FromAscObj<AscUnresolvedContractCall> for UnresolvedContractCall {
     }
 }
 
+pub struct AscUnresolvedContractCallArray(Array<AscPtr<AscUnresolvedContractCall_0_0_4>>);
+
+impl AscType for AscUnresolvedContractCallArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl FromAscObj<AscUnresolvedContractCallArray> for Vec<UnresolvedContractCall> {
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        array: AscUnresolvedContractCallArray,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        Vec::from_asc_obj(array.0, heap, gas, depth)
+    }
+}
+
+impl AscIndexId for AscUnresolvedContractCallArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArraySmartContractCall;
+}
+
+/// The results of `ethereum.multicall`; reverted calls are `null`
+pub struct AscContractCallResultArray(Array<AscPtr<Array<AscPtr<AscEnum<EthereumValueKind>>>>>);
+
+impl AscType for AscContractCallResultArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl ToAscObj<AscContractCallResultArray> for Vec<Option<Vec<ethabi::Token>>> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscContractCallResultArray, HostExportError> {
+        let results = self
+            .iter()
+            .map(|tokens| match tokens {
+                Some(tokens) => asc_new(heap, tokens.as_slice(), gas),
+                None => Ok(AscPtr::null()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscContractCallResultArray(Array::new(&results, heap, gas)?))
+    }
+}
+
+impl AscIndexId for AscContractCallResultArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::ArrayArrayEthereumValue;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscStorageAtCall {
+    pub address: AscPtr<AscAddress>,
+    pub slot: AscPtr<AscH256>,
+}
+
+impl AscIndexId for AscStorageAtCall {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumStorageAtCall;
+}
+
+impl FromAscObj<AscStorageAtCall> for StorageAtCall {
+    fn from_asc_obj<H: AscHeap + ?Sized>(
+        asc_call: AscStorageAtCall,
+        heap: &H,
+        gas: &GasCounter,
+        depth: usize,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(StorageAtCall {
+            address: asc_get(heap, asc_call.address, gas, depth)?,
+            slot: asc_get(heap, asc_call.slot, gas, depth)?,
+        })
+    }
+}
+
 #[repr(C)]
 #[derive(AscType)]
 pub(crate) struct AscEthereumBlock {
@@ -371,6 +464,36 @@ impl AscIndexId for AscEthereumEvent_0_0_7<AscEthereumTransaction_0_0_6, AscEthe
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumEvent;
 }
 
+/// The argument passed to transaction handlers, introduced with transaction
+/// handlers and only available for API Version 0.0.7 and later.
+#[repr(C)]
+#[derive(AscType)]
+pub(crate) struct AscEthereumTransactionWithReceipt {
+    pub block: AscPtr<AscEthereumBlock_0_0_6>,
+    pub transaction: AscPtr<AscEthereumTransaction_0_0_6>,
+    pub receipt: AscPtr<AscEthereumTransactionReceipt>,
+}
+
+impl AscIndexId for AscEthereumTransactionWithReceipt {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumTransactionWithReceipt;
+}
+
+/// The argument passed to transfer handlers, available for API Version
+/// 0.0.7 and later.
+#[repr(C)]
+#[derive(AscType)]
+pub(crate) struct AscEthereumTransfer {
+    pub from: AscPtr<AscAddress>,
+    pub to: AscPtr<AscAddress>,
+    pub value: AscPtr<AscBigInt>,
+    pub block: AscPtr<AscEthereumBlock_0_0_6>,
+    pub transaction: AscPtr<AscEthereumTransaction_0_0_6>,
+}
+
+impl AscIndexId for AscEthereumTransfer {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::EthereumTransfer;
+}
+
 #[repr(C)]
 #[derive(AscType)]
 pub(crate) struct AscLogParam {
@@ -718,6 +841,36 @@ impl ToAscObj<AscEthereumTransactionReceipt> for &TransactionReceipt {
     }
 }
 
+impl<'a> ToAscObj<AscEthereumTransactionWithReceipt> for EthereumTransactionTriggerData<'a> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEthereumTransactionWithReceipt, HostExportError> {
+        Ok(AscEthereumTransactionWithReceipt {
+            block: asc_new(heap, &self.block, gas)?,
+            transaction: asc_new(heap, &self.transaction, gas)?,
+            receipt: asc_new(heap, &self.receipt, gas)?,
+        })
+    }
+}
+
+impl<'a> ToAscObj<AscEthereumTransfer> for EthereumTransferData<'a> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscEthereumTransfer, HostExportError> {
+        Ok(AscEthereumTransfer {
+            from: asc_new(heap, &self.transfer.from, gas)?,
+            to: asc_new(heap, &self.transfer.to, gas)?,
+            value: asc_new(heap, &BigInt::from_unsigned_u256(&self.transfer.value), gas)?,
+            block: asc_new(heap, &self.block, gas)?,
+            transaction: asc_new(heap, &self.transaction, gas)?,
+        })
+    }
+}
+
 impl<'a> ToAscObj<AscEthereumCall> for EthereumCallData<'a> {
     fn to_asc_obj<H: AscHeap + ?Sized>(
         &self,
@@ -11,11 +11,11 @@ use graph::blockchain::ChainIdentifier;
 use graph::components::subgraph::HostMetrics;
 use graph::data::store::ethereum::call;
 use graph::data::store::scalar::BigInt;
-use graph::data::subgraph::API_VERSION_0_0_9;
+use graph::data::subgraph::{API_VERSION_0_0_10, API_VERSION_0_0_9};
 use graph::data_source;
 use graph::data_source::common::{ContractCall, MappingABI};
 use graph::futures03::compat::Future01CompatExt;
-use graph::prelude::web3::types::H160;
+use graph::prelude::web3::types::{H160, H256};
 use graph::runtime::gas::Gas;
 use graph::runtime::{AscIndexId, IndexForAscTypeId};
 use graph::slog::debug;
@@ -30,10 +30,15 @@ use graph::{
     semver::Version,
     slog::Logger,
 };
-use graph_runtime_wasm::asc_abi::class::{AscBigInt, AscEnumArray, AscWrapped, EthereumValueKind};
+use graph_runtime_wasm::asc_abi::class::{
+    AscBigInt, AscEnumArray, AscWrapped, EthereumValueKind, Uint8Array,
+};
 use itertools::Itertools;
 
-use super::abi::{AscUnresolvedContractCall, AscUnresolvedContractCall_0_0_4};
+use super::abi::{
+    AscContractCallResultArray, AscStorageAtCall, AscUnresolvedContractCall,
+    AscUnresolvedContractCallArray, AscUnresolvedContractCall_0_0_4,
+};
 
 /// Gas limit for `eth_call`. The value of 50_000_000 is a protocol-wide parameter so this
 /// should be changed only for debugging purposes and never on an indexer in the network. This
@@ -61,6 +66,9 @@ pub const ETH_GET_BALANCE: Gas = Gas::new(5_000_000_000);
 // TODO: Determine the appropriate gas cost for `ETH_HAS_CODE`, initially aligned with `ETHEREUM_CALL`.
 pub const ETH_HAS_CODE: Gas = Gas::new(5_000_000_000);
 
+// TODO: Determine the appropriate gas cost for `ETH_GET_STORAGE_AT`, initially aligned with `ETHEREUM_CALL`.
+pub const ETH_GET_STORAGE_AT: Gas = Gas::new(5_000_000_000);
+
 pub struct RuntimeAdapter {
     pub eth_adapters: Arc<EthereumNetworkAdapters>,
     pub call_cache: Arc<dyn EthereumCallCache>,
@@ -142,6 +150,46 @@ impl blockchain::RuntimeAdapter<Chain> for RuntimeAdapter {
                         }
                     }),
                 },
+                HostFn {
+                    name: "ethereum.getStorageAt",
+                    func: Arc::new({
+                        let eth_adapters = eth_adapters.clone();
+                        let call_cache = call_cache.clone();
+                        move |ctx, wasm_ptr| {
+                            let eth_adapter =
+                                eth_adapters.unverified_cheapest_with(&NodeCapabilities {
+                                    archive,
+                                    traces: false,
+                                })?;
+                            eth_get_storage_at(&eth_adapter, call_cache.clone(), ctx, wasm_ptr)
+                                .map(|ptr| ptr.wasm_ptr())
+                        }
+                    }),
+                },
+                HostFn {
+                    name: "ethereum.multicall",
+                    func: Arc::new({
+                        let eth_adapters = eth_adapters.clone();
+                        let call_cache = call_cache.clone();
+                        let abis = abis.clone();
+                        move |ctx, wasm_ptr| {
+                            let eth_adapter =
+                                eth_adapters.call_or_cheapest(Some(&NodeCapabilities {
+                                    archive,
+                                    traces: false,
+                                }))?;
+                            ethereum_multicall(
+                                &eth_adapter,
+                                call_cache.clone(),
+                                ctx,
+                                wasm_ptr,
+                                &abis,
+                                eth_call_gas,
+                            )
+                            .map(|ptr| ptr.wasm_ptr())
+                        }
+                    }),
+                },
             ]
         }
 
@@ -182,6 +230,7 @@ fn ethereum_call(
 ) -> Result<AscEnumArray<EthereumValueKind>, HostExportError> {
     ctx.gas
         .consume_host_fn_with_metrics(ETHEREUM_CALL, "ethereum_call")?;
+    ctx.metrics.track_eth_call();
 
     // For apiVersion >= 0.0.4 the call passed from the mapping includes the
     // function signature; subgraphs using an apiVersion < 0.0.4 don't pass
@@ -208,6 +257,45 @@ fn ethereum_call(
     }
 }
 
+/// function ethereum.multicall(calls: Array<SmartContractCall>): Array<Array<Token> | null>
+fn ethereum_multicall(
+    eth_adapter: &EthereumAdapter,
+    call_cache: Arc<dyn EthereumCallCache>,
+    ctx: HostFnCtx,
+    wasm_ptr: u32,
+    abis: &[Arc<MappingABI>],
+    eth_call_gas: Option<u32>,
+) -> Result<AscPtr<AscContractCallResultArray>, HostExportError> {
+    if ctx.heap.api_version() < API_VERSION_0_0_10 {
+        return Err(HostExportError::Deterministic(anyhow!(
+            "ethereum.multicall call is not supported before API version 0.0.10"
+        )));
+    }
+
+    let calls: Vec<UnresolvedContractCall> =
+        asc_get::<_, AscUnresolvedContractCallArray, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?;
+
+    // Batching calls saves round trips, but not work on the Ethereum node,
+    // so each call costs as much as a call through `ethereum.call`
+    for _ in &calls {
+        ctx.gas
+            .consume_host_fn_with_metrics(ETHEREUM_CALL, "ethereum_multicall")?;
+        ctx.metrics.track_eth_call();
+    }
+
+    let results = eth_multicall(
+        eth_adapter,
+        call_cache,
+        &ctx.logger,
+        &ctx.block_ptr,
+        calls,
+        abis,
+        eth_call_gas,
+        ctx.metrics.cheap_clone(),
+    )?;
+    Ok(asc_new(ctx.heap, &results, &ctx.gas)?)
+}
+
 fn eth_get_balance(
     eth_adapter: &EthereumAdapter,
     ctx: HostFnCtx<'_>,
@@ -282,6 +370,46 @@ fn eth_has_code(
     }
 }
 
+/// function ethereum.getStorageAt(call: StorageAtCall): Bytes
+fn eth_get_storage_at(
+    eth_adapter: &EthereumAdapter,
+    call_cache: Arc<dyn EthereumCallCache>,
+    ctx: HostFnCtx<'_>,
+    wasm_ptr: u32,
+) -> Result<AscPtr<Uint8Array>, HostExportError> {
+    ctx.gas
+        .consume_host_fn_with_metrics(ETH_GET_STORAGE_AT, "eth_get_storage_at")?;
+
+    if ctx.heap.api_version() < API_VERSION_0_0_10 {
+        return Err(HostExportError::Deterministic(anyhow!(
+            "ethereum.getStorageAt call is not supported before API version 0.0.10"
+        )));
+    }
+
+    let logger = &ctx.logger;
+    let block_ptr = &ctx.block_ptr;
+
+    let call: StorageAtCall =
+        asc_get::<_, AscStorageAtCall, _>(ctx.heap, wasm_ptr.into(), &ctx.gas, 0)?;
+
+    let result = graph::block_on(eth_adapter.get_storage_at(
+        logger,
+        call.address,
+        call.slot,
+        block_ptr.clone(),
+        call_cache,
+    ));
+
+    match result {
+        Ok((value, _)) => Ok(asc_new(ctx.heap, &value, &ctx.gas)?),
+        // Retry on any kind of error
+        Err(EthereumRpcError::Web3Error(e)) => Err(HostExportError::PossibleReorg(e.into())),
+        Err(EthereumRpcError::Timeout) => Err(HostExportError::PossibleReorg(
+            EthereumRpcError::Timeout.into(),
+        )),
+    }
+}
+
 /// Returns `Ok(None)` if the call was reverted.
 fn eth_call(
     eth_adapter: &EthereumAdapter,
@@ -308,35 +436,7 @@ fn eth_call(
 
     let start_time = Instant::now();
 
-    // Obtain the path to the contract ABI
-    let abi = abis
-        .iter()
-        .find(|abi| abi.name == unresolved_call.contract_name)
-        .with_context(|| {
-            format!(
-                "Could not find ABI for contract \"{}\", try adding it to the 'abis' section \
-                     of the subgraph manifest",
-                unresolved_call.contract_name
-            )
-        })
-        .map_err(HostExportError::Deterministic)?;
-
-    let function = abi
-        .function(
-            &unresolved_call.contract_name,
-            &unresolved_call.function_name,
-            unresolved_call.function_signature.as_deref(),
-        )
-        .map_err(HostExportError::Deterministic)?;
-
-    let call = ContractCall {
-        contract_name: unresolved_call.contract_name.clone(),
-        address: unresolved_call.contract_address,
-        block_ptr: block_ptr.cheap_clone(),
-        function: function.clone(),
-        args: unresolved_call.function_args.clone(),
-        gas: eth_call_gas,
-    };
+    let call = resolve_call(&unresolved_call, abis, block_ptr, eth_call_gas)?;
 
     // Run Ethereum call in tokio runtime
     let logger1 = logger.clone();
@@ -398,6 +498,116 @@ fn eth_call(
     result
 }
 
+/// Runs `unresolved_calls` in one batch. The result for each call is
+/// `None` if that call was reverted.
+fn eth_multicall(
+    eth_adapter: &EthereumAdapter,
+    call_cache: Arc<dyn EthereumCallCache>,
+    logger: &Logger,
+    block_ptr: &BlockPtr,
+    unresolved_calls: Vec<UnresolvedContractCall>,
+    abis: &[Arc<MappingABI>],
+    eth_call_gas: Option<u32>,
+    metrics: Arc<HostMetrics>,
+) -> Result<Vec<Option<Vec<Token>>>, HostExportError> {
+    let start_time = Instant::now();
+
+    let calls = unresolved_calls
+        .iter()
+        .map(|call| resolve_call(call, abis, block_ptr, eth_call_gas))
+        .collect::<Result<Vec<_>, _>>()?;
+    let calls: Vec<_> = calls.iter().collect();
+
+    // The adapter logs which of the calls failed
+    let results = graph::block_on(eth_adapter.contract_calls(logger, &calls, call_cache)).map_err(
+        |e| match e {
+            // As with single calls, any error reported by the Ethereum node
+            // and timeouts could be caused by a reorg
+            ContractCallError::Web3Error(e) => HostExportError::PossibleReorg(anyhow::anyhow!(
+                "Ethereum node returned an error during a multicall of {} calls: {}",
+                calls.len(),
+                e
+            )),
+            ContractCallError::Timeout => HostExportError::PossibleReorg(anyhow::anyhow!(
+                "Ethereum node did not respond during a multicall of {} calls",
+                calls.len()
+            )),
+            e => HostExportError::Unknown(anyhow::anyhow!(
+                "Failed to make a multicall of {} calls: {}",
+                calls.len(),
+                e
+            )),
+        },
+    )?;
+
+    let elapsed = start_time.elapsed();
+
+    Ok(unresolved_calls
+        .iter()
+        .zip(results)
+        .map(|(unresolved_call, (result, source))| {
+            if source.observe() {
+                metrics.observe_eth_call_execution_time(
+                    elapsed.as_secs_f64(),
+                    &unresolved_call.contract_name,
+                    &unresolved_call.function_name,
+                );
+            }
+
+            debug!(logger, "Contract call finished";
+                  "address" => format!("0x{:x}", &unresolved_call.contract_address),
+                  "contract" => &unresolved_call.contract_name,
+                  "signature" => &unresolved_call.function_signature,
+                  "batch_size" => calls.len(),
+                  "time_ms" => format!("{}ms", elapsed.as_millis()),
+                  "reverted" => result.is_none(),
+                  "block_hash" => block_ptr.hash_hex(),
+                  "block_number" => block_ptr.block_number(),
+                  "source" => source.to_string());
+
+            result
+        })
+        .collect())
+}
+
+/// Look up the ABI and function for `unresolved_call`
+fn resolve_call(
+    unresolved_call: &UnresolvedContractCall,
+    abis: &[Arc<MappingABI>],
+    block_ptr: &BlockPtr,
+    eth_call_gas: Option<u32>,
+) -> Result<ContractCall, HostExportError> {
+    // Obtain the path to the contract ABI
+    let abi = abis
+        .iter()
+        .find(|abi| abi.name == unresolved_call.contract_name)
+        .with_context(|| {
+            format!(
+                "Could not find ABI for contract \"{}\", try adding it to the 'abis' section \
+                     of the subgraph manifest",
+                unresolved_call.contract_name
+            )
+        })
+        .map_err(HostExportError::Deterministic)?;
+
+    let function = abi
+        .function(
+            &unresolved_call.contract_name,
+            &unresolved_call.function_name,
+            unresolved_call.function_signature.as_deref(),
+        )
+        .map_err(HostExportError::Deterministic)?;
+
+    Ok(ContractCall {
+        contract_name: unresolved_call.contract_name.clone(),
+        address: unresolved_call.contract_address,
+        block_ptr: block_ptr.cheap_clone(),
+        function: function.clone(),
+        args: unresolved_call.function_args.clone(),
+        gas: eth_call_gas,
+    })
+}
+
 #[derive(Clone, Debug)]
 pub struct UnresolvedContractCall {
     pub contract_name: String,
@@ -410,3 +620,9 @@ pub struct UnresolvedContractCall {
 impl AscIndexId for AscUnresolvedContractCall {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SmartContractCall;
 }
+
+#[derive(Clone, Debug)]
+pub struct StorageAtCall {
+    pub address: Address,
+    pub slot: H256,
+}
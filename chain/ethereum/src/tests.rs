@@ -3,8 +3,8 @@ use std::sync::Arc;
 use graph::{
     blockchain::{block_stream::BlockWithTriggers, BlockPtr, Trigger},
     prelude::{
-        web3::types::{Address, Bytes, Log, H160, H256, U64},
-        EthereumCall, LightEthereumBlock,
+        web3::types::{Address, Bytes, Log, Transaction, TransactionReceipt, H160, H256, U64},
+        EthereumCall, EthereumTransfer, LightEthereumBlock,
     },
     slog::{self, o, Logger},
 };
@@ -212,3 +212,164 @@ fn test_trigger_dedup() {
 
     assert_eq!(block_with_triggers.trigger_data, expected);
 }
+
+#[test]
+fn test_transaction_trigger_ordering() {
+    let block1 = EthereumTrigger::Block(
+        BlockPtr::from((H256::random(), 1u64)),
+        EthereumBlockTriggerType::End,
+    );
+
+    let mut call1 = EthereumCall::default();
+    call1.transaction_index = 1;
+    let call1 = EthereumTrigger::Call(Arc::new(call1));
+
+    fn create_transaction(tx_index: u64) -> EthereumTrigger {
+        let hash = H256::from_low_u64_be(tx_index);
+        let tx = Transaction {
+            hash,
+            transaction_index: Some(tx_index.into()),
+            ..Default::default()
+        };
+        let receipt = TransactionReceipt {
+            transaction_hash: hash,
+            transaction_index: tx_index.into(),
+            block_hash: Some(H256::zero()),
+            block_number: Some(U64::zero()),
+            ..Default::default()
+        };
+        EthereumTrigger::Transaction(Arc::new(tx), Arc::new(receipt))
+    }
+
+    fn create_log(tx_index: u64, log_index: u64) -> Arc<Log> {
+        Arc::new(Log {
+            address: H160::default(),
+            topics: vec![],
+            data: Bytes::default(),
+            block_hash: Some(H256::zero()),
+            block_number: Some(U64::zero()),
+            transaction_hash: Some(H256::from_low_u64_be(tx_index)),
+            transaction_index: Some(tx_index.into()),
+            log_index: Some(log_index.into()),
+            transaction_log_index: Some(log_index.into()),
+            log_type: Some("".into()),
+            removed: Some(false),
+        })
+    }
+
+    let tx1 = create_transaction(1);
+    let tx2 = create_transaction(2);
+    // duplicate tx2
+    let tx3 = tx2.clone();
+
+    let log1 = EthereumTrigger::Log(LogRef::FullLog(create_log(1, 0), None));
+    let log2 = EthereumTrigger::Log(LogRef::FullLog(create_log(2, 1), None));
+
+    let triggers = vec![
+        block1.clone(),
+        tx3,
+        tx2.clone(),
+        tx1.clone(),
+        log2.clone(),
+        call1.clone(),
+        log1.clone(),
+    ];
+
+    let logger = Logger::root(slog::Discard, o!());
+
+    let mut b: LightEthereumBlock = Default::default();
+    b.number = Some(Default::default());
+    b.hash = Some(Default::default());
+
+    // Transaction triggers come after the events and calls of their
+    // transaction, and duplicates are removed
+    let block_with_triggers = BlockWithTriggers::<crate::Chain>::new(
+        BlockFinality::Final(Arc::new(b)),
+        triggers,
+        &logger,
+    );
+
+    let expected = vec![log1, call1, tx1, log2, tx2, block1]
+        .into_iter()
+        .map(|t| Trigger::Chain(t))
+        .collect::<Vec<_>>();
+
+    assert_eq!(block_with_triggers.trigger_data, expected);
+}
+
+#[test]
+fn test_transfer_trigger_ordering() {
+    let block1 = EthereumTrigger::Block(
+        BlockPtr::from((H256::random(), 1u64)),
+        EthereumBlockTriggerType::End,
+    );
+
+    let mut call1 = EthereumCall::default();
+    call1.transaction_index = 1;
+    let call1 = EthereumTrigger::Call(Arc::new(call1));
+
+    let mut call2 = EthereumCall::default();
+    call2.transaction_index = 2;
+    let call2 = EthereumTrigger::Call(Arc::new(call2));
+
+    fn create_transfer(tx_index: u64, transfer_index: u64) -> EthereumTrigger {
+        EthereumTrigger::Transfer(Arc::new(EthereumTransfer {
+            transaction_hash: H256::from_low_u64_be(tx_index),
+            transaction_index: tx_index,
+            transfer_index,
+            ..Default::default()
+        }))
+    }
+
+    let tx1 = {
+        let hash = H256::from_low_u64_be(1);
+        let tx = Transaction {
+            hash,
+            transaction_index: Some(1.into()),
+            ..Default::default()
+        };
+        let receipt = TransactionReceipt {
+            transaction_hash: hash,
+            transaction_index: 1.into(),
+            block_hash: Some(H256::zero()),
+            block_number: Some(U64::zero()),
+            ..Default::default()
+        };
+        EthereumTrigger::Transaction(Arc::new(tx), Arc::new(receipt))
+    };
+
+    let transfer1 = create_transfer(1, 0);
+    let transfer2 = create_transfer(1, 1);
+    let transfer3 = create_transfer(2, 0);
+
+    let triggers = vec![
+        block1.clone(),
+        transfer3.clone(),
+        tx1.clone(),
+        transfer2.clone(),
+        call2.clone(),
+        transfer1.clone(),
+        call1.clone(),
+    ];
+
+    let logger = Logger::root(slog::Discard, o!());
+
+    let mut b: LightEthereumBlock = Default::default();
+    b.number = Some(Default::default());
+    b.hash = Some(Default::default());
+
+    // Transfers come after the calls of their transaction, but before the
+    // transaction trigger itself
+    let block_with_triggers = BlockWithTriggers::<crate::Chain>::new(
+        BlockFinality::Final(Arc::new(b)),
+        triggers,
+        &logger,
+    );
+
+    let expected = vec![call1, transfer1, transfer2, tx1, call2, transfer3, block1]
+        .into_iter()
+        .map(|t| Trigger::Chain(t))
+        .collect::<Vec<_>>();
+
+    assert_eq!(block_with_triggers.trigger_data, expected);
+}
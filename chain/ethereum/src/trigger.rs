@@ -17,7 +17,7 @@ use graph::prelude::web3::types::Transaction;
 use graph::prelude::web3::types::TransactionReceipt;
 use graph::prelude::BlockNumber;
 use graph::prelude::BlockPtr;
-use graph::prelude::{CheapClone, EthereumCall};
+use graph::prelude::{CheapClone, EthereumCall, EthereumTransfer};
 use graph::runtime::asc_new;
 use graph::runtime::gas::GasCounter;
 use graph::runtime::AscHeap;
@@ -25,6 +25,7 @@ use graph::runtime::AscPtr;
 use graph::runtime::HostExportError;
 use graph::semver::Version;
 use graph_runtime_wasm::module::ToAscPtr;
+use graph_runtime_wasm::native_abi::{Encode, Encoder};
 use std::{cmp::Ordering, sync::Arc};
 
 use crate::runtime::abi::AscEthereumBlock;
@@ -33,9 +34,11 @@ use crate::runtime::abi::AscEthereumCall;
 use crate::runtime::abi::AscEthereumCall_0_0_3;
 use crate::runtime::abi::AscEthereumEvent;
 use crate::runtime::abi::AscEthereumEvent_0_0_7;
+use crate::runtime::abi::AscEthereumTransactionWithReceipt;
 use crate::runtime::abi::AscEthereumTransaction_0_0_1;
 use crate::runtime::abi::AscEthereumTransaction_0_0_2;
 use crate::runtime::abi::AscEthereumTransaction_0_0_6;
+use crate::runtime::abi::AscEthereumTransfer;
 
 // ETHDEP: This should be defined in only one place.
 type LightEthereumBlock = Block<Transaction>;
@@ -61,6 +64,16 @@ pub enum MappingTrigger {
     Block {
         block: Arc<LightEthereumBlock>,
     },
+    Transaction {
+        block: Arc<LightEthereumBlock>,
+        transaction: Arc<Transaction>,
+        receipt: Arc<TransactionReceipt>,
+    },
+    Transfer {
+        block: Arc<LightEthereumBlock>,
+        transaction: Arc<Transaction>,
+        transfer: Arc<EthereumTransfer>,
+    },
 }
 
 impl MappingTriggerTrait for MappingTrigger {
@@ -69,6 +82,8 @@ impl MappingTriggerTrait for MappingTrigger {
             MappingTrigger::Log { log, .. } => log.transaction_hash,
             MappingTrigger::Call { call, .. } => call.transaction_hash,
             MappingTrigger::Block { .. } => None,
+            MappingTrigger::Transaction { transaction, .. } => Some(transaction.hash),
+            MappingTrigger::Transfer { transfer, .. } => Some(transfer.transaction_hash),
         };
 
         match transaction_id {
@@ -95,6 +110,13 @@ impl std::fmt::Debug for MappingTrigger {
                 _outputs: Vec<LogParam>,
             },
             Block,
+            Transaction {
+                _transaction: Arc<Transaction>,
+                _receipt: Arc<TransactionReceipt>,
+            },
+            Transfer {
+                _transfer: Arc<EthereumTransfer>,
+            },
         }
 
         let trigger_without_block = match self {
@@ -123,6 +145,21 @@ impl std::fmt::Debug for MappingTrigger {
                 _outputs: outputs.clone(),
             },
             MappingTrigger::Block { block: _ } => MappingTriggerWithoutBlock::Block,
+            MappingTrigger::Transaction {
+                block: _,
+                transaction,
+                receipt,
+            } => MappingTriggerWithoutBlock::Transaction {
+                _transaction: transaction.cheap_clone(),
+                _receipt: receipt.cheap_clone(),
+            },
+            MappingTrigger::Transfer {
+                block: _,
+                transaction: _,
+                transfer,
+            } => MappingTriggerWithoutBlock::Transfer {
+                _transfer: transfer.cheap_clone(),
+            },
         };
 
         write!(f, "{:?}", trigger_without_block)
@@ -218,8 +255,102 @@ impl ToAscPtr for MappingTrigger {
                     asc_new::<AscEthereumBlock, _, _>(heap, &block, gas)?.erase()
                 }
             }
+            // Transaction handlers require API Version 0.0.7 or later, which is
+            // checked when the data source is validated
+            MappingTrigger::Transaction {
+                block,
+                transaction,
+                receipt,
+            } => {
+                let data = EthereumTransactionTriggerData::new(&block, &transaction, &receipt);
+                asc_new::<AscEthereumTransactionWithReceipt, _, _>(heap, &data, gas)?.erase()
+            }
+            // Like transaction handlers, transfer handlers require API
+            // Version 0.0.7 or later
+            MappingTrigger::Transfer {
+                block,
+                transaction,
+                transfer,
+            } => {
+                let data = EthereumTransferData::new(&block, &transaction, &transfer);
+                asc_new::<AscEthereumTransfer, _, _>(heap, &data, gas)?.erase()
+            }
         })
     }
+
+    /// Every trigger starts with a tag for its kind, followed by the block
+    /// and, except for block triggers, the transaction
+    fn to_native_bytes(self) -> Result<Vec<u8>, HostExportError> {
+        const LOG: u8 = 0;
+        const CALL: u8 = 1;
+        const BLOCK: u8 = 2;
+        const TRANSACTION: u8 = 3;
+        const TRANSFER: u8 = 4;
+
+        let mut enc = Encoder::new();
+        match self {
+            MappingTrigger::Log {
+                block,
+                transaction,
+                log,
+                ..
+            } => {
+                enc.put(&LOG)
+                    .put(&EthereumBlockData::from(block.as_ref()))
+                    .put(&EthereumTransactionData::new(&transaction))
+                    .put(&log.address)
+                    .put(&log.topics)
+                    .put(&log.data)
+                    .put(log.log_index.as_ref().unwrap_or(&U256_DEFAULT));
+            }
+            MappingTrigger::Call {
+                block,
+                transaction,
+                call,
+                ..
+            } => {
+                enc.put(&CALL)
+                    .put(&EthereumBlockData::from(block.as_ref()))
+                    .put(&EthereumTransactionData::new(&transaction))
+                    .put(&call.from)
+                    .put(&call.to)
+                    .put(&call.value)
+                    .put(&call.input)
+                    .put(&call.output);
+            }
+            MappingTrigger::Block { block } => {
+                enc.put(&BLOCK)
+                    .put(&EthereumBlockData::from(block.as_ref()));
+            }
+            MappingTrigger::Transaction {
+                block,
+                transaction,
+                receipt,
+            } => {
+                enc.put(&TRANSACTION)
+                    .put(&EthereumBlockData::from(block.as_ref()))
+                    .put(&EthereumTransactionData::new(&transaction))
+                    .put(&receipt.status)
+                    .put(&receipt.gas_used)
+                    .put(&receipt.cumulative_gas_used)
+                    .put(&receipt.contract_address);
+            }
+            MappingTrigger::Transfer {
+                block,
+                transaction,
+                transfer,
+            } => {
+                enc.put(&TRANSFER)
+                    .put(&EthereumBlockData::from(block.as_ref()))
+                    .put(&EthereumTransactionData::new(&transaction))
+                    .put(&transfer.from)
+                    .put(&transfer.to)
+                    .put(&transfer.value)
+                    .put(&transfer.transfer_index);
+            }
+        }
+        Ok(enc.finish())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -273,6 +404,8 @@ pub enum EthereumTrigger {
     Block(BlockPtr, EthereumBlockTriggerType),
     Call(Arc<EthereumCall>),
     Log(LogRef),
+    Transaction(Arc<Transaction>, Arc<TransactionReceipt>),
+    Transfer(Arc<EthereumTransfer>),
 }
 
 impl PartialEq for EthereumTrigger {
@@ -287,6 +420,10 @@ impl PartialEq for EthereumTrigger {
             (Self::Log(a), Self::Log(b)) => {
                 a.transaction_hash() == b.transaction_hash() && a.log_index() == b.log_index()
             }
+
+            (Self::Transaction(a, _), Self::Transaction(b, _)) => a.hash == b.hash,
+
+            (Self::Transfer(a), Self::Transfer(b)) => a == b,
             _ => false,
         }
     }
@@ -309,6 +446,10 @@ impl EthereumTrigger {
             EthereumTrigger::Log(log_ref) => {
                 i32::try_from(log_ref.block_number().unwrap().as_u64()).unwrap()
             }
+            EthereumTrigger::Transaction(_, receipt) => {
+                i32::try_from(receipt.block_number.unwrap().as_u64()).unwrap()
+            }
+            EthereumTrigger::Transfer(transfer) => transfer.block_number,
         }
     }
 
@@ -317,6 +458,20 @@ impl EthereumTrigger {
             EthereumTrigger::Block(block_ptr, _) => block_ptr.hash_as_h256(),
             EthereumTrigger::Call(call) => call.block_hash,
             EthereumTrigger::Log(log_ref) => log_ref.block_hash().unwrap(),
+            EthereumTrigger::Transaction(_, receipt) => receipt.block_hash.unwrap(),
+            EthereumTrigger::Transfer(transfer) => transfer.block_hash,
+        }
+    }
+
+    /// The index of the transaction this trigger belongs to; `None` for
+    /// block triggers.
+    fn transaction_index(&self) -> Option<u64> {
+        match self {
+            EthereumTrigger::Block(..) => None,
+            EthereumTrigger::Call(call) => Some(call.transaction_index),
+            EthereumTrigger::Log(log_ref) => log_ref.transaction_index().map(|i| i.as_u64()),
+            EthereumTrigger::Transaction(_, receipt) => Some(receipt.transaction_index.as_u64()),
+            EthereumTrigger::Transfer(transfer) => Some(transfer.transaction_index),
         }
     }
 
@@ -331,6 +486,11 @@ impl EthereumTrigger {
             // Unfiltered block triggers match any data source address.
             EthereumTrigger::Block(_, EthereumBlockTriggerType::End) => None,
             EthereumTrigger::Block(_, EthereumBlockTriggerType::Start) => None,
+            // Transactions can match on their sender or their receiver, which
+            // is checked when the data source matches the trigger.
+            EthereumTrigger::Transaction(..) => None,
+            // The same goes for transfers
+            EthereumTrigger::Transfer(..) => None,
         }
     }
 }
@@ -349,6 +509,34 @@ impl Ord for EthereumTrigger {
             (Self::Block(..), _) => Ordering::Greater,
             (_, Self::Block(..)) => Ordering::Less,
 
+            // Transactions are ordered by their tx index; they come after all
+            // events and calls from the same transaction
+            (Self::Transaction(..), Self::Transaction(..)) => {
+                self.transaction_index().cmp(&other.transaction_index())
+            }
+            (Self::Transaction(..), _) => self
+                .transaction_index()
+                .cmp(&other.transaction_index())
+                .then(Ordering::Greater),
+            (_, Self::Transaction(..)) => self
+                .transaction_index()
+                .cmp(&other.transaction_index())
+                .then(Ordering::Less),
+
+            // Transfers are ordered by their tx index and their position in
+            // the transaction; they come after all events and calls from the
+            // same transaction, but before the transaction itself
+            (Self::Transfer(a), Self::Transfer(b)) => (a.transaction_index, a.transfer_index)
+                .cmp(&(b.transaction_index, b.transfer_index)),
+            (Self::Transfer(..), _) => self
+                .transaction_index()
+                .cmp(&other.transaction_index())
+                .then(Ordering::Greater),
+            (_, Self::Transfer(..)) => self
+                .transaction_index()
+                .cmp(&other.transaction_index())
+                .then(Ordering::Less),
+
             // Calls are ordered by their tx indexes
             (Self::Call(a), Self::Call(b)) => a.transaction_index.cmp(&b.transaction_index),
 
@@ -391,6 +579,8 @@ impl TriggerData for EthereumTrigger {
             EthereumTrigger::Log(log) => log.transaction_hash(),
             EthereumTrigger::Call(call) => call.transaction_hash,
             EthereumTrigger::Block(..) => None,
+            EthereumTrigger::Transaction(tx, _) => Some(tx.hash),
+            EthereumTrigger::Transfer(transfer) => Some(transfer.transaction_hash),
         };
 
         match transaction_id {
@@ -486,6 +676,17 @@ impl<'a> EthereumBlockData<'a> {
     }
 }
 
+impl Encode for EthereumBlockData<'_> {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(self.hash())
+            .put(self.parent_hash())
+            .put(&self.number())
+            .put(self.timestamp())
+            .put(self.author())
+            .put(self.base_fee_per_gas());
+    }
+}
+
 /// Ethereum transaction data.
 #[derive(Clone, Debug)]
 pub struct EthereumTransactionData<'a> {
@@ -539,6 +740,17 @@ impl<'a> EthereumTransactionData<'a> {
     }
 }
 
+impl Encode for EthereumTransactionData<'_> {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(self.hash())
+            .put(&self.tx.transaction_index.unwrap_or_default())
+            .put(self.from())
+            .put(self.to())
+            .put(self.value())
+            .put(self.input());
+    }
+}
+
 /// An Ethereum event logged from a specific contract address and block.
 #[derive(Debug, Clone)]
 pub struct EthereumEventData<'a> {
@@ -618,3 +830,47 @@ impl<'a> EthereumCallData<'a> {
         &self.call.to
     }
 }
+
+/// A transaction together with its receipt, passed to transaction handlers.
+#[derive(Debug, Clone)]
+pub struct EthereumTransactionTriggerData<'a> {
+    pub block: EthereumBlockData<'a>,
+    pub transaction: EthereumTransactionData<'a>,
+    pub receipt: &'a TransactionReceipt,
+}
+
+impl<'a> EthereumTransactionTriggerData<'a> {
+    fn new(
+        block: &'a Block<Transaction>,
+        transaction: &'a Transaction,
+        receipt: &'a TransactionReceipt,
+    ) -> EthereumTransactionTriggerData<'a> {
+        EthereumTransactionTriggerData {
+            block: EthereumBlockData::from(block),
+            transaction: EthereumTransactionData::new(transaction),
+            receipt,
+        }
+    }
+}
+
+/// An internal ETH transfer, passed to transfer handlers.
+#[derive(Debug, Clone)]
+pub struct EthereumTransferData<'a> {
+    pub block: EthereumBlockData<'a>,
+    pub transaction: EthereumTransactionData<'a>,
+    pub transfer: &'a EthereumTransfer,
+}
+
+impl<'a> EthereumTransferData<'a> {
+    fn new(
+        block: &'a Block<Transaction>,
+        transaction: &'a Transaction,
+        transfer: &'a EthereumTransfer,
+    ) -> EthereumTransferData<'a> {
+        EthereumTransferData {
+            block: EthereumBlockData::from(block),
+            transaction: EthereumTransactionData::new(transaction),
+            transfer,
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{data_source::DataSource, Chain};
+use graph::blockchain as bc;
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, Default)]
+pub struct TriggerFilter {
+    pub(crate) block_filter: SolanaBlockFilter,
+    pub(crate) instruction_filter: SolanaProgramFilter,
+    pub(crate) log_filter: SolanaProgramFilter,
+}
+
+impl bc::TriggerFilter<Chain> for TriggerFilter {
+    fn extend<'a>(&mut self, data_sources: impl Iterator<Item = &'a DataSource> + Clone) {
+        let TriggerFilter {
+            block_filter,
+            instruction_filter,
+            log_filter,
+        } = self;
+
+        block_filter.extend(SolanaBlockFilter::from_data_sources(data_sources.clone()));
+        instruction_filter.extend(SolanaProgramFilter::from_data_sources(
+            data_sources.clone(),
+            |ds| !ds.mapping.instruction_handlers.is_empty(),
+        ));
+        log_filter.extend(SolanaProgramFilter::from_data_sources(data_sources, |ds| {
+            !ds.mapping.log_handlers.is_empty()
+        }));
+    }
+
+    fn node_capabilities(&self) -> bc::EmptyNodeCapabilities<Chain> {
+        bc::EmptyNodeCapabilities::default()
+    }
+
+    fn extend_with_template(
+        &mut self,
+        _data_source: impl Iterator<Item = <Chain as bc::Blockchain>::DataSourceTemplate>,
+    ) {
+    }
+
+    fn to_firehose_filter(self) -> Vec<prost_types::Any> {
+        vec![]
+    }
+}
+
+/// Matches instructions or logs of the programs with the given ids. The
+/// ids are the raw 32 bytes of the base58 encoded program id in the
+/// manifest
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SolanaProgramFilter {
+    program_ids: HashSet<Vec<u8>>,
+}
+
+impl SolanaProgramFilter {
+    pub fn matches(&self, program_id: &[u8]) -> bool {
+        self.program_ids.contains(program_id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.program_ids.is_empty()
+    }
+
+    /// Collect the program ids of all data sources for which `has_handlers`
+    /// is true
+    pub fn from_data_sources<'a>(
+        iter: impl IntoIterator<Item = &'a DataSource>,
+        has_handlers: impl Fn(&DataSource) -> bool,
+    ) -> Self {
+        Self {
+            program_ids: iter
+                .into_iter()
+                .filter(|data_source| has_handlers(data_source))
+                .filter_map(|data_source| data_source.program_id())
+                .collect(),
+        }
+    }
+
+    pub fn extend(&mut self, other: SolanaProgramFilter) {
+        self.program_ids.extend(other.program_ids);
+    }
+}
+
+/// SolanaBlockFilter will match every block regardless of source being set.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SolanaBlockFilter {
+    pub trigger_every_block: bool,
+}
+
+impl SolanaBlockFilter {
+    pub fn from_data_sources<'a>(iter: impl IntoIterator<Item = &'a DataSource>) -> Self {
+        Self {
+            trigger_every_block: iter
+                .into_iter()
+                .any(|data_source| !data_source.mapping.block_handlers.is_empty()),
+        }
+    }
+
+    pub fn extend(&mut self, other: SolanaBlockFilter) {
+        self.trigger_every_block = self.trigger_every_block || other.trigger_every_block;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use graph::blockchain::TriggerFilter as _;
+    use graph::{prelude::Link, semver::Version};
+
+    use crate::data_source::{
+        DataSource, InstructionHandler, LogHandler, Mapping, MappingBlockHandler, Source,
+    };
+
+    use super::TriggerFilter;
+
+    const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuvvf5mNmGvPiuHw6";
+    const MY_PROGRAM: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+
+    fn program(id: &str) -> Vec<u8> {
+        bs58::decode(id).into_vec().unwrap()
+    }
+
+    #[test]
+    fn program_filters_match_handlers() {
+        let dss = vec![
+            new_datasource(None, true, false, false),
+            new_datasource(Some(TOKEN_PROGRAM), false, true, false),
+            new_datasource(Some(MY_PROGRAM), false, false, true),
+        ];
+
+        let mut filter = TriggerFilter::default();
+        filter.extend(dss.iter());
+
+        assert!(filter.block_filter.trigger_every_block);
+
+        assert!(filter.instruction_filter.matches(&program(TOKEN_PROGRAM)));
+        assert!(!filter.instruction_filter.matches(&program(MY_PROGRAM)));
+
+        assert!(filter.log_filter.matches(&program(MY_PROGRAM)));
+        assert!(!filter.log_filter.matches(&program(TOKEN_PROGRAM)));
+    }
+
+    #[test]
+    fn program_filters_extend() {
+        let mut filter = TriggerFilter::default();
+        filter.extend(vec![new_datasource(Some(TOKEN_PROGRAM), false, true, false)].iter());
+        filter.extend(vec![new_datasource(Some(MY_PROGRAM), false, true, false)].iter());
+
+        assert!(!filter.block_filter.trigger_every_block);
+        assert!(filter.instruction_filter.matches(&program(TOKEN_PROGRAM)));
+        assert!(filter.instruction_filter.matches(&program(MY_PROGRAM)));
+        assert!(filter.log_filter.is_empty());
+    }
+
+    fn new_datasource(
+        program_id: Option<&str>,
+        block_handler: bool,
+        instruction_handler: bool,
+        log_handler: bool,
+    ) -> DataSource {
+        DataSource {
+            kind: "solana".into(),
+            network: None,
+            name: "".into(),
+            source: Source {
+                program_id: program_id.map(str::to_string),
+                start_block: 10,
+                end_block: None,
+            },
+            mapping: Mapping {
+                api_version: Version::new(0, 0, 7),
+                language: "".into(),
+                entities: vec![],
+                block_handlers: block_handler
+                    .then(|| MappingBlockHandler {
+                        handler: "handleBlock".into(),
+                    })
+                    .into_iter()
+                    .collect(),
+                instruction_handlers: instruction_handler
+                    .then(|| InstructionHandler {
+                        handler: "handleInstruction".into(),
+                        discriminator: None,
+                    })
+                    .into_iter()
+                    .collect(),
+                log_handlers: log_handler
+                    .then(|| LogHandler {
+                        handler: "handleLog".into(),
+                    })
+                    .into_iter()
+                    .collect(),
+                runtime: Arc::new(vec![]),
+                link: Link { link: "".into() },
+            },
+            context: Arc::new(None),
+            creation_block: None,
+        }
+    }
+}
@@ -0,0 +1,298 @@
+#[rustfmt::skip]
+#[path = "protobuf/sf.solana.r#type.v1.rs"]
+mod pbcodec;
+
+use std::ops::Deref;
+
+use graph::{
+    blockchain::Block as BlockchainBlock,
+    blockchain::{BlockHash, BlockPtr, BlockTime},
+    prelude::{anyhow, BlockNumber, Error},
+};
+use prost::bytes::{Buf, BufMut};
+use prost::encoding::{DecodeContext, WireType};
+use prost::{DecodeError, Message as _};
+
+pub use pbcodec::*;
+
+/// Solana block hashes are base58 encoded in the Firehose block
+fn decode_hash(hash: &str) -> Result<BlockHash, Error> {
+    bs58::decode(hash)
+        .into_vec()
+        .map(BlockHash::from)
+        .map_err(|e| anyhow::anyhow!("invalid block hash `{}`: {}", hash, e))
+}
+
+/// A Firehose Solana block together with its decoded hashes. The hashes
+/// are decoded while the block is decoded, so that a block with an invalid
+/// hash fails to decode, no matter where it comes from, and `ptr` and
+/// `parent_ptr` can not fail
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Block {
+    block: pbcodec::Block,
+    hash: BlockHash,
+    parent_hash: BlockHash,
+}
+
+impl Deref for Block {
+    type Target = pbcodec::Block;
+
+    fn deref(&self) -> &Self::Target {
+        &self.block
+    }
+}
+
+/// Tags of the hash fields in `pbcodec::Block`
+const PREVIOUS_BLOCKHASH_TAG: u32 = 1;
+const BLOCKHASH_TAG: u32 = 2;
+
+impl prost::Message for Block {
+    fn encode_raw(&self, buf: &mut impl BufMut) {
+        self.block.encode_raw(buf)
+    }
+
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        self.block.merge_field(tag, wire_type, buf, ctx)?;
+
+        let decode = |hash: &str| decode_hash(hash).map_err(|e| DecodeError::new(e.to_string()));
+        match tag {
+            PREVIOUS_BLOCKHASH_TAG => self.parent_hash = decode(&self.block.previous_blockhash)?,
+            BLOCKHASH_TAG => self.hash = decode(&self.block.blockhash)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.block.encoded_len()
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl Block {
+    pub fn hash(&self) -> &BlockHash {
+        &self.hash
+    }
+
+    pub fn parent_hash(&self) -> &BlockHash {
+        &self.parent_hash
+    }
+}
+
+impl BlockchainBlock for Block {
+    fn number(&self) -> i32 {
+        BlockNumber::try_from(self.slot).unwrap()
+    }
+
+    fn ptr(&self) -> BlockPtr {
+        BlockPtr {
+            hash: self.hash.clone(),
+            number: self.number(),
+        }
+    }
+
+    fn parent_ptr(&self) -> Option<BlockPtr> {
+        if self.slot == 0 {
+            return None;
+        }
+
+        // Slots can be skipped, the parent is not necessarily `slot - 1`
+        Some(BlockPtr {
+            hash: self.parent_hash.clone(),
+            number: BlockNumber::try_from(self.parent_slot).unwrap(),
+        })
+    }
+
+    fn timestamp(&self) -> BlockTime {
+        let timestamp = self
+            .block_time
+            .as_ref()
+            .map(|time| time.timestamp)
+            .unwrap_or_default();
+        BlockTime::since_epoch(timestamp, 0)
+    }
+}
+
+impl ConfirmedTransaction {
+    /// The id of a transaction is its first signature
+    pub fn id(&self) -> &[u8] {
+        self.transaction
+            .as_ref()
+            .and_then(|tx| tx.signatures.first())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn failed(&self) -> bool {
+        self.meta.as_ref().map_or(false, |meta| meta.err.is_some())
+    }
+
+    pub fn log_messages(&self) -> &[String] {
+        self.meta
+            .as_ref()
+            .map(|meta| meta.log_messages.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// All accounts the transaction can reference by index: the static
+    /// account keys of the message followed by the writable and the
+    /// readonly accounts loaded from address lookup tables
+    pub fn accounts(&self) -> Vec<Account> {
+        let message = match self.transaction.as_ref().and_then(|tx| tx.message.as_ref()) {
+            Some(message) => message,
+            None => return vec![],
+        };
+        let header = message.header.clone().unwrap_or_default();
+        let (pre_balances, post_balances) = self
+            .meta
+            .as_ref()
+            .map(|meta| (meta.pre_balances.as_slice(), meta.post_balances.as_slice()))
+            .unwrap_or_default();
+
+        let signers = header.num_required_signatures as usize;
+        let writable_signers = signers.saturating_sub(header.num_readonly_signed_accounts as usize);
+        let static_keys = message.account_keys.len();
+        let writable_unsigned =
+            static_keys.saturating_sub(header.num_readonly_unsigned_accounts as usize);
+
+        let static_accounts = message.account_keys.iter().enumerate().map(|(i, key)| {
+            (
+                key,
+                i < signers,
+                i < writable_signers || (i >= signers && i < writable_unsigned),
+            )
+        });
+        let (loaded_writable, loaded_readonly) = self
+            .meta
+            .as_ref()
+            .map(|meta| {
+                (
+                    meta.loaded_writable_addresses.as_slice(),
+                    meta.loaded_readonly_addresses.as_slice(),
+                )
+            })
+            .unwrap_or_default();
+        let loaded_accounts = loaded_writable
+            .iter()
+            .map(|key| (key, false, true))
+            .chain(loaded_readonly.iter().map(|key| (key, false, false)));
+
+        static_accounts
+            .chain(loaded_accounts)
+            .enumerate()
+            .map(|(i, (key, is_signer, is_writable))| Account {
+                pubkey: key.clone(),
+                is_signer,
+                is_writable,
+                pre_balance: pre_balances.get(i).copied().unwrap_or_default(),
+                post_balance: post_balances.get(i).copied().unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// The instructions of the transaction in execution order. Each
+    /// top-level instruction is followed by the instructions it invoked
+    pub fn instructions(&self) -> Vec<InstructionRef<'_>> {
+        let message = match self.transaction.as_ref().and_then(|tx| tx.message.as_ref()) {
+            Some(message) => message,
+            None => return vec![],
+        };
+        let inner = self
+            .meta
+            .as_ref()
+            .map(|meta| meta.inner_instructions.as_slice())
+            .unwrap_or_default();
+
+        let mut instructions = Vec::new();
+        for (index, instruction) in message.instructions.iter().enumerate() {
+            let index = index as u32;
+            instructions.push(InstructionRef {
+                index,
+                inner_index: None,
+                stack_height: 1,
+                program_id_index: instruction.program_id_index,
+                accounts: &instruction.accounts,
+                data: &instruction.data,
+            });
+            for inner_instructions in inner.iter().filter(|inner| inner.index == index) {
+                for (inner_index, instruction) in inner_instructions.instructions.iter().enumerate()
+                {
+                    instructions.push(InstructionRef {
+                        index,
+                        inner_index: Some(inner_index as u32),
+                        stack_height: instruction.stack_height.unwrap_or(2),
+                        program_id_index: instruction.program_id_index,
+                        accounts: &instruction.accounts,
+                        data: &instruction.data,
+                    });
+                }
+            }
+        }
+        instructions
+    }
+}
+
+/// An account referenced by a transaction, with its balance in lamports
+/// before and after the transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Account {
+    pub pubkey: Vec<u8>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    pub pre_balance: u64,
+    pub post_balance: u64,
+}
+
+/// A top-level or inner instruction of a transaction. `accounts` and
+/// `program_id_index` are indexes into the accounts of the transaction
+#[derive(Clone, Debug)]
+pub struct InstructionRef<'a> {
+    pub index: u32,
+    pub inner_index: Option<u32>,
+    pub stack_height: u32,
+    pub program_id_index: u32,
+    pub accounts: &'a [u8],
+    pub data: &'a [u8],
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::blockchain::{Block as _, BlockHash, BlockPtr};
+    use prost::Message;
+
+    use super::{pbcodec, Block};
+
+    #[test]
+    fn decode_block_hashes() {
+        let mut block = pbcodec::Block {
+            previous_blockhash: bs58::encode([1u8; 32]).into_string(),
+            blockhash: bs58::encode([2u8; 32]).into_string(),
+            parent_slot: 8,
+            slot: 10,
+            ..Default::default()
+        };
+
+        let decoded = Block::decode(block.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(
+            BlockPtr::new(BlockHash::from(vec![2u8; 32]), 10),
+            decoded.ptr()
+        );
+        assert_eq!(
+            Some(BlockPtr::new(BlockHash::from(vec![1u8; 32]), 8)),
+            decoded.parent_ptr()
+        );
+
+        // `0`, `O`, `I` and `l` are not part of the base58 alphabet
+        block.blockhash = "0OIl".to_string();
+        assert!(Block::decode(block.encode_to_vec().as_slice()).is_err());
+    }
+}
@@ -0,0 +1,491 @@
+use graph::anyhow::Context;
+use graph::blockchain::{Block, TriggerWithHandler};
+use graph::components::store::StoredDynamicDataSource;
+use graph::components::subgraph::InstanceDSTemplateInfo;
+use graph::data::subgraph::DataSourceContext;
+use graph::prelude::{hex, SubgraphManifestValidationError};
+use graph::{
+    anyhow::{anyhow, Error},
+    blockchain::{self, Blockchain},
+    prelude::{async_trait, BlockNumber, CheapClone, Deserialize, Link, LinkResolver, Logger},
+    semver,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::chain::Chain;
+use crate::trigger::SolanaTrigger;
+
+pub const SOLANA_KIND: &str = "solana";
+const BLOCK_HANDLER_KIND: &str = "block";
+const INSTRUCTION_HANDLER_KIND: &str = "instruction";
+const LOG_HANDLER_KIND: &str = "log";
+// Size of an ed25519 public key, which is what program ids are
+const PUBKEY_LEN: usize = 32;
+
+/// Runtime representation of a data source.
+#[derive(Clone, Debug)]
+pub struct DataSource {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub(crate) source: Source,
+    pub mapping: Mapping,
+    pub context: Arc<Option<DataSourceContext>>,
+    pub creation_block: Option<BlockNumber>,
+}
+
+impl blockchain::DataSource<Chain> for DataSource {
+    fn from_template_info(
+        _info: InstanceDSTemplateInfo,
+        _template: &graph::data_source::DataSourceTemplate<Chain>,
+    ) -> Result<Self, Error> {
+        Err(anyhow!("Solana subgraphs do not support templates"))
+    }
+
+    fn address(&self) -> Option<&[u8]> {
+        self.source.program_id.as_ref().map(String::as_bytes)
+    }
+
+    fn start_block(&self) -> BlockNumber {
+        self.source.start_block
+    }
+
+    fn handler_kinds(&self) -> HashSet<&str> {
+        let mut kinds = HashSet::new();
+
+        if !self.mapping.block_handlers.is_empty() {
+            kinds.insert(BLOCK_HANDLER_KIND);
+        }
+
+        if !self.mapping.instruction_handlers.is_empty() {
+            kinds.insert(INSTRUCTION_HANDLER_KIND);
+        }
+
+        if !self.mapping.log_handlers.is_empty() {
+            kinds.insert(LOG_HANDLER_KIND);
+        }
+
+        kinds
+    }
+
+    fn end_block(&self) -> Option<BlockNumber> {
+        self.source.end_block
+    }
+
+    fn match_and_decode(
+        &self,
+        trigger: &<Chain as Blockchain>::TriggerData,
+        block: &Arc<<Chain as Blockchain>::Block>,
+        _logger: &Logger,
+    ) -> Result<Option<TriggerWithHandler<Chain>>, Error> {
+        if self.source.start_block > block.number() {
+            return Ok(None);
+        }
+
+        let handler = match trigger {
+            // A block trigger matches if a block handler is present.
+            SolanaTrigger::Block(_) => match self.mapping.block_handlers.first() {
+                Some(handler) => &handler.handler,
+                None => return Ok(None),
+            },
+            // An instruction trigger matches if it is for the program of
+            // this data source and a handler accepts its data.
+            SolanaTrigger::Instruction(instruction) => {
+                if self.program_id().as_ref() != Some(&instruction.program_id) {
+                    return Ok(None);
+                }
+                match self.handler_for_instruction(&instruction.data) {
+                    Some(handler) => &handler.handler,
+                    None => return Ok(None),
+                }
+            }
+            // A log trigger matches if it is for the program of this data
+            // source and a log handler is present.
+            SolanaTrigger::Log(log) => {
+                if self.program_id().as_ref() != Some(&log.log.program_id) {
+                    return Ok(None);
+                }
+                match self.mapping.log_handlers.first() {
+                    Some(handler) => &handler.handler,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        Ok(Some(TriggerWithHandler::<Chain>::new(
+            trigger.cheap_clone(),
+            handler.clone(),
+            block.ptr(),
+            block.timestamp(),
+        )))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn network(&self) -> Option<&str> {
+        self.network.as_deref()
+    }
+
+    fn context(&self) -> Arc<Option<DataSourceContext>> {
+        self.context.cheap_clone()
+    }
+
+    fn creation_block(&self) -> Option<BlockNumber> {
+        self.creation_block
+    }
+
+    fn is_duplicate_of(&self, other: &Self) -> bool {
+        let DataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context,
+
+            // The creation block is ignored for detection duplicate data sources.
+            creation_block: _,
+        } = self;
+
+        kind == &other.kind
+            && network == &other.network
+            && name == &other.name
+            && source == &other.source
+            && mapping.block_handlers == other.mapping.block_handlers
+            && mapping.instruction_handlers == other.mapping.instruction_handlers
+            && mapping.log_handlers == other.mapping.log_handlers
+            && context == &other.context
+    }
+
+    fn as_stored_dynamic_data_source(&self) -> StoredDynamicDataSource {
+        unreachable!("Solana subgraphs do not support dynamic data sources")
+    }
+
+    fn from_stored_dynamic_data_source(
+        _template: &DataSourceTemplate,
+        _stored: StoredDynamicDataSource,
+    ) -> Result<Self, Error> {
+        Err(anyhow!(
+            "Solana subgraphs do not support dynamic data sources"
+        ))
+    }
+
+    fn validate(&self, _: &semver::Version) -> Vec<Error> {
+        let mut errors = Vec::new();
+
+        if self.kind != SOLANA_KIND {
+            errors.push(anyhow!(
+                "data source has invalid `kind`, expected {} but found {}",
+                SOLANA_KIND,
+                self.kind
+            ))
+        }
+
+        // Validate that there is a valid program id if there are
+        // instruction or log handlers
+        let has_program_handlers =
+            !self.mapping.instruction_handlers.is_empty() || !self.mapping.log_handlers.is_empty();
+        match &self.source.program_id {
+            None if has_program_handlers => {
+                errors.push(SubgraphManifestValidationError::SourceAddressRequired.into())
+            }
+            Some(program_id) if self.program_id().is_none() => errors.push(anyhow!(
+                "data source has invalid program id `{}`, expected a base58 encoded public key",
+                program_id
+            )),
+            _ => {}
+        }
+
+        // Validate that there are no more than one of block handlers and
+        // log handlers, and that instruction handlers are distinguished by
+        // their discriminator
+        if self.mapping.block_handlers.len() > 1 {
+            errors.push(anyhow!("data source has duplicated block handlers"));
+        }
+        if self.mapping.log_handlers.len() > 1 {
+            errors.push(anyhow!("data source has duplicated log handlers"));
+        }
+        let mut discriminators = HashSet::new();
+        for handler in &self.mapping.instruction_handlers {
+            if let Some(discriminator) = &handler.discriminator {
+                if handler.discriminator_bytes().is_none() {
+                    errors.push(anyhow!(
+                        "instruction handler {} has invalid discriminator `{}`, expected hex encoded bytes",
+                        handler.handler,
+                        discriminator
+                    ));
+                }
+            }
+            if !discriminators.insert(handler.discriminator_bytes()) {
+                errors.push(anyhow!(
+                    "data source has duplicated instruction handlers for the same discriminator"
+                ));
+            }
+        }
+
+        errors
+    }
+
+    fn api_version(&self) -> semver::Version {
+        self.mapping.api_version.clone()
+    }
+
+    fn runtime(&self) -> Option<Arc<Vec<u8>>> {
+        Some(self.mapping.runtime.cheap_clone())
+    }
+}
+
+impl DataSource {
+    fn from_manifest(
+        kind: String,
+        network: Option<String>,
+        name: String,
+        source: Source,
+        mapping: Mapping,
+        context: Option<DataSourceContext>,
+    ) -> Result<Self, Error> {
+        // Data sources in the manifest are created "before genesis" so they have no creation block.
+        let creation_block = None;
+
+        Ok(DataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context: Arc::new(context),
+            creation_block,
+        })
+    }
+
+    /// The decoded program id of the source, `None` if there is none or it
+    /// is not a valid public key
+    pub(crate) fn program_id(&self) -> Option<Vec<u8>> {
+        self.source
+            .program_id
+            .as_ref()
+            .and_then(|program_id| bs58::decode(program_id).into_vec().ok())
+            .filter(|program_id| program_id.len() == PUBKEY_LEN)
+    }
+
+    /// The first instruction handler whose discriminator is a prefix of
+    /// `data`. Handlers without a discriminator accept any instruction
+    fn handler_for_instruction(&self, data: &[u8]) -> Option<&InstructionHandler> {
+        self.mapping.instruction_handlers.iter().find(|handler| {
+            match handler.discriminator_bytes() {
+                Some(discriminator) => data.starts_with(&discriminator),
+                None => handler.discriminator.is_none(),
+            }
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct UnresolvedDataSource {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub(crate) source: Source,
+    pub mapping: UnresolvedMapping,
+    pub context: Option<DataSourceContext>,
+}
+
+#[async_trait]
+impl blockchain::UnresolvedDataSource<Chain> for UnresolvedDataSource {
+    async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+        _manifest_idx: u32,
+    ) -> Result<DataSource, Error> {
+        let UnresolvedDataSource {
+            kind,
+            network,
+            name,
+            source,
+            mapping,
+            context,
+        } = self;
+
+        let mapping = mapping.resolve(resolver, logger).await.with_context(|| {
+            format!(
+                "failed to resolve data source {} with program_id {:?} and start_block {}",
+                name, source.program_id, source.start_block
+            )
+        })?;
+
+        DataSource::from_manifest(kind, network, name, source, mapping, context)
+    }
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+pub struct BaseDataSourceTemplate<M> {
+    pub kind: String,
+    pub network: Option<String>,
+    pub name: String,
+    pub mapping: M,
+}
+
+pub type UnresolvedDataSourceTemplate = BaseDataSourceTemplate<UnresolvedMapping>;
+pub type DataSourceTemplate = BaseDataSourceTemplate<Mapping>;
+
+#[async_trait]
+impl blockchain::UnresolvedDataSourceTemplate<Chain> for UnresolvedDataSourceTemplate {
+    async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+        _manifest_idx: u32,
+    ) -> Result<DataSourceTemplate, Error> {
+        let UnresolvedDataSourceTemplate {
+            kind,
+            network,
+            name,
+            mapping,
+        } = self;
+
+        let mapping = mapping
+            .resolve(resolver, logger)
+            .await
+            .with_context(|| format!("failed to resolve data source template {}", name))?;
+
+        Ok(DataSourceTemplate {
+            kind,
+            network,
+            name,
+            mapping,
+        })
+    }
+}
+
+impl blockchain::DataSourceTemplate<Chain> for DataSourceTemplate {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn api_version(&self) -> semver::Version {
+        self.mapping.api_version.clone()
+    }
+
+    fn runtime(&self) -> Option<Arc<Vec<u8>>> {
+        Some(self.mapping.runtime.cheap_clone())
+    }
+
+    fn manifest_idx(&self) -> u32 {
+        unreachable!("solana does not support dynamic data sources")
+    }
+
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+}
+
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedMapping {
+    pub api_version: String,
+    pub language: String,
+    pub entities: Vec<String>,
+    #[serde(default)]
+    pub block_handlers: Vec<MappingBlockHandler>,
+    #[serde(default)]
+    pub instruction_handlers: Vec<InstructionHandler>,
+    #[serde(default)]
+    pub log_handlers: Vec<LogHandler>,
+    pub file: Link,
+}
+
+impl UnresolvedMapping {
+    pub async fn resolve(
+        self,
+        resolver: &Arc<dyn LinkResolver>,
+        logger: &Logger,
+    ) -> Result<Mapping, Error> {
+        let UnresolvedMapping {
+            api_version,
+            language,
+            entities,
+            block_handlers,
+            instruction_handlers,
+            log_handlers,
+            file: link,
+        } = self;
+
+        let api_version = semver::Version::parse(&api_version)?;
+
+        let module_bytes = resolver
+            .cat(logger, &link)
+            .await
+            .with_context(|| format!("failed to resolve mapping {}", link.link))?;
+
+        Ok(Mapping {
+            api_version,
+            language,
+            entities,
+            block_handlers,
+            instruction_handlers,
+            log_handlers,
+            runtime: Arc::new(module_bytes),
+            link,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Mapping {
+    pub api_version: semver::Version,
+    pub language: String,
+    pub entities: Vec<String>,
+    pub block_handlers: Vec<MappingBlockHandler>,
+    pub instruction_handlers: Vec<InstructionHandler>,
+    pub log_handlers: Vec<LogHandler>,
+    pub runtime: Arc<Vec<u8>>,
+    pub link: Link,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct MappingBlockHandler {
+    pub handler: String,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct InstructionHandler {
+    pub handler: String,
+    /// Hex encoded prefix of the instruction data, e.g., the 8 byte
+    /// discriminator that Anchor programs put at the start of each
+    /// instruction. Without it, the handler is called for every instruction
+    /// of the program
+    pub discriminator: Option<String>,
+}
+
+impl InstructionHandler {
+    fn discriminator_bytes(&self) -> Option<Vec<u8>> {
+        self.discriminator.as_ref().and_then(|discriminator| {
+            hex::decode(discriminator.trim_start_matches("0x"))
+                .ok()
+                .filter(|bytes| !bytes.is_empty())
+        })
+    }
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct LogHandler {
+    pub handler: String,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Source {
+    // A data source that does not have a program id can only have block handlers.
+    pub(crate) program_id: Option<String>,
+    #[serde(default)]
+    pub(crate) start_block: BlockNumber,
+    pub(crate) end_block: Option<BlockNumber>,
+}
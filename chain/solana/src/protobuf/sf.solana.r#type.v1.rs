@@ -0,0 +1,136 @@
+// This file is @generated by prost-build.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Block {
+    #[prost(string, tag = "1")]
+    pub previous_blockhash: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub blockhash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub parent_slot: u64,
+    #[prost(message, repeated, tag = "4")]
+    pub transactions: ::prost::alloc::vec::Vec<ConfirmedTransaction>,
+    #[prost(message, optional, tag = "6")]
+    pub block_time: ::core::option::Option<UnixTimestamp>,
+    #[prost(message, optional, tag = "7")]
+    pub block_height: ::core::option::Option<BlockHeight>,
+    #[prost(uint64, tag = "20")]
+    pub slot: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConfirmedTransaction {
+    #[prost(message, optional, tag = "1")]
+    pub transaction: ::core::option::Option<Transaction>,
+    #[prost(message, optional, tag = "2")]
+    pub meta: ::core::option::Option<TransactionStatusMeta>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Transaction {
+    /// The first signature is the transaction id
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(message, optional, tag = "2")]
+    pub message: ::core::option::Option<Message>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Message {
+    #[prost(message, optional, tag = "1")]
+    pub header: ::core::option::Option<MessageHeader>,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub account_keys: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub recent_blockhash: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, repeated, tag = "4")]
+    pub instructions: ::prost::alloc::vec::Vec<CompiledInstruction>,
+    #[prost(bool, tag = "5")]
+    pub versioned: bool,
+    #[prost(message, repeated, tag = "6")]
+    pub address_table_lookups: ::prost::alloc::vec::Vec<MessageAddressTableLookup>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageHeader {
+    #[prost(uint32, tag = "1")]
+    pub num_required_signatures: u32,
+    #[prost(uint32, tag = "2")]
+    pub num_readonly_signed_accounts: u32,
+    #[prost(uint32, tag = "3")]
+    pub num_readonly_unsigned_accounts: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageAddressTableLookup {
+    #[prost(bytes = "vec", tag = "1")]
+    pub account_key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub writable_indexes: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub readonly_indexes: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransactionStatusMeta {
+    /// Set if the transaction failed
+    #[prost(message, optional, tag = "1")]
+    pub err: ::core::option::Option<TransactionError>,
+    #[prost(uint64, tag = "2")]
+    pub fee: u64,
+    #[prost(uint64, repeated, tag = "3")]
+    pub pre_balances: ::prost::alloc::vec::Vec<u64>,
+    #[prost(uint64, repeated, tag = "4")]
+    pub post_balances: ::prost::alloc::vec::Vec<u64>,
+    #[prost(message, repeated, tag = "5")]
+    pub inner_instructions: ::prost::alloc::vec::Vec<InnerInstructions>,
+    #[prost(string, repeated, tag = "6")]
+    pub log_messages: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(bool, tag = "10")]
+    pub inner_instructions_none: bool,
+    #[prost(bool, tag = "11")]
+    pub log_messages_none: bool,
+    /// Accounts loaded through address lookup tables, in the order in which
+    /// they are appended to the account keys of the message
+    #[prost(bytes = "vec", repeated, tag = "12")]
+    pub loaded_writable_addresses: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "13")]
+    pub loaded_readonly_addresses: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TransactionError {
+    #[prost(bytes = "vec", tag = "1")]
+    pub err: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InnerInstructions {
+    /// The index of the top-level instruction these instructions were invoked by
+    #[prost(uint32, tag = "1")]
+    pub index: u32,
+    #[prost(message, repeated, tag = "2")]
+    pub instructions: ::prost::alloc::vec::Vec<InnerInstruction>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InnerInstruction {
+    #[prost(uint32, tag = "1")]
+    pub program_id_index: u32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub accounts: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint32, optional, tag = "4")]
+    pub stack_height: ::core::option::Option<u32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CompiledInstruction {
+    #[prost(uint32, tag = "1")]
+    pub program_id_index: u32,
+    /// Indexes into the account keys of the transaction
+    #[prost(bytes = "vec", tag = "2")]
+    pub accounts: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnixTimestamp {
+    #[prost(int64, tag = "1")]
+    pub timestamp: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BlockHeight {
+    #[prost(uint64, tag = "1")]
+    pub block_height: u64,
+}
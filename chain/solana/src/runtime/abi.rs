@@ -0,0 +1,112 @@
+use crate::codec;
+use crate::trigger::{InstructionWithInfo, LogWithInfo};
+use graph::runtime::gas::GasCounter;
+use graph::runtime::{asc_new, AscHeap, AscPtr, HostExportError, ToAscObj};
+use graph_runtime_wasm::asc_abi::class::{Array, Uint8Array};
+
+pub(crate) use super::generated::*;
+
+impl ToAscObj<AscBlock> for codec::Block {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscBlock, HostExportError> {
+        Ok(AscBlock {
+            slot: self.slot,
+            parent_slot: self.parent_slot,
+            block_height: self
+                .block_height
+                .as_ref()
+                .map(|height| height.block_height)
+                .unwrap_or_default(),
+            timestamp: self
+                .block_time
+                .as_ref()
+                .map(|time| time.timestamp)
+                .unwrap_or_default(),
+            hash: asc_new(heap, self.hash().as_slice(), gas)?,
+            previous_hash: asc_new(heap, self.parent_hash().as_slice(), gas)?,
+        })
+    }
+}
+
+impl ToAscObj<AscAccount> for codec::Account {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscAccount, HostExportError> {
+        Ok(AscAccount {
+            pre_balance: self.pre_balance,
+            post_balance: self.post_balance,
+            pubkey: asc_new(heap, self.pubkey.as_slice(), gas)?,
+            is_signer: self.is_signer,
+            is_writable: self.is_writable,
+        })
+    }
+}
+
+impl ToAscObj<AscAccountArray> for Vec<codec::Account> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscAccountArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x, gas))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AscAccountArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscBytesArray> for Vec<Vec<u8>> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscBytesArray, HostExportError> {
+        let content = self
+            .iter()
+            .map(|x| asc_new(heap, x.as_slice(), gas))
+            .collect::<Result<Vec<AscPtr<Uint8Array>>, _>>()?;
+        Ok(AscBytesArray(Array::new(&content, heap, gas)?))
+    }
+}
+
+impl ToAscObj<AscInstruction> for InstructionWithInfo {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscInstruction, HostExportError> {
+        Ok(AscInstruction {
+            program_id: asc_new(heap, self.program_id.as_slice(), gas)?,
+            accounts: asc_new(heap, &self.accounts, gas)?,
+            data: asc_new(heap, self.data.as_slice(), gas)?,
+            index: self.index,
+            inner_index: self.inner_index.map(|index| index as i32).unwrap_or(-1),
+            stack_height: self.stack_height,
+            signature: asc_new(heap, self.transaction.id(), gas)?,
+            block: asc_new(heap, self.block.as_ref(), gas)?,
+        })
+    }
+}
+
+impl ToAscObj<AscLog> for LogWithInfo {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscLog, HostExportError> {
+        Ok(AscLog {
+            program_id: asc_new(heap, self.log.program_id.as_slice(), gas)?,
+            message: asc_new(heap, &self.log.message, gas)?,
+            data: asc_new(heap, &self.log.data, gas)?,
+            index: self.index,
+            signature: asc_new(heap, self.transaction.id(), gas)?,
+            block: asc_new(heap, self.block.as_ref(), gas)?,
+        })
+    }
+}
@@ -0,0 +1,106 @@
+use graph::runtime::{AscIndexId, AscPtr, AscType, DeterministicHostError, IndexForAscTypeId};
+use graph::semver::Version;
+use graph_runtime_derive::AscType;
+use graph_runtime_wasm::asc_abi::class::{Array, AscString, Uint8Array};
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscBlock {
+    pub slot: u64,
+    pub parent_slot: u64,
+    pub block_height: u64,
+    pub timestamp: i64,
+    pub hash: AscPtr<Uint8Array>,
+    pub previous_hash: AscPtr<Uint8Array>,
+}
+
+impl AscIndexId for AscBlock {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SolanaBlock;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscAccount {
+    pub pre_balance: u64,
+    pub post_balance: u64,
+    pub pubkey: AscPtr<Uint8Array>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl AscIndexId for AscAccount {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SolanaAccount;
+}
+
+#[repr(C)]
+pub struct AscAccountArray(pub(crate) Array<AscPtr<AscAccount>>);
+
+impl AscType for AscAccountArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscAccountArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SolanaAccountArray;
+}
+
+#[repr(C)]
+pub struct AscBytesArray(pub(crate) Array<AscPtr<Uint8Array>>);
+
+impl AscType for AscBytesArray {
+    fn to_asc_bytes(&self) -> Result<Vec<u8>, DeterministicHostError> {
+        self.0.to_asc_bytes()
+    }
+
+    fn from_asc_bytes(
+        asc_obj: &[u8],
+        api_version: &Version,
+    ) -> Result<Self, DeterministicHostError> {
+        Ok(Self(Array::from_asc_bytes(asc_obj, api_version)?))
+    }
+}
+
+impl AscIndexId for AscBytesArray {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SolanaBytesArray;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscInstruction {
+    pub program_id: AscPtr<Uint8Array>,
+    pub accounts: AscPtr<AscAccountArray>,
+    pub data: AscPtr<Uint8Array>,
+    pub index: u32,
+    /// `-1` for top-level instructions
+    pub inner_index: i32,
+    pub stack_height: u32,
+    pub signature: AscPtr<Uint8Array>,
+    pub block: AscPtr<AscBlock>,
+}
+
+impl AscIndexId for AscInstruction {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SolanaInstruction;
+}
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscLog {
+    pub program_id: AscPtr<Uint8Array>,
+    pub message: AscPtr<AscString>,
+    pub data: AscPtr<AscBytesArray>,
+    pub index: u32,
+    pub signature: AscPtr<Uint8Array>,
+    pub block: AscPtr<AscBlock>,
+}
+
+impl AscIndexId for AscLog {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::SolanaLog;
+}
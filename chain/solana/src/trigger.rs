@@ -0,0 +1,441 @@
+use graph::blockchain::Block;
+use graph::blockchain::MappingTriggerTrait;
+use graph::blockchain::TriggerData;
+use graph::derive::CheapClone;
+use graph::prelude::BlockNumber;
+use graph::runtime::asc_new;
+use graph::runtime::gas::GasCounter;
+use graph::runtime::AscHeap;
+use graph::runtime::AscPtr;
+use graph::runtime::HostExportError;
+use graph_runtime_wasm::module::ToAscPtr;
+use std::{cmp::Ordering, sync::Arc};
+
+use crate::codec;
+
+// Logging the block is too verbose, so this strips the block from the trigger for Debug.
+impl std::fmt::Debug for SolanaTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        #[allow(unused)]
+        #[derive(Debug)]
+        pub enum MappingTriggerWithoutBlock {
+            Block,
+            Instruction {
+                program_id: String,
+                signature: String,
+                index: u32,
+                inner_index: Option<u32>,
+            },
+            Log {
+                program_id: String,
+                signature: String,
+                index: u32,
+            },
+        }
+
+        let trigger_without_block = match self {
+            SolanaTrigger::Block(_) => MappingTriggerWithoutBlock::Block,
+            SolanaTrigger::Instruction(instruction) => MappingTriggerWithoutBlock::Instruction {
+                program_id: bs58::encode(&instruction.program_id).into_string(),
+                signature: bs58::encode(instruction.transaction.id()).into_string(),
+                index: instruction.index,
+                inner_index: instruction.inner_index,
+            },
+            SolanaTrigger::Log(log) => MappingTriggerWithoutBlock::Log {
+                program_id: bs58::encode(&log.log.program_id).into_string(),
+                signature: bs58::encode(log.transaction.id()).into_string(),
+                index: log.index,
+            },
+        };
+
+        write!(f, "{:?}", trigger_without_block)
+    }
+}
+
+impl ToAscPtr for SolanaTrigger {
+    fn to_asc_ptr<H: AscHeap>(
+        self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<AscPtr<()>, HostExportError> {
+        Ok(match self {
+            SolanaTrigger::Block(block) => asc_new(heap, block.as_ref(), gas)?.erase(),
+            SolanaTrigger::Instruction(instruction) => {
+                asc_new(heap, instruction.as_ref(), gas)?.erase()
+            }
+            SolanaTrigger::Log(log) => asc_new(heap, log.as_ref(), gas)?.erase(),
+        })
+    }
+}
+
+#[derive(Clone, CheapClone)]
+pub enum SolanaTrigger {
+    Block(Arc<codec::Block>),
+    Instruction(Arc<InstructionWithInfo>),
+    Log(Arc<LogWithInfo>),
+}
+
+impl PartialEq for SolanaTrigger {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Block(a_ptr), Self::Block(b_ptr)) => a_ptr == b_ptr,
+            (Self::Instruction(a), Self::Instruction(b)) => {
+                a.transaction.id() == b.transaction.id()
+                    && a.index == b.index
+                    && a.inner_index == b.inner_index
+            }
+            (Self::Log(a), Self::Log(b)) => {
+                a.transaction.id() == b.transaction.id() && a.index == b.index
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for SolanaTrigger {}
+
+impl SolanaTrigger {
+    pub fn block_number(&self) -> BlockNumber {
+        match self {
+            SolanaTrigger::Block(block) => block.number(),
+            SolanaTrigger::Instruction(instruction) => instruction.block.number(),
+            SolanaTrigger::Log(log) => log.block.number(),
+        }
+    }
+
+    pub fn block_hash(&self) -> String {
+        match self {
+            SolanaTrigger::Block(block) => block.blockhash.clone(),
+            SolanaTrigger::Instruction(instruction) => instruction.block.blockhash.clone(),
+            SolanaTrigger::Log(log) => log.block.blockhash.clone(),
+        }
+    }
+
+    fn transaction_index(&self) -> Option<u32> {
+        match self {
+            SolanaTrigger::Block(_) => None,
+            SolanaTrigger::Instruction(instruction) => Some(instruction.transaction_index),
+            SolanaTrigger::Log(log) => Some(log.transaction_index),
+        }
+    }
+
+    fn error_context(&self) -> std::string::String {
+        match self {
+            SolanaTrigger::Block(..) => {
+                format!("Block #{} ({})", self.block_number(), self.block_hash())
+            }
+            SolanaTrigger::Instruction(instruction) => {
+                let index = match instruction.inner_index {
+                    Some(inner_index) => format!("{}.{}", instruction.index, inner_index),
+                    None => instruction.index.to_string(),
+                };
+                format!(
+                    "Instruction #{} of program {}, tx {}, block #{}({})",
+                    index,
+                    bs58::encode(&instruction.program_id).into_string(),
+                    bs58::encode(instruction.transaction.id()).into_string(),
+                    self.block_number(),
+                    self.block_hash()
+                )
+            }
+            SolanaTrigger::Log(log) => {
+                format!(
+                    "Log #{} of program {}, tx {}, block #{}({})",
+                    log.index,
+                    bs58::encode(&log.log.program_id).into_string(),
+                    bs58::encode(log.transaction.id()).into_string(),
+                    self.block_number(),
+                    self.block_hash()
+                )
+            }
+        }
+    }
+}
+
+impl Ord for SolanaTrigger {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            // Keep the order when comparing two block triggers
+            (Self::Block(..), Self::Block(..)) => Ordering::Equal,
+
+            // Block triggers always come last
+            (Self::Block(..), _) => Ordering::Greater,
+            (_, Self::Block(..)) => Ordering::Less,
+
+            // Instructions and logs are ordered by transaction. Within a
+            // transaction, we keep the order in which they were created
+            // from the block, i.e., instructions in execution order followed
+            // by the logs of the transaction
+            _ => self.transaction_index().cmp(&other.transaction_index()),
+        }
+    }
+}
+
+impl PartialOrd for SolanaTrigger {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl TriggerData for SolanaTrigger {
+    fn error_context(&self) -> String {
+        self.error_context()
+    }
+
+    fn address_match(&self) -> Option<&[u8]> {
+        match self {
+            SolanaTrigger::Block(_) => None,
+            SolanaTrigger::Instruction(instruction) => Some(&instruction.program_id),
+            SolanaTrigger::Log(log) => Some(&log.log.program_id),
+        }
+    }
+}
+
+impl MappingTriggerTrait for SolanaTrigger {
+    fn error_context(&self) -> String {
+        self.error_context()
+    }
+}
+
+/// A top-level or inner instruction together with the transaction and
+/// block it was executed in
+pub struct InstructionWithInfo {
+    pub program_id: Vec<u8>,
+    pub accounts: Vec<codec::Account>,
+    pub data: Vec<u8>,
+    /// The index of the top-level instruction in the transaction
+    pub index: u32,
+    /// The index among the instructions invoked by the top-level
+    /// instruction, `None` for top-level instructions
+    pub inner_index: Option<u32>,
+    pub stack_height: u32,
+    pub transaction_index: u32,
+    pub transaction: Arc<codec::ConfirmedTransaction>,
+    pub block: Arc<codec::Block>,
+}
+
+impl InstructionWithInfo {
+    /// All successful instructions in `block`, in execution order
+    pub(crate) fn from_block(block: &Arc<codec::Block>) -> Vec<InstructionWithInfo> {
+        let mut instructions = Vec::new();
+        for (transaction_index, transaction) in block.transactions.iter().enumerate() {
+            if transaction.failed() {
+                continue;
+            }
+            let accounts = transaction.accounts();
+            let shared_transaction = Arc::new(transaction.clone());
+
+            for instruction in transaction.instructions() {
+                let program_id = match accounts.get(instruction.program_id_index as usize) {
+                    Some(account) => account.pubkey.clone(),
+                    None => continue,
+                };
+                instructions.push(InstructionWithInfo {
+                    program_id,
+                    accounts: instruction
+                        .accounts
+                        .iter()
+                        .filter_map(|index| accounts.get(*index as usize).cloned())
+                        .collect(),
+                    data: instruction.data.to_vec(),
+                    index: instruction.index,
+                    inner_index: instruction.inner_index,
+                    stack_height: instruction.stack_height,
+                    transaction_index: transaction_index as u32,
+                    transaction: shared_transaction.clone(),
+                    block: block.clone(),
+                });
+            }
+        }
+        instructions
+    }
+}
+
+/// A message logged by a program with `msg!` or data emitted with
+/// `sol_log_data`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramLog {
+    /// The program that was executing when the log was emitted
+    pub program_id: Vec<u8>,
+    /// The text of a `Program log:` line, empty for data logs
+    pub message: String,
+    /// The decoded chunks of a `Program data:` line, empty for text logs
+    pub data: Vec<Vec<u8>>,
+}
+
+impl ProgramLog {
+    /// Extract the program logs from the log messages of a transaction.
+    /// Lines that are not emitted by programs themselves, like invocation
+    /// and compute unit reports, only serve to track which program is
+    /// executing and are not returned
+    pub fn parse(messages: &[String]) -> Vec<ProgramLog> {
+        use base64::Engine;
+
+        let mut stack: Vec<Vec<u8>> = Vec::new();
+        let mut logs = Vec::new();
+
+        for message in messages {
+            if let Some(message) = message.strip_prefix("Program log: ") {
+                if let Some(program_id) = stack.last() {
+                    logs.push(ProgramLog {
+                        program_id: program_id.clone(),
+                        message: message.to_string(),
+                        data: vec![],
+                    });
+                }
+            } else if let Some(data) = message.strip_prefix("Program data: ") {
+                if let Some(program_id) = stack.last() {
+                    let data = data
+                        .split(' ')
+                        .map(|chunk| base64::engine::general_purpose::STANDARD.decode(chunk))
+                        .collect::<Result<Vec<_>, _>>();
+                    // Programs can't log invalid base64 data, but a truncated
+                    // log could contain it; skip those entries
+                    if let Ok(data) = data {
+                        logs.push(ProgramLog {
+                            program_id: program_id.clone(),
+                            message: String::new(),
+                            data,
+                        });
+                    }
+                }
+            } else if let Some(rest) = message.strip_prefix("Program ") {
+                let mut parts = rest.split(' ');
+                let (program_id, action) = match (parts.next(), parts.next()) {
+                    (Some(program_id), Some(action)) => (program_id, action),
+                    _ => continue,
+                };
+                if action == "invoke" {
+                    match bs58::decode(program_id).into_vec() {
+                        Ok(program_id) => stack.push(program_id),
+                        // Not a program invocation; keep the stack balanced
+                        Err(_) => continue,
+                    }
+                } else if action == "success" || action.starts_with("failed") {
+                    stack.pop();
+                }
+            }
+        }
+        logs
+    }
+}
+
+/// A program log together with the transaction and block it was emitted in
+pub struct LogWithInfo {
+    pub log: ProgramLog,
+    /// The index of the log among the program logs of the transaction
+    pub index: u32,
+    pub transaction_index: u32,
+    pub transaction: Arc<codec::ConfirmedTransaction>,
+    pub block: Arc<codec::Block>,
+}
+
+impl LogWithInfo {
+    /// All program logs of successful transactions in `block`
+    pub(crate) fn from_block(block: &Arc<codec::Block>) -> Vec<LogWithInfo> {
+        let mut logs = Vec::new();
+        for (transaction_index, transaction) in block.transactions.iter().enumerate() {
+            if transaction.failed() {
+                continue;
+            }
+            let program_logs = ProgramLog::parse(transaction.log_messages());
+            if program_logs.is_empty() {
+                continue;
+            }
+            let shared_transaction = Arc::new(transaction.clone());
+
+            logs.extend(
+                program_logs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, log)| LogWithInfo {
+                        log,
+                        index: index as u32,
+                        transaction_index: transaction_index as u32,
+                        transaction: shared_transaction.clone(),
+                        block: block.clone(),
+                    }),
+            );
+        }
+        logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProgramLog;
+
+    const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuvvf5mNmGvPiuHw6";
+    const MY_PROGRAM: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+
+    fn program(id: &str) -> Vec<u8> {
+        bs58::decode(id).into_vec().unwrap()
+    }
+
+    #[test]
+    fn parse_program_logs() {
+        let messages: Vec<String> = vec![
+            format!("Program {} invoke [1]", MY_PROGRAM),
+            "Program log: Instruction: Swap".to_string(),
+            format!("Program {} invoke [2]", TOKEN_PROGRAM),
+            "Program log: Instruction: Transfer".to_string(),
+            format!(
+                "Program {} consumed 4645 of 180013 compute units",
+                TOKEN_PROGRAM
+            ),
+            format!("Program {} success", TOKEN_PROGRAM),
+            "Program data: aGVsbG8= d29ybGQ=".to_string(),
+            format!("Program return: {} AQ==", MY_PROGRAM),
+            format!("Program {} success", MY_PROGRAM),
+        ];
+
+        let logs = ProgramLog::parse(&messages);
+
+        assert_eq!(
+            vec![
+                ProgramLog {
+                    program_id: program(MY_PROGRAM),
+                    message: "Instruction: Swap".to_string(),
+                    data: vec![],
+                },
+                ProgramLog {
+                    program_id: program(TOKEN_PROGRAM),
+                    message: "Instruction: Transfer".to_string(),
+                    data: vec![],
+                },
+                ProgramLog {
+                    program_id: program(MY_PROGRAM),
+                    message: String::new(),
+                    data: vec![b"hello".to_vec(), b"world".to_vec()],
+                },
+            ],
+            logs
+        );
+    }
+
+    #[test]
+    fn parse_program_logs_after_failed_invocation() {
+        let messages: Vec<String> = vec![
+            format!("Program {} invoke [1]", MY_PROGRAM),
+            format!("Program {} invoke [2]", TOKEN_PROGRAM),
+            format!(
+                "Program {} failed: custom program error: 0x1",
+                TOKEN_PROGRAM
+            ),
+            "Program log: recovered".to_string(),
+            format!("Program {} success", MY_PROGRAM),
+            "Program log: outside of any program".to_string(),
+        ];
+
+        let logs = ProgramLog::parse(&messages);
+
+        assert_eq!(
+            vec![ProgramLog {
+                program_id: program(MY_PROGRAM),
+                message: "recovered".to_string(),
+                data: vec![],
+            }],
+            logs
+        );
+    }
+}
@@ -0,0 +1,439 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Error};
+use bytes::Bytes;
+use graph::components::link_resolver::ArweaveClient;
+use graph::data_source::offchain::{self, ChunkFormat, Chunks};
+use graph::data_source::CausalityRegion;
+use graph::futures03::stream::{BoxStream, StreamExt, TryStreamExt};
+use graph::ipfs::{IpfsClient, RetryPolicy};
+use graph::prelude::tokio::{sync::mpsc, task::AbortHandle};
+use graph::slog::{debug, error, warn, Logger};
+use graph::util::backoff::ExponentialBackoff;
+use graph::{derive::CheapClone, prelude::CheapClone};
+
+use super::ipfs_service::SAFE_MULTIHASHES;
+
+const MIN_BACKOFF: Duration = Duration::from_secs(5);
+
+const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// Opens files of offchain data sources as streams so that large files can
+/// be processed in chunks without loading them into memory.
+#[derive(Clone, CheapClone)]
+pub struct FileStreamer {
+    ipfs: Arc<dyn IpfsClient>,
+    arweave: Arc<ArweaveClient>,
+    timeout: Duration,
+    max_chunk_bytes: usize,
+}
+
+impl FileStreamer {
+    pub fn new(
+        ipfs: Arc<dyn IpfsClient>,
+        arweave: Arc<ArweaveClient>,
+        timeout: Duration,
+        max_chunk_bytes: usize,
+    ) -> Self {
+        Self {
+            ipfs,
+            arweave,
+            timeout,
+            max_chunk_bytes,
+        }
+    }
+
+    async fn open(
+        &self,
+        source: &offchain::Source,
+    ) -> Result<BoxStream<'static, Result<Bytes, Error>>, Error> {
+        match source {
+            offchain::Source::Ipfs(path) => {
+                let multihash = path.cid().hash().code();
+                if !SAFE_MULTIHASHES.contains(&multihash) {
+                    bail!("CID multihash {} is not allowed", multihash);
+                }
+                Ok(self
+                    .ipfs
+                    .cheap_clone()
+                    .cat_stream(path, Some(self.timeout), RetryPolicy::None)
+                    .await?
+                    .err_into()
+                    .boxed())
+            }
+            offchain::Source::Arweave(base64) => {
+                Ok(self.arweave.get_stream(base64).await?.err_into().boxed())
+            }
+        }
+    }
+}
+
+/// Splits a file into chunks of `Chunks::size` records. Records are lines,
+/// except that for CSV files newlines inside quoted fields do not end a
+/// record. Blank lines are ignored. For CSV files, the first record is the
+/// header and it is repeated at the start of each chunk.
+pub(crate) struct ChunkSplitter {
+    format: ChunkFormat,
+    size: usize,
+    max_chunk_bytes: usize,
+    /// The record that is currently being read
+    record: Vec<u8>,
+    in_quotes: bool,
+    header: Option<Vec<u8>>,
+    /// The complete records of the chunk that is currently being built
+    records: Vec<Vec<u8>>,
+    records_bytes: usize,
+    chunks: u32,
+}
+
+impl ChunkSplitter {
+    pub(crate) fn new(chunks: &Chunks, max_chunk_bytes: usize) -> Self {
+        Self {
+            format: chunks.format,
+            size: chunks.size as usize,
+            max_chunk_bytes,
+            record: vec![],
+            in_quotes: false,
+            header: None,
+            records: vec![],
+            records_bytes: 0,
+            chunks: 0,
+        }
+    }
+
+    /// Feed the next part of the file to the splitter and return all chunks
+    /// that are complete after that
+    pub(crate) fn push(&mut self, data: &[u8]) -> Result<Vec<Bytes>, Error> {
+        let mut chunks = vec![];
+        for &byte in data {
+            match byte {
+                b'"' if self.format == ChunkFormat::Csv => {
+                    self.in_quotes = !self.in_quotes;
+                    self.record.push(byte);
+                }
+                b'\n' if !self.in_quotes => {
+                    let record = std::mem::take(&mut self.record);
+                    if let Some(chunk) = self.add_record(record)? {
+                        chunks.push(chunk);
+                    }
+                }
+                _ => self.record.push(byte),
+            }
+        }
+        self.check_size()?;
+        Ok(chunks)
+    }
+
+    /// Signal the end of the file and return the last chunk. A file without
+    /// any records still produces one chunk so that its data source gets
+    /// processed.
+    pub(crate) fn finish(mut self) -> Result<Option<Bytes>, Error> {
+        if self.in_quotes {
+            bail!("the file ends inside a quoted field");
+        }
+        let record = std::mem::take(&mut self.record);
+        if let Some(chunk) = self.add_record(record)? {
+            return Ok(Some(chunk));
+        }
+        if self.records.is_empty() && self.chunks > 0 {
+            return Ok(None);
+        }
+        Ok(Some(self.take_chunk()))
+    }
+
+    fn add_record(&mut self, mut record: Vec<u8>) -> Result<Option<Bytes>, Error> {
+        if record.last() == Some(&b'\r') {
+            record.pop();
+        }
+        if record.iter().all(u8::is_ascii_whitespace) {
+            return Ok(None);
+        }
+        if self.format == ChunkFormat::Csv && self.header.is_none() {
+            self.header = Some(record);
+            return Ok(None);
+        }
+
+        self.records_bytes += record.len() + 1;
+        self.records.push(record);
+        self.check_size()?;
+
+        if self.records.len() == self.size {
+            Ok(Some(self.take_chunk()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn check_size(&self) -> Result<(), Error> {
+        let header_bytes = self.header.as_ref().map_or(0, |header| header.len() + 1);
+        let bytes = header_bytes + self.records_bytes + self.record.len();
+        if bytes > self.max_chunk_bytes {
+            bail!(
+                "chunk {} is larger than the maximum of {} bytes",
+                self.chunks,
+                self.max_chunk_bytes
+            );
+        }
+        Ok(())
+    }
+
+    fn take_chunk(&mut self) -> Bytes {
+        let mut chunk = Vec::with_capacity(self.records_bytes);
+        if let Some(header) = &self.header {
+            chunk.extend_from_slice(header);
+            chunk.push(b'\n');
+        }
+        for record in self.records.drain(..) {
+            chunk.extend_from_slice(&record);
+            chunk.push(b'\n');
+        }
+        self.records_bytes = 0;
+        self.chunks += 1;
+        Bytes::from(chunk)
+    }
+}
+
+/// Why streaming a file in chunks failed
+enum StreamError {
+    /// Fetching the file failed; fetching it again might succeed
+    Fetch(Error),
+    /// The file can not be split into chunks. Since that depends only on
+    /// its content, fetching it again would fail the same way
+    Content(Error),
+}
+
+/// Stream the file of `data_source` in chunks to `sender`, starting with the
+/// first chunk that has not been processed yet. If fetching the file fails,
+/// it is fetched again from the start, skipping the chunks that were already
+/// sent. Since `sender` is bounded, at most a few chunks are held in memory
+/// at any time.
+///
+/// If the file can not be split into chunks, the chunks before the error
+/// are sent, followed by a chunk that carries the error and marks the data
+/// source as done.
+///
+/// The task stops once the last chunk was sent or the receiver was dropped.
+/// The returned handle can be used to stop it earlier.
+pub fn spawn_chunk_stream(
+    logger: Logger,
+    streamer: FileStreamer,
+    data_source: &offchain::DataSource,
+    chunks: Chunks,
+    sender: mpsc::Sender<offchain::TriggerData>,
+) -> AbortHandle {
+    let source = data_source.source.clone();
+    let causality_region = data_source.causality_region;
+    let mut next = data_source.chunks_done();
+
+    graph::spawn(async move {
+        let mut backoff = ExponentialBackoff::new(MIN_BACKOFF, MAX_BACKOFF);
+        loop {
+            let res = stream_chunks(
+                &streamer,
+                &source,
+                causality_region,
+                &chunks,
+                &mut next,
+                &sender,
+            )
+            .await;
+            match res {
+                Ok(()) => {
+                    debug!(logger, "Finished streaming file"; "source" => format!("{:?}", source));
+                    return;
+                }
+                Err(StreamError::Content(e)) => {
+                    error!(logger, "Failed to split file into chunks, giving up on it";
+                        "source" => format!("{:?}", source),
+                        "next_chunk" => next,
+                        "error" => format!("{:#}", e));
+                    return;
+                }
+                Err(StreamError::Fetch(e)) => {
+                    warn!(logger, "Failed to stream file, retrying";
+                        "source" => format!("{:?}", source),
+                        "next_chunk" => next,
+                        "error" => format!("{:#}", e));
+                    backoff.sleep_async().await;
+                }
+            }
+        }
+    })
+    .abort_handle()
+}
+
+async fn stream_chunks(
+    streamer: &FileStreamer,
+    source: &offchain::Source,
+    causality_region: CausalityRegion,
+    chunks: &Chunks,
+    next: &mut u32,
+    sender: &mpsc::Sender<offchain::TriggerData>,
+) -> Result<(), StreamError> {
+    let mut file = streamer.open(source).await.map_err(StreamError::Fetch)?;
+    let mut splitter = ChunkSplitter::new(chunks, streamer.max_chunk_bytes);
+
+    // Hold back the most recent chunk until the next one arrives, since
+    // only then is it known that it is not the last one.
+    let mut index = 0;
+    let mut pending: Option<Bytes> = None;
+    let mut send = |index: u32, data: Bytes, last: bool, error: Option<String>| {
+        let skip = index < *next;
+        if !skip {
+            *next = index + 1;
+        }
+        let trigger = offchain::TriggerData {
+            source: source.clone(),
+            data: Arc::new(data),
+            chunk: Some(offchain::Chunk {
+                causality_region,
+                index,
+                last,
+                error,
+            }),
+        };
+        async move {
+            if skip {
+                return Ok(());
+            }
+            sender
+                .send(trigger)
+                .await
+                .map_err(|_| anyhow!("the receiver of the chunks was dropped"))
+        }
+    };
+
+    let content = loop {
+        let data = match file.try_next().await.map_err(StreamError::Fetch)? {
+            Some(data) => data,
+            None => break splitter.finish(),
+        };
+        let chunks = match splitter.push(&data) {
+            Ok(chunks) => chunks,
+            Err(e) => break Err(e),
+        };
+        for chunk in chunks {
+            if let Some(prev) = pending.replace(chunk) {
+                if send(index, prev, false, None).await.is_err() {
+                    return Ok(());
+                }
+                index += 1;
+            }
+        }
+    };
+
+    match content {
+        Ok(chunk) => {
+            if let Some(chunk) = chunk {
+                if let Some(prev) = pending.replace(chunk) {
+                    if send(index, prev, false, None).await.is_err() {
+                        return Ok(());
+                    }
+                    index += 1;
+                }
+            }
+            // `finish` always produces a chunk if no chunk was produced before
+            let last = pending.expect("a file has at least one chunk");
+            let _ = send(index, last, true, None).await;
+            Ok(())
+        }
+        Err(e) => {
+            if let Some(prev) = pending.take() {
+                if send(index, prev, false, None).await.is_err() {
+                    return Ok(());
+                }
+                index += 1;
+            }
+            let _ = send(index, Bytes::new(), true, Some(format!("{:#}", e))).await;
+            Err(StreamError::Content(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use graph::data_source::offchain::{ChunkFormat, Chunks};
+
+    use super::ChunkSplitter;
+
+    fn split(format: ChunkFormat, size: u32, parts: &[&str]) -> Vec<String> {
+        let mut splitter = ChunkSplitter::new(&Chunks { format, size }, 1024);
+        let mut chunks = vec![];
+        for part in parts {
+            chunks.extend(splitter.push(part.as_bytes()).unwrap());
+        }
+        chunks.extend(splitter.finish().unwrap());
+        chunks
+            .into_iter()
+            .map(|chunk| String::from_utf8(chunk.to_vec()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn splits_json_lines() {
+        let chunks = split(
+            ChunkFormat::JsonLines,
+            2,
+            &[
+                "{\"a\":1}\n{\"a\"",
+                ":2}\r\n\n{\"a\":3}\n",
+                "{\"a\":4}\n{\"a\":5}",
+            ],
+        );
+        assert_eq!(
+            vec![
+                "{\"a\":1}\n{\"a\":2}\n",
+                "{\"a\":3}\n{\"a\":4}\n",
+                "{\"a\":5}\n"
+            ],
+            chunks
+        );
+
+        // A file that ends on a chunk boundary has no empty chunk at the end
+        let chunks = split(ChunkFormat::JsonLines, 1, &["1\n2\n"]);
+        assert_eq!(vec!["1\n", "2\n"], chunks);
+    }
+
+    #[test]
+    fn splits_csv() {
+        let chunks = split(
+            ChunkFormat::Csv,
+            2,
+            &["id,name\n1,\"a\nb\"\n2,c", "\n3,\"d,e\"\n"],
+        );
+        assert_eq!(
+            vec!["id,name\n1,\"a\nb\"\n2,c\n", "id,name\n3,\"d,e\"\n"],
+            chunks
+        );
+
+        let mut splitter = ChunkSplitter::new(
+            &Chunks {
+                format: ChunkFormat::Csv,
+                size: 2,
+            },
+            1024,
+        );
+        splitter.push(b"id,name\n1,\"a").unwrap();
+        assert!(splitter.finish().is_err());
+    }
+
+    #[test]
+    fn empty_file_has_one_chunk() {
+        assert_eq!(vec![""], split(ChunkFormat::JsonLines, 10, &[]));
+        assert_eq!(vec!["id\n"], split(ChunkFormat::Csv, 10, &["id\n\n"]));
+    }
+
+    #[test]
+    fn chunks_are_bounded() {
+        let mut splitter = ChunkSplitter::new(
+            &Chunks {
+                format: ChunkFormat::JsonLines,
+                size: 10,
+            },
+            8,
+        );
+        assert!(splitter.push(b"1234\n").is_ok());
+        assert!(splitter.push(b"123456").is_err());
+    }
+}
@@ -73,7 +73,7 @@ impl IpfsServiceInner {
 // Multihashes that are collision resistant. This is not complete but covers the commonly used ones.
 // Code table: https://github.com/multiformats/multicodec/blob/master/table.csv
 // rust-multihash code enum: https://github.com/multiformats/rust-multihash/blob/master/src/multihash_impl.rs
-const SAFE_MULTIHASHES: [u64; 15] = [
+pub(super) const SAFE_MULTIHASHES: [u64; 15] = [
     0x0,    // Identity
     0x12,   // SHA2-256 (32-byte hash size)
     0x13,   // SHA2-512 (64-byte hash size)
@@ -48,12 +48,14 @@ where
     T: RuntimeHostBuilder<C>,
 {
     /// All onchain data sources that are part of this subgraph. This includes data sources
-    /// that are included in the subgraph manifest and dynamic data sources.
+    /// that are included in the subgraph manifest and dynamic data sources, but not dynamic
+    /// data sources that have been removed by a mapping.
     pub fn onchain_data_sources(&self) -> impl Iterator<Item = &C::DataSource> + Clone {
         let host_data_sources = self
             .onchain_hosts
             .hosts()
             .iter()
+            .filter(|h| h.done_at().is_none())
             .map(|h| h.data_source().as_onchain().unwrap());
 
         // Datasources that are defined in the subgraph manifest but does not correspond to any host
@@ -108,8 +110,10 @@ where
             if let Some(sender) = self.module_cache.get(&module_hash) {
                 sender.clone()
             } else {
+                let source_map = data_source.source_map();
                 let sender = T::spawn_mapping(
                     module_bytes.as_ref(),
+                    source_map.as_deref().map(Vec::as_slice),
                     logger,
                     self.subgraph_id.clone(),
                     self.host_metrics.cheap_clone(),
@@ -186,16 +190,24 @@ where
     }
 
     /// Reverts any DataSources that have been added from the block forwards (inclusively)
-    /// This function also reverts the done_at status if it was 'done' on this block or later.
-    /// It only returns the offchain::Source because we don't currently need to know which
-    /// DataSources were removed, the source is used so that the offchain DDS can be found again.
+    /// This function also reverts the done_at status if it was 'done' on this block or later,
+    /// which reactivates onchain data sources that were removed by a mapping, as well as the
+    /// chunks of files that were processed on this block or later.
+    /// It only returns the offchain data sources that need to be fetched again because we don't
+    /// currently need to know which DataSources were removed.
     pub(super) fn revert_data_sources(
         &mut self,
         reverted_block: BlockNumber,
-    ) -> Vec<offchain::Source> {
+    ) -> Vec<offchain::DataSource> {
         self.revert_onchain_hosts(reverted_block);
         self.offchain_hosts.remove_ge_block(reverted_block);
 
+        self.onchain_hosts
+            .hosts()
+            .iter()
+            .filter(|host| matches!(host.done_at(), Some(done_at) if done_at >= reverted_block))
+            .for_each(|host| host.set_done_at(None));
+
         // Any File DataSources (Dynamic Data Sources), will have their own causality region
         // which currently is the next number of the sequence but that should be an internal detail.
         // Regardless of the sequence logic, if the current causality region is ONCHAIN then there are
@@ -206,14 +218,27 @@ where
 
         self.offchain_hosts
             .all()
-            .filter(|host| matches!(host.done_at(), Some(done_at) if done_at >= reverted_block))
-            .map(|host| {
-                host.set_done_at(None);
-                host.data_source().as_offchain().unwrap().source.clone()
+            .filter_map(|host| {
+                let ds = host.data_source().as_offchain().unwrap();
+                let chunks_reverted = ds.revert_chunks(reverted_block);
+                let done_reverted =
+                    matches!(host.done_at(), Some(done_at) if done_at >= reverted_block);
+                if done_reverted {
+                    host.set_done_at(None);
+                }
+                (done_reverted || chunks_reverted).then(|| ds.clone())
             })
             .collect()
     }
 
+    /// Whether any onchain data source was removed by a mapping at `block` or later.
+    pub(super) fn has_removed_onchain_hosts_since(&self, block: BlockNumber) -> bool {
+        self.onchain_hosts
+            .hosts()
+            .iter()
+            .any(|host| matches!(host.done_at(), Some(done_at) if done_at >= block))
+    }
+
     /// Because onchain hosts are ordered, removing them based on creation block is cheap and simple.
     fn revert_onchain_hosts(&mut self, reverted_block: BlockNumber) {
         // `onchain_hosts` is ordered by the creation block.
@@ -1,7 +1,8 @@
 mod instance;
 
 use crate::polling_monitor::{
-    spawn_monitor, ArweaveService, IpfsService, PollingMonitor, PollingMonitorMetrics,
+    spawn_chunk_stream, spawn_monitor, ArweaveService, FileStreamer, IpfsService, PollingMonitor,
+    PollingMonitorMetrics,
 };
 use anyhow::{self, Error};
 use bytes::Bytes;
@@ -25,7 +26,7 @@ use graph::{
         TriggerProcessor,
     },
     slog::Logger,
-    tokio::sync::mpsc,
+    tokio::{sync::mpsc, task::AbortHandle},
 };
 use std::sync::{Arc, RwLock};
 use std::{collections::HashMap, time::Instant};
@@ -171,13 +172,22 @@ impl<C: Blockchain, T: RuntimeHostBuilder<C>> IndexingContext<C, T> {
     /// does not affect correctness since triggers that have no matching host will be ignored by
     /// `process_trigger`.
     ///
-    /// File data sources that have been marked not done during this process will get re-queued
+    /// Onchain data sources that were removed by a mapping from `reverted_block` onwards become
+    /// active again. File data sources that have been marked not done during this process will
+    /// get re-queued
     pub fn revert_data_sources(&mut self, reverted_block: BlockNumber) -> Result<(), Error> {
         let removed = self.instance.revert_data_sources(reverted_block);
 
         removed
             .into_iter()
-            .try_for_each(|source| self.offchain_monitor.add_source(source))
+            .try_for_each(|ds| self.offchain_monitor.add_source(&ds))
+    }
+
+    /// Whether reverting to `reverted_block` would reactivate onchain data sources that were
+    /// removed by a mapping, in which case the filters need to be rebuilt.
+    pub fn has_removed_data_sources_since(&self, reverted_block: BlockNumber) -> bool {
+        self.instance
+            .has_removed_onchain_hosts_since(reverted_block)
     }
 
     pub fn add_dynamic_data_source(
@@ -185,16 +195,14 @@ impl<C: Blockchain, T: RuntimeHostBuilder<C>> IndexingContext<C, T> {
         logger: &Logger,
         data_source: DataSource<C>,
     ) -> Result<Option<Arc<T::Host>>, Error> {
-        let offchain_fields = data_source
-            .as_offchain()
-            .map(|ds| (ds.source.clone(), ds.is_processed()));
+        let offchain_ds = data_source.as_offchain().cloned();
         let host = self.instance.add_dynamic_data_source(logger, data_source)?;
 
         if host.is_some() {
-            if let Some((source, is_processed)) = offchain_fields {
+            if let Some(ds) = offchain_ds {
                 // monitor data source only if it has not yet been processed.
-                if !is_processed {
-                    self.offchain_monitor.add_source(source)?;
+                if !ds.is_processed() {
+                    self.offchain_monitor.add_source(&ds)?;
                 }
             }
         }
@@ -224,10 +232,17 @@ impl<C: Blockchain, T: RuntimeHostBuilder<C>> IndexingContext<C, T> {
 }
 
 pub struct OffchainMonitor {
+    logger: Logger,
     ipfs_monitor: PollingMonitor<ContentPath>,
     ipfs_monitor_rx: mpsc::UnboundedReceiver<(ContentPath, Bytes)>,
     arweave_monitor: PollingMonitor<Base64>,
     arweave_monitor_rx: mpsc::UnboundedReceiver<(Base64, Bytes)>,
+    file_streamer: FileStreamer,
+    chunk_tx: mpsc::Sender<offchain::TriggerData>,
+    chunk_rx: mpsc::Receiver<offchain::TriggerData>,
+    /// The tasks streaming the files of data sources that are processed in
+    /// chunks, keyed by the causality region of the data source
+    chunk_streams: HashMap<CausalityRegion, AbortHandle>,
 }
 
 impl OffchainMonitor {
@@ -237,12 +252,17 @@ impl OffchainMonitor {
         subgraph_hash: &DeploymentHash,
         ipfs_service: IpfsService,
         arweave_service: ArweaveService,
+        file_streamer: FileStreamer,
+        max_buffered_chunks: usize,
     ) -> Self {
         let metrics = Arc::new(PollingMonitorMetrics::new(registry, subgraph_hash));
         // The channel is unbounded, as it is expected that `fn ready_offchain_events` is called
         // frequently, or at least with the same frequency that requests are sent.
         let (ipfs_monitor_tx, ipfs_monitor_rx) = mpsc::unbounded_channel();
         let (arweave_monitor_tx, arweave_monitor_rx) = mpsc::unbounded_channel();
+        // The chunk channel is bounded so that streaming a large file never holds more than a
+        // few chunks in memory, which also limits how many chunks are processed in one block.
+        let (chunk_tx, chunk_rx) = mpsc::channel(max_buffered_chunks.max(1));
 
         let ipfs_monitor = spawn_monitor(
             ipfs_service,
@@ -251,17 +271,42 @@ impl OffchainMonitor {
             metrics.cheap_clone(),
         );
 
-        let arweave_monitor = spawn_monitor(arweave_service, arweave_monitor_tx, logger, metrics);
+        let arweave_monitor = spawn_monitor(
+            arweave_service,
+            arweave_monitor_tx,
+            logger.cheap_clone(),
+            metrics,
+        );
         Self {
+            logger,
             ipfs_monitor,
             ipfs_monitor_rx,
             arweave_monitor,
             arweave_monitor_rx,
+            file_streamer,
+            chunk_tx,
+            chunk_rx,
+            chunk_streams: HashMap::new(),
         }
     }
 
-    fn add_source(&mut self, source: offchain::Source) -> Result<(), Error> {
-        match source {
+    fn add_source(&mut self, ds: &offchain::DataSource) -> Result<(), Error> {
+        if let Some(chunks) = &ds.mapping.chunks {
+            // Restart the stream in case the data source was reverted to an earlier chunk
+            let stream = spawn_chunk_stream(
+                self.logger.cheap_clone(),
+                self.file_streamer.cheap_clone(),
+                ds,
+                chunks.clone(),
+                self.chunk_tx.clone(),
+            );
+            if let Some(old) = self.chunk_streams.insert(ds.causality_region, stream) {
+                old.abort();
+            }
+            return Ok(());
+        }
+
+        match ds.source.clone() {
             offchain::Source::Ipfs(cid_file) => self.ipfs_monitor.monitor(cid_file),
             offchain::Source::Arweave(base64) => self.arweave_monitor.monitor(base64),
         };
@@ -277,6 +322,7 @@ impl OffchainMonitor {
                 Ok((cid_file, data)) => triggers.push(offchain::TriggerData {
                     source: offchain::Source::Ipfs(cid_file),
                     data: Arc::new(data),
+                    chunk: None,
                 }),
                 Err(TryRecvError::Disconnected) => {
                     anyhow::bail!("ipfs monitor unexpectedly terminated")
@@ -290,6 +336,7 @@ impl OffchainMonitor {
                 Ok((base64, data)) => triggers.push(offchain::TriggerData {
                     source: offchain::Source::Arweave(base64),
                     data: Arc::new(data),
+                    chunk: None,
                 }),
                 Err(TryRecvError::Disconnected) => {
                     anyhow::bail!("arweave monitor unexpectedly terminated")
@@ -298,6 +345,19 @@ impl OffchainMonitor {
             }
         }
 
+        // `chunk_tx` is owned by `self`, so the channel can not be disconnected.
+        while let Ok(trigger) = self.chunk_rx.try_recv() {
+            triggers.push(trigger);
+        }
+
         Ok(triggers)
     }
 }
+
+impl Drop for OffchainMonitor {
+    fn drop(&mut self) {
+        for stream in self.chunk_streams.values() {
+            stream.abort();
+        }
+    }
+}
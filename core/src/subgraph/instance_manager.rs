@@ -1,7 +1,7 @@
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 
-use crate::polling_monitor::{ArweaveService, IpfsService};
+use crate::polling_monitor::{ArweaveService, FileStreamer, IpfsService};
 use crate::subgraph::context::{IndexingContext, SubgraphKeepAlive};
 use crate::subgraph::inputs::IndexingInputs;
 use crate::subgraph::loader::load_dynamic_data_sources;
@@ -12,6 +12,7 @@ use crate::subgraph::runner::SubgraphRunner;
 use graph::blockchain::block_stream::{BlockStreamMetrics, TriggersAdapterWrapper};
 use graph::blockchain::{Blockchain, BlockchainKind, DataSource, NodeCapabilities};
 use graph::components::metrics::gas::GasMetrics;
+use graph::components::metrics::handler_profile::HandlerProfiler;
 use graph::components::metrics::subgraph::DeploymentStatusMetric;
 use graph::components::store::SourceableStore;
 use graph::components::subgraph::ProofOfIndexingVersion;
@@ -35,10 +36,12 @@ pub struct SubgraphInstanceManager<S: SubgraphStore> {
     subgraph_store: Arc<S>,
     chains: Arc<BlockchainMap>,
     metrics_registry: Arc<MetricsRegistry>,
+    handler_profiler: Arc<HandlerProfiler>,
     instances: SubgraphKeepAlive,
     link_resolver: Arc<dyn LinkResolver>,
     ipfs_service: IpfsService,
     arweave_service: ArweaveService,
+    file_streamer: FileStreamer,
     static_filters: bool,
     env_vars: Arc<EnvVars>,
 
@@ -94,6 +97,36 @@ impl<S: SubgraphStore> SubgraphInstanceManagerTrait for SubgraphInstanceManager<
 
                         self.start_subgraph_inner(logger, loc, runner).await
                     }
+                    BlockchainKind::Bitcoin => {
+                        let runner = instance_manager
+                            .build_subgraph_runner::<graph_chain_bitcoin::Chain>(
+                                logger.clone(),
+                                self.env_vars.cheap_clone(),
+                                loc.clone(),
+                                manifest,
+                                stop_block,
+                                Box::new(SubgraphTriggerProcessor {}),
+                                deployment_status_metric,
+                            )
+                            .await?;
+
+                        self.start_subgraph_inner(logger, loc, runner).await
+                    }
+                    BlockchainKind::Cosmos => {
+                        let runner = instance_manager
+                            .build_subgraph_runner::<graph_chain_cosmos::Chain>(
+                                logger.clone(),
+                                self.env_vars.cheap_clone(),
+                                loc.clone(),
+                                manifest,
+                                stop_block,
+                                Box::new(SubgraphTriggerProcessor {}),
+                                deployment_status_metric,
+                            )
+                            .await?;
+
+                        self.start_subgraph_inner(logger, loc, runner).await
+                    }
                     BlockchainKind::Ethereum => {
                         let runner = instance_manager
                             .build_subgraph_runner::<graph_chain_ethereum::Chain>(
@@ -124,6 +157,21 @@ impl<S: SubgraphStore> SubgraphInstanceManagerTrait for SubgraphInstanceManager<
 
                         self.start_subgraph_inner(logger, loc, runner).await
                     }
+                    BlockchainKind::Solana => {
+                        let runner = instance_manager
+                            .build_subgraph_runner::<graph_chain_solana::Chain>(
+                                logger.clone(),
+                                self.env_vars.cheap_clone(),
+                                loc.clone(),
+                                manifest,
+                                stop_block,
+                                Box::new(SubgraphTriggerProcessor {}),
+                                deployment_status_metric,
+                            )
+                            .await?;
+
+                        self.start_subgraph_inner(logger, loc, runner).await
+                    }
                     BlockchainKind::Substreams => {
                         let runner = instance_manager
                             .build_subgraph_runner::<graph_chain_substreams::Chain>(
@@ -178,6 +226,7 @@ impl<S: SubgraphStore> SubgraphInstanceManagerTrait for SubgraphInstanceManager<
         }
 
         self.instances.remove(&loc.id);
+        self.handler_profiler.remove(loc.hash.as_str());
 
         info!(logger, "Stopped subgraph");
     }
@@ -191,9 +240,11 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
         chains: Arc<BlockchainMap>,
         sg_metrics: Arc<SubgraphCountMetric>,
         metrics_registry: Arc<MetricsRegistry>,
+        handler_profiler: Arc<HandlerProfiler>,
         link_resolver: Arc<dyn LinkResolver>,
         ipfs_service: IpfsService,
         arweave_service: ArweaveService,
+        file_streamer: FileStreamer,
         static_filters: bool,
     ) -> Self {
         let logger = logger_factory.component_logger("SubgraphInstanceManager", None);
@@ -204,12 +255,14 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             subgraph_store,
             chains,
             metrics_registry: metrics_registry.cheap_clone(),
+            handler_profiler,
             instances: SubgraphKeepAlive::new(sg_metrics),
             link_resolver,
             ipfs_service,
             static_filters,
             env_vars,
             arweave_service,
+            file_streamer,
             subgraph_start_counter: Arc::new(AtomicU64::new(0)),
         }
     }
@@ -356,14 +409,19 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
         // that is done
         store.start_subgraph_deployment(&logger).await?;
 
-        let dynamic_data_sources =
+        let (dynamic_data_sources, dynamic_done_at): (Vec<_>, Vec<_>) =
             load_dynamic_data_sources(store.clone(), logger.clone(), &manifest)
                 .await
-                .context("Failed to load dynamic data sources")?;
+                .context("Failed to load dynamic data sources")?
+                .into_iter()
+                .unzip();
 
-        // Combine the data sources from the manifest with the dynamic data sources
+        // Combine the data sources from the manifest with the dynamic data sources. `done_at`
+        // lines up with `data_sources` and is only set for dynamic data sources.
         let mut data_sources = manifest.data_sources.clone();
+        let mut done_at = vec![None; data_sources.len()];
         data_sources.extend(dynamic_data_sources);
+        done_at.extend(dynamic_done_at);
 
         info!(logger, "Data source count at start: {}", data_sources.len());
 
@@ -449,6 +507,7 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             deployment.hash.as_str(),
             stopwatch_metrics.clone(),
             gas_metrics.clone(),
+            self.handler_profiler.cheap_clone(),
         ));
 
         let subgraph_metrics = Arc::new(SubgraphInstanceMetrics::new(
@@ -472,6 +531,8 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
             &manifest.id,
             self.ipfs_service.clone(),
             self.arweave_service.clone(),
+            self.file_streamer.cheap_clone(),
+            self.env_vars.mappings.max_buffered_file_chunks,
         );
 
         // Initialize deployment_head with current deployment head. Any sort of trouble in
@@ -544,8 +605,13 @@ impl<S: SubgraphStore> SubgraphInstanceManager<S> {
                 tp,
                 decoder,
             );
-            for data_source in data_sources {
-                ctx.add_dynamic_data_source(&logger, data_source)?;
+            for (data_source, done_at) in data_sources.into_iter().zip(done_at) {
+                let host = ctx.add_dynamic_data_source(&logger, data_source)?;
+
+                // Keep data sources that were removed by a mapping inactive.
+                if let (Some(host), Some(done_at)) = (host, done_at) {
+                    host.set_done_at(Some(done_at));
+                }
             }
             ctx
         };
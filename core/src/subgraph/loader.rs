@@ -5,15 +5,18 @@ use graph::components::store::WritableStore;
 use graph::data_source::DataSource;
 use graph::prelude::*;
 
+/// Loads the dynamic data sources of the subgraph, together with the block at which they were
+/// marked as done. Onchain data sources that were removed by a mapping are loaded as well, so that
+/// they can be reactivated by a revert.
 pub async fn load_dynamic_data_sources<C: Blockchain>(
     store: Arc<dyn WritableStore>,
     logger: Logger,
     manifest: &SubgraphManifest<C>,
-) -> Result<Vec<DataSource<C>>, Error> {
+) -> Result<Vec<(DataSource<C>, Option<BlockNumber>)>, Error> {
     let manifest_idx_and_name = manifest.template_idx_and_name().collect();
     let start_time = Instant::now();
 
-    let mut data_sources: Vec<DataSource<C>> = vec![];
+    let mut data_sources: Vec<(DataSource<C>, Option<BlockNumber>)> = vec![];
 
     for stored in store
         .load_dynamic_data_sources(manifest_idx_and_name)
@@ -25,16 +28,17 @@ pub async fn load_dynamic_data_sources<C: Blockchain>(
             .find(|template| template.manifest_idx() == stored.manifest_idx)
             .ok_or_else(|| anyhow!("no template with idx `{}` was found", stored.manifest_idx))?;
 
+        let done_at = stored.done_at;
         let ds = DataSource::from_stored_dynamic_data_source(template, stored)?;
 
         // The data sources are ordered by the creation block.
         // See also 8f1bca33-d3b7-4035-affc-fd6161a12448.
         anyhow::ensure!(
-            data_sources.last().and_then(|d| d.creation_block()) <= ds.creation_block(),
+            data_sources.last().and_then(|(d, _)| d.creation_block()) <= ds.creation_block(),
             "Assertion failure: new data source has lower creation block than existing ones"
         );
 
-        data_sources.push(ds);
+        data_sources.push((ds, done_at));
     }
 
     trace!(
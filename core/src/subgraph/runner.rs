@@ -40,6 +40,7 @@ use graph::prelude::{
 use graph::schema::EntityKey;
 use graph::slog::{debug, error, info, o, trace, warn, Logger};
 use graph::util::{backoff::ExponentialBackoff, lfu_cache::LfuCache};
+use std::collections::BTreeSet;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::vec;
@@ -158,11 +159,15 @@ where
             .map(|ds| SubgraphFilter {
                 subgraph: ds.source.address(),
                 start_block: ds.source.start_block,
+                // Several handlers can handle different operations on the
+                // same entity, but we only want to fetch its changes once
                 entities: ds
                     .mapping
                     .handlers
                     .iter()
                     .map(|handler| handler.entity.clone())
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
                     .collect(),
                 manifest_idx: ds.manifest_idx,
             })
@@ -465,6 +470,12 @@ where
                 // Losing the cache is a bit annoying but not an issue for correctness.
                 //
                 // See also b21fa73b-6453-4340-99fb-1a78ec62efb1.
+                //
+                // Data sources removed by the mappings for this block are reactivated, since the
+                // block will be processed again.
+                self.ctx
+                    .revert_data_sources(block_ptr.number)
+                    .non_deterministic()?;
                 return Ok(Action::Restart);
             }
         }
@@ -724,10 +735,23 @@ where
         let BlockState {
             deterministic_errors,
             mut persisted_data_sources,
+            removed_data_sources,
             metrics: block_state_metrics,
             ..
         } = block_state;
 
+        // Onchain data sources removed by a mapping must be dropped from the block stream
+        // filters, unless static filters are in use.
+        let needs_restart = needs_restart
+            || (!self.is_static_filters_enabled() && !removed_data_sources.is_empty());
+
+        // Both processed offchain data sources and removed onchain data sources are marked as
+        // done in the store.
+        let done_data_sources: Vec<_> = processed_offchain_data_sources
+            .into_iter()
+            .chain(removed_data_sources)
+            .collect();
+
         let first_error = deterministic_errors.first().cloned();
 
         let is_caught_up = self.is_caught_up(&block_ptr).await.non_deterministic()?;
@@ -743,7 +767,7 @@ where
                 &self.metrics.host.stopwatch,
                 persisted_data_sources,
                 deterministic_errors,
-                processed_offchain_data_sources,
+                done_data_sources,
                 is_non_fatal_errors_active,
                 is_caught_up,
             )
@@ -1598,9 +1622,17 @@ where
             .deployment_head
             .set(subgraph_ptr.number as f64);
 
+        // Data sources that were removed in the reverted blocks become active again, so the
+        // filters need to include them.
+        let reactivates_data_sources = !self.is_static_filters_enabled()
+            && self
+                .ctx
+                .has_removed_data_sources_since(revert_to_ptr.number + 1);
+
         self.revert_state_to(revert_to_ptr.number)?;
 
-        let needs_restart: bool = self.needs_restart(revert_to_ptr, subgraph_ptr);
+        let needs_restart: bool =
+            self.needs_restart(revert_to_ptr, subgraph_ptr) || reactivates_data_sources;
 
         let action = if needs_restart {
             Action::Restart
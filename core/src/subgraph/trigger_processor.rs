@@ -4,12 +4,12 @@ use graph::cheap_clone::CheapClone;
 use graph::components::store::SubgraphFork;
 use graph::components::subgraph::{MappingError, SharedProofOfIndexing};
 use graph::components::trigger_processor::{HostedTrigger, RunnableTriggers};
-use graph::data_source::TriggerData;
+use graph::data_source::{MappingTrigger, TriggerData};
 use graph::prelude::tokio::time::Instant;
 use graph::prelude::{
     BlockState, RuntimeHost, RuntimeHostBuilder, SubgraphInstanceMetrics, TriggerProcessor,
 };
-use graph::slog::Logger;
+use graph::slog::{error, Logger};
 use std::marker::PhantomData;
 use std::sync::Arc;
 
@@ -46,27 +46,59 @@ where
             mapping_trigger,
         } in triggers
         {
+            // A handler for an earlier trigger in this block might have removed the data source.
+            if host.data_source().is_chain_based() && host.done_at().is_some() {
+                continue;
+            }
+
+            let chunk = match &mapping_trigger.trigger {
+                MappingTrigger::Offchain(trigger) => trigger.chunk.clone(),
+                _ => None,
+            };
+
             let start = Instant::now();
-            state = host
-                .process_mapping_trigger(
-                    logger,
-                    mapping_trigger,
-                    state,
-                    proof_of_indexing.cheap_clone(),
-                    debug_fork,
-                    instrument,
-                )
-                .await?;
+            match chunk.as_ref().and_then(|chunk| chunk.error.as_ref()) {
+                // The rest of the file can never be processed; the data
+                // source is done without calling the handler again
+                Some(e) => error!(logger, "Failed to split file into chunks, giving up on it";
+                    "data_source" => host.data_source().name(),
+                    "error" => e),
+                None => {
+                    state = host
+                        .process_mapping_trigger(
+                            logger,
+                            mapping_trigger,
+                            state,
+                            proof_of_indexing.cheap_clone(),
+                            debug_fork,
+                            instrument,
+                        )
+                        .await?
+                }
+            }
             let elapsed = start.elapsed().as_secs_f64();
             subgraph_metrics.observe_trigger_processing_duration(elapsed);
 
             if let Some(ds) = host.data_source().as_offchain() {
-                ds.mark_processed_at(block.number());
-                // Remove this offchain data source since it has just been processed.
+                if chunk.is_some() {
+                    ds.mark_chunk_processed_at(block.number());
+                }
+                // A file that is processed in chunks is only done after its last chunk.
+                if chunk.map_or(true, |chunk| chunk.last) {
+                    // Remove this offchain data source since it has just been processed.
+                    ds.mark_processed_at(block.number());
+                }
                 state
                     .processed_data_sources
                     .push(ds.as_stored_dynamic_data_source());
             }
+
+            if state.take_removed_data_source() {
+                host.set_done_at(Some(block.number()));
+                let mut stored = host.data_source().as_stored_dynamic_data_source();
+                stored.done_at = Some(block.number());
+                state.removed_data_sources.push(stored);
+            }
         }
 
         if state.deterministic_errors.len() != error_count {
@@ -7,6 +7,7 @@ use anyhow::Error;
 use async_stream::stream;
 use futures03::Stream;
 use prost_types::Any;
+use serde::Deserialize;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
 use std::sync::Arc;
@@ -471,13 +472,27 @@ pub enum SubgraphTriggerScanRange<C: Blockchain> {
     Range(BlockNumber, BlockNumber),
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// The kind of change that a source subgraph made to an entity. Handlers of
+/// subgraph data sources list the kinds of changes they want to be called
+/// for in the manifest as `create`, `modify`, and `delete`
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum EntityOperationKind {
     Create,
     Modify,
     Delete,
 }
 
+impl fmt::Display for EntityOperationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntityOperationKind::Create => write!(f, "create"),
+            EntityOperationKind::Modify => write!(f, "modify"),
+            EntityOperationKind::Delete => write!(f, "delete"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct EntitySourceOperation {
     pub entity_op: EntityOperationKind,
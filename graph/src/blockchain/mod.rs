@@ -332,6 +332,12 @@ pub trait DataSource<C: Blockchain>: 'static + Sized + Send + Sync + Clone {
 
     fn runtime(&self) -> Option<Arc<Vec<u8>>>;
 
+    /// The source map for the wasm module returned by `runtime`, if one was
+    /// published alongside the mapping file
+    fn source_map(&self) -> Option<Arc<Vec<u8>>> {
+        None
+    }
+
     fn handler_kinds(&self) -> HashSet<&str>;
 
     /// Checks if `trigger` matches this data source, and if so decodes it into a `MappingTrigger`.
@@ -559,12 +565,21 @@ pub enum BlockchainKind {
     /// Arweave chains that are compatible.
     Arweave,
 
+    /// Bitcoin itself or UTXO chains that are compatible.
+    Bitcoin,
+
+    /// Cosmos SDK chains that are compatible.
+    Cosmos,
+
     /// Ethereum itself or chains that are compatible.
     Ethereum,
 
     /// NEAR chains (Mainnet, Testnet) or chains that are compatible
     Near,
 
+    /// Solana clusters (Mainnet Beta, Devnet) or chains that are compatible
+    Solana,
+
     Substreams,
 }
 
@@ -572,8 +587,11 @@ impl fmt::Display for BlockchainKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let value = match self {
             BlockchainKind::Arweave => "arweave",
+            BlockchainKind::Bitcoin => "bitcoin",
+            BlockchainKind::Cosmos => "cosmos",
             BlockchainKind::Ethereum => "ethereum",
             BlockchainKind::Near => "near",
+            BlockchainKind::Solana => "solana",
             BlockchainKind::Substreams => "substreams",
         };
         write!(f, "{}", value)
@@ -586,8 +604,11 @@ impl FromStr for BlockchainKind {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "arweave" => Ok(BlockchainKind::Arweave),
+            "bitcoin" => Ok(BlockchainKind::Bitcoin),
+            "cosmos" => Ok(BlockchainKind::Cosmos),
             "ethereum" => Ok(BlockchainKind::Ethereum),
             "near" => Ok(BlockchainKind::Near),
+            "solana" => Ok(BlockchainKind::Solana),
             "substreams" => Ok(BlockchainKind::Substreams),
             "subgraph" => Ok(BlockchainKind::Ethereum), // TODO(krishna): We should detect the blockchain kind from the source subgraph
             _ => Err(anyhow!("unknown blockchain kind {}", s)),
@@ -245,6 +245,14 @@ where
         // Make sure not to include genesis in the reorg threshold.
         let reorg_threshold = ctx.reorg_threshold.min(head_ptr.number);
 
+        if let Some(ptr) = &subgraph_ptr {
+            if !self.filter.subgraph_filter.is_empty() {
+                if let Some(revert_to) = source_revert_ptr(&ctx.adapter, ptr, &head_ptr).await? {
+                    return Ok(ReconciliationStep::Revert(revert_to));
+                }
+            }
+        }
+
         // Only continue if the subgraph block ptr is behind the head block ptr.
         // subgraph_ptr > head_ptr shouldn't happen, but if it does, it's safest to just stop.
         if let Some(ptr) = &subgraph_ptr {
@@ -489,13 +497,57 @@ where
     }
 
     async fn parent_ptr(&self, block_ptr: &BlockPtr, reason: &str) -> Result<BlockPtr, Error> {
-        let ptr =
-            self.adapter.parent_ptr(block_ptr).await?.ok_or_else(|| {
-                anyhow!("Failed to get parent pointer for {block_ptr} ({reason})")
-            })?;
+        parent_ptr(&self.adapter, block_ptr, reason).await
+    }
+}
+
+async fn parent_ptr<C: Blockchain>(
+    adapter: &TriggersAdapterWrapper<C>,
+    block_ptr: &BlockPtr,
+    reason: &str,
+) -> Result<BlockPtr, Error> {
+    let ptr = adapter
+        .parent_ptr(block_ptr)
+        .await?
+        .ok_or_else(|| anyhow!("Failed to get parent pointer for {block_ptr} ({reason})"))?;
+
+    Ok(ptr)
+}
 
-        Ok(ptr)
+/// When the subgraph uses other subgraphs as its sources, the head block
+/// ptr `source_ptr` is the block ptr of the source that is furthest behind.
+/// Sources only move backwards when they revert blocks, because of a reorg
+/// or because they were rewound, and we need to revert the changes we made
+/// based on their data for these blocks, too. Return the block to which
+/// the subgraph needs to revert, or `None` if the sources have not reverted
+/// any blocks the subgraph has processed.
+///
+/// Sources are read through their own deployment's store, and it therefore
+/// does not matter which shard they are stored in
+async fn source_revert_ptr<C: Blockchain>(
+    adapter: &TriggersAdapterWrapper<C>,
+    subgraph_ptr: &BlockPtr,
+    source_ptr: &BlockPtr,
+) -> Result<Option<BlockPtr>, Error> {
+    if subgraph_ptr.number > source_ptr.number {
+        // If both pointers are on the main chain, the source head is an
+        // ancestor of ours and we can revert to it in one step. Otherwise,
+        // we step back one block at a time until we are back on the chain
+        // of the source
+        let revert_to = if adapter.is_on_main_chain(subgraph_ptr.clone()).await?
+            && adapter.is_on_main_chain(source_ptr.clone()).await?
+        {
+            source_ptr.clone()
+        } else {
+            parent_ptr(adapter, subgraph_ptr, "source reverted").await?
+        };
+        return Ok(Some(revert_to));
+    }
+    if subgraph_ptr.number == source_ptr.number && subgraph_ptr.hash != source_ptr.hash {
+        let parent = parent_ptr(adapter, subgraph_ptr, "source on different fork").await?;
+        return Ok(Some(parent));
     }
+    Ok(None)
 }
 
 impl<C: Blockchain> BlockStream<C> for PollingBlockStream<C> {
@@ -648,3 +700,107 @@ impl<C: Blockchain> Stream for PollingBlockStream<C> {
         result.map_err(BlockStreamError::from)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+
+    use anyhow::Error;
+    use async_trait::async_trait;
+    use web3::types::H256;
+
+    use crate::blockchain::block_stream::{BlockWithTriggers, TriggersAdapterWrapper};
+    use crate::blockchain::mock::{MockBlock, MockBlockchain, MockTriggerFilter};
+    use crate::blockchain::{BlockPtr, TriggersAdapter};
+    use crate::prelude::{BlockHash, BlockNumber, Logger};
+
+    use super::source_revert_ptr;
+
+    /// The block with `number` on `fork`; fork 0 is the main chain
+    fn ptr(number: BlockNumber, fork: u8) -> BlockPtr {
+        let mut hash = [fork; 32];
+        hash[0] = number as u8;
+        BlockPtr::from((H256::from(hash), number))
+    }
+
+    fn fork_of(ptr: &BlockPtr) -> u8 {
+        ptr.hash.as_slice()[1]
+    }
+
+    struct ForkAdapter;
+
+    #[async_trait]
+    impl TriggersAdapter<MockBlockchain> for ForkAdapter {
+        async fn ancestor_block(
+            &self,
+            _ptr: BlockPtr,
+            _offset: BlockNumber,
+            _root: Option<BlockHash>,
+        ) -> Result<Option<MockBlock>, Error> {
+            unimplemented!()
+        }
+
+        async fn load_block_ptrs_by_numbers(
+            &self,
+            _logger: Logger,
+            _block_numbers: BTreeSet<BlockNumber>,
+        ) -> Result<Vec<MockBlock>, Error> {
+            unimplemented!()
+        }
+
+        async fn chain_head_ptr(&self) -> Result<Option<BlockPtr>, Error> {
+            unimplemented!()
+        }
+
+        async fn scan_triggers(
+            &self,
+            _from: BlockNumber,
+            _to: BlockNumber,
+            _filter: &MockTriggerFilter,
+        ) -> Result<(Vec<BlockWithTriggers<MockBlockchain>>, BlockNumber), Error> {
+            unimplemented!()
+        }
+
+        async fn triggers_in_block(
+            &self,
+            _logger: &Logger,
+            _block: MockBlock,
+            _filter: &MockTriggerFilter,
+        ) -> Result<BlockWithTriggers<MockBlockchain>, Error> {
+            unimplemented!()
+        }
+
+        async fn is_on_main_chain(&self, ptr: BlockPtr) -> Result<bool, Error> {
+            Ok(fork_of(&ptr) == 0)
+        }
+
+        async fn parent_ptr(&self, block: &BlockPtr) -> Result<Option<BlockPtr>, Error> {
+            Ok(Some(ptr(block.number - 1, fork_of(block))))
+        }
+    }
+
+    #[tokio::test]
+    async fn revert_when_source_reverts() {
+        let adapter = TriggersAdapterWrapper::new(Arc::new(ForkAdapter), vec![]);
+        let revert_ptr = |subgraph_ptr, source_ptr| {
+            let adapter = &adapter;
+            async move {
+                source_revert_ptr(adapter, &subgraph_ptr, &source_ptr)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // The source was rewound on the main chain; revert to it directly
+        assert_eq!(Some(ptr(5, 0)), revert_ptr(ptr(10, 0), ptr(5, 0)).await);
+        // The subgraph processed blocks from a fork the source reverted;
+        // step back one block at a time
+        assert_eq!(Some(ptr(9, 1)), revert_ptr(ptr(10, 1), ptr(8, 0)).await);
+        // The source is at the same block number, but on a different fork
+        assert_eq!(Some(ptr(9, 1)), revert_ptr(ptr(10, 1), ptr(10, 0)).await);
+        // The source is at the same block or ahead of the subgraph
+        assert_eq!(None, revert_ptr(ptr(10, 0), ptr(10, 0)).await);
+        assert_eq!(None, revert_ptr(ptr(5, 0), ptr(10, 0)).await);
+    }
+}
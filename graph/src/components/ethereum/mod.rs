@@ -2,5 +2,5 @@ mod types;
 
 pub use self::types::{
     evaluate_transaction_status, EthereumBlock, EthereumBlockWithCalls, EthereumCall,
-    LightEthereumBlock, LightEthereumBlockExt,
+    EthereumTransfer, LightEthereumBlock, LightEthereumBlockExt,
 };
@@ -72,6 +72,9 @@ pub struct EthereumBlockWithCalls {
     /// The calls in this block; `None` means we haven't checked yet,
     /// `Some(vec![])` means that we checked and there were none
     pub calls: Option<Vec<EthereumCall>>,
+    /// The internal transfers in this block, with the same meaning for
+    /// `None` as for `calls`
+    pub transfers: Option<Vec<EthereumTransfer>>,
 }
 
 impl EthereumBlockWithCalls {
@@ -162,6 +165,23 @@ impl EthereumCall {
     }
 }
 
+/// A transfer of ETH that happens while a transaction is executed, for
+/// example when a contract sends ETH to another account. The value that
+/// the transaction itself sends is not an internal transfer.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EthereumTransfer {
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub block_number: BlockNumber,
+    pub block_hash: H256,
+    pub transaction_hash: H256,
+    pub transaction_index: u64,
+    /// The position of this transfer among the internal transfers of its
+    /// transaction
+    pub transfer_index: u64,
+}
+
 impl From<EthereumBlock> for BlockPtr {
     fn from(b: EthereumBlock) -> BlockPtr {
         BlockPtr::from((b.block.hash.unwrap(), b.block.number.unwrap().as_u64()))
@@ -1,8 +1,10 @@
+use crate::components::store::StoreEvent;
 use crate::data::query::QueryResults;
-use crate::data::query::{Query, QueryTarget};
+use crate::data::query::{Query, QueryExecutionError, QueryTarget, RegisteredQueries};
 use crate::prelude::DeploymentHash;
 
 use async_trait::async_trait;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -27,9 +29,54 @@ pub trait GraphQlRunner: Send + Sync + 'static {
         max_skip: Option<u32>,
     ) -> QueryResults;
 
+    /// Determine the deployment and the entity types in it that the result
+    /// of `query` depends on, so that a subscription for `query` can be
+    /// rerun whenever one of them changes
+    async fn subscription_target(
+        self: Arc<Self>,
+        _query: Query,
+        _target: QueryTarget,
+    ) -> Result<SubscriptionTarget, Vec<QueryExecutionError>> {
+        Err(vec![QueryExecutionError::NotSupported(
+            "Subscriptions are not supported".to_owned(),
+        )])
+    }
+
+    /// Return the query documents that have been registered for the
+    /// deployment that `target` refers to
+    async fn registered_queries(
+        self: Arc<Self>,
+        _target: QueryTarget,
+    ) -> Result<Arc<RegisteredQueries>, QueryExecutionError> {
+        Ok(Arc::new(RegisteredQueries::default()))
+    }
+
     fn metrics(&self) -> Arc<dyn GraphQLMetrics>;
 }
 
+/// The data that a GraphQL subscription depends on
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubscriptionTarget {
+    pub deployment: DeploymentHash,
+    /// The names of the entity types, aggregations included, that the
+    /// query selects
+    pub entity_types: BTreeSet<String>,
+    /// Whether any change to the deployment affects the query, e.g.,
+    /// because it selects `_meta`
+    pub any_change: bool,
+}
+
+impl SubscriptionTarget {
+    /// Return `true` if the result of the query might have changed
+    /// because of `event`
+    pub fn is_affected_by(&self, event: &StoreEvent) -> bool {
+        event.entity_changes.iter().any(|change| {
+            change.subgraph_id == self.deployment
+                && (self.any_change || self.entity_types.contains(&change.entity_type))
+        })
+    }
+}
+
 pub trait GraphQLMetrics: Send + Sync + 'static {
     fn observe_query_execution(&self, duration: Duration, results: &QueryResults);
     fn observe_query_parsing(&self, duration: Duration, results: &QueryResults);
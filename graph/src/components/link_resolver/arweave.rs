@@ -1,7 +1,9 @@
 use std::pin::Pin;
 
 use async_trait::async_trait;
+use bytes::Bytes;
 use futures03::prelude::Stream;
+use futures03::stream::{BoxStream, StreamExt, TryStreamExt};
 use reqwest::Client;
 use serde_json::Value;
 use slog::{debug, Logger};
@@ -56,6 +58,24 @@ impl ArweaveClient {
             client: Client::default(),
         }
     }
+
+    /// Stream the contents of `file` without loading all of it into memory
+    pub async fn get_stream(
+        &self,
+        file: &Base64,
+    ) -> Result<BoxStream<'static, Result<Bytes, ArweaveClientError>>, ArweaveClientError> {
+        let url = self.base_url.join(file.as_str())?;
+        let rsp = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|rsp| rsp.error_for_status())?;
+
+        debug!(self.logger, "Streaming arweave file {file}");
+
+        Ok(rsp.bytes_stream().err_into().boxed())
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// What a single run of a handler cost
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HandlerRun {
+    pub wall_time: Duration,
+    pub gas: u64,
+    pub store_get_count: u64,
+    /// The `store.get` calls that could not be answered from the entity
+    /// cache and had to go to the database
+    pub store_get_cache_misses: u64,
+    pub eth_call_count: u64,
+}
+
+/// Totals over all runs of a handler
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HandlerStats {
+    pub count: u64,
+    pub wall_time: Duration,
+    pub max_wall_time: Duration,
+    pub gas: u64,
+    pub store_get_count: u64,
+    pub store_get_cache_misses: u64,
+    pub eth_call_count: u64,
+}
+
+impl HandlerStats {
+    fn add(&mut self, run: &HandlerRun) {
+        self.count += 1;
+        self.wall_time += run.wall_time;
+        self.max_wall_time = self.max_wall_time.max(run.wall_time);
+        self.gas += run.gas;
+        self.store_get_count += run.store_get_count;
+        self.store_get_cache_misses += run.store_get_cache_misses;
+        self.eth_call_count += run.eth_call_count;
+    }
+}
+
+/// Collects `HandlerStats` for all deployments indexed by this node since
+/// it was started so that they can be queried through the index node
+/// API. The same numbers are also exported as Prometheus histograms by
+/// `HostMetrics`.
+#[derive(Debug, Default)]
+pub struct HandlerProfiler {
+    stats: RwLock<HashMap<String, BTreeMap<String, HandlerStats>>>,
+}
+
+impl HandlerProfiler {
+    pub fn record(&self, deployment: &str, handler: &str, run: &HandlerRun) {
+        self.stats
+            .write()
+            .unwrap()
+            .entry(deployment.to_string())
+            .or_default()
+            .entry(handler.to_string())
+            .or_default()
+            .add(run);
+    }
+
+    /// The stats for all handlers of `deployment`, ordered by handler name
+    pub fn stats(&self, deployment: &str) -> Vec<(String, HandlerStats)> {
+        self.stats
+            .read()
+            .unwrap()
+            .get(deployment)
+            .map(|handlers| {
+                handlers
+                    .iter()
+                    .map(|(handler, stats)| (handler.clone(), stats.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Forget everything about `deployment`, e.g., when it is unassigned
+    /// from this node
+    pub fn remove(&self, deployment: &str) {
+        self.stats.write().unwrap().remove(deployment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{HandlerProfiler, HandlerRun};
+
+    #[test]
+    fn aggregates_runs() {
+        let profiler = HandlerProfiler::default();
+        let run = |ms, eth_calls| HandlerRun {
+            wall_time: Duration::from_millis(ms),
+            gas: 10,
+            store_get_count: 2,
+            store_get_cache_misses: 1,
+            eth_call_count: eth_calls,
+        };
+
+        profiler.record("QmDeployment", "handleTransfer", &run(5, 0));
+        profiler.record("QmDeployment", "handleTransfer", &run(20, 3));
+        profiler.record("QmDeployment", "handleApproval", &run(1, 0));
+        profiler.record("QmOther", "handleTransfer", &run(7, 1));
+
+        let stats = profiler.stats("QmDeployment");
+        let handlers: Vec<_> = stats.iter().map(|(handler, _)| handler.as_str()).collect();
+        assert_eq!(vec!["handleApproval", "handleTransfer"], handlers);
+
+        let transfer = &stats[1].1;
+        assert_eq!(2, transfer.count);
+        assert_eq!(Duration::from_millis(25), transfer.wall_time);
+        assert_eq!(Duration::from_millis(20), transfer.max_wall_time);
+        assert_eq!(20, transfer.gas);
+        assert_eq!(4, transfer.store_get_count);
+        assert_eq!(2, transfer.store_get_cache_misses);
+        assert_eq!(3, transfer.eth_call_count);
+
+        profiler.remove("QmDeployment");
+        assert!(profiler.stats("QmDeployment").is_empty());
+        assert_eq!(1, profiler.stats("QmOther").len());
+    }
+}
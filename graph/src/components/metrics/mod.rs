@@ -18,6 +18,9 @@ pub mod gas;
 
 pub mod block_state;
 
+/// Per-handler profiles for the index node API.
+pub mod handler_profile;
+
 /// Create an unregistered counter with labels
 pub fn counter_with_labels(
     name: &str,
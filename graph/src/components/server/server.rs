@@ -62,6 +62,8 @@ where
                 http1::Builder::new()
                     // `service_fn` converts our function in a `Service`
                     .serve_connection(io, new_service)
+                    // Needed for WebSocket connections
+                    .with_upgrades()
                     .await
             });
         }
@@ -1,6 +1,6 @@
 use anyhow::{anyhow, bail};
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::{self, Debug};
 use std::sync::Arc;
 
@@ -13,7 +13,7 @@ use crate::schema::{EntityKey, InputSchema};
 use crate::util::intern::Error as InternError;
 use crate::util::lfu_cache::{EvictStats, LfuCache};
 
-use super::{BlockNumber, DerivedEntityQuery, LoadRelatedRequest, StoreError};
+use super::{BlockNumber, DerivedEntityQuery, FilteredEntityQuery, LoadRelatedRequest, StoreError};
 
 pub type EntityLfuCache = LfuCache<EntityKey, Option<Arc<Entity>>>;
 
@@ -113,6 +113,10 @@ pub struct EntityCache {
     // Sequence number of the next VID value for this block. The value written
     // in the database consist of a block number and this SEQ number.
     pub vid_seq: u32,
+
+    /// The number of times `get` had to read an entity from the store
+    /// because it was not in `current`
+    pub store_reads: usize,
 }
 
 impl Debug for EntityCache {
@@ -141,6 +145,7 @@ impl EntityCache {
             store,
             seq: 0,
             vid_seq: RESERVED_VIDS,
+            store_reads: 0,
         }
     }
 
@@ -162,6 +167,7 @@ impl EntityCache {
             store,
             seq: 0,
             vid_seq: RESERVED_VIDS,
+            store_reads: 0,
         }
     }
 
@@ -199,6 +205,7 @@ impl EntityCache {
                 if !self.current.contains_key(key) {
                     let entity = self.store.get(key)?;
                     self.current.insert(key.clone(), entity.map(Arc::new));
+                    self.store_reads += 1;
                 }
                 // Unwrap: we just inserted the entity
                 self.current.get(key).unwrap().cheap_clone()
@@ -355,6 +362,68 @@ impl EntityCache {
         Ok(entity_map.into_values().collect())
     }
 
+    /// Find the entities that match `query`, including changes that have
+    /// not been written to the store yet. The result is ordered by id and
+    /// contains at most `query.first` entities
+    pub fn query(&mut self, query: &FilteredEntityQuery) -> Result<Vec<Entity>, anyhow::Error> {
+        // What the store has for entities with pending changes is out of
+        // date; whether they match is decided by their current version
+        let changed: BTreeSet<EntityKey> = self
+            .updates
+            .keys()
+            .chain(self.handler_updates.keys())
+            .filter(|key| {
+                key.entity_type == query.entity_type
+                    && key.causality_region == query.causality_region
+            })
+            .cloned()
+            .collect();
+
+        // Ask for enough entities that we still have `first` of them after
+        // dropping the ones with pending changes
+        let store_query = FilteredEntityQuery {
+            entity_type: query.entity_type.clone(),
+            filter: query.filter.clone(),
+            first: query.first + changed.len(),
+            causality_region: query.causality_region,
+        };
+        let mut entity_map = self.store.get_filtered(&store_query)?;
+
+        for (key, entity) in entity_map.iter() {
+            // Only insert to the cache if it's not already there
+            if !self.current.contains_key(key) {
+                self.current
+                    .insert(key.clone(), Some(Arc::new(entity.clone())));
+            }
+        }
+        entity_map.retain(|key, _| !changed.contains(key));
+
+        // Load the stored versions of changed entities we haven't seen yet
+        // with one query so that `get` below does not hit the store
+        let missing: BTreeSet<EntityKey> = changed
+            .iter()
+            .filter(|key| !self.current.contains_key(key))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            let mut found = self.store.get_many(missing.clone())?;
+            for key in missing {
+                let entity = found.remove(&key).map(Arc::new);
+                self.current.insert(key, entity);
+            }
+        }
+
+        for key in changed {
+            if let Some(entity) = self.get(&key, GetScope::Store)? {
+                if query.matches(&key, &entity) {
+                    entity_map.insert(key, entity.as_ref().clone());
+                }
+            }
+        }
+
+        Ok(entity_map.into_values().take(query.first).collect())
+    }
+
     pub fn remove(&mut self, key: EntityKey) {
         self.entity_op(key, EntityOp::Remove);
     }
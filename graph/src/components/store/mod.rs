@@ -96,6 +96,71 @@ impl DerivedEntityQuery {
     }
 }
 
+/// A query for the entities of one type that match a filter, used by the
+/// `store.query` host function. Results are ordered by id and the query
+/// returns at most `first` entities
+#[derive(Debug)]
+pub struct FilteredEntityQuery {
+    /// Name of the entity to search
+    pub entity_type: EntityType,
+    /// The filter entities must match. It is a conjunction of comparisons
+    /// of scalar attributes with a value; `matches` treats any other kind of
+    /// filter as not matching anything
+    pub filter: EntityFilter,
+    /// The maximum number of entities to return
+    pub first: usize,
+
+    /// The causality region of the data source that is doing the lookup.
+    /// Only entities from that causality region are returned
+    pub causality_region: CausalityRegion,
+}
+
+impl FilteredEntityQuery {
+    /// Checks if a given key and entity match this query.
+    pub fn matches(&self, key: &EntityKey, entity: &Entity) -> bool {
+        use std::cmp::Ordering as Cmp;
+
+        fn cmp(a: &Value, b: &Value) -> Option<Cmp> {
+            match (a, b) {
+                (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+                (Value::Int8(a), Value::Int8(b)) => Some(a.cmp(b)),
+                (Value::Timestamp(a), Value::Timestamp(b)) => Some(a.cmp(b)),
+                (Value::BigDecimal(a), Value::BigDecimal(b)) => Some(a.cmp(b)),
+                (Value::BigInt(a), Value::BigInt(b)) => Some(a.cmp(b)),
+                (Value::Bytes(a), Value::Bytes(b)) => Some(a.cmp(b)),
+                (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+                _ => None,
+            }
+        }
+
+        fn matches_filter(filter: &EntityFilter, entity: &Entity) -> bool {
+            use EntityFilter::*;
+
+            let (attr, value, accept): (_, _, &[Cmp]) = match filter {
+                And(filters) => return filters.iter().all(|f| matches_filter(f, entity)),
+                Equal(attr, value) => {
+                    return entity.get(attr).unwrap_or(&Value::Null) == value;
+                }
+                GreaterThan(attr, value) => (attr, value, &[Cmp::Greater]),
+                LessThan(attr, value) => (attr, value, &[Cmp::Less]),
+                GreaterOrEqual(attr, value) => (attr, value, &[Cmp::Greater, Cmp::Equal]),
+                LessOrEqual(attr, value) => (attr, value, &[Cmp::Less, Cmp::Equal]),
+                _ => return false,
+            };
+            entity
+                .get(attr)
+                .and_then(|actual| cmp(actual, value))
+                .map(|ord| accept.contains(&ord))
+                .unwrap_or(false)
+        }
+
+        key.entity_type == self.entity_type
+            && key.causality_region == self.causality_region
+            && matches_filter(&self.filter, entity)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Child {
     pub attr: Attribute,
@@ -451,6 +516,12 @@ pub struct EntityQuery {
     /// A range to limit the size of the result.
     pub range: EntityRange,
 
+    /// For queries of aggregations, the dimensions to group the results
+    /// by. Aggregates are combined across all rows of a bucket that have
+    /// the same values for these dimensions. When this is `None`, the
+    /// aggregations are returned as they are stored
+    pub group_by: Option<Vec<Attribute>>,
+
     /// Optional logger for anything related to this query
     pub logger: Option<Logger>,
 
@@ -474,6 +545,7 @@ impl EntityQuery {
             filter: None,
             order: EntityOrder::Default,
             range: EntityRange::default(),
+            group_by: None,
             logger: None,
             query_id: None,
             trace: false,
@@ -496,6 +568,11 @@ impl EntityQuery {
         self
     }
 
+    pub fn group_by(mut self, group_by: Vec<Attribute>) -> Self {
+        self.group_by = Some(group_by);
+        self
+    }
+
     pub fn first(mut self, first: u32) -> Self {
         self.range.first = Some(first);
         self
@@ -575,6 +652,15 @@ impl AssignmentChange {
     }
 }
 
+/// The entity types of a deployment that changed. These are only sent if
+/// `GRAPH_STORE_SEND_ENTITY_CHANGES` is set, and are used to rerun GraphQL
+/// subscriptions.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct EntityChange {
+    pub subgraph_id: DeploymentHash,
+    pub entity_type: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 /// The store emits `StoreEvents` to indicate that some entities have changed.
 /// For block-related data, at most one `StoreEvent` is emitted for each block
@@ -589,34 +675,65 @@ pub struct StoreEvent {
     // logs as they flow through the system
     pub tag: usize,
     pub changes: HashSet<AssignmentChange>,
+    #[serde(default)]
+    pub entity_changes: HashSet<EntityChange>,
 }
 
 impl StoreEvent {
     pub fn new(changes: Vec<AssignmentChange>) -> StoreEvent {
         let changes = changes.into_iter().collect();
-        StoreEvent::from_set(changes)
+        StoreEvent::from_set(changes, HashSet::new())
     }
 
-    fn from_set(changes: HashSet<AssignmentChange>) -> StoreEvent {
+    /// An event for changes to the entity types `entity_types` of the
+    /// deployment `subgraph_id`
+    pub fn entities<'a>(
+        subgraph_id: &DeploymentHash,
+        entity_types: impl IntoIterator<Item = &'a str>,
+    ) -> StoreEvent {
+        let entity_changes = entity_types
+            .into_iter()
+            .map(|entity_type| EntityChange {
+                subgraph_id: subgraph_id.clone(),
+                entity_type: entity_type.to_string(),
+            })
+            .collect();
+        StoreEvent::from_set(HashSet::new(), entity_changes)
+    }
+
+    fn from_set(
+        changes: HashSet<AssignmentChange>,
+        entity_changes: HashSet<EntityChange>,
+    ) -> StoreEvent {
         static NEXT_TAG: AtomicUsize = AtomicUsize::new(0);
 
         let tag = NEXT_TAG.fetch_add(1, Ordering::Relaxed);
-        StoreEvent { tag, changes }
+        StoreEvent {
+            tag,
+            changes,
+            entity_changes,
+        }
     }
 
     pub fn extend(mut self, other: StoreEvent) -> Self {
         self.changes.extend(other.changes);
+        self.entity_changes.extend(other.entity_changes);
         self
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.entity_changes.is_empty()
+    }
 }
 
 impl fmt::Display for StoreEvent {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "StoreEvent[{}](changes: {})",
+            "StoreEvent[{}](changes: {}, entity changes: {})",
             self.tag,
-            self.changes.len()
+            self.changes.len(),
+            self.entity_changes.len()
         )
     }
 }
@@ -624,7 +741,7 @@ impl fmt::Display for StoreEvent {
 impl PartialEq for StoreEvent {
     fn eq(&self, other: &StoreEvent) -> bool {
         // Ignore tag for equality
-        self.changes == other.changes
+        self.changes == other.changes && self.entity_changes == other.entity_changes
     }
 }
 
@@ -684,6 +801,9 @@ pub struct StoredDynamicDataSource {
     pub context: Option<serde_json::Value>,
     pub creation_block: Option<BlockNumber>,
     pub done_at: Option<i32>,
+    /// For offchain data sources that are processed in chunks, the block at
+    /// which each chunk that has been processed was processed
+    pub chunks_done_at: Vec<BlockNumber>,
     pub causality_region: CausalityRegion,
 }
 
@@ -881,6 +1001,13 @@ impl ReadStore for EmptyStore {
         Ok(BTreeMap::new())
     }
 
+    fn get_filtered(
+        &self,
+        _query: &FilteredEntityQuery,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        Ok(BTreeMap::new())
+    }
+
     fn input_schema(&self) -> InputSchema {
         self.schema.cheap_clone()
     }
@@ -13,7 +13,7 @@ use crate::components::server::index_node::VersionInfo;
 use crate::components::subgraph::SubgraphVersionSwitchingMode;
 use crate::components::transaction_receipt;
 use crate::components::versions::ApiVersion;
-use crate::data::query::Trace;
+use crate::data::query::{RegisteredQueries, Trace};
 use crate::data::store::ethereum::call;
 use crate::data::store::QueryObject;
 use crate::data::subgraph::{status, DeploymentFeatures};
@@ -236,6 +236,13 @@ pub trait ReadStore: Send + Sync + 'static {
         query_derived: &DerivedEntityQuery,
     ) -> Result<BTreeMap<EntityKey, Entity>, StoreError>;
 
+    /// Find the entities that match `query` as of the latest block,
+    /// returning at most `query.first` of them, ordered by id
+    fn get_filtered(
+        &self,
+        query: &FilteredEntityQuery,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError>;
+
     fn input_schema(&self) -> InputSchema;
 }
 
@@ -259,6 +266,13 @@ impl<T: ?Sized + ReadStore> ReadStore for Arc<T> {
         (**self).get_derived(entity_derived)
     }
 
+    fn get_filtered(
+        &self,
+        query: &FilteredEntityQuery,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        (**self).get_filtered(query)
+    }
+
     fn input_schema(&self) -> InputSchema {
         (**self).input_schema()
     }
@@ -635,6 +649,10 @@ pub trait QueryStore: Send + Sync {
         query: EntityQuery,
     ) -> Result<(Vec<QueryObject>, Trace), QueryExecutionError>;
 
+    /// Count the entities that match the filter of `query`, ignoring its
+    /// order and range
+    fn count_query_values(&self, query: EntityQuery) -> Result<i64, QueryExecutionError>;
+
     async fn is_deployment_synced(&self) -> Result<bool, Error>;
 
     async fn block_ptr(&self) -> Result<Option<BlockPtr>, StoreError>;
@@ -676,6 +694,10 @@ pub trait QueryStore: Send + Sync {
 
     /// Return the deployment id that is queried by this `QueryStore`
     fn deployment_id(&self) -> DeploymentId;
+
+    /// Return the query documents that have been registered for the
+    /// deployment. The result may be cached for a short amount of time
+    async fn registered_queries(&self) -> Result<Arc<RegisteredQueries>, StoreError>;
 }
 
 /// A view of the store that can provide information about the indexing status
@@ -647,6 +647,8 @@ pub struct Batch {
     /// New data sources
     pub data_sources: DataSources,
     pub deterministic_errors: Vec<SubgraphError>,
+    /// Data sources that are done: processed offchain data sources and
+    /// onchain data sources that were removed by a mapping
     pub offchain_to_remove: DataSources,
     pub error: Option<StoreError>,
     pub is_non_fatal_errors_active: bool,
@@ -1,4 +1,5 @@
 use std::cmp::PartialEq;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -8,6 +9,7 @@ use futures01::sync::mpsc;
 
 use crate::blockchain::BlockTime;
 use crate::components::metrics::gas::GasMetrics;
+use crate::components::metrics::handler_profile::{HandlerProfiler, HandlerRun};
 use crate::components::store::SubgraphFork;
 use crate::data_source::{
     DataSource, DataSourceTemplate, MappingTrigger, TriggerData, TriggerWithHandler,
@@ -99,11 +101,12 @@ pub trait RuntimeHost<C: Blockchain>: Send + Sync + 'static {
     fn creation_block_number(&self) -> Option<BlockNumber>;
 
     /// Offchain data sources track done_at which is set once the
-    /// trigger has been processed.
+    /// trigger has been processed. Onchain data sources track done_at
+    /// which is set once they have been removed by a mapping.
     fn done_at(&self) -> Option<BlockNumber>;
 
     /// Convenience function to avoid leaking internal representation of
-    /// mutable number. Calling this on OnChain Datasources is a noop.
+    /// mutable number. Calling this on subgraph data sources is a noop.
     fn set_done_at(&self, block: Option<BlockNumber>);
 
     /// Return a metrics object for this host.
@@ -112,10 +115,24 @@ pub trait RuntimeHost<C: Blockchain>: Send + Sync + 'static {
 
 pub struct HostMetrics {
     handler_execution_time: Box<HistogramVec>,
+    handler_gas: Box<HistogramVec>,
+    handler_store_get_count: Box<HistogramVec>,
+    handler_store_get_cache_misses: Box<HistogramVec>,
+    handler_eth_call_count: Box<HistogramVec>,
     host_fn_execution_time: Box<HistogramVec>,
     eth_call_execution_time: Box<HistogramVec>,
     pub gas_metrics: GasMetrics,
     pub stopwatch: StopwatchMetrics,
+
+    // Counters for the handler that is currently running. The handlers of a
+    // deployment run one after the other, so these only ever count calls
+    // made by one handler; they are reset by `observe_handler_run`
+    store_get_count: AtomicU64,
+    store_get_cache_misses: AtomicU64,
+    eth_call_count: AtomicU64,
+
+    subgraph: String,
+    profiler: Arc<HandlerProfiler>,
 }
 
 impl HostMetrics {
@@ -124,6 +141,7 @@ impl HostMetrics {
         subgraph: &str,
         stopwatch: StopwatchMetrics,
         gas_metrics: GasMetrics,
+        profiler: Arc<HandlerProfiler>,
     ) -> Self {
         let handler_execution_time = registry
             .new_deployment_histogram_vec(
@@ -134,6 +152,42 @@ impl HostMetrics {
                 vec![0.1, 0.5, 1.0, 10.0, 100.0],
             )
             .expect("failed to create `deployment_handler_execution_time` histogram");
+        let handler_gas = registry
+            .new_deployment_histogram_vec(
+                "deployment_handler_gas",
+                "Measures the gas used by handlers",
+                subgraph,
+                vec![String::from("handler")],
+                vec![1e6, 1e8, 1e10, 1e12, 1e14],
+            )
+            .expect("failed to create `deployment_handler_gas` histogram");
+        let handler_store_get_count = registry
+            .new_deployment_histogram_vec(
+                "deployment_handler_store_get_count",
+                "Measures the number of `store.get` calls per handler",
+                subgraph,
+                vec![String::from("handler")],
+                vec![0.0, 1.0, 5.0, 10.0, 50.0, 100.0, 500.0],
+            )
+            .expect("failed to create `deployment_handler_store_get_count` histogram");
+        let handler_store_get_cache_misses = registry
+            .new_deployment_histogram_vec(
+                "deployment_handler_store_get_cache_misses",
+                "Measures the number of `store.get` calls per handler that had to read from the database",
+                subgraph,
+                vec![String::from("handler")],
+                vec![0.0, 1.0, 5.0, 10.0, 50.0, 100.0, 500.0],
+            )
+            .expect("failed to create `deployment_handler_store_get_cache_misses` histogram");
+        let handler_eth_call_count = registry
+            .new_deployment_histogram_vec(
+                "deployment_handler_eth_call_count",
+                "Measures the number of eth_calls per handler",
+                subgraph,
+                vec![String::from("handler")],
+                vec![0.0, 1.0, 5.0, 10.0, 50.0, 100.0, 500.0],
+            )
+            .expect("failed to create `deployment_handler_eth_call_count` histogram");
         let eth_call_execution_time = registry
             .new_deployment_histogram_vec(
                 "deployment_eth_call_execution_time",
@@ -155,10 +209,19 @@ impl HostMetrics {
             .expect("failed to create `deployment_host_fn_execution_time` histogram");
         Self {
             handler_execution_time,
+            handler_gas,
+            handler_store_get_count,
+            handler_store_get_cache_misses,
+            handler_eth_call_count,
             host_fn_execution_time,
             stopwatch,
             gas_metrics,
             eth_call_execution_time,
+            store_get_count: AtomicU64::new(0),
+            store_get_cache_misses: AtomicU64::new(0),
+            eth_call_count: AtomicU64::new(0),
+            subgraph: subgraph.to_string(),
+            profiler,
         }
     }
 
@@ -168,6 +231,46 @@ impl HostMetrics {
             .observe(duration);
     }
 
+    /// Record everything we know about the run of `handler` that just
+    /// finished, including the calls tracked with `track_store_get` and
+    /// `track_eth_call` while it was running
+    pub fn observe_handler_run(&self, handler: &str, wall_time: Duration, gas: u64) {
+        let run = HandlerRun {
+            wall_time,
+            gas,
+            store_get_count: self.store_get_count.swap(0, Ordering::SeqCst),
+            store_get_cache_misses: self.store_get_cache_misses.swap(0, Ordering::SeqCst),
+            eth_call_count: self.eth_call_count.swap(0, Ordering::SeqCst),
+        };
+
+        self.observe_handler_execution_time(wall_time.as_secs_f64(), handler);
+        self.handler_gas
+            .with_label_values(&[handler][..])
+            .observe(gas as f64);
+        self.handler_store_get_count
+            .with_label_values(&[handler][..])
+            .observe(run.store_get_count as f64);
+        self.handler_store_get_cache_misses
+            .with_label_values(&[handler][..])
+            .observe(run.store_get_cache_misses as f64);
+        self.handler_eth_call_count
+            .with_label_values(&[handler][..])
+            .observe(run.eth_call_count as f64);
+
+        self.profiler.record(&self.subgraph, handler, &run);
+    }
+
+    pub fn track_store_get(&self, cache_miss: bool) {
+        self.store_get_count.fetch_add(1, Ordering::SeqCst);
+        if cache_miss {
+            self.store_get_cache_misses.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    pub fn track_eth_call(&self) {
+        self.eth_call_count.fetch_add(1, Ordering::SeqCst);
+    }
+
     pub fn observe_host_fn_execution_time(&self, duration: f64, fn_name: &str) {
         self.host_fn_execution_time
             .with_label_values(&[fn_name][..])
@@ -228,9 +331,11 @@ pub trait RuntimeHostBuilder<C: Blockchain>: Clone + Send + Sync + 'static {
     ) -> Result<Self::Host, Error>;
 
     /// Spawn a mapping and return a channel for mapping requests. The sender should be able to be
-    /// cached and shared among mappings that use the same wasm file.
+    /// cached and shared among mappings that use the same wasm file. The `source_map` is used to
+    /// point errors in the mapping at its source.
     fn spawn_mapping(
         raw_module: &[u8],
+        source_map: Option<&[u8]>,
         logger: Logger,
         subgraph_id: DeploymentHash,
         metrics: Arc<HostMetrics>,
@@ -78,6 +78,16 @@ pub struct BlockState {
     // data source that have been processed.
     pub processed_data_sources: Vec<StoredDynamicDataSource>,
 
+    // Onchain data sources that have been removed by a mapping.
+    pub removed_data_sources: Vec<StoredDynamicDataSource>,
+
+    // Marks whether the current handler asked to remove its data source.
+    handler_removed_data_source: bool,
+
+    // Marks whether a completed handler asked to remove its data source. This
+    // is taken by the trigger processor after each trigger.
+    removed_data_source: bool,
+
     // Marks whether a handler is currently executing.
     in_handler: bool,
 
@@ -95,6 +105,9 @@ impl BlockState {
             persisted_data_sources: Vec::new(),
             handler_created_data_sources: Vec::new(),
             processed_data_sources: Vec::new(),
+            removed_data_sources: Vec::new(),
+            handler_removed_data_source: false,
+            removed_data_source: false,
             in_handler: false,
             metrics: BlockStateMetrics::new(),
             write_capacity_remaining: ENV_VARS.block_write_capacity,
@@ -113,18 +126,28 @@ impl BlockState {
             persisted_data_sources,
             handler_created_data_sources,
             processed_data_sources,
+            removed_data_sources,
+            handler_removed_data_source,
+            removed_data_source,
             in_handler,
             metrics,
             write_capacity_remaining,
         } = self;
 
         match in_handler {
-            true => handler_created_data_sources.extend(other.created_data_sources),
-            false => created_data_sources.extend(other.created_data_sources),
+            true => {
+                handler_created_data_sources.extend(other.created_data_sources);
+                *handler_removed_data_source |= other.removed_data_source;
+            }
+            false => {
+                created_data_sources.extend(other.created_data_sources);
+                *removed_data_source |= other.removed_data_source;
+            }
         }
         deterministic_errors.extend(other.deterministic_errors);
         entity_cache.extend(other.entity_cache);
         processed_data_sources.extend(other.processed_data_sources);
+        removed_data_sources.extend(other.removed_data_sources);
         persisted_data_sources.extend(other.persisted_data_sources);
         metrics.extend(other.metrics);
         *write_capacity_remaining =
@@ -166,6 +189,7 @@ impl BlockState {
         self.in_handler = false;
         self.created_data_sources
             .append(&mut self.handler_created_data_sources);
+        self.removed_data_source |= std::mem::take(&mut self.handler_removed_data_source);
         self.entity_cache.exit_handler()
     }
 
@@ -173,6 +197,7 @@ impl BlockState {
         assert!(self.in_handler);
         self.in_handler = false;
         self.handler_created_data_sources.clear();
+        self.handler_removed_data_source = false;
         self.entity_cache.exit_handler_and_discard_changes();
         self.deterministic_errors.push(e);
     }
@@ -182,6 +207,24 @@ impl BlockState {
         self.handler_created_data_sources.push(ds);
     }
 
+    /// Mark the data source of the current handler as removed. This only
+    /// takes effect if the handler completes without errors.
+    pub fn remove_data_source(&mut self) {
+        assert!(self.in_handler);
+        self.handler_removed_data_source = true;
+    }
+
+    /// Returns `true` if a handler asked to remove its data source since
+    /// the last call, and resets the flag.
+    pub fn take_removed_data_source(&mut self) -> bool {
+        assert!(!self.in_handler);
+        std::mem::take(&mut self.removed_data_source)
+    }
+
+    pub fn has_removed_data_sources(&self) -> bool {
+        !self.removed_data_sources.is_empty()
+    }
+
     pub fn persist_data_source(&mut self, ds: StoredDynamicDataSource) {
         self.persisted_data_sources.push(ds)
     }
@@ -0,0 +1,189 @@
+//! Per-client budgets for the work that queries cause
+//!
+//! Clients are identified by the value of the request header named by
+//! `GRAPH_GRAPHQL_CLIENT_ID_HEADER`. For each client, we add up the
+//! complexity of its queries and the time it took to execute them over a
+//! fixed window of time. Once a client has used up its budget for either
+//! of them, its queries are declined until the next window starts. Unlike
+//! the `LoadManager`, which reacts to the overall load of the system, this
+//! makes sure that one heavy client can not crowd out all the others.
+//!
+//! Requests without the header are all charged to the anonymous client
+//! `""`; anonymous clients therefore share one budget.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::data::query::QueryExecutionError;
+use crate::prelude::ENV_VARS;
+
+#[derive(Debug, Default)]
+struct Usage {
+    complexity: u64,
+    query_time: Duration,
+}
+
+#[derive(Debug)]
+struct Window {
+    start: Instant,
+    usage: HashMap<String, Usage>,
+}
+
+impl Window {
+    fn new(start: Instant) -> Self {
+        Window {
+            start,
+            usage: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ClientQuotas {
+    window_size: Duration,
+    max_complexity: Option<u64>,
+    max_query_time: Option<Duration>,
+    window: Mutex<Window>,
+}
+
+/// Create `ClientQuotas` with the limits configured in the environment
+impl Default for ClientQuotas {
+    fn default() -> Self {
+        Self::new(
+            ENV_VARS.graphql.client_quota_window,
+            ENV_VARS.graphql.client_max_complexity,
+            ENV_VARS.graphql.client_max_query_time,
+        )
+    }
+}
+
+impl ClientQuotas {
+    pub fn new(
+        window_size: Duration,
+        max_complexity: Option<u64>,
+        max_query_time: Option<Duration>,
+    ) -> Self {
+        ClientQuotas {
+            window_size,
+            max_complexity,
+            max_query_time,
+            window: Mutex::new(Window::new(Instant::now())),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.max_complexity.is_some() || self.max_query_time.is_some()
+    }
+
+    /// Run `f` on the usage of `client` in the current window, starting a
+    /// new window if the current one has ended
+    fn with_usage<T>(&self, client: &str, now: Instant, f: impl FnOnce(&mut Usage) -> T) -> T {
+        let mut window = self.window.lock().unwrap();
+        if now.saturating_duration_since(window.start) >= self.window_size {
+            *window = Window::new(now);
+        }
+        f(window.usage.entry(client.to_string()).or_default())
+    }
+
+    /// Decide whether `client` may run a query with the given
+    /// `complexity`. If it may, the complexity is charged against its
+    /// budget right away so that concurrent queries can not exceed it
+    pub fn admit(&self, client: &str, complexity: u64) -> Result<(), QueryExecutionError> {
+        self.admit_at(client, complexity, Instant::now())
+    }
+
+    fn admit_at(
+        &self,
+        client: &str,
+        complexity: u64,
+        now: Instant,
+    ) -> Result<(), QueryExecutionError> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        self.with_usage(client, now, |usage| {
+            let over_time = self
+                .max_query_time
+                .map(|max| usage.query_time >= max)
+                .unwrap_or(false);
+            let over_complexity = self
+                .max_complexity
+                .map(|max| usage.complexity.saturating_add(complexity) > max)
+                .unwrap_or(false);
+            if over_time || over_complexity {
+                return Err(QueryExecutionError::ClientQuotaExceeded);
+            }
+            usage.complexity = usage.complexity.saturating_add(complexity);
+            Ok(())
+        })
+    }
+
+    /// Charge `query_time`, the time it took to run a query that was
+    /// admitted with `admit`, against the budget of `client`
+    pub fn record_query_time(&self, client: &str, query_time: Duration) {
+        self.record_query_time_at(client, query_time, Instant::now())
+    }
+
+    fn record_query_time_at(&self, client: &str, query_time: Duration, now: Instant) {
+        if self.max_query_time.is_none() {
+            return;
+        }
+        self.with_usage(client, now, |usage| usage.query_time += query_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::ClientQuotas;
+
+    #[test]
+    fn complexity_budget() {
+        let quotas = ClientQuotas::new(Duration::from_secs(60), Some(100), None);
+        let now = Instant::now();
+
+        assert!(quotas.admit_at("alice", 60, now).is_ok());
+        assert!(quotas.admit_at("alice", 60, now).is_err());
+        // Other clients have their own budget
+        assert!(quotas.admit_at("bob", 60, now).is_ok());
+        // Smaller queries still fit into the remaining budget
+        assert!(quotas.admit_at("alice", 40, now).is_ok());
+        assert!(quotas.admit_at("alice", 1, now).is_err());
+
+        // The budget is restored when the next window starts
+        let later = now + Duration::from_secs(61);
+        assert!(quotas.admit_at("alice", 60, later).is_ok());
+    }
+
+    #[test]
+    fn query_time_budget() {
+        let quotas = ClientQuotas::new(
+            Duration::from_secs(60),
+            None,
+            Some(Duration::from_millis(500)),
+        );
+        let now = Instant::now();
+
+        assert!(quotas.admit_at("alice", 1000, now).is_ok());
+        quotas.record_query_time_at("alice", Duration::from_millis(300), now);
+        assert!(quotas.admit_at("alice", 1000, now).is_ok());
+        quotas.record_query_time_at("alice", Duration::from_millis(300), now);
+        assert!(quotas.admit_at("alice", 1, now).is_err());
+        assert!(quotas.admit_at("bob", 1, now).is_ok());
+
+        let later = now + Duration::from_secs(61);
+        assert!(quotas.admit_at("alice", 1, later).is_ok());
+    }
+
+    #[test]
+    fn no_limits() {
+        let quotas = ClientQuotas::new(Duration::from_secs(60), None, None);
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            assert!(quotas.admit_at("alice", u64::MAX, now).is_ok());
+        }
+    }
+}
@@ -39,6 +39,7 @@ pub enum QueryExecutionError {
     OrderByNotSupportedError(String, String),
     OrderByNotSupportedForType(String),
     FilterNotSupportedError(String, String),
+    GroupByNotSupportedError(String, String),
     UnknownField(Pos, String, String),
     EmptyQuery,
     SubgraphDeploymentIdError(String),
@@ -74,6 +75,10 @@ pub enum QueryExecutionError {
     DeploymentNotFound(String),
     IdMissing,
     IdNotString,
+    PersistedQueryNotFound,
+    PersistedQueryHashMismatch(String),
+    UnregisteredQuery(String),
+    ClientQuotaExceeded,
     InternalError(String),
 }
 
@@ -94,6 +99,7 @@ impl QueryExecutionError {
             | OrderByNotSupportedError(_, _)
             | OrderByNotSupportedForType(_)
             | FilterNotSupportedError(_, _)
+            | GroupByNotSupportedError(_, _)
             | ChildFilterNestingNotSupportedError(_, _)
             | UnknownField(_, _, _)
             | EmptyQuery
@@ -132,6 +138,10 @@ impl QueryExecutionError {
             | DeploymentNotFound(_)
             | IdMissing
             | IdNotString
+            | PersistedQueryNotFound
+            | PersistedQueryHashMismatch(_)
+            | UnregisteredQuery(_)
+            | ClientQuotaExceeded
             | InternalError(_) => false,
         }
     }
@@ -196,6 +206,9 @@ impl fmt::Display for QueryExecutionError {
             FilterNotSupportedError(value, filter) => {
                 write!(f, "Filter not supported by value `{}`: `{}`", value, filter)
             }
+            GroupByNotSupportedError(entity, field) => {
+                write!(f, "Field `{}` of type `{}` can not be used when grouping; only the grouped dimensions and aggregates that can be combined across groups can be selected or used for ordering", field, entity)
+            }
             ChildFilterNestingNotSupportedError(value, filter) => {
                 write!(f, "Child filter nesting not supported by value `{}`: `{}`", value, filter)
             }
@@ -274,6 +287,12 @@ impl fmt::Display for QueryExecutionError {
             DeploymentNotFound(id_or_name) => write!(f, "deployment `{}` does not exist", id_or_name),
             IdMissing => write!(f, "entity is missing an `id` attribute"),
             IdNotString => write!(f, "entity `id` attribute is not a string"),
+            // Clients for automatic persisted queries look for this exact message
+            PersistedQueryNotFound => write!(f, "PersistedQueryNotFound"),
+            PersistedQueryHashMismatch(hash) => write!(f, "the query does not have the sha256 hash `{}`", hash),
+            UnregisteredQuery(hash) if hash.is_empty() => write!(f, "only registered queries can be run against this deployment"),
+            UnregisteredQuery(hash) => write!(f, "only registered queries can be run against this deployment, but the query with hash `{}` has not been registered", hash),
+            ClientQuotaExceeded => write!(f, "the query budget for this client has been used up for the current period; please try again later"),
             InternalError(msg) => write!(f, "internal error: {}", msg),
         }
     }
@@ -1,5 +1,6 @@
 use serde::de::Deserializer;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::ops::{Deref, DerefMut};
@@ -7,7 +8,7 @@ use std::sync::Arc;
 
 use crate::{
     data::graphql::shape_hash::shape_hash,
-    prelude::{q, r, ApiVersion, DeploymentHash, SubgraphName, ENV_VARS},
+    prelude::{q, r, ApiVersion, DeploymentHash, QueryExecutionError, SubgraphName, ENV_VARS},
 };
 
 fn deserialize_number<'de, D>(deserializer: D) -> Result<q::Number, D::Error>
@@ -124,6 +125,37 @@ impl QueryTarget {
     }
 }
 
+/// The hash under which the query document `text` is persisted. This is
+/// the hex-encoded sha256 hash of `text` that clients send for automatic
+/// persisted queries
+pub fn persisted_query_hash(text: &str) -> String {
+    hex::encode(Sha256::digest(text.as_bytes()))
+}
+
+/// The query documents that have been registered for a deployment with
+/// `graphman persisted-query register`
+#[derive(Clone, Debug, Default)]
+pub struct RegisteredQueries {
+    /// Whether only registered documents can be run against the deployment
+    pub only_registered: bool,
+    /// Maps the `persisted_query_hash` of each document to the document
+    pub queries: HashMap<String, String>,
+}
+
+impl RegisteredQueries {
+    /// Check that `query` can be run against the deployment
+    pub fn check(&self, query: &Query) -> Result<(), QueryExecutionError> {
+        if !self.only_registered {
+            return Ok(());
+        }
+        match &query.hash {
+            Some(hash) if self.queries.contains_key(hash) => Ok(()),
+            Some(hash) => Err(QueryExecutionError::UnregisteredQuery(hash.clone())),
+            None => Err(QueryExecutionError::UnregisteredQuery(String::new())),
+        }
+    }
+}
+
 /// A GraphQL query as submitted by a client, either directly or through a subscription.
 #[derive(Clone, Debug)]
 pub struct Query {
@@ -133,6 +165,14 @@ pub struct Query {
     pub query_text: Arc<String>,
     pub variables_text: Arc<String>,
     pub trace: bool,
+    /// The identity of the client that sent the query, taken from the
+    /// header named by `GRAPH_GRAPHQL_CLIENT_ID_HEADER`
+    pub client: Option<String>,
+    /// The `persisted_query_hash` of the text of the query. It is set for
+    /// all queries that clients send, and used to check whether the query
+    /// has been registered for a deployment that only allows registered
+    /// queries
+    pub hash: Option<String>,
     _force_use_of_new: (),
 }
 
@@ -161,6 +201,8 @@ impl Query {
             query_text: Arc::new(query_text),
             variables_text: Arc::new(variables_text),
             trace,
+            client: None,
+            hash: None,
             _force_use_of_new: (),
         }
     }
@@ -47,6 +47,8 @@ pub mod call {
 
     use super::CheapClone;
 
+    const STORAGE_AT_PREFIX: &[u8; 16] = b"eth_getStorageAt";
+
     /// The return value of an ethereum call. `Null` indicates that we made
     /// the call but didn't get a value back (including when we get the
     /// error 'call reverted')
@@ -120,6 +122,19 @@ pub mod call {
             }
         }
 
+        /// A request for the value of storage slot `slot` of the contract
+        /// at `address`. These are cached in the same place as `eth_call`s;
+        /// the encoded call is `STORAGE_AT_PREFIX` followed by the slot.
+        /// Since ABI encoded calls always have a length of 4 plus a
+        /// multiple of 32 bytes, the 48 bytes of such a request can never
+        /// clash with a real call
+        pub fn storage_at(address: ethabi::Address, slot: &[u8; 32], index: u32) -> Self {
+            let mut encoded_call = Vec::with_capacity(STORAGE_AT_PREFIX.len() + slot.len());
+            encoded_call.extend_from_slice(STORAGE_AT_PREFIX);
+            encoded_call.extend_from_slice(slot);
+            Request::new(address, encoded_call, index)
+        }
+
         /// Create a response struct for this request
         pub fn response(self, retval: Retval, source: Source) -> Response {
             Response {
@@ -104,6 +104,177 @@ impl BigDecimal {
 
         BigDecimal(OldBigDecimal::new(int_val, scale))
     }
+
+    /// Raise `self` to the integer power `exp`. Every intermediate result is
+    /// rounded to `MAX_SIGNFICANT_DIGITS`, like the result of any other
+    /// operation on `BigDecimal`, so the last digits of the result may differ
+    /// from the exact value.
+    pub fn pow(&self, exp: i32) -> Result<BigDecimal, anyhow::Error> {
+        let zero = BigDecimal::zero();
+        if self == &zero && exp < 0 {
+            return Err(anyhow!(
+                "Cannot raise zero-valued `BigDecimal` to negative power {}",
+                exp
+            ));
+        }
+
+        // Exponentiation by squaring
+        let mut result = BigDecimal::from(1);
+        let mut base = self.clone();
+        let mut n = exp.unsigned_abs();
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result * base.clone();
+            }
+            n >>= 1;
+            if n > 0 {
+                base = base.clone() * base;
+            }
+        }
+
+        if exp < 0 {
+            result = BigDecimal::from(1) / result;
+        }
+
+        result.checked_exp()
+    }
+
+    /// The square root of `self`, rounded to `MAX_SIGNFICANT_DIGITS`.
+    pub fn sqrt(&self) -> Result<BigDecimal, anyhow::Error> {
+        use num_integer::Roots;
+        use num_traits::pow::Pow;
+
+        let zero = BigDecimal::zero();
+        if self < &zero {
+            return Err(anyhow!(
+                "Cannot take the square root of negative `BigDecimal` {}",
+                self
+            ));
+        }
+        if self == &zero {
+            return Ok(zero);
+        }
+
+        // Scale the digits up so that the integer square root has more
+        // significant digits than we keep, and so that the scale is even.
+        let (int, scale) = self.as_bigint_and_exponent();
+        let min_digits = 2 * (Self::MAX_SIGNFICANT_DIGITS as u64 + 1);
+        let mut shift = min_digits.saturating_sub(self.digits()) as i64;
+        if (scale + shift) % 2 != 0 {
+            shift += 1;
+        }
+        let int = int * num_bigint::BigInt::from(10).pow(shift as u32);
+
+        Ok(Self::from(OldBigDecimal::new(
+            Roots::sqrt(&int),
+            (scale + shift) / 2,
+        )))
+    }
+
+    /// Round `self` to `decimals` digits after the decimal point using
+    /// `mode`. A negative `decimals` rounds to a power of ten, e.g., `-2`
+    /// rounds to hundreds.
+    pub fn round(&self, decimals: i32, mode: RoundingMode) -> Result<BigDecimal, anyhow::Error> {
+        use num_traits::{pow::Pow, Signed, Zero};
+
+        let (int, scale) = self.as_bigint_and_exponent();
+        let decimals = decimals as i64;
+        if scale <= decimals {
+            return Ok(self.clone());
+        }
+
+        // Dropping more digits than `int` has gives the same quotient and
+        // remainder, so we don't need to compute a huge power of ten.
+        let shift = (scale - decimals).min(self.digits() as i64 + 1);
+        let divisor = num_bigint::BigInt::from(10).pow(shift as u32);
+        let mut quot = &int / &divisor;
+        let rem = &int % &divisor;
+
+        if !rem.is_zero() {
+            let away_from_zero = match mode {
+                RoundingMode::Down => false,
+                RoundingMode::Up => true,
+                RoundingMode::Floor => int.is_negative(),
+                RoundingMode::Ceiling => int.is_positive(),
+                RoundingMode::HalfUp | RoundingMode::HalfDown | RoundingMode::HalfEven => {
+                    let twice_rem = rem.abs() * 2;
+                    match twice_rem.cmp(&divisor) {
+                        std::cmp::Ordering::Less => false,
+                        std::cmp::Ordering::Greater => true,
+                        std::cmp::Ordering::Equal => match mode {
+                            RoundingMode::HalfUp => true,
+                            RoundingMode::HalfDown => false,
+                            _ => !(&quot % 2).is_zero(),
+                        },
+                    }
+                }
+            };
+            if away_from_zero {
+                quot += int.signum();
+            }
+        }
+
+        Self::from(OldBigDecimal::new(quot, decimals)).checked_exp()
+    }
+
+    /// Truncate `self` to `decimals` digits after the decimal point, i.e.,
+    /// round towards zero.
+    pub fn truncate(&self, decimals: i32) -> Result<BigDecimal, anyhow::Error> {
+        self.round(decimals, RoundingMode::Down)
+    }
+
+    /// Return `self` if its exponent is within `MIN_EXP` and `MAX_EXP`, and
+    /// an error otherwise.
+    fn checked_exp(self) -> Result<BigDecimal, anyhow::Error> {
+        let exp = -self.as_bigint_and_exponent().1;
+        let min_exp: i64 = Self::MIN_EXP.into();
+        let max_exp: i64 = Self::MAX_EXP.into();
+        if exp < min_exp || max_exp < exp {
+            return Err(anyhow!(
+                "big decimal exponent `{}` is outside the `{}` to `{}` range",
+                exp,
+                Self::MIN_EXP,
+                Self::MAX_EXP
+            ));
+        }
+        Ok(self)
+    }
+}
+
+/// How `BigDecimal::round` treats the digits it drops.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round towards zero.
+    Down,
+    /// Round away from zero.
+    Up,
+    /// Round towards negative infinity.
+    Floor,
+    /// Round towards positive infinity.
+    Ceiling,
+    /// Round to the nearest value, and away from zero on a tie.
+    HalfUp,
+    /// Round to the nearest value, and towards zero on a tie.
+    HalfDown,
+    /// Round to the nearest value, and to the even neighbour on a tie.
+    HalfEven,
+}
+
+impl TryFrom<i32> for RoundingMode {
+    type Error = anyhow::Error;
+
+    fn try_from(mode: i32) -> Result<Self, Self::Error> {
+        match mode {
+            0 => Ok(RoundingMode::Down),
+            1 => Ok(RoundingMode::Up),
+            2 => Ok(RoundingMode::Floor),
+            3 => Ok(RoundingMode::Ceiling),
+            4 => Ok(RoundingMode::HalfUp),
+            5 => Ok(RoundingMode::HalfDown),
+            6 => Ok(RoundingMode::HalfEven),
+            _ => Err(anyhow!("invalid rounding mode `{}`", mode)),
+        }
+    }
 }
 
 impl Display for BigDecimal {
@@ -594,10 +765,14 @@ mod test {
     use super::{
         super::test::{crypto_stable_hash, same_stable_hash},
         super::Bytes,
-        BigDecimal, BigInt, OldBigDecimal,
+        BigDecimal, BigInt, OldBigDecimal, RoundingMode,
     };
     use std::str::FromStr;
 
+    fn dec(s: &str) -> BigDecimal {
+        BigDecimal::from_str(s).unwrap()
+    }
+
     #[test]
     fn big_int_stable_hash_same_as_int() {
         same_stable_hash(0, BigInt::from(0u64));
@@ -685,4 +860,65 @@ mod test {
         assert_eq!("BigDecimal(-0.17)", format!("{:?}", bd));
         assert_eq!("Bytes(0xdeadbeef)", format!("{:?}", bytes));
     }
+
+    #[test]
+    fn pow() {
+        assert_eq!(dec("1.5").pow(2).unwrap(), dec("2.25"));
+        assert_eq!(dec("-2").pow(3).unwrap(), dec("-8"));
+        assert_eq!(dec("2").pow(-2).unwrap(), dec("0.25"));
+        assert_eq!(dec("123.456").pow(0).unwrap(), dec("1"));
+        assert_eq!(dec("1.1").pow(10).unwrap(), dec("2.5937424601"));
+        assert!(dec("0").pow(-1).is_err());
+        assert!(dec("10").pow(7000).is_err());
+    }
+
+    #[test]
+    fn sqrt() {
+        assert_eq!(dec("0").sqrt().unwrap(), dec("0"));
+        assert_eq!(dec("4").sqrt().unwrap(), dec("2"));
+        assert_eq!(dec("0.0144").sqrt().unwrap(), dec("0.12"));
+        assert_eq!(dec("1e10").sqrt().unwrap(), dec("1e5"));
+        assert_eq!(
+            dec("2").sqrt().unwrap(),
+            dec("1.414213562373095048801688724209698")
+        );
+        assert!(dec("-1").sqrt().is_err());
+    }
+
+    #[test]
+    fn round() {
+        use RoundingMode::*;
+
+        let cases = vec![
+            ("1.25", 1, Down, "1.2"),
+            ("1.25", 1, Up, "1.3"),
+            ("-1.25", 1, Floor, "-1.3"),
+            ("-1.25", 1, Ceiling, "-1.2"),
+            ("1.25", 1, HalfUp, "1.3"),
+            ("1.25", 1, HalfDown, "1.2"),
+            ("1.25", 1, HalfEven, "1.2"),
+            ("1.35", 1, HalfEven, "1.4"),
+            ("-1.35", 1, HalfUp, "-1.4"),
+            ("1.26", 1, HalfDown, "1.3"),
+            ("9.99", 1, HalfUp, "10"),
+            ("1.25", 5, Up, "1.25"),
+            ("1234.5", -2, HalfUp, "1200"),
+            ("0.001", 0, Up, "1"),
+            ("0.001", 0, HalfUp, "0"),
+        ];
+        for (x, decimals, mode, expected) in cases {
+            assert_eq!(
+                dec(x).round(decimals, mode).unwrap(),
+                dec(expected),
+                "{} rounded to {} decimals with {:?}",
+                x,
+                decimals,
+                mode
+            );
+        }
+
+        assert_eq!(dec("-1.99").truncate(0).unwrap(), dec("-1"));
+        assert!(dec("1").round(-7000, Up).is_err());
+        assert!(RoundingMode::try_from(7).is_err());
+    }
 }
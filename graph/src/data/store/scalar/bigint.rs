@@ -138,6 +138,26 @@ impl<'a> TryFrom<&'a BigInt> for u64 {
     }
 }
 
+impl<'a> TryFrom<&'a BigInt> for i64 {
+    type Error = BigIntOutOfRangeError;
+    fn try_from(value: &'a BigInt) -> Result<i64, BigIntOutOfRangeError> {
+        let bytes = value.to_signed_bytes_le();
+
+        if bytes.len() > 8 {
+            return Err(BigIntOutOfRangeError::Overflow);
+        }
+
+        // Sign-extend the two's complement representation to 8 bytes
+        let fill = match bytes.last() {
+            Some(b) if b & 0x80 != 0 => 0xff,
+            _ => 0,
+        };
+        let mut buf = [fill; 8];
+        buf[..bytes.len()].copy_from_slice(&bytes);
+        Ok(i64::from_le_bytes(buf))
+    }
+}
+
 impl TryFrom<BigInt> for u64 {
     type Error = BigIntOutOfRangeError;
     fn try_from(value: BigInt) -> Result<u64, BigIntOutOfRangeError> {
@@ -377,6 +397,15 @@ mod test {
         }
     }
 
+    #[test]
+    fn bigint_to_i64() {
+        for n in [0, 1, -1, 127, 128, -128, -129, i64::MAX, i64::MIN] {
+            assert_eq!(n, i64::try_from(&BigInt::from(n)).unwrap());
+        }
+        let too_large = BigInt::from(i64::MAX) + BigInt::from(1);
+        assert!(i64::try_from(&too_large).is_err());
+    }
+
     #[test]
     fn big_int_stable_hash_same_as_int() {
         same_stable_hash(0, BigInt::from(0u64));
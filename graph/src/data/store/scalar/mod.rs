@@ -3,7 +3,7 @@ mod bigint;
 mod bytes;
 mod timestamp;
 
-pub use bigdecimal::BigDecimal;
+pub use bigdecimal::{BigDecimal, RoundingMode};
 pub use bigint::{BigInt, BigIntSign};
 pub use bytes::Bytes;
 pub use old_bigdecimal::ToPrimitive;
@@ -21,6 +21,17 @@ pub const API_VERSION_0_0_8: Version = Version::new(0, 0, 8);
 /// Enables new host function `eth_get_balance`
 pub const API_VERSION_0_0_9: Version = Version::new(0, 0, 9);
 
+/// Enables new host functions:
+/// - the sha256, ripemd160, blake2 and signature verification functions in the `crypto` namespace
+/// - `store.query`
+/// - `dataSource.remove`
+/// - `bigDecimal.pow`, `bigDecimal.sqrt`, `bigDecimal.truncate`, `bigDecimal.round` and
+///   `bigDecimal.compare`
+/// - `ethereum.getStorageAt` and `ethereum.multicall`
+/// - `regex.isMatch`, `regex.captures`, `string.format`, `dateTime.fromTimestamp` and
+///   `dateTime.parseIso`
+pub const API_VERSION_0_0_10: Version = Version::new(0, 0, 10);
+
 /// Before this check was introduced, there were already subgraphs in the wild with spec version
 /// 0.0.3, due to confusion with the api version. To avoid breaking those, we accept 0.0.3 though it
 /// doesn't exist.
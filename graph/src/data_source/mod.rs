@@ -217,6 +217,13 @@ impl<C: Blockchain> DataSource<C> {
         }
     }
 
+    pub fn source_map(&self) -> Option<Arc<Vec<u8>>> {
+        match self {
+            Self::Onchain(ds) => ds.source_map(),
+            Self::Offchain(_) | Self::Subgraph(_) => None,
+        }
+    }
+
     pub fn entities(&self) -> EntityTypeAccess {
         match self {
             // Note: Onchain data sources have an `entities` field in the manifest, but it has never
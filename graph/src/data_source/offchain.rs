@@ -12,7 +12,7 @@ use crate::{
     prelude::{DataSourceContext, Link},
     schema::{EntityType, InputSchema},
 };
-use anyhow::{anyhow, Context, Error};
+use anyhow::{anyhow, ensure, Context, Error};
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use serde::Deserialize;
@@ -21,7 +21,7 @@ use std::{
     collections::HashMap,
     fmt,
     str::FromStr,
-    sync::{atomic::AtomicI32, Arc},
+    sync::{atomic::AtomicI32, Arc, Mutex},
 };
 
 use super::{CausalityRegion, DataSourceCreationError, DataSourceTemplateInfo, TriggerWithHandler};
@@ -101,6 +101,9 @@ pub struct DataSource {
     pub context: Arc<Option<DataSourceContext>>,
     pub creation_block: Option<BlockNumber>,
     done_at: Arc<AtomicI32>,
+    /// For data sources whose file is processed in chunks, the block at
+    /// which each of the chunks processed so far was processed
+    chunks_done_at: Arc<Mutex<Vec<BlockNumber>>>,
     pub causality_region: CausalityRegion,
 }
 
@@ -124,6 +127,7 @@ impl DataSource {
             context,
             creation_block,
             done_at: Arc::new(AtomicI32::new(NOT_DONE_VALUE)),
+            chunks_done_at: Arc::new(Mutex::new(vec![])),
             causality_region,
         }
     }
@@ -154,6 +158,24 @@ impl DataSource {
             .store(value, std::sync::atomic::Ordering::SeqCst);
     }
 
+    /// The number of chunks of the file that have been processed
+    pub fn chunks_done(&self) -> u32 {
+        self.chunks_done_at.lock().unwrap().len() as u32
+    }
+
+    pub fn mark_chunk_processed_at(&self, block_no: BlockNumber) {
+        self.chunks_done_at.lock().unwrap().push(block_no);
+    }
+
+    /// Forget about chunks that were processed at `block` or later. Returns
+    /// `true` if any chunks were forgotten
+    pub fn revert_chunks(&self, block: BlockNumber) -> bool {
+        let mut chunks_done_at = self.chunks_done_at.lock().unwrap();
+        let len = chunks_done_at.len();
+        chunks_done_at.retain(|done_at| *done_at < block);
+        chunks_done_at.len() != len
+    }
+
     pub fn min_spec_version(&self) -> semver::Version {
         // off-chain data sources are only supported in spec version 0.0.7 and up
         // As more and more kinds of off-chain data sources are added, this
@@ -201,6 +223,7 @@ impl DataSource {
             context: Arc::new(info.context),
             creation_block: Some(info.creation_block),
             done_at: Arc::new(AtomicI32::new(NOT_DONE_VALUE)),
+            chunks_done_at: Arc::new(Mutex::new(vec![])),
             causality_region,
         })
     }
@@ -212,6 +235,16 @@ impl DataSource {
         if self.source != trigger.source || self.is_processed() {
             return None;
         }
+        // Chunks are streamed separately for each data source, and a chunk
+        // is only processed once, in order. Chunks that do not line up with
+        // the progress of this data source were either already processed or
+        // come from a stream that was restarted, e.g., after a revert.
+        if let Some(chunk) = &trigger.chunk {
+            if chunk.causality_region != self.causality_region || chunk.index != self.chunks_done()
+            {
+                return None;
+            }
+        }
         Some(TriggerWithHandler::new(
             data_source::MappingTrigger::Offchain(trigger.clone()),
             self.mapping.handler.clone(),
@@ -241,6 +274,7 @@ impl DataSource {
             context,
             creation_block: self.creation_block,
             done_at,
+            chunks_done_at: self.chunks_done_at.lock().unwrap().clone(),
             causality_region: self.causality_region,
         }
     }
@@ -255,6 +289,7 @@ impl DataSource {
             context,
             creation_block,
             done_at,
+            chunks_done_at,
             causality_region,
         } = stored;
 
@@ -271,6 +306,7 @@ impl DataSource {
             context,
             creation_block,
             done_at: Arc::new(AtomicI32::new(done_at.unwrap_or(NOT_DONE_VALUE))),
+            chunks_done_at: Arc::new(Mutex::new(chunks_done_at)),
             causality_region,
         })
     }
@@ -292,6 +328,7 @@ impl DataSource {
 
             // We want to deduplicate across done status or creation block.
             done_at: _,
+            chunks_done_at: _,
             creation_block: _,
 
             // The causality region is also ignored, to be able to detect duplicated file data
@@ -349,6 +386,27 @@ pub struct Mapping {
     pub handler: String,
     pub runtime: Arc<Vec<u8>>,
     pub link: Link,
+    /// If set, the file is streamed and the handler is called once for
+    /// each chunk of records instead of once for the whole file
+    pub chunks: Option<Chunks>,
+}
+
+/// How the file of a data source is split into chunks. Chunk boundaries only
+/// depend on the contents of the file, which makes them deterministic.
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Deserialize)]
+pub struct Chunks {
+    pub format: ChunkFormat,
+    /// The number of records in each chunk
+    pub size: u32,
+}
+
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChunkFormat {
+    /// One JSON value per line
+    JsonLines,
+    /// CSV with a header line, which is repeated at the start of each chunk
+    Csv,
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
@@ -372,6 +430,8 @@ pub struct UnresolvedMapping {
     pub file: Link,
     pub handler: String,
     pub entities: Vec<String>,
+    #[serde(default)]
+    pub chunks: Option<Chunks>,
 }
 
 impl UnresolvedDataSource {
@@ -402,6 +462,7 @@ impl UnresolvedDataSource {
             context: Arc::new(None),
             creation_block: None,
             done_at: Arc::new(AtomicI32::new(NOT_DONE_VALUE)),
+            chunks_done_at: Arc::new(Mutex::new(vec![])),
             causality_region,
         })
     }
@@ -428,6 +489,9 @@ impl UnresolvedMapping {
             warn!(logger, "Ignoring unknown entity types in mapping"; "entities" => errs, "link" => &self.file.link);
         }
         let entities = entities.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+        if let Some(chunks) = &self.chunks {
+            ensure!(chunks.size > 0, "the chunk size must be greater than 0");
+        }
         Ok(Mapping {
             language: self.language,
             api_version: semver::Version::parse(&self.api_version)?,
@@ -435,6 +499,7 @@ impl UnresolvedMapping {
             handler: self.handler,
             runtime: Arc::new(resolver.cat(logger, &self.file).await?),
             link: self.file,
+            chunks: self.chunks,
         })
     }
 }
@@ -506,6 +571,21 @@ impl UnresolvedDataSourceTemplate {
 pub struct TriggerData {
     pub source: Source,
     pub data: Arc<bytes::Bytes>,
+    /// Set if `data` is only one chunk of the file
+    pub chunk: Option<Chunk>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Chunk {
+    /// The data source the chunk was streamed for
+    pub causality_region: CausalityRegion,
+    pub index: u32,
+    /// Whether this is the last chunk of the file
+    pub last: bool,
+    /// Set if the rest of the file can not be split into chunks, e.g.,
+    /// because a record is too large. Such a chunk has no data; processing
+    /// it only marks the data source as done
+    pub error: Option<String>,
 }
 
 impl fmt::Debug for TriggerData {
@@ -513,12 +593,14 @@ impl fmt::Debug for TriggerData {
         #[derive(Debug)]
         struct TriggerDataWithoutData<'a> {
             _source: &'a Source,
+            _chunk: &'a Option<Chunk>,
         }
         write!(
             f,
             "{:?}",
             TriggerDataWithoutData {
-                _source: &self.source
+                _source: &self.source,
+                _chunk: &self.chunk,
             }
         )
     }
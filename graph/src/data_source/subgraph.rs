@@ -1,5 +1,8 @@
 use crate::{
-    blockchain::{block_stream::EntitySourceOperation, Block, Blockchain},
+    blockchain::{
+        block_stream::{EntityOperationKind, EntitySourceOperation},
+        Block, Blockchain,
+    },
     components::{link_resolver::LinkResolver, store::BlockNumber},
     data::{
         subgraph::{
@@ -10,14 +13,14 @@ use crate::{
     },
     data_source::{self, common::DeclaredCall},
     ensure,
-    prelude::{CheapClone, DataSourceContext, DeploymentHash, Link},
+    prelude::{CheapClone, DataSourceContext, DeploymentHash, Link, ENV_VARS},
     schema::TypeKind,
 };
 use anyhow::{anyhow, Context, Error, Result};
 use futures03::{stream::FuturesOrdered, TryStreamExt};
 use serde::Deserialize;
 use slog::{info, Logger};
-use std::{fmt, sync::Arc};
+use std::{collections::HashSet, fmt, sync::Arc};
 
 use super::{
     common::{CallDecls, FindMappingABI, MappingABI, UnresolvedMappingABI},
@@ -38,6 +41,10 @@ pub struct DataSource {
     pub mapping: Mapping,
     pub context: Arc<Option<DataSourceContext>>,
     pub creation_block: Option<BlockNumber>,
+    /// How many levels of subgraph data sources this data source sits on
+    /// top of. A data source whose source subgraph only uses onchain data
+    /// sources has a depth of 1
+    pub depth: usize,
 }
 
 impl DataSource {
@@ -50,6 +57,7 @@ impl DataSource {
         mapping: Mapping,
         context: Arc<Option<DataSourceContext>>,
         creation_block: Option<BlockNumber>,
+        depth: usize,
     ) -> Self {
         Self {
             kind,
@@ -60,6 +68,7 @@ impl DataSource {
             mapping,
             context,
             creation_block,
+            depth,
         }
     }
 
@@ -89,6 +98,7 @@ impl DataSource {
             .handlers
             .iter()
             .filter(|handler| handler.entity == trigger.entity_type())
+            .filter(|handler| handler.handles(trigger.entity.entity_op))
             .collect();
 
         // Get the matching handler if any
@@ -100,7 +110,8 @@ impl DataSource {
         ensure!(
             matching_handlers.is_empty(),
             format!(
-                "Multiple handlers defined for entity `{}`, only one is supported",
+                "Multiple handlers defined for `{}` operations on entity `{}`, only one is supported",
+                trigger.entity.entity_op,
                 trigger.entity_type()
             )
         );
@@ -184,6 +195,29 @@ pub struct EntityHandler {
     pub entity: String,
     #[serde(default)]
     pub calls: CallDecls,
+    /// The kinds of changes to `entity` in the source subgraph that this
+    /// handler should be called for. If this is not set, the handler is
+    /// called for creations and modifications. Deletions have to be asked
+    /// for explicitly; the handler is then called with the last version of
+    /// the deleted entity
+    #[serde(default)]
+    pub operations: Option<Vec<EntityOperationKind>>,
+}
+
+impl EntityHandler {
+    const DEFAULT_OPERATIONS: [EntityOperationKind; 2] =
+        [EntityOperationKind::Create, EntityOperationKind::Modify];
+
+    pub fn operations(&self) -> &[EntityOperationKind] {
+        match &self.operations {
+            Some(ops) => ops,
+            None => &Self::DEFAULT_OPERATIONS,
+        }
+    }
+
+    pub fn handles(&self, op: EntityOperationKind) -> bool {
+        self.operations().contains(&op)
+    }
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
@@ -217,10 +251,12 @@ pub struct UnresolvedMapping {
 
 impl UnresolvedDataSource {
     fn validate_mapping_entities<C: Blockchain>(
-        mapping_entities: &[String],
+        handlers: &[EntityHandler],
         source_manifest: &SubgraphManifest<C>,
     ) -> Result<(), Error> {
-        for entity in mapping_entities {
+        let mut seen = HashSet::new();
+        for handler in handlers {
+            let entity = &handler.entity;
             let type_kind = source_manifest.schema.kind_of_declared_type(&entity);
 
             match type_kind {
@@ -240,14 +276,38 @@ impl UnresolvedDataSource {
                     return Err(anyhow!("Entity {} not found in source manifest", entity));
                 }
                 Some(TypeKind::Object) => {
-                    // Check if the entity is immutable
-                    let entity_type = source_manifest.schema.entity_type(entity)?;
-                    if !entity_type.is_immutable() {
+                    let explicit_ops = handler.operations.as_deref().unwrap_or_default();
+                    if handler.operations.is_some() && explicit_ops.is_empty() {
                         return Err(anyhow!(
-                            "Entity {} is not immutable and cannot be used as a mapping entity",
+                            "Handler {} for entity {} does not list any operations",
+                            handler.handler,
                             entity
                         ));
                     }
+                    // Immutable entities can only ever be created
+                    let entity_type = source_manifest.schema.entity_type(entity)?;
+                    if entity_type.is_immutable() {
+                        if let Some(op) = explicit_ops
+                            .iter()
+                            .find(|op| **op != EntityOperationKind::Create)
+                        {
+                            return Err(anyhow!(
+                                "Entity {} is immutable and handler {} can not handle `{}` operations for it",
+                                entity,
+                                handler.handler,
+                                op
+                            ));
+                        }
+                    }
+                    for op in handler.operations() {
+                        if !seen.insert((entity, *op)) {
+                            return Err(anyhow!(
+                                "Multiple handlers defined for `{}` operations on entity {}, only one is supported",
+                                op,
+                                entity
+                            ));
+                        }
+                    }
                 }
             }
         }
@@ -375,25 +435,27 @@ impl UnresolvedDataSource {
         )
         .await?;
 
-        if source_manifest
+        // The subgraph data sources of the source manifest have been
+        // resolved, and their depth checked, as part of resolving the
+        // source manifest
+        let depth = source_manifest
             .data_sources
             .iter()
-            .any(|ds| matches!(ds, crate::data_source::DataSource::Subgraph(_)))
-        {
+            .filter_map(|ds| ds.as_subgraph())
+            .map(|ds| ds.depth)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        if depth > ENV_VARS.subgraph_composition_max_depth {
             return Err(anyhow!(
-                "Nested subgraph data sources [{}] are not supported.",
-                self.name
+                "Subgraph data source [{}] is nested {} levels deep, but at most {} levels of nesting are supported",
+                self.name,
+                depth,
+                ENV_VARS.subgraph_composition_max_depth
             ));
         }
 
-        let mapping_entities: Vec<String> = self
-            .mapping
-            .handlers
-            .iter()
-            .map(|handler| handler.entity.clone())
-            .collect();
-
-        Self::validate_mapping_entities(&mapping_entities, &source_manifest)?;
+        Self::validate_mapping_entities(&self.mapping.handlers, &source_manifest)?;
 
         let source = Source {
             address: self.source.address,
@@ -409,6 +471,7 @@ impl UnresolvedDataSource {
             mapping: self.mapping.resolve(resolver, logger).await?,
             context: Arc::new(self.context),
             creation_block: None,
+            depth,
         })
     }
 }
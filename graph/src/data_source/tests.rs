@@ -46,6 +46,42 @@ fn offchain_mark_processed_error() {
     x.mark_processed_at(-1)
 }
 
+#[test]
+fn offchain_chunks() {
+    let ds = new_datasource();
+    let trigger = |index| offchain::TriggerData {
+        source: ds.source.clone(),
+        data: Arc::new(bytes::Bytes::new()),
+        chunk: Some(offchain::Chunk {
+            causality_region: ds.causality_region,
+            index,
+            last: false,
+            error: None,
+        }),
+    };
+
+    // Only the next chunk matches
+    assert!(ds.match_and_decode::<MockBlockchain>(&trigger(0)).is_some());
+    assert!(ds.match_and_decode::<MockBlockchain>(&trigger(1)).is_none());
+    ds.mark_chunk_processed_at(5);
+    ds.mark_chunk_processed_at(6);
+    assert!(ds.match_and_decode::<MockBlockchain>(&trigger(0)).is_none());
+    assert!(ds.match_and_decode::<MockBlockchain>(&trigger(2)).is_some());
+
+    // Chunks streamed for another data source with the same file don't match
+    let mut other = trigger(2);
+    other.chunk.as_mut().unwrap().causality_region = ds.causality_region.next();
+    assert!(ds.match_and_decode::<MockBlockchain>(&other).is_none());
+
+    assert_eq!(
+        vec![5, 6],
+        ds.as_stored_dynamic_data_source().chunks_done_at
+    );
+    assert!(ds.revert_chunks(6));
+    assert!(!ds.revert_chunks(6));
+    assert_eq!(1, ds.chunks_done());
+}
+
 #[test]
 fn data_source_helpers() {
     let offchain = new_datasource();
@@ -80,6 +116,7 @@ fn new_datasource() -> offchain::DataSource {
             link: Link {
                 link: String::new(),
             },
+            chunks: None,
         },
         Arc::new(None),
         Some(0),
@@ -100,6 +100,43 @@ pub struct EnvVarsGraphQl {
     /// Set by the env var `GRAPH_PARALLEL_BLOCK_CONSTRAINTS`
     /// Whether to run top-level queries with different block constraints in parallel
     pub parallel_block_constraints: bool,
+    /// Set by `GRAPH_GRAPHQL_MAX_WS_CONNECTIONS`, the maximum number of
+    /// WebSocket connections for GraphQL subscriptions that a query node
+    /// accepts at the same time. Setting it to 0 disables subscriptions.
+    /// The default is 1000
+    pub max_ws_connections: usize,
+    /// Set by `GRAPH_GRAPHQL_MAX_SUBSCRIPTIONS_PER_CONNECTION`, the maximum
+    /// number of subscriptions that can be active on one WebSocket
+    /// connection. The default is 100
+    pub max_subscriptions_per_connection: usize,
+    /// Set by `GRAPH_GRAPHQL_MAX_SUBSCRIPTION_RESULT_SIZE`, the maximum size
+    /// in bytes of the serialized result of a subscription. Subscriptions
+    /// whose result gets larger than that are ended with an error. The
+    /// default is 1MB
+    pub max_subscription_result_size: usize,
+    /// Set by `GRAPH_GRAPHQL_PERSISTED_QUERY_CACHE_SIZE`, the maximum size
+    /// in bytes of the query documents that a query node remembers for
+    /// automatic persisted queries. Setting it to 0 disables automatic
+    /// persisted queries. The default is 10MB
+    pub persisted_query_cache_size: usize,
+    /// Set by `GRAPH_GRAPHQL_CLIENT_ID_HEADER`, the name of the request
+    /// header that identifies the client, e.g., by an API key. Per-client
+    /// query budgets are only enforced if this is set. Requests without
+    /// the header all share the budget of one anonymous client. No default
+    /// value is provided
+    pub client_id_header: String,
+    /// Set by `GRAPH_GRAPHQL_CLIENT_QUOTA_WINDOW` (expressed in seconds),
+    /// the period over which the work of each client's queries is added up
+    /// and compared against its budget. The default is 60s
+    pub client_quota_window: Duration,
+    /// Set by `GRAPH_GRAPHQL_CLIENT_MAX_COMPLEXITY`, the total complexity
+    /// of the queries that one client can run within one quota window. No
+    /// default value is provided
+    pub client_max_complexity: Option<u64>,
+    /// Set by `GRAPH_GRAPHQL_CLIENT_MAX_QUERY_TIME` (expressed in
+    /// milliseconds), the total time that the queries of one client can
+    /// take within one quota window. No default value is provided
+    pub client_max_query_time: Option<Duration>,
 }
 
 // This does not print any values avoid accidentally leaking any sensitive env vars
@@ -145,6 +182,14 @@ impl From<InnerGraphQl> for EnvVarsGraphQl {
             disable_child_sorting: x.disable_child_sorting.0,
             query_trace_token: x.query_trace_token,
             parallel_block_constraints: x.parallel_block_constraints.0,
+            max_ws_connections: x.max_ws_connections,
+            max_subscriptions_per_connection: x.max_subscriptions_per_connection,
+            max_subscription_result_size: x.max_subscription_result_size.0,
+            persisted_query_cache_size: x.persisted_query_cache_size.0,
+            client_id_header: x.client_id_header,
+            client_quota_window: Duration::from_secs(x.client_quota_window_in_secs),
+            client_max_complexity: x.client_max_complexity.map(|x| x.0),
+            client_max_query_time: x.client_max_query_time_in_ms.map(Duration::from_millis),
         }
     }
 }
@@ -196,4 +241,29 @@ pub struct InnerGraphQl {
     query_trace_token: String,
     #[envconfig(from = "GRAPH_PARALLEL_BLOCK_CONSTRAINTS", default = "false")]
     pub parallel_block_constraints: EnvVarBoolean,
+    #[envconfig(from = "GRAPH_GRAPHQL_MAX_WS_CONNECTIONS", default = "1000")]
+    max_ws_connections: usize,
+    #[envconfig(
+        from = "GRAPH_GRAPHQL_MAX_SUBSCRIPTIONS_PER_CONNECTION",
+        default = "100"
+    )]
+    max_subscriptions_per_connection: usize,
+    #[envconfig(
+        from = "GRAPH_GRAPHQL_MAX_SUBSCRIPTION_RESULT_SIZE",
+        default = "1000000"
+    )]
+    max_subscription_result_size: NoUnderscores<usize>,
+    #[envconfig(
+        from = "GRAPH_GRAPHQL_PERSISTED_QUERY_CACHE_SIZE",
+        default = "10000000"
+    )]
+    persisted_query_cache_size: NoUnderscores<usize>,
+    #[envconfig(from = "GRAPH_GRAPHQL_CLIENT_ID_HEADER", default = "")]
+    client_id_header: String,
+    #[envconfig(from = "GRAPH_GRAPHQL_CLIENT_QUOTA_WINDOW", default = "60")]
+    client_quota_window_in_secs: u64,
+    #[envconfig(from = "GRAPH_GRAPHQL_CLIENT_MAX_COMPLEXITY")]
+    client_max_complexity: Option<NoUnderscores<u64>>,
+    #[envconfig(from = "GRAPH_GRAPHQL_CLIENT_MAX_QUERY_TIME")]
+    client_max_query_time_in_ms: Option<u64>,
 }
@@ -16,7 +16,7 @@ pub struct EnvVarsMapping {
     /// kilobytes). The default value is 10 megabytes.
     pub entity_cache_size: usize,
     /// Set by the environment variable `GRAPH_MAX_API_VERSION`. The default
-    /// value is `0.0.8`.
+    /// value is `0.0.10`.
     pub max_api_version: Version,
     /// Set by the environment variable `GRAPH_MAPPING_HANDLER_TIMEOUT`
     /// (expressed in seconds). No default is provided.
@@ -26,6 +26,11 @@ pub struct EnvVarsMapping {
     /// Set by the environment variable `GRAPH_RUNTIME_MAX_STACK_SIZE`
     /// (expressed in bytes). The default value is 512KiB.
     pub max_stack_size: usize,
+    /// Maximum number of entities a call to `store.query` may ask for.
+    ///
+    /// Set by the environment variable `GRAPH_STORE_QUERY_MAX_FIRST`. The
+    /// default value is 1000.
+    pub store_query_max_first: usize,
 
     /// Set by the environment variable `GRAPH_MAX_IPFS_CACHE_FILE_SIZE`
     /// (expressed in bytes). The default value is 1MiB.
@@ -49,6 +54,14 @@ pub struct EnvVarsMapping {
     /// bytes). Defaults to 25 MiB.
     pub max_ipfs_file_bytes: usize,
 
+    /// The number of chunks of files that are processed in chunks that are
+    /// fetched ahead of time. This bounds the memory used for streaming
+    /// files and the number of chunks processed per block.
+    ///
+    /// Set by the environment variable `GRAPH_MAX_BUFFERED_FILE_CHUNKS`.
+    /// Defaults to 16.
+    pub max_buffered_file_chunks: usize,
+
     /// Limits per second requests to IPFS for file data sources.
     ///
     /// Set by the environment variable `GRAPH_IPFS_REQUEST_LIMIT`. Defaults to 100.
@@ -87,12 +100,14 @@ impl From<InnerMappingHandlers> for EnvVarsMapping {
             max_api_version: x.max_api_version,
             timeout: x.mapping_handler_timeout_in_secs.map(Duration::from_secs),
             max_stack_size: x.runtime_max_stack_size.0 .0,
+            store_query_max_first: x.store_query_max_first,
 
             max_ipfs_cache_file_size: x.max_ipfs_cache_file_size.0,
             max_ipfs_cache_size: x.max_ipfs_cache_size,
             ipfs_timeout: Duration::from_secs(x.ipfs_timeout_in_secs),
             max_ipfs_map_file_size: x.max_ipfs_map_file_size.0,
             max_ipfs_file_bytes: x.max_ipfs_file_bytes.0,
+            max_buffered_file_chunks: x.max_buffered_file_chunks,
             ipfs_request_limit: x.ipfs_request_limit,
             allow_non_deterministic_ipfs: x.allow_non_deterministic_ipfs.0,
             disable_declared_calls: x.disable_declared_calls.0,
@@ -107,12 +122,14 @@ pub struct InnerMappingHandlers {
     entity_cache_dead_weight: EnvVarBoolean,
     #[envconfig(from = "GRAPH_ENTITY_CACHE_SIZE", default = "10000")]
     entity_cache_size_in_kb: usize,
-    #[envconfig(from = "GRAPH_MAX_API_VERSION", default = "0.0.9")]
+    #[envconfig(from = "GRAPH_MAX_API_VERSION", default = "0.0.10")]
     max_api_version: Version,
     #[envconfig(from = "GRAPH_MAPPING_HANDLER_TIMEOUT")]
     mapping_handler_timeout_in_secs: Option<u64>,
     #[envconfig(from = "GRAPH_RUNTIME_MAX_STACK_SIZE", default = "")]
     runtime_max_stack_size: WithDefaultUsize<NoUnderscores<usize>, { 512 * 1024 }>,
+    #[envconfig(from = "GRAPH_STORE_QUERY_MAX_FIRST", default = "1000")]
+    store_query_max_first: usize,
 
     // IPFS.
     #[envconfig(from = "GRAPH_MAX_IPFS_CACHE_FILE_SIZE", default = "")]
@@ -125,6 +142,8 @@ pub struct InnerMappingHandlers {
     max_ipfs_map_file_size: WithDefaultUsize<usize, { 256 * 1024 * 1024 }>,
     #[envconfig(from = "GRAPH_MAX_IPFS_FILE_BYTES", default = "")]
     max_ipfs_file_bytes: WithDefaultUsize<usize, { 25 * 1024 * 1024 }>,
+    #[envconfig(from = "GRAPH_MAX_BUFFERED_FILE_CHUNKS", default = "16")]
+    max_buffered_file_chunks: usize,
     #[envconfig(from = "GRAPH_IPFS_REQUEST_LIMIT", default = "100")]
     ipfs_request_limit: u16,
     #[envconfig(from = "GRAPH_ALLOW_NON_DETERMINISTIC_IPFS", default = "false")]
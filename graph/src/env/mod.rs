@@ -139,6 +139,12 @@ pub struct EnvVars {
     pub poi_access_token: Option<String>,
     /// Set by the environment variable `GRAPH_SUBGRAPH_MAX_DATA_SOURCES`. Defaults to 1 billion.
     pub subgraph_max_data_sources: usize,
+    /// How deeply subgraph data sources can be nested, i.e., how long a
+    /// chain of subgraphs that use each other as sources can be.
+    ///
+    /// Set by the environment variable
+    /// `GRAPH_SUBGRAPH_COMPOSITION_MAX_DEPTH`. The default value is 3.
+    pub subgraph_composition_max_depth: usize,
     /// Keep deterministic errors non-fatal even if the subgraph is pending.
     /// Used for testing Graph Node itself.
     ///
@@ -306,6 +312,7 @@ impl EnvVars {
             ),
             poi_access_token: inner.poi_access_token,
             subgraph_max_data_sources: inner.subgraph_max_data_sources.0,
+            subgraph_composition_max_depth: inner.subgraph_composition_max_depth,
             disable_fail_fast: inner.disable_fail_fast.0,
             subgraph_error_retry_ceil: Duration::from_secs(inner.subgraph_error_retry_ceil_in_secs),
             subgraph_error_retry_jitter: inner.subgraph_error_retry_jitter,
@@ -464,6 +471,8 @@ struct Inner {
     poi_access_token: Option<String>,
     #[envconfig(from = "GRAPH_SUBGRAPH_MAX_DATA_SOURCES", default = "1_000_000_000")]
     subgraph_max_data_sources: NoUnderscores<usize>,
+    #[envconfig(from = "GRAPH_SUBGRAPH_COMPOSITION_MAX_DEPTH", default = "3")]
+    subgraph_composition_max_depth: usize,
     #[envconfig(from = "GRAPH_DISABLE_FAIL_FAST", default = "false")]
     disable_fail_fast: EnvVarBoolean,
     #[envconfig(from = "GRAPH_SUBGRAPH_ERROR_RETRY_CEIL_SECS", default = "3600")]
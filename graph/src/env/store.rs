@@ -137,6 +137,11 @@ pub struct EnvVarsStore {
     /// The number of rows to fetch from the foreign data wrapper in one go,
     /// this will be set as the option 'fetch_size' on all foreign servers
     pub fdw_fetch_size: usize,
+    /// Whether to send a notification with the changed entity types after
+    /// each write and revert so that GraphQL subscriptions on query nodes
+    /// can be updated. Set by `GRAPH_STORE_SEND_ENTITY_CHANGES`. The
+    /// default is `false`
+    pub send_entity_changes: bool,
 }
 
 // This does not print any values avoid accidentally leaking any sensitive env vars
@@ -191,6 +196,7 @@ impl TryFrom<InnerStore> for EnvVarsStore {
             disable_block_cache_for_lookup: x.disable_block_cache_for_lookup,
             insert_extra_cols: x.insert_extra_cols,
             fdw_fetch_size: x.fdw_fetch_size,
+            send_entity_changes: x.send_entity_changes,
         };
         if let Some(timeout) = vars.batch_timeout {
             if timeout < 2 * vars.batch_target_duration {
@@ -271,6 +277,8 @@ pub struct InnerStore {
     insert_extra_cols: usize,
     #[envconfig(from = "GRAPH_STORE_FDW_FETCH_SIZE", default = "1000")]
     fdw_fetch_size: usize,
+    #[envconfig(from = "GRAPH_STORE_SEND_ENTITY_CHANGES", default = "false")]
+    send_entity_changes: bool,
 }
 
 #[derive(Clone, Copy, Debug)]
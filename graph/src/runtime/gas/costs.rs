@@ -53,8 +53,18 @@ pub const BIG_MATH_GAS_OP: GasOp = GasOp {
 // Allow up to 100,000 data sources to be created
 pub const CREATE_DATA_SOURCE: Gas = Gas(CONST_MAX_GAS_PER_HANDLER / 100_000);
 
+// Removing a data source is as expensive as creating one
+pub const REMOVE_DATA_SOURCE: Gas = CREATE_DATA_SOURCE;
+
 pub const ENS_NAME_BY_HASH: Gas = Gas(DEFAULT_BASE_COST);
 
+/// Recovering a public key or verifying a signature takes roughly 50µs, which is a lot more than
+/// hashing the message. The size cost covers hashing the message where that is part of the check.
+pub const CRYPTO_SIGNATURE_OP: GasOp = GasOp {
+    base_cost: GAS_PER_SECOND / 20_000,
+    size_mult: DEFAULT_GAS_PER_BYTE,
+};
+
 pub const LOG_OP: GasOp = GasOp {
     // Allow up to 100,000 logs
     base_cost: CONST_MAX_GAS_PER_HANDLER / 100_000,
@@ -75,8 +85,22 @@ pub const STORE_GET: GasOp = GasOp {
     size_mult: CONST_MAX_GAS_PER_HANDLER / 10_000_000_000,
 };
 
+// A query runs a database query that is much more expensive than looking up an entity by id,
+// even if it returns few entities. Allow up to 100k queries per handler.
+pub const STORE_QUERY: GasOp = GasOp {
+    base_cost: CONST_MAX_GAS_PER_HANDLER / 100_000,
+    size_mult: STORE_GET.size_mult,
+};
+
 pub const STORE_REMOVE: GasOp = STORE_SET;
 
+// Compiling a regex takes much longer than a plain host export, and in the worst case matching
+// takes time proportional to the size of the pattern times the size of the input.
+pub const REGEX_OP: GasOp = GasOp {
+    base_cost: GAS_PER_SECOND / 10_000,
+    size_mult: DEFAULT_GAS_PER_BYTE,
+};
+
 // Deeply nested JSON can take over 100x the memory of the serialized format, so multiplying the
 // size cost by 100 makes sense.
 pub const JSON_FROM_BYTES: GasOp = GasOp {
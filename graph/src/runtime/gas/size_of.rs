@@ -1,7 +1,7 @@
 //! Various implementations of GasSizeOf;
 
 use crate::{
-    components::store::LoadRelatedRequest,
+    components::store::{EntityFilter, FilteredEntityQuery, LoadRelatedRequest},
     data::store::{scalar::Bytes, Value},
     schema::{EntityKey, EntityType},
 };
@@ -162,6 +162,48 @@ impl GasSizeOf for LoadRelatedRequest {
     }
 }
 
+impl GasSizeOf for FilteredEntityQuery {
+    fn gas_size_of(&self) -> Gas {
+        self.entity_type.gas_size_of() + self.filter.gas_size_of()
+    }
+}
+
+impl GasSizeOf for EntityFilter {
+    fn gas_size_of(&self) -> Gas {
+        use EntityFilter::*;
+
+        match self {
+            And(filters) | Or(filters) => filters.gas_size_of(),
+            Equal(attr, value)
+            | Not(attr, value)
+            | GreaterThan(attr, value)
+            | LessThan(attr, value)
+            | GreaterOrEqual(attr, value)
+            | LessOrEqual(attr, value)
+            | Contains(attr, value)
+            | ContainsNoCase(attr, value)
+            | NotContains(attr, value)
+            | NotContainsNoCase(attr, value)
+            | StartsWith(attr, value)
+            | StartsWithNoCase(attr, value)
+            | NotStartsWith(attr, value)
+            | NotStartsWithNoCase(attr, value)
+            | EndsWith(attr, value)
+            | EndsWithNoCase(attr, value)
+            | NotEndsWith(attr, value)
+            | NotEndsWithNoCase(attr, value)
+            | Fulltext(attr, value) => attr.gas_size_of() + value.gas_size_of(),
+            In(attr, values) | NotIn(attr, values) => attr.gas_size_of() + values.gas_size_of(),
+            ChangeBlockGte(_) => Gas(4),
+            Child(child) => {
+                child.attr.gas_size_of()
+                    + child.entity_type.gas_size_of()
+                    + child.filter.gas_size_of()
+            }
+        }
+    }
+}
+
 impl GasSizeOf for EntityType {
     fn gas_size_of(&self) -> Gas {
         self.as_str().gas_size_of()
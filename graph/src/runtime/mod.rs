@@ -261,14 +261,39 @@ pub enum IndexForAscTypeId {
     ArrayH256 = 1002,
     ArrayLog = 1003,
     ArrayTypedMapStringStoreValue = 1004,
+    EthereumTransactionWithReceipt = 1005,
+    EthereumTransfer = 1006,
+    ArraySmartContractCall = 1007,
+    ArrayArrayEthereumValue = 1008,
+    EthereumStorageAtCall = 1009,
     // Continue to add more Ethereum type IDs here.
     // e.g.:
-    // NextEthereumType = 1004,
-    // AnotherEthereumType = 1005,
+    // NextEthereumType = 1010,
+    // AnotherEthereumType = 1011,
     // ...
     // LastEthereumType = 1499,
 
-    // Discriminant space [1,500, 2,499] was reserved for Cosmos, which has been removed
+    // Cosmos types
+    CosmosBlock = 1500,
+    CosmosHeader = 1501,
+    CosmosEvent = 1502,
+    CosmosEventArray = 1503,
+    CosmosEventAttribute = 1504,
+    CosmosEventAttributeArray = 1505,
+    CosmosTxResult = 1506,
+    CosmosTxResultArray = 1507,
+    CosmosResponseDeliverTx = 1508,
+    CosmosTransactionContext = 1509,
+    CosmosAny = 1510,
+    CosmosEventData = 1511,
+    CosmosTransactionData = 1512,
+    CosmosMessageData = 1513,
+    // Continue to add more Cosmos type IDs here.
+    // e.g.:
+    // NextCosmosType = 1514,
+    // AnotherCosmosType = 1515,
+    // ...
+    // LastCosmosType = 2499,
 
     // Arweave types
     ArweaveBlock = 2500,
@@ -311,7 +336,38 @@ pub enum IndexForAscTypeId {
     YamlWrappedValue = 5506,
     YamlResultValueBool = 5507,
 
-    // Reserved discriminant space for a future blockchain type IDs: [6,500, 7,499]
+    // Solana types
+    SolanaBlock = 6500,
+    SolanaAccount = 6501,
+    SolanaAccountArray = 6502,
+    SolanaBytesArray = 6503,
+    SolanaInstruction = 6504,
+    SolanaLog = 6505,
+    // Continue to add more Solana type IDs here.
+    // e.g.:
+    // NextSolanaType = 6506,
+    // AnotherSolanaType = 6507,
+    // ...
+    // LastSolanaType = 7499,
+
+    // Bitcoin types
+    BitcoinBlock = 7500,
+    BitcoinTransaction = 7501,
+    BitcoinInput = 7502,
+    BitcoinOutput = 7503,
+    BitcoinInputArray = 7504,
+    BitcoinOutputArray = 7505,
+    BitcoinBytesArray = 7506,
+    BitcoinInputWithInfo = 7507,
+    BitcoinOutputWithInfo = 7508,
+    // Continue to add more Bitcoin type IDs here.
+    // e.g.:
+    // NextBitcoinType = 7509,
+    // AnotherBitcoinType = 7510,
+    // ...
+    // LastBitcoinType = 8499,
+
+    // Reserved discriminant space for a future blockchain type IDs: [8,500, 9,499]
     //
     // Generated with the following shell script:
     //
@@ -324,6 +380,16 @@ pub enum IndexForAscTypeId {
     //    name and implementation before running this script.
     // 2. Replace `3500` part with the first number of that blockchain's reserved discriminant space.
     // 3. Insert the output right before the end of this block.
+
+    // Reserved discriminant space for types of host exports that are not
+    // specific to a blockchain: [9,500, 9,999]
+    DateTimeParts = 9500,
+    // Continue to add more type IDs here.
+    // e.g.:
+    // NextType = 9501,
+    // AnotherType = 9502,
+    // ...
+    // LastType = 9999,
     UnitTestNetworkUnitTestTypeU32 = u32::MAX - 7,
     UnitTestNetworkUnitTestTypeU32Array = u32::MAX - 6,
 
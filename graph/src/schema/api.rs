@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -11,7 +11,7 @@ use crate::cheap_clone::CheapClone;
 use crate::data::graphql::{ObjectOrInterface, ObjectTypeExt, TypeExt};
 use crate::data::store::IdType;
 use crate::env::ENV_VARS;
-use crate::schema::{ast, META_FIELD_NAME, META_FIELD_TYPE, SCHEMA_TYPE_NAME};
+use crate::schema::{ast, COUNT_FIELD_SUFFIX, META_FIELD_NAME, META_FIELD_TYPE, SCHEMA_TYPE_NAME};
 
 use crate::data::graphql::ext::{
     camel_cased_names, DefinitionExt, DirectiveExt, DocumentExt, ValueExt,
@@ -19,7 +19,7 @@ use crate::data::graphql::ext::{
 use crate::derive::CheapClone;
 use crate::prelude::{q, r, s, DeploymentHash};
 
-use super::{Aggregation, Field, InputSchema, Schema, TypeKind};
+use super::{kw, Aggregation, AggregationInterval, Field, InputSchema, Schema, TypeKind};
 
 #[derive(Error, Debug)]
 pub enum APISchemaError {
@@ -39,6 +39,7 @@ pub enum APISchemaError {
 const BLOCK_HEIGHT: &str = "Block_height";
 const CHANGE_BLOCK_FILTER_NAME: &str = "BlockChangedFilter";
 const ERROR_POLICY_TYPE: &str = "_SubgraphErrorPolicy_";
+const AGGREGATION_INTERVAL: &str = "Aggregation_interval";
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, CheapClone)]
 pub enum ErrorPolicy {
@@ -349,6 +350,7 @@ pub(in crate::schema) fn api_schema(
     // Refactor: Don't clone the schema.
     let mut api = init_api_schema(input_schema)?;
     add_meta_field_type(&mut api.document);
+    add_fixed_aggregation_intervals(&mut api.document, input_schema);
     add_types_for_object_types(&mut api, input_schema)?;
     add_types_for_interface_types(&mut api, input_schema)?;
     add_types_for_aggregation_types(&mut api, input_schema)?;
@@ -457,6 +459,39 @@ fn add_meta_field_type(api: &mut s::Document) {
         .extend(META_FIELD_SCHEMA.definitions.iter().cloned());
 }
 
+/// Add the fixed-length intervals like `15m` that aggregations in the input
+/// schema use to the `Aggregation_interval` enum from `meta.graphql`, which
+/// only contains the named intervals
+fn add_fixed_aggregation_intervals(api: &mut s::Document, input_schema: &InputSchema) {
+    let intervals: BTreeSet<_> = input_schema
+        .aggregation_types()
+        .flat_map(|(_, agg_type)| agg_type.intervals.iter())
+        .filter(|interval| matches!(interval, AggregationInterval::Fixed(_)))
+        .collect();
+    if intervals.is_empty() {
+        return;
+    }
+
+    let enum_type = api.definitions.iter_mut().find_map(|defn| match defn {
+        s::Definition::TypeDefinition(s::TypeDefinition::Enum(enum_type))
+            if enum_type.name == AGGREGATION_INTERVAL =>
+        {
+            Some(enum_type)
+        }
+        _ => None,
+    });
+    if let Some(enum_type) = enum_type {
+        enum_type
+            .values
+            .extend(intervals.into_iter().map(|interval| s::EnumValue {
+                position: Pos::default(),
+                description: None,
+                name: interval.graphql_name(),
+                directives: vec![],
+            }));
+    }
+}
+
 fn add_types_for_object_types(
     api: &mut Schema,
     schema: &InputSchema,
@@ -492,6 +527,46 @@ fn add_types_for_aggregation_types(
         }
         add_order_by_type(&mut api.document, name, &all_fields)?;
         add_aggregation_filter_type(api, name, agg_type)?;
+        add_group_by_type(&mut api.document, name, agg_type)?;
+    }
+    Ok(())
+}
+
+/// Adds a `<type_name>_groupBy` enum type with the dimensions of the
+/// aggregation to the schema. Aggregations without dimensions do not get
+/// such a type since there is nothing to group by
+fn add_group_by_type(
+    api: &mut s::Document,
+    type_name: &str,
+    agg: &Aggregation,
+) -> Result<(), APISchemaError> {
+    let values: Vec<_> = agg
+        .dimensions()
+        .map(|field| s::EnumValue {
+            position: Pos::default(),
+            description: None,
+            name: field.name.to_string(),
+            directives: vec![],
+        })
+        .collect();
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    let type_name = format!("{}_groupBy", type_name);
+    match api.get_named_type(&type_name) {
+        None => {
+            let typedef = s::TypeDefinition::Enum(s::EnumType {
+                position: Pos::default(),
+                description: None,
+                name: type_name,
+                directives: vec![],
+                values,
+            });
+            let def = s::Definition::TypeDefinition(typedef);
+            api.definitions.push(def);
+        }
+        Some(_) => return Err(APISchemaError::TypeExists(type_name)),
     }
     Ok(())
 }
@@ -689,7 +764,7 @@ impl FilterOps {
                     "interval",
                     "",
                     s::Type::NonNullType(Box::new(s::Type::NamedType(
-                        "Aggregation_interval".to_string(),
+                        AGGREGATION_INTERVAL.to_string(),
                     ))),
                 ),
                 input_value(
@@ -706,6 +781,13 @@ impl FilterOps {
         };
 
         let mut args = vec![skip, first];
+        if let FilterOps::Object = self {
+            args.push(input_value(
+                "after",
+                "",
+                s::Type::NamedType("String".to_string()),
+            ));
+        }
         args.extend(order_by);
         args.push(filter);
 
@@ -899,6 +981,10 @@ fn field_filter_ops(set: FilterOpsSet<'_>) -> &'static [&'static str] {
             "not_ends_with",
             "not_ends_with_nocase",
         ],
+        Aggregation("Boolean")
+        | Aggregation("Bytes")
+        | Aggregation("ID")
+        | Aggregation("String") => &["", "not", "in", "not_in"],
         Aggregation("BigInt")
         | Aggregation("BigDecimal")
         | Aggregation("Int")
@@ -1059,8 +1145,7 @@ fn add_query_type(api: &mut s::Document, input_schema: &InputSchema) -> Result<(
         .collect::<Vec<s::Field>>();
     let mut agg_fields = input_schema
         .aggregation_types()
-        .map(|(name, _)| name)
-        .flat_map(query_fields_for_agg_type)
+        .flat_map(|(name, agg)| query_fields_for_agg_type(name, agg))
         .collect::<Vec<s::Field>>();
     let mut fulltext_fields = input_schema
         .get_fulltext_directives()
@@ -1210,8 +1295,19 @@ fn query_fields_for_type(type_name: &str, ops: FilterOps) -> Vec<s::Field> {
     collection_arguments.push(subgraph_error_argument());
     by_id_arguments.push(subgraph_error_argument());
 
+    let count_arguments = vec![
+        input_value(
+            "where",
+            "",
+            s::Type::NamedType(format!("{}_filter", type_name)),
+        ),
+        block_argument(),
+        subgraph_error_argument(),
+    ];
+
     // Name formatting must be updated in sync with `graph::data::schema::validate_fulltext_directive_name()`
     let (singular, plural) = camel_cased_names(type_name);
+    let count = format!("{}{}", plural, COUNT_FIELD_SUFFIX);
     vec![
         s::Field {
             position: Pos::default(),
@@ -1231,11 +1327,31 @@ fn query_fields_for_type(type_name: &str, ops: FilterOps) -> Vec<s::Field> {
             )))),
             directives: vec![],
         },
+        s::Field {
+            position: Pos::default(),
+            description: Some(format!(
+                "Number of `{}` entities that match the `where` filter",
+                type_name
+            )),
+            name: count,
+            arguments: count_arguments,
+            field_type: s::Type::NonNullType(Box::new(s::Type::NamedType("Int".to_string()))),
+            directives: vec![],
+        },
     ]
 }
 
-fn query_fields_for_agg_type(type_name: &str) -> Vec<s::Field> {
+fn query_fields_for_agg_type(type_name: &str, agg: &Aggregation) -> Vec<s::Field> {
     let mut collection_arguments = FilterOps::Aggregation.collection_arguments(type_name);
+    if agg.dimensions().next().is_some() {
+        collection_arguments.push(input_value(
+            kw::GROUP_BY,
+            "",
+            s::Type::ListType(Box::new(s::Type::NonNullType(Box::new(
+                s::Type::NamedType(format!("{}_groupBy", type_name)),
+            )))),
+        ));
+    }
     collection_arguments.push(block_argument());
     collection_arguments.push(subgraph_error_argument());
 
@@ -1974,6 +2090,7 @@ mod tests {
             [
                 "skip",
                 "first",
+                "after",
                 "orderBy",
                 "orderDirection",
                 "where",
@@ -2069,6 +2186,7 @@ mod tests {
             [
                 "skip",
                 "first",
+                "after",
                 "orderBy",
                 "orderDirection",
                 "where",
@@ -2159,6 +2277,31 @@ type Gravatar @entity {
         query_field(&schema, "stats_collection");
     }
 
+    #[test]
+    fn count_fields() {
+        const SCHEMA: &str = r#"
+        type Musician @entity {
+            id: Bytes!
+            name: String!
+        }
+        "#;
+        let schema = parse(SCHEMA);
+
+        let count = query_field(&schema, "musicians_count");
+        assert_eq!(
+            s::Type::NonNullType(Box::new(s::Type::NamedType("Int".to_string()))),
+            count.field_type
+        );
+        assert_eq!(
+            vec!["where", "block", "subgraphError"],
+            count
+                .arguments
+                .iter()
+                .map(|arg| arg.name.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn nested_filters() {
         const SCHEMA: &str = r#"
@@ -2277,6 +2420,91 @@ type Gravatar @entity {
         assert_aggregation_field(&schema, stats, "Stats");
     }
 
+    #[test]
+    fn aggregation_intervals() {
+        const SCHEMA: &str = r#"
+        type Data @entity(timeseries: true) {
+            id: Int8!
+            timestamp: Timestamp!
+            value: BigDecimal!
+        }
+
+        type Stats @aggregation(source: "Data", intervals: ["15m", "hour", "4h", "month"]) {
+            id: Int8!
+            timestamp: Timestamp!
+            sum: BigDecimal! @aggregate(fn: "sum", arg: "value")
+        }
+        "#;
+        let schema = parse(SCHEMA);
+
+        let Some(s::TypeDefinition::Enum(intervals)) =
+            schema.get_named_type("Aggregation_interval")
+        else {
+            panic!("Aggregation_interval enum is missing")
+        };
+        let values: Vec<_> = intervals.values.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(
+            ["hour", "day", "week", "month", "_15m", "_4h"],
+            values.as_slice()
+        );
+
+        query_field(&schema, "stats_collection");
+    }
+
+    #[test]
+    fn aggregation_group_by() {
+        const SCHEMA: &str = r#"
+        type Data @entity(timeseries: true) {
+            id: Int8!
+            timestamp: Timestamp!
+            token: Bytes!
+            pool: String!
+            value: BigDecimal!
+        }
+
+        type Stats @aggregation(source: "Data", intervals: ["hour", "day"]) {
+            id: Int8!
+            timestamp: Timestamp!
+            token: Bytes!
+            pool: String!
+            sum: BigDecimal! @aggregate(fn: "sum", arg: "value")
+        }
+
+        type Volume @aggregation(source: "Data", intervals: ["hour", "day"]) {
+            id: Int8!
+            timestamp: Timestamp!
+            sum: BigDecimal! @aggregate(fn: "sum", arg: "value")
+        }
+        "#;
+        let schema = parse(SCHEMA);
+
+        let Some(s::TypeDefinition::Enum(group_by)) = schema.get_named_type("Stats_groupBy") else {
+            panic!("Stats_groupBy enum is missing")
+        };
+        let values: Vec<_> = group_by.values.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(["token", "pool"], values.as_slice());
+
+        let stats = query_field(&schema, "stats_collection");
+        let arg = stats.argument("groupBy").unwrap();
+        assert_eq!("Stats_groupBy", arg.value_type.get_base_type());
+
+        let Some(s::TypeDefinition::InputObject(filter)) = schema.get_named_type("Stats_filter")
+        else {
+            panic!("Stats_filter is missing")
+        };
+        for name in ["pool", "pool_not", "pool_in", "pool_not_in", "token_in"] {
+            assert!(
+                filter.fields.iter().any(|f| f.name == name),
+                "Stats_filter has a `{name}` field"
+            );
+        }
+
+        // Aggregations without dimensions can not be grouped
+        assert!(schema.get_named_type("Volume_groupBy").is_none());
+        let volumes = query_field(&schema, "volumes");
+        assert!(volumes.argument("groupBy").is_none());
+    }
+
     #[test]
     fn no_extra_filters_for_interface_children() {
         #[track_caller]
@@ -11,7 +11,7 @@ use crate::{
     util::intern::Atom,
 };
 
-use super::{EntityKey, Field, InputSchema, InterfaceType, ObjectType, POI_OBJECT};
+use super::{Aggregation, EntityKey, Field, InputSchema, InterfaceType, ObjectType, POI_OBJECT};
 
 use graph_derive::CheapClone;
 
@@ -52,6 +52,12 @@ impl EntityType {
         self.schema.typename(self.atom)
     }
 
+    /// Return the aggregation this type belongs to, or `None` if it is not
+    /// an aggregation
+    pub fn aggregation(&self) -> Option<&Aggregation> {
+        self.schema.aggregation(self.atom)
+    }
+
     pub fn is_poi(&self) -> bool {
         self.as_str() == POI_OBJECT
     }
@@ -5,6 +5,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{anyhow, Error};
+use chrono::Datelike;
 use semver::Version;
 use store::Entity;
 
@@ -26,7 +27,7 @@ use crate::schema::api::api_schema;
 use crate::util::intern::{Atom, AtomPool};
 
 use crate::schema::fulltext::FulltextDefinition;
-use crate::schema::{ApiSchema, AsEntityTypeName, EntityType, Schema};
+use crate::schema::{ApiSchema, AsEntityTypeName, EntityType, Schema, COUNT_FIELD_SUFFIX};
 
 pub mod sqlexpr;
 
@@ -50,6 +51,7 @@ pub mod kw {
     pub const ARG: &str = "arg";
     pub const INTERVALS: &str = "intervals";
     pub const INTERVAL: &str = "interval";
+    pub const GROUP_BY: &str = "groupBy";
     pub const CUMULATIVE: &str = "cumulative";
 }
 
@@ -612,6 +614,15 @@ pub enum AggregateFn {
     Count,
     First,
     Last,
+    Avg,
+    /// The population variance
+    Variance,
+    /// The population standard deviation
+    Stddev,
+    CountDistinct,
+    /// A percentile, given as an integer between 1 and 99. In the schema,
+    /// percentiles are written as `p50`, `p99` etc.
+    Percentile(u8),
 }
 
 impl FromStr for AggregateFn {
@@ -625,7 +636,17 @@ impl FromStr for AggregateFn {
             "count" => Ok(AggregateFn::Count),
             "first" => Ok(AggregateFn::First),
             "last" => Ok(AggregateFn::Last),
-            _ => Err(anyhow!("invalid aggregate function `{}`", s)),
+            "avg" => Ok(AggregateFn::Avg),
+            "variance" => Ok(AggregateFn::Variance),
+            "stddev" => Ok(AggregateFn::Stddev),
+            "countDistinct" => Ok(AggregateFn::CountDistinct),
+            _ => s
+                .strip_prefix('p')
+                .filter(|pct| pct.len() == 2 && pct.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|pct| pct.parse::<u8>().ok())
+                .filter(|pct| *pct > 0)
+                .map(AggregateFn::Percentile)
+                .ok_or_else(|| anyhow!("invalid aggregate function `{}`", s)),
         }
     }
 }
@@ -634,46 +655,214 @@ impl AggregateFn {
     pub fn has_arg(&self) -> bool {
         use AggregateFn::*;
         match self {
-            Sum | Max | Min | First | Last => true,
+            Sum | Max | Min | First | Last | Avg | Variance | Stddev | CountDistinct
+            | Percentile(_) => true,
             Count => false,
         }
     }
 
-    fn as_str(&self) -> &'static str {
+    /// Whether the aggregate for a time interval can be computed by
+    /// combining the aggregates of two adjacent time intervals. That is
+    /// what cumulative aggregations do with the previous aggregate and the
+    /// aggregate for the current bucket
+    pub fn is_mergeable(&self) -> bool {
         use AggregateFn::*;
         match self {
-            Sum => "sum",
-            Max => "max",
-            Min => "min",
-            Count => "count",
-            First => "first",
-            Last => "last",
+            Sum | Max | Min | Count | First | Last => true,
+            Avg | Variance | Stddev | CountDistinct | Percentile(_) => false,
+        }
+    }
+
+    /// Whether a cumulative aggregate needs to keep a running state of
+    /// the count, sum, and sum of squares of the values it has seen so
+    /// far. The aggregate is then computed from that state, which can be
+    /// combined like a mergeable aggregate
+    pub fn needs_state(&self) -> bool {
+        use AggregateFn::*;
+        match self {
+            Avg | Variance | Stddev => true,
+            Sum | Max | Min | Count | First | Last | CountDistinct | Percentile(_) => false,
+        }
+    }
+
+    /// Whether the aggregate can be cumulative. Distinct counts and
+    /// percentiles can only be computed from all the values they cover and
+    /// would have to be recomputed over the entire timeseries for every
+    /// bucket, which is too expensive
+    pub fn can_be_cumulative(&self) -> bool {
+        self.is_mergeable() || self.needs_state()
+    }
+
+    /// Whether the aggregate produces fractional values, even for integer
+    /// arguments. Such aggregates must be stored in a `BigDecimal` field
+    pub fn is_fractional(&self) -> bool {
+        use AggregateFn::*;
+        match self {
+            Avg | Variance | Stddev | Percentile(_) => true,
+            Sum | Max | Min | Count | First | Last | CountDistinct => false,
         }
     }
 }
 
-/// The supported intervals for timeseries in order of decreasing
-/// granularity. The intervals must all be divisible by the smallest
-/// interval
-#[derive(Clone, Copy, PartialEq, Eq, Debug, PartialOrd, Ord, Hash)]
+impl std::fmt::Display for AggregateFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use AggregateFn::*;
+        match self {
+            Sum => f.write_str("sum"),
+            Max => f.write_str("max"),
+            Min => f.write_str("min"),
+            Count => f.write_str("count"),
+            First => f.write_str("first"),
+            Last => f.write_str("last"),
+            Avg => f.write_str("avg"),
+            Variance => f.write_str("variance"),
+            Stddev => f.write_str("stddev"),
+            CountDistinct => f.write_str("countDistinct"),
+            Percentile(pct) => write!(f, "p{:02}", pct),
+        }
+    }
+}
+
+#[test]
+fn parse_aggregate_fn() {
+    use AggregateFn::*;
+
+    assert_eq!(Avg, "avg".parse().unwrap());
+    assert_eq!(CountDistinct, "countDistinct".parse().unwrap());
+    assert_eq!(Percentile(50), "p50".parse().unwrap());
+    assert_eq!(Percentile(5), "p05".parse().unwrap());
+    assert_eq!("p05", Percentile(5).to_string());
+
+    for invalid in [
+        "p",
+        "p0",
+        "p00",
+        "p5",
+        "p100",
+        "p+5",
+        "median",
+        "countdistinct",
+    ] {
+        assert!(invalid.parse::<AggregateFn>().is_err(), "{invalid}");
+    }
+}
+
+/// The supported intervals for timeseries. Intervals are ordered by their
+/// (nominal) length; for calendar intervals like `Month` that do not have a
+/// fixed length, the longest possible length is used for ordering
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 pub enum AggregationInterval {
     Hour,
     Day,
+    /// An ISO week, starting on Monday at midnight UTC
+    Week,
+    /// A calendar month, starting on the first of the month at midnight UTC
+    Month,
+    /// An interval with a fixed length, given in seconds. Buckets for these
+    /// intervals are aligned to the Unix epoch. In the schema, these are
+    /// written as a number followed by a unit, like `15m`, `4h`, or `2d`
+    Fixed(u32),
 }
 
 impl AggregationInterval {
-    pub fn as_str(&self) -> &'static str {
+    const MINUTE: u32 = 60;
+    const HOUR: u32 = 60 * Self::MINUTE;
+    const DAY: u32 = 24 * Self::HOUR;
+    const WEEK: u32 = 7 * Self::DAY;
+    /// The length of the longest month
+    const MONTH: u32 = 31 * Self::DAY;
+
+    /// The Monday after the Unix epoch, 1970-01-05T00:00Z. Weekly buckets
+    /// are aligned to this time
+    const FIRST_MONDAY: i64 = 4 * Self::DAY as i64;
+
+    /// Create an interval with a fixed length of `secs`. Lengths that
+    /// correspond to `hour` and `day` are normalized to those intervals
+    /// so that `60m` and `hour` are treated as the same interval
+    fn fixed(secs: u32) -> Self {
+        match secs {
+            Self::HOUR => AggregationInterval::Hour,
+            Self::DAY => AggregationInterval::Day,
+            _ => AggregationInterval::Fixed(secs),
+        }
+    }
+
+    /// Return the length of this interval if it has a fixed length, and
+    /// `None` for calendar intervals whose length varies
+    pub fn fixed_duration(&self) -> Option<Duration> {
+        use AggregationInterval::*;
         match self {
-            AggregationInterval::Hour => "hour",
-            AggregationInterval::Day => "day",
+            Hour => Some(Duration::from_secs(Self::HOUR as u64)),
+            Day => Some(Duration::from_secs(Self::DAY as u64)),
+            Week => Some(Duration::from_secs(Self::WEEK as u64)),
+            Month => None,
+            Fixed(secs) => Some(Duration::from_secs(*secs as u64)),
         }
     }
 
+    /// The length of this interval, or the longest length of it if the
+    /// length varies
     pub fn as_duration(&self) -> Duration {
+        self.fixed_duration()
+            .unwrap_or_else(|| Duration::from_secs(Self::MONTH as u64))
+    }
+
+    /// The name of this interval as a GraphQL enum value. Since GraphQL
+    /// names can not start with a digit, fixed intervals like `15m` are
+    /// prefixed with an underscore, i.e., become `_15m`
+    pub fn graphql_name(&self) -> String {
+        match self {
+            AggregationInterval::Fixed(_) => format!("_{}", self),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Return the start of the bucket that contains `time`
+    fn bucket_start(&self, time: BlockTime) -> BlockTime {
         use AggregationInterval::*;
+
+        // Treat any time before the epoch as the epoch; in practice, we
+        // will only deal with block times that are pretty far after the
+        // epoch
+        let secs = time.as_secs_since_epoch().max(0);
+        match self {
+            Hour | Day | Fixed(_) => {
+                let length = self.as_duration().as_secs() as i64;
+                BlockTime::since_epoch(secs - secs % length, 0)
+            }
+            Week => {
+                let length = Self::WEEK as i64;
+                let start =
+                    Self::FIRST_MONDAY + (secs - Self::FIRST_MONDAY).div_euclid(length) * length;
+                BlockTime::since_epoch(start.max(0), 0)
+            }
+            Month => {
+                let date = chrono::DateTime::from_timestamp(secs, 0)
+                    .expect("block times are valid timestamps")
+                    .date_naive();
+                month_start(date.year(), date.month())
+            }
+        }
+    }
+
+    /// Return the start of the bucket that follows the bucket starting at
+    /// `start`. `start` must be the start of a bucket
+    pub fn next_bucket(&self, start: BlockTime) -> BlockTime {
         match self {
-            Hour => Duration::from_secs(3600),
-            Day => Duration::from_secs(3600 * 24),
+            AggregationInterval::Month => {
+                let date = chrono::DateTime::from_timestamp(start.as_secs_since_epoch(), 0)
+                    .expect("block times are valid timestamps")
+                    .date_naive();
+                if date.month() == 12 {
+                    month_start(date.year() + 1, 1)
+                } else {
+                    month_start(date.year(), date.month() + 1)
+                }
+            }
+            _ => {
+                let length = self.as_duration().as_secs() as i64;
+                BlockTime::since_epoch(start.as_secs_since_epoch() + length, 0)
+            }
         }
     }
 
@@ -682,22 +871,64 @@ impl AggregationInterval {
     /// that overlap `from..to` and end before `to`. The ranges are in
     /// increasing order of the start time
     pub fn buckets(&self, from: BlockTime, to: BlockTime) -> Vec<Range<BlockTime>> {
-        let first = from.bucket(self.as_duration());
-        let last = to.bucket(self.as_duration());
-        (first..last)
-            .map(|nr| self.as_duration() * nr as u32)
-            .map(|start| {
-                let lower = BlockTime::from(start);
-                let upper = BlockTime::from(start + self.as_duration());
-                lower..upper
-            })
-            .collect()
+        let last = self.bucket_start(to);
+        let mut start = self.bucket_start(from);
+        let mut buckets = Vec::new();
+        while start < last {
+            let end = self.next_bucket(start);
+            buckets.push(start..end);
+            start = end;
+        }
+        buckets
+    }
+
+    fn rank(&self) -> u8 {
+        use AggregationInterval::*;
+        match self {
+            Fixed(_) => 0,
+            Hour => 1,
+            Day => 2,
+            Week => 3,
+            Month => 4,
+        }
+    }
+}
+
+/// The block time for midnight UTC on the first of the given month
+fn month_start(year: i32, month: u32) -> BlockTime {
+    let start = chrono::NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .expect("the first of a month is a valid date")
+        .and_utc();
+    BlockTime::since_epoch(start.timestamp(), 0)
+}
+
+impl PartialOrd for AggregationInterval {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AggregationInterval {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_duration()
+            .cmp(&other.as_duration())
+            .then_with(|| self.rank().cmp(&other.rank()))
     }
 }
 
 impl std::fmt::Display for AggregationInterval {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.as_str())
+        use AggregationInterval::*;
+        match self {
+            Hour => f.write_str("hour"),
+            Day => f.write_str("day"),
+            Week => f.write_str("week"),
+            Month => f.write_str("month"),
+            Fixed(secs) if secs % Self::DAY == 0 => write!(f, "{}d", secs / Self::DAY),
+            Fixed(secs) if secs % Self::HOUR == 0 => write!(f, "{}h", secs / Self::HOUR),
+            Fixed(secs) => write!(f, "{}m", secs / Self::MINUTE),
+        }
     }
 }
 
@@ -726,6 +957,70 @@ fn buckets() {
     );
     assert_eq!(vec![eight_am..nine_am], Hour.buckets(one_hour, two_hour));
     assert_eq!(Vec::<Range<BlockTime>>::new(), Day.buckets(start, two_hour));
+
+    // 15 minute buckets
+    let seven_30 = BlockTime::since_epoch(START - 10 * 60, 0);
+    let seven_45 = BlockTime::since_epoch(START + 5 * 60, 0);
+    assert_eq!(
+        vec![seven_30..seven_45, seven_45..eight_am],
+        Fixed(15 * 60).buckets(start, eight_am)
+    );
+}
+
+#[test]
+fn calendar_buckets() {
+    fn ts(s: &str) -> BlockTime {
+        let secs = chrono::DateTime::parse_from_rfc3339(s).unwrap().timestamp();
+        BlockTime::since_epoch(secs, 0)
+    }
+
+    use AggregationInterval::*;
+    // 2006-07-16 is a Sunday, 2006-07-10 and 2006-07-17 are Mondays
+    assert_eq!(
+        vec![ts("2006-07-10T00:00:00Z")..ts("2006-07-17T00:00:00Z")],
+        Week.buckets(ts("2006-07-16T07:40:00Z"), ts("2006-07-17T01:00:00Z"))
+    );
+    assert_eq!(
+        Vec::<Range<BlockTime>>::new(),
+        Week.buckets(ts("2006-07-17T07:40:00Z"), ts("2006-07-23T23:59:00Z"))
+    );
+    // Months have different lengths, including across year boundaries
+    assert_eq!(
+        vec![
+            ts("2023-12-01T00:00:00Z")..ts("2024-01-01T00:00:00Z"),
+            ts("2024-01-01T00:00:00Z")..ts("2024-02-01T00:00:00Z"),
+            ts("2024-02-01T00:00:00Z")..ts("2024-03-01T00:00:00Z"),
+        ],
+        Month.buckets(ts("2023-12-15T12:00:00Z"), ts("2024-03-01T00:00:00Z"))
+    );
+}
+
+#[test]
+fn parse_intervals() {
+    use AggregationInterval::*;
+
+    assert_eq!(Hour, "hour".parse().unwrap());
+    assert_eq!(Week, "week".parse().unwrap());
+    assert_eq!(Month, "month".parse().unwrap());
+    assert_eq!(Fixed(15 * 60), "15m".parse().unwrap());
+    assert_eq!(Fixed(4 * 3600), "4h".parse().unwrap());
+    assert_eq!(Fixed(4 * 3600), "240m".parse().unwrap());
+    assert_eq!(Fixed(4 * 3600), "_4h".parse().unwrap());
+    assert_eq!(Hour, "60m".parse().unwrap());
+    assert_eq!(Day, "1d".parse().unwrap());
+    assert_eq!("4h", Fixed(4 * 3600).to_string());
+    assert_eq!("_15m", Fixed(15 * 60).graphql_name());
+
+    for invalid in ["", "m", "0m", "15", "15s", "-1h", "1.5h", "minute"] {
+        assert!(invalid.parse::<AggregationInterval>().is_err(), "{invalid}");
+    }
+
+    let mut intervals = vec![Month, Fixed(15 * 60), Day, Week, Fixed(4 * 3600), Hour];
+    intervals.sort();
+    assert_eq!(
+        vec![Fixed(15 * 60), Hour, Fixed(4 * 3600), Day, Week, Month],
+        intervals
+    );
 }
 
 impl FromStr for AggregationInterval {
@@ -735,7 +1030,26 @@ impl FromStr for AggregationInterval {
         match s {
             "hour" => Ok(AggregationInterval::Hour),
             "day" => Ok(AggregationInterval::Day),
-            _ => Err(anyhow!("invalid aggregation interval `{}`", s)),
+            "week" => Ok(AggregationInterval::Week),
+            "month" => Ok(AggregationInterval::Month),
+            _ => {
+                // Fixed intervals use a leading `_` when they are used as
+                // GraphQL enum values; see `graphql_name`
+                let fixed = s.strip_prefix('_').unwrap_or(s);
+                let unit = match fixed.chars().last() {
+                    Some('m') => Self::MINUTE,
+                    Some('h') => Self::HOUR,
+                    Some('d') => Self::DAY,
+                    _ => return Err(anyhow!("invalid aggregation interval `{}`", s)),
+                };
+                let count = fixed[..fixed.len() - 1]
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|count| *count > 0 && fixed.as_bytes()[0].is_ascii_digit())
+                    .and_then(|count| count.checked_mul(unit))
+                    .ok_or_else(|| anyhow!("invalid aggregation interval `{}`", s))?;
+                Ok(AggregationInterval::fixed(count))
+            }
         }
     }
 }
@@ -769,6 +1083,10 @@ impl AggregationMapping {
     }
 }
 
+/// The suffix of the names of the fields that hold the running state of
+/// cumulative aggregates; see `Aggregate::state_field`
+const AGGREGATE_STATE_SUFFIX: &str = "$state";
+
 /// The `@aggregate` annotation in an aggregation. The annotation controls
 /// how values from the source table are aggregated
 #[derive(PartialEq, Debug)]
@@ -822,6 +1140,28 @@ impl Aggregate {
         }
     }
 
+    /// The function to use to combine values of this aggregate from
+    /// several rows of the same bucket into one when a query groups by a
+    /// subset of the dimensions. Returns `None` if the aggregate can not be
+    /// combined that way.
+    ///
+    /// Cumulative aggregates can not be combined since a group that has no
+    /// data in a bucket has no row for it, and its value would therefore be
+    /// missing from the combined value
+    pub fn regroup_fn(&self) -> Option<AggregateFn> {
+        use AggregateFn::*;
+
+        if self.cumulative {
+            return None;
+        }
+        match self.func {
+            Sum | Count => Some(Sum),
+            Max => Some(Max),
+            Min => Some(Min),
+            First | Last | Avg | Variance | Stddev | CountDistinct | Percentile(_) => None,
+        }
+    }
+
     /// The field needed for the finalised aggregation for hourly/daily
     /// values
     pub fn as_agg_field(&self) -> Field {
@@ -832,6 +1172,37 @@ impl Aggregate {
             derived_from: None,
         }
     }
+
+    /// The field that holds the running state of this aggregate if it is
+    /// cumulative and its function needs state (see
+    /// `AggregateFn::needs_state`). The state is the list `[count, sum,
+    /// sum of squares]` of the values aggregated so far. The field is only
+    /// used by rollups and is not exposed in the GraphQL API
+    pub fn state_field(&self) -> Option<Field> {
+        if !self.cumulative || !self.func.needs_state() {
+            return None;
+        }
+        let decimal = s::Type::NamedType(ValueType::BigDecimal.to_str().to_string());
+        Some(Field {
+            name: Word::from(Self::state_name(&self.name)),
+            field_type: s::Type::ListType(Box::new(s::Type::NonNullType(Box::new(decimal)))),
+            value_type: ValueType::BigDecimal,
+            derived_from: None,
+        })
+    }
+
+    /// The name of the state field for the aggregate `name`. Since GraphQL
+    /// names can not contain a `$`, it can never clash with a field that
+    /// is declared in the schema
+    pub fn state_name(name: &str) -> String {
+        format!("{name}{AGGREGATE_STATE_SUFFIX}")
+    }
+
+    /// If `field` is the name of the state field of an aggregate, return
+    /// the name of that aggregate
+    pub fn state_of(field: &str) -> Option<&str> {
+        field.strip_suffix(AGGREGATE_STATE_SUFFIX)
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -877,7 +1248,7 @@ impl Aggregation {
         let obj_types = intervals
             .iter()
             .map(|interval| {
-                let name = format!("{}_{}", &agg_type.name, interval.as_str());
+                let name = format!("{}_{}", &agg_type.name, interval);
                 let name = pool.lookup(&name).unwrap();
                 ObjectType {
                     name,
@@ -885,7 +1256,9 @@ impl Aggregation {
                     fields: fields
                         .iter()
                         .cloned()
-                        .chain(aggregates.iter().map(Aggregate::as_agg_field))
+                        .chain(aggregates.iter().flat_map(|agg| {
+                            std::iter::once(agg.as_agg_field()).chain(agg.state_field())
+                        }))
                         .collect(),
                     immutable: true,
                     aggregation: Some(name),
@@ -943,6 +1316,14 @@ impl Aggregation {
     fn field(&self, name: &str) -> Option<&Field> {
         self.fields.iter().find(|field| field.name == name)
     }
+
+    pub fn dimension(&self, name: &str) -> Option<&Field> {
+        self.dimensions().find(|field| field.name == name)
+    }
+
+    pub fn aggregate(&self, name: &str) -> Option<&Aggregate> {
+        self.aggregates.iter().find(|agg| agg.name == name)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -1383,6 +1764,18 @@ impl InputSchema {
             .any(|ti| matches!(ti, TypeInfo::Aggregation(_)))
     }
 
+    /// Return the names of all aggregations defined in the schema
+    pub fn aggregation_names(&self) -> Vec<&str> {
+        self.inner
+            .type_infos
+            .iter()
+            .filter_map(|ti| match ti {
+                TypeInfo::Aggregation(agg_type) => self.inner.pool.get(agg_type.name),
+                TypeInfo::Object(_) | TypeInfo::Interface(_) => None,
+            })
+            .collect()
+    }
+
     pub fn entity_fulltext_definitions(
         &self,
         entity: &str,
@@ -1524,6 +1917,12 @@ impl InputSchema {
         })
     }
 
+    /// Return the aggregation that `atom` belongs to if `atom` is the name
+    /// of an aggregation or of the type for one of its intervals
+    pub(in crate::schema) fn aggregation(&self, atom: Atom) -> Option<&Aggregation> {
+        self.type_info(atom).ok().and_then(TypeInfo::aggregation)
+    }
+
     pub(crate) fn typename(&self, atom: Atom) -> &str {
         let name = self.type_info(atom).unwrap().name();
         self.inner.pool.get(name).unwrap()
@@ -1627,12 +2026,18 @@ fn atom_pool(document: &s::Document) -> AtomPool {
                         .and_then(Value::as_list)
                         .unwrap_or(&NO_VALUE);
                     for interval in intervals {
-                        if let Some(interval) = interval.as_str() {
+                        if let Some(interval) = interval
+                            .as_str()
+                            .and_then(|interval| interval.parse::<AggregationInterval>().ok())
+                        {
                             pool.intern(&format!("{}_{}", t.name, interval));
                         }
                     }
                     for field in &t.fields {
                         pool.intern(&field.name);
+                        if field.find_directive(kw::AGGREGATE).is_some() {
+                            pool.intern(&Aggregate::state_name(&field.name));
+                        }
                     }
                 }
                 s::TypeDefinition::Enum(t) => {
@@ -1824,7 +2229,10 @@ mod validations {
             if self.entity_types.iter().any(|typ| {
                 typ.fields.iter().any(|field| {
                     let (singular, plural) = field.camel_cased_names();
-                    name == &singular || name == &plural || field.name.eq(name)
+                    name == &singular
+                        || name == &plural
+                        || name.strip_suffix(COUNT_FIELD_SUFFIX) == Some(plural.as_str())
+                        || field.name.eq(name)
                 })
             }) {
                 return vec![SchemaValidationError::FulltextNameCollision(
@@ -2492,7 +2900,7 @@ mod validations {
                                         errors.push(Err::AggregationMissingArg(
                                             agg_type.name.to_owned(),
                                             field.name.to_owned(),
-                                            func.as_str().to_owned(),
+                                            func.to_string(),
                                         ));
                                         continue;
                                     } else {
@@ -2503,6 +2911,14 @@ mod validations {
                                 }
                             };
                             match agg.argument(kw::CUMULATIVE) {
+                                Some(s::Value::Boolean(true)) if !func.can_be_cumulative() => {
+                                    errors.push(Err::AggregationNonCumulative(
+                                        agg_type.name.to_owned(),
+                                        field.name.to_owned(),
+                                        func.to_string(),
+                                    ));
+                                    continue;
+                                }
                                 Some(s::Value::Boolean(_)) | None => { /* ok */ }
                                 Some(_) => {
                                     errors.push(Err::AggregationInvalidCumulative(
@@ -2522,6 +2938,14 @@ mod validations {
                                     continue;
                                 }
                             };
+                            if func.is_fractional() && field_type != ValueType::BigDecimal {
+                                errors.push(Err::AggregationNonDecimalAggregate(
+                                    agg_type.name.to_owned(),
+                                    field.name.to_owned(),
+                                    func.to_string(),
+                                ));
+                                continue;
+                            }
                             // It would be nicer to use a proper struct here
                             // and have that implement
                             // `sqlexpr::ExprVisitor` but we need access to
@@ -2549,7 +2973,9 @@ mod validations {
                                         ));
                                     }
                                 };
-                                if arg_type > field_type {
+                                // Distinct counts are always integers,
+                                // regardless of the type of the argument
+                                if arg_type > field_type && func != AggregateFn::CountDistinct {
                                     return Err(Err::AggregationNonMatchingArg(
                                         agg_type.name.to_owned(),
                                         field.name.to_owned(),
@@ -42,6 +42,10 @@ pub const INTROSPECTION_SCHEMA_FIELD_NAME: &str = "__schema";
 pub const META_FIELD_TYPE: &str = "_Meta_";
 pub const META_FIELD_NAME: &str = "_meta";
 
+/// The suffix of the `Query` fields that count the entities of a
+/// collection, e.g., `tokens_count` for `tokens`
+pub const COUNT_FIELD_SUFFIX: &str = "_count";
+
 pub const INTROSPECTION_TYPE_FIELD_NAME: &str = "__type";
 
 pub const BLOCK_FIELD_TYPE: &str = "_Block_";
@@ -173,10 +177,14 @@ pub enum SchemaValidationError {
         "Field {1} in aggregation {0} has `arg` {2} of type {3} but it is of the wider type {4} in the source"
     )]
     AggregationNonMatchingArg(String, String, String, String, String),
+    #[error("Field {1} in aggregation {0} uses the function {2} which produces fractional values, but the field is not of type BigDecimal")]
+    AggregationNonDecimalAggregate(String, String, String),
     #[error("Field {1} in aggregation {0} has arg `{3}` but that is not a numeric field in {2}")]
     AggregationNonNumericArg(String, String, String, String),
     #[error("Field {1} in aggregation {0} has an invalid value for `cumulative`. It needs to be a boolean")]
     AggregationInvalidCumulative(String, String),
+    #[error("Field {1} in aggregation {0} uses the function {2} which can not be cumulative")]
+    AggregationNonCumulative(String, String, String),
     #[error("Aggregations are not supported with spec version {0}; please migrate the subgraph to the latest version")]
     AggregationsNotSupported(Version),
     #[error("Using Int8 as the type for the `id` field is not supported with spec version {0}; please migrate the subgraph to the latest version")]
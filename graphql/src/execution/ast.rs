@@ -105,7 +105,7 @@ impl SelectionSet {
         self.fields_for_name(&obj_type.name)
     }
 
-    fn fields_for_name(
+    pub(crate) fn fields_for_name(
         &self,
         name: &str,
     ) -> Result<impl Iterator<Item = &Field>, QueryExecutionError> {
@@ -20,7 +20,7 @@ use graph::prelude::{
     Logger, TryFromValue, ENV_VARS,
 };
 use graph::schema::ast::{self as sast};
-use graph::schema::ErrorPolicy;
+use graph::schema::{ErrorPolicy, COUNT_FIELD_SUFFIX};
 
 use crate::execution::ast as a;
 use crate::execution::get_field;
@@ -116,6 +116,9 @@ pub struct Query {
     pub selection_set: Arc<a::SelectionSet>,
     /// The ShapeHash of the original query
     pub shape_hash: u64,
+    /// The complexity of the query, used to charge clients for the work
+    /// their queries cause
+    pub complexity: u64,
 
     pub network: Option<String>,
 
@@ -247,10 +250,8 @@ impl Query {
         };
 
         // It's important to check complexity first, so `validate_fields`
-        // doesn't risk a stack overflow from invalid queries. We don't
-        // really care about the resulting complexity, only that all the
-        // checks that `check_complexity` performs pass successfully
-        let _ = raw_query.check_complexity(max_complexity, max_depth)?;
+        // doesn't risk a stack overflow from invalid queries
+        let complexity = raw_query.check_complexity(max_complexity, max_depth)?;
         raw_query.validate_fields()?;
         let selection_set = raw_query.convert()?;
 
@@ -258,6 +259,7 @@ impl Query {
             schema,
             selection_set: Arc::new(selection_set),
             shape_hash: query.shape_hash,
+            complexity,
             network,
             logger,
             start,
@@ -461,6 +463,20 @@ struct RawQuery<'s> {
     root_type: &'s s::ObjectType,
 }
 
+/// Whether `name` is the `<collection>_count` field for a collection
+/// field of `ty`
+fn is_count_field(ty: &s::TypeDefinition, name: &str) -> bool {
+    let Some(collection) = name.strip_suffix(COUNT_FIELD_SUFFIX) else {
+        return false;
+    };
+    let field = match ty {
+        s::TypeDefinition::Object(t) => get_field(t, collection),
+        s::TypeDefinition::Interface(t) => get_field(t, collection),
+        _ => None,
+    };
+    field.is_some_and(|field| sast::is_list_or_non_null_list_field(&field))
+}
+
 impl<'s> RawQuery<'s> {
     fn check_complexity(
         &self,
@@ -499,8 +515,15 @@ impl<'s> RawQuery<'s> {
             .try_fold(0, |total_complexity, selection| {
                 match selection {
                     q::Selection::Field(field) => {
-                        // Empty selection sets are the base case.
+                        // Empty selection sets are the base case. Counting a
+                        // collection has to look at every matching entity,
+                        // which we charge like a query with the maximum `first`
                         if field.selection_set.items.is_empty() {
+                            if is_count_field(ty, &field.name) {
+                                return total_complexity
+                                    .checked_add(ENV_VARS.graphql.max_first as u64)
+                                    .ok_or(Overflow);
+                            }
                             return Ok(total_complexity);
                         }
 
@@ -1,18 +1,23 @@
+use std::collections::BTreeSet;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+use crate::execution::ast as a;
 use crate::metrics::GraphQLMetrics;
 use crate::prelude::{QueryExecutionOptions, StoreResolver};
 use crate::query::execute_query;
+use graph::components::graphql::SubscriptionTarget;
+use graph::data::graphql::client_quota::ClientQuotas;
 use graph::futures03::future;
 use graph::prelude::MetricsRegistry;
 use graph::prelude::{
-    async_trait, o, CheapClone, DeploymentState, GraphQLMetrics as GraphQLMetricsTrait,
+    async_trait, o, r, CheapClone, DeploymentState, GraphQLMetrics as GraphQLMetricsTrait,
     GraphQlRunner as GraphQlRunnerTrait, Logger, Query, QueryExecutionError, ENV_VARS,
 };
+use graph::schema::{is_introspection_field, COUNT_FIELD_SUFFIX, META_FIELD_NAME};
 use graph::{data::graphql::load_manager::LoadManager, prelude::QueryStoreManager};
 use graph::{
-    data::query::{LatestBlockInfo, QueryResults, QueryTarget},
+    data::query::{LatestBlockInfo, QueryResults, QueryTarget, RegisteredQueries},
     prelude::QueryStore,
 };
 
@@ -21,6 +26,7 @@ pub struct GraphQlRunner<S> {
     logger: Logger,
     store: Arc<S>,
     load_manager: Arc<LoadManager>,
+    client_quotas: ClientQuotas,
     graphql_metrics: Arc<GraphQLMetrics>,
 }
 
@@ -47,6 +53,7 @@ where
             logger,
             store,
             load_manager,
+            client_quotas: ClientQuotas::default(),
             graphql_metrics,
         }
     }
@@ -104,6 +111,11 @@ where
         // setting up here
 
         let store = self.store.query_store(target.clone()).await?;
+        store
+            .registered_queries()
+            .await
+            .map_err(QueryExecutionError::from)?
+            .check(&query)?;
         let state = store.deployment_state().await?;
         let network = Some(store.network_name().to_string());
         let schema = store.api_schema()?;
@@ -132,6 +144,7 @@ where
 
         let max_depth = max_depth.unwrap_or(ENV_VARS.graphql.max_depth);
         let do_trace = query.trace;
+        let client = query.client.clone();
         let query = crate::execution::Query::new(
             &self.logger,
             schema,
@@ -150,6 +163,9 @@ where
                 query.query_text.as_ref(),
             )
             .to_result()?;
+        if let Some(client) = &client {
+            self.client_quotas.admit(client, query.complexity)?;
+        }
         let by_block_constraint =
             StoreResolver::locate_blocks(store.as_ref(), &state, &query).await?;
         let mut max_block = 0;
@@ -157,6 +173,10 @@ where
             QueryResults::empty(query.root_trace(do_trace), latest_block);
         let mut query_res_futures: Vec<_> = vec![];
         let setup_elapsed = execute_start.elapsed();
+        // Queries from clients with a quota are always traced since we
+        // charge them for the time their SQL queries took, which we only
+        // know from the trace
+        let trace_sql = do_trace || client.is_some();
 
         // Note: This will always iterate at least once.
         for (ptr, (selection_set, error_policy)) in by_block_constraint {
@@ -181,7 +201,7 @@ where
                     deadline: ENV_VARS.graphql.query_timeout.map(|t| Instant::now() + t),
                     max_first: max_first.unwrap_or(ENV_VARS.graphql.max_first),
                     max_skip: max_skip.unwrap_or(ENV_VARS.graphql.max_skip),
-                    trace: do_trace,
+                    trace: trace_sql,
                 },
             ));
         }
@@ -196,12 +216,22 @@ where
             results
         };
 
+        let mut sql_elapsed = Duration::ZERO;
         for (query_res, cache_status) in results {
+            if cache_status.uses_database() {
+                sql_elapsed += query_res.trace.query_total().elapsed;
+            }
             result.append(query_res, cache_status);
         }
 
         query.log_execution(max_block);
         result.trace.finish(setup_elapsed, execute_start.elapsed());
+        if let Some(client) = &client {
+            // Charge the client only for the time its SQL queries took,
+            // and not for time spent waiting for a query permit or a
+            // database connection, or for results that came from the cache
+            self.client_quotas.record_query_time(client, sql_elapsed);
+        }
         self.deployment_changed(store.as_ref(), state, max_block as u64)
             .await
             .map_err(QueryResults::from)
@@ -209,6 +239,56 @@ where
     }
 }
 
+/// Return `true` if `filter`, the value of a `where` argument, filters by
+/// the attributes of related entities, e.g., `{ bands_: { name: "x" } }`
+fn has_child_filter(filter: &r::Value) -> bool {
+    match filter {
+        r::Value::Object(obj) => obj
+            .iter()
+            .any(|(key, value)| key.ends_with('_') || has_child_filter(value)),
+        r::Value::List(values) => values.iter().any(has_child_filter),
+        _ => false,
+    }
+}
+
+/// Return `true` if `field` filters or sorts by the attributes of related
+/// entities. Which entity types that involves is not visible from the
+/// selection set
+fn uses_child_entities(field: &a::Field) -> bool {
+    let child_filter = field
+        .argument_value("where")
+        .map(has_child_filter)
+        .unwrap_or(false);
+    let child_sort = match field.argument_value("orderBy") {
+        Some(r::Value::Enum(order_by)) | Some(r::Value::String(order_by)) => {
+            order_by.contains("__")
+        }
+        _ => false,
+    };
+    child_filter || child_sort
+}
+
+/// Add the names of all object types that `selection_set` selects fields
+/// from, directly or in nested selections, to `target`
+fn collect_types(selection_set: &a::SelectionSet, target: &mut SubscriptionTarget) {
+    for (_, fields) in selection_set.fields() {
+        for field in fields {
+            // Rather than working out the types of the related entities,
+            // we rerun such queries on any change
+            if field.name == META_FIELD_NAME || uses_child_entities(field) {
+                target.any_change = true;
+            }
+            if is_introspection_field(&field.name) {
+                continue;
+            }
+            for (obj_type, _) in field.selection_set.fields() {
+                target.entity_types.insert(obj_type.name.clone());
+            }
+            collect_types(&field.selection_set, target);
+        }
+    }
+}
+
 #[async_trait]
 impl<S> GraphQlRunnerTrait for GraphQlRunner<S>
 where
@@ -248,6 +328,54 @@ where
         .unwrap_or_else(|e| e)
     }
 
+    async fn subscription_target(
+        self: Arc<Self>,
+        query: Query,
+        target: QueryTarget,
+    ) -> Result<SubscriptionTarget, Vec<QueryExecutionError>> {
+        let store = self.store.query_store(target).await.map_err(|e| vec![e])?;
+        store
+            .registered_queries()
+            .await
+            .map_err(|e| vec![e.into()])?
+            .check(&query)
+            .map_err(|e| vec![e])?;
+        let network = Some(store.network_name().to_string());
+        let schema = store.api_schema().map_err(|e| vec![e])?;
+        let query = crate::execution::Query::new(
+            &self.logger,
+            schema,
+            network,
+            query,
+            ENV_VARS.graphql.max_complexity,
+            ENV_VARS.graphql.max_depth,
+            self.graphql_metrics.cheap_clone(),
+        )?;
+
+        let mut target = SubscriptionTarget {
+            deployment: query.schema.id().clone(),
+            entity_types: BTreeSet::new(),
+            any_change: false,
+        };
+        // Counts of collections do not select any fields, and we rerun
+        // them on any change
+        target.any_change = query.selection_set.fields().any(|(_, mut fields)| {
+            fields.any(|field| {
+                field.selection_set.is_empty() && field.name.ends_with(COUNT_FIELD_SUFFIX)
+            })
+        });
+        collect_types(&query.selection_set, &mut target);
+        Ok(target)
+    }
+
+    async fn registered_queries(
+        self: Arc<Self>,
+        target: QueryTarget,
+    ) -> Result<Arc<RegisteredQueries>, QueryExecutionError> {
+        let store = self.store.query_store(target).await?;
+        Ok(store.registered_queries().await?)
+    }
+
     fn metrics(&self) -> Arc<dyn GraphQLMetricsTrait> {
         self.graphql_metrics.clone()
     }
@@ -23,7 +23,7 @@ use graph::prelude::{
     AttributeNames, ChildMultiplicity, EntityCollection, EntityFilter, EntityLink, EntityOrder,
     EntityWindow, ParentLink, QueryExecutionError, Value as StoreValue, WindowAttribute, ENV_VARS,
 };
-use graph::schema::{EntityType, InputSchema, ObjectOrInterface};
+use graph::schema::{EntityType, InputSchema, ObjectOrInterface, COUNT_FIELD_SUFFIX};
 
 use crate::execution::ast as a;
 use crate::metrics::GraphQLMetrics;
@@ -559,16 +559,27 @@ pub fn run(
     // Execute the root selection set against the root query type.
     let (nodes, trace) =
         loader.execute_selection_set(make_root_node(), trace, selection_set, None)?;
+    let counts = loader.count_collections(selection_set)?;
 
     graphql_metrics.observe_query_result_size(nodes.weight());
-    let obj = Object::from_iter(nodes.into_iter().flat_map(|node| {
-        node.children.into_iter().map(|(key, nodes)| {
-            (
-                Word::from(format!("prefetch:{}", key)),
-                node_list_as_value(nodes),
-            )
-        })
-    }));
+    let obj = Object::from_iter(
+        nodes
+            .into_iter()
+            .flat_map(|node| {
+                node.children.into_iter().map(|(key, nodes)| {
+                    (
+                        Word::from(format!("prefetch:{}", key)),
+                        node_list_as_value(nodes),
+                    )
+                })
+            })
+            .chain(counts.into_iter().map(|(key, count)| {
+                (
+                    Word::from(format!("prefetch:{}", key)),
+                    r::Value::Int(count),
+                )
+            })),
+    );
 
     Ok((r::Value::Object(obj), trace))
 }
@@ -746,6 +757,62 @@ impl<'a> Loader<'a> {
             .map(|(values, trace)| (values.into_iter().map(Node::from).collect(), trace))
     }
 
+    /// Resolve the `<collection>_count` fields of the root selection set by
+    /// counting the entities that match their `where` filter. Returns the
+    /// count for each response key
+    fn count_collections<'s>(
+        &self,
+        selection_set: &'s a::SelectionSet,
+    ) -> Result<Vec<(&'s str, i64)>, Vec<QueryExecutionError>> {
+        let input_schema = self.resolver.store.input_schema()?;
+        let mut counts = Vec::new();
+        let mut errors = Vec::new();
+
+        for (object_type, fields) in selection_set.fields() {
+            for field in fields.filter(|field| field.selection_set.is_empty()) {
+                let Some(collection) = field
+                    .name
+                    .strip_suffix(COUNT_FIELD_SUFFIX)
+                    .and_then(|name| object_type.field(name))
+                else {
+                    continue;
+                };
+                let child_type = input_schema
+                    .object_or_interface(collection.field_type.get_base_type(), None)
+                    .ok_or_else(|| {
+                        vec![QueryExecutionError::InternalError(format!(
+                            "the type of `{}` is not an object or interface type",
+                            collection.name
+                        ))]
+                    })?;
+
+                let count = build_query(
+                    &child_type,
+                    self.resolver.block_number(),
+                    field,
+                    self.ctx.max_first,
+                    self.ctx.max_skip,
+                    &input_schema,
+                )
+                .and_then(|mut query| {
+                    query.query_id = Some(self.ctx.query.query_id.clone());
+                    query.logger = Some(self.ctx.logger.cheap_clone());
+                    self.resolver.store.count_query_values(query)
+                });
+                match count {
+                    Ok(count) => counts.push((field.response_key(), count)),
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(counts)
+        } else {
+            Err(errors)
+        }
+    }
+
     fn check_result_size(&self, parents: &[&mut Node]) -> Result<(), QueryExecutionError> {
         let size = parents.iter().map(|parent| parent.weight()).sum::<usize>();
 
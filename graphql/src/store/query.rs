@@ -7,12 +7,12 @@ use graph::components::store::{
 };
 use graph::data::graphql::TypeExt as _;
 use graph::data::query::QueryExecutionError;
-use graph::data::store::{Attribute, Value, ValueType};
+use graph::data::store::{Attribute, Value, ValueType, ID};
 use graph::data::value::Object;
 use graph::data::value::Value as DataValue;
-use graph::prelude::{r, TryFromValue, ENV_VARS};
+use graph::prelude::{q, r, serde_json, TryFromValue, ENV_VARS};
 use graph::schema::ast::{self as sast, FilterOp};
-use graph::schema::{EntityType, InputSchema, ObjectOrInterface};
+use graph::schema::{kw, Aggregate, EntityType, InputSchema, ObjectOrInterface};
 
 use crate::execution::ast as a;
 
@@ -48,10 +48,221 @@ pub(crate) fn build_query<'a>(
     if let Some(filter) = build_filter(entity, field, schema)? {
         query = query.filter(filter);
     }
+    if let Some(after) = build_after(entity, field, &order)? {
+        query.filter = Some(after.and_maybe(query.filter));
+    }
+    if let Some(group_by) = build_group_by(entity, field, &order)? {
+        query = query.group_by(group_by);
+    }
     query = query.order(order);
     Ok(query)
 }
 
+/// Parses the `groupBy` argument of aggregation queries into the list of
+/// dimensions to group by, if present. Since aggregates are combined across
+/// all rows of a bucket with the same values for the grouped dimensions,
+/// the query can only select and order by `id`, `timestamp`, the grouped
+/// dimensions and aggregates that can be combined
+fn build_group_by(
+    entity: &ObjectOrInterface,
+    field: &a::Field,
+    order: &EntityOrder,
+) -> Result<Option<Vec<Attribute>>, QueryExecutionError> {
+    let value = match field.argument_value(kw::GROUP_BY) {
+        Some(r::Value::Null) | None => return Ok(None),
+        Some(value) => value,
+    };
+    let invalid = || {
+        QueryExecutionError::InvalidArgumentError(
+            field.position,
+            kw::GROUP_BY.to_string(),
+            q::Value::from(value.clone()),
+        )
+    };
+
+    let entity_type = match entity.object_types().into_iter().next() {
+        Some(entity_type) if entity.is_aggregation() => entity_type,
+        _ => return Err(invalid()),
+    };
+    let agg = entity_type.aggregation().ok_or_else(invalid)?;
+
+    let r::Value::List(dims) = value else {
+        return Err(invalid());
+    };
+    let mut group_by: Vec<Attribute> = Vec::new();
+    for dim in dims {
+        match dim {
+            r::Value::Enum(name) if agg.dimension(name).is_some() => {
+                if !group_by.contains(name) {
+                    group_by.push(name.clone());
+                }
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    let can_use = |name: &str| {
+        name == ID.as_str()
+            || name == kw::TIMESTAMP
+            || name.starts_with("__")
+            || group_by.iter().any(|dim| dim == name)
+            || agg
+                .aggregate(name)
+                .and_then(Aggregate::regroup_fn)
+                .is_some()
+    };
+    let not_supported = |name: &str| {
+        QueryExecutionError::GroupByNotSupportedError(
+            entity_type.typename().to_string(),
+            name.to_string(),
+        )
+    };
+
+    match order {
+        EntityOrder::Ascending(name, _) | EntityOrder::Descending(name, _) => {
+            if !can_use(name) {
+                return Err(not_supported(name));
+            }
+        }
+        EntityOrder::ChildAscending(child) | EntityOrder::ChildDescending(child) => {
+            let (EntityOrderByChild::Object(info, _) | EntityOrderByChild::Interface(info, _)) =
+                child;
+            return Err(not_supported(&info.join_attribute));
+        }
+        EntityOrder::Default | EntityOrder::Unordered => {}
+    }
+
+    for selected in field
+        .selection_set
+        .fields_for_name(entity_type.typename())?
+    {
+        if !can_use(&selected.name) {
+            return Err(not_supported(&selected.name));
+        }
+    }
+
+    Ok(Some(group_by))
+}
+
+/// Parses the `after` argument into a filter that only matches entities
+/// that come after the cursor in the order `order`, so that clients can
+/// page through large collections with a keyset instead of `skip`.
+///
+/// The cursor is a JSON array that contains the `id` of the last entity of
+/// the previous page. If the query is ordered by an attribute other than
+/// `id`, the value of that attribute for the last entity comes before the
+/// `id`. Both are given exactly as they appear in query results, e.g.,
+/// `["1000", "0xab"]` for a query ordered by a `BigInt` attribute.
+fn build_after(
+    entity: &ObjectOrInterface,
+    field: &a::Field,
+    order: &EntityOrder,
+) -> Result<Option<EntityFilter>, QueryExecutionError> {
+    let cursor = match field.argument_value("after") {
+        Some(r::Value::Null) | None => return Ok(None),
+        Some(cursor) => cursor,
+    };
+    let invalid = || {
+        QueryExecutionError::InvalidArgumentError(
+            field.position,
+            "after".to_string(),
+            q::Value::from(cursor.clone()),
+        )
+    };
+    let r::Value::String(cursor) = cursor else {
+        return Err(invalid());
+    };
+    let Ok(serde_json::Value::Array(mut values)) = serde_json::from_str(cursor) else {
+        return Err(invalid());
+    };
+
+    if field.argument_value("text").is_some() {
+        return Err(QueryExecutionError::NotSupported(
+            "`after` can not be used with fulltext search".to_string(),
+        ));
+    }
+
+    // Convert a part of the cursor to a value of the type of `attr`
+    let value_of = |attr: &str, value: serde_json::Value| -> Result<Value, QueryExecutionError> {
+        let field = entity.field(attr).ok_or_else(|| {
+            QueryExecutionError::EntityFieldError(entity.typename().to_owned(), attr.to_string())
+        })?;
+        let value = match (value, field.field_type.get_base_type()) {
+            // Large numbers may be written without quotes
+            (serde_json::Value::Number(n), "BigInt" | "BigDecimal" | "Int8" | "Timestamp") => {
+                r::Value::String(n.to_string())
+            }
+            (value, _) => r::Value::from(value),
+        };
+        Value::from_query_value(&value, &field.field_type).map_err(|_| invalid())
+    };
+
+    let id_only = |ascending: bool,
+                   mut values: Vec<serde_json::Value>|
+     -> Result<Option<EntityFilter>, QueryExecutionError> {
+        if values.len() != 1 {
+            return Err(invalid());
+        }
+        let id = value_of(ID.as_str(), values.pop().unwrap())?;
+        if id == Value::Null {
+            return Err(invalid());
+        }
+        Ok(Some(if ascending {
+            EntityFilter::GreaterThan(ID.to_string(), id)
+        } else {
+            EntityFilter::LessThan(ID.to_string(), id)
+        }))
+    };
+
+    let (attr, ascending) = match order {
+        EntityOrder::Default => return id_only(true, values),
+        EntityOrder::Ascending(attr, _) if attr == ID.as_str() => return id_only(true, values),
+        EntityOrder::Descending(attr, _) if attr == ID.as_str() => return id_only(false, values),
+        EntityOrder::Ascending(attr, _) => (attr, true),
+        EntityOrder::Descending(attr, _) => (attr, false),
+        EntityOrder::ChildAscending(_)
+        | EntityOrder::ChildDescending(_)
+        | EntityOrder::Unordered => {
+            return Err(QueryExecutionError::NotSupported(
+                "`after` can not be used when sorting by child attributes".to_string(),
+            ))
+        }
+    };
+
+    if values.len() != 2 {
+        return Err(invalid());
+    }
+    let id = value_of(ID.as_str(), values.pop().unwrap())?;
+    let value = value_of(attr, values.pop().unwrap())?;
+    if id == Value::Null {
+        return Err(invalid());
+    }
+
+    // Entities are sorted by `attr` and then by `id`, both in the same
+    // direction. Postgres sorts nulls last when sorting in ascending order
+    // and first when sorting in descending order
+    use EntityFilter::*;
+    let attr = attr.to_string();
+    let id_attr = ID.to_string();
+    let filter = match (value == Value::Null, ascending) {
+        (false, true) => Or(vec![
+            GreaterThan(attr.clone(), value.clone()),
+            And(vec![Equal(attr.clone(), value), GreaterThan(id_attr, id)]),
+            Equal(attr, Value::Null),
+        ]),
+        (false, false) => Or(vec![
+            LessThan(attr.clone(), value.clone()),
+            And(vec![Equal(attr, value), LessThan(id_attr, id)]),
+        ]),
+        (true, true) => And(vec![Equal(attr, Value::Null), GreaterThan(id_attr, id)]),
+        (true, false) => Or(vec![
+            Not(attr.clone(), Value::Null),
+            And(vec![Equal(attr, Value::Null), LessThan(id_attr, id)]),
+        ]),
+    };
+    Ok(Some(filter))
+}
+
 /// Parses GraphQL arguments into a EntityRange, if present.
 fn build_range(
     field: &a::Field,
@@ -654,6 +865,7 @@ fn build_order_direction(field: &a::Field) -> Result<OrderDirection, QueryExecut
 #[cfg(test)]
 mod tests {
     use graph::components::store::EntityQuery;
+    use graph::data::query::QueryExecutionError;
     use graph::data::store::ID;
     use graph::env::ENV_VARS;
     use graph::{
@@ -963,4 +1175,81 @@ mod tests {
             Some(EntityFilter::And(vec![EntityFilter::ChangeBlockGte(10)]))
         )
     }
+
+    #[test]
+    fn build_query_yields_after_filter() {
+        use EntityFilter::*;
+
+        let after = |cursor: &str| ("after", r::Value::String(cursor.to_string()));
+        let string = |s: &str| Value::String(s.to_string());
+
+        let field = default_field_with_vec(vec![after(r#"["a"]"#)]);
+        assert_eq!(
+            query(&field).filter,
+            Some(GreaterThan("id".to_string(), string("a")))
+        );
+
+        let field = default_field_with_vec(vec![
+            after(r#"["bob", "a"]"#),
+            ("orderBy", r::Value::Enum("name".to_string())),
+            ("orderDirection", r::Value::Enum("desc".to_string())),
+        ]);
+        assert_eq!(
+            query(&field).filter,
+            Some(Or(vec![
+                LessThan("name".to_string(), string("bob")),
+                And(vec![
+                    Equal("name".to_string(), string("bob")),
+                    LessThan("id".to_string(), string("a")),
+                ]),
+            ]))
+        );
+
+        let field = default_field_with_vec(vec![
+            after(r#"[null, "a"]"#),
+            ("orderBy", r::Value::Enum("name".to_string())),
+        ]);
+        assert_eq!(
+            query(&field).filter,
+            Some(And(vec![
+                Equal("name".to_string(), Value::Null),
+                GreaterThan("id".to_string(), string("a")),
+            ]))
+        );
+
+        // The cursor is combined with other filters
+        let field = default_field_with_vec(vec![
+            after(r#"["a"]"#),
+            (
+                "where",
+                r::Value::Object(Object::from_iter(vec![(
+                    "name".into(),
+                    r::Value::String("bob".to_string()),
+                )])),
+            ),
+        ]);
+        assert_eq!(
+            query(&field).filter,
+            Some(And(vec![
+                Equal("name".to_string(), string("bob")),
+                GreaterThan("id".to_string(), string("a")),
+            ]))
+        );
+    }
+
+    #[test]
+    fn build_query_rejects_invalid_after_cursor() {
+        let object = INPUT_SCHEMA
+            .object_or_interface(DEFAULT_OBJECT, None)
+            .unwrap();
+        for cursor in ["a", r#"{"id": "a"}"#, r#"["bob", "a"]"#, "[null]"] {
+            let field = default_field_with("after", r::Value::String(cursor.to_string()));
+            let res = build_query(&object, BLOCK_NUMBER_MAX, &field, 100, 100, &INPUT_SCHEMA);
+            assert!(
+                matches!(res, Err(QueryExecutionError::InvalidArgumentError(..))),
+                "cursor {} is invalid",
+                cursor
+            );
+        }
+    }
 }
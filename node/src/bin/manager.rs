@@ -297,6 +297,14 @@ pub enum Command {
     #[clap(subcommand)]
     Index(IndexCommand),
 
+    /// Manage aggregations
+    #[clap(subcommand)]
+    Aggregation(AggregationCommand),
+
+    /// Manage the queries registered for a deployment
+    #[clap(subcommand)]
+    PersistedQuery(PersistedQueryCommand),
+
     /// Prune a deployment
     ///
     /// Keep only entity versions that are needed to respond to queries at
@@ -770,6 +778,78 @@ pub enum IndexCommand {
     },
 }
 
+#[derive(Clone, Debug, Subcommand)]
+pub enum AggregationCommand {
+    /// Compute an aggregation from timeseries data that has already been
+    /// indexed
+    ///
+    /// All buckets of the aggregation are recomputed from its source
+    /// timeseries, replacing any buckets that were computed before. This
+    /// makes it possible to add an aggregation to a subgraph without
+    /// reindexing it from the start block, for example, by grafting the
+    /// new version onto the old one and then backfilling the aggregation.
+    ///
+    /// The deployment is paused while the aggregation is computed.
+    Backfill {
+        /// Sleep for this many seconds after pausing the deployment
+        #[clap(
+            long,
+            short,
+            default_value = "20",
+            value_parser = parse_duration_in_secs
+        )]
+        sleep: Duration,
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// The name of the aggregation as declared in the GraphQL schema
+        #[clap(value_parser = clap::builder::NonEmptyStringValueParser::new())]
+        aggregation: String,
+    },
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum PersistedQueryCommand {
+    /// Register a query document for a deployment
+    ///
+    /// Clients can run a registered query by sending just its sha256 hash.
+    /// If the deployment is restricted (see `help restrict`), only
+    /// registered queries can be run against it.
+    Register {
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// The file containing the query document
+        file: String,
+    },
+    /// Remove a query document from the registered queries of a deployment
+    Unregister {
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+        /// The sha256 hash of the query document
+        hash: String,
+    },
+    /// List the hashes of the queries registered for a deployment
+    List {
+        /// Also print the query documents
+        #[clap(long, short)]
+        text: bool,
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+    },
+    /// Only allow registered queries to be run against a deployment
+    ///
+    /// All other queries and subscriptions against the deployment fail.
+    /// Query nodes notice the change within a minute.
+    Restrict {
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+    },
+    /// Allow any query to be run against a deployment again
+    Unrestrict {
+        /// The deployment (see `help info`)
+        deployment: DeploymentSearch,
+    },
+}
+
 #[derive(Clone, Debug, Subcommand)]
 pub enum DatabaseCommand {
     /// Apply any pending migrations to the database schema in all shards
@@ -1595,6 +1675,49 @@ async fn main() -> anyhow::Result<()> {
                 }
             }
         }
+        Aggregation(cmd) => {
+            use AggregationCommand::*;
+            match cmd {
+                Backfill {
+                    sleep,
+                    deployment,
+                    aggregation,
+                } => {
+                    let sender = ctx.notification_sender();
+                    let (store, primary_pool) = ctx.store_and_primary();
+                    commands::aggregation::backfill(
+                        primary_pool,
+                        store.subgraph_store(),
+                        &sender,
+                        deployment,
+                        &aggregation,
+                        sleep,
+                    )
+                }
+            }
+        }
+        PersistedQuery(cmd) => {
+            use PersistedQueryCommand::*;
+            let (store, primary_pool) = ctx.store_and_primary();
+            let store = store.subgraph_store();
+            match cmd {
+                Register { deployment, file } => {
+                    commands::persisted_query::register(primary_pool, store, deployment, file)
+                }
+                Unregister { deployment, hash } => {
+                    commands::persisted_query::unregister(primary_pool, store, deployment, hash)
+                }
+                List { text, deployment } => {
+                    commands::persisted_query::list(primary_pool, store, deployment, text)
+                }
+                Restrict { deployment } => {
+                    commands::persisted_query::restrict(primary_pool, store, deployment, true)
+                }
+                Unrestrict { deployment } => {
+                    commands::persisted_query::restrict(primary_pool, store, deployment, false)
+                }
+            }
+        }
         Database(cmd) => {
             match cmd {
                 DatabaseCommand::Migrate => {
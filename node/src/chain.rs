@@ -1,6 +1,6 @@
 use crate::config::{Config, ProviderDetails};
 use crate::network_setup::{
-    AdapterConfiguration, EthAdapterConfig, FirehoseAdapterConfig, Networks,
+    AdapterConfiguration, BitcoinAdapterConfig, EthAdapterConfig, FirehoseAdapterConfig, Networks,
 };
 use ethereum::chain::{
     EthereumAdapterSelector, EthereumBlockRefetcher, EthereumRuntimeAdapterBuilder,
@@ -341,6 +341,53 @@ pub async fn create_ethereum_networks_for_chain(
     }))
 }
 
+/// Creates the JSON-RPC clients for the `web3` providers of chains that use
+/// the bitcoin protocol. Bitcoin chains that only use Firehose are set up by
+/// `create_firehose_networks`.
+pub fn create_bitcoin_networks(
+    logger: Logger,
+    config: &Config,
+    chain_filter: &dyn ChainFilter,
+) -> anyhow::Result<Vec<AdapterConfiguration>> {
+    let mut networks = vec![];
+
+    for (name, chain) in config
+        .chains
+        .chains
+        .iter()
+        .filter(|(_, chain)| chain.protocol == BlockchainKind::Bitcoin)
+        .filter(|(name, _)| chain_filter.filter(name))
+    {
+        let mut adapters = vec![];
+        for provider in &chain.providers {
+            let web3 = match &provider.details {
+                ProviderDetails::Web3(web3) => web3,
+                _ => continue,
+            };
+
+            let logger = logger.new(o!("provider" => provider.label.clone()));
+            info!(logger, "Creating bitcoin rpc client"; "network" => name);
+
+            adapters.push(Arc::new(graph_chain_bitcoin::BitcoinRpcAdapter::new(
+                provider.label.clone(),
+                Url::parse(&web3.url)?,
+            )));
+        }
+
+        if adapters.is_empty() {
+            continue;
+        }
+
+        networks.push(AdapterConfiguration::BitcoinRpc(BitcoinAdapterConfig {
+            chain_id: name.as_str().into(),
+            adapters,
+            polling_interval: Some(chain.polling_interval),
+        }));
+    }
+
+    Ok(networks)
+}
+
 /// Networks as chains will create the necessary chains from the adapter information.
 /// There are two major cases that are handled currently:
 /// Deep integration chains (explicitly defined on the graph-node like Ethereum, Near, etc):
@@ -461,6 +508,79 @@ pub async fn networks_as_chains(
                 )
                 .await;
             }
+            BlockchainKind::Bitcoin => {
+                // polling interval is set per chain so if set all adapter configuration will have
+                // the same value.
+                let polling_interval = adapters
+                    .iter()
+                    .find_map(|a| a.as_bitcoin_rpc())
+                    .and_then(|a| a.polling_interval)
+                    .unwrap_or(config.ingestor_polling_interval);
+
+                let firehose_endpoints = networks.firehose_endpoints(chain_id.clone());
+                let cc = if firehose_endpoints.len() > 0 {
+                    ChainClient::<graph_chain_bitcoin::Chain>::new_firehose(firehose_endpoints)
+                } else {
+                    ChainClient::<graph_chain_bitcoin::Chain>::new_rpc(
+                        networks.bitcoin_rpcs(chain_id.clone()),
+                    )
+                };
+
+                let chain = graph_chain_bitcoin::Chain::new(
+                    logger_factory.clone(),
+                    chain_id.clone(),
+                    node_id.clone(),
+                    metrics_registry.clone(),
+                    chain_store.cheap_clone(),
+                    Arc::new(cc),
+                    chain_head_update_listener.clone(),
+                    ENV_VARS.reorg_threshold(),
+                    polling_interval,
+                );
+
+                blockchain_map
+                    .insert::<graph_chain_bitcoin::Chain>(chain_id.clone(), Arc::new(chain));
+
+                add_substreams::<graph_chain_bitcoin::Chain>(
+                    networks,
+                    config,
+                    chain_id.clone(),
+                    blockchain_map,
+                    logger_factory.clone(),
+                    chain_store,
+                    metrics_registry.clone(),
+                )
+                .await;
+            }
+            BlockchainKind::Cosmos => {
+                let firehose_endpoints = networks.firehose_endpoints(chain_id.clone());
+
+                blockchain_map.insert::<graph_chain_cosmos::Chain>(
+                    chain_id.clone(),
+                    Arc::new(
+                        BasicBlockchainBuilder {
+                            logger_factory: logger_factory.clone(),
+                            name: chain_id.clone(),
+                            chain_store: chain_store.cheap_clone(),
+                            firehose_endpoints,
+                            metrics_registry: metrics_registry.clone(),
+                        }
+                        .build(config)
+                        .await,
+                    ),
+                );
+
+                add_substreams::<graph_chain_cosmos::Chain>(
+                    networks,
+                    config,
+                    chain_id.clone(),
+                    blockchain_map,
+                    logger_factory.clone(),
+                    chain_store,
+                    metrics_registry.clone(),
+                )
+                .await;
+            }
             BlockchainKind::Ethereum => {
                 // polling interval is set per chain so if set all adapter configuration will have
                 // the same value.
@@ -551,6 +671,34 @@ pub async fn networks_as_chains(
                 )
                 .await;
             }
+            BlockchainKind::Solana => {
+                let firehose_endpoints = networks.firehose_endpoints(chain_id.clone());
+                blockchain_map.insert::<graph_chain_solana::Chain>(
+                    chain_id.clone(),
+                    Arc::new(
+                        BasicBlockchainBuilder {
+                            logger_factory: logger_factory.clone(),
+                            name: chain_id.clone(),
+                            chain_store: chain_store.cheap_clone(),
+                            firehose_endpoints,
+                            metrics_registry: metrics_registry.clone(),
+                        }
+                        .build(config)
+                        .await,
+                    ),
+                );
+
+                add_substreams::<graph_chain_solana::Chain>(
+                    networks,
+                    config,
+                    chain_id.clone(),
+                    blockchain_map,
+                    logger_factory.clone(),
+                    chain_store,
+                    metrics_registry.clone(),
+                )
+                .await;
+            }
             BlockchainKind::Substreams => {
                 let substreams_endpoints = networks.substreams_endpoints(chain_id.clone());
                 blockchain_map.insert::<graph_chain_substreams::Chain>(
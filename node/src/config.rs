@@ -573,6 +573,21 @@ impl Chain {
             }
         }
 
+        // Bitcoin nodes are queried with JSON-RPC over HTTP
+        if matches!(self.protocol, BlockchainKind::Bitcoin) {
+            for provider in &self.providers {
+                match &provider.details {
+                    ProviderDetails::Web3Call(_) => {
+                        bail!("Bitcoin protocol does not support call-only providers")
+                    }
+                    ProviderDetails::Web3(web3) if web3.transport != Transport::Rpc => {
+                        bail!("Bitcoin protocol only supports the rpc transport")
+                    }
+                    _ => {}
+                }
+            }
+        }
+
         Ok(())
     }
 }
@@ -1415,6 +1430,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fails_if_non_rpc_transport_for_bitcoin_protocol() {
+        let mut actual = toml::from_str::<ChainSection>(
+            r#"
+            ingestor = "block_ingestor_node"
+            [bitcoin]
+            shard = "primary"
+            protocol = "bitcoin"
+            provider = [
+              { label = "bitcoind", details = { type = "web3", transport = "ws", url = "ws://127.0.0.1:8332", features = [] }},
+            ]
+        "#,
+        )
+        .unwrap();
+        let err = actual.validate().unwrap_err().to_string();
+
+        assert!(err.contains("only supports the rpc transport"), "{err}");
+    }
+
     #[test]
     fn it_works_on_new_web3_provider_from_toml() {
         let actual = toml::from_str(
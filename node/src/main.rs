@@ -6,6 +6,7 @@ use graph::futures03::future::TryFutureExt;
 
 use graph::blockchain::{Blockchain, BlockchainKind};
 use graph::components::link_resolver::{ArweaveClient, FileSizeLimit};
+use graph::components::metrics::handler_profile::HandlerProfiler;
 use graph::components::subgraph::Settings;
 use graph::data::graphql::load_manager::LoadManager;
 use graph::endpoint::EndpointMetrics;
@@ -14,7 +15,7 @@ use graph::log::logger;
 use graph::prelude::*;
 use graph::prometheus::Registry;
 use graph::url::Url;
-use graph_core::polling_monitor::{arweave_service, ipfs_service};
+use graph_core::polling_monitor::{arweave_service, ipfs_service, FileStreamer};
 use graph_core::{
     SubgraphAssignmentProvider as IpfsSubgraphAssignmentProvider, SubgraphInstanceManager,
     SubgraphRegistrar as IpfsSubgraphRegistrar,
@@ -237,6 +238,12 @@ async fn main_inner() {
             n => FileSizeLimit::MaxBytes(n as u64),
         },
     );
+    let file_streamer = FileStreamer::new(
+        ipfs_client.cheap_clone(),
+        arweave_resolver.cheap_clone(),
+        env_vars.mappings.ipfs_timeout,
+        env_vars.mappings.max_ipfs_file_bytes,
+    );
 
     // Convert the clients into a link resolver. Since we want to get past
     // possible temporary DNS failures, make the resolver retry
@@ -371,13 +378,22 @@ async fn main_inner() {
             load_manager,
             graphql_metrics_registry,
         ));
-        let graphql_server = GraphQLQueryServer::new(&logger_factory, graphql_runner.clone());
+        let graphql_server = GraphQLQueryServer::new(
+            &logger_factory,
+            graphql_runner.clone(),
+            subscription_manager.cheap_clone(),
+        );
+
+        // Shared between the subgraph instance manager, which collects
+        // handler profiles, and the index node server, which reports them
+        let handler_profiler = Arc::new(HandlerProfiler::default());
 
         let index_node_server = IndexNodeServer::new(
             &logger_factory,
             blockchain_map.clone(),
             network_store.clone(),
             link_resolver.clone(),
+            handler_profiler.cheap_clone(),
         );
 
         if !opt.disable_block_ingestor {
@@ -414,9 +430,11 @@ async fn main_inner() {
             blockchain_map.cheap_clone(),
             sg_count.cheap_clone(),
             metrics_registry.clone(),
+            handler_profiler,
             link_resolver.clone(),
             ipfs_service,
             arweave_service,
+            file_streamer,
             static_filters,
         );
 
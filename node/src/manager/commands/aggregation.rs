@@ -0,0 +1,69 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use graph::components::store::DeploymentLocator;
+use graph::prelude::anyhow::{self, anyhow};
+use graph_store_postgres::{
+    command_support::catalog, ConnectionPool, NotificationSender, SubgraphStore,
+};
+
+use crate::manager::commands::assign::pause_or_resume;
+use crate::manager::deployment::DeploymentSearch;
+
+/// Recompute the aggregation `aggregation` of a deployment from the
+/// timeseries data it has already indexed. The deployment is paused while
+/// the aggregation is computed, and is left in the state it was in before,
+/// whether the backfill succeeds or not
+pub fn backfill(
+    primary: ConnectionPool,
+    store: Arc<SubgraphStore>,
+    sender: &NotificationSender,
+    search: DeploymentSearch,
+    aggregation: &str,
+    sleep: Duration,
+) -> Result<(), anyhow::Error> {
+    let locator = search.locate_unique(&primary)?;
+
+    let was_paused = is_paused(&primary, &locator)?;
+    if was_paused {
+        println!("Deployment {locator} is already paused");
+    } else {
+        println!("Pausing deployment {locator}");
+        pause_or_resume(primary.clone(), sender, &locator, true)?;
+
+        // There's no good way to tell that a subgraph has in fact stopped
+        // indexing. We sleep and hope for the best.
+        println!(
+            "Waiting {}s to make sure pausing was processed",
+            sleep.as_secs()
+        );
+        thread::sleep(sleep);
+    }
+
+    println!("Backfilling aggregation {aggregation}");
+    let res = store.backfill_aggregation(&locator, aggregation);
+
+    if !was_paused {
+        println!("Resuming deployment {locator}");
+        pause_or_resume(primary, sender, &locator, false)?;
+    }
+
+    let count = res?;
+    println!("  ... filled {count} buckets");
+    Ok(())
+}
+
+fn is_paused(primary: &ConnectionPool, locator: &DeploymentLocator) -> Result<bool, anyhow::Error> {
+    let mut conn = catalog::Connection::new(primary.get()?);
+
+    let site = conn
+        .locate_site(locator.clone())?
+        .ok_or_else(|| anyhow!("failed to locate site for {locator}"))?;
+
+    let is_paused = conn
+        .assignment_status(&site)?
+        .map(|(_, is_paused)| is_paused)
+        .unwrap_or(false);
+    Ok(is_paused)
+}
@@ -1,3 +1,4 @@
+pub mod aggregation;
 pub mod assign;
 pub mod chain;
 pub mod check_blocks;
@@ -10,6 +11,7 @@ pub mod deployment;
 pub mod drop;
 pub mod index;
 pub mod listen;
+pub mod persisted_query;
 pub mod provider_checks;
 pub mod prune;
 pub mod query;
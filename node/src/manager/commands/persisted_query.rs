@@ -0,0 +1,90 @@
+use std::fs;
+use std::sync::Arc;
+
+use graph::prelude::anyhow::{self, anyhow};
+use graph::prelude::q;
+use graph_store_postgres::{ConnectionPool, SubgraphStore};
+
+use crate::manager::deployment::DeploymentSearch;
+
+/// Register the query document in `file` for a deployment so that clients
+/// can run it by its hash, even when only registered queries are allowed
+pub fn register(
+    primary: ConnectionPool,
+    store: Arc<SubgraphStore>,
+    search: DeploymentSearch,
+    file: String,
+) -> Result<(), anyhow::Error> {
+    let locator = search.locate_unique(&primary)?;
+
+    let query =
+        fs::read_to_string(&file).map_err(|e| anyhow!("could not read query from {file}: {e}"))?;
+    q::parse_query(&query).map_err(|e| anyhow!("{file} is not a valid query: {e}"))?;
+
+    let hash = store.register_persisted_query(&locator, &query)?;
+    println!("Registered query {hash} for deployment {locator}");
+    Ok(())
+}
+
+pub fn unregister(
+    primary: ConnectionPool,
+    store: Arc<SubgraphStore>,
+    search: DeploymentSearch,
+    hash: String,
+) -> Result<(), anyhow::Error> {
+    let locator = search.locate_unique(&primary)?;
+
+    if store.unregister_persisted_query(&locator, &hash.to_lowercase())? {
+        println!("Unregistered query {hash} for deployment {locator}");
+    } else {
+        println!("Query {hash} is not registered for deployment {locator}");
+    }
+    Ok(())
+}
+
+pub fn list(
+    primary: ConnectionPool,
+    store: Arc<SubgraphStore>,
+    search: DeploymentSearch,
+    text: bool,
+) -> Result<(), anyhow::Error> {
+    let locator = search.locate_unique(&primary)?;
+
+    if store.persisted_queries_only(&locator)? {
+        println!("Only registered queries can be run against deployment {locator}");
+    }
+    let queries = store.persisted_queries(&locator)?;
+    if queries.is_empty() {
+        println!("No queries are registered for deployment {locator}");
+        return Ok(());
+    }
+    for (hash, query) in queries {
+        println!("{hash}");
+        if text {
+            for line in query.lines() {
+                println!("    {line}");
+            }
+            println!();
+        }
+    }
+    Ok(())
+}
+
+/// Set whether only registered queries can be run against a deployment
+pub fn restrict(
+    primary: ConnectionPool,
+    store: Arc<SubgraphStore>,
+    search: DeploymentSearch,
+    only_registered: bool,
+) -> Result<(), anyhow::Error> {
+    let locator = search.locate_unique(&primary)?;
+
+    store.set_persisted_queries_only(&locator, only_registered)?;
+    if only_registered {
+        let count = store.persisted_queries(&locator)?.len();
+        println!("Only the {count} registered queries can now be run against deployment {locator}");
+    } else {
+        println!("Any query can now be run against deployment {locator}");
+    }
+    Ok(())
+}
@@ -10,6 +10,7 @@ use crate::MetricsContext;
 use graph::anyhow::bail;
 use graph::cheap_clone::CheapClone;
 use graph::components::link_resolver::{ArweaveClient, FileSizeLimit};
+use graph::components::metrics::handler_profile::HandlerProfiler;
 use graph::components::network_provider::ChainIdentifierStore;
 use graph::components::store::DeploymentLocator;
 use graph::components::subgraph::Settings;
@@ -21,7 +22,7 @@ use graph::prelude::{
     SubgraphStore, SubgraphVersionSwitchingMode, ENV_VARS,
 };
 use graph::slog::{debug, info, Logger};
-use graph_core::polling_monitor::{arweave_service, ipfs_service};
+use graph_core::polling_monitor::{arweave_service, ipfs_service, FileStreamer};
 use graph_core::{
     SubgraphAssignmentProvider as IpfsSubgraphAssignmentProvider, SubgraphInstanceManager,
     SubgraphRegistrar as IpfsSubgraphRegistrar,
@@ -79,6 +80,12 @@ pub async fn run(
             n => FileSizeLimit::MaxBytes(n as u64),
         },
     );
+    let file_streamer = FileStreamer::new(
+        ipfs_client.cheap_clone(),
+        arweave_resolver.cheap_clone(),
+        env_vars.mappings.ipfs_timeout,
+        env_vars.mappings.max_ipfs_file_bytes,
+    );
 
     let endpoint_metrics = Arc::new(EndpointMetrics::new(
         logger.clone(),
@@ -151,9 +158,11 @@ pub async fn run(
         blockchain_map.clone(),
         sg_metrics.cheap_clone(),
         metrics_registry.clone(),
+        Arc::new(HandlerProfiler::default()),
         link_resolver.cheap_clone(),
         ipfs_service,
         arweave_service,
+        file_streamer,
         static_filters,
     );
 
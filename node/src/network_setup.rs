@@ -24,14 +24,15 @@ use graph::{
     },
     slog::{o, warn, Discard},
 };
+use graph_chain_bitcoin::{BitcoinRpcAdapter, BitcoinRpcAdapters};
 use graph_chain_ethereum as ethereum;
 use graph_store_postgres::{BlockStore, ChainHeadUpdateListener};
 
 use std::{any::Any, cmp::Ordering, sync::Arc, time::Duration};
 
 use crate::chain::{
-    create_ethereum_networks, create_firehose_networks, create_substreams_networks,
-    networks_as_chains, AnyChainFilter, ChainFilter, OneChainFilter,
+    create_bitcoin_networks, create_ethereum_networks, create_firehose_networks,
+    create_substreams_networks, networks_as_chains, AnyChainFilter, ChainFilter, OneChainFilter,
 };
 
 #[derive(Debug, Clone)]
@@ -44,6 +45,13 @@ pub struct EthAdapterConfig {
     pub polling_interval: Option<Duration>,
 }
 
+#[derive(Debug, Clone)]
+pub struct BitcoinAdapterConfig {
+    pub chain_id: ChainName,
+    pub adapters: Vec<Arc<BitcoinRpcAdapter>>,
+    pub polling_interval: Option<Duration>,
+}
+
 #[derive(Debug, Clone)]
 pub struct FirehoseAdapterConfig {
     pub chain_id: ChainName,
@@ -54,6 +62,7 @@ pub struct FirehoseAdapterConfig {
 #[derive(Debug, Clone)]
 pub enum AdapterConfiguration {
     Rpc(EthAdapterConfig),
+    BitcoinRpc(BitcoinAdapterConfig),
     Firehose(FirehoseAdapterConfig),
     Substreams(FirehoseAdapterConfig),
 }
@@ -62,12 +71,14 @@ impl AdapterConfiguration {
     pub fn blockchain_kind(&self) -> &BlockchainKind {
         match self {
             AdapterConfiguration::Rpc(_) => &BlockchainKind::Ethereum,
+            AdapterConfiguration::BitcoinRpc(_) => &BlockchainKind::Bitcoin,
             AdapterConfiguration::Firehose(fh) | AdapterConfiguration::Substreams(fh) => &fh.kind,
         }
     }
     pub fn chain_id(&self) -> &ChainName {
         match self {
             AdapterConfiguration::Rpc(EthAdapterConfig { chain_id, .. })
+            | AdapterConfiguration::BitcoinRpc(BitcoinAdapterConfig { chain_id, .. })
             | AdapterConfiguration::Firehose(FirehoseAdapterConfig { chain_id, .. })
             | AdapterConfiguration::Substreams(FirehoseAdapterConfig { chain_id, .. }) => chain_id,
         }
@@ -80,6 +91,13 @@ impl AdapterConfiguration {
         }
     }
 
+    pub fn as_bitcoin_rpc(&self) -> Option<&BitcoinAdapterConfig> {
+        match self {
+            AdapterConfiguration::BitcoinRpc(rpc) => Some(rpc),
+            _ => None,
+        }
+    }
+
     pub fn as_firehose(&self) -> Option<&FirehoseAdapterConfig> {
         match self {
             AdapterConfiguration::Firehose(fh) => Some(fh),
@@ -180,9 +198,35 @@ impl Networks {
                     "substreams",
                 )
             })
+            .or_else(|_| self.bitcoin_chain_identifier(logger, chain_id))
             .await
     }
 
+    /// Bitcoin nodes are not managed by a `ProviderManager`, so we ask them
+    /// for the identifier directly
+    async fn bitcoin_chain_identifier(
+        &self,
+        logger: &Logger,
+        chain_id: &ChainName,
+    ) -> Result<ChainIdentifier> {
+        for adapter in self.bitcoin_rpcs(chain_id.clone()).adapters() {
+            match adapter.chain_identifier().await {
+                Ok(ident) => return Ok(ident),
+                Err(err) => {
+                    warn!(
+                        logger,
+                        "unable to get chain identification from bitcoin provider {} for chain {}, err: {}",
+                        adapter.provider(),
+                        chain_id,
+                        err.to_string(),
+                    );
+                }
+            }
+        }
+
+        bail!("no working adapters for chain {}", chain_id);
+    }
+
     async fn from_config_inner(
         logger: Logger,
         config: &crate::config::Config,
@@ -209,6 +253,7 @@ impl Networks {
             endpoint_metrics.cheap_clone(),
             chain_filter,
         );
+        let bitcoin = create_bitcoin_networks(logger.cheap_clone(), &config, chain_filter)?;
         let substreams = create_substreams_networks(
             logger.cheap_clone(),
             &config,
@@ -217,6 +262,7 @@ impl Networks {
         );
         let adapters: Vec<_> = eth
             .into_iter()
+            .chain(bitcoin.into_iter())
             .chain(firehose.into_iter())
             .chain(substreams.into_iter())
             .collect();
@@ -376,6 +422,13 @@ impl Networks {
                     block_ingestor::<graph_chain_arweave::Chain>(logger, id, chain, &mut res)
                         .await?
                 }
+                BlockchainKind::Bitcoin => {
+                    block_ingestor::<graph_chain_bitcoin::Chain>(logger, id, chain, &mut res)
+                        .await?
+                }
+                BlockchainKind::Cosmos => {
+                    block_ingestor::<graph_chain_cosmos::Chain>(logger, id, chain, &mut res).await?
+                }
                 BlockchainKind::Ethereum => {
                     block_ingestor::<graph_chain_ethereum::Chain>(logger, id, chain, &mut res)
                         .await?
@@ -383,6 +436,9 @@ impl Networks {
                 BlockchainKind::Near => {
                     block_ingestor::<graph_chain_near::Chain>(logger, id, chain, &mut res).await?
                 }
+                BlockchainKind::Solana => {
+                    block_ingestor::<graph_chain_solana::Chain>(logger, id, chain, &mut res).await?
+                }
                 BlockchainKind::Substreams => {}
             }
         }
@@ -453,4 +509,16 @@ impl Networks {
             None,
         )
     }
+
+    pub fn bitcoin_rpcs(&self, chain_id: ChainName) -> BitcoinRpcAdapters {
+        let adapters = self
+            .adapters
+            .iter()
+            .filter(|a| a.chain_id().eq(&chain_id))
+            .flat_map(|a| a.as_bitcoin_rpc())
+            .flat_map(|btc| btc.adapters.clone())
+            .collect_vec();
+
+        BitcoinRpcAdapters::new(adapters)
+    }
 }
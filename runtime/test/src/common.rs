@@ -49,10 +49,13 @@ fn mock_host_exports(
                 event_handlers: vec![],
                 call_handlers: vec![],
                 block_handlers: vec![],
+                transaction_handlers: vec![],
+                transfer_handlers: vec![],
                 link: Link {
                     link: "link".to_owned(),
                 },
                 runtime: Arc::new(vec![]),
+                source_map: None,
             },
         },
     )];
@@ -156,10 +159,13 @@ pub fn mock_data_source(path: &str, api_version: Version) -> DataSource {
             event_handlers: vec![],
             call_handlers: vec![],
             block_handlers: vec![],
+            transaction_handlers: vec![],
+            transfer_handlers: vec![],
             link: Link {
                 link: "link".to_owned(),
             },
             runtime: Arc::new(runtime),
+            source_map: None,
         },
         context: Default::default(),
         creation_block: None,
@@ -1,5 +1,6 @@
 use graph::blockchain::BlockTime;
 use graph::components::metrics::gas::GasMetrics;
+use graph::components::metrics::handler_profile::HandlerProfiler;
 use graph::components::store::*;
 use graph::data::store::{scalar, Id, IdType};
 use graph::data::subgraph::*;
@@ -26,6 +27,7 @@ use web3::types::H160;
 use crate::common::{mock_context, mock_data_source};
 
 mod abi;
+mod native;
 
 pub const API_VERSION_0_0_4: Version = Version::new(0, 0, 4);
 pub const API_VERSION_0_0_5: Version = Version::new(0, 0, 5);
@@ -95,6 +97,7 @@ async fn test_valid_module_and_store_with_timeout(
         deployment_id.as_str(),
         stopwatch_metrics,
         gas_metrics,
+        Arc::new(HandlerProfiler::default()),
     ));
 
     let experimental_features = ExperimentalFeatures {
@@ -102,7 +105,9 @@ async fn test_valid_module_and_store_with_timeout(
     };
 
     let module = WasmInstance::from_valid_module_with_ctx(
-        Arc::new(ValidModule::new(&logger, data_source.mapping.runtime.as_ref(), timeout).unwrap()),
+        Arc::new(
+            ValidModule::new(&logger, data_source.mapping.runtime.as_ref(), None, timeout).unwrap(),
+        ),
         mock_context(
             deployment.clone(),
             data_source,
@@ -0,0 +1,99 @@
+use graph_runtime_wasm::native_abi::{self, Decode, Encode};
+
+use super::*;
+
+fn native_file_path(wasm_file: &str) -> String {
+    format!("wasm_test/native/{}", wasm_file)
+}
+
+/// Copy the encoding of `args` into memory allocated with the module's
+/// allocator and return a pointer to it and its length
+fn write_args<T: Encode + ?Sized>(instance: &mut WasmInstance, args: &T) -> (u32, u32) {
+    let bytes = native_abi::encode(args);
+    let len = bytes.len() as u32;
+    let ptr: u32 = instance
+        .get_func("graph_alloc")
+        .typed(&mut instance.store.as_context_mut())
+        .unwrap()
+        .call(&mut instance.store.as_context_mut(), len)
+        .unwrap();
+    let memory = instance
+        .instance
+        .get_memory(&mut instance.store, "memory")
+        .unwrap();
+    memory
+        .write(&mut instance.store, ptr as usize, &bytes)
+        .unwrap();
+    (ptr, len)
+}
+
+/// Decode the length-prefixed result that the host returned at `ptr`
+fn read_result<T: Decode>(instance: &mut WasmInstance, ptr: u32) -> T {
+    let memory = instance
+        .instance
+        .get_memory(&mut instance.store, "memory")
+        .unwrap();
+    let mut len = [0u8; 4];
+    memory
+        .read(&instance.store, ptr as usize, &mut len)
+        .unwrap();
+    let mut buf = vec![0u8; u32::from_le_bytes(len) as usize];
+    memory
+        .read(&instance.store, ptr as usize + 4, &mut buf)
+        .unwrap();
+    native_abi::decode(&buf).unwrap()
+}
+
+fn call_with_args<R: wasmtime::WasmResults>(
+    instance: &mut WasmInstance,
+    func: &str,
+    args: (u32, u32),
+) -> R {
+    instance
+        .get_func(func)
+        .typed::<(u32, u32), R>(&mut instance.store.as_context_mut())
+        .unwrap()
+        .call(&mut instance.store.as_context_mut(), args)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn native_store_set_and_get() {
+    let mut instance = test_module(
+        "nativeStore",
+        mock_data_source(&native_file_path("store.wasm"), API_VERSION_0_0_5),
+        API_VERSION_0_0_5,
+    )
+    .await;
+
+    let data = vec![
+        (Word::from("id"), Value::from("one")),
+        (Word::from("value"), Value::from("hello")),
+    ];
+    let args = write_args(&mut instance, &("Thing", "one", data));
+    call_with_args::<()>(&mut instance, "handleSet", args);
+
+    let args = write_args(&mut instance, &("Thing", "one"));
+    let ptr: u32 = call_with_args(&mut instance, "getThing", args);
+    assert_ne!(0, ptr);
+    let thing: HashMap<Word, Value> = read_result(&mut instance, ptr);
+    assert_eq!(Some(&Value::from("hello")), thing.get("value"));
+
+    let args = write_args(&mut instance, &("Thing", "two"));
+    let ptr: u32 = call_with_args(&mut instance, "getThing", args);
+    assert_eq!(0, ptr);
+}
+
+#[test]
+fn native_module_can_not_import_chain_host_fns() {
+    let logger = Logger::root(slog::Discard, o!());
+    let raw_module = std::fs::read(native_file_path("chain_import.wasm")).unwrap();
+
+    let err = ValidModule::new(&logger, &raw_module, None, None)
+        .err()
+        .unwrap();
+    err_says(
+        err,
+        "can only import from the `graph` module, but this module imports `call` from `ethereum`",
+    );
+}
@@ -0,0 +1,12 @@
+;; A mapping that uses the native ABI but imports a chain-specific host
+;; function, which is only available to AssemblyScript mappings
+(module
+  (import "ethereum" "call" (func $call (param i32) (result i32)))
+
+  (memory (export "memory") 1)
+
+  (func (export "graph_abi_version") (result i32)
+    i32.const 1)
+
+  (func (export "graph_alloc") (param $size i32) (result i32)
+    i32.const 0))
@@ -0,0 +1,36 @@
+;; A mapping that uses the native ABI. Handlers pass their arguments, which
+;; the test writes into memory allocated with `graph_alloc`, straight to
+;; the host.
+(module
+  (import "graph" "store_set" (func $store_set (param i32 i32)))
+  (import "graph" "store_get" (func $store_get (param i32 i32) (result i32)))
+
+  (memory (export "memory") 1)
+
+  ;; A bump allocator that never frees memory
+  (global $heap (mut i32) (i32.const 1024))
+
+  (func (export "graph_abi_version") (result i32)
+    i32.const 1)
+
+  (func (export "graph_alloc") (param $size i32) (result i32)
+    (local $ptr i32)
+    global.get $heap
+    local.set $ptr
+    global.get $heap
+    local.get $size
+    i32.add
+    global.set $heap
+    local.get $ptr)
+
+  ;; handleSet(args: (entity_type, id, data))
+  (func (export "handleSet") (param $ptr i32) (param $len i32)
+    local.get $ptr
+    local.get $len
+    call $store_set)
+
+  ;; getThing(args: (entity_type, id)): Option<Entity>
+  (func (export "getThing") (param $ptr i32) (param $len i32) (result i32)
+    local.get $ptr
+    local.get $len
+    call $store_get))
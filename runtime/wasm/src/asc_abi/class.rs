@@ -820,3 +820,22 @@ pub struct AscYamlTaggedValue {
 impl AscIndexId for AscYamlTaggedValue {
     const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::YamlTaggedValue;
 }
+
+#[repr(C)]
+#[derive(AscType)]
+pub struct AscDateTimeParts {
+    pub year: i32,
+    pub month: i32,
+    pub day: i32,
+    pub hour: i32,
+    pub minute: i32,
+    pub second: i32,
+    // 1 for Monday through 7 for Sunday
+    pub weekday: i32,
+    // 1 for January 1st
+    pub day_of_year: i32,
+}
+
+impl AscIndexId for AscDateTimeParts {
+    const INDEX_ASC_TYPE_ID: IndexForAscTypeId = IndexForAscTypeId::DateTimeParts;
+}
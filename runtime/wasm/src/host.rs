@@ -1,4 +1,5 @@
 use std::cmp::PartialEq;
+use std::sync::atomic::{AtomicI32, Ordering};
 use std::time::Instant;
 
 use async_trait::async_trait;
@@ -63,6 +64,7 @@ where
 
     fn spawn_mapping(
         raw_module: &[u8],
+        source_map: Option<&[u8]>,
         logger: Logger,
         subgraph_id: DeploymentHash,
         metrics: Arc<HostMetrics>,
@@ -72,6 +74,7 @@ where
         };
         crate::mapping::spawn_module(
             raw_module,
+            source_map,
             logger,
             subgraph_id,
             metrics,
@@ -104,12 +107,18 @@ where
     }
 }
 
+/// Sentinel for `RuntimeHost::done_at` when the data source is active.
+const NOT_DONE_VALUE: i32 = -1;
+
 pub struct RuntimeHost<C: Blockchain> {
     host_fns: Arc<Vec<HostFn>>,
     data_source: DataSource<C>,
     mapping_request_sender: Sender<WasmRequest<C>>,
     host_exports: Arc<HostExports>,
     metrics: Arc<HostMetrics>,
+    /// The block at which an onchain data source was removed by its
+    /// mapping. Offchain data sources track this themselves.
+    done_at: AtomicI32,
 }
 
 impl<C> RuntimeHost<C>
@@ -150,6 +159,7 @@ where
             mapping_request_sender,
             host_exports,
             metrics,
+            done_at: AtomicI32::new(NOT_DONE_VALUE),
         })
     }
 
@@ -205,10 +215,10 @@ where
             .context("Mapping terminated before handling trigger")?;
 
         let elapsed = start_time.elapsed();
-        metrics.observe_handler_execution_time(elapsed.as_secs_f64(), &handler);
 
         // If there is an error, "gas_used" is incorrectly reported as 0.
         let gas_used = result.as_ref().map(|(_, gas)| gas).unwrap_or(&Gas::ZERO);
+        metrics.observe_handler_run(&handler, elapsed, gas_used.0);
         info!(
             logger, "Done processing trigger";
             &extras,
@@ -273,10 +283,10 @@ where
             .context("Mapping terminated before handling block")?;
 
         let elapsed = start_time.elapsed();
-        metrics.observe_handler_execution_time(elapsed.as_secs_f64(), &handler);
 
         // If there is an error, "gas_used" is incorrectly reported as 0.
         let gas_used = result.as_ref().map(|(_, gas)| gas).unwrap_or(&Gas::ZERO);
+        metrics.observe_handler_run(&handler, elapsed, gas_used.0);
         info!(
             logger, "Done processing wasm block";
             "block_ptr" => &block_ptr,
@@ -303,6 +313,11 @@ impl<C: Blockchain> RuntimeHostTrait<C> for RuntimeHost<C> {
         block: &Arc<C::Block>,
         logger: &Logger,
     ) -> Result<Option<TriggerWithHandler<MappingTrigger<C>>>, Error> {
+        // Removed onchain data sources do not match any triggers anymore.
+        if self.data_source.is_chain_based() && self.done_at().is_some() {
+            return Ok(None);
+        }
+
         self.data_source.match_and_decode(trigger, block, logger)
     }
 
@@ -357,10 +372,14 @@ impl<C: Blockchain> RuntimeHostTrait<C> for RuntimeHost<C> {
     }
 
     /// Offchain data sources track done_at which is set once the
-    /// trigger has been processed.
+    /// trigger has been processed. Onchain data sources have it set when
+    /// they are removed by a mapping.
     fn done_at(&self) -> Option<BlockNumber> {
         match self.data_source() {
-            DataSource::Onchain(_) => None,
+            DataSource::Onchain(_) => match self.done_at.load(Ordering::SeqCst) {
+                NOT_DONE_VALUE => None,
+                n => Some(n),
+            },
             DataSource::Offchain(ds) => ds.done_at(),
             DataSource::Subgraph(_) => None,
         }
@@ -368,7 +387,9 @@ impl<C: Blockchain> RuntimeHostTrait<C> for RuntimeHost<C> {
 
     fn set_done_at(&self, block: Option<BlockNumber>) {
         match self.data_source() {
-            DataSource::Onchain(_) => {}
+            DataSource::Onchain(_) => self
+                .done_at
+                .store(block.unwrap_or(NOT_DONE_VALUE), Ordering::SeqCst),
             DataSource::Offchain(ds) => ds.set_done_at(block),
             DataSource::Subgraph(_) => {}
         }
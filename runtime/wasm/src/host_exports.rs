@@ -1,27 +1,39 @@
 use std::collections::HashMap;
-use std::str::FromStr;
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
 use std::time::{Duration, Instant};
 
-use graph::data::subgraph::API_VERSION_0_0_8;
+use blake2::{Blake2b512, Blake2s256};
+use ed25519_dalek::{Signature as Ed25519Signature, VerifyingKey};
+use graph::data::subgraph::{API_VERSION_0_0_10, API_VERSION_0_0_8};
 use graph::data::value::Word;
 
 use graph::futures03::stream::StreamExt;
+use graph::schema::ast::{parse_field_as_filter, FilterOp};
 use graph::schema::EntityType;
 use never::Never;
+use ripemd::Ripemd160;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::Secp256k1;
 use semver::Version;
+use sha2::{Digest, Sha256};
 use web3::types::H160;
 
 use graph::blockchain::BlockTime;
 use graph::blockchain::Blockchain;
-use graph::components::store::{EnsLookup, GetScope, LoadRelatedRequest};
+use graph::components::store::{
+    EnsLookup, EntityFilter, FilteredEntityQuery, GetScope, LoadRelatedRequest,
+};
 use graph::components::subgraph::{
     InstanceDSTemplate, PoICausalityRegion, ProofOfIndexingEvent, SharedProofOfIndexing,
 };
-use graph::data::store::{self};
+use graph::data::store::{self, scalar::RoundingMode};
 use graph::data_source::{CausalityRegion, DataSource, EntityTypeAccess};
 use graph::ensure;
+use graph::prelude::chrono::{DateTime, NaiveDate, Utc};
 use graph::prelude::ethabi::param_type::Reader;
 use graph::prelude::ethabi::{decode, encode, Token};
+use graph::prelude::regex::{Regex, RegexBuilder};
 use graph::prelude::serde_json;
 use graph::prelude::{slog::b, slog::record_static, *};
 use graph::runtime::gas::{self, complexity, Gas, GasCounter};
@@ -64,6 +76,7 @@ pub struct DataSourceDetails {
     pub entity_type_access: EntityTypeAccess,
     pub templates: Arc<Vec<InstanceDSTemplate>>,
     pub causality_region: CausalityRegion,
+    pub creation_block: Option<BlockNumber>,
 }
 
 impl DataSourceDetails {
@@ -79,6 +92,7 @@ impl DataSourceDetails {
             entity_type_access: ds.entities(),
             templates,
             causality_region: ds.causality_region(),
+            creation_block: ds.creation_block(),
         }
     }
 }
@@ -446,6 +460,133 @@ impl HostExports {
         Ok(result)
     }
 
+    /// Find up to `first` entities of type `entity_type` that match all
+    /// conditions in `filter`, ordered by id. Keys in `filter` follow the
+    /// GraphQL `where` conventions: `amount_gt` compares the `amount`
+    /// attribute, and a plain attribute name tests for equality
+    pub(crate) fn store_query(
+        &self,
+        state: &mut BlockState,
+        entity_type: String,
+        filter: HashMap<Word, Value>,
+        first: u32,
+        gas: &GasCounter,
+    ) -> Result<Vec<Entity>, HostExportError> {
+        self.ensure_api_version_0_0_10("store.query")?;
+
+        let entity_type = state.entity_cache.schema.entity_type(&entity_type)?;
+        Self::expect_object_type(&entity_type, "query")?;
+        self.check_entity_type_access(&entity_type)?;
+
+        let max_first = ENV_VARS.mappings.store_query_max_first;
+        if first as usize > max_first {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "store.query can return at most {} entities, but {} were requested",
+                max_first,
+                first
+            )));
+        }
+
+        // Sort the conditions so that the query does not depend on the
+        // iteration order of the map
+        let mut filter: Vec<_> = filter.into_iter().collect();
+        filter.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let filters = filter
+            .into_iter()
+            .map(|(key, value)| Self::store_query_condition(&entity_type, &key, value))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let query = FilteredEntityQuery {
+            entity_type: entity_type.clone(),
+            filter: EntityFilter::And(filters),
+            first: first as usize,
+            causality_region: self.data_source.causality_region,
+        };
+
+        // Charge for running the query before running it so that handlers
+        // that have run out of gas can not cause database work
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::STORE_QUERY.with_args(complexity::Size, &query),
+            "store_query",
+        )?;
+
+        let result = state.entity_cache.query(&query)?;
+
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::STORE_GET.with_args(complexity::Size, &result),
+            "store_query",
+        )?;
+
+        state.metrics.track_entity_read_batch(&entity_type, &result);
+
+        Ok(result)
+    }
+
+    /// Turn one entry of the filter passed to `store.query` into a filter
+    /// condition. Only equality and range comparisons on attributes that
+    /// get an index by default, i.e., scalar attributes that are not
+    /// derived, are allowed. Which attributes can be used only depends on
+    /// the schema so that the same query succeeds or fails on every
+    /// indexer, regardless of which indexes have been created or dropped
+    fn store_query_condition(
+        entity_type: &EntityType,
+        key: &str,
+        value: Value,
+    ) -> Result<EntityFilter, HostExportError> {
+        let (attr, op) = match entity_type.field(key) {
+            Some(_) => (key.to_string(), FilterOp::Equal),
+            None => parse_field_as_filter(key),
+        };
+        let field = entity_type.field(&attr).ok_or_else(|| {
+            HostExportError::Deterministic(anyhow!(
+                "store.query: entity type `{}` has no attribute `{}`",
+                entity_type,
+                attr
+            ))
+        })?;
+        if field.is_list() || field.is_derived() {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "store.query: attribute `{}.{}` can not be used in a filter since it is a list \
+                 or derived attribute and therefore does not get an index",
+                entity_type,
+                attr
+            )));
+        }
+        if !value.is_assignable(&field.value_type, false) {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "store.query: value for `{}` has type {} but attribute `{}.{}` has type {}",
+                key,
+                value.type_name(),
+                entity_type,
+                attr,
+                field.value_type.to_str()
+            )));
+        }
+        if matches!(value, Value::Null) && !matches!(op, FilterOp::Equal) {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "store.query: `{}` can not be compared with null",
+                key
+            )));
+        }
+
+        match op {
+            FilterOp::Equal => Ok(EntityFilter::Equal(attr, value)),
+            FilterOp::GreaterThan => Ok(EntityFilter::GreaterThan(attr, value)),
+            FilterOp::LessThan => Ok(EntityFilter::LessThan(attr, value)),
+            FilterOp::GreaterOrEqual => Ok(EntityFilter::GreaterOrEqual(attr, value)),
+            FilterOp::LessOrEqual => Ok(EntityFilter::LessOrEqual(attr, value)),
+            _ => Err(HostExportError::Deterministic(anyhow!(
+                "store.query: filter `{}` is not supported; only equality and the `_gt`, \
+                 `_gte`, `_lt` and `_lte` comparisons can be used",
+                key
+            ))),
+        }
+    }
+
     /// Prints the module of `n` in hex.
     /// Integers are encoded using the least amount of digits (no leading zero digits).
     /// Their encoding may be of uneven length. The number zero encodes as "0x0".
@@ -654,6 +795,134 @@ impl HostExports {
         Ok(tiny_keccak::keccak256(data))
     }
 
+    /// Host functions added with apiVersion 0.0.10, like the hash and
+    /// signature functions in the `crypto` namespace other than
+    /// `keccak256`, are not available to mappings with an older apiVersion
+    fn ensure_api_version_0_0_10(&self, name: &str) -> Result<(), DeterministicHostError> {
+        if self.data_source.api_version < API_VERSION_0_0_10 {
+            return Err(DeterministicHostError::from(anyhow!(
+                "{} is not supported before API version 0.0.10",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn crypto_sha256(
+        &self,
+        input: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("crypto.sha256")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &input),
+            "crypto_sha256",
+        )?;
+        Ok(Sha256::digest(&input).to_vec())
+    }
+
+    pub(crate) fn crypto_ripemd160(
+        &self,
+        input: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("crypto.ripemd160")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &input),
+            "crypto_ripemd160",
+        )?;
+        Ok(Ripemd160::digest(&input).to_vec())
+    }
+
+    /// BLAKE2b with a 64 byte digest
+    pub(crate) fn crypto_blake2b(
+        &self,
+        input: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("crypto.blake2b")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &input),
+            "crypto_blake2b",
+        )?;
+        Ok(Blake2b512::digest(&input).to_vec())
+    }
+
+    /// BLAKE2s with a 32 byte digest
+    pub(crate) fn crypto_blake2s(
+        &self,
+        input: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("crypto.blake2s")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &input),
+            "crypto_blake2s",
+        )?;
+        Ok(Blake2s256::digest(&input).to_vec())
+    }
+
+    /// Recover the public key that produced the compact 64 byte
+    /// `signature` of the 32 byte `hash`. The `recovery_id` must be
+    /// between 0 and 3; Ethereum's `v` values 27 and 28 need to be
+    /// converted by the caller.
+    ///
+    /// Returns the 65 byte uncompressed public key, or `None` if the
+    /// inputs are malformed or no key can be recovered. The Ethereum
+    /// address is the last 20 bytes of the keccak256 hash of the key
+    /// without its leading `0x04` byte
+    pub(crate) fn crypto_secp256k1_recover(
+        &self,
+        hash: Vec<u8>,
+        signature: Vec<u8>,
+        recovery_id: u32,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Option<Vec<u8>>, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("crypto.secp256k1Recover")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::CRYPTO_SIGNATURE_OP.with_args(complexity::Size, &signature),
+            "crypto_secp256k1_recover",
+        )?;
+
+        Ok(secp256k1_recover(&hash, &signature, recovery_id))
+    }
+
+    /// Check the 64 byte ed25519 `signature` of `message` against the 32
+    /// byte `public_key`. Malformed keys and signatures never verify
+    pub(crate) fn crypto_ed25519_verify(
+        &self,
+        message: Vec<u8>,
+        signature: Vec<u8>,
+        public_key: Vec<u8>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<bool, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("crypto.ed25519Verify")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::CRYPTO_SIGNATURE_OP.with_args(complexity::Size, &message),
+            "crypto_ed25519_verify",
+        )?;
+
+        Ok(ed25519_verify(&message, &signature, &public_key))
+    }
+
     pub(crate) fn big_int_plus(
         &self,
         x: BigInt,
@@ -948,6 +1217,96 @@ impl HostExports {
         Ok(x == y)
     }
 
+    pub(crate) fn big_decimal_compare(
+        &self,
+        x: BigDecimal,
+        y: BigDecimal,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<i32, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("bigDecimal.compare")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::BIG_MATH_GAS_OP.with_args(complexity::Min, (&x, &y)),
+            "big_decimal_compare",
+        )?;
+        Ok(x.cmp(&y) as i32)
+    }
+
+    /// Intermediate results are rounded to 34 significant digits, so the
+    /// cost only grows with the number of bits in `exp`.
+    pub(crate) fn big_decimal_pow(
+        &self,
+        x: BigDecimal,
+        exp: i32,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("bigDecimal.pow")?;
+        let steps = u32::BITS - exp.unsigned_abs().leading_zeros();
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&x, &x)) * (2 * steps as u64),
+            "big_decimal_pow",
+        )?;
+        x.pow(exp).map_err(DeterministicHostError::from)
+    }
+
+    pub(crate) fn big_decimal_sqrt(
+        &self,
+        x: BigDecimal,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("bigDecimal.sqrt")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::BIG_MATH_GAS_OP.with_args(complexity::Mul, (&x, &x)),
+            "big_decimal_sqrt",
+        )?;
+        x.sqrt().map_err(DeterministicHostError::from)
+    }
+
+    pub(crate) fn big_decimal_truncate(
+        &self,
+        x: BigDecimal,
+        decimals: i32,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("bigDecimal.truncate")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::BIG_MATH_GAS_OP.with_args(complexity::Size, &x),
+            "big_decimal_truncate",
+        )?;
+        x.truncate(decimals).map_err(DeterministicHostError::from)
+    }
+
+    pub(crate) fn big_decimal_round(
+        &self,
+        x: BigDecimal,
+        decimals: i32,
+        mode: i32,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<BigDecimal, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("bigDecimal.round")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::BIG_MATH_GAS_OP.with_args(complexity::Size, &x),
+            "big_decimal_round",
+        )?;
+        let mode = RoundingMode::try_from(mode).map_err(DeterministicHostError::from)?;
+        x.round(decimals, mode)
+            .map_err(DeterministicHostError::from)
+    }
+
     pub(crate) fn big_decimal_to_string(
         &self,
         x: BigDecimal,
@@ -1033,6 +1392,41 @@ impl HostExports {
         Ok(())
     }
 
+    /// Deactivate the data source of the current handler from the current
+    /// block onwards. Only dynamic onchain data sources can be removed.
+    pub(crate) fn data_source_remove(
+        &self,
+        logger: &Logger,
+        state: &mut BlockState,
+        gas: &GasCounter,
+    ) -> Result<(), HostExportError> {
+        self.ensure_api_version_0_0_10("dataSource.remove")?;
+        Self::track_gas_and_ops(gas, state, gas::REMOVE_DATA_SOURCE, "data_source_remove")?;
+
+        if self.data_source.causality_region != CausalityRegion::ONCHAIN {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "Failed to remove data source `{}`: offchain data sources can not be removed",
+                self.data_source.name
+            )));
+        }
+        if self.data_source.creation_block.is_none() {
+            return Err(HostExportError::Deterministic(anyhow!(
+                "Failed to remove data source `{}`: data sources declared in the manifest can not be removed",
+                self.data_source.name
+            )));
+        }
+
+        info!(
+            logger,
+            "Remove data source";
+            "name" => &self.data_source.name,
+        );
+
+        state.remove_data_source();
+
+        Ok(())
+    }
+
     pub(crate) fn ens_name_by_hash(
         &self,
         hash: &str,
@@ -1179,6 +1573,108 @@ impl HostExports {
         Ok(bytes_to_string(logger, bytes))
     }
 
+    pub(crate) fn regex_is_match(
+        &self,
+        pattern: &str,
+        input: &str,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<bool, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("regex.isMatch")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::REGEX_OP.with_args(complexity::Mul, (pattern, input)),
+            "regex_is_match",
+        )?;
+        Ok(build_regex(pattern)?.is_match(input))
+    }
+
+    /// The groups of the first match of `pattern` in `input`, starting with
+    /// the whole match. Groups that did not participate in the match are
+    /// `None`.
+    pub(crate) fn regex_captures(
+        &self,
+        pattern: &str,
+        input: &str,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<Option<Vec<Option<String>>>, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("regex.captures")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::REGEX_OP.with_args(complexity::Mul, (pattern, input)),
+            "regex_captures",
+        )?;
+        let captures = build_regex(pattern)?.captures(input).map(|captures| {
+            captures
+                .iter()
+                .map(|group| group.map(|group| group.as_str().to_string()))
+                .collect()
+        });
+        Ok(captures)
+    }
+
+    /// Widths and precisions can add at most 10,000 characters to the
+    /// result, so that charging for `format` and `args` up front covers the
+    /// cost of formatting.
+    pub(crate) fn string_format(
+        &self,
+        format: &str,
+        args: Vec<String>,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<String, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("string.format")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Linear, (format, &args)),
+            "string_format",
+        )?;
+        format_string(format, &args).map_err(DeterministicHostError::from)
+    }
+
+    pub(crate) fn date_time_from_timestamp(
+        &self,
+        timestamp: BigInt,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<DateTime<Utc>, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("dateTime.fromTimestamp")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &timestamp),
+            "date_time_from_timestamp",
+        )?;
+        i64::try_from(&timestamp)
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .ok_or_else(|| anyhow!("timestamp `{}` is out of range", timestamp))
+            .map_err(DeterministicHostError::from)
+    }
+
+    pub(crate) fn date_time_parse_iso(
+        &self,
+        s: String,
+        gas: &GasCounter,
+        state: &mut BlockState,
+    ) -> Result<BigInt, DeterministicHostError> {
+        self.ensure_api_version_0_0_10("dateTime.parseIso")?;
+        Self::track_gas_and_ops(
+            gas,
+            state,
+            gas::DEFAULT_GAS_OP.with_args(complexity::Size, &s),
+            "date_time_parse_iso",
+        )?;
+        parse_iso_date_time(&s)
+            .map(BigInt::from)
+            .ok_or_else(|| anyhow!("string is not an ISO 8601 date or date and time: `{}`", s))
+            .map_err(DeterministicHostError::from)
+    }
+
     pub(crate) fn ethereum_encode(
         &self,
         token: Token,
@@ -1261,6 +1757,34 @@ fn string_to_h160(string: &str) -> Result<H160, DeterministicHostError> {
         .map_err(DeterministicHostError::from)
 }
 
+fn secp256k1_recover(hash: &[u8], signature: &[u8], recovery_id: u32) -> Option<Vec<u8>> {
+    let message = secp256k1::Message::from_slice(hash).ok()?;
+    let recovery_id = RecoveryId::from_i32(i32::try_from(recovery_id).ok()?).ok()?;
+    let signature = RecoverableSignature::from_compact(signature, recovery_id).ok()?;
+    let key = Secp256k1::verification_only()
+        .recover_ecdsa(&message, &signature)
+        .ok()?;
+    Some(key.serialize_uncompressed().to_vec())
+}
+
+fn ed25519_verify(message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+    let public_key = match <[u8; 32]>::try_from(public_key)
+        .ok()
+        .and_then(|key| VerifyingKey::from_bytes(&key).ok())
+    {
+        Some(public_key) => public_key,
+        None => return false,
+    };
+    let signature = match <[u8; 64]>::try_from(signature) {
+        Ok(signature) => Ed25519Signature::from_bytes(&signature),
+        Err(_) => return false,
+    };
+
+    // `verify_strict` rejects weak keys and malleable signatures that
+    // `verify` accepts
+    public_key.verify_strict(message, &signature).is_ok()
+}
+
 fn bytes_to_string(logger: &Logger, bytes: Vec<u8>) -> String {
     let s = String::from_utf8_lossy(&bytes);
 
@@ -1280,6 +1804,145 @@ fn bytes_to_string(logger: &Logger, bytes: Vec<u8>) -> String {
     s.trim_end_matches('\u{0000}').to_string()
 }
 
+/// Regexes use the `regex` crate, which matches in time linear in the size
+/// of the input and never backtracks. On top of that, we bound the size of
+/// the pattern and of the compiled regex.
+fn build_regex(pattern: &str) -> Result<Regex, DeterministicHostError> {
+    const MAX_PATTERN_LEN: usize = 1_000;
+    const MAX_COMPILED_SIZE: usize = 1 << 20;
+
+    if pattern.len() > MAX_PATTERN_LEN {
+        return Err(DeterministicHostError::from(anyhow!(
+            "regex is longer than {} bytes",
+            MAX_PATTERN_LEN
+        )));
+    }
+    RegexBuilder::new(pattern)
+        .size_limit(MAX_COMPILED_SIZE)
+        .dfa_size_limit(MAX_COMPILED_SIZE)
+        .build()
+        .with_context(|| format!("invalid regex `{}`", pattern))
+        .map_err(DeterministicHostError::from)
+}
+
+/// Format `args` according to the `printf`-style `format`. Supported are
+/// `%s` and `%d`, which expects an integer, with the `-` and `0` flags, a
+/// width and a precision, as well as `%%`. All `args` must be used.
+fn format_string(format: &str, args: &[String]) -> Result<String, Error> {
+    // Keeps padding from producing arbitrarily large strings; widths and
+    // precisions can not add more than this many characters in total
+    const MAX_WIDTH: usize = 10_000;
+
+    let mut padding_left = MAX_WIDTH;
+    let mut pad = |n: usize| -> Result<usize, Error> {
+        padding_left = padding_left.checked_sub(n).ok_or_else(|| {
+            anyhow!(
+                "widths and precisions add more than {} characters",
+                MAX_WIDTH
+            )
+        })?;
+        Ok(n)
+    };
+
+    fn number(chars: &mut Peekable<Chars>) -> Result<Option<usize>, Error> {
+        let mut n = None;
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            let value = n.unwrap_or(0) * 10 + digit.to_digit(10).unwrap() as usize;
+            if value > MAX_WIDTH {
+                return Err(anyhow!("width or precision is larger than {}", MAX_WIDTH));
+            }
+            n = Some(value);
+        }
+        Ok(n)
+    }
+
+    let mut out = String::with_capacity(format.len());
+    let mut args = args.iter();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        if chars.next_if_eq(&'%').is_some() {
+            out.push('%');
+            continue;
+        }
+
+        let (mut left, mut zero) = (false, false);
+        while let Some(flag) = chars.next_if(|c| *c == '-' || *c == '0') {
+            match flag {
+                '-' => left = true,
+                _ => zero = true,
+            }
+        }
+        let width = number(&mut chars)?.unwrap_or(0);
+        let precision = match chars.next_if_eq(&'.') {
+            Some(_) => Some(number(&mut chars)?.unwrap_or(0)),
+            None => None,
+        };
+        let conversion = chars.next().ok_or_else(|| {
+            anyhow!(
+                "format string `{}` ends in the middle of a conversion",
+                format
+            )
+        })?;
+        let arg = args
+            .next()
+            .ok_or_else(|| anyhow!("format string `{}` needs more arguments", format))?;
+
+        let (sign, body) = match conversion {
+            's' => match precision {
+                Some(precision) => ("", arg.chars().take(precision).collect()),
+                None => ("", arg.clone()),
+            },
+            'd' => {
+                let n = BigInt::from_str(arg)
+                    .with_context(|| format!("argument for `%d` is not an integer: `{}`", arg))?;
+                let n = n.to_string();
+                let (sign, digits) = match n.strip_prefix('-') {
+                    Some(digits) => ("-", digits),
+                    None => ("", n.as_str()),
+                };
+                let zeros = pad(precision.unwrap_or(0).saturating_sub(digits.len()))?;
+                (sign, "0".repeat(zeros) + digits)
+            }
+            c => return Err(anyhow!("unsupported conversion `%{}` in format string", c)),
+        };
+
+        let padding = pad(width.saturating_sub(sign.len() + body.chars().count()))?;
+        if left {
+            out.push_str(sign);
+            out.push_str(&body);
+            out.extend(std::iter::repeat(' ').take(padding));
+        } else if zero && conversion == 'd' {
+            out.push_str(sign);
+            out.extend(std::iter::repeat('0').take(padding));
+            out.push_str(&body);
+        } else {
+            out.extend(std::iter::repeat(' ').take(padding));
+            out.push_str(sign);
+            out.push_str(&body);
+        }
+    }
+    if args.next().is_some() {
+        return Err(anyhow!("format string `{}` needs fewer arguments", format));
+    }
+    Ok(out)
+}
+
+/// Parse an RFC 3339 date and time, or a date on its own which is taken to
+/// be midnight UTC, into seconds since the epoch
+fn parse_iso_date_time(s: &str) -> Option<i64> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(s) {
+        return Some(date_time.timestamp());
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date_time| date_time.and_utc().timestamp())
+}
+
 /// Expose some host functions for testing only
 #[cfg(debug_assertions)]
 pub mod test_support {
@@ -1350,6 +2013,40 @@ pub mod test_support {
         }
     }
 }
+#[test]
+fn store_query_condition_needs_default_index() {
+    use graph::schema::InputSchema;
+
+    const SCHEMA: &str = "
+        type Wallet @entity {
+            id: ID!
+            balance: BigInt!
+            tags: [String!]!
+            owner: Owner!
+        }
+
+        type Owner @entity {
+            id: ID!
+            wallets: [Wallet!]! @derivedFrom(field: \"owner\")
+        }";
+
+    let id = DeploymentHash::new("storeQuery").unwrap();
+    let schema = InputSchema::parse_latest(SCHEMA, id).unwrap();
+    let wallet = schema.entity_type("Wallet").unwrap();
+    let owner = schema.entity_type("Owner").unwrap();
+
+    let condition = |entity_type: &EntityType, key: &str, value: Value| {
+        HostExports::store_query_condition(entity_type, key, value)
+    };
+    assert!(condition(&wallet, "balance_gt", Value::BigInt(BigInt::from(10))).is_ok());
+    assert!(condition(&wallet, "owner", Value::from("o1")).is_ok());
+
+    let err = condition(&wallet, "tags", Value::List(vec![])).unwrap_err();
+    assert!(matches!(err, HostExportError::Deterministic(_)));
+    let err = condition(&owner, "wallets", Value::List(vec![])).unwrap_err();
+    assert!(matches!(err, HostExportError::Deterministic(_)));
+}
+
 #[test]
 fn test_string_to_h160_with_0x() {
     assert_eq!(
@@ -1379,3 +2076,87 @@ fn bytes_to_string_is_lossy() {
         )
     )
 }
+
+#[test]
+fn secp256k1_recover_returns_signing_key() {
+    use secp256k1::{PublicKey, SecretKey};
+
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+    let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+    let hash = tiny_keccak::keccak256(b"graph");
+    let message = secp256k1::Message::from_slice(&hash).unwrap();
+    let (recovery_id, signature) = secp
+        .sign_ecdsa_recoverable(&message, &secret_key)
+        .serialize_compact();
+    let recovery_id = recovery_id.to_i32() as u32;
+
+    assert_eq!(
+        Some(public_key.serialize_uncompressed().to_vec()),
+        secp256k1_recover(&hash, &signature, recovery_id)
+    );
+
+    // Malformed inputs recover nothing instead of failing
+    assert_eq!(None, secp256k1_recover(&hash[1..], &signature, recovery_id));
+    assert_eq!(None, secp256k1_recover(&hash, &signature[1..], recovery_id));
+    assert_eq!(None, secp256k1_recover(&hash, &signature, 4));
+}
+
+#[test]
+fn ed25519_verify_checks_signature() {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let public_key = signing_key.verifying_key().to_bytes();
+    let signature = signing_key.sign(b"graph").to_bytes();
+
+    assert!(ed25519_verify(b"graph", &signature, &public_key));
+    assert!(!ed25519_verify(b"graph!", &signature, &public_key));
+    assert!(!ed25519_verify(b"graph", &signature[1..], &public_key));
+    assert!(!ed25519_verify(b"graph", &signature, &public_key[1..]));
+}
+
+#[test]
+fn regex_is_bounded() {
+    assert!(build_regex(r"^(\w+)\.eth$")
+        .unwrap()
+        .is_match("vitalik.eth"));
+    assert!(build_regex("(").is_err());
+    assert!(build_regex(&"a".repeat(1001)).is_err());
+    // Compiles to more than the size limit
+    assert!(build_regex("(?:a{1000}){1000}").is_err());
+}
+
+#[test]
+fn format_string_pads_and_truncates() {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(
+        "token 42 of [ab   ]: 100%",
+        format_string("token %s of [%-5.2s]: 100%%", &args(&["42", "abc"])).unwrap()
+    );
+    assert_eq!(
+        "-0042|  -42|-42  |00042",
+        format_string("%05d|%5d|%-5d|%.5d", &args(&["-42", "-42", "-42", "42"])).unwrap()
+    );
+    assert!(format_string("%d", &args(&["4.2"])).is_err());
+    assert!(format_string("%s %s", &args(&["a"])).is_err());
+    assert!(format_string("%s", &args(&["a", "b"])).is_err());
+    assert!(format_string("%x", &args(&["a"])).is_err());
+    assert!(format_string("%100000s", &args(&["a"])).is_err());
+    assert!(format_string("%6000s%.6000d", &args(&["a", "1"])).is_err());
+}
+
+#[test]
+fn parse_iso_date_time_accepts_dates() {
+    assert_eq!(
+        Some(1_700_000_000),
+        parse_iso_date_time("2023-11-14T22:13:20Z")
+    );
+    assert_eq!(
+        Some(1_700_000_000),
+        parse_iso_date_time("2023-11-15T00:13:20+02:00")
+    );
+    assert_eq!(Some(1_699_920_000), parse_iso_date_time("2023-11-14"));
+    assert_eq!(None, parse_iso_date_time("14/11/2023"));
+}
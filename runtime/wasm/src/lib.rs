@@ -17,6 +17,12 @@ pub mod host_exports;
 pub mod error;
 mod gas_rules;
 
+/// Language-neutral ABI for mappings not written in AssemblyScript.
+pub mod native_abi;
+
+/// Source maps for symbolizing traps in mappings.
+pub mod source_map;
+
 pub use host::RuntimeHostBuilder;
 pub use host_exports::HostExports;
 pub use mapping::{MappingContext, ValidModule};
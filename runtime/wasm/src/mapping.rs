@@ -1,5 +1,7 @@
 use crate::gas_rules::GasRules;
 use crate::module::{ExperimentalFeatures, ToAscPtr, WasmInstance};
+use crate::native_abi::{self, MappingAbi};
+use crate::source_map::{ModuleSourceMap, SourceMap};
 use graph::blockchain::{BlockTime, Blockchain, HostFn};
 use graph::components::store::SubgraphFork;
 use graph::components::subgraph::{MappingError, SharedProofOfIndexing};
@@ -9,7 +11,7 @@ use graph::futures01::{Future as _, Stream as _};
 use graph::futures03::channel::oneshot::Sender;
 use graph::prelude::*;
 use graph::runtime::gas::Gas;
-use parity_wasm::elements::ExportEntry;
+use parity_wasm::elements::{ExportEntry, ImportCountType};
 use std::collections::BTreeMap;
 use std::panic::AssertUnwindSafe;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -19,6 +21,7 @@ use std::{panic, thread};
 /// Spawn a wasm module in its own thread.
 pub fn spawn_module<C: Blockchain>(
     raw_module: &[u8],
+    source_map: Option<&[u8]>,
     logger: Logger,
     subgraph_id: DeploymentHash,
     host_metrics: Arc<HostMetrics>,
@@ -31,7 +34,7 @@ where
 {
     static THREAD_COUNT: AtomicUsize = AtomicUsize::new(0);
 
-    let valid_module = Arc::new(ValidModule::new(&logger, raw_module, timeout)?);
+    let valid_module = Arc::new(ValidModule::new(&logger, raw_module, source_map, timeout)?);
 
     // Create channel for event handling requests
     let (mapping_request_sender, mapping_request_receiver) = mpsc::channel(100);
@@ -253,6 +256,14 @@ pub struct ValidModule {
     // The timeout for the module.
     pub timeout: Option<Duration>,
 
+    // Whether the module was compiled from AssemblyScript or uses the
+    // language-neutral native ABI, see `native_abi`.
+    pub abi: MappingAbi,
+
+    // Translates trap locations to the mapping's source if a source map was
+    // published alongside the mapping file.
+    pub source_map: Option<ModuleSourceMap>,
+
     // Used as a guard to terminate this task dependency.
     epoch_counter_abort_handle: Option<tokio::task::AbortHandle>,
 }
@@ -262,6 +273,7 @@ impl ValidModule {
     pub fn new(
         logger: &Logger,
         raw_module: &[u8],
+        source_map: Option<&[u8]>,
         timeout: Option<Duration>,
     ) -> Result<Self, anyhow::Error> {
         // Add the gas calls here. Module name "gas" must match. See also
@@ -299,9 +311,26 @@ impl ValidModule {
 
             name
         });
+        // Gas metering imports its function after all other imported functions
+        let gas_func = parity_module.import_count(ImportCountType::Function) as u32;
         let parity_module = wasm_instrument::gas_metering::inject(parity_module, &GasRules, "gas")
             .map_err(|_| anyhow!("Failed to inject gas counter"))?;
-        let raw_module = parity_module.into_bytes()?;
+        let instrumented_module = parity_module.into_bytes()?;
+
+        // A broken source map only makes error messages less helpful, so we
+        // don't fail the module over it
+        let source_map = source_map.and_then(|source_map| match SourceMap::parse(source_map) {
+            Ok(source_map) => Some(ModuleSourceMap::new(
+                source_map,
+                raw_module,
+                &instrumented_module,
+                gas_func,
+            )),
+            Err(e) => {
+                warn!(logger, "Ignoring invalid source map"; "error" => format!("{:#}", e));
+                None
+            }
+        });
 
         // We currently use Cranelift as a compilation engine. Cranelift is an optimizing compiler,
         // but that should not cause determinism issues since it adheres to the Wasm spec. Still we
@@ -314,7 +343,7 @@ impl ValidModule {
         config.max_wasm_stack(ENV_VARS.mappings.max_stack_size);
 
         let engine = &wasmtime::Engine::new(&config)?;
-        let module = wasmtime::Module::from_binary(engine, &raw_module)?;
+        let module = wasmtime::Module::from_binary(engine, &instrumented_module)?;
 
         let mut import_name_to_modules: BTreeMap<String, Vec<String>> = BTreeMap::new();
 
@@ -329,6 +358,29 @@ impl ValidModule {
                 .push(module.to_string());
         }
 
+        let abi = if module
+            .exports()
+            .any(|export| export.name() == native_abi::ABI_VERSION_EXPORT)
+        {
+            // The gas module is the one we injected above. Chain-specific
+            // host fns only work with AssemblyScript objects, and native
+            // modules can therefore not import them
+            if let Some(import) = module.imports().find(|import| {
+                import.module() != native_abi::IMPORT_MODULE && import.module() != "gas"
+            }) {
+                return Err(anyhow!(
+                    "modules using the native ABI can only import from the `{}` module, \
+                     but this module imports `{}` from `{}`",
+                    native_abi::IMPORT_MODULE,
+                    import.name(),
+                    import.module()
+                ));
+            }
+            MappingAbi::Native
+        } else {
+            MappingAbi::AssemblyScript
+        };
+
         let mut epoch_counter_abort_handle = None;
         if let Some(timeout) = timeout {
             let timeout = timeout.clone();
@@ -352,9 +404,19 @@ impl ValidModule {
             import_name_to_modules,
             start_function,
             timeout,
+            abi,
+            source_map,
             epoch_counter_abort_handle,
         })
     }
+
+    /// Translate the wasm backtrace of a trap into locations in the mapping's
+    /// source. Returns `None` if there is no source map for this module or
+    /// it does not cover any of the frames.
+    pub fn symbolize(&self, trap: &Error) -> Option<String> {
+        let backtrace = trap.downcast_ref::<wasmtime::WasmBacktrace>()?;
+        self.source_map.as_ref()?.symbolize(backtrace)
+    }
 }
 
 impl Drop for ValidModule {
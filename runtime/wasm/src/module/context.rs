@@ -28,6 +28,7 @@ use graph::runtime::{asc_new, gas::GasCounter, DeterministicHostError, HostExpor
 
 use super::asc_get;
 use super::AscHeapCtx;
+use super::NativeHeapCtx;
 
 pub(crate) struct WasmInstanceContext<'a> {
     inner: StoreContextMut<'a, WasmInstanceData>,
@@ -56,6 +57,10 @@ impl WasmInstanceContext<'_> {
         self.as_mut().asc_heap_mut()
     }
 
+    pub fn native_heap_ref(&self) -> &NativeHeapCtx {
+        self.as_ref().native_heap_ref()
+    }
+
     pub fn suspend_timeout(&mut self) {
         // See also: runtime-timeouts
         self.inner.set_epoch_deadline(u64::MAX);
@@ -97,6 +102,10 @@ pub struct WasmInstanceData {
     // This option is needed to break the cyclic dependency between, instance, store, and context.
     // during execution it should always be populated.
     asc_heap: Option<AscHeapCtx>,
+
+    // Same as `asc_heap`, for modules that use the native ABI. Exactly one of
+    // the two is populated during execution.
+    native_heap: Option<NativeHeapCtx>,
 }
 
 impl WasmInstanceData {
@@ -108,6 +117,7 @@ impl WasmInstanceData {
     ) -> Self {
         WasmInstanceData {
             asc_heap: None,
+            native_heap: None,
             ctx,
             valid_module,
             host_metrics,
@@ -128,6 +138,14 @@ impl WasmInstanceData {
         self.asc_heap.as_mut().unwrap()
     }
 
+    pub fn set_native_heap(&mut self, native_heap: NativeHeapCtx) {
+        self.native_heap = Some(native_heap);
+    }
+
+    pub fn native_heap_ref(&self) -> &NativeHeapCtx {
+        self.native_heap.as_ref().unwrap()
+    }
+
     pub fn take_state(mut self) -> BlockState {
         let state = &mut self.ctx.state;
 
@@ -155,6 +173,7 @@ impl WasmInstanceContext<'_> {
 
         let entity_type: String = asc_get(self, entity_ptr, gas)?;
         let id: String = asc_get(self, id_ptr, gas)?;
+        let store_reads = self.as_ref().ctx.state.entity_cache.store_reads;
         let entity_option = host_exports.store_get(
             &mut self.as_mut().ctx.state,
             entity_type.clone(),
@@ -162,6 +181,8 @@ impl WasmInstanceContext<'_> {
             gas,
             scope,
         )?;
+        let cache_miss = self.as_ref().ctx.state.entity_cache.store_reads > store_reads;
+        self.as_ref().host_metrics.track_store_get(cache_miss);
 
         if self.as_ref().ctx.instrument {
             debug!(self.as_ref().ctx.logger, "store_get";
@@ -363,6 +384,31 @@ impl WasmInstanceContext<'_> {
         Ok(ret)
     }
 
+    /// function store.query(entity_type: string, filter: Entity, first: u32): Array<Entity>
+    pub fn store_query(
+        &mut self,
+        gas: &GasCounter,
+        entity_type_ptr: AscPtr<AscString>,
+        filter_ptr: AscPtr<AscEntity>,
+        first: u32,
+    ) -> Result<AscPtr<Array<AscPtr<AscEntity>>>, HostExportError> {
+        let entity_type: String = asc_get(self, entity_type_ptr, gas)?;
+        let filter = asc_get(self, filter_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let entities = host_exports.store_query(
+            &mut self.as_mut().ctx.state,
+            entity_type,
+            filter,
+            first,
+            gas,
+        )?;
+
+        let entities: Vec<Vec<(Word, Value)>> =
+            entities.into_iter().map(|entity| entity.sorted()).collect();
+        let ret = asc_new(self, &entities, gas)?;
+        Ok(ret)
+    }
+
     /// function typeConversion.bytesToString(bytes: Bytes): string
     pub fn bytes_to_string(
         &mut self,
@@ -458,6 +504,81 @@ impl WasmInstanceContext<'_> {
         asc_new(self, &h160, gas)
     }
 
+    /// function regex.isMatch(pattern: string, input: string): bool
+    pub fn regex_is_match(
+        &mut self,
+        gas: &GasCounter,
+        pattern_ptr: AscPtr<AscString>,
+        input_ptr: AscPtr<AscString>,
+    ) -> Result<bool, HostExportError> {
+        let pattern: String = asc_get(self, pattern_ptr, gas)?;
+        let input: String = asc_get(self, input_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let is_match = host_exports.regex_is_match(&pattern, &input, gas, &mut ctx.state)?;
+        Ok(is_match)
+    }
+
+    /// function regex.captures(pattern: string, input: string): Array<string | null> | null
+    pub fn regex_captures(
+        &mut self,
+        gas: &GasCounter,
+        pattern_ptr: AscPtr<AscString>,
+        input_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<Array<AscPtr<AscString>>>, HostExportError> {
+        let pattern: String = asc_get(self, pattern_ptr, gas)?;
+        let input: String = asc_get(self, input_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let captures = host_exports.regex_captures(&pattern, &input, gas, &mut ctx.state)?;
+
+        // map `None` to `null`
+        captures
+            .map(|captures| asc_new(self, &captures, gas))
+            .unwrap_or(Ok(AscPtr::null()))
+    }
+
+    /// function string.format(format: string, args: Array<string>): string
+    pub fn string_format(
+        &mut self,
+        gas: &GasCounter,
+        format_ptr: AscPtr<AscString>,
+        args_ptr: AscPtr<Array<AscPtr<AscString>>>,
+    ) -> Result<AscPtr<AscString>, HostExportError> {
+        let format: String = asc_get(self, format_ptr, gas)?;
+        let args: Vec<String> = asc_get(self, args_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let formatted = host_exports.string_format(&format, args, gas, &mut ctx.state)?;
+        asc_new(self, &formatted, gas)
+    }
+
+    /// function dateTime.fromTimestamp(timestamp: BigInt): DateTimeParts
+    pub fn date_time_from_timestamp(
+        &mut self,
+        gas: &GasCounter,
+        timestamp_ptr: AscPtr<AscBigInt>,
+    ) -> Result<AscPtr<AscDateTimeParts>, HostExportError> {
+        let timestamp = asc_get(self, timestamp_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let date_time = host_exports.date_time_from_timestamp(timestamp, gas, &mut ctx.state)?;
+        asc_new(self, &date_time, gas)
+    }
+
+    /// function dateTime.parseIso(s: string): BigInt
+    pub fn date_time_parse_iso(
+        &mut self,
+        gas: &GasCounter,
+        string_ptr: AscPtr<AscString>,
+    ) -> Result<AscPtr<AscBigInt>, HostExportError> {
+        let s: String = asc_get(self, string_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let timestamp = host_exports.date_time_parse_iso(s, gas, &mut ctx.state)?;
+        asc_new(self, &timestamp, gas)
+    }
+
     /// function json.fromBytes(bytes: Bytes): JSONValue
     pub fn json_from_bytes(
         &mut self,
@@ -704,6 +825,107 @@ impl WasmInstanceContext<'_> {
         asc_new(self, input.as_ref(), gas)
     }
 
+    /// function crypto.sha256(input: Bytes): Bytes
+    pub fn crypto_sha256(
+        &mut self,
+        gas: &GasCounter,
+        input_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let input = asc_get(self, input_ptr, gas)?;
+        let ctx = &mut self.as_mut().ctx;
+
+        let hash = host_exports.crypto_sha256(input, gas, &mut ctx.state)?;
+        asc_new(self, hash.as_slice(), gas)
+    }
+
+    /// function crypto.ripemd160(input: Bytes): Bytes
+    pub fn crypto_ripemd160(
+        &mut self,
+        gas: &GasCounter,
+        input_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let input = asc_get(self, input_ptr, gas)?;
+        let ctx = &mut self.as_mut().ctx;
+
+        let hash = host_exports.crypto_ripemd160(input, gas, &mut ctx.state)?;
+        asc_new(self, hash.as_slice(), gas)
+    }
+
+    /// function crypto.blake2b(input: Bytes): Bytes
+    pub fn crypto_blake2b(
+        &mut self,
+        gas: &GasCounter,
+        input_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let input = asc_get(self, input_ptr, gas)?;
+        let ctx = &mut self.as_mut().ctx;
+
+        let hash = host_exports.crypto_blake2b(input, gas, &mut ctx.state)?;
+        asc_new(self, hash.as_slice(), gas)
+    }
+
+    /// function crypto.blake2s(input: Bytes): Bytes
+    pub fn crypto_blake2s(
+        &mut self,
+        gas: &GasCounter,
+        input_ptr: AscPtr<Uint8Array>,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let input = asc_get(self, input_ptr, gas)?;
+        let ctx = &mut self.as_mut().ctx;
+
+        let hash = host_exports.crypto_blake2s(input, gas, &mut ctx.state)?;
+        asc_new(self, hash.as_slice(), gas)
+    }
+
+    /// function crypto.secp256k1Recover(hash: Bytes, signature: Bytes, recoveryId: u32): Bytes | null
+    pub fn crypto_secp256k1_recover(
+        &mut self,
+        gas: &GasCounter,
+        hash_ptr: AscPtr<Uint8Array>,
+        signature_ptr: AscPtr<Uint8Array>,
+        recovery_id: u32,
+    ) -> Result<AscPtr<Uint8Array>, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let hash = asc_get(self, hash_ptr, gas)?;
+        let signature = asc_get(self, signature_ptr, gas)?;
+        let ctx = &mut self.as_mut().ctx;
+
+        let key = host_exports.crypto_secp256k1_recover(
+            hash,
+            signature,
+            recovery_id,
+            gas,
+            &mut ctx.state,
+        )?;
+        match key {
+            Some(key) => asc_new(self, key.as_slice(), gas),
+            None => Ok(AscPtr::null()),
+        }
+    }
+
+    /// function crypto.ed25519Verify(message: Bytes, signature: Bytes, publicKey: Bytes): bool
+    pub fn crypto_ed25519_verify(
+        &mut self,
+        gas: &GasCounter,
+        message_ptr: AscPtr<Uint8Array>,
+        signature_ptr: AscPtr<Uint8Array>,
+        public_key_ptr: AscPtr<Uint8Array>,
+    ) -> Result<bool, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let message = asc_get(self, message_ptr, gas)?;
+        let signature = asc_get(self, signature_ptr, gas)?;
+        let public_key = asc_get(self, public_key_ptr, gas)?;
+        let ctx = &mut self.as_mut().ctx;
+
+        host_exports
+            .crypto_ed25519_verify(message, signature, public_key, gas, &mut ctx.state)
+            .map_err(HostExportError::from)
+    }
+
     /// function bigInt.plus(x: BigInt, y: BigInt): BigInt
     pub fn big_int_plus(
         &mut self,
@@ -1012,6 +1234,84 @@ impl WasmInstanceContext<'_> {
         host_exports.big_decimal_equals(x, y, gas, &mut ctx.state)
     }
 
+    /// function bigDecimal.compare(x: BigDecimal, y: BigDecimal): i32
+    pub fn big_decimal_compare(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigDecimal>,
+        y_ptr: AscPtr<AscBigDecimal>,
+    ) -> Result<i32, HostExportError> {
+        let x = asc_get(self, x_ptr, gas)?;
+        let y = asc_get(self, y_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+
+        Ok(host_exports.big_decimal_compare(x, y, gas, &mut ctx.state)?)
+    }
+
+    /// function bigDecimal.pow(x: BigDecimal, exp: i32): BigDecimal
+    pub fn big_decimal_pow(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigDecimal>,
+        exp: u32,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        // Wasm passes the `i32` as its bit pattern.
+        let exp = exp as i32;
+        let x = asc_get(self, x_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let result = host_exports.big_decimal_pow(x, exp, gas, &mut ctx.state)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.sqrt(x: BigDecimal): BigDecimal
+    pub fn big_decimal_sqrt(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigDecimal>,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        let x = asc_get(self, x_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let result = host_exports.big_decimal_sqrt(x, gas, &mut ctx.state)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.truncate(x: BigDecimal, decimals: i32): BigDecimal
+    pub fn big_decimal_truncate(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigDecimal>,
+        decimals: u32,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        // Wasm passes the `i32` as its bit pattern.
+        let decimals = decimals as i32;
+        let x = asc_get(self, x_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let result = host_exports.big_decimal_truncate(x, decimals, gas, &mut ctx.state)?;
+        asc_new(self, &result, gas)
+    }
+
+    /// function bigDecimal.round(x: BigDecimal, decimals: i32, mode: i32): BigDecimal
+    pub fn big_decimal_round(
+        &mut self,
+        gas: &GasCounter,
+        x_ptr: AscPtr<AscBigDecimal>,
+        decimals: u32,
+        mode: u32,
+    ) -> Result<AscPtr<AscBigDecimal>, HostExportError> {
+        // Wasm passes the `i32` as its bit pattern.
+        let decimals = decimals as i32;
+        let x = asc_get(self, x_ptr, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let result =
+            host_exports.big_decimal_round(x, decimals, mode as i32, gas, &mut ctx.state)?;
+        asc_new(self, &result, gas)
+    }
+
     /// function dataSource.create(name: string, params: Array<string>): void
     pub fn data_source_create(
         &mut self,
@@ -1062,6 +1362,13 @@ impl WasmInstanceContext<'_> {
         )
     }
 
+    /// function dataSource.remove(): void
+    pub fn data_source_remove(&mut self, gas: &GasCounter) -> Result<(), HostExportError> {
+        let logger = self.as_ref().ctx.logger.cheap_clone();
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        host_exports.data_source_remove(&logger, &mut self.as_mut().ctx.state, gas)
+    }
+
     /// function dataSource.address(): Bytes
     pub fn data_source_address(
         &mut self,
@@ -25,9 +25,10 @@ use crate::error::DeterminismLevel;
 use crate::mapping::MappingContext;
 use crate::mapping::ValidModule;
 use crate::module::WasmInstanceData;
+use crate::native_abi::MappingAbi;
 use crate::ExperimentalFeatures;
 
-use super::{is_trap_deterministic, AscHeapCtx, ToAscPtr};
+use super::{is_trap_deterministic, AscHeapCtx, NativeHeapCtx, ToAscPtr};
 
 /// Handle to a WASM instance, which is terminated if and only if this is dropped.
 pub struct WasmInstance {
@@ -113,11 +114,18 @@ impl WasmInstance {
     ) -> Result<(BlockState, Gas), MappingError> {
         let gas = self.gas.clone();
         let mut ctx = self.instance_ctx();
+
+        if ctx.as_ref().valid_module.abi == MappingAbi::Native {
+            let len = block_data.len() as u32;
+            let ptr = ctx.native_write(&block_data, &gas)?;
+            return self.invoke_handler(handler_name, (ptr, len), Arc::new(o!()), None);
+        }
+
         let obj = block_data.to_vec().to_asc_obj(&mut ctx, &gas)?;
 
         let obj = AscPtr::alloc_obj(obj, &mut ctx, &gas)?;
 
-        self.invoke_handler(handler_name, obj, Arc::new(o!()), None)
+        self.invoke_handler(handler_name, obj.wasm_ptr(), Arc::new(o!()), None)
     }
 
     pub(crate) fn handle_trigger<C: Blockchain>(
@@ -132,9 +140,22 @@ impl WasmInstance {
         let logging_extras = trigger.logging_extras().cheap_clone();
         let error_context = trigger.trigger.error_context();
         let mut ctx = self.instance_ctx();
+
+        if ctx.as_ref().valid_module.abi == MappingAbi::Native {
+            let bytes = trigger.to_native_bytes()?;
+            let len = bytes.len() as u32;
+            let ptr = ctx.native_write(&bytes, &gas)?;
+            return self.invoke_handler(&handler_name, (ptr, len), logging_extras, error_context);
+        }
+
         let asc_trigger = trigger.to_asc_ptr(&mut ctx, &gas)?;
 
-        self.invoke_handler(&handler_name, asc_trigger, logging_extras, error_context)
+        self.invoke_handler(
+            &handler_name,
+            asc_trigger.wasm_ptr(),
+            logging_extras,
+            error_context,
+        )
     }
 
     pub fn take_ctx(self) -> WasmInstanceData {
@@ -157,10 +178,12 @@ impl WasmInstance {
         self.gas.get().value()
     }
 
-    fn invoke_handler<T>(
+    /// Call `handler` with `handler_args`, which is a pointer to the trigger for
+    /// AssemblyScript mappings and a `(ptr, len)` pair for native mappings
+    fn invoke_handler<P: wasmtime::WasmParams>(
         mut self,
         handler: &str,
-        arg: AscPtr<T>,
+        handler_args: P,
         logging_extras: Arc<dyn SendSyncRefUnwindSafeKV>,
         error_context: Option<String>,
     ) -> Result<(BlockState, Gas), MappingError> {
@@ -170,7 +193,7 @@ impl WasmInstance {
             .with_context(|| format!("function {} not found", handler))?;
 
         let func = func
-            .typed(self.store.as_context_mut())
+            .typed::<P, ()>(self.store.as_context_mut())
             .context("wasm function has incorrect signature")?;
 
         // Caution: Make sure all exit paths from this function call `exit_handler`.
@@ -178,7 +201,7 @@ impl WasmInstance {
 
         // This `match` will return early if there was a non-deterministic trap.
         let deterministic_error: Option<Error> =
-            match func.call(self.store.as_context_mut(), arg.wasm_ptr()) {
+            match func.call(self.store.as_context_mut(), handler_args) {
                 Ok(()) => {
                     assert!(self.instance_ctx().as_ref().possible_reorg == false);
                     assert!(self.instance_ctx().as_ref().deterministic_host_trap == false);
@@ -218,11 +241,20 @@ impl WasmInstance {
             };
 
         if let Some(deterministic_error) = deterministic_error {
+            let mapping_backtrace = self
+                .instance_ctx()
+                .as_ref()
+                .valid_module
+                .symbolize(&deterministic_error);
             let deterministic_error = match error_context {
                 Some(error_context) => deterministic_error.context(error_context),
                 None => deterministic_error,
             };
-            let message = format!("{:#}", deterministic_error).replace('\n', "\t");
+            let mut message = format!("{:#}", deterministic_error);
+            if let Some(mapping_backtrace) = mapping_backtrace {
+                message = format!("{}\n{}", message, mapping_backtrace);
+            }
+            let message = message.replace('\n', "\t");
 
             // Log the error and restore the updates snapshot, effectively reverting the handler.
             error!(&self.instance_ctx().as_ref().ctx.logger,
@@ -268,7 +300,11 @@ impl WasmInstance {
     ) -> Result<WasmInstance, anyhow::Error> {
         let engine = valid_module.module.engine();
         let mut linker: Linker<WasmInstanceData> = wasmtime::Linker::new(engine);
-        let host_fns = ctx.host_fns.cheap_clone();
+        // Chain-specific host fns only know how to talk to AssemblyScript
+        let host_fns = match valid_module.abi {
+            MappingAbi::AssemblyScript => ctx.host_fns.cheap_clone(),
+            MappingAbi::Native => Arc::new(vec![]),
+        };
         let api_version = ctx.host_exports.data_source.api_version.clone();
 
         let wasm_ctx = WasmInstanceData::from_instance(
@@ -294,17 +330,17 @@ impl WasmInstance {
         let gas = GasCounter::new(host_metrics.gas_metrics.clone());
         let deterministic_host_trap = Arc::new(AtomicBool::new(false));
 
+        // `link!` links host fns for AssemblyScript modules, `link_native!`
+        // for modules that use the native ABI. Only the host fns for the
+        // module's ABI get linked.
         macro_rules! link {
-            ($wasm_name:expr, $rust_name:ident, $($param:ident),*) => {
-                link!($wasm_name, $rust_name, "host_export_other",$($param),*)
-            };
-
-            ($wasm_name:expr, $rust_name:ident, $section:expr, $($param:ident),*) => {
+            (@abi $abi:expr, $wasm_name:expr, $rust_name:ident, $section:expr, $($param:ident),*) => {
                 let modules = valid_module
                     .import_name_to_modules
                     .get($wasm_name)
                     .into_iter()
-                    .flatten();
+                    .flatten()
+                    .filter(|_| valid_module.abi == $abi);
 
                 // link an import with all the modules that require it.
                 for module in modules {
@@ -344,6 +380,24 @@ impl WasmInstance {
                     )?;
                 }
             };
+
+            ($wasm_name:expr, $rust_name:ident, $($param:ident),*) => {
+                link!($wasm_name, $rust_name, "host_export_other",$($param),*)
+            };
+
+            ($wasm_name:expr, $rust_name:ident, $section:expr, $($param:ident),*) => {
+                link!(@abi MappingAbi::AssemblyScript, $wasm_name, $rust_name, $section, $($param),*)
+            };
+        }
+
+        macro_rules! link_native {
+            ($wasm_name:expr, $rust_name:ident, $($param:ident),*) => {
+                link_native!($wasm_name, $rust_name, "host_export_other",$($param),*)
+            };
+
+            ($wasm_name:expr, $rust_name:ident, $section:expr, $($param:ident),*) => {
+                link!(@abi MappingAbi::Native, $wasm_name, $rust_name, $section, $($param),*)
+            };
         }
 
         // Link chain-specifc host fns.
@@ -411,6 +465,14 @@ impl WasmInstance {
             id,
             field
         );
+        link!(
+            "store.query",
+            store_query,
+            "host_export_store_query",
+            entity,
+            filter,
+            first
+        );
         link!(
             "store.get_in_block",
             store_get_in_block,
@@ -461,6 +523,14 @@ impl WasmInstance {
         link!("typeConversion.stringToH160", string_to_h160, ptr);
         link!("typeConversion.bytesToBase58", bytes_to_base58, ptr);
 
+        link!("regex.isMatch", regex_is_match, pattern_ptr, input_ptr);
+        link!("regex.captures", regex_captures, pattern_ptr, input_ptr);
+
+        link!("string.format", string_format, format_ptr, args_ptr);
+
+        link!("dateTime.fromTimestamp", date_time_from_timestamp, ptr);
+        link!("dateTime.parseIso", date_time_parse_iso, ptr);
+
         link!("json.fromBytes", json_from_bytes, ptr);
         link!("json.try_fromBytes", json_try_from_bytes, ptr);
         link!("json.toI64", json_to_i64, ptr);
@@ -472,6 +542,24 @@ impl WasmInstance {
         link!("yaml.try_fromBytes", yaml_try_from_bytes, ptr);
 
         link!("crypto.keccak256", crypto_keccak_256, ptr);
+        link!("crypto.sha256", crypto_sha256, ptr);
+        link!("crypto.ripemd160", crypto_ripemd160, ptr);
+        link!("crypto.blake2b", crypto_blake2b, ptr);
+        link!("crypto.blake2s", crypto_blake2s, ptr);
+        link!(
+            "crypto.secp256k1Recover",
+            crypto_secp256k1_recover,
+            hash_ptr,
+            signature_ptr,
+            recovery_id
+        );
+        link!(
+            "crypto.ed25519Verify",
+            crypto_ed25519_verify,
+            message_ptr,
+            signature_ptr,
+            public_key_ptr
+        );
 
         link!("bigInt.plus", big_int_plus, x_ptr, y_ptr);
         link!("bigInt.minus", big_int_minus, x_ptr, y_ptr);
@@ -493,6 +581,11 @@ impl WasmInstance {
         link!("bigDecimal.times", big_decimal_times, x_ptr, y_ptr);
         link!("bigDecimal.dividedBy", big_decimal_divided_by, x, y);
         link!("bigDecimal.equals", big_decimal_equals, x_ptr, y_ptr);
+        link!("bigDecimal.compare", big_decimal_compare, x_ptr, y_ptr);
+        link!("bigDecimal.pow", big_decimal_pow, x_ptr, exp);
+        link!("bigDecimal.sqrt", big_decimal_sqrt, x_ptr);
+        link!("bigDecimal.truncate", big_decimal_truncate, x_ptr, decimals);
+        link!("bigDecimal.round", big_decimal_round, x_ptr, decimals, mode);
 
         link!("dataSource.create", data_source_create, name, params);
         link!(
@@ -502,6 +595,7 @@ impl WasmInstance {
             params,
             context
         );
+        link!("dataSource.remove", data_source_remove,);
         link!("dataSource.address", data_source_address,);
         link!("dataSource.network", data_source_network,);
         link!("dataSource.context", data_source_context,);
@@ -516,6 +610,48 @@ impl WasmInstance {
             link!("box.profile", box_profile, ptr);
         }
 
+        // Host fns for the native ABI, see `native_abi`
+        link_native!("abort", native_abort, args_ptr, args_len);
+        link_native!("log", native_log, args_ptr, args_len);
+        link_native!(
+            "store_get",
+            native_store_get,
+            "host_export_store_get",
+            args_ptr,
+            args_len
+        );
+        link_native!(
+            "store_get_in_block",
+            native_store_get_in_block,
+            "host_export_store_get_in_block",
+            args_ptr,
+            args_len
+        );
+        link_native!(
+            "store_set",
+            native_store_set,
+            "host_export_store_set",
+            args_ptr,
+            args_len
+        );
+        link_native!("store_remove", native_store_remove, args_ptr, args_len);
+        link_native!(
+            "data_source_create",
+            native_data_source_create,
+            args_ptr,
+            args_len
+        );
+        link_native!("data_source_remove", native_data_source_remove,);
+        link_native!("data_source_address", native_data_source_address,);
+        link_native!("data_source_network", native_data_source_network,);
+        link_native!("data_source_context", native_data_source_context,);
+        link_native!(
+            "crypto_keccak256",
+            native_crypto_keccak_256,
+            args_ptr,
+            args_len
+        );
+
         // link the `gas` function
         // See also e3f03e62-40e4-4f8c-b4a1-d0375cca0b76
         {
@@ -537,12 +673,21 @@ impl WasmInstance {
 
         let instance = linker.instantiate(store.as_context_mut(), &valid_module.module)?;
 
-        let asc_heap = AscHeapCtx::new(
-            &instance,
-            &mut WasmInstanceContext::new(&mut store),
-            api_version.clone(),
-        )?;
-        store.data_mut().set_asc_heap(asc_heap);
+        match valid_module.abi {
+            MappingAbi::AssemblyScript => {
+                let asc_heap = AscHeapCtx::new(
+                    &instance,
+                    &mut WasmInstanceContext::new(&mut store),
+                    api_version.clone(),
+                )?;
+                store.data_mut().set_asc_heap(asc_heap);
+            }
+            MappingAbi::Native => {
+                let native_heap =
+                    NativeHeapCtx::new(&instance, &mut WasmInstanceContext::new(&mut store))?;
+                store.data_mut().set_native_heap(native_heap);
+            }
+        }
 
         // See start_function comment for more information
         // TL;DR; we need the wasmtime::Instance to create the heap, therefore
@@ -555,7 +700,9 @@ impl WasmInstance {
                 .call(store.as_context_mut(), ())?;
         }
 
+        // `_start` is specific to AssemblyScript
         match api_version {
+            _ if valid_module.abi == MappingAbi::Native => {}
             version if version <= Version::new(0, 0, 4) => {}
             _ => {
                 instance
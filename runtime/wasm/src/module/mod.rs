@@ -25,12 +25,15 @@ pub use into_wasm_ret::IntoWasmRet;
 use crate::error::DeterminismLevel;
 use crate::gas_rules::{GAS_COST_LOAD, GAS_COST_STORE};
 pub use crate::host_exports;
+use crate::native_abi;
 
 pub use context::*;
 pub use instance::*;
+pub use native::NativeHeapCtx;
 mod context;
 mod instance;
 mod into_wasm_ret;
+mod native;
 
 // Convenience for a 'top-level' asc_get, with depth 0.
 fn asc_get<T, C: AscType, H: AscHeap + ?Sized>(
@@ -58,6 +61,18 @@ pub trait ToAscPtr {
         heap: &mut H,
         gas: &GasCounter,
     ) -> Result<AscPtr<()>, HostExportError>;
+
+    /// Encode the trigger for mappings that use the native ABI, see
+    /// `native_abi`. Triggers that can't be passed to native mappings use
+    /// the default implementation which fails the handler.
+    fn to_native_bytes(self) -> Result<Vec<u8>, HostExportError>
+    where
+        Self: Sized,
+    {
+        Err(HostExportError::Deterministic(anyhow!(
+            "this trigger is not supported by mappings that use the native ABI"
+        )))
+    }
 }
 
 impl ToAscPtr for offchain::TriggerData {
@@ -68,6 +83,10 @@ impl ToAscPtr for offchain::TriggerData {
     ) -> Result<AscPtr<()>, HostExportError> {
         asc_new(heap, self.data.as_ref() as &[u8], gas).map(|ptr| ptr.erase())
     }
+
+    fn to_native_bytes(self) -> Result<Vec<u8>, HostExportError> {
+        Ok(native_abi::encode(self.data.as_ref() as &[u8]))
+    }
 }
 
 impl ToAscPtr for subgraph::MappingEntityTrigger {
@@ -78,6 +97,10 @@ impl ToAscPtr for subgraph::MappingEntityTrigger {
     ) -> Result<AscPtr<()>, HostExportError> {
         asc_new(heap, &self.data.entity.entity.sorted_ref(), gas).map(|ptr| ptr.erase())
     }
+
+    fn to_native_bytes(self) -> Result<Vec<u8>, HostExportError> {
+        Ok(native_abi::encode(&self.data.entity.entity.sorted_ref()))
+    }
 }
 
 impl<C: Blockchain> ToAscPtr for MappingTrigger<C>
@@ -95,6 +118,14 @@ where
             MappingTrigger::Subgraph(trigger) => trigger.to_asc_ptr(heap, gas),
         }
     }
+
+    fn to_native_bytes(self) -> Result<Vec<u8>, HostExportError> {
+        match self {
+            MappingTrigger::Onchain(trigger) => trigger.to_native_bytes(),
+            MappingTrigger::Offchain(trigger) => trigger.to_native_bytes(),
+            MappingTrigger::Subgraph(trigger) => trigger.to_native_bytes(),
+        }
+    }
 }
 
 impl<T: ToAscPtr> ToAscPtr for TriggerWithHandler<T> {
@@ -105,6 +136,10 @@ impl<T: ToAscPtr> ToAscPtr for TriggerWithHandler<T> {
     ) -> Result<AscPtr<()>, HostExportError> {
         self.trigger.to_asc_ptr(heap, gas)
     }
+
+    fn to_native_bytes(self) -> Result<Vec<u8>, HostExportError> {
+        self.trigger.to_native_bytes()
+    }
 }
 
 fn is_trap_deterministic(trap: &Error) -> bool {
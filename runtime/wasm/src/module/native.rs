@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context};
+use graph::components::store::GetScope;
+use graph::data::store::Value;
+use graph::data::value::Word;
+use graph::prelude::*;
+use graph::runtime::gas::{Gas, GasCounter};
+use graph::runtime::{DeterministicHostError, HostExportError};
+use never::Never;
+use wasmtime::{AsContext, AsContextMut, Memory};
+
+use crate::asc_abi::class::LogLevel;
+use crate::gas_rules::{GAS_COST_LOAD, GAS_COST_STORE};
+use crate::native_abi::{
+    self, Decode, Encode, ABI_VERSION_EXPORT, ALLOC_EXPORT, NATIVE_ABI_VERSION,
+};
+
+use super::{host_export_error_from_trap, WasmInstanceContext};
+
+/// Access to the memory of a module that uses the native ABI, the
+/// counterpart of `AscHeapCtx`
+pub struct NativeHeapCtx {
+    memory: Memory,
+
+    // The `graph_alloc` function exported by the module
+    alloc: wasmtime::TypedFunc<u32, u32>,
+}
+
+impl NativeHeapCtx {
+    pub(crate) fn new(
+        instance: &wasmtime::Instance,
+        ctx: &mut WasmInstanceContext<'_>,
+    ) -> anyhow::Result<NativeHeapCtx> {
+        let memory = instance
+            .get_memory(ctx.as_context_mut(), "memory")
+            .context("Failed to find memory export in the WASM module")?;
+
+        let abi_version = instance
+            .get_func(ctx.as_context_mut(), ABI_VERSION_EXPORT)
+            .with_context(|| format!("`{}` function not found", ABI_VERSION_EXPORT))?
+            .typed::<(), u32>(ctx.as_context())?
+            .call(ctx.as_context_mut(), ())?;
+        if abi_version != NATIVE_ABI_VERSION {
+            bail!(
+                "the module uses version {} of the native ABI but only version {} is supported",
+                abi_version,
+                NATIVE_ABI_VERSION
+            );
+        }
+
+        let alloc = instance
+            .get_func(ctx.as_context_mut(), ALLOC_EXPORT)
+            .with_context(|| format!("`{}` function not found", ALLOC_EXPORT))?
+            .typed(ctx.as_context())?;
+
+        Ok(NativeHeapCtx { memory, alloc })
+    }
+}
+
+// Moving data in and out of the module
+impl WasmInstanceContext<'_> {
+    fn native_read(
+        &self,
+        ptr: u32,
+        len: u32,
+        gas: &GasCounter,
+    ) -> Result<Vec<u8>, DeterministicHostError> {
+        // Same cost as for `AscHeap::read`
+        gas.consume_host_fn_with_metrics(
+            Gas::new(GAS_COST_LOAD as u64 * len as u64),
+            "native_read",
+        )?;
+
+        let mut buf = vec![0; len as usize];
+        self.native_heap_ref()
+            .memory
+            .read(self, ptr as usize, &mut buf)
+            .map_err(|_| {
+                DeterministicHostError::from(anyhow!(
+                    "Heap access out of bounds. Offset: {} Size: {}",
+                    ptr,
+                    len
+                ))
+            })?;
+        Ok(buf)
+    }
+
+    fn native_args<T: Decode>(
+        &self,
+        ptr: u32,
+        len: u32,
+        gas: &GasCounter,
+    ) -> Result<T, DeterministicHostError> {
+        let buf = self.native_read(ptr, len, gas)?;
+        native_abi::decode(&buf)
+            .map_err(|e| DeterministicHostError::from(anyhow!("invalid arguments: {:#}", e)))
+    }
+
+    /// Copy `bytes` into memory allocated with the module's allocator and
+    /// return a pointer to them
+    pub(crate) fn native_write(
+        &mut self,
+        bytes: &[u8],
+        gas: &GasCounter,
+    ) -> Result<u32, HostExportError> {
+        // Same cost as for `AscHeap::raw_new`
+        gas.consume_host_fn_with_metrics(
+            Gas::new(GAS_COST_STORE as u64 * bytes.len() as u64),
+            "native_write",
+        )?;
+
+        let alloc = self.native_heap_ref().alloc.clone();
+        let ptr = alloc
+            .call(self.as_context_mut(), bytes.len() as u32)
+            .map_err(|trap| {
+                host_export_error_from_trap(trap, format!("Failed to call `{}`", ALLOC_EXPORT))
+            })?;
+
+        let memory = self.native_heap_ref().memory;
+        memory
+            .write(self.as_context_mut(), ptr as usize, bytes)
+            .map_err(|_| {
+                DeterministicHostError::from(anyhow!(
+                    "`{}` returned memory that is out of bounds. Offset: {} Size: {}",
+                    ALLOC_EXPORT,
+                    ptr,
+                    bytes.len()
+                ))
+            })?;
+        Ok(ptr)
+    }
+
+    /// Pass `value` back to the module, prefixed with its length
+    fn native_return<T: Encode + ?Sized>(
+        &mut self,
+        value: &T,
+        gas: &GasCounter,
+    ) -> Result<u32, HostExportError> {
+        let payload = native_abi::encode(value);
+        let mut buf = Vec::with_capacity(payload.len() + 4);
+        buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&payload);
+        self.native_write(&buf, gas)
+    }
+}
+
+// Host functions for the native ABI. They are all imported from the
+// `graph` module and use the same `HostExports` as the AssemblyScript
+// host functions
+impl WasmInstanceContext<'_> {
+    /// graph.abort(message: Option<String>)
+    pub fn native_abort(
+        &mut self,
+        gas: &GasCounter,
+        args_ptr: u32,
+        args_len: u32,
+    ) -> Result<Never, DeterministicHostError> {
+        let message: Option<String> = self.native_args(args_ptr, args_len, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        host_exports.abort(message, None, None, None, gas, &mut ctx.state)
+    }
+
+    /// graph.log(level: u32, message: String)
+    pub fn native_log(
+        &mut self,
+        gas: &GasCounter,
+        args_ptr: u32,
+        args_len: u32,
+    ) -> Result<(), DeterministicHostError> {
+        let (level, msg): (u32, String) = self.native_args(args_ptr, args_len, gas)?;
+        let level = LogLevel::from(level).into();
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        host_exports.log_log(&ctx.mapping_logger, level, msg, gas, &mut ctx.state)
+    }
+
+    /// graph.store_get(entity_type: String, id: String): Option<Entity>
+    pub fn native_store_get(
+        &mut self,
+        gas: &GasCounter,
+        args_ptr: u32,
+        args_len: u32,
+    ) -> Result<u32, HostExportError> {
+        self.native_store_get_scoped(gas, args_ptr, args_len, GetScope::Store)
+    }
+
+    /// graph.store_get_in_block(entity_type: String, id: String): Option<Entity>
+    pub fn native_store_get_in_block(
+        &mut self,
+        gas: &GasCounter,
+        args_ptr: u32,
+        args_len: u32,
+    ) -> Result<u32, HostExportError> {
+        self.native_store_get_scoped(gas, args_ptr, args_len, GetScope::InBlock)
+    }
+
+    fn native_store_get_scoped(
+        &mut self,
+        gas: &GasCounter,
+        args_ptr: u32,
+        args_len: u32,
+        scope: GetScope,
+    ) -> Result<u32, HostExportError> {
+        let (entity_type, id): (String, String) = self.native_args(args_ptr, args_len, gas)?;
+        let _timer = self
+            .as_ref()
+            .host_metrics
+            .cheap_clone()
+            .time_host_fn_execution_region("store_get");
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let store_reads = self.as_ref().ctx.state.entity_cache.store_reads;
+        let entity =
+            host_exports.store_get(&mut self.as_mut().ctx.state, entity_type, id, gas, scope)?;
+        let cache_miss = self.as_ref().ctx.state.entity_cache.store_reads > store_reads;
+        self.as_ref().host_metrics.track_store_get(cache_miss);
+
+        match entity {
+            Some(entity) => self.native_return(&entity.sorted_ref(), gas),
+            None => Ok(0),
+        }
+    }
+
+    /// graph.store_set(entity_type: String, id: String, data: Entity)
+    pub fn native_store_set(
+        &mut self,
+        gas: &GasCounter,
+        args_ptr: u32,
+        args_len: u32,
+    ) -> Result<(), HostExportError> {
+        let stopwatch = self.as_ref().host_metrics.stopwatch.cheap_clone();
+        let logger = self.as_ref().ctx.logger.cheap_clone();
+        let block_number = self.as_ref().ctx.block_ptr.block_number();
+
+        let (entity_type, id, data): (String, String, HashMap<Word, Value>) =
+            self.native_args(args_ptr, args_len, gas)?;
+
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        host_exports.store_set(
+            &logger,
+            block_number,
+            &mut ctx.state,
+            &ctx.proof_of_indexing,
+            ctx.timestamp,
+            entity_type,
+            id,
+            data,
+            &stopwatch,
+            gas,
+        )
+    }
+
+    /// graph.store_remove(entity_type: String, id: String)
+    pub fn native_store_remove(
+        &mut self,
+        gas: &GasCounter,
+        args_ptr: u32,
+        args_len: u32,
+    ) -> Result<(), HostExportError> {
+        let logger = self.as_ref().ctx.logger.cheap_clone();
+        let (entity_type, id): (String, String) = self.native_args(args_ptr, args_len, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        host_exports.store_remove(
+            &logger,
+            &mut ctx.state,
+            &ctx.proof_of_indexing,
+            entity_type,
+            id,
+            gas,
+        )
+    }
+
+    /// graph.data_source_create(name: String, params: Vec<String>, context: Option<Entity>)
+    pub fn native_data_source_create(
+        &mut self,
+        gas: &GasCounter,
+        args_ptr: u32,
+        args_len: u32,
+    ) -> Result<(), HostExportError> {
+        let logger = self.as_ref().ctx.logger.cheap_clone();
+        let block_number = self.as_ref().ctx.block_ptr.number;
+        let (name, params, context): (String, Vec<String>, Option<HashMap<Word, Value>>) =
+            self.native_args(args_ptr, args_len, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        host_exports.data_source_create(
+            &logger,
+            &mut self.as_mut().ctx.state,
+            name,
+            params,
+            context.map(DataSourceContext::from),
+            block_number,
+            gas,
+        )
+    }
+
+    /// graph.data_source_remove()
+    pub fn native_data_source_remove(&mut self, gas: &GasCounter) -> Result<(), HostExportError> {
+        self.data_source_remove(gas)
+    }
+
+    /// graph.data_source_address(): Bytes
+    pub fn native_data_source_address(&mut self, gas: &GasCounter) -> Result<u32, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let address = host_exports.data_source_address(gas, &mut ctx.state)?;
+        self.native_return(address.as_slice(), gas)
+    }
+
+    /// graph.data_source_network(): String
+    pub fn native_data_source_network(&mut self, gas: &GasCounter) -> Result<u32, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let network = host_exports.data_source_network(gas, &mut ctx.state)?;
+        self.native_return(&network, gas)
+    }
+
+    /// graph.data_source_context(): Entity
+    pub fn native_data_source_context(&mut self, gas: &GasCounter) -> Result<u32, HostExportError> {
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let context = host_exports
+            .data_source_context(gas, &mut ctx.state)?
+            .map(|e| e.sorted())
+            .unwrap_or_default();
+        self.native_return(&context, gas)
+    }
+
+    /// graph.crypto_keccak256(input: Bytes): Bytes
+    pub fn native_crypto_keccak_256(
+        &mut self,
+        gas: &GasCounter,
+        args_ptr: u32,
+        args_len: u32,
+    ) -> Result<u32, HostExportError> {
+        let input: Vec<u8> = self.native_args(args_ptr, args_len, gas)?;
+        let host_exports = self.as_ref().ctx.host_exports.cheap_clone();
+        let ctx = &mut self.as_mut().ctx;
+        let hash = host_exports.crypto_keccak_256(input, gas, &mut ctx.state)?;
+        self.native_return(hash.as_slice(), gas)
+    }
+}
@@ -0,0 +1,491 @@
+//! A language-neutral ABI for mappings that are not written in
+//! AssemblyScript. Instead of emulating AssemblyScript classes in the
+//! module's memory, all data crosses the boundary between host and mapping
+//! as plain byte buffers in a simple length-prefixed serialization format.
+//!
+//! A module opts into this ABI by exporting these functions:
+//!
+//! - `graph_abi_version() -> u32`, which must return `NATIVE_ABI_VERSION`
+//! - `graph_alloc(size: u32) -> u32`, which allocates `size` bytes in the
+//!   module's memory and returns a pointer to them. The host never frees
+//!   that memory; that is up to the mapping
+//!
+//! Handlers are exported as `handler(ptr: u32, len: u32)` and receive the
+//! encoded trigger. Host functions are imported from the `graph` module.
+//! Host functions with arguments take them encoded in one buffer as
+//! `(ptr: u32, len: u32)`. Host functions with a result return a pointer to
+//! a buffer allocated with `graph_alloc` that holds the length of the
+//! encoded result as a little-endian `u32`, followed by the result itself.
+//! A pointer of `0` stands for a missing result.
+//!
+//! Only the host functions in `module::native` are available. The
+//! chain-specific host functions, like `ethereum.call`,
+//! `ethereum.getBalance` or `ethereum.getStorageAt`, read their arguments
+//! from and write their results as AssemblyScript objects and are therefore
+//! not available to native modules. Modules that import anything other than
+//! the `graph` module are rejected when they are loaded.
+//!
+//! The encoding uses little-endian for all integers. Byte arrays and
+//! strings are encoded as their length as a `u32` followed by their
+//! bytes; lists as the number of elements as a `u32` followed by the
+//! elements. Options are a `0` byte for `None` and a `1` byte followed by
+//! the value for `Some`. Store values start with a byte for their type
+//! followed by the value, see `Value`'s `Encode` implementation. Entities
+//! are lists of pairs of attribute name and value.
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::str::FromStr;
+
+use graph::data::store::scalar::{BigDecimal, BigInt, Bytes, Timestamp};
+use graph::data::store::Value;
+use graph::data::value::Word;
+use graph::prelude::web3::types::{self as web3, H160, H256, U256, U64};
+use graph::runtime::DeterministicHostError;
+
+/// The version of the native ABI this node implements
+pub const NATIVE_ABI_VERSION: u32 = 1;
+
+/// The export through which modules declare that they use the native ABI
+pub const ABI_VERSION_EXPORT: &str = "graph_abi_version";
+
+/// The export that the host uses to allocate memory in the module
+pub const ALLOC_EXPORT: &str = "graph_alloc";
+
+/// The module from which native mappings import host functions
+pub const IMPORT_MODULE: &str = "graph";
+
+/// The ABI a mapping uses to exchange data with the host
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MappingAbi {
+    AssemblyScript,
+    Native,
+}
+
+const VALUE_NULL: u8 = 0;
+const VALUE_STRING: u8 = 1;
+const VALUE_INT: u8 = 2;
+const VALUE_INT8: u8 = 3;
+const VALUE_BIG_INT: u8 = 4;
+const VALUE_BIG_DECIMAL: u8 = 5;
+const VALUE_BOOL: u8 = 6;
+const VALUE_BYTES: u8 = 7;
+const VALUE_LIST: u8 = 8;
+const VALUE_TIMESTAMP: u8 = 9;
+
+#[derive(Default)]
+pub struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put<T: Encode + ?Sized>(&mut self, value: &T) -> &mut Self {
+        value.encode(self);
+        self
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.put_len(bytes.len());
+        self.buf.extend_from_slice(bytes);
+        self
+    }
+
+    fn put_len(&mut self, len: usize) {
+        // Wasm memory is addressed with 32 bits, nothing we pass to a
+        // module can be longer than that
+        self.put(&(len as u32));
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+pub struct Decoder<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Decoder { buf }
+    }
+
+    pub fn get<T: Decode>(&mut self) -> Result<T, DeterministicHostError> {
+        T::decode(self)
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8], DeterministicHostError> {
+        let len = self.get::<u32>()? as usize;
+        self.take(len)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DeterministicHostError> {
+        if self.buf.len() < len {
+            return Err(DeterministicHostError::from(anyhow::anyhow!(
+                "unexpected end of input: needed {} more bytes but only {} are left",
+                len,
+                self.buf.len()
+            )));
+        }
+        let (head, tail) = self.buf.split_at(len);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], DeterministicHostError> {
+        // unwrap: `take` returns exactly `N` bytes
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    /// Make sure that all input was consumed
+    pub fn finish(self) -> Result<(), DeterministicHostError> {
+        if !self.buf.is_empty() {
+            return Err(DeterministicHostError::from(anyhow::anyhow!(
+                "{} bytes of trailing input",
+                self.buf.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Decode a `T` from `buf`, which must contain exactly one `T`
+pub fn decode<T: Decode>(buf: &[u8]) -> Result<T, DeterministicHostError> {
+    let mut decoder = Decoder::new(buf);
+    let value = decoder.get()?;
+    decoder.finish()?;
+    Ok(value)
+}
+
+pub fn encode<T: Encode + ?Sized>(value: &T) -> Vec<u8> {
+    let mut encoder = Encoder::new();
+    encoder.put(value);
+    encoder.finish()
+}
+
+pub trait Encode {
+    fn encode(&self, enc: &mut Encoder);
+}
+
+pub trait Decode: Sized {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError>;
+}
+
+macro_rules! int_codec {
+    ($($ty:ty),*) => {
+        $(
+            impl Encode for $ty {
+                fn encode(&self, enc: &mut Encoder) {
+                    enc.buf.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+
+            impl Decode for $ty {
+                fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+                    Ok(<$ty>::from_le_bytes(dec.take_array()?))
+                }
+            }
+        )*
+    };
+}
+
+int_codec!(u8, u32, i32, u64, i64);
+
+impl Encode for bool {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(&(*self as u8));
+    }
+}
+
+impl Decode for bool {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+        match dec.get::<u8>()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(DeterministicHostError::from(anyhow::anyhow!(
+                "invalid boolean {}",
+                b
+            ))),
+        }
+    }
+}
+
+impl Encode for str {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.bytes(self.as_bytes());
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, enc: &mut Encoder) {
+        self.as_str().encode(enc)
+    }
+}
+
+impl Decode for String {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+        let bytes = dec.bytes()?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|e| DeterministicHostError::from(anyhow::anyhow!("invalid string: {}", e)))
+    }
+}
+
+impl Encode for Word {
+    fn encode(&self, enc: &mut Encoder) {
+        self.as_str().encode(enc)
+    }
+}
+
+impl Decode for Word {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+        String::decode(dec).map(Word::from)
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode(&self, enc: &mut Encoder) {
+        (**self).encode(enc)
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put_len(self.len());
+        for item in self {
+            item.encode(enc);
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, enc: &mut Encoder) {
+        self.as_slice().encode(enc)
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+        let len = dec.get::<u32>()? as usize;
+        // Don't trust `len` for the allocation; every element takes at
+        // least one byte
+        let mut items = Vec::with_capacity(len.min(dec.buf.len()));
+        for _ in 0..len {
+            items.push(dec.get()?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, enc: &mut Encoder) {
+        match self {
+            None => enc.put(&0u8),
+            Some(value) => enc.put(&1u8).put(value),
+        };
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+        match dec.get::<u8>()? {
+            0 => Ok(None),
+            1 => Ok(Some(dec.get()?)),
+            b => Err(DeterministicHostError::from(anyhow::anyhow!(
+                "invalid option tag {}",
+                b
+            ))),
+        }
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(&self.0).put(&self.1);
+    }
+}
+
+impl<A: Decode, B: Decode> Decode for (A, B) {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+        Ok((dec.get()?, dec.get()?))
+    }
+}
+
+impl<A: Encode, B: Encode, C: Encode> Encode for (A, B, C) {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(&self.0).put(&self.1).put(&self.2);
+    }
+}
+
+impl<A: Decode, B: Decode, C: Decode> Decode for (A, B, C) {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+        Ok((dec.get()?, dec.get()?, dec.get()?))
+    }
+}
+
+impl<K: Decode + Eq + Hash, V: Decode> Decode for HashMap<K, V> {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+        Ok(Vec::<(K, V)>::decode(dec)?.into_iter().collect())
+    }
+}
+
+impl Encode for BigInt {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.bytes(&self.to_signed_bytes_le());
+    }
+}
+
+impl Decode for BigInt {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+        BigInt::from_signed_bytes_le(dec.bytes()?).map_err(DeterministicHostError::from)
+    }
+}
+
+impl Encode for BigDecimal {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(&self.to_string());
+    }
+}
+
+impl Decode for BigDecimal {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+        let s = dec.get::<String>()?;
+        BigDecimal::from_str(&s).map_err(|e| {
+            DeterministicHostError::from(anyhow::anyhow!("invalid BigDecimal `{}`: {}", s, e))
+        })
+    }
+}
+
+impl Encode for Value {
+    fn encode(&self, enc: &mut Encoder) {
+        match self {
+            Value::Null => enc.put(&VALUE_NULL),
+            Value::String(s) => enc.put(&VALUE_STRING).put(s),
+            Value::Int(i) => enc.put(&VALUE_INT).put(i),
+            Value::Int8(i) => enc.put(&VALUE_INT8).put(i),
+            Value::BigInt(n) => enc.put(&VALUE_BIG_INT).put(n),
+            Value::BigDecimal(d) => enc.put(&VALUE_BIG_DECIMAL).put(d),
+            Value::Bool(b) => enc.put(&VALUE_BOOL).put(b),
+            Value::Bytes(b) => enc.put(&VALUE_BYTES).bytes(b.as_slice()),
+            Value::List(values) => enc.put(&VALUE_LIST).put(values),
+            Value::Timestamp(ts) => enc
+                .put(&VALUE_TIMESTAMP)
+                .put(&ts.as_microseconds_since_epoch()),
+        };
+    }
+}
+
+impl Decode for Value {
+    fn decode(dec: &mut Decoder<'_>) -> Result<Self, DeterministicHostError> {
+        let value = match dec.get::<u8>()? {
+            VALUE_NULL => Value::Null,
+            VALUE_STRING => Value::String(dec.get()?),
+            VALUE_INT => Value::Int(dec.get()?),
+            VALUE_INT8 => Value::Int8(dec.get()?),
+            VALUE_BIG_INT => Value::BigInt(dec.get()?),
+            VALUE_BIG_DECIMAL => Value::BigDecimal(dec.get()?),
+            VALUE_BOOL => Value::Bool(dec.get()?),
+            VALUE_BYTES => Value::Bytes(Bytes::from(dec.bytes()?)),
+            VALUE_LIST => Value::List(dec.get()?),
+            VALUE_TIMESTAMP => {
+                let micros = dec.get::<i64>()?;
+                let ts = Timestamp::from_microseconds_since_epoch(micros).map_err(|e| {
+                    DeterministicHostError::from(anyhow::anyhow!("invalid timestamp: {}", e))
+                })?;
+                Value::Timestamp(ts)
+            }
+            tag => {
+                return Err(DeterministicHostError::from(anyhow::anyhow!(
+                    "invalid value type {}",
+                    tag
+                )))
+            }
+        };
+        Ok(value)
+    }
+}
+
+impl Encode for H160 {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.bytes(self.as_bytes());
+    }
+}
+
+impl Encode for H256 {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.bytes(self.as_bytes());
+    }
+}
+
+/// Unsigned 256 bit integers are encoded as 32 big-endian bytes
+impl Encode for U256 {
+    fn encode(&self, enc: &mut Encoder) {
+        let mut bytes = [0u8; 32];
+        self.to_big_endian(&mut bytes);
+        enc.bytes(&bytes);
+    }
+}
+
+impl Encode for U64 {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.put(&self.as_u64());
+    }
+}
+
+impl Encode for web3::Bytes {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.bytes(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graph::data::store::scalar::{BigDecimal, BigInt, Bytes, Timestamp};
+    use graph::data::store::Value;
+    use std::str::FromStr;
+
+    use super::{decode, encode};
+
+    #[test]
+    fn value_roundtrip() {
+        let values = vec![
+            Value::Null,
+            Value::String("graph".to_string()),
+            Value::Int(-17),
+            Value::Int8(i64::MAX),
+            Value::BigInt(BigInt::from(-12345678901234567i64)),
+            Value::BigDecimal(BigDecimal::from_str("-1.25").unwrap()),
+            Value::Bool(true),
+            Value::Bytes(Bytes::from(vec![1u8, 2, 3])),
+            Value::List(vec![Value::Int(1), Value::Null]),
+            Value::Timestamp(Timestamp::from_microseconds_since_epoch(1_700_000_000).unwrap()),
+        ];
+
+        for value in values {
+            let decoded: Value = decode(&encode(&value)).unwrap();
+            assert_eq!(value, decoded);
+        }
+    }
+
+    #[test]
+    fn length_prefixed() {
+        assert_eq!(vec![3, 0, 0, 0, b'a', b'b', b'c'], encode("abc"));
+        assert_eq!(
+            vec![2, 0, 0, 0, 7, 0, 0, 0, 9, 0, 0, 0],
+            encode(&vec![7u32, 9])
+        );
+        assert_eq!(vec![0], encode(&None::<u32>));
+    }
+
+    #[test]
+    fn malformed_input() {
+        // Claims to have 5 bytes but only has 1
+        assert!(decode::<String>(&[5, 0, 0, 0, b'a']).is_err());
+        // Trailing input
+        assert!(decode::<u8>(&[1, 2]).is_err());
+        assert!(decode::<Value>(&[42]).is_err());
+        assert!(decode::<bool>(&[2]).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+//! Symbolizing traps in mappings with the source map that can be published
+//! alongside the mapping file in the manifest.
+//!
+//! Compilers that target wasm, like AssemblyScript through binaryen, put all
+//! mappings of a [source map](https://sourcemaps.info/spec.html) on its first
+//! line and use the byte offset into the wasm module as the column. The
+//! module we actually run has gas metering injected and is laid out
+//! differently from the module the source map describes, so offsets in
+//! backtraces first need to be translated back to the uploaded module.
+
+use std::fmt;
+use std::io::Cursor;
+use std::ops::Range;
+
+use anyhow::{anyhow, Error};
+use graph::prelude::serde_json;
+use parity_wasm::elements::{Deserialize, Instruction};
+use wasmtime::WasmBacktrace;
+
+/// A position in a source file; `line` and `column` are 1-based
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceLocation<'a> {
+    pub file: &'a str,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for SourceLocation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Position {
+    source: u32,
+    line: u32,
+    column: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    offset: u32,
+    /// `None` for code that the compiler did not attribute to any source
+    position: Option<Position>,
+}
+
+#[derive(Debug)]
+pub struct SourceMap {
+    sources: Vec<String>,
+    /// Sorted by `offset`
+    segments: Vec<Segment>,
+}
+
+impl SourceMap {
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let json: serde_json::Value = serde_json::from_slice(bytes)?;
+
+        let version = json.get("version").and_then(|version| version.as_u64());
+        if version != Some(3) {
+            return Err(anyhow!("unsupported source map version {:?}", version));
+        }
+
+        let root = match json.get("sourceRoot").and_then(|root| root.as_str()) {
+            Some(root) if !root.is_empty() && !root.ends_with('/') => format!("{}/", root),
+            Some(root) => root.to_string(),
+            None => String::new(),
+        };
+        let sources = json
+            .get("sources")
+            .and_then(|sources| sources.as_array())
+            .ok_or_else(|| anyhow!("source map has no `sources`"))?
+            .iter()
+            .map(|source| {
+                source
+                    .as_str()
+                    .map(|source| format!("{}{}", root, source))
+                    .ok_or_else(|| anyhow!("source map has a source that is not a string"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mappings = json
+            .get("mappings")
+            .and_then(|mappings| mappings.as_str())
+            .ok_or_else(|| anyhow!("source map has no `mappings`"))?;
+        let segments = decode_mappings(mappings, sources.len())?;
+
+        Ok(SourceMap { sources, segments })
+    }
+
+    /// The source location of the instruction at `offset` in the module the
+    /// source map was generated for
+    pub fn lookup(&self, offset: u32) -> Option<SourceLocation<'_>> {
+        let idx = self
+            .segments
+            .partition_point(|segment| segment.offset <= offset);
+        let position = self.segments[..idx].last()?.position?;
+        Some(SourceLocation {
+            file: &self.sources[position.source as usize],
+            line: position.line + 1,
+            column: position.column + 1,
+        })
+    }
+}
+
+/// Decode the `mappings` of a source map for a wasm module. Only the first
+/// line is used since that's where all the mappings for wasm are.
+fn decode_mappings(mappings: &str, source_count: usize) -> Result<Vec<Segment>, Error> {
+    let first_line = mappings.split(';').next().unwrap_or_default();
+
+    let mut segments = Vec::new();
+    let (mut offset, mut source, mut line, mut column) = (0i64, 0i64, 0i64, 0i64);
+    for segment in first_line.split(',').filter(|segment| !segment.is_empty()) {
+        let fields = decode_vlq(segment)?;
+        let invalid = || anyhow!("invalid segment `{}` in source map", segment);
+
+        offset += fields[0];
+        let position = if fields.len() >= 4 {
+            source += fields[1];
+            line += fields[2];
+            column += fields[3];
+            if source < 0 || source as usize >= source_count || line < 0 || column < 0 {
+                return Err(invalid());
+            }
+            Some(Position {
+                source: source as u32,
+                line: line as u32,
+                column: column as u32,
+            })
+        } else {
+            None
+        };
+        segments.push(Segment {
+            offset: u32::try_from(offset).map_err(|_| invalid())?,
+            position,
+        });
+    }
+    segments.sort_by_key(|segment| segment.offset);
+
+    Ok(segments)
+}
+
+/// Decode the base64 VLQ fields of one segment
+fn decode_vlq(segment: &str) -> Result<Vec<i64>, Error> {
+    const CONTINUATION: u8 = 0x20;
+
+    let mut fields = Vec::with_capacity(5);
+    let (mut value, mut shift) = (0i64, 0u32);
+    for c in segment.bytes() {
+        let digit = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(anyhow!("invalid character `{}` in source map", c as char)),
+        };
+        if shift > 55 {
+            return Err(anyhow!(
+                "value in source map segment `{}` is too large",
+                segment
+            ));
+        }
+        value += ((digit & !CONTINUATION) as i64) << shift;
+        if digit & CONTINUATION != 0 {
+            shift += 5;
+        } else {
+            let negative = value & 1 == 1;
+            value >>= 1;
+            fields.push(if negative { -value } else { value });
+            value = 0;
+            shift = 0;
+        }
+    }
+    if shift != 0 {
+        return Err(anyhow!("truncated segment `{}` in source map", segment));
+    }
+    Ok(fields)
+}
+
+/// A source map together with what's needed to apply it to the module
+/// after gas metering was injected
+#[derive(Debug)]
+pub struct ModuleSourceMap {
+    source_map: SourceMap,
+    original: Vec<u8>,
+    instrumented: Vec<u8>,
+    /// The index of the imported function that gas metering calls
+    gas_func: u32,
+}
+
+impl ModuleSourceMap {
+    pub fn new(source_map: SourceMap, original: &[u8], instrumented: &[u8], gas_func: u32) -> Self {
+        ModuleSourceMap {
+            source_map,
+            original: original.to_vec(),
+            instrumented: instrumented.to_vec(),
+            gas_func,
+        }
+    }
+
+    /// Describe the frames of `backtrace` in terms of the mapping's source.
+    /// Returns `None` if none of the frames could be symbolized.
+    pub fn symbolize(&self, backtrace: &WasmBacktrace) -> Option<String> {
+        let instrumented = code_bodies(&self.instrumented)?;
+        let original = code_bodies(&self.original)?;
+
+        let mut symbolized = false;
+        let frames: Vec<_> = backtrace
+            .frames()
+            .iter()
+            .enumerate()
+            .map(|(i, frame)| {
+                let name = frame.func_name().unwrap_or("<unknown>");
+                let location = frame
+                    .module_offset()
+                    .and_then(|offset| self.original_offset(&instrumented, &original, offset))
+                    .and_then(|offset| self.source_map.lookup(offset));
+                match location {
+                    Some(location) => {
+                        symbolized = true;
+                        format!("{:>5}: {} at {}", i, name, location)
+                    }
+                    None => format!("{:>5}: {}", i, name),
+                }
+            })
+            .collect();
+
+        symbolized.then(|| format!("mapping backtrace:\n{}", frames.join("\n")))
+    }
+
+    /// Translate `offset` in the instrumented module to the offset of the
+    /// same instruction in the original module. Gas metering only inserts
+    /// an `i32.const <cost>; call <gas_func>` pair at the start of each
+    /// metered block and otherwise leaves instructions one for one, which
+    /// lets us line up the instructions of the two versions of a function.
+    fn original_offset(
+        &self,
+        instrumented: &[Range<usize>],
+        original: &[Range<usize>],
+        offset: usize,
+    ) -> Option<u32> {
+        let idx = instrumented
+            .iter()
+            .position(|body| body.contains(&offset))?;
+        let instructions = instructions(&self.instrumented, instrumented[idx].clone())?;
+        // Functions added by gas metering come after all original functions
+        let original = instructions(&self.original, original.get(idx)?.clone())?;
+
+        let target = instructions
+            .partition_point(|(start, _)| *start <= offset)
+            .checked_sub(1)?;
+        let (mut i, mut j) = (0, 0);
+        while i < target {
+            if self.is_gas_charge(&instructions, i) {
+                i += 2;
+            } else {
+                i += 1;
+                j += 1;
+            }
+        }
+        original.get(j).map(|(start, _)| *start as u32)
+    }
+
+    fn is_gas_charge(&self, instructions: &[(usize, Instruction)], i: usize) -> bool {
+        matches!(
+            instructions[i].1,
+            Instruction::I32Const(_) | Instruction::I64Const(_)
+        ) && matches!(
+            instructions.get(i + 1),
+            Some((_, Instruction::Call(func))) if *func == self.gas_func
+        )
+    }
+}
+
+/// The byte ranges of the function bodies in the code section of `module`,
+/// without their size prefix
+fn code_bodies(module: &[u8]) -> Option<Vec<Range<usize>>> {
+    const CODE_SECTION: u8 = 10;
+
+    // Skip the magic number and version
+    let mut pos = 8;
+    while pos < module.len() {
+        let id = module[pos];
+        pos += 1;
+        let size = read_leb(module, &mut pos)? as usize;
+        let end = pos.checked_add(size)?;
+        if id != CODE_SECTION {
+            pos = end;
+            continue;
+        }
+
+        let count = read_leb(module, &mut pos)?;
+        let mut bodies = Vec::new();
+        for _ in 0..count {
+            let size = read_leb(module, &mut pos)? as usize;
+            bodies.push(pos..pos.checked_add(size)?);
+            pos += size;
+        }
+        return (pos <= end).then_some(bodies);
+    }
+    None
+}
+
+/// The instructions of the function body at `body` in `module` together
+/// with their offsets in the module
+fn instructions(module: &[u8], body: Range<usize>) -> Option<Vec<(usize, Instruction)>> {
+    let bytes = module.get(body.clone())?;
+
+    // Skip the local declarations, each of which is a count and a value type
+    let mut pos = 0;
+    for _ in 0..read_leb(bytes, &mut pos)? {
+        read_leb(bytes, &mut pos)?;
+        pos += 1;
+    }
+
+    let mut cursor = Cursor::new(bytes);
+    cursor.set_position(pos as u64);
+    let mut instructions = Vec::new();
+    while (cursor.position() as usize) < bytes.len() {
+        let start = body.start + cursor.position() as usize;
+        let instruction = Instruction::deserialize(&mut cursor).ok()?;
+        instructions.push((start, instruction));
+    }
+    Some(instructions)
+}
+
+fn read_leb(bytes: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut value = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SourceLocation, SourceMap};
+
+    #[test]
+    fn lookup() {
+        // Segments at offsets 10, 15, 20 and 25; the last one has no source
+        let source_map = SourceMap::parse(
+            br#"{
+                "version": 3,
+                "sourceRoot": "src",
+                "sources": ["~lib/rt.ts", "mapping.ts"],
+                "names": [],
+                "mappings": "UAAA,KCUK,KAAE,K"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(None, source_map.lookup(9));
+        assert_eq!(
+            Some(SourceLocation {
+                file: "src/~lib/rt.ts",
+                line: 1,
+                column: 1,
+            }),
+            source_map.lookup(12)
+        );
+        assert_eq!(
+            Some(SourceLocation {
+                file: "src/mapping.ts",
+                line: 11,
+                column: 6,
+            }),
+            source_map.lookup(15)
+        );
+        assert_eq!(
+            Some(SourceLocation {
+                file: "src/mapping.ts",
+                line: 11,
+                column: 8,
+            }),
+            source_map.lookup(22)
+        );
+        assert_eq!(None, source_map.lookup(100));
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        assert!(SourceMap::parse(br#"{"version": 2, "sources": [], "mappings": ""}"#).is_err());
+        assert!(SourceMap::parse(br#"{"version": 3, "sources": [], "mappings": "AAAA"}"#).is_err());
+        assert!(
+            SourceMap::parse(br#"{"version": 3, "sources": ["a"], "mappings": "A!"}"#).is_err()
+        );
+    }
+}
@@ -2,6 +2,7 @@ use ethabi;
 
 use graph::data::store::scalar::Timestamp;
 use graph::data::value::Word;
+use graph::prelude::chrono::{DateTime, Datelike, Timelike, Utc};
 use graph::prelude::{BigDecimal, BigInt};
 use graph::runtime::gas::GasCounter;
 use graph::runtime::{
@@ -163,6 +164,25 @@ impl ToAscObj<Array<AscPtr<AscString>>> for Vec<String> {
     }
 }
 
+/// `None` elements become `null`
+impl ToAscObj<Array<AscPtr<AscString>>> for Vec<Option<String>> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        heap: &mut H,
+        gas: &GasCounter,
+    ) -> Result<Array<AscPtr<AscString>>, HostExportError> {
+        let content: Result<Vec<_>, _> = self
+            .iter()
+            .map(|x| match x {
+                Some(x) => asc_new(heap, x, gas),
+                None => Ok(AscPtr::null()),
+            })
+            .collect();
+        let content = content?;
+        Array::new(&content, heap, gas)
+    }
+}
+
 impl ToAscObj<AscEnum<EthereumValueKind>> for ethabi::Token {
     fn to_asc_obj<H: AscHeap + ?Sized>(
         &self,
@@ -522,3 +542,22 @@ impl ToAscObj<AscYamlTaggedValue> for serde_yaml::value::TaggedValue {
         })
     }
 }
+
+impl ToAscObj<AscDateTimeParts> for DateTime<Utc> {
+    fn to_asc_obj<H: AscHeap + ?Sized>(
+        &self,
+        _heap: &mut H,
+        _gas: &GasCounter,
+    ) -> Result<AscDateTimeParts, HostExportError> {
+        Ok(AscDateTimeParts {
+            year: self.year(),
+            month: self.month() as i32,
+            day: self.day() as i32,
+            hour: self.hour() as i32,
+            minute: self.minute() as i32,
+            second: self.second() as i32,
+            weekday: self.weekday().number_from_monday() as i32,
+            day_of_year: self.ordinal() as i32,
+        })
+    }
+}
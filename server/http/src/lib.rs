@@ -2,9 +2,11 @@ extern crate graph;
 extern crate graph_graphql;
 extern crate serde;
 
+mod persisted;
 mod request;
 mod server;
 mod service;
+mod subscription;
 
 pub use self::server::GraphQLServer;
 pub use self::service::GraphQLService;
@@ -0,0 +1,122 @@
+//! Support for persisted queries. Clients can send the sha256 hash of a
+//! query document in `extensions.persistedQuery.sha256Hash` instead of, or
+//! in addition to, the document itself, following the Automatic Persisted
+//! Queries protocol. Documents that clients sent are remembered in an
+//! in-memory cache; documents that were registered for a deployment with
+//! `graphman persisted-query register` are looked up in the store.
+//!
+//! Whether a deployment only allows registered queries is checked by the
+//! `GraphQlRunner` when the query is run, so that it applies to queries
+//! and subscriptions alike.
+use std::sync::{Arc, Mutex};
+
+use graph::components::graphql::GraphQlRunner;
+use graph::components::server::query::ServerError;
+use graph::data::query::{persisted_query_hash, QueryTarget};
+use graph::env::ENV_VARS;
+use graph::prelude::{serde_json, Query, QueryError, QueryExecutionError};
+use graph::util::lfu_cache::LfuCache;
+
+use crate::request::{parse_graphql_payload, parse_query_with_variables};
+
+#[derive(Debug)]
+pub(crate) struct PersistedQueries {
+    /// Maps the hash of a query document to the document
+    cache: Mutex<LfuCache<String, String>>,
+}
+
+impl PersistedQueries {
+    pub fn new() -> Self {
+        PersistedQueries {
+            cache: Mutex::new(LfuCache::new()),
+        }
+    }
+
+    fn remember(&self, hash: String, query: String) {
+        let max_weight = ENV_VARS.graphql.persisted_query_cache_size;
+        if max_weight == 0 {
+            return;
+        }
+        let mut cache = self.cache.lock().unwrap();
+        if !cache.contains_key(&hash) {
+            cache.insert(hash, query);
+            cache.evict(max_weight);
+        }
+    }
+
+    /// Turn the JSON body of a request into a `Query`, resolving the query
+    /// document from its hash if the request only contains the hash
+    pub async fn parse_request<Q: GraphQlRunner>(
+        &self,
+        runner: Arc<Q>,
+        target: QueryTarget,
+        json: &serde_json::Value,
+        trace: bool,
+    ) -> Result<Query, ServerError> {
+        let obj = json.as_object().ok_or_else(|| {
+            ServerError::ClientError(String::from("Request data is not an object"))
+        })?;
+
+        let hash = match obj
+            .get("extensions")
+            .and_then(|ext| ext.get("persistedQuery"))
+            .and_then(|pq| pq.get("sha256Hash"))
+        {
+            None | Some(serde_json::Value::Null) => None,
+            Some(hash) => Some(
+                hash.as_str()
+                    .ok_or_else(|| {
+                        ServerError::ClientError(String::from(
+                            "The \"sha256Hash\" of a persisted query is not a string",
+                        ))
+                    })?
+                    .to_lowercase(),
+            ),
+        };
+
+        // Without a hash, this is an ordinary request
+        let hash = match hash {
+            Some(hash) => hash,
+            None => return parse_graphql_payload(json, trace),
+        };
+
+        match obj.get("query") {
+            None | Some(serde_json::Value::Null) => { /* look up the hash below */ }
+            Some(query) => {
+                let text = query.as_str().ok_or_else(|| {
+                    ServerError::ClientError(String::from("The \"query\" field is not a string"))
+                })?;
+                if persisted_query_hash(text) != hash {
+                    return Err(query_error(
+                        QueryExecutionError::PersistedQueryHashMismatch(hash),
+                    ));
+                }
+                self.remember(hash, text.to_string());
+                return parse_query_with_variables(obj, text, trace);
+            }
+        }
+
+        let cached = self.cache.lock().unwrap().get(&hash).cloned();
+        let query = match cached {
+            Some(query) => query,
+            None => {
+                let registered = runner
+                    .registered_queries(target)
+                    .await
+                    .map_err(query_error)?;
+                let query = registered
+                    .queries
+                    .get(&hash)
+                    .cloned()
+                    .ok_or_else(|| query_error(QueryExecutionError::PersistedQueryNotFound))?;
+                self.remember(hash, query.clone());
+                query
+            }
+        };
+        parse_query_with_variables(obj, &query, trace)
+    }
+}
+
+fn query_error(e: QueryExecutionError) -> ServerError {
+    ServerError::from(QueryError::from(e))
+}
@@ -1,14 +1,36 @@
 use graph::prelude::serde_json;
 
 use graph::components::server::query::ServerError;
+use graph::data::query::persisted_query_hash;
 use graph::hyper::body::Bytes;
+use graph::hyper::Request;
 use graph::prelude::*;
 
-pub fn parse_graphql_request(body: &Bytes, trace: bool) -> Result<Query, ServerError> {
-    // Parse request body as JSON
-    let json: serde_json::Value =
-        serde_json::from_slice(body).map_err(|e| ServerError::ClientError(format!("{}", e)))?;
+/// Parse the body of a GraphQL request as JSON; turning the JSON into a
+/// `Query` is left to the caller, since that might require looking up a
+/// persisted query
+pub fn parse_request_body(body: &Bytes) -> Result<serde_json::Value, ServerError> {
+    serde_json::from_slice(body).map_err(|e| ServerError::ClientError(format!("{}", e)))
+}
+
+/// Identify the client that sent `req` by the header named by
+/// `GRAPH_GRAPHQL_CLIENT_ID_HEADER`. Requests without that header are all
+/// treated as coming from the same anonymous client `""`, and therefore
+/// share one budget
+pub fn client_id<T>(req: &Request<T>) -> Option<String> {
+    let header = ENV_VARS.graphql.client_id_header.as_str();
+    (!header.is_empty()).then(|| {
+        req.headers()
+            .get(header)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string()
+    })
+}
 
+/// Parse a GraphQL request that has already been decoded as JSON, e.g.,
+/// the payload of a subscription message
+pub fn parse_graphql_payload(json: &serde_json::Value, trace: bool) -> Result<Query, ServerError> {
     // Ensure the JSON data is an object
     let obj = json
         .as_object()
@@ -26,6 +48,16 @@ pub fn parse_graphql_request(body: &Bytes, trace: bool) -> Result<Query, ServerE
         ServerError::ClientError(String::from("The \"query\" field is not a string"))
     })?;
 
+    parse_query_with_variables(obj, query_string, trace)
+}
+
+/// Parse `query_string` as the query document of the request `obj`, and
+/// combine it with the variables from `obj`
+pub fn parse_query_with_variables(
+    obj: &serde_json::Map<String, serde_json::Value>,
+    query_string: &str,
+    trace: bool,
+) -> Result<Query, ServerError> {
     // Parse the "query" field of the JSON body
     let document = q::parse_query(query_string)
         .map_err(|e| ServerError::from(QueryError::ParseError(Arc::new(e.into()))))?
@@ -42,7 +74,9 @@ pub fn parse_graphql_request(body: &Bytes, trace: bool) -> Result<Query, ServerE
         )),
     }?;
 
-    Ok(Query::new(document, variables, trace))
+    let mut query = Query::new(document, variables, trace);
+    query.hash = Some(persisted_query_hash(query_string));
+    Ok(query)
 }
 
 #[cfg(test)]
@@ -59,7 +93,13 @@ mod tests {
         prelude::*,
     };
 
-    use super::parse_graphql_request;
+    use graph::components::server::query::ServerError;
+
+    use super::{parse_graphql_payload, parse_request_body};
+
+    fn parse_graphql_request(body: &Bytes, trace: bool) -> Result<Query, ServerError> {
+        parse_graphql_payload(&parse_request_body(body)?, trace)
+    }
 
     lazy_static! {
         static ref TARGET: QueryTarget = QueryTarget::Name(
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use graph::anyhow;
 use graph::cheap_clone::CheapClone;
 use graph::components::server::server::{start, ServerHandle};
+use graph::components::store::SubscriptionManager;
 use graph::log::factory::{ComponentLoggerConfig, ElasticComponentLoggerConfig};
 use graph::slog::info;
 
@@ -13,11 +14,16 @@ use graph::prelude::{GraphQlRunner, Logger, LoggerFactory};
 pub struct GraphQLServer<Q> {
     logger: Logger,
     graphql_runner: Arc<Q>,
+    subscription_manager: Arc<dyn SubscriptionManager>,
 }
 
 impl<Q: GraphQlRunner> GraphQLServer<Q> {
     /// Creates a new GraphQL server.
-    pub fn new(logger_factory: &LoggerFactory, graphql_runner: Arc<Q>) -> Self {
+    pub fn new(
+        logger_factory: &LoggerFactory,
+        graphql_runner: Arc<Q>,
+        subscription_manager: Arc<dyn SubscriptionManager>,
+    ) -> Self {
         let logger = logger_factory.component_logger(
             "GraphQLServer",
             Some(ComponentLoggerConfig {
@@ -29,6 +35,7 @@ impl<Q: GraphQlRunner> GraphQLServer<Q> {
         GraphQLServer {
             logger,
             graphql_runner,
+            subscription_manager,
         }
     }
 
@@ -42,7 +49,11 @@ impl<Q: GraphQlRunner> GraphQLServer<Q> {
 
         let graphql_runner = self.graphql_runner.clone();
 
-        let service = Arc::new(GraphQLService::new(logger.clone(), graphql_runner));
+        let service = Arc::new(GraphQLService::new(
+            logger.clone(),
+            graphql_runner,
+            self.subscription_manager.cheap_clone(),
+        ));
 
         start(logger, port, move |req| {
             let service = service.cheap_clone();
@@ -7,6 +7,7 @@ use graph::cheap_clone::CheapClone;
 use graph::components::graphql::GraphQlRunner;
 use graph::components::server::query::ServerResponse;
 use graph::components::server::query::ServerResult;
+use graph::components::store::SubscriptionManager;
 use graph::components::versions::ApiVersion;
 use graph::data::query::QueryResult;
 use graph::data::subgraph::DeploymentHash;
@@ -27,7 +28,9 @@ use graph::slog::Logger;
 use graph::url::form_urlencoded;
 use graph::{components::server::query::ServerError, data::query::QueryTarget};
 
-use crate::request::parse_graphql_request;
+use crate::persisted::PersistedQueries;
+use crate::request::{client_id, parse_request_body};
+use crate::subscription::{self, Subscriptions};
 
 fn client_error(msg: impl Into<String>) -> ServerResponse {
     let response_obj = json!({
@@ -43,11 +46,14 @@ fn client_error(msg: impl Into<String>) -> ServerResponse {
         .unwrap()
 }
 
-/// A Hyper Service that serves GraphQL over a POST / endpoint.
+/// A Hyper Service that serves GraphQL over a POST / endpoint, and
+/// GraphQL subscriptions over WebSocket connections to the same endpoint.
 #[derive(Debug)]
 pub struct GraphQLService<Q> {
     logger: Logger,
     graphql_runner: Arc<Q>,
+    subscriptions: Arc<Subscriptions<Q>>,
+    persisted_queries: Arc<PersistedQueries>,
 }
 
 impl<Q> GraphQLService<Q>
@@ -55,10 +61,23 @@ where
     Q: GraphQlRunner,
 {
     /// Creates a new GraphQL service.
-    pub fn new(logger: Logger, graphql_runner: Arc<Q>) -> Self {
+    pub fn new(
+        logger: Logger,
+        graphql_runner: Arc<Q>,
+        subscription_manager: Arc<dyn SubscriptionManager>,
+    ) -> Self {
+        let persisted_queries = Arc::new(PersistedQueries::new());
+        let subscriptions = Arc::new(Subscriptions::new(
+            logger.clone(),
+            graphql_runner.cheap_clone(),
+            persisted_queries.cheap_clone(),
+            subscription_manager,
+        ));
         GraphQLService {
             logger,
             graphql_runner,
+            subscriptions,
+            persisted_queries,
         }
     }
 
@@ -147,6 +166,31 @@ where
             .await
     }
 
+    fn handle_subscription<T>(&self, target: QueryTarget, request: Request<T>) -> ServerResult {
+        self.subscriptions.upgrade(target, request)
+    }
+
+    fn handle_subscription_by_name<T>(
+        &self,
+        subgraph_name: String,
+        request: Request<T>,
+    ) -> ServerResult {
+        let version = self.resolve_api_version(&request)?;
+        let subgraph_name = SubgraphName::new(subgraph_name.as_str()).map_err(|()| {
+            ServerError::ClientError(format!("Invalid subgraph name {:?}", subgraph_name))
+        })?;
+
+        self.handle_subscription(QueryTarget::Name(subgraph_name, version), request)
+    }
+
+    fn handle_subscription_by_id<T>(&self, id: String, request: Request<T>) -> ServerResult {
+        let id = DeploymentHash::new(id)
+            .map_err(|id| ServerError::ClientError(format!("Invalid subgraph id `{}`", id)))?;
+        let version = self.resolve_api_version(&request)?;
+
+        self.handle_subscription(QueryTarget::Deployment(id, version), request)
+    }
+
     async fn handle_graphql_query<T: Body>(
         &self,
         target: QueryTarget,
@@ -165,16 +209,30 @@ where
                     })
                     .unwrap_or(false)
         };
+        let client = client_id(&request);
         let body = request
             .collect()
             .await
             .map_err(|_| ServerError::InternalError("Failed to read request body".into()))?
             .to_bytes();
-        let query = parse_graphql_request(&body, trace);
+        let query = match parse_request_body(&body) {
+            Ok(json) => {
+                self.persisted_queries
+                    .parse_request(
+                        self.graphql_runner.cheap_clone(),
+                        target.clone(),
+                        &json,
+                        trace,
+                    )
+                    .await
+            }
+            Err(e) => Err(e),
+        };
         let query_parsing_time = start.elapsed();
 
         let mut result = match query {
-            Ok(query) => {
+            Ok(mut query) => {
+                query.client = client;
                 self.graphql_runner
                     .cheap_clone()
                     .run_query(query, target)
@@ -310,8 +368,16 @@ where
             .trim()
             .to_lowercase()
             .starts_with("mutation");
+        let is_upgrade = subscription::is_upgrade(&req);
         match (method, path_segments.as_slice()) {
             (Method::GET, [""]) => self.index().await,
+            (Method::GET, &["subgraphs", "id", subgraph_id]) if is_upgrade => {
+                self.handle_subscription_by_id(subgraph_id.to_owned(), req)
+            }
+            (Method::GET, path @ ["subgraphs", "name", ..]) if is_upgrade => {
+                let subgraph_name = filter_and_join_segments(&path[2..]);
+                self.handle_subscription_by_name(subgraph_name, req)
+            }
             (Method::GET, &["subgraphs", "id", _, "graphql"])
             | (Method::GET, &["subgraphs", "name", .., "graphql"])
             | (Method::GET, &["subgraphs", "network", _, _, "graphql"])
@@ -402,10 +468,10 @@ mod tests {
     use graph::hyper::{Method, Request, StatusCode};
     use graph::prelude::serde_json::json;
 
-    use graph::data::query::{QueryResults, QueryTarget};
+    use graph::data::query::{persisted_query_hash, QueryResults, QueryTarget};
     use graph::prelude::*;
 
-    use crate::test_utils;
+    use crate::test_utils::{self, TestSubscriptionManager};
 
     use super::GraphQLService;
 
@@ -456,7 +522,8 @@ mod tests {
         let logger = Logger::root(slog::Discard, o!());
         let graphql_runner = Arc::new(TestGraphQlRunner);
 
-        let service = GraphQLService::new(logger, graphql_runner);
+        let service =
+            GraphQLService::new(logger, graphql_runner, Arc::new(TestSubscriptionManager));
 
         let request: Request<Full<Bytes>> = Request::builder()
             .method(Method::GET)
@@ -488,7 +555,8 @@ mod tests {
         let subgraph_id = USERS.clone();
         let graphql_runner = Arc::new(TestGraphQlRunner);
 
-        let service = GraphQLService::new(logger, graphql_runner);
+        let service =
+            GraphQLService::new(logger, graphql_runner, Arc::new(TestSubscriptionManager));
 
         let request: Request<Full<Bytes>> = Request::builder()
             .method(Method::POST)
@@ -520,7 +588,8 @@ mod tests {
         let subgraph_id = USERS.clone();
         let graphql_runner = Arc::new(TestGraphQlRunner);
 
-        let service = GraphQLService::new(logger, graphql_runner);
+        let service =
+            GraphQLService::new(logger, graphql_runner, Arc::new(TestSubscriptionManager));
 
         let request: Request<Full<Bytes>> = Request::builder()
             .method(Method::POST)
@@ -546,4 +615,61 @@ mod tests {
             .expect("Query result field \"name\" is not a string");
         assert_eq!(name, "Jordi".to_string());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn posting_persisted_query_hash() {
+        let logger = Logger::root(slog::Discard, o!());
+        let subgraph_id = USERS.clone();
+        let graphql_runner = Arc::new(TestGraphQlRunner);
+
+        let service =
+            GraphQLService::new(logger, graphql_runner, Arc::new(TestSubscriptionManager));
+
+        let request = |body: serde_json::Value| -> Request<Full<Bytes>> {
+            Request::builder()
+                .method(Method::POST)
+                .header(CONTENT_TYPE, "application/json")
+                .uri(format!(
+                    "http://localhost:8000/subgraphs/id/{}",
+                    subgraph_id
+                ))
+                .body(Full::from(body.to_string()))
+                .unwrap()
+        };
+        let hash = persisted_query_hash("{ name }");
+        let extensions = json!({ "persistedQuery": { "version": 1, "sha256Hash": hash } });
+
+        // The query node has not seen the query yet
+        let response = service
+            .call(request(json!({ "extensions": extensions })))
+            .await;
+        let errors = test_utils::assert_error_response(response, StatusCode::OK, true).await;
+        assert_eq!(errors[0]["message"], "PersistedQueryNotFound");
+
+        // A hash that does not match the query is rejected
+        let response = service
+            .call(request(
+                json!({ "query": "{ other }", "extensions": extensions }),
+            ))
+            .await;
+        let errors = test_utils::assert_error_response(response, StatusCode::OK, true).await;
+        assert!(errors[0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("does not have the sha256 hash"));
+
+        // Sending the query with its hash runs it and remembers it
+        let response = service
+            .call(request(
+                json!({ "query": "{ name }", "extensions": extensions }),
+            ))
+            .await;
+        test_utils::assert_successful_response(response).await;
+
+        let response = service
+            .call(request(json!({ "extensions": extensions })))
+            .await;
+        let data = test_utils::assert_successful_response(response).await;
+        assert_eq!(data["name"], "Jordi");
+    }
 }
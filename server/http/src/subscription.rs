@@ -0,0 +1,842 @@
+//! GraphQL subscriptions over WebSocket. Both the `graphql-transport-ws`
+//! protocol and the older `graphql-ws` protocol of
+//! `subscriptions-transport-ws` are supported.
+//!
+//! A subscription is executed as an ordinary query. It is rerun whenever
+//! the store reports a change to one of the entity types that the query
+//! selects, and its result is sent to the client whenever it differs from
+//! the result that was sent last.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use graph::cheap_clone::CheapClone;
+use graph::components::graphql::GraphQlRunner;
+use graph::components::server::query::{ServerError, ServerResult};
+use graph::components::store::{StoreEvent, SubscriptionManager};
+use graph::data::query::{Query, QueryError, QueryExecutionError, QueryTarget};
+use graph::futures03::compat::Stream01CompatExt;
+use graph::futures03::{SinkExt, StreamExt};
+use graph::http_body_util::Full;
+use graph::hyper::header::{
+    HeaderValue, CONNECTION, SEC_WEBSOCKET_ACCEPT, SEC_WEBSOCKET_KEY, SEC_WEBSOCKET_PROTOCOL,
+    SEC_WEBSOCKET_VERSION, UPGRADE,
+};
+use graph::hyper::{Request, Response, StatusCode};
+use graph::hyper_util::rt::TokioIo;
+use graph::prelude::serde_json::{self, json};
+use graph::prelude::{q, Deserialize, ENV_VARS};
+use graph::slog::{debug, warn, Logger};
+use graph::tokio::sync::{broadcast, mpsc};
+use graph::tokio::task::AbortHandle;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, Role};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::persisted::PersistedQueries;
+use crate::request::client_id;
+
+/// How many store events can be buffered for a subscription before it
+/// misses events; a subscription that missed events is rerun
+const EVENT_BUFFER: usize = 1000;
+
+/// How many messages can be queued up for a client before subscriptions
+/// wait for the client to catch up
+const OUTGOING_BUFFER: usize = 16;
+
+/// The WebSocket subprotocols we support
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Protocol {
+    /// The `graphql-transport-ws` protocol of the `graphql-ws` library
+    TransportWs,
+    /// The legacy `graphql-ws` protocol of `subscriptions-transport-ws`
+    Ws,
+}
+
+impl Protocol {
+    /// Pick the first protocol we support from the comma separated list in
+    /// a `Sec-WebSocket-Protocol` header
+    fn negotiate(header: &str) -> Option<Protocol> {
+        header
+            .split(',')
+            .map(str::trim)
+            .find_map(|protocol| match protocol {
+                "graphql-transport-ws" => Some(Protocol::TransportWs),
+                "graphql-ws" => Some(Protocol::Ws),
+                _ => None,
+            })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Protocol::TransportWs => "graphql-transport-ws",
+            Protocol::Ws => "graphql-ws",
+        }
+    }
+}
+
+/// A message from the client. The variants cover the messages of both
+/// protocols
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    ConnectionInit,
+    Ping,
+    Pong,
+    /// `graphql-transport-ws`
+    Subscribe {
+        id: String,
+        payload: serde_json::Value,
+    },
+    /// `graphql-ws`
+    Start {
+        id: String,
+        payload: serde_json::Value,
+    },
+    /// `graphql-transport-ws`
+    Complete {
+        id: String,
+    },
+    /// `graphql-ws`
+    Stop {
+        id: String,
+    },
+    /// `graphql-ws`
+    ConnectionTerminate,
+}
+
+/// A message to the client
+#[derive(Debug)]
+enum ServerMessage {
+    ConnectionAck,
+    Pong,
+    Next {
+        id: String,
+        payload: serde_json::Value,
+    },
+    Error {
+        id: String,
+        errors: Vec<QueryError>,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+impl ServerMessage {
+    fn into_message(self, protocol: Protocol) -> Message {
+        let value = match self {
+            ServerMessage::ConnectionAck => json!({ "type": "connection_ack" }),
+            ServerMessage::Pong => json!({ "type": "pong" }),
+            ServerMessage::Next { id, payload } => {
+                let ty = match protocol {
+                    Protocol::TransportWs => "next",
+                    Protocol::Ws => "data",
+                };
+                json!({ "type": ty, "id": id, "payload": payload })
+            }
+            ServerMessage::Error { id, errors } => {
+                let errors = serde_json::to_value(&errors).unwrap_or_default();
+                let payload = match protocol {
+                    Protocol::TransportWs => errors,
+                    Protocol::Ws => json!({ "errors": errors }),
+                };
+                json!({ "type": "error", "id": id, "payload": payload })
+            }
+            ServerMessage::Complete { id } => json!({ "type": "complete", "id": id }),
+        };
+        Message::text(value.to_string())
+    }
+
+    fn error(id: String, errors: Vec<QueryError>) -> Self {
+        ServerMessage::Error { id, errors }
+    }
+
+    fn client_error(id: String, msg: impl Into<String>) -> Self {
+        let error = QueryExecutionError::NotSupported(msg.into());
+        ServerMessage::error(id, vec![error.into()])
+    }
+}
+
+fn close(code: u16, reason: &str) -> Message {
+    Message::Close(Some(CloseFrame {
+        code: CloseCode::from(code),
+        reason: reason.to_string().into(),
+    }))
+}
+
+/// Turn a `subscription` operation into a `query` operation so that it can
+/// be run like any other query
+fn into_query(query: Query) -> Query {
+    let Query {
+        document,
+        variables,
+        trace,
+        hash,
+        client,
+        ..
+    } = query;
+    let definitions = document
+        .definitions
+        .into_iter()
+        .map(|defn| match defn {
+            q::Definition::Operation(q::OperationDefinition::Subscription(sub)) => {
+                q::Definition::Operation(q::OperationDefinition::Query(q::Query {
+                    position: sub.position,
+                    name: sub.name,
+                    variable_definitions: sub.variable_definitions,
+                    directives: sub.directives,
+                    selection_set: sub.selection_set,
+                }))
+            }
+            defn => defn,
+        })
+        .collect();
+    let mut query = Query::new(q::Document { definitions }, variables, trace);
+    query.hash = hash;
+    query.client = client;
+    query
+}
+
+/// Return `true` if `req` asks for an upgrade to a WebSocket connection
+pub(crate) fn is_upgrade<T>(req: &Request<T>) -> bool {
+    req.headers()
+        .get(UPGRADE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.eq_ignore_ascii_case("websocket"))
+        .unwrap_or(false)
+}
+
+/// Decrements the number of open connections when the connection ends
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Accepts WebSocket connections and runs the subscriptions on them
+pub(crate) struct Subscriptions<Q> {
+    logger: Logger,
+    graphql_runner: Arc<Q>,
+    persisted_queries: Arc<PersistedQueries>,
+    events: broadcast::Sender<Arc<StoreEvent>>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl<Q> fmt::Debug for Subscriptions<Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscriptions")
+            .field("connections", &self.connections)
+            .finish()
+    }
+}
+
+impl<Q: GraphQlRunner> Subscriptions<Q> {
+    pub fn new(
+        logger: Logger,
+        graphql_runner: Arc<Q>,
+        persisted_queries: Arc<PersistedQueries>,
+        subscription_manager: Arc<dyn SubscriptionManager>,
+    ) -> Self {
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+
+        // The subscription manager waits for each of its subscribers to
+        // accept an event, and we therefore need to take events off the
+        // stream as quickly as possible and leave it to each subscription
+        // to keep up
+        let sender = events.clone();
+        let mut store_events = subscription_manager.subscribe().compat();
+        graph::spawn(async move {
+            while let Some(Ok(event)) = store_events.next().await {
+                // An error only means that there are no subscriptions
+                let _ = sender.send(event);
+            }
+        });
+
+        Subscriptions {
+            logger,
+            graphql_runner,
+            persisted_queries,
+            events,
+            connections: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn reserve_slot(&self) -> Option<ConnectionSlot> {
+        let max = ENV_VARS.graphql.max_ws_connections;
+        self.connections
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| ConnectionSlot(self.connections.cheap_clone()))
+    }
+
+    /// Complete the WebSocket handshake for `req` and serve subscriptions
+    /// against `target` on the connection
+    pub fn upgrade<T>(self: &Arc<Self>, target: QueryTarget, mut req: Request<T>) -> ServerResult {
+        let headers = req.headers();
+        if headers.get(SEC_WEBSOCKET_VERSION) != Some(&HeaderValue::from_static("13")) {
+            return Err(ServerError::ClientError(
+                "Only version 13 of the WebSocket protocol is supported".to_string(),
+            ));
+        }
+        let key = headers.get(SEC_WEBSOCKET_KEY).ok_or_else(|| {
+            ServerError::ClientError("The Sec-WebSocket-Key header is missing".to_string())
+        })?;
+        let accept = derive_accept_key(key.as_bytes());
+        // All subscriptions on the connection are charged to the client
+        // that opened it
+        let client = client_id(&req);
+        let protocol = headers
+            .get(SEC_WEBSOCKET_PROTOCOL)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Protocol::negotiate)
+            .ok_or_else(|| {
+                ServerError::ClientError(
+                    "The Sec-WebSocket-Protocol header must contain \
+                     `graphql-transport-ws` or `graphql-ws`"
+                        .to_string(),
+                )
+            })?;
+
+        let slot = match self.reserve_slot() {
+            Some(slot) => slot,
+            None => {
+                return Ok(Response::builder()
+                    .status(StatusCode::SERVICE_UNAVAILABLE)
+                    .body(Full::from("Too many WebSocket connections"))
+                    .unwrap())
+            }
+        };
+
+        let on_upgrade = graph::hyper::upgrade::on(&mut req);
+        let this = self.cheap_clone();
+        graph::spawn(async move {
+            match on_upgrade.await {
+                Ok(upgraded) => {
+                    let ws = WebSocketStream::from_raw_socket(
+                        TokioIo::new(upgraded),
+                        Role::Server,
+                        None,
+                    )
+                    .await;
+                    this.serve(protocol, target, client, ws).await;
+                }
+                Err(e) => {
+                    warn!(this.logger, "WebSocket upgrade failed"; "error" => e.to_string())
+                }
+            }
+            drop(slot);
+        });
+
+        Ok(Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header(UPGRADE, "websocket")
+            .header(CONNECTION, "Upgrade")
+            .header(SEC_WEBSOCKET_ACCEPT, accept)
+            .header(SEC_WEBSOCKET_PROTOCOL, protocol.name())
+            .body(Full::from(""))
+            .unwrap())
+    }
+
+    async fn serve<S>(
+        self: Arc<Self>,
+        protocol: Protocol,
+        target: QueryTarget,
+        client: Option<String>,
+        ws: WebSocketStream<S>,
+    ) where
+        S: graph::tokio::io::AsyncRead + graph::tokio::io::AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut sink, mut stream) = ws.split();
+        let (sender, mut receiver) = mpsc::channel::<Message>(OUTGOING_BUFFER);
+
+        let writer = graph::spawn(async move {
+            while let Some(msg) = receiver.recv().await {
+                let is_close = matches!(msg, Message::Close(_));
+                if sink.send(msg).await.is_err() || is_close {
+                    break;
+                }
+            }
+            let _ = sink.close().await;
+        });
+
+        let mut conn = Connection {
+            subscriptions: self.cheap_clone(),
+            protocol,
+            target,
+            client,
+            acknowledged: false,
+            running: HashMap::new(),
+            sender,
+        };
+
+        while let Some(msg) = stream.next().await {
+            let text = match msg {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => break,
+                Ok(Message::Ping(_)) | Ok(Message::Pong(_)) | Ok(Message::Frame(_)) => continue,
+                Ok(Message::Binary(_)) => {
+                    conn.send(close(4400, "Binary messages are not supported"))
+                        .await;
+                    break;
+                }
+                Err(e) => {
+                    debug!(self.logger, "WebSocket connection failed"; "error" => e.to_string());
+                    break;
+                }
+            };
+            if !conn.handle(text.as_str()).await {
+                break;
+            }
+        }
+
+        conn.stop_all();
+        drop(conn);
+        let _ = writer.await;
+    }
+}
+
+/// The state of one WebSocket connection
+struct Connection<Q> {
+    subscriptions: Arc<Subscriptions<Q>>,
+    protocol: Protocol,
+    target: QueryTarget,
+    /// The client that opened the connection, see `request::client_id`
+    client: Option<String>,
+    acknowledged: bool,
+    running: HashMap<String, AbortHandle>,
+    sender: mpsc::Sender<Message>,
+}
+
+impl<Q: GraphQlRunner> Connection<Q> {
+    async fn send(&self, msg: Message) {
+        // If this fails, the connection is closing anyway
+        let _ = self.sender.send(msg).await;
+    }
+
+    async fn reply(&self, msg: ServerMessage) {
+        self.send(msg.into_message(self.protocol)).await
+    }
+
+    /// Handle a message from the client. Return `false` if the connection
+    /// should be closed
+    async fn handle(&mut self, text: &str) -> bool {
+        let msg = match serde_json::from_str::<ClientMessage>(text) {
+            Ok(msg) => msg,
+            Err(e) => {
+                self.send(close(4400, &format!("Invalid message: {}", e)))
+                    .await;
+                return false;
+            }
+        };
+
+        match msg {
+            ClientMessage::ConnectionInit => {
+                if self.acknowledged && self.protocol == Protocol::TransportWs {
+                    self.send(close(4429, "Too many initialisation requests"))
+                        .await;
+                    return false;
+                }
+                self.acknowledged = true;
+                self.reply(ServerMessage::ConnectionAck).await;
+            }
+            ClientMessage::Ping => self.reply(ServerMessage::Pong).await,
+            ClientMessage::Pong => {}
+            ClientMessage::Subscribe { id, payload } | ClientMessage::Start { id, payload } => {
+                if !self.acknowledged {
+                    self.send(close(4401, "Unauthorized")).await;
+                    return false;
+                }
+                self.running.retain(|_, handle| !handle.is_finished());
+                if self.running.contains_key(&id) {
+                    self.send(close(
+                        4409,
+                        &format!("Subscriber for {} already exists", id),
+                    ))
+                    .await;
+                    return false;
+                }
+                let max = ENV_VARS.graphql.max_subscriptions_per_connection;
+                if self.running.len() >= max {
+                    let msg = format!("a connection can have at most {} active subscriptions", max);
+                    self.reply(ServerMessage::client_error(id, msg)).await;
+                    return true;
+                }
+                // Resolve persisted queries the same way as for queries
+                // over HTTP
+                let query = self
+                    .subscriptions
+                    .persisted_queries
+                    .parse_request(
+                        self.subscriptions.graphql_runner.cheap_clone(),
+                        self.target.clone(),
+                        &payload,
+                        false,
+                    )
+                    .await;
+                match query {
+                    Ok(mut query) => {
+                        query.client = self.client.clone();
+                        self.start(id, into_query(query))
+                    }
+                    Err(ServerError::QueryError(e)) => {
+                        self.reply(ServerMessage::error(id, vec![e])).await
+                    }
+                    Err(e) => {
+                        self.reply(ServerMessage::client_error(id, e.to_string()))
+                            .await
+                    }
+                }
+            }
+            ClientMessage::Complete { id } | ClientMessage::Stop { id } => {
+                if let Some(handle) = self.running.remove(&id) {
+                    handle.abort();
+                }
+            }
+            ClientMessage::ConnectionTerminate => return false,
+        }
+        true
+    }
+
+    fn start(&mut self, id: String, query: Query) {
+        let subscription = Subscription {
+            graphql_runner: self.subscriptions.graphql_runner.cheap_clone(),
+            events: self.subscriptions.events.subscribe(),
+            target: self.target.clone(),
+            protocol: self.protocol,
+            id: id.clone(),
+            query,
+            sender: self.sender.clone(),
+        };
+        let handle = graph::spawn(subscription.run()).abort_handle();
+        self.running.insert(id, handle);
+    }
+
+    fn stop_all(&mut self) {
+        for (_, handle) in self.running.drain() {
+            handle.abort();
+        }
+    }
+}
+
+/// A running subscription
+struct Subscription<Q> {
+    graphql_runner: Arc<Q>,
+    events: broadcast::Receiver<Arc<StoreEvent>>,
+    target: QueryTarget,
+    protocol: Protocol,
+    id: String,
+    query: Query,
+    sender: mpsc::Sender<Message>,
+}
+
+impl<Q: GraphQlRunner> Subscription<Q> {
+    async fn send(&self, msg: ServerMessage) -> bool {
+        self.sender
+            .send(msg.into_message(self.protocol))
+            .await
+            .is_ok()
+    }
+
+    async fn run(mut self) {
+        let sub_target = match self
+            .graphql_runner
+            .cheap_clone()
+            .subscription_target(self.query.clone(), self.target.clone())
+            .await
+        {
+            Ok(sub_target) => sub_target,
+            Err(errors) => {
+                let errors = errors.into_iter().map(QueryError::from).collect();
+                self.send(ServerMessage::error(self.id.clone(), errors))
+                    .await;
+                return;
+            }
+        };
+
+        let max_size = ENV_VARS.graphql.max_subscription_result_size;
+        let mut last = None;
+        loop {
+            let result = self
+                .graphql_runner
+                .cheap_clone()
+                .run_query(self.query.clone(), self.target.clone())
+                .await;
+            let payload = serde_json::to_value(&result).unwrap_or_default();
+            if last.as_ref() != Some(&payload) {
+                if payload.to_string().len() > max_size {
+                    let msg = format!(
+                        "the result of the subscription is larger than the maximum of {} bytes",
+                        max_size
+                    );
+                    self.send(ServerMessage::client_error(self.id.clone(), msg))
+                        .await;
+                    return;
+                }
+                let next = ServerMessage::Next {
+                    id: self.id.clone(),
+                    payload: payload.clone(),
+                };
+                if !self.send(next).await {
+                    return;
+                }
+                last = Some(payload);
+            }
+
+            // Wait for a change that might affect the result
+            loop {
+                match self.events.recv().await {
+                    Ok(event) if sub_target.is_affected_by(&event) => break,
+                    Ok(_) => {}
+                    // We don't know what we missed, rerun to be safe
+                    Err(broadcast::error::RecvError::Lagged(_)) => break,
+                    Err(broadcast::error::RecvError::Closed) => {
+                        self.send(ServerMessage::Complete {
+                            id: self.id.clone(),
+                        })
+                        .await;
+                        return;
+                    }
+                }
+            }
+            // Changes that arrived in the meantime are covered by rerunning
+            // the query now
+            while !matches!(
+                self.events.try_recv(),
+                Err(broadcast::error::TryRecvError::Empty)
+                    | Err(broadcast::error::TryRecvError::Closed)
+            ) {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use graph::components::graphql::{GraphQLMetrics, GraphQlRunner, SubscriptionTarget};
+    use graph::data::query::{
+        persisted_query_hash, Query, QueryExecutionError, QueryResults, QueryTarget,
+        RegisteredQueries,
+    };
+    use graph::prelude::{async_trait, q, serde_json::json, DeploymentHash};
+    use graph::slog::{o, Discard, Logger};
+    use graph::tokio::sync::mpsc;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use crate::persisted::PersistedQueries;
+    use crate::request::parse_graphql_payload;
+    use crate::test_utils::TestSubscriptionManager;
+
+    use super::{into_query, ClientMessage, Connection, Protocol, ServerMessage, Subscriptions};
+
+    const REGISTERED: &str = "subscription { things { id } }";
+
+    /// A runner that knows one registered query and remembers the
+    /// subscriptions that were started
+    #[derive(Default)]
+    struct RegisteredRunner {
+        started: Mutex<Vec<Query>>,
+    }
+
+    #[async_trait]
+    impl GraphQlRunner for RegisteredRunner {
+        async fn run_query(self: Arc<Self>, _query: Query, _target: QueryTarget) -> QueryResults {
+            unimplemented!()
+        }
+
+        async fn run_query_with_complexity(
+            self: Arc<Self>,
+            _query: Query,
+            _target: QueryTarget,
+            _max_complexity: Option<u64>,
+            _max_depth: Option<u8>,
+            _max_first: Option<u32>,
+            _max_skip: Option<u32>,
+        ) -> QueryResults {
+            unimplemented!()
+        }
+
+        async fn subscription_target(
+            self: Arc<Self>,
+            query: Query,
+            _target: QueryTarget,
+        ) -> Result<SubscriptionTarget, Vec<QueryExecutionError>> {
+            self.started.lock().unwrap().push(query);
+            Err(vec![QueryExecutionError::NotSupported("done".to_string())])
+        }
+
+        async fn registered_queries(
+            self: Arc<Self>,
+            _target: QueryTarget,
+        ) -> Result<Arc<RegisteredQueries>, QueryExecutionError> {
+            let queries =
+                HashMap::from([(persisted_query_hash(REGISTERED), REGISTERED.to_string())]);
+            Ok(Arc::new(RegisteredQueries {
+                only_registered: true,
+                queries,
+            }))
+        }
+
+        fn metrics(&self) -> Arc<dyn GraphQLMetrics> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn negotiates_protocol() {
+        assert_eq!(
+            Some(Protocol::TransportWs),
+            Protocol::negotiate("graphql-transport-ws")
+        );
+        assert_eq!(
+            Some(Protocol::Ws),
+            Protocol::negotiate("chat, graphql-ws, graphql-transport-ws")
+        );
+        assert_eq!(None, Protocol::negotiate("chat"));
+    }
+
+    #[test]
+    fn parses_client_messages() {
+        let msg: ClientMessage =
+            serde_json::from_str(r#"{"type":"connection_init","payload":{"token":"x"}}"#).unwrap();
+        assert_eq!(ClientMessage::ConnectionInit, msg);
+
+        let msg: ClientMessage = serde_json::from_str(
+            r#"{"type":"subscribe","id":"1","payload":{"query":"subscription { things { id } }"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            ClientMessage::Subscribe {
+                id: "1".to_string(),
+                payload: json!({ "query": "subscription { things { id } }" })
+            },
+            msg
+        );
+
+        let msg: ClientMessage = serde_json::from_str(r#"{"type":"stop","id":"1"}"#).unwrap();
+        assert_eq!(
+            ClientMessage::Stop {
+                id: "1".to_string()
+            },
+            msg
+        );
+
+        assert!(serde_json::from_str::<ClientMessage>(r#"{"type":"bogus"}"#).is_err());
+    }
+
+    #[test]
+    fn formats_server_messages() {
+        let next = || ServerMessage::Next {
+            id: "1".to_string(),
+            payload: json!({ "data": { "things": [] } }),
+        };
+        let text = |msg: ServerMessage, protocol| {
+            let msg = msg.into_message(protocol);
+            serde_json::from_str::<serde_json::Value>(msg.to_text().unwrap()).unwrap()
+        };
+
+        assert_eq!(
+            json!({ "type": "next", "id": "1", "payload": { "data": { "things": [] } } }),
+            text(next(), Protocol::TransportWs)
+        );
+        assert_eq!(
+            json!({ "type": "data", "id": "1", "payload": { "data": { "things": [] } } }),
+            text(next(), Protocol::Ws)
+        );
+    }
+
+    #[test]
+    fn turns_subscriptions_into_queries() {
+        let payload = json!({
+            "query": "subscription Things($first: Int) { things(first: $first) { id } }",
+            "variables": { "first": 5 }
+        });
+        let query = into_query(parse_graphql_payload(&payload, false).unwrap());
+
+        assert_eq!(1, query.document.definitions.len());
+        match &query.document.definitions[0] {
+            q::Definition::Operation(q::OperationDefinition::Query(query)) => {
+                assert_eq!(Some("Things"), query.name.as_deref());
+                assert_eq!(1, query.variable_definitions.len());
+            }
+            defn => panic!("expected a query but got {:?}", defn),
+        }
+        assert!(query.variables.is_some());
+    }
+
+    /// Send `msg` over `conn` and return the message it replies with
+    async fn reply(
+        conn: &mut Connection<RegisteredRunner>,
+        receiver: &mut mpsc::Receiver<Message>,
+        msg: serde_json::Value,
+    ) -> serde_json::Value {
+        assert!(conn.handle(&msg.to_string()).await);
+        match receiver.recv().await {
+            Some(Message::Text(text)) => serde_json::from_str(text.as_str()).unwrap(),
+            msg => panic!("expected a text message but got {:?}", msg),
+        }
+    }
+
+    #[tokio::test]
+    async fn resolves_persisted_subscriptions() {
+        let runner = Arc::new(RegisteredRunner::default());
+        let subscriptions = Arc::new(Subscriptions::new(
+            Logger::root(Discard, o!()),
+            runner.clone(),
+            Arc::new(PersistedQueries::new()),
+            Arc::new(TestSubscriptionManager),
+        ));
+        let (sender, mut receiver) = mpsc::channel(10);
+        let mut conn = Connection {
+            subscriptions,
+            protocol: Protocol::TransportWs,
+            target: QueryTarget::Deployment(
+                DeploymentHash::new("things").unwrap(),
+                Default::default(),
+            ),
+            client: Some("alice".to_string()),
+            acknowledged: true,
+            running: HashMap::new(),
+            sender,
+        };
+        let subscribe = |id: &str, hash: String| {
+            json!({
+                "type": "subscribe",
+                "id": id,
+                "payload": { "extensions": { "persistedQuery": { "sha256Hash": hash } } }
+            })
+        };
+
+        // An unknown hash is rejected without starting a subscription
+        let unknown = persisted_query_hash("subscription { other { id } }");
+        let msg = reply(&mut conn, &mut receiver, subscribe("1", unknown)).await;
+        assert_eq!("error", msg["type"]);
+        assert_eq!("PersistedQueryNotFound", msg["payload"][0]["message"]);
+        assert!(runner.started.lock().unwrap().is_empty());
+
+        // A registered hash is resolved to its query, and the hash is
+        // passed on so that the runner can check it against the deployment
+        let hash = persisted_query_hash(REGISTERED);
+        let msg = reply(&mut conn, &mut receiver, subscribe("2", hash.clone())).await;
+        assert_eq!("error", msg["type"]);
+        assert_eq!("Not supported: done", msg["payload"][0]["message"]);
+        let started = runner.started.lock().unwrap();
+        assert_eq!(1, started.len());
+        assert_eq!(Some(hash), started[0].hash);
+        assert_eq!(Some("alice"), started[0].client.as_deref());
+        match &started[0].document.definitions[0] {
+            q::Definition::Operation(q::OperationDefinition::Query(_)) => {}
+            defn => panic!("expected a query but got {:?}", defn),
+        }
+    }
+}
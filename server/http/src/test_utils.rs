@@ -1,7 +1,18 @@
+use graph::components::store::{StoreEventStream, StoreEventStreamBox, SubscriptionManager};
+use graph::futures01::stream;
 use graph::http_body_util::{BodyExt, Full};
 use graph::hyper::{body::Bytes, header::ACCESS_CONTROL_ALLOW_ORIGIN, Response, StatusCode};
 use graph::prelude::serde_json;
 
+/// A subscription manager that never sends any store events
+pub struct TestSubscriptionManager;
+
+impl SubscriptionManager for TestSubscriptionManager {
+    fn subscribe(&self) -> StoreEventStreamBox {
+        StoreEventStream::new(Box::new(stream::empty()))
+    }
+}
+
 /// Asserts that the response is a successful GraphQL response; returns its `"data"` field.
 pub async fn assert_successful_response(
     response: Response<Full<Bytes>>,
@@ -6,6 +6,7 @@ use graph::data::{
     value::{Object, Word},
 };
 use graph::prelude::*;
+use graph_server_http::test_utils::TestSubscriptionManager;
 use graph_server_http::GraphQLServer as HyperGraphQLServer;
 
 use tokio::time::sleep;
@@ -163,7 +164,11 @@ mod test {
         let logger_factory = LoggerFactory::new(logger, None, Arc::new(MetricsRegistry::mock()));
         let id = USERS.clone();
         let query_runner = Arc::new(TestGraphQlRunner);
-        let server = HyperGraphQLServer::new(&logger_factory, query_runner);
+        let server = HyperGraphQLServer::new(
+            &logger_factory,
+            query_runner,
+            Arc::new(TestSubscriptionManager),
+        );
         let server_handle = server
             .start(8007)
             .await
@@ -195,7 +200,11 @@ mod test {
         let logger_factory = LoggerFactory::new(logger, None, Arc::new(MetricsRegistry::mock()));
         let id = USERS.clone();
         let query_runner = Arc::new(TestGraphQlRunner);
-        let server = HyperGraphQLServer::new(&logger_factory, query_runner);
+        let server = HyperGraphQLServer::new(
+            &logger_factory,
+            query_runner,
+            Arc::new(TestSubscriptionManager),
+        );
         let server_handle = server
             .start(8002)
             .await
@@ -267,7 +276,11 @@ mod test {
         let logger_factory = LoggerFactory::new(logger, None, Arc::new(MetricsRegistry::mock()));
         let id = USERS.clone();
         let query_runner = Arc::new(TestGraphQlRunner);
-        let server = HyperGraphQLServer::new(&logger_factory, query_runner);
+        let server = HyperGraphQLServer::new(
+            &logger_factory,
+            query_runner,
+            Arc::new(TestSubscriptionManager),
+        );
         let server_handle = server
             .start(8003)
             .await
@@ -304,7 +317,11 @@ mod test {
         let logger_factory = LoggerFactory::new(logger, None, Arc::new(MetricsRegistry::mock()));
         let id = USERS.clone();
         let query_runner = Arc::new(TestGraphQlRunner);
-        let server = HyperGraphQLServer::new(&logger_factory, query_runner);
+        let server = HyperGraphQLServer::new(
+            &logger_factory,
+            query_runner,
+            Arc::new(TestSubscriptionManager),
+        );
         let server_handle = server
             .start(8005)
             .await
@@ -8,6 +8,7 @@ use web3::types::Address;
 
 use git_testament::{git_testament, CommitKind};
 use graph::blockchain::{Blockchain, BlockchainKind, BlockchainMap};
+use graph::components::metrics::handler_profile::HandlerProfiler;
 use graph::components::store::{BlockPtrForNumber, BlockStore, QueryPermit, Store};
 use graph::components::versions::VERSIONS;
 use graph::data::graphql::{object, IntoValue, ObjectOrInterface, ValueMap};
@@ -101,6 +102,7 @@ pub struct IndexNodeResolver<S: Store> {
     #[allow(dead_code)]
     link_resolver: Arc<dyn LinkResolver>,
     bearer_token: Option<String>,
+    handler_profiler: Arc<HandlerProfiler>,
 }
 
 impl<S: Store> IndexNodeResolver<S> {
@@ -110,6 +112,7 @@ impl<S: Store> IndexNodeResolver<S> {
         link_resolver: Arc<dyn LinkResolver>,
         bearer_token: Option<String>,
         blockchain_map: Arc<BlockchainMap>,
+        handler_profiler: Arc<HandlerProfiler>,
     ) -> Self {
         let logger = logger.new(o!("component" => "IndexNodeResolver"));
 
@@ -119,6 +122,7 @@ impl<S: Store> IndexNodeResolver<S> {
             store,
             link_resolver,
             bearer_token,
+            handler_profiler,
         }
     }
 
@@ -557,6 +561,57 @@ impl<S: Store> IndexNodeResolver<S> {
                 )
                 .await?
             }
+            BlockchainKind::Solana => {
+                let unvalidated_subgraph_manifest =
+                    UnvalidatedSubgraphManifest::<graph_chain_solana::Chain>::resolve(
+                        deployment_hash.clone(),
+                        raw_yaml,
+                        &self.link_resolver,
+                        &self.logger,
+                        max_spec_version,
+                    )
+                    .await?;
+
+                Self::validate_and_extract_features(
+                    &self.store.subgraph_store(),
+                    unvalidated_subgraph_manifest,
+                )
+                .await?
+            }
+            BlockchainKind::Bitcoin => {
+                let unvalidated_subgraph_manifest =
+                    UnvalidatedSubgraphManifest::<graph_chain_bitcoin::Chain>::resolve(
+                        deployment_hash.clone(),
+                        raw_yaml,
+                        &self.link_resolver,
+                        &self.logger,
+                        max_spec_version,
+                    )
+                    .await?;
+
+                Self::validate_and_extract_features(
+                    &self.store.subgraph_store(),
+                    unvalidated_subgraph_manifest,
+                )
+                .await?
+            }
+            BlockchainKind::Cosmos => {
+                let unvalidated_subgraph_manifest =
+                    UnvalidatedSubgraphManifest::<graph_chain_cosmos::Chain>::resolve(
+                        deployment_hash.clone(),
+                        raw_yaml,
+                        &self.link_resolver,
+                        &self.logger,
+                        max_spec_version,
+                    )
+                    .await?;
+
+                Self::validate_and_extract_features(
+                    &self.store.subgraph_store(),
+                    unvalidated_subgraph_manifest,
+                )
+                .await?
+            }
             BlockchainKind::Substreams => {
                 let unvalidated_subgraph_manifest =
                     UnvalidatedSubgraphManifest::<graph_chain_substreams::Chain>::resolve(
@@ -617,6 +672,31 @@ impl<S: Store> IndexNodeResolver<S> {
         Ok(features.into_value())
     }
 
+    fn resolve_handler_profiles(&self, field: &a::Field) -> Result<r::Value, QueryExecutionError> {
+        let subgraph_id = field
+            .get_required::<DeploymentHash>("subgraphId")
+            .expect("Valid subgraphId required");
+
+        Ok(r::Value::List(
+            self.handler_profiler
+                .stats(subgraph_id.as_str())
+                .into_iter()
+                .map(|(handler, stats)| {
+                    object! {
+                        handler: handler,
+                        count: stats.count,
+                        totalTimeMs: stats.wall_time.as_millis() as u64,
+                        maxTimeMs: stats.max_wall_time.as_millis() as u64,
+                        gas: stats.gas,
+                        storeGetCount: stats.store_get_count,
+                        storeGetCacheMisses: stats.store_get_cache_misses,
+                        ethCallCount: stats.eth_call_count,
+                    }
+                })
+                .collect(),
+        ))
+    }
+
     fn resolve_api_versions(&self, _field: &a::Field) -> Result<r::Value, QueryExecutionError> {
         Ok(r::Value::List(
             VERSIONS
@@ -682,6 +762,9 @@ impl<S: Store> IndexNodeResolver<S> {
         try_resolve_for_chain!(graph_chain_ethereum::Chain);
         try_resolve_for_chain!(graph_chain_arweave::Chain);
         try_resolve_for_chain!(graph_chain_near::Chain);
+        try_resolve_for_chain!(graph_chain_solana::Chain);
+        try_resolve_for_chain!(graph_chain_bitcoin::Chain);
+        try_resolve_for_chain!(graph_chain_cosmos::Chain);
 
         // If you're adding support for a new chain and this `match` clause just
         // gave you a compiler error, then this message is for you! You need to
@@ -692,7 +775,10 @@ impl<S: Store> IndexNodeResolver<S> {
             BlockchainKind::Substreams
             | BlockchainKind::Arweave
             | BlockchainKind::Ethereum
-            | BlockchainKind::Near => (),
+            | BlockchainKind::Near
+            | BlockchainKind::Solana
+            | BlockchainKind::Bitcoin
+            | BlockchainKind::Cosmos => (),
         }
 
         // The given network does not exist.
@@ -834,6 +920,7 @@ impl<S: Store> Resolver for IndexNodeResolver<S> {
             (None, "CachedEthereumCall", "cachedEthereumCalls") => {
                 self.resolve_cached_ethereum_calls(field).await
             }
+            (None, "HandlerProfile", "handlerProfiles") => self.resolve_handler_profiles(field),
 
             // The top-level `publicProofsOfIndexing` field
             (None, "PublicProofOfIndexingResult", "publicProofsOfIndexing") => {
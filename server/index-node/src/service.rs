@@ -5,6 +5,7 @@ use graph::blockchain::BlockchainMap;
 use graph::cheap_clone::CheapClone;
 use graph::components::graphql::GraphQLMetrics;
 use graph::components::link_resolver::LinkResolver;
+use graph::components::metrics::handler_profile::HandlerProfiler;
 use graph::components::server::query::{ServerResponse, ServerResult};
 use graph::data::subgraph::DeploymentHash;
 use graph::http_body_util::{BodyExt, Full};
@@ -45,6 +46,7 @@ pub struct IndexNodeService<S> {
     store: Arc<S>,
     explorer: Arc<Explorer<S>>,
     link_resolver: Arc<dyn LinkResolver>,
+    handler_profiler: Arc<HandlerProfiler>,
 }
 
 impl<S> IndexNodeService<S>
@@ -56,6 +58,7 @@ where
         blockchain_map: Arc<BlockchainMap>,
         store: Arc<S>,
         link_resolver: Arc<dyn LinkResolver>,
+        handler_profiler: Arc<HandlerProfiler>,
     ) -> Self {
         let explorer = Arc::new(Explorer::new(store.clone()));
 
@@ -65,6 +68,7 @@ where
             store,
             explorer,
             link_resolver,
+            handler_profiler,
         }
     }
 
@@ -140,6 +144,7 @@ where
                 self.link_resolver.clone(),
                 validated.bearer_token,
                 self.blockchain_map.clone(),
+                self.handler_profiler.cheap_clone(),
             );
             let options = QueryExecutionOptions {
                 resolver,
@@ -0,0 +1 @@
+raise 'This migration is irreversible';
@@ -0,0 +1,2 @@
+drop table subgraphs.persisted_query_setting;
+drop table subgraphs.persisted_query;
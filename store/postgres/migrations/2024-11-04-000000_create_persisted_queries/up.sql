@@ -0,0 +1,16 @@
+create table subgraphs.persisted_query
+(
+    id         int                      not null
+                   references subgraphs.subgraph_deployment(id) on delete cascade,
+    hash       text                     not null,
+    query      text                     not null,
+    created_at timestamp with time zone not null default now(),
+    primary key (id, hash)
+);
+
+create table subgraphs.persisted_query_setting
+(
+    id              int     primary key
+                        references subgraphs.subgraph_deployment(id) on delete cascade,
+    only_registered boolean not null
+);
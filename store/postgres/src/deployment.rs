@@ -16,6 +16,7 @@ use diesel::{
 use graph::semver::Version;
 use graph::{
     blockchain::block_stream::FirehoseCursor,
+    data::query::RegisteredQueries,
     data::subgraph::schema::SubgraphError,
     env::ENV_VARS,
     schema::EntityType,
@@ -207,6 +208,22 @@ table! {
     }
 }
 
+table! {
+    subgraphs.persisted_query (id, hash) {
+        id -> Integer,
+        hash -> Text,
+        query -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    subgraphs.persisted_query_setting (id) {
+        id -> Integer,
+        only_registered -> Bool,
+    }
+}
+
 allow_tables_to_appear_in_same_query!(subgraph_deployment, subgraph_error, subgraph_manifest);
 
 /// Look up the graft point for the given subgraph in the database and
@@ -380,6 +397,102 @@ pub fn set_history_blocks(
         .map_err(StoreError::from)
 }
 
+/// Register the query document `query` under `hash` for the deployment.
+/// Registering a document that is already registered does nothing
+pub fn register_persisted_query(
+    conn: &mut PgConnection,
+    site: &Site,
+    hash: &str,
+    query: &str,
+) -> Result<(), StoreError> {
+    use persisted_query as pq;
+
+    insert_into(pq::table)
+        .values((pq::id.eq(site.id), pq::hash.eq(hash), pq::query.eq(query)))
+        .on_conflict_do_nothing()
+        .execute(conn)
+        .map(|_| ())
+        .map_err(StoreError::from)
+}
+
+/// Remove the query document with `hash` from the registered documents of
+/// the deployment. Return `true` if such a document existed
+pub fn unregister_persisted_query(
+    conn: &mut PgConnection,
+    site: &Site,
+    hash: &str,
+) -> Result<bool, StoreError> {
+    use persisted_query as pq;
+
+    delete(
+        pq::table
+            .filter(pq::id.eq(site.id))
+            .filter(pq::hash.eq(hash)),
+    )
+    .execute(conn)
+    .map(|count| count > 0)
+    .map_err(StoreError::from)
+}
+
+/// Return the hashes and texts of all query documents registered for the
+/// deployment, ordered by when they were registered
+pub fn persisted_queries(
+    conn: &mut PgConnection,
+    site: &Site,
+) -> Result<Vec<(String, String)>, StoreError> {
+    use persisted_query as pq;
+
+    pq::table
+        .filter(pq::id.eq(site.id))
+        .order_by((pq::created_at, pq::hash))
+        .select((pq::hash, pq::query))
+        .load::<(String, String)>(conn)
+        .map_err(StoreError::from)
+}
+
+/// Return whether only registered query documents can be run against the
+/// deployment
+pub fn persisted_queries_only(conn: &mut PgConnection, site: &Site) -> Result<bool, StoreError> {
+    use persisted_query_setting as pqs;
+
+    pqs::table
+        .filter(pqs::id.eq(site.id))
+        .select(pqs::only_registered)
+        .first::<bool>(conn)
+        .optional()
+        .map(|only| only.unwrap_or(false))
+        .map_err(StoreError::from)
+}
+
+pub fn set_persisted_queries_only(
+    conn: &mut PgConnection,
+    site: &Site,
+    only: bool,
+) -> Result<(), StoreError> {
+    use persisted_query_setting as pqs;
+
+    insert_into(pqs::table)
+        .values((pqs::id.eq(site.id), pqs::only_registered.eq(only)))
+        .on_conflict(pqs::id)
+        .do_update()
+        .set(pqs::only_registered.eq(only))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(StoreError::from)
+}
+
+pub fn registered_queries(
+    conn: &mut PgConnection,
+    site: &Site,
+) -> Result<RegisteredQueries, StoreError> {
+    let only_registered = persisted_queries_only(conn, site)?;
+    let queries = persisted_queries(conn, site)?.into_iter().collect();
+    Ok(RegisteredQueries {
+        only_registered,
+        queries,
+    })
+}
+
 #[allow(dead_code)]
 pub fn features(
     conn: &mut PgConnection,
@@ -8,11 +8,11 @@ use graph::blockchain::block_stream::{EntitySourceOperation, FirehoseCursor};
 use graph::blockchain::BlockTime;
 use graph::components::store::write::RowGroup;
 use graph::components::store::{
-    Batch, DeploymentLocator, DerivedEntityQuery, PrunePhase, PruneReporter, PruneRequest,
-    PruningStrategy, QueryPermit, StoredDynamicDataSource, VersionStats,
+    Batch, DeploymentLocator, DerivedEntityQuery, FilteredEntityQuery, PrunePhase, PruneReporter,
+    PruneRequest, PruningStrategy, QueryPermit, StoredDynamicDataSource, VersionStats,
 };
 use graph::components::versions::VERSIONS;
-use graph::data::query::Trace;
+use graph::data::query::{RegisteredQueries, Trace};
 use graph::data::store::IdList;
 use graph::data::subgraph::{status, SPEC_VERSION_0_0_6};
 use graph::data_source::CausalityRegion;
@@ -24,6 +24,7 @@ use graph::prelude::{
 };
 use graph::semver::Version;
 use graph::tokio::task::JoinHandle;
+use graph::util::timed_cache::TimedCache;
 use itertools::Itertools;
 use lru_time_cache::LruCache;
 use rand::{seq::SliceRandom, thread_rng};
@@ -90,6 +91,11 @@ pub(crate) struct SubgraphInfo {
 
 type PruneHandle = JoinHandle<Result<(), StoreError>>;
 
+/// How long we cache the registered queries of a deployment. Changes made
+/// with `graphman persisted-query` take at most this long to be noticed by
+/// query nodes
+const REGISTERED_QUERIES_CACHE_TTL: Duration = Duration::from_secs(60);
+
 pub struct StoreInner {
     logger: Logger,
 
@@ -115,6 +121,9 @@ pub struct StoreInner {
     pub(crate) layout_cache: LayoutCache,
 
     prune_handles: Mutex<HashMap<DeploymentId, PruneHandle>>,
+
+    /// A cache of the query documents registered for each deployment
+    registered_queries: TimedCache<DeploymentId, RegisteredQueries>,
 }
 
 /// Storage of the data for individual deployments. Each `DeploymentStore`
@@ -171,6 +180,7 @@ impl DeploymentStore {
             subgraph_cache: Mutex::new(LruCache::with_capacity(100)),
             layout_cache: LayoutCache::new(ENV_VARS.store.query_stats_refresh_interval),
             prune_handles: Mutex::new(HashMap::new()),
+            registered_queries: TimedCache::new(REGISTERED_QUERIES_CACHE_TTL),
         };
 
         DeploymentStore(Arc::new(store))
@@ -290,6 +300,21 @@ impl DeploymentStore {
         layout.query(&logger, conn, query)
     }
 
+    pub(crate) fn execute_count(
+        &self,
+        conn: &mut PgConnection,
+        site: Arc<Site>,
+        query: EntityQuery,
+    ) -> Result<i64, QueryExecutionError> {
+        let layout = self.layout(conn, site)?;
+
+        let logger = query
+            .logger
+            .cheap_clone()
+            .unwrap_or_else(|| self.logger.cheap_clone());
+        layout.count(&logger, conn, query)
+    }
+
     fn check_intf_uniqueness(
         &self,
         conn: &mut PgConnection,
@@ -1065,6 +1090,18 @@ impl DeploymentStore {
         layout.find_derived(&mut conn, derived_query, block, excluded_keys)
     }
 
+    pub(crate) fn get_filtered(
+        &self,
+        site: Arc<Site>,
+        query: &FilteredEntityQuery,
+        block: BlockNumber,
+        excluded_keys: &Vec<EntityKey>,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        let mut conn = self.get_conn()?;
+        let layout = self.layout(&mut conn, site)?;
+        layout.find_filtered(&mut conn, query, block, excluded_keys)
+    }
+
     pub(crate) fn get_changes(
         &self,
         site: Arc<Site>,
@@ -1121,7 +1158,7 @@ impl DeploymentStore {
 
                 dynds::insert(conn, &site, &batch.data_sources, manifest_idx_and_name)?;
 
-                dynds::update_offchain_status(conn, &site, &batch.offchain_to_remove)?;
+                dynds::update_done_at(conn, &site, &batch.offchain_to_remove)?;
 
                 if !batch.deterministic_errors.is_empty() {
                     deployment::insert_subgraph_errors(
@@ -1340,6 +1377,93 @@ impl DeploymentStore {
         )
     }
 
+    /// Recompute all buckets of the aggregation `aggregation` from the
+    /// timeseries data that the deployment has already indexed. Returns
+    /// the number of buckets that were filled. See
+    /// `Layout::backfill_aggregation` for details
+    pub(crate) fn backfill_aggregation(
+        &self,
+        site: Arc<Site>,
+        aggregation: &str,
+    ) -> Result<usize, StoreError> {
+        let mut conn = self.get_conn()?;
+
+        deployment::with_lock(&mut conn, &site, |conn| {
+            conn.transaction(|conn| -> Result<_, StoreError> {
+                let layout = self.layout(conn, site.clone())?;
+                layout.backfill_aggregation(conn, aggregation)
+            })
+        })
+    }
+
+    pub(crate) fn register_persisted_query(
+        &self,
+        site: &Site,
+        hash: &str,
+        query: &str,
+    ) -> Result<(), StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::register_persisted_query(&mut conn, site, hash, query)?;
+        self.registered_queries.remove(&site.id);
+        Ok(())
+    }
+
+    pub(crate) fn unregister_persisted_query(
+        &self,
+        site: &Site,
+        hash: &str,
+    ) -> Result<bool, StoreError> {
+        let mut conn = self.get_conn()?;
+        let removed = deployment::unregister_persisted_query(&mut conn, site, hash)?;
+        self.registered_queries.remove(&site.id);
+        Ok(removed)
+    }
+
+    pub(crate) fn persisted_queries(
+        &self,
+        site: &Site,
+    ) -> Result<Vec<(String, String)>, StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::persisted_queries(&mut conn, site)
+    }
+
+    pub(crate) fn persisted_queries_only(&self, site: &Site) -> Result<bool, StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::persisted_queries_only(&mut conn, site)
+    }
+
+    pub(crate) fn set_persisted_queries_only(
+        &self,
+        site: &Site,
+        only: bool,
+    ) -> Result<(), StoreError> {
+        let mut conn = self.get_conn()?;
+        deployment::set_persisted_queries_only(&mut conn, site, only)?;
+        self.registered_queries.remove(&site.id);
+        Ok(())
+    }
+
+    pub(crate) async fn registered_queries(
+        &self,
+        site: Arc<Site>,
+    ) -> Result<Arc<RegisteredQueries>, StoreError> {
+        if let Some(queries) = self.registered_queries.get(&site.id) {
+            return Ok(queries);
+        }
+
+        let id = site.id;
+        let queries = self
+            .with_conn(move |conn, cancel| {
+                cancel.check_cancel()?;
+
+                deployment::registered_queries(conn, &site).map_err(Into::into)
+            })
+            .await?;
+        let queries = Arc::new(queries);
+        self.registered_queries.set(id, queries.cheap_clone());
+        Ok(queries)
+    }
+
     pub(crate) fn rewind(&self, site: Arc<Site>, block_ptr_to: BlockPtr) -> Result<(), StoreError> {
         let mut conn = self.get_conn()?;
 
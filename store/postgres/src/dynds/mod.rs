@@ -47,7 +47,9 @@ pub(crate) fn revert(
     }
 }
 
-pub(crate) fn update_offchain_status(
+/// Mark processed offchain data sources and onchain data sources that were removed by a mapping
+/// as done.
+pub(crate) fn update_done_at(
     conn: &mut PgConnection,
     site: &Site,
     data_sources: &write::DataSources,
@@ -57,11 +59,9 @@ pub(crate) fn update_offchain_status(
     }
 
     match site.schema_version.private_data_sources() {
-        true => {
-            DataSourcesTable::new(site.namespace.clone()).update_offchain_status(conn, data_sources)
-        }
+        true => DataSourcesTable::new(site.namespace.clone()).update_done_at(conn, data_sources),
         false => Err(internal_error!(
-            "shared schema does not support data source offchain_found",
+            "shared schema does not support marking data sources as done",
         )),
     }
 }
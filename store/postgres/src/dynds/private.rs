@@ -5,7 +5,7 @@ use diesel::{
     prelude::*,
     query_builder::{AstPass, QueryFragment, QueryId},
     sql_query,
-    sql_types::{Binary, Bool, Integer, Jsonb, Nullable},
+    sql_types::{Array, Binary, Bool, Integer, Jsonb, Nullable},
     PgConnection, QueryDsl, RunQueryDsl,
 };
 
@@ -34,6 +34,7 @@ pub(crate) struct DataSourcesTable {
     param: DynColumn<Nullable<Binary>>,
     context: DynColumn<Nullable<Jsonb>>,
     done_at: DynColumn<Nullable<Integer>>,
+    chunks_done_at: DynColumn<Array<Integer>>,
 }
 
 impl DataSourcesTable {
@@ -53,6 +54,7 @@ impl DataSourcesTable {
             param: table.column("param"),
             context: table.column("context"),
             done_at: table.column("done_at"),
+            chunks_done_at: table.column("chunks_done_at"),
             table,
         }
     }
@@ -69,7 +71,8 @@ impl DataSourcesTable {
                 id bytea,
                 param bytea,
                 context jsonb,
-                done_at int
+                done_at int,
+                chunks_done_at int[] not null default '{{}}'
             );
 
             create index gist_block_range_data_sources$ on {nsp}.data_sources$ using gist (block_range);
@@ -95,6 +98,7 @@ impl DataSourcesTable {
             Option<serde_json::Value>,
             CausalityRegion,
             Option<i32>,
+            Vec<i32>,
         );
         let tuples = self
             .table
@@ -107,6 +111,7 @@ impl DataSourcesTable {
                 &self.context,
                 &self.causality_region,
                 &self.done_at,
+                &self.chunks_done_at,
             ))
             .order_by(&self.vid)
             .load::<Tuple>(conn)?;
@@ -114,7 +119,15 @@ impl DataSourcesTable {
         let mut dses: Vec<_> = tuples
             .into_iter()
             .map(
-                |(block_range, manifest_idx, param, context, causality_region, done_at)| {
+                |(
+                    block_range,
+                    manifest_idx,
+                    param,
+                    context,
+                    causality_region,
+                    done_at,
+                    chunks_done_at,
+                )| {
                     let creation_block = match block_range.0 {
                         Bound::Included(block) => Some(block),
 
@@ -130,6 +143,7 @@ impl DataSourcesTable {
                         context,
                         creation_block,
                         done_at,
+                        chunks_done_at,
                         causality_region,
                     }
                 },
@@ -158,6 +172,7 @@ impl DataSourcesTable {
                     context,
                     creation_block,
                     done_at,
+                    chunks_done_at,
                     causality_region,
                 } = ds;
 
@@ -174,8 +189,8 @@ impl DataSourcesTable {
                 // Offchain data sources have a unique causality region assigned from a sequence in the
                 // database, while onchain data sources always have causality region 0.
                 let query = format!(
-                "insert into {}(block_range, manifest_idx, param, context, causality_region, done_at) \
-                            values (int4range($1, null), $2, $3, $4, $5, $6)",
+                "insert into {}(block_range, manifest_idx, param, context, causality_region, done_at, chunks_done_at) \
+                            values (int4range($1, null), $2, $3, $4, $5, $6, $7)",
                 self.qname
             );
 
@@ -185,7 +200,8 @@ impl DataSourcesTable {
                     .bind::<Nullable<Binary>, _>(param.as_ref().map(|p| &**p))
                     .bind::<Nullable<Jsonb>, _>(context)
                     .bind::<Integer, _>(causality_region)
-                    .bind::<Nullable<Integer>, _>(done_at);
+                    .bind::<Nullable<Integer>, _>(done_at)
+                    .bind::<Array<Integer>, _>(chunks_done_at);
 
                 inserted_total += query.execute(conn)?;
             }
@@ -208,6 +224,22 @@ impl DataSourcesTable {
             self.qname
         );
         sql_query(query).bind::<Integer, _>(block).execute(conn)?;
+
+        // Data sources that were marked as done in the reverted blocks become active again.
+        let query = format!(
+            "update {} set done_at = null where done_at >= $1",
+            self.qname
+        );
+        sql_query(query).bind::<Integer, _>(block).execute(conn)?;
+
+        // Chunks of files that were processed in the reverted blocks need to be processed again.
+        // Chunks are processed in order, so this only ever removes a suffix of `chunks_done_at`.
+        let query = format!(
+            "update {} set chunks_done_at = array(select b from unnest(chunks_done_at) b where b < $1) \
+              where $1 <= any(chunks_done_at)",
+            self.qname
+        );
+        sql_query(query).bind::<Integer, _>(block).execute(conn)?;
         Ok(())
     }
 
@@ -245,6 +277,7 @@ impl DataSourcesTable {
                 &self.context,
                 &self.causality_region,
                 &self.done_at,
+                &self.chunks_done_at,
             ))
             .order_by(&self.vid)
             .load::<DsForCopy>(conn)?
@@ -273,23 +306,47 @@ impl DataSourcesTable {
         Ok(count)
     }
 
-    // Remove offchain data sources by checking the causality region, which currently uniquely
-    // identifies an offchain data source.
-    pub(super) fn update_offchain_status(
+    // Mark data sources as done. Offchain data sources are identified by their causality region,
+    // which currently uniquely identifies an offchain data source. Onchain data sources that were
+    // removed by a mapping all share the onchain causality region and are identified by their
+    // template, params, context and creation block instead.
+    pub(super) fn update_done_at(
         &self,
         conn: &mut PgConnection,
         data_sources: &write::DataSources,
     ) -> Result<(), StoreError> {
         for (_, dss) in &data_sources.entries {
             for ds in dss {
+                if ds.causality_region == CausalityRegion::ONCHAIN {
+                    let query = format!(
+                        "update {} set done_at = $1 \
+                          where causality_region = $2 and manifest_idx = $3 \
+                            and param is not distinct from $4 \
+                            and context is not distinct from $5 \
+                            and lower(block_range) = $6 and done_at is null",
+                        self.qname
+                    );
+
+                    sql_query(query)
+                        .bind::<Nullable<Integer>, _>(ds.done_at)
+                        .bind::<Integer, _>(ds.causality_region)
+                        .bind::<Integer, _>(ds.manifest_idx as i32)
+                        .bind::<Nullable<Binary>, _>(ds.param.as_ref().map(|p| &**p))
+                        .bind::<Nullable<Jsonb>, _>(&ds.context)
+                        .bind::<Nullable<Integer>, _>(ds.creation_block)
+                        .execute(conn)?;
+                    continue;
+                }
+
                 let query = format!(
-                    "update {} set done_at = $1 where causality_region = $2",
+                    "update {} set done_at = $1, chunks_done_at = $3 where causality_region = $2",
                     self.qname
                 );
 
                 let count = sql_query(query)
                     .bind::<Nullable<Integer>, _>(ds.done_at)
                     .bind::<Integer, _>(ds.causality_region)
+                    .bind::<Array<Integer>, _>(&ds.chunks_done_at)
                     .execute(conn)?;
 
                 if count > 1 {
@@ -376,6 +433,7 @@ struct DsForCopy {
     context: Option<serde_json::Value>,
     causality_region: i32,
     done_at: Option<i32>,
+    chunks_done_at: Vec<i32>,
 }
 
 impl DsForCopy {
@@ -401,6 +459,8 @@ impl DsForCopy {
             Bound::Unbounded => 0,
         };
         self.idx = map.dst_idx(self.idx, src_nsp, src_created, dst_nsp)?;
+        // Chunks processed after the target block need to be processed again
+        self.chunks_done_at.retain(|block| *block <= target_block);
         Ok(self)
     }
 }
@@ -411,7 +471,7 @@ struct CopyDsQuery<'a> {
 }
 
 impl<'a> CopyDsQuery<'a> {
-    const BIND_PARAMS: usize = 6;
+    const BIND_PARAMS: usize = 7;
 
     fn new(dst: &'a DataSourcesTable, dss: &'a [DsForCopy]) -> Result<Self, StoreError> {
         Ok(CopyDsQuery { dst, dss })
@@ -424,7 +484,7 @@ impl<'a> QueryFragment<Pg> for CopyDsQuery<'a> {
         out.push_sql("insert into ");
         out.push_sql(&self.dst.qname);
         out.push_sql(
-            "(block_range, manifest_idx, param, context, causality_region, done_at) values ",
+            "(block_range, manifest_idx, param, context, causality_region, done_at, chunks_done_at) values ",
         );
         let mut first = true;
         for ds in self.dss.iter() {
@@ -445,6 +505,8 @@ impl<'a> QueryFragment<Pg> for CopyDsQuery<'a> {
             out.push_bind_param::<Integer, _>(&ds.causality_region)?;
             out.push_sql(", ");
             out.push_bind_param::<Nullable<Integer>, _>(&ds.done_at)?;
+            out.push_sql(", ");
+            out.push_bind_param::<Array<Integer>, _>(&ds.chunks_done_at)?;
             out.push_sql(")");
         }
 
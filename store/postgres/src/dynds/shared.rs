@@ -84,6 +84,7 @@ pub(super) fn load(
             // The shared schema is only used for legacy deployments, and therefore not used for
             // subgraphs that use file data sources.
             done_at: None,
+            chunks_done_at: vec![],
             causality_region: CausalityRegion::ONCHAIN,
         };
 
@@ -122,6 +123,7 @@ pub(super) fn insert(
                     context,
                     creation_block: _,
                     done_at: _,
+                    chunks_done_at: _,
                     causality_region,
                 } = ds;
 
@@ -1480,7 +1480,7 @@ impl<'a> Connection<'a> {
         event: &StoreEvent,
     ) -> Result<(), StoreError> {
         // Performance: Don't bog down the db with many empty changelists.
-        if event.changes.is_empty() {
+        if event.is_empty() {
             return Ok(());
         }
         let v = serde_json::to_value(event)?;
@@ -3,7 +3,7 @@ use std::time::Instant;
 
 use crate::deployment_store::{DeploymentStore, ReplicaId};
 use graph::components::store::{DeploymentId, QueryPermit, QueryStore as QueryStoreTrait};
-use graph::data::query::Trace;
+use graph::data::query::{RegisteredQueries, Trace};
 use graph::data::store::QueryObject;
 use graph::prelude::*;
 use graph::schema::{ApiSchema, InputSchema};
@@ -57,6 +57,19 @@ impl QueryStoreTrait for QueryStore {
             })
     }
 
+    fn count_query_values(
+        &self,
+        query: EntityQuery,
+    ) -> Result<i64, graph::prelude::QueryExecutionError> {
+        assert_eq!(&self.site.deployment, &query.subgraph_id);
+        let mut conn = self
+            .store
+            .get_replica_conn(self.replica_id)
+            .map_err(|e| QueryExecutionError::StoreError(e.into()))?;
+        self.store
+            .execute_count(&mut conn, self.site.clone(), query)
+    }
+
     /// Return true if the deployment with the given id is fully synced,
     /// and return false otherwise. Errors from the store are passed back up
     async fn is_deployment_synced(&self) -> Result<bool, Error> {
@@ -148,4 +161,8 @@ impl QueryStoreTrait for QueryStore {
     fn deployment_id(&self) -> DeploymentId {
         self.site.id.into()
     }
+
+    async fn registered_queries(&self) -> Result<Arc<RegisteredQueries>, StoreError> {
+        self.store.registered_queries(self.site.cheap_clone()).await
+    }
 }
@@ -39,7 +39,7 @@ use graph::data_source::CausalityRegion;
 use graph::internal_error;
 use graph::prelude::{q, EntityQuery, StopwatchMetrics, ENV_VARS};
 use graph::schema::{
-    EntityKey, EntityType, Field, FulltextConfig, FulltextDefinition, InputSchema,
+    Aggregate, EntityKey, EntityType, Field, FulltextConfig, FulltextDefinition, InputSchema,
 };
 use graph::slog::warn;
 use index::IndexList;
@@ -58,16 +58,17 @@ use std::time::{Duration, Instant};
 use crate::relational::value::{FromOidRow, OidRow};
 use crate::relational_queries::{
     ConflictingEntitiesData, ConflictingEntitiesQuery, EntityDataExt, FindChangesQuery,
-    FindDerivedQuery, FindPossibleDeletionsQuery, ReturnedEntityData,
+    FindDerivedQuery, FindFilteredQuery, FindPossibleDeletionsQuery, ReturnedEntityData,
 };
 use crate::{
     primary::{Namespace, Site},
     relational_queries::{
-        ClampRangeQuery, EntityData, EntityDeletion, FilterCollection, FilterQuery, FindManyQuery,
-        FindRangeQuery, InsertQuery, RevertClampQuery, RevertRemoveQuery,
+        ClampRangeQuery, CountData, CountQuery, EntityData, EntityDeletion, FilterCollection,
+        FilterQuery, FindManyQuery, FindRangeQuery, InsertQuery, RevertClampQuery,
+        RevertRemoveQuery,
     },
 };
-use graph::components::store::{AttributeNames, DerivedEntityQuery};
+use graph::components::store::{AttributeNames, DerivedEntityQuery, FilteredEntityQuery};
 use graph::data::store::{IdList, IdType, BYTES_SCALAR};
 use graph::data::subgraph::schema::POI_TABLE;
 use graph::prelude::{
@@ -678,6 +679,31 @@ impl Layout {
         Ok(entities)
     }
 
+    pub fn find_filtered(
+        &self,
+        conn: &mut PgConnection,
+        query: &FilteredEntityQuery,
+        block: BlockNumber,
+        excluded_keys: &Vec<EntityKey>,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        let table = self.table_for_entity(&query.entity_type)?;
+        let ids = excluded_keys.iter().map(|key| &key.entity_id).cloned();
+        let excluded_keys = IdList::try_from_iter(query.entity_type.id_type()?, ids)?;
+        let query = FindFilteredQuery::new(self, table, query, block, excluded_keys)?;
+
+        let mut entities = BTreeMap::new();
+
+        for data in query.load::<EntityData>(conn)? {
+            let entity_type = data.entity_type(&self.input_schema);
+            let entity_data: Entity = data.deserialize_with_layout(self, None)?;
+            let key =
+                entity_type.key_in(entity_data.id(), CausalityRegion::from_entity(&entity_data));
+
+            entities.insert(key, entity_data);
+        }
+        Ok(entities)
+    }
+
     pub fn find_changes(
         &self,
         conn: &mut PgConnection,
@@ -842,6 +868,7 @@ impl Layout {
             query.filter.as_ref(),
             query.order,
             query.range,
+            query.group_by.as_deref(),
             query.block,
             query.query_id,
             &self.site,
@@ -897,6 +924,45 @@ impl Layout {
             .map(|values| (values, trace))
     }
 
+    /// Count the entities that match the filter of `query`. The order and
+    /// range of `query` are ignored
+    pub fn count(
+        &self,
+        logger: &Logger,
+        conn: &mut PgConnection,
+        query: EntityQuery,
+    ) -> Result<i64, QueryExecutionError> {
+        let filter_collection =
+            FilterCollection::new(self, query.collection, query.filter.as_ref(), query.block)?;
+        let count_query =
+            CountQuery::new(&filter_collection, query.block, query.query_id, &self.site)?;
+
+        let start = Instant::now();
+        let count = conn
+            .transaction(|conn| {
+                if let Some(ref timeout_sql) = *STATEMENT_TIMEOUT {
+                    conn.batch_execute(timeout_sql)?;
+                }
+                count_query.get_result::<CountData>(conn)
+            })
+            .map_err(|e| {
+                QueryExecutionError::ResolveEntitiesError(format!(
+                    "{e}, query = count from {filter_collection}"
+                ))
+            })?
+            .count;
+        if ENV_VARS.log_sql_timing() {
+            info!(
+                logger,
+                "Query timing (SQL)";
+                "query" => debug_query(&count_query).to_string().replace('\n', "\t"),
+                "time_ms" => start.elapsed().as_millis(),
+                "entity_count" => count
+            );
+        }
+        Ok(count)
+    }
+
     pub fn update<'a>(
         &'a self,
         conn: &mut PgConnection,
@@ -1226,11 +1292,11 @@ impl Layout {
                 // `t2`, `t3`, and `t4`.
                 match buckets.first() {
                     None => {
-                        // The rollups are in increasing order of interval size, so
-                        // if a smaller interval doesn't have a bucket between
-                        // last_rollup and block_time, a larger one can't either and
-                        // we are done with this rollup.
-                        break;
+                        // Nothing to roll up for this interval. We can not
+                        // conclude that longer intervals have nothing to
+                        // roll up either since bucket boundaries of, e.g.,
+                        // `90m` intervals are not hour boundaries
+                        continue;
                     }
                     Some(bucket) => {
                         rollup.insert(conn, &bucket, *block)?;
@@ -1241,6 +1307,50 @@ impl Layout {
         }
         Ok(())
     }
+
+    /// Recompute all buckets of the aggregation `aggregation` from the
+    /// data in its source timeseries, replacing any buckets that had been
+    /// computed before. This makes it possible to add an aggregation to a
+    /// deployment whose timeseries data has already been indexed.
+    ///
+    /// Buckets are filled up to the point where the other aggregations of
+    /// the deployment have been rolled up, so that normal rollups continue
+    /// seamlessly once indexing resumes. If the deployment has no other
+    /// aggregations, buckets are filled up to the bucket that contains the
+    /// latest timeseries data. Each bucket is marked with the largest block
+    /// number of the data it was computed from.
+    ///
+    /// Returns the number of buckets that were filled
+    pub(crate) fn backfill_aggregation(
+        &self,
+        conn: &mut PgConnection,
+        aggregation: &str,
+    ) -> Result<usize, StoreError> {
+        let rollups: Vec<_> = self
+            .rollups
+            .iter()
+            .filter(|rollup| rollup.aggregation() == aggregation)
+            .collect();
+        if rollups.is_empty() {
+            return Err(StoreError::UnknownTable(aggregation.to_string()));
+        }
+
+        for rollup in &rollups {
+            rollup.clear(conn)?;
+        }
+        let end = Rollup::last_rollup(&self.rollups, conn)?;
+
+        let mut count = 0;
+        for rollup in rollups {
+            // Buckets must be filled in order since cumulative aggregates
+            // depend on the values of earlier buckets
+            for (bucket, block) in rollup.data_buckets(conn, end)? {
+                rollup.insert(conn, &bucket, block)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
 }
 
 /// A user-defined enum
@@ -1415,9 +1525,15 @@ impl Column {
         field: &Field,
         catalog: &Catalog,
     ) -> Result<Column, StoreError> {
-        SqlName::check_valid_identifier(&field.name, "attribute")?;
-
-        let sql_name = SqlName::from(&*field.name);
+        let sql_name = match Aggregate::state_of(&field.name) {
+            // The state of an aggregate can not clash with a column for
+            // an attribute since those can not contain a `$`
+            Some(agg) => SqlName::verbatim(Aggregate::state_name(SqlName::from(agg).as_str())),
+            None => {
+                SqlName::check_valid_identifier(&field.name, "attribute")?;
+                SqlName::from(&*field.name)
+            }
+        };
 
         let is_reference = schema.is_reference(&field.field_type.get_base_type());
 
@@ -33,8 +33,11 @@
 //! be careful to do the combination in the correct order, expressed by the
 //! `seq` variable in the combined query. We also need to be carful to use
 //! the right aggregation function for combining aggregates; in particular,
-//! counts need to be combined with `sum` and not `count`. That query looks
-//! like
+//! counts need to be combined with `sum` and not `count`. Cumulative
+//! aggregates like `avg` that can not be combined directly keep a running
+//! state of the count, sum, and sum of squares of their values in an
+//! additional column. The state is combined like a sum, and the aggregate
+//! is computed from the combined state. That query looks like
 //!
 //! ```text
 //!   with bucket as (<query from above>),
@@ -146,6 +149,9 @@ pub(crate) struct Agg<'a> {
     src_columns: Vec<&'a str>,
     expr: String,
     agg_column: &'a Column,
+    /// The column with the running state of the aggregate if it needs one;
+    /// see `Aggregate::state_field`
+    state_column: Option<&'a Column>,
 }
 
 impl<'a> Agg<'a> {
@@ -156,11 +162,16 @@ impl<'a> Agg<'a> {
     ) -> Result<Self, StoreError> {
         let (expr, src_columns) = rewrite(src_table, &aggregate.arg)?;
         let agg_column = agg_table.column_for_field(&aggregate.name)?;
+        let state_column = aggregate
+            .state_field()
+            .map(|field| agg_table.column_for_field(&field.name))
+            .transpose()?;
         Ok(Self {
             aggregate,
             src_columns,
             expr,
             agg_column,
+            state_column,
         })
     }
 
@@ -180,6 +191,19 @@ impl<'a> Agg<'a> {
                 write!(w, "arg_max_{}(({}, {time}))", sql_type, src)?
             }
             Count => write!(w, "count(*)")?,
+            Avg => write!(w, "avg({})", src)?,
+            Variance => write!(w, "var_pop({})", src)?,
+            Stddev => write!(w, "stddev_pop({})", src)?,
+            CountDistinct => write!(w, "count(distinct {})", src)?,
+            Percentile(pct) => {
+                // `percentile_cont` computes in `float8`; cast the result
+                // to the type of the aggregation column
+                let sql_type = self.agg_column.column_type.sql_type();
+                write!(
+                    w,
+                    "(percentile_cont(0.{pct:02}) within group (order by {src}))::{sql_type}"
+                )?
+            }
         }
         write!(w, " as \"{}\"", self.agg_column.name)
     }
@@ -187,9 +211,50 @@ impl<'a> Agg<'a> {
     /// Generate a SQL fragment `func(expr) as agg_column` where
     /// `func` is the aggregation function. The `time` parameter is the name
     /// of the column with respect to which `first` and `last` should decide
-    /// which values are earlier or later
+    /// which values are earlier or later. If the aggregate has a state
+    /// column, also compute the state for `expr`
     fn aggregate(&self, time: &str, w: &mut dyn fmt::Write) -> fmt::Result {
-        self.aggregate_over(&self.expr, time, w)
+        self.aggregate_over(&self.expr, time, w)?;
+        if let Some(state) = self.state_column {
+            write!(
+                w,
+                ", array[count({expr}), coalesce(sum({expr}), 0), \
+                   coalesce(sum(({expr}) * ({expr})), 0)]::numeric[] as \"{}\"",
+                state.name,
+                expr = self.expr
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Generate a SQL fragment that computes the aggregate from the
+    /// combined state in the column `state`, i.e., the sums of the count,
+    /// sum, and sum of squares of the values in the state of each row
+    fn from_state(&self, state: &Column, w: &mut dyn fmt::Write) -> fmt::Result {
+        use AggregateFn::*;
+
+        let count = format!("nullif(sum(\"{}\"[1]), 0)", state.name);
+        let sum = format!("sum(\"{}\"[2])", state.name);
+        let sum_sq = format!("sum(\"{}\"[3])", state.name);
+        let variance = format!("({count} * {sum_sq} - {sum} * {sum}) / ({count} * {count})");
+        match self.aggregate.func {
+            Avg => write!(w, "{sum} / {count}"),
+            Variance => write!(w, "{variance}"),
+            Stddev => write!(w, "sqrt({variance})"),
+            Sum | Max | Min | Count | First | Last | CountDistinct | Percentile(_) => {
+                unreachable!("only aggregates that need state have a state column")
+            }
+        }
+    }
+
+    /// Write the names of the aggregate column and of the state column if
+    /// there is one
+    fn columns(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        write!(w, "\"{}\"", self.agg_column.name)?;
+        if let Some(state) = self.state_column {
+            write!(w, ", \"{}\"", state.name)?;
+        }
+        Ok(())
     }
 
     /// Generate a SQL fragment `func(src_column) as agg_column` where
@@ -199,6 +264,18 @@ impl<'a> Agg<'a> {
     fn combine(&self, time: &str, w: &mut dyn fmt::Write) -> fmt::Result {
         use AggregateFn::*;
 
+        if let Some(state) = self.state_column {
+            // Combine the states by adding them up, and compute the
+            // aggregate from the combined state
+            self.from_state(state, w)?;
+            return write!(
+                w,
+                " as \"{agg}\", array[sum(\"{state}\"[1]), sum(\"{state}\"[2]), \
+                   sum(\"{state}\"[3])] as \"{state}\"",
+                agg = self.agg_column.name,
+                state = state.name
+            );
+        }
         match self.aggregate.func {
             Sum | Max | Min | First | Last => {
                 // For these, combining and aggregating is done by the same
@@ -207,33 +284,52 @@ impl<'a> Agg<'a> {
                 return self.aggregate_over(&name, time, w);
             }
             Count => write!(w, "sum(\"{}\")", self.agg_column.name)?,
+            Avg | Variance | Stddev | CountDistinct | Percentile(_) => {
+                // Without state, these aggregates are never combined with
+                // a previous value (see `prev_agg`) and there is therefore
+                // exactly one non-null value in the group
+                write!(w, "max(\"{}\")", self.agg_column.name)?
+            }
         }
         write!(w, " as \"{}\"", self.agg_column.name)
     }
 
     /// Generate a SQL fragment that computes that selects the previous
     /// value from an aggregation when the aggregation is cumulative and
-    /// `null` when it is not
+    /// `null` when it is not. Cumulative aggregates that are not mergeable
+    /// use `null` for their value and select the previous state instead
     fn prev_agg(&self, w: &mut dyn fmt::Write) -> fmt::Result {
-        if self.aggregate.cumulative {
+        if self.is_merged() {
             write!(w, "prev.\"{}\"", self.agg_column.name)
         } else {
             let sql_type = self.agg_column.column_type.sql_type();
-            write!(w, "null::{sql_type} as \"{}\"", self.agg_column.name)
+            write!(w, "null::{sql_type} as \"{}\"", self.agg_column.name)?;
+            if let Some(state) = self.state_column {
+                write!(w, ", prev.\"{}\"", state.name)?;
+            }
+            Ok(())
         }
     }
+
+    /// Whether this aggregate is cumulative and computed by combining the
+    /// previous aggregate with the aggregate for the current bucket
+    fn is_merged(&self) -> bool {
+        self.aggregate.cumulative && self.aggregate.func.is_mergeable()
+    }
 }
 
 #[derive(Debug, Clone)]
 pub(crate) struct Rollup {
     pub(crate) interval: AggregationInterval,
-    #[allow(dead_code)]
     agg_table: Arc<Table>,
     insert_sql: String,
     /// A query that determines the last time a rollup was done. The query
     /// finds the latest timestamp in the aggregation table and adds the
     /// length of the aggregation interval to deduce the last rollup time
     last_rollup_sql: String,
+    /// A query that finds the buckets for which the source timeseries has
+    /// data; used when backfilling an aggregation
+    data_buckets_sql: String,
 }
 
 impl Rollup {
@@ -262,14 +358,22 @@ impl Rollup {
         let mut insert_sql = String::new();
         sql.insert(&mut insert_sql)?;
         let last_rollup_sql = sql.last_rollup();
+        let mut data_buckets_sql = String::new();
+        sql.data_buckets(&mut data_buckets_sql)?;
         Ok(Self {
             interval,
             agg_table,
             insert_sql,
             last_rollup_sql,
+            data_buckets_sql,
         })
     }
 
+    /// The name of the aggregation this rollup belongs to
+    pub(crate) fn aggregation(&self) -> &str {
+        self.agg_table.object.typename()
+    }
+
     pub(crate) fn insert(
         &self,
         conn: &mut PgConnection,
@@ -283,6 +387,43 @@ impl Rollup {
         query.execute(conn)
     }
 
+    /// Delete all aggregated values for this rollup's interval
+    pub(crate) fn clear(&self, conn: &mut PgConnection) -> Result<usize, StoreError> {
+        let query = format!("delete from {}", self.agg_table.qualified_name);
+        Ok(sql_query(query).execute(conn)?)
+    }
+
+    /// Return the buckets for which the source timeseries has data,
+    /// together with the largest block number of that data. Only buckets
+    /// that end before the bucket containing `end` are returned. If `end`
+    /// is `None`, the bucket containing the latest data point is used
+    /// instead, since that bucket might not be complete yet
+    pub(crate) fn data_buckets(
+        &self,
+        conn: &mut PgConnection,
+        end: Option<BlockTime>,
+    ) -> Result<Vec<(Range<BlockTime>, BlockNumber)>, StoreError> {
+        #[derive(QueryableByName)]
+        #[diesel(check_for_backend(diesel::pg::Pg))]
+        struct BucketRes {
+            #[diesel(sql_type = Timestamptz)]
+            start: BlockTime,
+            #[diesel(sql_type = Integer)]
+            block: BlockNumber,
+        }
+
+        let buckets = sql_query(&self.data_buckets_sql)
+            .bind::<Nullable<Timestamptz>, _>(end)
+            .get_results::<BucketRes>(conn)?
+            .into_iter()
+            .map(|res| {
+                let end = self.interval.next_bucket(res.start);
+                (res.start..end, res.block)
+            })
+            .collect();
+        Ok(buckets)
+    }
+
     pub(crate) fn last_rollup(
         rollups: &[Rollup],
         conn: &mut PgConnection,
@@ -371,11 +512,9 @@ impl<'a> RollupSql<'a> {
         }
         write_dims(self.dimensions, w)?;
         comma_sep(self.aggregates, w, |w, agg| agg.aggregate("id", w))?;
-        let secs = self.interval.as_duration().as_secs();
-        write!(
-            w,
-            " from (select id, date_bin('{secs}s', timestamp, 'epoch'::timestamptz) as timestamp"
-        )?;
+        write!(w, " from (select id, ")?;
+        write_bucket_start(self.interval, w)?;
+        write!(w, " as timestamp")?;
         write_dims(self.dimensions, w)?;
         let agg_srcs: Vec<&str> = {
             let mut agg_srcs: Vec<_> = self
@@ -418,9 +557,7 @@ impl<'a> RollupSql<'a> {
             self.agg_table.qualified_name
         )?;
         write_dims(self.dimensions, w)?;
-        comma_sep(self.aggregates, w, |w, agg| {
-            write!(w, "\"{}\"", agg.agg_column.name)
-        })?;
+        comma_sep(self.aggregates, w, |w, agg| agg.columns(w))?;
         write!(w, ") ")
     }
 
@@ -499,9 +636,7 @@ impl<'a> RollupSql<'a> {
         self.insert_into(w)?;
         write!(w, "select id, timestamp, $3 as block$")?;
         write_dims(self.dimensions, w)?;
-        comma_sep(self.aggregates, w, |w, agg| {
-            write!(w, "\"{}\"", agg.agg_column.name)
-        })?;
+        comma_sep(self.aggregates, w, |w, agg| agg.columns(w))?;
         write!(w, " from combined")
     }
 
@@ -513,17 +648,91 @@ impl<'a> RollupSql<'a> {
         }
     }
 
+    /// Generate a query that finds the start of all buckets that contain
+    /// data in the source timeseries and the largest block number of that
+    /// data. Only buckets that end before the bucket containing the bound
+    /// starts are considered
+    ///
+    /// with bound as (select <bucket start> as bound
+    ///                  from (select coalesce($1, max(timestamp)) as timestamp
+    ///                          from <source>) last)
+    /// select <bucket start> as start, max(block$) as block
+    ///   from <source>, bound
+    ///  where timestamp < bound.bound
+    ///  group by 1
+    ///  order by 1
+    ///
+    /// Bind variables:
+    ///   $1: the bound, or `null` to use the time of the latest data point
+    fn data_buckets(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        write!(w, "with bound as (select ")?;
+        write_bucket_start(self.interval, w)?;
+        write!(
+            w,
+            " as bound from (select coalesce($1, max(timestamp)) as timestamp from {}) last) ",
+            self.src_table
+        )?;
+        write!(w, "select ")?;
+        write_bucket_start(self.interval, w)?;
+        write!(
+            w,
+            " as start, max(block$) as block from {}, bound \
+              where timestamp < bound.bound group by 1 order by 1",
+            self.src_table
+        )
+    }
+
     /// Generate a query that selects the timestamp of the last rollup
     fn last_rollup(&self) -> String {
         // The timestamp column contains the timestamp of the start of the
         // last bucket. The last rollup was therefore at least
         // `self.interval` after that. We add 1 second to make sure we are
         // well within the next bucket
-        let secs = self.interval.as_duration().as_secs() + 1;
-        format!(
-            "select max(timestamp) + '{} s'::interval as last_rollup from {}",
-            secs, self.agg_table.qualified_name
-        )
+        match self.interval.fixed_duration() {
+            Some(duration) => {
+                let secs = duration.as_secs() + 1;
+                format!(
+                    "select max(timestamp) + '{} s'::interval as last_rollup from {}",
+                    secs, self.agg_table.qualified_name
+                )
+            }
+            None => {
+                // Calendar intervals have to be added in UTC since the
+                // result of adding them to a `timestamptz` depends on the
+                // session's time zone
+                format!(
+                    "select (max(timestamp) at time zone 'utc' + '1 month 1 s'::interval) \
+                       at time zone 'utc' as last_rollup from {}",
+                    self.agg_table.qualified_name
+                )
+            }
+        }
+    }
+}
+
+/// Write a SQL expression that computes the start of the bucket for
+/// `interval` that contains the `timestamp` column. Fixed intervals are
+/// aligned to the Unix epoch, weeks start on Mondays, and months on the
+/// first of the month; all of them at midnight UTC
+fn write_bucket_start(interval: AggregationInterval, w: &mut dyn fmt::Write) -> fmt::Result {
+    use AggregationInterval::*;
+
+    match interval {
+        Hour | Day | Fixed(_) => {
+            let secs = interval.as_duration().as_secs();
+            write!(w, "date_bin('{secs}s', timestamp, 'epoch'::timestamptz)")
+        }
+        Week => {
+            let secs = interval.as_duration().as_secs();
+            write!(
+                w,
+                "date_bin('{secs}s', timestamp, '1970-01-05T00:00:00Z'::timestamptz)"
+            )
+        }
+        Month => write!(
+            w,
+            "date_trunc('month', timestamp at time zone 'utc') at time zone 'utc'"
+        ),
     }
 }
 
@@ -624,6 +833,21 @@ mod tests {
         timestamp: Timestamp!
         count: Int8! @aggregate(fn: "count")
       }
+
+      type Calendar @aggregation(intervals: ["15m", "week", "month"], source: "Data") {
+        id: Int8!
+        timestamp: Timestamp!
+        count: Int8! @aggregate(fn: "count")
+      }
+
+      type Distribution @aggregation(intervals: ["day"], source: "Data") {
+        id: Int8!
+        timestamp: Timestamp!
+        token: Bytes!
+        avg: BigDecimal! @aggregate(fn: "avg", arg: "price", cumulative: true)
+        sd: BigDecimal! @aggregate(fn: "stddev", arg: "amount", cumulative: true)
+        p99: BigDecimal! @aggregate(fn: "p99", arg: "price")
+      }
       "#;
 
         const STATS_HOUR_SQL: &str = r#"\
@@ -704,6 +928,85 @@ mod tests {
              order by "sgd007"."data".timestamp) data \
         group by timestamp"#;
 
+        const CALENDAR_15M_SQL: &str = r#"\
+        insert into "sgd007"."calendar_15m"(id, timestamp, block$, "count") \
+        select max(id) as id, timestamp, $3, count(*) as "count" \
+          from (select id, date_bin('900s', timestamp, 'epoch'::timestamptz) as timestamp from "sgd007"."data" \
+             where "sgd007"."data".timestamp >= $1 and "sgd007"."data".timestamp < $2 \
+             order by "sgd007"."data".timestamp) data \
+        group by timestamp"#;
+
+        const CALENDAR_WEEK_SQL: &str = r#"\
+        insert into "sgd007"."calendar_week"(id, timestamp, block$, "count") \
+        select max(id) as id, timestamp, $3, count(*) as "count" \
+          from (select id, date_bin('604800s', timestamp, '1970-01-05T00:00:00Z'::timestamptz) as timestamp \
+                  from "sgd007"."data" \
+             where "sgd007"."data".timestamp >= $1 and "sgd007"."data".timestamp < $2 \
+             order by "sgd007"."data".timestamp) data \
+        group by timestamp"#;
+
+        const CALENDAR_MONTH_SQL: &str = r#"\
+        insert into "sgd007"."calendar_month"(id, timestamp, block$, "count") \
+        select max(id) as id, timestamp, $3, count(*) as "count" \
+          from (select id, date_trunc('month', timestamp at time zone 'utc') at time zone 'utc' as timestamp \
+                  from "sgd007"."data" \
+             where "sgd007"."data".timestamp >= $1 and "sgd007"."data".timestamp < $2 \
+             order by "sgd007"."data".timestamp) data \
+        group by timestamp"#;
+
+        const CALENDAR_MONTH_LAST_ROLLUP_SQL: &str = r#"\
+        select (max(timestamp) at time zone 'utc' + '1 month 1 s'::interval) at time zone 'utc' \
+               as last_rollup from "sgd007"."calendar_month""#;
+
+        const DISTRIBUTION_SQL: &str = r#"\
+        with bucket as (
+            select max(id) as id, timestamp, "token",
+                   avg("price") as "avg",
+                   array[count("price"), coalesce(sum("price"), 0),
+                         coalesce(sum(("price") * ("price")), 0)]::numeric[] as "avg$state",
+                   stddev_pop("amount") as "sd",
+                   array[count("amount"), coalesce(sum("amount"), 0),
+                         coalesce(sum(("amount") * ("amount")), 0)]::numeric[] as "sd$state",
+                   (percentile_cont(0.99) within group (order by "price"))::numeric as "p99"
+              from (select id, date_bin('86400s', timestamp, 'epoch'::timestamptz) as timestamp, "token", "amount", "price"
+                      from "sgd007"."data"
+                     where "sgd007"."data".timestamp >= $1
+                       and "sgd007"."data".timestamp < $2
+                     order by "sgd007"."data".timestamp) data
+              group by timestamp, "token"),
+             prev as (select bucket.id, bucket.timestamp, bucket."token",
+                             null::numeric as "avg", prev."avg$state",
+                             null::numeric as "sd", prev."sd$state",
+                             null::numeric as "p99"
+                        from bucket cross join lateral (
+                             select * from "sgd007"."distribution_day" prev
+                              where prev.timestamp < $1
+                                and prev."token" = bucket."token"
+                              order by prev.timestamp desc limit 1) prev),
+             combined as (select id, timestamp, "token",
+                                 sum("avg$state"[2]) / nullif(sum("avg$state"[1]), 0) as "avg",
+                                 array[sum("avg$state"[1]), sum("avg$state"[2]), sum("avg$state"[3])] as "avg$state",
+                                 sqrt((nullif(sum("sd$state"[1]), 0) * sum("sd$state"[3]) - sum("sd$state"[2]) * sum("sd$state"[2]))
+                                      / (nullif(sum("sd$state"[1]), 0) * nullif(sum("sd$state"[1]), 0))) as "sd",
+                                 array[sum("sd$state"[1]), sum("sd$state"[2]), sum("sd$state"[3])] as "sd$state",
+                                 max("p99") as "p99" from (
+                            select *, 1 as seq from prev
+                            union all
+                            select *, 2 as seq from bucket) u
+                          group by id, timestamp, "token")
+        insert into "sgd007"."distribution_day"(id, timestamp, block$, "token", "avg", "avg$state", "sd", "sd$state", "p99")
+        select id, timestamp, $3 as block$, "token", "avg", "avg$state", "sd", "sd$state", "p99" from combined
+        "#;
+
+        const STATS_HOUR_DATA_BUCKETS_SQL: &str = r#"\
+        with bound as (select date_bin('3600s', timestamp, 'epoch'::timestamptz) as bound \
+                         from (select coalesce($1, max(timestamp)) as timestamp \
+                                 from "sgd007"."data") last) \
+        select date_bin('3600s', timestamp, 'epoch'::timestamptz) as start, max(block$) as block \
+          from "sgd007"."data", bound \
+         where timestamp < bound.bound \
+         group by 1 order by 1"#;
+
         #[track_caller]
         fn rollup_for<'a>(layout: &'a Layout, table_name: &str) -> &'a Rollup {
             layout
@@ -719,7 +1022,7 @@ mod tests {
         let site = Arc::new(make_dummy_site(hash, nsp, "rollup".to_string()));
         let catalog = Catalog::for_tests(site.clone(), BTreeSet::new()).unwrap();
         let layout = Layout::new(site, &schema, catalog).unwrap();
-        assert_eq!(6, layout.rollups.len());
+        assert_eq!(10, layout.rollups.len());
 
         // Intervals are non-decreasing
         assert!(layout.rollups[0].interval <= layout.rollups[1].interval);
@@ -732,6 +1035,7 @@ mod tests {
         check_eqv(STATS_HOUR_SQL, &stats_hour.insert_sql);
         check_eqv(STATS_DAY_SQL, &stats_day.insert_sql);
         check_eqv(TOTAL_SQL, &stats_total.insert_sql);
+        check_eqv(STATS_HOUR_DATA_BUCKETS_SQL, &stats_hour.data_buckets_sql);
 
         let open_close = rollup_for(&layout, "open_close_day");
         check_eqv(OPEN_CLOSE_SQL, &open_close.insert_sql);
@@ -741,5 +1045,19 @@ mod tests {
 
         let count_only = rollup_for(&layout, "count_only_day");
         check_eqv(COUNT_ONLY_SQL, &count_only.insert_sql);
+
+        let calendar_15m = rollup_for(&layout, "calendar_15m");
+        check_eqv(CALENDAR_15M_SQL, &calendar_15m.insert_sql);
+        let calendar_week = rollup_for(&layout, "calendar_week");
+        check_eqv(CALENDAR_WEEK_SQL, &calendar_week.insert_sql);
+        let calendar_month = rollup_for(&layout, "calendar_month");
+        check_eqv(CALENDAR_MONTH_SQL, &calendar_month.insert_sql);
+        check_eqv(
+            CALENDAR_MONTH_LAST_ROLLUP_SQL,
+            &calendar_month.last_rollup_sql,
+        );
+
+        let distribution = rollup_for(&layout, "distribution_day");
+        check_eqv(DISTRIBUTION_SQL, &distribution.insert_sql);
     }
 }
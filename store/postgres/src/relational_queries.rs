@@ -13,17 +13,17 @@ use diesel::sql_types::Untyped;
 use diesel::sql_types::{Array, BigInt, Binary, Bool, Int8, Integer, Jsonb, Text, Timestamptz};
 use diesel::QuerySource as _;
 use graph::components::store::write::{EntityWrite, RowGroup, WriteChunk};
-use graph::components::store::{Child as StoreChild, DerivedEntityQuery};
+use graph::components::store::{Child as StoreChild, DerivedEntityQuery, FilteredEntityQuery};
 use graph::data::store::{Id, IdType, NULL};
 use graph::data::store::{IdList, IdRef, QueryObject};
 use graph::data::value::{Object, Word};
 use graph::data_source::CausalityRegion;
 use graph::prelude::{
-    anyhow, r, serde_json, BlockNumber, ChildMultiplicity, Entity, EntityCollection, EntityFilter,
-    EntityLink, EntityOrder, EntityOrderByChild, EntityOrderByChildInfo, EntityRange, EntityWindow,
-    ParentLink, QueryExecutionError, StoreError, Value, ENV_VARS,
+    anyhow, r, serde_json, Attribute, BlockNumber, ChildMultiplicity, Entity, EntityCollection,
+    EntityFilter, EntityLink, EntityOrder, EntityOrderByChild, EntityOrderByChildInfo, EntityRange,
+    EntityWindow, ParentLink, QueryExecutionError, StoreError, Value, ENV_VARS,
 };
-use graph::schema::{EntityType, FulltextAlgorithm, FulltextConfig, InputSchema};
+use graph::schema::{kw, AggregateFn, EntityType, FulltextAlgorithm, FulltextConfig, InputSchema};
 use graph::{components::store::AttributeNames, data::store::scalar};
 use inflector::Inflector;
 use itertools::Itertools;
@@ -2194,6 +2194,97 @@ impl<'a> Query for FindDerivedQuery<'a> {
 
 impl<'a, Conn> RunQueryDsl<Conn> for FindDerivedQuery<'a> {}
 
+/// A query that finds the entities of one type that match a filter,
+/// ordered by id. Used for `store.query` during indexing
+#[derive(Debug)]
+pub struct FindFilteredQuery<'a> {
+    table: dsl::Table<'a>,
+    from_table: dsl::FromTable<'a>,
+    primary_key: dsl::Column<'a>,
+    filter: Filter<'a>,
+    causality_region: CausalityRegion,
+    excluded_keys: IdList,
+    at_block: AtBlock<'a>,
+    first: i64,
+}
+
+impl<'a> FindFilteredQuery<'a> {
+    pub fn new(
+        layout: &'a Layout,
+        table: &'a Table,
+        query: &'a FilteredEntityQuery,
+        block: BlockNumber,
+        excluded_keys: IdList,
+    ) -> Result<Self, StoreError> {
+        let table = table.dsl_table();
+        let filter = Filter::main(layout, table, &query.filter, block)?;
+        Ok(Self {
+            table,
+            from_table: table.from_clause(),
+            primary_key: table.primary_key(),
+            filter,
+            causality_region: query.causality_region,
+            excluded_keys,
+            at_block: table.at_block(block),
+            first: query.first as i64,
+        })
+    }
+}
+
+impl<'a> QueryFragment<Pg> for FindFilteredQuery<'a> {
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.unsafe_to_cache_prepared();
+
+        // Generate
+        //    select '..' as entity, to_jsonb(c.*) as data
+        //      from schema.table c
+        //     where {at_block} and c.causality_region = $cr
+        //       and not c.id = any($excluded) and {filter}
+        //     order by c.id
+        //     limit $first
+        out.push_sql("select ");
+        out.push_bind_param::<Text, _>(self.table.meta.object.as_str())?;
+        out.push_sql(" as entity, to_jsonb(");
+        self.table.walk_ast(out.reborrow())?;
+        out.push_sql(".*) as data\n");
+        out.push_sql("  from ");
+        self.from_table.walk_ast(out.reborrow())?;
+        out.push_sql("\n where ");
+        self.at_block.walk_ast(out.reborrow())?;
+        if self.table.meta.has_causality_region {
+            out.push_sql(" and ");
+            self.table.walk_ast(out.reborrow())?;
+            out.push_sql(".causality_region = ");
+            out.push_bind_param::<Integer, _>(&self.causality_region)?;
+        }
+        if self.excluded_keys.len() > 0 {
+            out.push_sql(" and not ");
+            self.primary_key.walk_ast(out.reborrow())?;
+            out.push_sql(" = any(");
+            self.excluded_keys.push_bind_param(&mut out)?;
+            out.push_sql(")");
+        }
+        out.push_sql("\n   and ");
+        self.filter.walk_ast(out.reborrow())?;
+        out.push_sql("\n order by ");
+        self.primary_key.walk_ast(out.reborrow())?;
+        out.push_sql("\n limit ");
+        out.push_bind_param::<BigInt, _>(&self.first)
+    }
+}
+
+impl<'a> QueryId for FindFilteredQuery<'a> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<'a> Query for FindFilteredQuery<'a> {
+    type SqlType = Untyped;
+}
+
+impl<'a, Conn> RunQueryDsl<Conn> for FindFilteredQuery<'a> {}
+
 /// One value for inserting into a column of a table
 #[derive(Debug)]
 enum InsertValue<'a> {
@@ -4188,6 +4279,112 @@ impl QueryFragment<Pg> for FilterRange {
     }
 }
 
+/// How to combine the rows of an aggregation when a query groups them by
+/// a subset of the aggregation's dimensions. Rows are always grouped by
+/// their timestamp so that buckets stay separate
+#[derive(Debug, Clone)]
+struct GroupBy<'a> {
+    id: dsl::Column<'a>,
+    timestamp: dsl::Column<'a>,
+    dimensions: Vec<dsl::Column<'a>>,
+    /// The aggregates that can be combined across rows, together with the
+    /// function that combines them
+    aggregates: Vec<(dsl::Column<'a>, AggregateFn)>,
+}
+
+impl<'a> GroupBy<'a> {
+    fn new(
+        collection: &'a FilterCollection<'a>,
+        dimensions: &[Attribute],
+    ) -> Result<Self, QueryExecutionError> {
+        let table = match collection {
+            FilterCollection::All(entities) if entities.len() == 1 => entities[0].table,
+            _ => {
+                return Err(QueryExecutionError::NotSupported(
+                    "grouping is only supported for toplevel queries of one aggregation"
+                        .to_string(),
+                ))
+            }
+        };
+        let agg = table.meta.object.aggregation().ok_or_else(|| {
+            QueryExecutionError::NotSupported(format!(
+                "type `{}` is not an aggregation and can not be grouped",
+                table.meta.object.typename()
+            ))
+        })?;
+
+        let id = table.primary_key();
+        let timestamp = table.column_for_field(kw::TIMESTAMP)?;
+        let dimensions = dimensions
+            .iter()
+            .map(|dim| table.column_for_field(dim))
+            .collect::<Result<Vec<_>, _>>()?;
+        let aggregates = agg
+            .aggregates
+            .iter()
+            .filter_map(|agg| agg.regroup_fn().map(|func| (agg, func)))
+            .map(|(agg, func)| {
+                table
+                    .column_for_field(&agg.name)
+                    .map(|column| (column, func))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(GroupBy {
+            id,
+            timestamp,
+            dimensions,
+            aggregates,
+        })
+    }
+
+    /// Generate `c.timestamp, c.dim1, c.dim2, ..`
+    fn group_columns<'b>(&'b self, out: &mut AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        self.timestamp.walk_ast(out.reborrow())?;
+        for dim in &self.dimensions {
+            out.push_sql(", ");
+            dim.walk_ast(out.reborrow())?;
+        }
+        Ok(())
+    }
+
+    /// Generate the select list for the grouped rows. Since the `id` of an
+    /// aggregation is not meaningful for grouped rows, we just pick the
+    /// largest one of the group
+    ///
+    ///   max(c.id) as id, c.timestamp, c.dim1, ..,
+    ///   sum(c.agg1)::numeric as agg1, ..
+    fn select<'b>(&'b self, out: &mut AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.push_sql("max(");
+        self.id.walk_ast(out.reborrow())?;
+        out.push_sql(") as ");
+        out.push_identifier(self.id.name())?;
+        out.push_sql(", ");
+        self.group_columns(out)?;
+        for (column, func) in &self.aggregates {
+            out.push_sql(", ");
+            out.push_sql(&func.to_string());
+            out.push_sql("(");
+            column.walk_ast(out.reborrow())?;
+            out.push_sql(")::");
+            out.push_sql(column.column_type().sql_type());
+            out.push_sql(" as ");
+            out.push_identifier(column.name())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for GroupBy<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.timestamp)?;
+        for dim in &self.dimensions {
+            write!(f, ", {}", dim)?;
+        }
+        Ok(())
+    }
+}
+
 /// The parallel to `EntityQuery`.
 ///
 /// Details of how query generation for `FilterQuery` works can be found
@@ -4196,6 +4393,7 @@ impl QueryFragment<Pg> for FilterRange {
 pub struct FilterQuery<'a> {
     collection: &'a FilterCollection<'a>,
     limit: ParentLimit<'a>,
+    group_by: Option<GroupBy<'a>>,
     block: BlockNumber,
     query_id: Option<String>,
     site: &'a Site,
@@ -4209,6 +4407,9 @@ impl<'a> fmt::Display for FilterQuery<'a> {
             "from {} order {} {} at {}",
             &self.collection, &self.limit.sort_key, &self.limit.range, self.block
         )?;
+        if let Some(group_by) = &self.group_by {
+            write!(f, " group by {}", group_by)?;
+        }
         if let Some(query_id) = &self.query_id {
             write!(f, " query_id {}", query_id)?;
         }
@@ -4223,6 +4424,7 @@ impl<'a> FilterQuery<'a> {
         filter: Option<&'a EntityFilter>,
         order: EntityOrder,
         range: EntityRange,
+        group_by: Option<&[Attribute]>,
         block: BlockNumber,
         query_id: Option<String>,
         site: &'a Site,
@@ -4230,10 +4432,14 @@ impl<'a> FilterQuery<'a> {
         let sort_key = SortKey::new(order, collection, filter, layout, block)?;
         let range = FilterRange(range);
         let limit = ParentLimit { sort_key, range };
+        let group_by = group_by
+            .map(|dimensions| GroupBy::new(collection, dimensions))
+            .transpose()?;
 
         Ok(FilterQuery {
             collection,
             limit,
+            group_by,
             block,
             query_id,
             site,
@@ -4301,6 +4507,40 @@ impl<'a> FilterQuery<'a> {
         Ok(())
     }
 
+    /// Only one table/filter pair for an aggregation, no window, and
+    /// grouped by some of the aggregation's dimensions
+    ///
+    /// The grouping has to happen in a subquery so that sorting uses the
+    /// combined aggregates rather than the values of the underlying rows
+    ///
+    ///   select '..' as entity, to_jsonb(e.*) as data
+    ///     from
+    ///       (select c.*
+    ///          from (select {group_by.select}
+    ///                  from table c
+    ///                 where block$ <= $block
+    ///                   and filter
+    ///                 group by c.timestamp, {dimensions}) c
+    ///         order by .. limit .. skip ..) c
+    fn query_grouped_one_entity<'b>(
+        &'b self,
+        wh: &'b WholeTable<'a>,
+        group_by: &'b GroupBy<'a>,
+        out: &mut AstPass<'_, 'b, Pg>,
+    ) -> QueryResult<()> {
+        Self::select_entity_and_data(wh.table, out);
+        out.push_sql(" from (select c.* from (select ");
+        group_by.select(out)?;
+        self.filtered_rows(wh, out)?;
+        out.push_sql(" group by ");
+        group_by.group_columns(out)?;
+        out.push_sql(") c\n ");
+        self.limit.sort_key.order_by(out, false)?;
+        self.limit.range.walk_ast(out.reborrow())?;
+        out.push_sql(") c");
+        Ok(())
+    }
+
     /// Only one table/filter pair, and a window
     ///
     /// Generate a query
@@ -4522,7 +4762,10 @@ impl<'a> QueryFragment<Pg> for FilterQuery<'a> {
                     let wh = entities
                         .first()
                         .expect("a query always uses at least one table");
-                    self.query_no_window_one_entity(wh, &mut out)
+                    match &self.group_by {
+                        Some(group_by) => self.query_grouped_one_entity(wh, group_by, &mut out),
+                        None => self.query_no_window_one_entity(wh, &mut out),
+                    }
                 } else {
                     self.query_no_window(entities, &mut out)
                 }
@@ -4547,6 +4790,107 @@ impl<'a> Query for FilterQuery<'a> {
 
 impl<'a, Conn> RunQueryDsl<Conn> for FilterQuery<'a> {}
 
+/// A query that counts the entities in a collection that match a filter.
+/// Only collections that do not need windowing, i.e., the collections
+/// for toplevel query fields, can be counted
+#[derive(Debug)]
+pub struct CountQuery<'a> {
+    collection: &'a FilterCollection<'a>,
+    block: BlockNumber,
+    query_id: Option<String>,
+    site: &'a Site,
+}
+
+impl<'a> CountQuery<'a> {
+    pub fn new(
+        collection: &'a FilterCollection,
+        block: BlockNumber,
+        query_id: Option<String>,
+        site: &'a Site,
+    ) -> Result<Self, QueryExecutionError> {
+        match collection {
+            FilterCollection::All(_) => Ok(CountQuery {
+                collection,
+                block,
+                query_id,
+                site,
+            }),
+            FilterCollection::SingleWindow(_) | FilterCollection::MultiWindow(_, _) => {
+                Err(QueryExecutionError::NotSupported(
+                    "only toplevel collections can be counted".to_string(),
+                ))
+            }
+        }
+    }
+}
+
+impl<'a> QueryFragment<Pg> for CountQuery<'a> {
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.unsafe_to_cache_prepared();
+
+        let entities = match self.collection {
+            FilterCollection::All(entities) if !entities.is_empty() => entities,
+            _ => {
+                out.push_sql("select 0::int8 as count");
+                return Ok(());
+            }
+        };
+
+        if let Some(qid) = &self.query_id {
+            out.push_sql("/* controller='count',application='");
+            out.push_sql(self.site.namespace.as_str());
+            out.push_sql("',route='");
+            out.push_sql(qid);
+            out.push_sql("',action='");
+            out.push_sql(&self.block.to_string());
+            out.push_sql("' */\n");
+        }
+
+        // Generate
+        //   select count(*) as count
+        //     from (select c.vid
+        //             from table c
+        //            where block_range @> $block
+        //              and filter
+        //            union all
+        //            ...) c
+        out.push_sql("select count(*) as count\n  from (");
+        for (i, wh) in entities.iter().enumerate() {
+            if i > 0 {
+                out.push_sql("\nunion all\n");
+            }
+            out.push_sql("select c.vid\n  from ");
+            wh.from_table.walk_ast(out.reborrow())?;
+            out.push_sql("\n where ");
+            wh.at_block.walk_ast(out.reborrow())?;
+            if let Some(filter) = &wh.filter {
+                out.push_sql(" and ");
+                filter.walk_ast(out.reborrow())?;
+            }
+        }
+        out.push_sql(") c");
+        Ok(())
+    }
+}
+
+impl<'a> QueryId for CountQuery<'a> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<'a> Query for CountQuery<'a> {
+    type SqlType = Untyped;
+}
+
+impl<'a, Conn> RunQueryDsl<Conn> for CountQuery<'a> {}
+
+#[derive(QueryableByName)]
+pub struct CountData {
+    #[diesel(sql_type = BigInt)]
+    pub count: i64,
+}
+
 /// Reduce the upper bound of the current entry's block range to `block` as
 /// long as that does not result in an empty block range
 #[derive(Debug)]
@@ -21,7 +21,7 @@ use graph::{
             PruneReporter, PruneRequest, SubgraphFork,
         },
     },
-    data::query::QueryTarget,
+    data::query::{persisted_query_hash, QueryTarget},
     data::subgraph::{schema::DeploymentCreate, status, DeploymentFeatures},
     internal_error,
     prelude::{
@@ -1249,6 +1249,80 @@ impl SubgraphStoreInner {
         store.prune(reporter, site, req).await
     }
 
+    /// Recompute the aggregation `aggregation` of `deployment` from the
+    /// timeseries data it has already indexed and return the number of
+    /// buckets that were filled. The deployment should not be indexing
+    /// while this runs
+    pub fn backfill_aggregation(
+        &self,
+        deployment: &DeploymentLocator,
+        aggregation: &str,
+    ) -> Result<usize, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.backfill_aggregation(site, aggregation)
+    }
+
+    /// Register the query document `query` for the deployment and return
+    /// the hash under which it was registered
+    pub fn register_persisted_query(
+        &self,
+        deployment: &DeploymentLocator,
+        query: &str,
+    ) -> Result<String, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        let hash = persisted_query_hash(query);
+        store.register_persisted_query(&site, &hash, query)?;
+        Ok(hash)
+    }
+
+    pub fn unregister_persisted_query(
+        &self,
+        deployment: &DeploymentLocator,
+        hash: &str,
+    ) -> Result<bool, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.unregister_persisted_query(&site, hash)
+    }
+
+    pub fn persisted_queries(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<Vec<(String, String)>, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.persisted_queries(&site)
+    }
+
+    pub fn persisted_queries_only(
+        &self,
+        deployment: &DeploymentLocator,
+    ) -> Result<bool, StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.persisted_queries_only(&site)
+    }
+
+    /// Set whether only registered query documents can be run against the
+    /// deployment
+    pub fn set_persisted_queries_only(
+        &self,
+        deployment: &DeploymentLocator,
+        only: bool,
+    ) -> Result<(), StoreError> {
+        let site = self.find_site(deployment.id.into())?;
+        let store = self.for_site(&site)?;
+
+        store.set_persisted_queries_only(&site, only)
+    }
+
     pub fn set_history_blocks(
         &self,
         deployment: &DeploymentLocator,
@@ -1538,6 +1612,9 @@ impl SubgraphStoreTrait for SubgraphStore {
     ) -> Result<Arc<dyn store::SourceableStore>, StoreError> {
         let deployment = deployment.into();
         let site = self.find_site(deployment)?;
+        // Sources can be in a different shard than the subgraph that uses
+        // them; reading them through the store for their own shard makes
+        // that work
         let store = self.for_site(&site)?;
         let input_schema = self.input_schema(&site.deployment)?;
 
@@ -8,7 +8,9 @@ use std::{collections::BTreeMap, sync::Arc};
 use async_trait::async_trait;
 use graph::blockchain::block_stream::{EntitySourceOperation, FirehoseCursor};
 use graph::blockchain::BlockTime;
-use graph::components::store::{Batch, DeploymentCursorTracker, DerivedEntityQuery, ReadStore};
+use graph::components::store::{
+    Batch, DeploymentCursorTracker, DerivedEntityQuery, FilteredEntityQuery, ReadStore,
+};
 use graph::data::store::IdList;
 use graph::data::subgraph::schema;
 use graph::data_source::CausalityRegion;
@@ -242,7 +244,16 @@ impl SyncStore {
 
             let block_time = self.writable.block_time(self.site.cheap_clone())?;
             self.last_rollup.set(block_time)
-        })
+        })?;
+
+        // We don't know which entity types the revert touched
+        let entity_types = self.input_schema.entity_types();
+        self.send_entity_changes(
+            entity_types
+                .iter()
+                .map(|entity_type| entity_type.as_str())
+                .chain(self.input_schema.aggregation_names()),
+        )
     }
 
     fn unfail_deterministic_error(
@@ -303,6 +314,32 @@ impl SyncStore {
             let last_block_time = batch.block_times.last().unwrap().1;
             self.last_rollup.set(Some(last_block_time))?;
             Ok(())
+        })?;
+
+        // Any write can lead to a rollup of the aggregations
+        self.send_entity_changes(
+            batch
+                .groups()
+                .map(|group| group.entity_type.as_str())
+                .chain(self.input_schema.aggregation_names()),
+        )
+    }
+
+    /// Notify GraphQL subscriptions that `entity_types` changed if that is
+    /// enabled with `GRAPH_STORE_SEND_ENTITY_CHANGES`
+    fn send_entity_changes<'a>(
+        &self,
+        entity_types: impl Iterator<Item = &'a str>,
+    ) -> Result<(), StoreError> {
+        if !ENV_VARS.store.send_entity_changes {
+            return Ok(());
+        }
+        let event = StoreEvent::entities(&self.site.deployment, entity_types);
+        if event.is_empty() {
+            return Ok(());
+        }
+        retry::forever(&self.logger, "send_entity_changes", || {
+            self.store.send_store_event(&event)
         })
     }
 
@@ -338,6 +375,18 @@ impl SyncStore {
         })
     }
 
+    fn get_filtered(
+        &self,
+        query: &FilteredEntityQuery,
+        block: BlockNumber,
+        excluded_keys: Vec<EntityKey>,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        retry::forever(&self.logger, "get_filtered", || {
+            self.writable
+                .get_filtered(self.site.cheap_clone(), query, block, &excluded_keys)
+        })
+    }
+
     async fn is_deployment_synced(&self) -> Result<bool, StoreError> {
         retry::forever_async(&self.logger, "is_deployment_synced", || async {
             self.writable
@@ -1255,6 +1304,46 @@ impl Queue {
         Ok(items_from_database)
     }
 
+    fn get_filtered(
+        &self,
+        query: &FilteredEntityQuery,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        // Get the latest version of every entity of the queried type that
+        // is in the queue, regardless of whether it matches the query
+        let (entities_in_queue, query_block) = BlockTracker::fold(
+            &self.queue,
+            BTreeMap::new(),
+            |mut map: BTreeMap<EntityKey, Option<Entity>>, batch, at| {
+                // Since we are going newest to oldest, do not clobber
+                // already existing entries in map as that would make us
+                // produce stale values
+                for op in batch.effective_ops(&query.entity_type, at) {
+                    let (key, entity) = match op {
+                        EntityOp::Write { key, entity } => (key, Some(entity)),
+                        EntityOp::Remove { key } => (key, None),
+                    };
+                    if !map.contains_key(key) {
+                        map.insert(key.clone(), entity.cloned());
+                    }
+                }
+                map
+            },
+        );
+
+        // What the database has for entities in the queue is out of date,
+        // and we only use their version from the queue
+        let excluded_keys: Vec<EntityKey> = entities_in_queue.keys().cloned().collect();
+        let mut items_from_database = self.store.get_filtered(query, query_block, excluded_keys)?;
+
+        let items_from_queue = entities_in_queue
+            .into_iter()
+            .filter_map(|(key, entity)| entity.map(|entity| (key, entity)))
+            .filter(|(key, entity)| query.matches(key, entity));
+        items_from_database.extend(items_from_queue);
+
+        Ok(items_from_database.into_iter().take(query.first).collect())
+    }
+
     /// Load dynamic data sources by looking at both the queue and the store
     async fn load_dynamic_data_sources(
         &self,
@@ -1415,6 +1504,16 @@ impl Writer {
         }
     }
 
+    fn get_filtered(
+        &self,
+        query: &FilteredEntityQuery,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        match self {
+            Writer::Sync(store) => store.get_filtered(query, BLOCK_NUMBER_MAX, vec![]),
+            Writer::Async { queue, .. } => queue.get_filtered(query),
+        }
+    }
+
     async fn load_dynamic_data_sources(
         &self,
         manifest_idx_and_name: Vec<(u32, String)>,
@@ -1540,6 +1639,13 @@ impl ReadStore for WritableStore {
         self.writer.get_derived(key)
     }
 
+    fn get_filtered(
+        &self,
+        query: &FilteredEntityQuery,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        self.writer.get_filtered(query)
+    }
+
     fn input_schema(&self) -> InputSchema {
         self.store.input_schema()
     }
@@ -4,6 +4,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
+use graph::blockchain::block_stream::EntityOperationKind;
 use graph::blockchain::DataSource;
 use graph::components::store::BLOCK_NUMBER_MAX;
 use graph::data::store::scalar::Bytes;
@@ -17,7 +18,7 @@ use graph::data_source::offchain::OffchainDataSourceKind;
 use graph::data_source::{DataSourceEnum, DataSourceTemplate};
 use graph::entity;
 use graph::env::ENV_VARS;
-use graph::prelude::web3::types::H256;
+use graph::prelude::web3::types::{H160, H256};
 use graph::prelude::{
     anyhow, async_trait, serde_yaml, tokio, BigDecimal, BigInt, DeploymentHash, Link, Logger,
     SubgraphManifest, SubgraphManifestResolveError, SubgraphManifestValidationError, SubgraphStore,
@@ -1176,6 +1177,198 @@ specVersion: 0.0.2
     assert_eq!(true, required_capabilities.traces);
 }
 
+fn transaction_handlers_manifest(api_version: &str) -> String {
+    format!(
+        "
+dataSources:
+  - kind: ethereum/contract
+    name: Factory
+    network: mainnet
+    source:
+      address: \"0x0000000000000000000000000000000000000001\"
+      abi: Factory
+      startBlock: 9562480
+    mapping:
+      kind: ethereum/events
+      apiVersion: {api_version}
+      language: wasm/assemblyscript
+      entities:
+        - TestEntity
+      file:
+        /: /ipfs/Qmmapping
+      abis:
+        - name: Factory
+          file:
+            /: /ipfs/Qmabi
+      transactionHandlers:
+        - handler: handleTransaction
+        - handler: handleGet
+          to: \"0x0000000000000000000000000000000000000002\"
+          function: get(uint256)
+schema:
+  file:
+    /: /ipfs/Qmschema
+specVersion: 1.3.0
+"
+    )
+}
+
+#[tokio::test]
+async fn parse_transaction_handlers() {
+    let yaml = transaction_handlers_manifest("0.0.7");
+
+    let manifest = resolve_manifest(&yaml, SPEC_VERSION_1_3_0).await;
+    let onchain_data_sources = manifest
+        .data_sources
+        .iter()
+        .filter_map(|ds| ds.as_onchain().cloned())
+        .collect::<Vec<_>>();
+    let data_source = onchain_data_sources.get(0).unwrap();
+    let handlers = &data_source.mapping.transaction_handlers;
+
+    assert_eq!(2, handlers.len());
+    assert_eq!(None, handlers[0].from);
+    assert_eq!(None, handlers[0].to);
+    assert_eq!(None, handlers[0].function);
+    assert_eq!(Some(H160::from_low_u64_be(2)), handlers[1].to);
+    assert_eq!(Some("get(uint256)".to_string()), handlers[1].function);
+
+    assert!(data_source.validate(LATEST_VERSION).is_empty());
+    assert_eq!(SPEC_VERSION_1_3_0, data_source.min_spec_version());
+}
+
+#[tokio::test]
+async fn transaction_handlers_require_api_version_0_0_7() {
+    let yaml = transaction_handlers_manifest("0.0.6");
+
+    let manifest = resolve_manifest(&yaml, SPEC_VERSION_1_3_0).await;
+    let data_source = manifest
+        .data_sources
+        .iter()
+        .find_map(|ds| ds.as_onchain().cloned())
+        .unwrap();
+    let errors = data_source.validate(LATEST_VERSION);
+
+    assert_eq!(1, errors.len());
+    assert!(errors[0]
+        .to_string()
+        .contains("transaction handlers, but these are only supported for apiVersion >= 0.0.7"));
+}
+
+#[tokio::test]
+async fn template_transaction_handlers_require_filter() {
+    const YAML: &str = "
+dataSources:
+  - kind: ethereum/contract
+    name: Factory
+    network: mainnet
+    source:
+      address: \"0x0000000000000000000000000000000000000001\"
+      abi: Factory
+      startBlock: 9562480
+    mapping:
+      kind: ethereum/events
+      apiVersion: 0.0.7
+      language: wasm/assemblyscript
+      entities:
+        - TestEntity
+      file:
+        /: /ipfs/Qmmapping
+      abis:
+        - name: Factory
+          file:
+            /: /ipfs/Qmabi
+      transactionHandlers:
+        - handler: handleTransaction
+templates:
+  - kind: ethereum/contract
+    name: Pool
+    network: mainnet
+    source:
+      abi: Factory
+    mapping:
+      kind: ethereum/events
+      apiVersion: 0.0.7
+      language: wasm/assemblyscript
+      entities:
+        - TestEntity
+      file:
+        /: /ipfs/Qmmapping
+      abis:
+        - name: Factory
+          file:
+            /: /ipfs/Qmabi
+      transactionHandlers:
+        - handler: handleTransaction
+schema:
+  file:
+    /: /ipfs/Qmschema
+specVersion: 1.3.0
+";
+
+    let err = try_resolve_manifest(YAML, SPEC_VERSION_1_3_0)
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains(
+        "transaction handler handleTransaction must declare at least one of `from`, `to` or `function`"
+    ));
+
+    // A function filter is enough for template handlers
+    let yaml = YAML.replace(
+        "        - handler: handleTransaction\nschema:",
+        "        - handler: handleTransaction\n          function: get(uint256)\nschema:",
+    );
+    let manifest = resolve_manifest(&yaml, SPEC_VERSION_1_3_0).await;
+    assert_eq!(1, manifest.templates.len());
+}
+
+#[tokio::test]
+async fn parse_transfer_handlers() {
+    const YAML: &str = "
+dataSources:
+  - kind: ethereum/contract
+    name: Factory
+    network: mainnet
+    source:
+      address: \"0x0000000000000000000000000000000000000001\"
+      abi: Factory
+      startBlock: 9562480
+    mapping:
+      kind: ethereum/events
+      apiVersion: 0.0.7
+      language: wasm/assemblyscript
+      entities:
+        - TestEntity
+      file:
+        /: /ipfs/Qmmapping
+      abis:
+        - name: Factory
+          file:
+            /: /ipfs/Qmabi
+      transferHandlers:
+        - handler: handleTransfer
+schema:
+  file:
+    /: /ipfs/Qmschema
+specVersion: 1.3.0
+";
+
+    let manifest = resolve_manifest(YAML, SPEC_VERSION_1_3_0).await;
+    let data_source = manifest
+        .data_sources
+        .iter()
+        .find_map(|ds| ds.as_onchain().cloned())
+        .unwrap();
+    let handlers = &data_source.mapping.transfer_handlers;
+
+    assert_eq!(1, handlers.len());
+    assert_eq!("handleTransfer", handlers[0].handler);
+
+    assert!(data_source.validate(LATEST_VERSION).is_empty());
+    assert_eq!(SPEC_VERSION_1_3_0, data_source.min_spec_version());
+}
+
 #[test]
 fn undeclared_grafting_feature_causes_feature_validation_error() {
     const YAML: &str = "
@@ -1768,48 +1961,23 @@ specVersion: 1.3.0
         .contains("Subgraph datasources cannot be used alongside onchain datasources"));
 }
 
-#[test]
-fn nested_subgraph_ds_manifest_should_fail() {
-    let yaml = r#"
+/// A manifest with a single subgraph data source that uses `source` and
+/// handles `User` entities
+fn subgraph_ds_manifest(source: &str) -> String {
+    format!(
+        r#"
 schema:
   file:
-    /: /ipfs/Qmschema
+    /: /ipfs/QmSourceSchema
 dataSources:
 - name: SubgraphSource
   kind: subgraph
   entities:
       - User
   network: mainnet
-  source: 
-    address: 'QmNestedSource'
-    startBlock: 9562480
-  mapping:
-    apiVersion: 0.0.6
-    language: wasm/assemblyscript
-    entities:
-      - TestEntity
-    file:
-      /: /ipfs/Qmmapping
-    handlers:
-      - handler: handleEntity
-        entity: User
-specVersion: 1.3.0
-"#;
-
-    // First modify SOURCE_SUBGRAPH_MANIFEST to include a subgraph datasource
-    const NESTED_SOURCE_MANIFEST: &str = r#"
-schema:
-  file:
-    /: /ipfs/QmSourceSchema
-dataSources:
-- kind: subgraph
-  name: NestedSource
-  network: mainnet
-  entities:
-      - User
   source:
-    address: 'QmSource'
-    startBlock: 1
+    address: '{source}'
+    startBlock: 9562480
   mapping:
     apiVersion: 0.0.6
     language: wasm/assemblyscript
@@ -1818,51 +1986,79 @@ dataSources:
     file:
       /: /ipfs/Qmmapping
     handlers:
-      - handler: handleNested
+      - handler: handleEntity
         entity: User
 specVersion: 1.3.0
-"#;
+"#
+    )
+}
 
+/// Resolve a chain of subgraphs where each subgraph uses the next one as
+/// its source. The last subgraph in the chain uses `QmSource`, which only
+/// has onchain data sources
+async fn resolve_nested_manifest(
+    depth: usize,
+) -> Result<SubgraphManifest<Chain>, SubgraphManifestResolveError> {
     let mut resolver = TextResolver::default();
     let id = DeploymentHash::new("Qmmanifest").unwrap();
 
+    let mut source = "QmSource".to_string();
+    for level in 1..depth {
+        let nested = format!("QmNestedSource{level}");
+        resolver.add(&format!("/ipfs/{nested}"), &subgraph_ds_manifest(&source));
+        source = nested;
+    }
+    let yaml = subgraph_ds_manifest(&source);
+
     resolver.add(id.as_str(), &yaml);
-    resolver.add("/ipfs/Qmabi", &ABI);
-    resolver.add("/ipfs/Qmschema", &GQL_SCHEMA);
     resolver.add("/ipfs/Qmmapping", &MAPPING_WITH_IPFS_FUNC_WASM);
-    resolver.add("/ipfs/QmNestedSource", &NESTED_SOURCE_MANIFEST);
     resolver.add("/ipfs/QmSource", &SOURCE_SUBGRAPH_MANIFEST);
     resolver.add("/ipfs/QmSourceSchema", &SOURCE_SUBGRAPH_SCHEMA);
 
     let resolver: Arc<dyn LinkResolverTrait> = Arc::new(resolver);
 
-    let raw = serde_yaml::from_str(yaml).unwrap();
-    test_store::run_test_sequentially(|_| async move {
-        let result: Result<UnvalidatedSubgraphManifest<Chain>, _> =
-            UnvalidatedSubgraphManifest::resolve(
-                id,
-                raw,
-                &resolver,
-                &LOGGER,
-                SPEC_VERSION_1_3_0.clone(),
-            )
-            .await;
-
-        match result {
-            Ok(_) => panic!("Expected resolution to fail"),
-            Err(e) => {
-                assert!(matches!(e, SubgraphManifestResolveError::ResolveError(_)));
-                let error_msg = e.to_string();
-                println!("{}", error_msg);
-                assert!(error_msg
-                    .contains("Nested subgraph data sources [SubgraphSource] are not supported."));
-            }
+    let raw = serde_yaml::from_str(&yaml).unwrap();
+    SubgraphManifest::resolve_from_raw(id, raw, &resolver, &LOGGER, SPEC_VERSION_1_3_0.clone())
+        .await
+}
+
+#[tokio::test]
+async fn nested_subgraph_ds_manifest_should_succeed() {
+    let manifest = resolve_nested_manifest(2)
+        .await
+        .expect("nested subgraph data sources can be resolved");
+
+    let ds = manifest.data_sources[0].as_subgraph().unwrap();
+    assert_eq!("QmNestedSource1", ds.source.address().as_str());
+    assert_eq!(2, ds.depth);
+}
+
+#[tokio::test]
+async fn deeply_nested_subgraph_ds_manifest_should_fail() {
+    let max_depth = ENV_VARS.subgraph_composition_max_depth;
+
+    let manifest = resolve_nested_manifest(max_depth)
+        .await
+        .expect("subgraph data sources up to the maximum depth can be resolved");
+    let ds = manifest.data_sources[0].as_subgraph().unwrap();
+    assert_eq!(max_depth, ds.depth);
+
+    match resolve_nested_manifest(max_depth + 1).await {
+        Ok(_) => panic!("Expected resolution to fail"),
+        Err(e) => {
+            assert!(matches!(e, SubgraphManifestResolveError::ResolveError(_)));
+            let error_msg = format!("{:#}", anyhow::Error::from(e));
+            assert!(error_msg.contains(&format!(
+                "is nested {} levels deep, but at most {} levels of nesting are supported",
+                max_depth + 1,
+                max_depth
+            )));
         }
-    })
+    }
 }
 
 #[tokio::test]
-async fn subgraph_ds_manifest_mutable_entities_should_fail() {
+async fn subgraph_ds_manifest_mutable_entities_should_succeed() {
     let yaml = "
 schema:
   file:
@@ -1885,16 +2081,100 @@ dataSources:
         /: /ipfs/Qmmapping
       handlers:
         - handler: handleEntity
-          entity: MutableEntity # This is a mutable entity and should fail
+          entity: MutableEntity
+        - handler: handleEntityRemoved
+          entity: MutableEntity
+          operations:
+            - delete
+specVersion: 1.3.0
+";
+
+    let manifest = resolve_manifest(yaml, SPEC_VERSION_1_3_0).await;
+    let ds = manifest.data_sources[0].as_subgraph().unwrap();
+    let handlers = &ds.mapping.handlers;
+    assert_eq!(2, handlers.len());
+    assert!(handlers[0].handles(EntityOperationKind::Create));
+    assert!(handlers[0].handles(EntityOperationKind::Modify));
+    assert!(!handlers[0].handles(EntityOperationKind::Delete));
+    assert_eq!(&[EntityOperationKind::Delete], handlers[1].operations());
+}
+
+#[tokio::test]
+async fn subgraph_ds_manifest_conflicting_handlers_should_fail() {
+    let yaml = "
+schema:
+  file:
+    /: /ipfs/Qmschema
+dataSources:
+  - name: SubgraphSource
+    kind: subgraph
+    entities:
+        - Gravatar
+    network: mainnet
+    source: 
+      address: 'QmSource'
+      startBlock: 9562480
+    mapping:
+      apiVersion: 0.0.6
+      language: wasm/assemblyscript
+      entities:
+        - TestEntity
+      file:
+        /: /ipfs/Qmmapping
+      handlers:
+        - handler: handleEntity
+          entity: MutableEntity
+        - handler: handleEntityModified
+          entity: MutableEntity
+          operations:
+            - modify
 specVersion: 1.3.0
 ";
 
     let result = try_resolve_manifest(yaml, SPEC_VERSION_1_3_0).await;
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert!(err
-        .to_string()
-        .contains("Entity MutableEntity is not immutable and cannot be used as a mapping entity"));
+    assert!(format!("{:#}", err)
+        .contains("Multiple handlers defined for `modify` operations on entity MutableEntity"));
+}
+
+#[tokio::test]
+async fn subgraph_ds_manifest_deleting_immutable_entities_should_fail() {
+    let yaml = "
+schema:
+  file:
+    /: /ipfs/Qmschema
+dataSources:
+  - name: SubgraphSource
+    kind: subgraph
+    entities:
+        - Gravatar
+    network: mainnet
+    source: 
+      address: 'QmSource'
+      startBlock: 9562480
+    mapping:
+      apiVersion: 0.0.6
+      language: wasm/assemblyscript
+      entities:
+        - TestEntity
+      file:
+        /: /ipfs/Qmmapping
+      handlers:
+        - handler: handleEntity
+          entity: User # This is an immutable entity and can not be deleted
+          operations:
+            - create
+            - delete
+specVersion: 1.3.0
+";
+
+    let result = try_resolve_manifest(yaml, SPEC_VERSION_1_3_0).await;
+    assert!(result.is_err());
+    let err = result.unwrap_err();
+    assert!(format!("{:#}", err).contains(
+        "Entity User is immutable and handler handleEntity can not handle `delete` operations for it"
+    ));
 }
 
 #[tokio::test]
@@ -1,8 +1,8 @@
 use graph::blockchain::block_stream::FirehoseCursor;
 use graph::blockchain::BlockTime;
 use graph::components::store::{
-    DeploymentCursorTracker, DerivedEntityQuery, GetScope, LoadRelatedRequest, ReadStore,
-    StoredDynamicDataSource, WritableStore,
+    DeploymentCursorTracker, DerivedEntityQuery, EntityFilter, FilteredEntityQuery, GetScope,
+    LoadRelatedRequest, ReadStore, StoredDynamicDataSource, WritableStore,
 };
 use graph::data::store::Id;
 use graph::data::subgraph::schema::{DeploymentCreate, SubgraphError, SubgraphHealth};
@@ -73,6 +73,19 @@ impl ReadStore for MockStore {
         Ok(self.get_many_res.clone())
     }
 
+    fn get_filtered(
+        &self,
+        query: &FilteredEntityQuery,
+    ) -> Result<BTreeMap<EntityKey, Entity>, StoreError> {
+        Ok(self
+            .get_many_res
+            .iter()
+            .filter(|(key, entity)| query.matches(key, entity))
+            .take(query.first)
+            .map(|(key, entity)| (key.clone(), entity.clone()))
+            .collect())
+    }
+
     fn input_schema(&self) -> InputSchema {
         SCHEMA.clone()
     }
@@ -752,6 +765,58 @@ fn check_for_delete_async_related() {
         assert_eq!(result, expeted_vec);
     });
 }
+fn wallet_query(min_balance: i32, first: usize) -> FilteredEntityQuery {
+    FilteredEntityQuery {
+        entity_type: WALLET_TYPE.clone(),
+        filter: EntityFilter::And(vec![EntityFilter::GreaterOrEqual(
+            "balance".to_string(),
+            Value::Int(min_balance),
+        )]),
+        first,
+        causality_region: CausalityRegion::ONCHAIN,
+    }
+}
+
+#[test]
+fn query_wallets_by_balance() {
+    run_store_test(|mut cache, _store, _deployment, _writable| async move {
+        let account_id = ACCOUNT_TYPE.parse_id("1").unwrap();
+        let result = cache.query(&wallet_query(90, 10)).unwrap();
+        let wallet_2 = create_wallet_entity("2", &account_id, 92_i32, 2);
+        let wallet_3 = create_wallet_entity("3", &account_id, 192_i32, 3);
+        assert_eq!(result, vec![wallet_2.clone(), wallet_3]);
+
+        let result = cache.query(&wallet_query(90, 1)).unwrap();
+        assert_eq!(result, vec![wallet_2]);
+    });
+}
+
+#[test]
+fn query_sees_changes_in_cache() {
+    run_store_test(|mut cache, _store, _deployment, _writable| async move {
+        fn ids(entities: Vec<Entity>) -> Vec<String> {
+            entities.iter().map(|e| e.id().to_string()).collect()
+        }
+
+        let account_id = ACCOUNT_TYPE.parse_id("1").unwrap();
+        // Wallet 1 now matches, wallet 2 no longer does, and the new
+        // wallet 5 matches
+        for (id, balance) in [("1", 100), ("2", 50), ("5", 500)] {
+            let key = WALLET_TYPE.parse_key(id).unwrap();
+            let wallet = create_wallet_entity_no_vid(id, &account_id, balance);
+            cache.set(key, wallet, 0, None).unwrap();
+        }
+        // Wallet 3 is removed
+        cache.remove(WALLET_TYPE.parse_key("3").unwrap());
+
+        let result = cache.query(&wallet_query(90, 10)).unwrap();
+        assert_eq!(ids(result), vec!["1", "5"]);
+
+        let result = cache.query(&wallet_query(90, 1)).unwrap();
+        assert_eq!(ids(result), vec!["1"]);
+    });
+}
+
 #[test]
 fn scoped_get() {
     run_store_test(|mut cache, _store, _deployment, _writable| async move {
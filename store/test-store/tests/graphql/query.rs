@@ -1316,6 +1316,29 @@ fn can_query_with_child_filter_on_list_type_field() {
     })
 }
 
+#[test]
+fn can_count_collections() {
+    const QUERY: &str = "
+    query {
+        musicians_count
+        amateurs: musicians_count(where: { bands_: { name: \"The Amateurs\" } })
+        early: musicians_count(block: { number: 0 })
+        reviews_count
+    }";
+
+    run_query(QUERY, |result, _| {
+        let exp = object! {
+            musicians_count: 4,
+            amateurs: 2,
+            early: 2,
+            reviews_count: 6,
+        };
+
+        let data = extract_data!(result).unwrap();
+        assert_eq!(data, exp);
+    })
+}
+
 #[test]
 fn can_query_with_child_filter_on_derived_list_type_field() {
     const QUERY: &str = "
@@ -1817,6 +1840,36 @@ fn query_complexity() {
     })
 }
 
+#[test]
+fn count_complexity() {
+    // Each count costs as much as a collection query with the default
+    // `GRAPH_GRAPHQL_MAX_FIRST` of 1000
+    const QUERY: &str = "query {
+        musicians_count
+        bands_count(where: { name: \"The Musicians\" })
+    }";
+    let args = QueryArgs {
+        query: QUERY.to_owned(),
+        variables: None,
+        max_complexity: Some(2_000),
+    };
+    run_query(args, |result, _| {
+        assert!(!result.has_errors());
+    });
+
+    let args = QueryArgs {
+        query: QUERY.to_owned(),
+        variables: None,
+        max_complexity: Some(1_999),
+    };
+    run_query(args, |result, _| {
+        match result.to_result().unwrap_err()[0] {
+            QueryError::ExecutionError(QueryExecutionError::TooComplex(2_000, _)) => (),
+            _ => panic!("did not catch complexity"),
+        };
+    })
+}
+
 #[test]
 fn instant_timeout() {
     run_test_sequentially(|store| async move {
@@ -3049,3 +3102,193 @@ fn simple_aggregation() {
         assert_eq!(data, exp);
     })
 }
+
+#[test]
+fn grouped_aggregation() {
+    fn ts0() -> r::Value {
+        r::Value::Timestamp(Timestamp::since_epoch(0, 0).unwrap())
+    }
+
+    const TOTAL_QUERY: &str = "
+    query {
+        songPlays_collection(interval: hour, groupBy: []) {
+            timestamp
+            played
+        }
+    }";
+
+    const SONG_QUERY: &str = "
+    query {
+        songPlays_collection(interval: hour, groupBy: [song], orderBy: played) {
+            timestamp
+            song { id }
+            played
+        }
+    }";
+
+    const UNGROUPED_QUERY: &str = "
+    query {
+        songPlays_collection(interval: hour, groupBy: []) {
+            song { id }
+            played
+        }
+    }";
+
+    run_query(TOTAL_QUERY, |result, _| {
+        let exp = object! {
+            songPlays_collection: vec![
+                object! { timestamp: ts0(), played: 5 },
+            ]
+        };
+        let data = extract_data!(result).unwrap();
+        assert_eq!(data, exp);
+    });
+    run_query(SONG_QUERY, |result, id_type| {
+        let s = id_type.songs();
+        let exp = object! {
+            songPlays_collection: vec![
+                object! { timestamp: ts0(), song: object! { id: s[2] }, played: 1 },
+                object! { timestamp: ts0(), song: object! { id: s[1] }, played: 4 },
+            ]
+        };
+        let data = extract_data!(result).unwrap();
+        assert_eq!(data, exp);
+    });
+    run_query(UNGROUPED_QUERY, |result, _| {
+        match &result.to_result().unwrap_err()[0] {
+            QueryError::ExecutionError(QueryExecutionError::GroupByNotSupportedError(_, field)) => {
+                assert_eq!("song", field)
+            }
+            e => panic!("unexpected error {e}"),
+        }
+    });
+}
+
+#[test]
+fn restricted_deployment_only_runs_registered_queries() {
+    const REGISTERED: &str = "query { musicians(first: 1) { id } }";
+
+    run_test_sequentially(|store| async move {
+        let deployment = setup(
+            store.as_ref(),
+            "graphqlRestrictedToRegistered",
+            BTreeSet::new(),
+            IdType::String,
+        )
+        .await;
+        let subgraph_store = store.subgraph_store();
+        let hash = subgraph_store
+            .register_persisted_query(&deployment, REGISTERED)
+            .unwrap();
+        subgraph_store
+            .set_persisted_queries_only(&deployment, true)
+            .unwrap();
+
+        let runner = Arc::new(GraphQlRunner::new(
+            &LOGGER,
+            STORE.clone(),
+            LOAD_MANAGER.clone(),
+            METRICS_REGISTRY.clone(),
+        ));
+        let target = QueryTarget::Deployment(deployment.hash.clone(), Default::default());
+        let query = |text: &str, hash: Option<String>| {
+            let mut query = Query::new(q::parse_query(text).unwrap().into_static(), None, false);
+            query.hash = hash;
+            query
+        };
+
+        // The registered query runs
+        let result = first_result(
+            runner
+                .clone()
+                .run_query(query(REGISTERED, Some(hash.clone())), target.clone())
+                .await,
+        )
+        .await;
+        assert_eq!(
+            extract_data!(result),
+            Some(object!(musicians: vec![object!(id: "m1")]))
+        );
+
+        // Other queries, and queries that do not say what their hash is,
+        // are rejected
+        let other = "query { musicians { id } }";
+        let other_hash = graph::data::query::persisted_query_hash(other);
+        for query in [query(other, Some(other_hash)), query(REGISTERED, None)] {
+            let result = first_result(
+                runner
+                    .clone()
+                    .run_query(query.clone(), target.clone())
+                    .await,
+            )
+            .await;
+            assert!(matches!(
+                &result.to_result().unwrap_err()[0],
+                QueryError::ExecutionError(QueryExecutionError::UnregisteredQuery(_))
+            ));
+
+            let errors = runner
+                .clone()
+                .subscription_target(query, target.clone())
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                &errors[0],
+                QueryExecutionError::UnregisteredQuery(_)
+            ));
+        }
+
+        // Lifting the restriction allows all queries again
+        subgraph_store
+            .set_persisted_queries_only(&deployment, false)
+            .unwrap();
+        let result = first_result(
+            runner
+                .clone()
+                .run_query(query(other, None), target.clone())
+                .await,
+        )
+        .await;
+        assert!(result.to_result().is_ok());
+    })
+}
+
+#[test]
+fn subscription_target_covers_child_filters_and_sorts() {
+    async fn target(
+        deployment: &DeploymentLocator,
+        query: &str,
+    ) -> graph::components::graphql::SubscriptionTarget {
+        let runner = Arc::new(GraphQlRunner::new(
+            &LOGGER,
+            STORE.clone(),
+            LOAD_MANAGER.clone(),
+            METRICS_REGISTRY.clone(),
+        ));
+        let target = QueryTarget::Deployment(deployment.hash.clone(), Default::default());
+        let query = Query::new(q::parse_query(query).unwrap().into_static(), None, false);
+        runner.subscription_target(query, target).await.unwrap()
+    }
+
+    run_test_sequentially(|store| async move {
+        let deployment = setup_readonly(store.as_ref()).await;
+
+        let sub = target(&deployment, "query { musicians { id mainBand { id } } }").await;
+        assert!(!sub.any_change);
+        assert_eq!(
+            vec!["Band", "Musician"],
+            sub.entity_types
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+        );
+
+        for query in [
+            "query { musicians(where: { bands_: { name: \"x\" } }) { id } }",
+            "query { musicians(where: { or: [{ name: \"x\" }, { mainBand_: { name: \"x\" } }] }) { id } }",
+            "query { musicians(orderBy: mainBand__name) { id } }",
+        ] {
+            assert!(target(&deployment, query).await.any_change, "{query}");
+        }
+    })
+}
@@ -10,7 +10,9 @@ use lazy_static::lazy_static;
 use std::{marker::PhantomData, str::FromStr};
 use test_store::*;
 
-use graph::components::store::{DeploymentLocator, ReadStore, WritableStore};
+use graph::components::store::{
+    DeploymentLocator, ReadStore, StoredDynamicDataSource, WritableStore,
+};
 use graph::data::subgraph::*;
 use graph::{
     blockchain::DataSource,
@@ -1045,10 +1047,13 @@ fn mock_data_source() -> graph_chain_ethereum::DataSource {
             event_handlers: vec![],
             call_handlers: vec![],
             block_handlers: vec![],
+            transaction_handlers: vec![],
+            transfer_handlers: vec![],
             link: Link {
                 link: "link".to_owned(),
             },
             runtime: Arc::new(Vec::new()),
+            source_map: None,
         },
         context: Default::default(),
         creation_block: None,
@@ -1148,6 +1153,118 @@ fn revert_block_with_dynamic_data_source_operations() {
     })
 }
 
+/// Create a dynamic data source at block 3 and remove it at block 4 the
+/// way `dataSource.remove` does, by marking it as done
+async fn create_and_remove_data_source(
+    store: &Arc<DieselStore>,
+    writable: &Arc<dyn WritableStore>,
+    deployment: &DeploymentLocator,
+    manifest_idx_and_name: Vec<(u32, String)>,
+) {
+    let mut data_source = mock_data_source();
+    data_source.creation_block = Some(TEST_BLOCK_3_PTR.number);
+    let stored = data_source.as_stored_dynamic_data_source();
+
+    transact_entities_and_dynamic_data_sources(
+        &store.subgraph_store(),
+        deployment.clone(),
+        TEST_BLOCK_3_PTR.clone(),
+        vec![stored.clone()],
+        vec![],
+        manifest_idx_and_name,
+    )
+    .await
+    .unwrap();
+
+    let stopwatch_metrics = StopwatchMetrics::new(
+        Logger::root(slog::Discard, o!()),
+        deployment.hash.clone(),
+        "test",
+        Arc::new(MetricsRegistry::mock()),
+        "test_shard".to_string(),
+    );
+    let removed = StoredDynamicDataSource {
+        done_at: Some(TEST_BLOCK_4_PTR.number),
+        ..stored
+    };
+    writable
+        .transact_block_operations(
+            TEST_BLOCK_4_PTR.clone(),
+            BlockTime::for_test(&*TEST_BLOCK_4_PTR),
+            FirehoseCursor::None,
+            vec![],
+            &stopwatch_metrics,
+            Vec::new(),
+            Vec::new(),
+            vec![removed],
+            false,
+            false,
+        )
+        .await
+        .expect("Failed to remove data source");
+    writable.flush().await.unwrap();
+}
+
+#[test]
+fn remove_dynamic_data_source() {
+    run_test(|store, writable, deployment| async move {
+        let manifest_idx_and_name = vec![(0, "example data source".to_string())];
+
+        create_and_remove_data_source(
+            &store,
+            &writable,
+            &deployment,
+            manifest_idx_and_name.clone(),
+        )
+        .await;
+
+        // The removed data source is still loaded when the subgraph is
+        // restarted, but marked as done so that it stays inactive
+        let loaded_dds = writable
+            .load_dynamic_data_sources(manifest_idx_and_name)
+            .await
+            .unwrap();
+        assert_eq!(1, loaded_dds.len());
+        assert_eq!(Some(TEST_BLOCK_3_PTR.number), loaded_dds[0].creation_block);
+        assert_eq!(Some(TEST_BLOCK_4_PTR.number), loaded_dds[0].done_at);
+    })
+}
+
+#[test]
+fn revert_reactivates_removed_dynamic_data_source() {
+    run_test(|store, writable, deployment| async move {
+        let manifest_idx_and_name = vec![(0, "example data source".to_string())];
+
+        create_and_remove_data_source(
+            &store,
+            &writable,
+            &deployment,
+            manifest_idx_and_name.clone(),
+        )
+        .await;
+
+        // Reverting the block in which the data source was removed makes
+        // it active again
+        revert_block(&store, &deployment, &TEST_BLOCK_3_PTR).await;
+
+        let loaded_dds = writable
+            .load_dynamic_data_sources(manifest_idx_and_name.clone())
+            .await
+            .unwrap();
+        assert_eq!(1, loaded_dds.len());
+        assert_eq!(None, loaded_dds[0].done_at);
+
+        // Reverting the block in which it was created removes it entirely
+        revert_block(&store, &deployment, &TEST_BLOCK_2_PTR).await;
+
+        let loaded_dds = writable
+            .load_dynamic_data_sources(manifest_idx_and_name)
+            .await
+            .unwrap();
+        assert_eq!(0, loaded_dds.len());
+    })
+}
+
 #[test]
 fn subgraph_schema_types_have_subgraph_id_directive() {
     run_test(|store, _, deployment| async move {
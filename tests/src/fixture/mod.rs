@@ -18,6 +18,7 @@ use graph::blockchain::{
 };
 use graph::cheap_clone::CheapClone;
 use graph::components::link_resolver::{ArweaveClient, ArweaveResolver, FileSizeLimit};
+use graph::components::metrics::handler_profile::HandlerProfiler;
 use graph::components::metrics::MetricsRegistry;
 use graph::components::network_provider::ChainName;
 use graph::components::store::{BlockStore, DeploymentLocator, EthereumCallCache, SourceableStore};
@@ -46,7 +47,7 @@ use graph::schema::InputSchema;
 use graph_chain_ethereum::chain::RuntimeAdapterBuilder;
 use graph_chain_ethereum::network::EthereumNetworkAdapters;
 use graph_chain_ethereum::Chain;
-use graph_core::polling_monitor::{arweave_service, ipfs_service};
+use graph_core::polling_monitor::{arweave_service, ipfs_service, FileStreamer};
 use graph_core::{
     SubgraphAssignmentProvider as IpfsSubgraphAssignmentProvider, SubgraphInstanceManager,
     SubgraphRegistrar as IpfsSubgraphRegistrar, SubgraphTriggerProcessor,
@@ -504,9 +505,16 @@ pub async fn setup<C: Blockchain>(
             n => FileSizeLimit::MaxBytes(n as u64),
         },
     );
+    let file_streamer = FileStreamer::new(
+        ipfs_client.cheap_clone(),
+        arweave_resolver.cheap_clone(),
+        env_vars.mappings.ipfs_timeout,
+        env_vars.mappings.max_ipfs_file_bytes,
+    );
     let sg_count = Arc::new(SubgraphCountMetric::new(mock_registry.cheap_clone()));
 
     let blockchain_map = Arc::new(blockchain_map);
+    let handler_profiler = Arc::new(HandlerProfiler::default());
     let subgraph_instance_manager = SubgraphInstanceManager::new(
         &logger_factory,
         env_vars.cheap_clone(),
@@ -514,9 +522,11 @@ pub async fn setup<C: Blockchain>(
         blockchain_map.clone(),
         sg_count.cheap_clone(),
         mock_registry.clone(),
+        handler_profiler.cheap_clone(),
         link_resolver.cheap_clone(),
         ipfs_service,
         arweave_service,
+        file_streamer,
         static_filters,
     );
 
@@ -534,6 +544,7 @@ pub async fn setup<C: Blockchain>(
         blockchain_map.cheap_clone(),
         stores.network_store.cheap_clone(),
         link_resolver.cheap_clone(),
+        handler_profiler,
     ));
 
     // Create IPFS-based subgraph provider